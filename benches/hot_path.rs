@@ -0,0 +1,193 @@
+//! Benchmarks for what runs on every command: parsing the line, assembling the room view and
+//! rendering it. Run with `cargo bench --bench hot_path`.
+
+use criterion::{Criterion, criterion_group, criterion_main};
+use port4k::input::parser::parse_command;
+use port4k::models::room::{
+    BlueprintExit, BlueprintObject, BlueprintRoom, Discovery, Kv, RoomScripts, RoomSounds, RoomView, ScriptFirst,
+    UseLimits, build_room_view_impl,
+};
+use port4k::models::types::{BlueprintId, Direction, ExitId, ObjectId, RoomId};
+use port4k::renderer::room_view::render_room_view;
+use port4k::renderer::vars::get_roomview_vars;
+use port4k::renderer::{RenderVars, render_template};
+use serde_json::json;
+use std::collections::HashMap;
+use std::hint::black_box;
+
+/// Objects in the large room; well past what a builder puts in one room
+const OBJECTS: usize = 200;
+
+const DIRECTIONS: [Direction; 12] = [
+    Direction::North,
+    Direction::South,
+    Direction::East,
+    Direction::West,
+    Direction::Up,
+    Direction::Down,
+    Direction::In,
+    Direction::Out,
+    Direction::Northeast,
+    Direction::Northwest,
+    Direction::Southeast,
+    Direction::Southwest,
+];
+
+/// A room with an exit in every direction and `OBJECTS` objects, with zone and player state for
+/// half of them, as the repositories would load it
+struct LargeRoom {
+    room: BlueprintRoom,
+    exits: Vec<BlueprintExit>,
+    objects: Vec<BlueprintObject>,
+    scripts: RoomScripts,
+    room_kv: Kv,
+    zone_room_kv: Kv,
+    zone_obj_kv: HashMap<String, Kv>,
+    user_room_kv: Kv,
+    user_obj_kv: HashMap<String, Kv>,
+}
+
+impl LargeRoom {
+    fn new() -> Self {
+        let room = BlueprintRoom {
+            id: RoomId::new(),
+            bp_id: BlueprintId::new(),
+            key: "cargo_hold".into(),
+            title: "Cargo Hold".into(),
+            body: "Crates are stacked to the ceiling, strapped down with frayed webbing. ".repeat(20),
+            lockdown: false,
+            short: Some("The cargo hold.".into()),
+            hints: vec![],
+            hazards: vec![],
+            sounds: RoomSounds::default(),
+            vehicle: None,
+            script_first: ScriptFirst::default(),
+            api_version: 2,
+        };
+
+        let exits = DIRECTIONS
+            .iter()
+            .map(|dir| BlueprintExit {
+                id: ExitId::new(),
+                from_room_id: room.id,
+                from_room_key: room.key.clone(),
+                dir: dir.clone(),
+                to_room_id: RoomId::new(),
+                to_room_key: format!("beyond_{}", dir),
+                description: Some(format!("A hatch leads {}.", dir)),
+                visible_when_locked: true,
+                default_locked: false,
+                lock: None,
+            })
+            .collect();
+
+        let objects: Vec<BlueprintObject> = (0..OBJECTS)
+            .map(|i| BlueprintObject {
+                id: ObjectId::new(),
+                name: format!("crate_{}", i),
+                short: format!("a battered crate stencilled {}", i),
+                description: "A crate of station supplies.".into(),
+                examine: Some("The seals are intact.".into()),
+                on_use_lua: None,
+                pages: Vec::new(),
+                on_read_lua: None,
+                on_terminal_lua: None,
+                on_receive_lua: None,
+                board: None,
+                widget: None,
+                dialogue: None,
+                position: Some(i as i32),
+                nouns: vec!["crate".into(), format!("crate{}", i)],
+                object_kv: Kv::from(json!({ "open": false })),
+                initial_qty: Some(1),
+                default_locked: false,
+                default_revealed: false,
+                takeable: i % 4 == 0,
+                stackable: false,
+                movable: false,
+                turnable: false,
+                is_coin: false,
+                discovery: Discovery::Visible,
+                use_limits: UseLimits::default(),
+                lock: None,
+                storage: None,
+                loot: None,
+            })
+            .collect();
+
+        let overlay = |value: bool| -> HashMap<String, Kv> {
+            objects
+                .iter()
+                .step_by(2)
+                .map(|o| (o.name.clone(), Kv::from(json!({ "open": value }))))
+                .collect()
+        };
+
+        Self {
+            zone_obj_kv: overlay(true),
+            user_obj_kv: overlay(false),
+            room,
+            exits,
+            objects,
+            scripts: RoomScripts::default(),
+            room_kv: Kv::from(json!({ "lights": "dim", "alarm": false })),
+            zone_room_kv: Kv::from(json!({ "lights": "off", "exit.north.locked": true })),
+            user_room_kv: Kv::from(json!({ "exit.north.locked": false })),
+        }
+    }
+
+    fn view(&self) -> RoomView {
+        build_room_view_impl(
+            &self.room,
+            &self.exits,
+            &self.objects,
+            &self.scripts,
+            &self.room_kv,
+            &self.zone_room_kv,
+            &self.zone_obj_kv,
+            &HashMap::new(),
+            &self.user_room_kv,
+            &self.user_obj_kv,
+            &HashMap::new(),
+        )
+    }
+}
+
+fn bench_parse(c: &mut Criterion) {
+    let mut group = c.benchmark_group("parse_command");
+    for line in [
+        "look",
+        "n",
+        "take the rusty wrench from the third crate",
+        "put all coins in bag",
+        "say hello there, is anyone in the cargo hold?",
+    ] {
+        group.bench_function(line, |b| b.iter(|| parse_command(black_box(line))));
+    }
+    group.finish();
+}
+
+fn bench_room_view(c: &mut Criterion) {
+    let room = LargeRoom::new();
+    c.bench_function("build_room_view_impl/large_room", |b| b.iter(|| black_box(room.view())));
+}
+
+fn bench_render(c: &mut Criterion) {
+    let rv = LargeRoom::new().view();
+    let template = render_room_view();
+
+    c.bench_function("get_roomview_vars/large_room", |b| {
+        b.iter(|| get_roomview_vars(black_box(&rv)))
+    });
+
+    let vars = RenderVars {
+        room_view: get_roomview_vars(&rv),
+        ..RenderVars::default()
+    };
+    c.bench_function("render_template/large_room", |b| {
+        b.iter(|| render_template(black_box(&template), black_box(&vars), 80))
+    });
+}
+
+criterion_group!(benches, bench_parse, bench_room_view, bench_render);
+criterion_main!(benches);
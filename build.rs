@@ -0,0 +1,4 @@
+// The migrations are embedded into the binary; rebuild when one is added or changed
+fn main() {
+    println!("cargo:rerun-if-changed=migrations");
+}
@@ -0,0 +1,13 @@
+-- =====================================================================
+--  ITEM DURABILITY
+--  Catalog items with a max_durability wear down by wear_per_use each
+--  time they are used and break at zero. The remaining durability of an
+--  instance is kept in item_instances.condition as { "durability": N };
+--  a missing value means the item is in mint condition. repair_tool is
+--  the item key needed in the inventory to repair it.
+-- =====================================================================
+
+ALTER TABLE public.bp_items_catalog
+    ADD COLUMN max_durability integer,
+    ADD COLUMN wear_per_use   integer     DEFAULT 1 NOT NULL,
+    ADD COLUMN repair_tool    varchar(64);
@@ -0,0 +1,13 @@
+-- =====================================================================
+--  READABLES
+--  Books, logs and datapads: paginated text on catalog items and room
+--  objects, read page by page with the `read` command. Objects can
+--  generate their pages with an on_read Lua script instead.
+-- =====================================================================
+
+ALTER TABLE public.bp_items_catalog
+    ADD COLUMN pages text[] DEFAULT '{}' NOT NULL;
+
+ALTER TABLE public.bp_objects
+    ADD COLUMN pages    text[] DEFAULT '{}' NOT NULL,
+    ADD COLUMN read_lua text;
@@ -0,0 +1,9 @@
+-- =====================================================================
+--  ROOM SOUNDS
+--  Sound cue identifiers for room events, sent to web clients which can
+--  play a matching audio file. Telnet clients never see them.
+--  sounds is { "enter": "door_hiss", "unlock": "clank", "hint": "chime" }
+-- =====================================================================
+
+ALTER TABLE public.bp_rooms
+    ADD COLUMN sounds jsonb DEFAULT '{}'::jsonb NOT NULL;
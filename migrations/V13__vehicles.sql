@@ -0,0 +1,13 @@
+-- =====================================================================
+--  VEHICLES
+--  Rooms that travel between stops (elevators, shuttles). The vehicle
+--  block lists the stops; objects in the stop rooms name the vehicle room
+--  players step into with `board`. Where a vehicle is lives in the shared
+--  room state of the vehicle room (key __vehicle).
+-- =====================================================================
+
+ALTER TABLE public.bp_rooms
+    ADD COLUMN vehicle jsonb;
+
+ALTER TABLE public.bp_objects
+    ADD COLUMN board varchar(64);
@@ -0,0 +1,9 @@
+-- =====================================================================
+--  OBJECT WIDGETS
+--  Common devices (keypads, lever banks, terminal menus) configured in
+--  the blueprint instead of scripted in Lua. The widget block is handled
+--  by the server; its per-player state lives in the object state.
+-- =====================================================================
+
+ALTER TABLE public.bp_objects
+    ADD COLUMN widget jsonb;
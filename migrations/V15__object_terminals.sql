@@ -0,0 +1,9 @@
+-- =====================================================================
+--  OBJECT TERMINALS
+--  Objects with an on_terminal script can be `use`d as an in-game
+--  computer: the session hands every line the player types to the
+--  script until it lets go.
+-- =====================================================================
+
+ALTER TABLE public.bp_objects
+    ADD COLUMN terminal_lua text;
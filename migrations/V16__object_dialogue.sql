@@ -0,0 +1,9 @@
+-- =====================================================================
+--  OBJECT DIALOGUE
+--  NPCs are objects with a dialogue tree (nodes with numbered options,
+--  conditions and effects). Where a player is in a conversation lives
+--  in the session; what the conversation changed is object state.
+-- =====================================================================
+
+ALTER TABLE public.bp_objects
+    ADD COLUMN dialogue jsonb;
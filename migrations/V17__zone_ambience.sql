@@ -0,0 +1,35 @@
+-- =====================================================================
+--  ZONE AMBIENCE
+--  Blueprint-wide ambient events (day/night, storms): messages sent to
+--  occupied rooms and state changes, on an interval or by chance. The
+--  state they set is kept per realm in realm_kv, next to the step each
+--  event is at (key __ambience.<event>).
+-- =====================================================================
+
+CREATE TABLE public.bp_ambience (
+    id         uuid        DEFAULT gen_random_uuid() NOT NULL PRIMARY KEY,
+    bp_id      uuid                                  NOT NULL
+        REFERENCES public.blueprints
+            ON DELETE CASCADE,
+    event_key  varchar(64)                           NOT NULL,
+    event      jsonb                                 NOT NULL,
+    CONSTRAINT uq_bp_ambience_bp_event
+        UNIQUE (bp_id, event_key)
+);
+
+ALTER TABLE public.bp_ambience
+    OWNER TO port4k;
+
+CREATE TABLE public.realm_kv (
+    realm_id   uuid                                  NOT NULL
+        REFERENCES public.realms
+            ON DELETE CASCADE,
+    key        text                                  NOT NULL,
+    value      jsonb                                 NOT NULL,
+    version    bigint      DEFAULT 0                 NOT NULL,
+    updated_at timestamp with time zone DEFAULT now() NOT NULL,
+    PRIMARY KEY (realm_id, key)
+);
+
+ALTER TABLE public.realm_kv
+    OWNER TO port4k;
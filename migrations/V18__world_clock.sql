@@ -0,0 +1,15 @@
+-- =====================================================================
+--  WORLD CLOCK
+--  The in-world time, saved regularly by the server so the clock carries
+--  on where it was after a restart. A single row.
+-- =====================================================================
+
+CREATE TABLE public.world_clock (
+    id         smallint                 DEFAULT 1     NOT NULL PRIMARY KEY
+        CHECK (id = 1),
+    world_time timestamp with time zone               NOT NULL,
+    saved_at   timestamp with time zone DEFAULT now() NOT NULL
+);
+
+ALTER TABLE public.world_clock
+    OWNER TO port4k;
@@ -0,0 +1,25 @@
+-- =====================================================================
+--  SCHEDULED EVENTS
+--  Cron-like server events managed with `@event`: an announcement to
+--  everyone online, or a Lua chunk run in a realm. last_run_at makes sure
+--  an event runs once per matching minute.
+-- =====================================================================
+
+CREATE TABLE public.scheduled_events (
+    id          bigserial                                 NOT NULL PRIMARY KEY,
+    schedule    text                                      NOT NULL,
+    action      text                                      NOT NULL
+        CHECK (action IN ('announce', 'lua')),
+    payload     text                                      NOT NULL,
+    realm_id    uuid
+        REFERENCES public.realms
+            ON DELETE CASCADE,
+    created_by  uuid                                      NOT NULL
+        REFERENCES public.accounts
+            ON DELETE CASCADE,
+    created_at  timestamp with time zone DEFAULT now()    NOT NULL,
+    last_run_at timestamp with time zone
+);
+
+ALTER TABLE public.scheduled_events
+    OWNER TO port4k;
@@ -0,0 +1,12 @@
+-- =====================================================================
+--  ACCOUNT DELETION
+--  `delete account` schedules the account to be purged after a cooldown;
+--  delete_after is cleared again when the player cancels.
+-- =====================================================================
+
+ALTER TABLE public.accounts
+    ADD COLUMN delete_after timestamp with time zone;
+
+CREATE INDEX accounts_delete_after_idx
+    ON public.accounts (delete_after)
+    WHERE (delete_after IS NOT NULL);
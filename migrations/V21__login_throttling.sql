@@ -0,0 +1,27 @@
+-- =====================================================================
+--  LOGIN THROTTLING
+--  Every failed login is kept for a while, by account (when the name
+--  exists) and by IP address. Repeated failures slow down the answers,
+--  and lock the account until locked_until.
+-- =====================================================================
+
+CREATE TABLE public.login_failures (
+    id           bigint GENERATED ALWAYS AS IDENTITY PRIMARY KEY,
+    account_id   uuid
+        REFERENCES public.accounts
+            ON DELETE CASCADE,
+    ip           inet,
+    attempted_at timestamp with time zone DEFAULT now() NOT NULL
+);
+
+ALTER TABLE public.login_failures
+    OWNER TO port4k;
+
+CREATE INDEX login_failures_account_idx
+    ON public.login_failures (account_id, attempted_at);
+
+CREATE INDEX login_failures_ip_idx
+    ON public.login_failures (ip, attempted_at);
+
+ALTER TABLE public.accounts
+    ADD COLUMN locked_until timestamp with time zone;
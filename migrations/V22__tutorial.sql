@@ -0,0 +1,12 @@
+-- =====================================================================
+--  TUTORIAL
+--  New accounts play a private instance of the starter blueprint until
+--  a script marks the tutorial as completed. Accounts that existed
+--  before count as completed.
+-- =====================================================================
+
+ALTER TABLE public.accounts
+    ADD COLUMN tutorial_completed_at timestamp with time zone;
+
+UPDATE public.accounts
+SET tutorial_completed_at = created_at;
@@ -0,0 +1,30 @@
+-- =====================================================================
+--  REALM DIRECTORY
+--  Blueprints describe themselves for the `realms` listing with a
+--  difficulty and tags. realm_completions keeps when a player first
+--  entered a realm and when a script marked it as completed, for the
+--  average completion time.
+-- =====================================================================
+
+ALTER TABLE public.blueprints
+    ADD COLUMN difficulty text
+        CHECK (difficulty IN ('easy', 'normal', 'hard', 'expert')),
+    ADD COLUMN tags       text[] DEFAULT '{}'::text[] NOT NULL;
+
+CREATE INDEX blueprints_tags_idx
+    ON public.blueprints USING gin (tags);
+
+CREATE TABLE public.realm_completions (
+    realm_id     uuid                                   NOT NULL
+        REFERENCES public.realms
+            ON DELETE CASCADE,
+    account_id   uuid                                   NOT NULL
+        REFERENCES public.accounts
+            ON DELETE CASCADE,
+    started_at   timestamp with time zone DEFAULT now() NOT NULL,
+    completed_at timestamp with time zone,
+    PRIMARY KEY (realm_id, account_id)
+);
+
+ALTER TABLE public.realm_completions
+    OWNER TO port4k;
@@ -0,0 +1,42 @@
+-- =====================================================================
+--  REALM REVIEWS
+--  Players that completed a realm rate it with 1 to 5 stars and an
+--  optional short review, and tag it with their own words. One rating
+--  per player and realm; rating again replaces it.
+-- =====================================================================
+
+CREATE TABLE public.realm_reviews (
+    realm_id   uuid                                   NOT NULL
+        REFERENCES public.realms
+            ON DELETE CASCADE,
+    account_id uuid                                   NOT NULL
+        REFERENCES public.accounts
+            ON DELETE CASCADE,
+    stars      smallint                               NOT NULL
+        CHECK (stars BETWEEN 1 AND 5),
+    review     text
+        CHECK (char_length(review) <= 280),
+    created_at timestamp with time zone DEFAULT now() NOT NULL,
+    updated_at timestamp with time zone DEFAULT now() NOT NULL,
+    PRIMARY KEY (realm_id, account_id)
+);
+
+ALTER TABLE public.realm_reviews
+    OWNER TO port4k;
+
+CREATE TABLE public.realm_player_tags (
+    realm_id   uuid NOT NULL
+        REFERENCES public.realms
+            ON DELETE CASCADE,
+    account_id uuid NOT NULL
+        REFERENCES public.accounts
+            ON DELETE CASCADE,
+    tag        text NOT NULL,
+    PRIMARY KEY (realm_id, account_id, tag)
+);
+
+ALTER TABLE public.realm_player_tags
+    OWNER TO port4k;
+
+CREATE INDEX realm_player_tags_tag_idx
+    ON public.realm_player_tags (tag);
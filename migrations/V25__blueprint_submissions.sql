@@ -0,0 +1,44 @@
+-- =====================================================================
+--  BLUEPRINT SUBMISSIONS
+--  `@bp submit` puts a draft (or rejected) blueprint in the moderation
+--  queue as 'pending'; moderators approve (publish) or reject it with
+--  a comment through `@submissions`. The author is told about the
+--  decision, at the next login when offline (delivered_at).
+-- =====================================================================
+
+ALTER TABLE public.blueprints
+    ADD CONSTRAINT blueprints_status_check
+        CHECK (status IN ('draft', 'pending', 'published', 'rejected', 'archived', 'live'));
+
+CREATE TABLE public.blueprint_submissions (
+    id           bigint GENERATED ALWAYS AS IDENTITY PRIMARY KEY,
+    bp_id        uuid                                   NOT NULL
+        REFERENCES public.blueprints
+            ON DELETE CASCADE,
+    version      integer                                NOT NULL,
+    submitted_by uuid
+        REFERENCES public.accounts
+            ON DELETE SET NULL,
+    submitted_at timestamp with time zone DEFAULT now() NOT NULL,
+    status       text DEFAULT 'pending'                 NOT NULL
+        CHECK (status IN ('pending', 'approved', 'rejected')),
+    decided_by   uuid
+        REFERENCES public.accounts
+            ON DELETE SET NULL,
+    decided_at   timestamp with time zone,
+    comment      text,
+    delivered_at timestamp with time zone
+);
+
+ALTER TABLE public.blueprint_submissions
+    OWNER TO port4k;
+
+CREATE UNIQUE INDEX blueprint_submissions_pending_idx
+    ON public.blueprint_submissions (bp_id)
+    WHERE (status = 'pending');
+
+-- Blueprints that were submitted before there was a queue
+INSERT INTO public.blueprint_submissions (bp_id, version, submitted_by)
+SELECT id, version, owner_id
+FROM public.blueprints
+WHERE status = 'pending';
@@ -0,0 +1,31 @@
+-- =====================================================================
+--  BLUEPRINT COLLABORATORS
+--  Besides its owner a blueprint can have editors and testers. Each
+--  collaborator has a set of rights (rooms, scripts, publish,
+--  playtest) that starts out from the defaults of their role and can
+--  be adjusted per person by the owner.
+-- =====================================================================
+
+CREATE TABLE public.blueprint_collaborators (
+    bp_id      uuid                                   NOT NULL
+        REFERENCES public.blueprints
+            ON DELETE CASCADE,
+    account_id uuid                                   NOT NULL
+        REFERENCES public.accounts
+            ON DELETE CASCADE,
+    role       text                                   NOT NULL
+        CHECK (role IN ('editor', 'tester')),
+    rights     text[] DEFAULT '{}'::text[]            NOT NULL
+        CHECK (rights <@ ARRAY ['rooms', 'scripts', 'publish', 'playtest']::text[]),
+    added_by   uuid
+        REFERENCES public.accounts
+            ON DELETE SET NULL,
+    created_at timestamp with time zone DEFAULT now() NOT NULL,
+    PRIMARY KEY (bp_id, account_id)
+);
+
+ALTER TABLE public.blueprint_collaborators
+    OWNER TO port4k;
+
+CREATE INDEX blueprint_collaborators_account_idx
+    ON public.blueprint_collaborators (account_id);
@@ -0,0 +1,16 @@
+-- =====================================================================
+--  BLUEPRINT FORKS
+--  `@bp fork` deep-copies a blueprint into a new draft owned by the
+--  caller. The copy remembers which blueprint and version it started
+--  from; the link is cleared when the source is deleted.
+-- =====================================================================
+
+ALTER TABLE public.blueprints
+    ADD COLUMN forked_from    uuid
+        REFERENCES public.blueprints
+            ON DELETE SET NULL,
+    ADD COLUMN forked_version integer;
+
+CREATE INDEX blueprints_forked_from_idx
+    ON public.blueprints (forked_from)
+    WHERE (forked_from IS NOT NULL);
@@ -0,0 +1,10 @@
+-- =====================================================================
+--  LUA API VERSION
+--  Each room records the version of the Lua API its scripts were
+--  written for. Rooms on an older version get the removed names back
+--  through a compat shim, so existing realms keep working when the
+--  API moves on. Rooms from before this column are on version 1.
+-- =====================================================================
+
+ALTER TABLE public.bp_rooms
+    ADD COLUMN api_version smallint NOT NULL DEFAULT 1;
@@ -0,0 +1,19 @@
+-- =====================================================================
+--  BLUEPRINT LUA MODULES
+--  The `lib/*.lua` files of an imported blueprint. Scripts of that
+--  blueprint load them with `require("<name>")`; other blueprints
+--  cannot see them. An import replaces all modules of the blueprint.
+-- =====================================================================
+
+CREATE TABLE public.bp_lua_modules (
+    bp_id      uuid                                   NOT NULL
+        REFERENCES public.blueprints
+            ON DELETE CASCADE,
+    name       text                                   NOT NULL,
+    source     text                                   NOT NULL,
+    updated_at timestamp with time zone DEFAULT now() NOT NULL,
+    PRIMARY KEY (bp_id, name)
+);
+
+ALTER TABLE public.bp_lua_modules
+    OWNER TO port4k;
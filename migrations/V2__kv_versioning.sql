@@ -0,0 +1,12 @@
+-- =====================================================================
+--  SHARED KV VERSIONING
+--  Every write to a shared (realm level) KV row bumps its version, so
+--  read-modify-write updates can compare-and-swap against the version
+--  they have read.
+-- =====================================================================
+
+ALTER TABLE public.realm_room_kv
+    ADD COLUMN version bigint NOT NULL DEFAULT 0;
+
+ALTER TABLE public.realm_object_kv
+    ADD COLUMN version bigint NOT NULL DEFAULT 0;
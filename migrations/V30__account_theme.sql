@@ -0,0 +1,8 @@
+-- =====================================================================
+--  ACCOUNT THEME
+--  Color theme the player picked with the `theme` command: default,
+--  contrast, mono or none. Unknown names render as the default theme.
+-- =====================================================================
+
+ALTER TABLE public.accounts
+    ADD COLUMN theme text NOT NULL DEFAULT 'default';
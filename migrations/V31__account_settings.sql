@@ -0,0 +1,17 @@
+-- =====================================================================
+--  ACCOUNT SETTINGS
+--  Per-account player settings (theme, prompt, page size, brief room
+--  descriptions) as one JSON document, changed with `settings`. Missing
+--  keys take their default, so new settings need no migration. The
+--  theme column from V30 moves into the document.
+-- =====================================================================
+
+ALTER TABLE public.accounts
+    ADD COLUMN settings jsonb NOT NULL DEFAULT '{}'::jsonb;
+
+UPDATE public.accounts
+    SET settings = jsonb_build_object('theme', theme)
+    WHERE theme <> 'default';
+
+ALTER TABLE public.accounts
+    DROP COLUMN theme;
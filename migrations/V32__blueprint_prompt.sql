@@ -0,0 +1,9 @@
+-- =====================================================================
+--  BLUEPRINT PROMPT
+--  A blueprint can style the prompt of its realms, with the same %codes
+--  players use in their own prompt. Players that picked a prompt of
+--  their own keep theirs. NULL is the server's default prompt.
+-- =====================================================================
+
+ALTER TABLE public.blueprints
+    ADD COLUMN prompt text;
@@ -0,0 +1,11 @@
+-- =====================================================================
+--  OBJECT USE LIMITS
+--  Objects can declare a cooldown in seconds and a maximum number of
+--  uses, checked before their on_use script runs. The uses are counted
+--  per player or shared by the realm:
+--  { "cooldown": 30, "max_uses": 3, "scope": "player" | "shared" }
+--  The count itself lives in the object KV under "__usage".
+-- =====================================================================
+
+ALTER TABLE public.bp_objects
+    ADD COLUMN use_limits jsonb DEFAULT '{}'::jsonb NOT NULL;
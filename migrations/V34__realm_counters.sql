@@ -0,0 +1,35 @@
+-- =====================================================================
+--  REALM COUNTERS
+--  Named integer counters for scripts (port4k.counter_incr), kept per
+--  realm or per player in a realm. Increments are a single upsert, so
+--  players changing the same counter at once never lose a count.
+-- =====================================================================
+
+CREATE TABLE public.realm_counters (
+    realm_id   uuid                                   NOT NULL
+        REFERENCES public.realms
+            ON DELETE CASCADE,
+    key        text                                   NOT NULL,
+    value      bigint                   DEFAULT 0     NOT NULL,
+    updated_at timestamp with time zone DEFAULT now() NOT NULL,
+    PRIMARY KEY (realm_id, key)
+);
+
+ALTER TABLE public.realm_counters
+    OWNER TO port4k;
+
+CREATE TABLE public.user_counters (
+    realm_id   uuid                                   NOT NULL
+        REFERENCES public.realms
+            ON DELETE CASCADE,
+    account_id uuid                                   NOT NULL
+        REFERENCES public.accounts
+            ON DELETE CASCADE,
+    key        text                                   NOT NULL,
+    value      bigint                   DEFAULT 0     NOT NULL,
+    updated_at timestamp with time zone DEFAULT now() NOT NULL,
+    PRIMARY KEY (realm_id, account_id, key)
+);
+
+ALTER TABLE public.user_counters
+    OWNER TO port4k;
@@ -0,0 +1,8 @@
+-- =====================================================================
+--  ITEM WEIGHT
+--  Catalog items can state their weight in kilograms, shown when the
+--  item is examined. Items without a weight show none.
+-- =====================================================================
+
+ALTER TABLE public.bp_items_catalog
+    ADD COLUMN weight real CHECK (weight > 0);
@@ -0,0 +1,8 @@
+-- =====================================================================
+--  OBJECT ON_RECEIVE
+--  Objects (usually NPCs) with an on_receive script decide whether
+--  they accept the items players `give` them.
+-- =====================================================================
+
+ALTER TABLE public.bp_objects
+    ADD COLUMN receive_lua text;
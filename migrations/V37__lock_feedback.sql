@@ -0,0 +1,12 @@
+-- =====================================================================
+--  LOCK FEEDBACK
+--  Locked exits and objects can say what opens them (a key, a keycard,
+--  a keypad), so players bumping into them get a hint instead of a
+--  flat "It is locked.".
+-- =====================================================================
+
+ALTER TABLE public.bp_exits
+    ADD COLUMN lock_info jsonb;
+
+ALTER TABLE public.bp_objects
+    ADD COLUMN lock_info jsonb;
@@ -0,0 +1,24 @@
+-- =====================================================================
+--  CURRENCY
+--  Money is a balance per player in a realm. Catalog items flagged as
+--  coins are not carried: picking them up adds them to the balance.
+-- =====================================================================
+
+ALTER TABLE public.bp_items_catalog
+    ADD COLUMN is_coin boolean DEFAULT false NOT NULL;
+
+CREATE TABLE public.realm_balances (
+    realm_id   uuid                                   NOT NULL
+        REFERENCES public.realms
+            ON DELETE CASCADE,
+    account_id uuid                                   NOT NULL
+        REFERENCES public.accounts
+            ON DELETE CASCADE,
+    amount     bigint                   DEFAULT 0     NOT NULL
+        CHECK (amount >= 0),
+    updated_at timestamp with time zone DEFAULT now() NOT NULL,
+    PRIMARY KEY (realm_id, account_id)
+);
+
+ALTER TABLE public.realm_balances
+    OWNER TO port4k;
@@ -0,0 +1,38 @@
+-- =====================================================================
+--  OBJECT STORAGE
+--  Storage objects (lockers, bank boxes) keep a stash per player:
+--  { "capacity": 10, "persistent": true }
+--  Capacity counts slots; a stack takes one slot. A persistent stash
+--  belongs to the blueprint object and survives the realm being reset
+--  (realm_id is NULL); any other stash goes with its realm.
+-- =====================================================================
+
+ALTER TABLE public.bp_objects
+    ADD COLUMN storage jsonb;
+
+CREATE TABLE public.object_stashes (
+    id         uuid                     DEFAULT gen_random_uuid() NOT NULL PRIMARY KEY,
+    object_id  uuid                                               NOT NULL
+        REFERENCES public.bp_objects
+            ON DELETE CASCADE,
+    account_id uuid                                               NOT NULL
+        REFERENCES public.accounts
+            ON DELETE CASCADE,
+    realm_id   uuid
+        REFERENCES public.realms
+            ON DELETE CASCADE,
+    catalog_id uuid                                               NOT NULL
+        REFERENCES public.bp_items_catalog
+            ON DELETE CASCADE,
+    item_key   varchar(100)                                       NOT NULL,
+    quantity   integer                                            NOT NULL
+        CHECK (quantity > 0),
+    condition  jsonb                    DEFAULT '{}'::jsonb       NOT NULL,
+    stored_at  timestamp with time zone DEFAULT now()             NOT NULL
+);
+
+ALTER TABLE public.object_stashes
+    OWNER TO port4k;
+
+CREATE INDEX idx_object_stashes_owner
+    ON public.object_stashes (object_id, account_id);
@@ -0,0 +1,21 @@
+-- =====================================================================
+--  BLUEPRINT VERSIONS
+--  Each import bumps blueprints.version and records what happened to
+--  live realm state (renames, orphaned overlays) in bp_versions.
+-- =====================================================================
+
+ALTER TABLE public.blueprints
+    ADD COLUMN version integer NOT NULL DEFAULT 0;
+
+CREATE TABLE public.bp_versions (
+    bp_id       uuid                     NOT NULL
+        REFERENCES public.blueprints
+            ON DELETE CASCADE,
+    version     integer                  NOT NULL,
+    imported_at timestamp with time zone DEFAULT now() NOT NULL,
+    report      jsonb                    DEFAULT '{}'::jsonb NOT NULL,
+    PRIMARY KEY (bp_id, version)
+);
+
+ALTER TABLE public.bp_versions
+    OWNER TO port4k;
@@ -0,0 +1,23 @@
+-- =====================================================================
+--  DROPPED ITEMS
+--  A blueprint decides what happens to items players leave in rooms:
+--  they persist, despawn after some minutes, or return after some
+--  minutes to where they were spawned (the room or container they were
+--  found in). dropped_at is set whenever an item is put down in a room;
+--  home_room_id / home_object_id keep where it was spawned.
+-- =====================================================================
+
+ALTER TABLE public.blueprints
+    ADD COLUMN drop_policy        text DEFAULT 'persist'::text NOT NULL
+        CHECK (drop_policy IN ('persist', 'despawn', 'return')),
+    ADD COLUMN drop_after_minutes integer
+        CHECK (drop_after_minutes > 0);
+
+ALTER TABLE public.item_instances
+    ADD COLUMN dropped_at     timestamp with time zone,
+    ADD COLUMN home_room_id   uuid,
+    ADD COLUMN home_object_id uuid;
+
+CREATE INDEX idx_item_instances_dropped
+    ON public.item_instances (dropped_at)
+    WHERE (dropped_at IS NOT NULL);
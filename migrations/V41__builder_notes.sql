@@ -0,0 +1,12 @@
+-- =====================================================================
+--  BUILDER NOTES
+--  Rooms and objects can carry notes and TODOs for their builders.
+--  They are never rendered for players; they show while playtesting,
+--  in @bp lint and in the @notes listing.
+-- =====================================================================
+
+ALTER TABLE public.bp_rooms
+    ADD COLUMN notes text[] DEFAULT '{}'::text[] NOT NULL;
+
+ALTER TABLE public.bp_objects
+    ADD COLUMN notes text[] DEFAULT '{}'::text[] NOT NULL;
@@ -0,0 +1,9 @@
+-- =====================================================================
+--  BLUEPRINT LEXICON
+--  Words of a blueprint that are spelled right but are in no dictionary
+--  (names, jargon), from the lexicon.txt of its directory. Used by the
+--  spelling check of @bp lint <bp> prose.
+-- =====================================================================
+
+ALTER TABLE public.blueprints
+    ADD COLUMN lexicon text[] DEFAULT '{}'::text[] NOT NULL;
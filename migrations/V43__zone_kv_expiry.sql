@@ -0,0 +1,13 @@
+-- =====================================================================
+--  EXPIRING ZONE STATE
+--  Zone state set with port4k.zone_set(key, value, ttl) lasts for a
+--  while only (alarms, buffs). Expired entries read as unset and are
+--  deleted by a background sweep. Entries without expires_at stay.
+-- =====================================================================
+
+ALTER TABLE public.realm_kv
+    ADD COLUMN expires_at timestamp with time zone;
+
+CREATE INDEX idx_realm_kv_expires_at
+    ON public.realm_kv (expires_at)
+    WHERE expires_at IS NOT NULL;
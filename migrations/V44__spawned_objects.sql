@@ -0,0 +1,27 @@
+-- =====================================================================
+--  SPAWNED OBJECTS
+--  Objects scripts put into a room at runtime with port4k.spawn_object
+--  (debris, a dropped rope ladder). They belong to the realm, are seen
+--  by everyone in it and stay until a script removes them or the realm
+--  is reset. Keys are unique per room, next to the blueprint objects.
+-- =====================================================================
+
+CREATE TABLE public.realm_spawned_objects (
+    id          uuid        DEFAULT gen_random_uuid() NOT NULL PRIMARY KEY,
+    realm_id    uuid                                  NOT NULL
+        REFERENCES public.realms
+            ON DELETE CASCADE,
+    room_id     uuid                                  NOT NULL
+        REFERENCES public.bp_rooms
+            ON DELETE CASCADE,
+    key         varchar(64)                           NOT NULL,
+    short       text                                  NOT NULL,
+    description text                                  NOT NULL,
+    nouns       text[]      DEFAULT '{}'::text[]      NOT NULL,
+    created_at  timestamp with time zone DEFAULT now() NOT NULL,
+    CONSTRAINT uq_realm_spawned_objects_room_key
+        UNIQUE (realm_id, room_id, key)
+);
+
+ALTER TABLE public.realm_spawned_objects
+    OWNER TO port4k;
@@ -0,0 +1,12 @@
+-- =====================================================================
+--  SCRIPT FIRST VERBS
+--  Built-in verbs (take, open, ...) a room offers to its on_command
+--  script before the engine handles them. The script returns "pass" to
+--  let the engine go ahead after all.
+--  script_first maps a verb to the object keys it applies to, e.g.
+--  { "take": ["idol"], "open": [] }; an empty list means any command
+--  with that verb in the room.
+-- =====================================================================
+
+ALTER TABLE public.bp_rooms
+    ADD COLUMN script_first jsonb DEFAULT '{}'::jsonb NOT NULL;
@@ -0,0 +1,13 @@
+-- =====================================================================
+--  SHARED LOOT STATE
+--  Shared loot is instantiated once for the whole realm and recorded
+--  without an account, which the primary key did not allow. The key
+--  becomes a unique constraint that treats a missing account as one
+--  value, so ON CONFLICT still finds the shared row.
+-- =====================================================================
+
+ALTER TABLE public.loot_instantiation_state
+    DROP CONSTRAINT loot_instantiation_state_pkey,
+    ALTER COLUMN account_id DROP NOT NULL,
+    ADD CONSTRAINT loot_instantiation_state_uniq
+        UNIQUE NULLS NOT DISTINCT (realm_id, object_id, account_id);
@@ -0,0 +1,13 @@
+-- =====================================================================
+--  ACCOUNT ROLE DEFAULT
+--  Accounts inserted without a role got 'player', which is not one of
+--  the roles the server knows (admin, moderator, builder, user) and so
+--  could not be loaded. They are plain users.
+-- =====================================================================
+
+UPDATE public.accounts
+    SET role = 'user'
+    WHERE role = 'player';
+
+ALTER TABLE public.accounts
+    ALTER COLUMN role SET DEFAULT 'user'::text;
@@ -0,0 +1,32 @@
+-- =====================================================================
+--  FEATURE FLAGS
+--  Switch experimental subsystems on/off per realm or per blueprint.
+--  A realm flag wins over a blueprint flag; without either, the global
+--  [features] from the server config apply.
+-- =====================================================================
+
+CREATE TABLE public.feature_flags (
+    id         uuid                     DEFAULT gen_random_uuid() NOT NULL PRIMARY KEY,
+    feature    text                                               NOT NULL,
+    realm_id   uuid
+        REFERENCES public.realms
+            ON DELETE CASCADE,
+    bp_id      uuid
+        REFERENCES public.blueprints
+            ON DELETE CASCADE,
+    enabled    boolean                                            NOT NULL,
+    updated_at timestamp with time zone DEFAULT now()             NOT NULL,
+    CONSTRAINT feature_flags_scope_check
+        CHECK ((realm_id IS NULL) <> (bp_id IS NULL))
+);
+
+ALTER TABLE public.feature_flags
+    OWNER TO port4k;
+
+CREATE UNIQUE INDEX feature_flags_realm_uidx
+    ON public.feature_flags (feature, realm_id)
+    WHERE (realm_id IS NOT NULL);
+
+CREATE UNIQUE INDEX feature_flags_bp_uidx
+    ON public.feature_flags (feature, bp_id)
+    WHERE (bp_id IS NOT NULL);
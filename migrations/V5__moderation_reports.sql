@@ -0,0 +1,36 @@
+-- =====================================================================
+--  MODERATION REPORTS
+--  Abuse reports filed by players with `report <player> <reason>`,
+--  worked by moderators through `@reports`.
+-- =====================================================================
+
+CREATE TABLE public.reports (
+    id          bigint GENERATED ALWAYS AS IDENTITY PRIMARY KEY,
+    reporter_id uuid                     NOT NULL
+        REFERENCES public.accounts
+            ON DELETE CASCADE,
+    target_id   uuid                     NOT NULL
+        REFERENCES public.accounts
+            ON DELETE CASCADE,
+    reason      text                     NOT NULL,
+    realm_id    uuid
+        REFERENCES public.realms
+            ON DELETE SET NULL,
+    room_id     uuid,
+    status      text DEFAULT 'open'      NOT NULL,
+    created_at  timestamp with time zone DEFAULT now() NOT NULL,
+    resolved_by uuid
+        REFERENCES public.accounts
+            ON DELETE SET NULL,
+    resolved_at timestamp with time zone,
+    resolution  text,
+    CONSTRAINT reports_status_check
+        CHECK (status IN ('open', 'resolved'))
+);
+
+ALTER TABLE public.reports
+    OWNER TO port4k;
+
+CREATE INDEX reports_open_idx
+    ON public.reports (created_at)
+    WHERE (status = 'open');
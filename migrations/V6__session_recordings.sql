@@ -0,0 +1,25 @@
+-- =====================================================================
+--  SESSION RECORDINGS
+--  Transcripts of player sessions recorded with `@record`, stepped
+--  through with `@replay`. Events are stored as a JSON array of
+--  { "at_ms": .., "kind": "input" | "output", "text": .. }.
+-- =====================================================================
+
+CREATE TABLE public.session_recordings (
+    id          bigint GENERATED ALWAYS AS IDENTITY PRIMARY KEY,
+    account_id  uuid                     NOT NULL
+        REFERENCES public.accounts
+            ON DELETE CASCADE,
+    recorded_by uuid
+        REFERENCES public.accounts
+            ON DELETE SET NULL,
+    started_at  timestamp with time zone NOT NULL,
+    ended_at    timestamp with time zone DEFAULT now() NOT NULL,
+    events      jsonb DEFAULT '[]'::jsonb NOT NULL
+);
+
+ALTER TABLE public.session_recordings
+    OWNER TO port4k;
+
+CREATE INDEX session_recordings_account_idx
+    ON public.session_recordings (account_id, started_at);
@@ -0,0 +1,12 @@
+-- =====================================================================
+--  OBJECT DISCOVERY
+--  Objects can be obscured: hidden until a player's `search` beats the
+--  difficulty class with a perception roll.
+--  discovery is { "mode": "visible" } or { "mode": "obscured", "dc": 12 }
+-- =====================================================================
+
+ALTER TABLE public.bp_objects
+    ADD COLUMN discovery jsonb DEFAULT '{"mode": "visible"}'::jsonb NOT NULL;
+
+ALTER TABLE public.accounts
+    ADD COLUMN perception integer DEFAULT 10 NOT NULL;
@@ -0,0 +1,10 @@
+-- =====================================================================
+--  ROOM HAZARDS
+--  Environmental hazards (gas, radiation, vacuum) applied to the players
+--  in a room on a background tick: a message, damage and/or being forced
+--  into another room, unless they carry a mitigating item.
+--  hazards is a list of { "kind": "gas", "interval": 10, "damage": 5, ... }
+-- =====================================================================
+
+ALTER TABLE public.bp_rooms
+    ADD COLUMN hazards jsonb DEFAULT '[]'::jsonb NOT NULL;
@@ -0,0 +1,28 @@
+-- =====================================================================
+--  CRAFTING RECIPES
+--  Blueprint-wide recipes: two input items combine into an output item,
+--  optionally requiring a tool in the inventory (not consumed) and a
+--  minimum player level.
+-- =====================================================================
+
+CREATE TABLE public.bp_recipes (
+    id          uuid        DEFAULT gen_random_uuid() NOT NULL PRIMARY KEY,
+    bp_id       uuid                                  NOT NULL
+        REFERENCES public.blueprints
+            ON DELETE CASCADE,
+    recipe_key  varchar(64)                           NOT NULL,
+    inputs      text[]                                NOT NULL,
+    output_key  varchar(64)                           NOT NULL,
+    output_qty  integer     DEFAULT 1                 NOT NULL,
+    tool_key    varchar(64),
+    min_level   integer,
+    message     text,
+    CONSTRAINT uq_bp_recipes_bp_recipe
+        UNIQUE (bp_id, recipe_key)
+);
+
+ALTER TABLE public.bp_recipes
+    OWNER TO port4k;
+
+CREATE INDEX idx_bp_recipes_bp_id
+    ON public.bp_recipes (bp_id);
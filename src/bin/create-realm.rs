@@ -1,9 +1,8 @@
 use anyhow::Context;
 use clap::Parser;
 use port4k::config;
-use port4k::models::types::BlueprintId;
+use port4k::models::types::{AccountId, BlueprintId, RealmId};
 use std::sync::Arc;
-use uuid::Uuid;
 
 use port4k::db::Db;
 
@@ -82,8 +81,7 @@ async fn ensure_blueprint_exists(db: Arc<Db>, bp_key: &str) -> anyhow::Result<Bl
         .query_opt(r#"SELECT id FROM blueprints WHERE key = $1"#, &[&bp_key])
         .await?
     {
-        let id: Uuid = row.get("id");
-        return Ok(BlueprintId(id));
+        return Ok(row.get("id"));
     }
 
     Err(anyhow::anyhow!("Blueprint not found"))
@@ -98,8 +96,8 @@ async fn create_realm(
     title: &str,
     owner: &str,
     kind: &str,
-) -> anyhow::Result<Uuid> {
-    let realm_id = Uuid::new_v4();
+) -> anyhow::Result<RealmId> {
+    let realm_id = RealmId::new();
     let client = db.get_client().await?;
 
     // Get owner id from username
@@ -107,7 +105,7 @@ async fn create_realm(
         .query_one(r#"SELECT id FROM accounts WHERE username = $1"#, &[&owner])
         .await
         .with_context(|| format!("failed to find owner account '{}'", owner))?;
-    let owner_id: Uuid = owner_row.get("id");
+    let owner_id: AccountId = owner_row.get("id");
 
     // You might have extra columns: created_by, updated_at, jsonb config, etc.
     client
@@ -116,14 +114,7 @@ async fn create_realm(
             INSERT INTO realms (id, bp_id, key, title, owner_id, kind)
             VALUES ($1, $2, $3, $4, $5, $6)
             "#,
-            &[
-                &realm_id,
-                &blueprint_id.0, // assuming BlueprintId(Uuid)
-                &key,
-                &title,
-                &owner_id,
-                &kind,
-            ],
+            &[&realm_id, &blueprint_id, &key, &title, &owner_id, &kind],
         )
         .await
         .with_context(|| "failed to insert realm")?;
@@ -1,11 +1,11 @@
-use anyhow::Context;
 use clap::Parser;
 use port4k::config;
-use port4k::import_blueprint::import_blueprint_sub_dir;
+use port4k::import_blueprint::{
+    ensure_blueprint, find_blueprint, import_blueprint_sub_dir, import_blueprint_tree, set_entry_room,
+};
 use port4k::models::types::BlueprintId;
 use std::path::PathBuf;
 use std::sync::Arc;
-use uuid::Uuid;
 
 use port4k::db::Db;
 
@@ -14,7 +14,7 @@ use port4k::db::Db;
 struct Args {
     /// Blueprint UUID (mutually exclusive with --bp-key)
     #[arg(long)]
-    bp_id: Option<Uuid>,
+    bp_id: Option<BlueprintId>,
 
     /// Blueprint key (creates if missing when --owner is provided)
     #[arg(long, conflicts_with = "bp_id")]
@@ -28,10 +28,19 @@ struct Args {
     #[arg(long)]
     subdir: Option<String>,
 
+    /// Directory under content_base with a manifest.yaml and one sub-dir per blueprint.
+    /// Imports all blueprints listed in the manifest.
+    #[arg(long, conflicts_with_all = ["bp_id", "bp_key", "owner", "subdir", "entry_room"])]
+    root: Option<String>,
+
     /// Optionally set the blueprint’s entry room by key after import
     #[arg(long)]
     entry_room: Option<String>,
 
+    /// Validate and print what would change, without writing to the database
+    #[arg(long)]
+    dry_run: bool,
+
     /// DB URL (defaults to $DATABASE_URL)
     #[arg(long)]
     database_url: Option<String>,
@@ -46,24 +55,41 @@ async fn main() -> anyhow::Result<()> {
     let db = Arc::new(Db::new(&cfg.database_url)?);
     db.init().await?;
 
-    // Resolve or create blueprint
+    let content_base = PathBuf::from(cfg.content.import_dir.clone());
+
+    if let Some(root) = args.root.as_deref() {
+        return import_tree(root, &content_base, &db, args.dry_run).await;
+    }
+
+    // Resolve or create blueprint (a dry run never creates one)
     let bp_id = match (args.bp_id, args.bp_key.as_deref()) {
         (Some(id), _) => id,
-        (None, Some(key)) => ensure_blueprint(&db, key, args.owner.as_deref()).await?,
-        _ => anyhow::bail!("Provide either --bp-id or --bp-key (with optional --owner)"),
+        (None, Some(key)) if args.dry_run => find_blueprint(&db, key)
+            .await
+            .map_err(|e| anyhow::anyhow!("cannot resolve blueprint: {e}"))?
+            .unwrap_or_default(),
+        (None, Some(key)) => ensure_blueprint(&db, key, key, args.owner.as_deref(), None)
+            .await
+            .map_err(|e| anyhow::anyhow!("cannot resolve blueprint: {e}"))?,
+        _ => anyhow::bail!("Provide either --bp-id or --bp-key (with optional --owner), or --root"),
     };
 
-    let content_base = PathBuf::from(cfg.import_dir.clone());
     let sub_dir = args.subdir.unwrap_or_else(|| ".".to_string());
 
     // Run importer
-    import_blueprint_sub_dir(BlueprintId::from(bp_id), &sub_dir, content_base.as_path(), &db)
+    import_blueprint_sub_dir(bp_id, &sub_dir, content_base.as_path(), &db, args.dry_run)
         .await
         .map_err(|e| anyhow::anyhow!("import failed: {e}"))?;
 
+    if args.dry_run {
+        return Ok(());
+    }
+
     // Optionally set entry room
     if let Some(entry_key) = args.entry_room.as_deref() {
-        set_entry_room(&db, bp_id, entry_key).await?;
+        set_entry_room(&db, bp_id, entry_key)
+            .await
+            .map_err(|e| anyhow::anyhow!("cannot set entry room: {e}"))?;
     }
 
     println!("✓ Import complete into blueprint {bp_id}");
@@ -78,53 +104,26 @@ async fn main() -> anyhow::Result<()> {
     Ok(())
 }
 
-async fn ensure_blueprint(db: &Db, key: &str, owner_username: Option<&str>) -> anyhow::Result<Uuid> {
-    // Try existing
-    if let Some(id) = query_opt_uuid(db, "SELECT id FROM blueprints WHERE key = $1", &[&key]).await? {
-        return Ok(id);
+async fn import_tree(root: &str, content_base: &std::path::Path, db: &Db, dry_run: bool) -> anyhow::Result<()> {
+    let summary = import_blueprint_tree(root, content_base, db, dry_run)
+        .await
+        .map_err(|e| anyhow::anyhow!("import failed: {e}"))?;
+
+    println!("\n📊 Import summary for '{root}':");
+    let mut failed = 0;
+    for s in &summary {
+        match &s.result {
+            Ok(bp_id) => println!("  ✓ {:<20} {:<20} {}", s.key, s.dir, bp_id),
+            Err(e) => {
+                failed += 1;
+                println!("  ✗ {:<20} {:<20} {}", s.key, s.dir, e);
+            }
+        }
     }
-    let owner = owner_username.context("Blueprint not found; to create it, pass --owner <username>")?;
-    let owner_id = query_opt_uuid(db, "SELECT id FROM accounts WHERE username = $1", &[&owner])
-        .await?
-        .context("Owner username not found in accounts")?;
-
-    let client = db.get_client().await?;
-    let row = client
-        .query_one(
-            "INSERT INTO blueprints (key, title, owner_id, status)
-             VALUES ($1,$2,$3,'draft')
-             ON CONFLICT (key) DO UPDATE SET title = EXCLUDED.title
-             RETURNING id",
-            &[&key, &key, &owner_id],
-        )
-        .await?;
-    Ok(row.get(0))
-}
+    println!("  {} succeeded, {} failed", summary.len() - failed, failed);
 
-async fn set_entry_room(db: &Db, bp_id: Uuid, room_key: &str) -> anyhow::Result<()> {
-    let client = db.get_client().await?;
-    let row = client
-        .query_opt(
-            "SELECT id FROM bp_rooms WHERE bp_id = $1 AND key = $2",
-            &[&bp_id, &room_key],
-        )
-        .await?;
-    let room_id: Uuid = row.context("entry room key not found in blueprint")?.get(0);
-    client
-        .execute(
-            "UPDATE blueprints SET entry_room_id = $1 WHERE id = $2",
-            &[&room_id, &bp_id],
-        )
-        .await?;
+    if failed > 0 {
+        anyhow::bail!("{failed} blueprint(s) failed to import");
+    }
     Ok(())
 }
-
-async fn query_opt_uuid(
-    db: &Db,
-    sql: &str,
-    params: &[&(dyn tokio_postgres::types::ToSql + Sync)],
-) -> anyhow::Result<Option<Uuid>> {
-    let client = db.get_client().await?;
-    let row = client.query_opt(sql, params).await?;
-    Ok(row.map(|r| r.get::<_, Uuid>(0)))
-}
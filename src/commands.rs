@@ -1,8 +1,8 @@
 use crate::db::error::DbError;
 use crate::error::{AppResult, DomainError};
-use crate::input::parser::{Intent, Verb, parse_command};
-use crate::input::shell::{handle_shell_cmd, parse_shell_cmd};
-use crate::lua::LuaJob;
+use crate::input::parser::{Intent, Verb};
+use crate::lua::LuaPool;
+use crate::lua::queue::LuaSendError;
 use crate::models::account::Account;
 use crate::models::room::RoomView;
 use crate::models::types::{AccountId, RealmId, RoomId};
@@ -13,26 +13,66 @@ use crate::state::session::{Cursor, Session};
 use crate::{Registry, ansi};
 use async_trait::async_trait;
 use parking_lot::RwLock;
+use std::ops::Deref;
 use std::sync::Arc;
 use thiserror::Error;
-use tokio::sync::mpsc;
-use tokio::sync::mpsc::error::SendError;
 
+mod admin;
+mod alias;
+mod all;
 mod blueprint;
+mod combine;
+mod config;
 mod debug_cmd;
+mod delete_account;
+mod drop;
+mod editor;
+mod effects;
+mod event;
 mod examine;
 mod fallback;
+mod feature;
+mod filter;
+mod give;
 mod go;
+mod hint;
 mod inventory;
 mod login;
 mod logout;
 mod look;
 mod lua;
+mod money;
+mod notes;
+mod obj;
 mod open;
+mod physical;
+pub mod pipeline;
+mod playtest;
+mod rate;
+mod read;
+mod realm;
+mod realms;
+mod record;
 mod register;
+mod repair;
+mod replay;
+mod report;
+mod reports;
+mod resolve;
+mod room;
+mod say;
 mod search;
+mod settings;
+mod spectate;
+mod stash;
+mod submissions;
 mod take;
+mod talk;
+mod terminal;
+mod theme;
+mod vehicle;
 mod who;
+mod widget;
 
 pub type CommandResult = Result<(), CommandError>;
 
@@ -60,7 +100,7 @@ pub enum CommandError {
     NoCursor,
 
     #[error(transparent)]
-    Send(#[from] Box<SendError<LuaJob>>),
+    Lua(#[from] LuaSendError),
 
     #[error("custom error: {0}")]
     Custom(String),
@@ -78,14 +118,50 @@ pub enum CommandError {
     InvalidArgs(String),
 }
 
+impl CommandError {
+    /// The Lua workers turned the command's script away; not a failure of the command itself
+    pub fn is_busy(&self) -> bool {
+        matches!(
+            self,
+            CommandError::Lua(LuaSendError::Busy) | CommandError::Domain(DomainError::Lua(LuaSendError::Busy))
+        )
+    }
+}
+
+/// One command being handled: the player's context and what they typed. Every verb handler takes
+/// this, so data all commands need can be added here without changing each handler.
+pub struct CommandContext {
+    pub ctx: Arc<CmdCtx>,
+    /// The command as parsed
+    pub intent: Intent,
+    /// The line as typed, for handlers that take their arguments verbatim
+    pub raw: String,
+}
+
+impl CommandContext {
+    /// A command the engine runs itself, from an intent and not a typed line
+    pub fn new(ctx: Arc<CmdCtx>, intent: Intent) -> Self {
+        let raw = intent.original.clone();
+        Self { ctx, intent, raw }
+    }
+}
+
+impl Deref for CommandContext {
+    type Target = CmdCtx;
+
+    fn deref(&self) -> &CmdCtx {
+        &self.ctx
+    }
+}
+
 /// Command context passed to command handlers
 pub struct CmdCtx {
     /// Output system
     pub output: OutputHandle,
     /// Global service registry
     pub registry: Arc<Registry>,
-    /// Channel to send jobs to the Lua thread
-    pub lua_tx: mpsc::Sender<LuaJob>,
+    /// Queues jobs on the Lua workers
+    pub lua_tx: LuaPool,
     /// Player session
     pub sess: Arc<RwLock<Session>>,
 }
@@ -155,100 +231,144 @@ const ANONYMOUS_COMMANDS: [Verb; 6] = [
     Verb::Quit,
 ];
 
-const ADMIN_COMMANDS: [Verb; 1] = [Verb::LuaRepl];
+const ADMIN_COMMANDS: [Verb; 6] = [
+    Verb::LuaRepl,
+    Verb::ScConfig,
+    Verb::ScFeature,
+    Verb::ScEvent,
+    Verb::ScAdmin,
+    Verb::ScRealm,
+];
+const MODERATOR_COMMANDS: [Verb; 3] = [Verb::ScReports, Verb::ScSubmissions, Verb::ScFilter];
 
+/// Runs a command the player typed
 pub async fn process_command(raw: &str, ctx: Arc<CmdCtx>) -> CommandResult {
-    // See if we match a shell command, and handle it if so
-    if let Some(shell) = parse_shell_cmd(raw) {
-        handle_shell_cmd(shell, ctx.clone()).await?;
-        return Ok(());
+    let lock = ctx.sess.read().command_lock();
+    let _running = lock.lock().await;
+
+    {
+        let mut s = ctx.sess.write();
+        s.record_input(raw);
+        s.stats().add_command();
+        // The player is acting again, scripts may move them around anew
+        s.reset_script_chain();
     }
 
-    // Check if we are inside an interactive state (registration wizard etc)
-    match ctx.get_interactive() {
-        InteractiveState::None => {
-            // No interactive state, proceed as normal
-        }
-        st => {
-            return process_interactive_state(st, raw, ctx).await;
-        }
-    }
-
-    let intent = parse_command(raw);
-    dbg!(&intent);
+    ctx.registry.commands.run(&ctx, raw).await
+}
 
-    // Permission check
-    match permission_check(&intent, ctx.clone()) {
-        Ok(_) => {}
-        Err(PermissionError::NotLoggedIn) => {
-            ctx.output.system("You must be logged in to use that command.").await;
-            return Ok(());
-        }
-        Err(PermissionError::PermissionDenied) => {
-            ctx.output
-                .system("You do not have permission to use that command.")
-                .await;
-            return Ok(());
-        }
-    }
+/// Runs a command a script forces on the player, without taking the command lock; script actions
+/// already hold it
+pub(crate) async fn run_command(raw: &str, ctx: Arc<CmdCtx>) -> CommandResult {
+    ctx.registry.commands.run_forced(&ctx, raw).await
+}
 
+/// Calls the handler of the verb; the last stage of the pipeline
+async fn dispatch(cmd: CommandContext) -> CommandResult {
     // Let's parse the verb and call the correct command handler
-    match intent.verb {
+    match cmd.intent.verb {
         // --- Core anonymous commands ---
-        Verb::Login => login::login(ctx.clone(), intent).await,
-        Verb::Register => register::register(ctx.clone(), intent).await,
+        Verb::Login => login::login(cmd).await,
+        Verb::Register => register::register(cmd).await,
+        Verb::Report => report::report(cmd).await,
         Verb::Quit => {
-            ctx.output.system("Goodbye! Connection closed by user.").await;
+            cmd.output.system("Goodbye! Connection closed by user.").await;
             Ok(())
         }
         Verb::Close => {
-            ctx.output.system("Goodbye! Connection closed by user.").await;
+            cmd.output.system("Goodbye! Connection closed by user.").await;
             Ok(())
         }
         Verb::Help => {
-            ctx.output.system(help_text()).await;
+            cmd.output.system(help_text()).await;
             Ok(())
         }
         // --- Core logined commands ---
-        Verb::Look => look::look(ctx.clone(), intent).await,
-        Verb::Examine => examine::examine(ctx.clone(), intent).await,
-        Verb::Search => search::search(ctx.clone(), intent).await,
-        Verb::Take => take::take(ctx.clone(), intent).await,
-        Verb::Drop => {
-            ctx.output.system("Drop command not implemented yet.").await;
-            Ok(())
-        }
-        Verb::Open => open::open(ctx.clone(), intent).await,
+        Verb::Look => look::look(cmd).await,
+        Verb::Examine => examine::examine(cmd).await,
+        Verb::Search => search::search(cmd).await,
+        Verb::Hint => hint::hint(cmd).await,
+        Verb::Combine => combine::combine(cmd).await,
+        Verb::Repair => repair::repair(cmd).await,
+        Verb::Read => read::read(cmd).await,
+        Verb::Board => vehicle::board(cmd).await,
+        Verb::Disembark => vehicle::disembark(cmd).await,
+        Verb::Press => vehicle::press(cmd).await,
+        Verb::Take => take::take(cmd).await,
+        Verb::Drop => drop::drop(cmd).await,
+        Verb::Give => give::give(cmd).await,
+        Verb::Balance => money::balance(cmd).await,
+        Verb::Pay => money::pay(cmd).await,
+        Verb::Deposit => stash::deposit(cmd).await,
+        Verb::Withdraw => stash::withdraw(cmd).await,
+        Verb::Push => physical::push(cmd).await,
+        Verb::Pull => physical::pull(cmd).await,
+        Verb::Turn => physical::turn(cmd).await,
+        Verb::Throw => physical::throw(cmd).await,
+        Verb::Open => open::open(cmd).await,
         Verb::Unlock => {
-            ctx.output.system("Unlock command not implemented yet.").await;
+            cmd.output.system("Unlock command not implemented yet.").await;
             Ok(())
         }
         Verb::Lock => {
-            ctx.output.system("Lock command not implemented yet.").await;
+            cmd.output.system("Lock command not implemented yet.").await;
             Ok(())
         }
         Verb::Use => {
-            ctx.output.system("Use command not implemented yet.").await;
+            let rv = cmd.room_view()?;
+            let terminal = cmd
+                .intent
+                .direct
+                .as_ref()
+                .and_then(|np| rv.resolve_object(np))
+                .filter(|o| o.on_terminal.is_some());
+            if let Some(obj) = terminal {
+                return terminal::open(cmd.ctx.clone(), obj).await;
+            }
+            if !widget::use_widget(cmd.ctx.clone(), &cmd.intent).await? {
+                cmd.output.system("Use command not implemented yet.").await;
+            }
             Ok(())
         }
         Verb::Put => {
-            ctx.output.system("Put command not implemented yet.").await;
+            cmd.output.system("Put command not implemented yet.").await;
             Ok(())
         }
-        Verb::Talk => {
-            ctx.output.system("Talk command not implemented yet.").await;
-            Ok(())
-        }
-        Verb::Go => go::go(ctx.clone(), intent).await,
-        Verb::Inventory => inventory::inventory(ctx.clone(), intent).await,
-        Verb::Who => who::who(ctx.clone()).await,
-        Verb::Logout => logout::logout(ctx.clone(), intent).await,
+        Verb::Talk => talk::talk(cmd).await,
+        Verb::Say => say::say(cmd).await,
+        Verb::Go => go::go(cmd).await,
+        Verb::Inventory => inventory::inventory(cmd).await,
+        Verb::Who => who::who(cmd).await,
+        Verb::Realms => realms::realms(cmd).await,
+        Verb::Rate => rate::rate(cmd).await,
+        Verb::Theme => theme::theme(cmd).await,
+        Verb::Settings => settings::settings(cmd).await,
+        Verb::Alias | Verb::Unalias => alias::alias(cmd).await,
+        Verb::Logout => logout::logout(cmd).await,
+        Verb::DeleteAccount => delete_account::delete_account(cmd).await,
 
         // --- Admin commands ---
-        Verb::LuaRepl => lua::repl(ctx.clone()).await,
-
-        // --- Fallback for unimplemented commands ---
-        Verb::Custom(_) => fallback::fallback(ctx.clone(), intent).await,
+        Verb::LuaRepl => lua::repl(cmd).await,
+        Verb::ScConfig => config::config(cmd).await,
+        Verb::ScFeature => feature::feature(cmd).await,
+        Verb::ScEvent => event::event(cmd).await,
+        Verb::ScAdmin => admin::admin(cmd).await,
+        Verb::ScRealm => realm::realm(cmd).await,
+        Verb::ScReports => reports::reports(cmd).await,
+        Verb::ScSubmissions => submissions::submissions(cmd).await,
+        Verb::ScFilter => filter::filter(cmd).await,
+        Verb::ScSpectate => spectate::spectate(cmd).await,
+        Verb::ScPlaytest => playtest::playtest(cmd).await,
+        Verb::ScNotes => notes::notes(cmd).await,
+        Verb::ScRoom => room::room(cmd).await,
+        Verb::ScObj => obj::obj(cmd).await,
+        Verb::ScBlueprint => blueprint::blueprint(cmd).await,
+        Verb::ScRecord => record::record(cmd).await,
+        Verb::ScReplay => replay::replay(cmd).await,
+        Verb::ScDebug => debug_cmd::debug_cmd(cmd).await,
+
+        // --- Verbs the game does not know; the room had its chance in the pipeline already ---
+        Verb::Custom(_) => fallback::unknown(&cmd.ctx).await,
     }
 }
 
@@ -262,16 +382,75 @@ pub fn help_text() -> String {
   {fg_yellow}login <name> <password>{reset}      Log in (WebSocket or one-line)
   {fg_yellow}login <name>{reset}                 (Telnet two-step is supported; enter just `login <name>`)
   {fg_yellow}who{reset}                          List online users
+  {fg_yellow}realms [tag <t>] [by <name>]{reset} Browse the realms (add a page number for more)
+  {fg_yellow}realms <key>{reset}                 Travel to a realm
+  {fg_yellow}rate <1-5> [review]{reset}          Rate a realm you completed (also: rate tag <tag>)
+  {fg_yellow}theme [name]{reset}                 Preview color themes or switch (none for no color)
+  {fg_yellow}settings [get|set|reset]{reset}     Show or change your settings (prompt, theme, page size, brief)
+  {fg_yellow}alias [<name> <command>]{reset}     List your aliases or add one (unalias <name> removes it)
+  {fg_yellow}say <text>{reset}                   Say something to everyone in the room
+  {fg_yellow}report <name> <reason>{reset}       Report a player to the moderators
   {fg_yellow}look{reset}                         Look around your current room
+  {fg_yellow}look under <thing>{reset}           Look behind, under or over something
+  {fg_yellow}inventory [filter]{reset}           List what you carry, or only items called <filter>
+  {fg_yellow}hint{reset}                         Ask for a hint about this room
+  {fg_yellow}combine <item> with <item>{reset}   Craft something from two items you carry
+  {fg_yellow}repair <item> [with <tool>]{reset}  Repair a worn or broken item
+  {fg_yellow}read <thing> [page]{reset}          Read a book or log (then: read next / read prev)
+  {fg_yellow}board <thing> / disembark{reset}    Step into or out of an elevator or shuttle
+  {fg_yellow}press <button>{reset}               Send the vehicle you are in to a stop
+  {fg_yellow}enter <code> on <keypad>{reset}     Type a code (also: pull <lever>, select <n>)
+  {fg_yellow}use <terminal>{reset}               Work an in-game computer ('.exit' to leave)
+  {fg_yellow}talk to <npc>{reset}                Start a conversation; answer with a number, or bye
   {fg_yellow}go <dir>{reset}                     Move (e.g., go north / go east)
-  {fg_yellow}take coin [N]{reset}                Pick up up to N coins from the room
+  {fg_yellow}take [N] <item>{reset}              Pick up an item, or N of a stack
+  {fg_yellow}take all [<item>]{reset}            Pick up everything (of that name) here
+  {fg_yellow}drop [N] <item>{reset}              Drop an item, or N of a stack
+  {fg_yellow}drop all [<item>]{reset}            Drop everything (of that name) you carry
+  {fg_yellow}give [N] <item> to <who>{reset}     Give an item to a player or NPC here
+  {fg_yellow}push/pull/turn <thing>{reset}       Move, pull back or turn something
+  {fg_yellow}throw <item> [at <thing>]{reset}    Throw something you carry
   {fg_yellow}balance{reset}                      Show how many coins you have
+  {fg_yellow}pay <player> <amount>{reset}        Pay another player in this realm
+  {fg_yellow}deposit [N] <item>{reset}           Keep an item in the locker here
+  {fg_yellow}withdraw [<item>]{reset}            Take an item back from the locker, or list it
+  {fg_yellow}delete account [cancel]{reset}      Delete your account after a cooldown, or keep it
   {fg_yellow}quit{reset}                         Disconnect
 
 {bold}{fg_cyan}Special:{reset}
   {fg_green}@bp ...{reset}                      Manage blueprints and rooms
-  {fg_green}@playtest [key|stop]{reset}         Enter/exit playtest mode
+  {fg_green}@bp feedback <bp>{reset}            See how players rated your blueprint (builder)
+  {fg_green}@bp submit|status <bp>{reset}       Submit your blueprint for review / see the verdict
+  {fg_green}@bp lint <bp> [prose]{reset}        Check your blueprint for mistakes ('prose' adds spelling and style)
+  {fg_green}@bp graph <bp> [dot|mermaid]{reset}  Draw the rooms and exits of your blueprint (add 'file' to save it)
+  {fg_green}@bp grep <bp> <pattern>{reset}      Find where a text or Lua function is used in your blueprint
+  {fg_green}@bp fork <source> <newkey>{reset}   Copy a published blueprint into a new draft of yours
+  {fg_green}@bp collab <bp> [add|remove|grant|revoke]{reset} Manage editors and testers of your blueprint
+  {fg_green}@playtest <realm>|stop{reset}       Enter a realm of your blueprint / return
+  {fg_green}@playtest [realm] as guest{reset}   Playtest as a fresh guest persona
+  {fg_green}@playtest seed [n|reset]{reset}     Show or change the realm's random seed
+  {fg_green}@notes <bp>{reset}                  List the builder notes and TODOs of your blueprint
+  {fg_green}@room set title|body{reset}         Retitle or rewrite the room you are in (builder)
+  {fg_green}@room addexit <dir> <room>{reset}   Add an exit from the room you are in (builder)
+  {fg_green}@obj add|set|remove <obj>{reset}    Add, change or remove an object of the room you are in (builder)
   {fg_green}@debug where{reset}                 Show debug info
+  {fg_green}@debug conn [<name>]{reset}         Show traffic and queue stats of your connection (admins: anyone's)
+  {fg_green}@config reload{reset}               Reload the server config (admin)
+  {fg_green}@feature list|set|clear{reset}      Manage feature flags of this realm (admin)
+  {fg_green}@event list|add|remove{reset}       Schedule announcements and realm Lua (admin)
+  {fg_green}@admin export-player <name>{reset}  Export a player's full state to a file (admin)
+  {fg_green}@admin import-player <file>{reset}  Restore a player from an export file (admin)
+  {fg_green}@admin delete-player <name>{reset}  Delete a player's account right away (admin)
+  {fg_green}@admin lua-queue{reset}             Show the load on the Lua workers (admin)
+  {fg_green}@admin gc [purge]{reset}            Find (or delete) orphaned items and stale state (admin)
+  {fg_green}@realm reset <realm>{reset}         Wipe a realm's state, keeping the accounts (admin)
+  {fg_green}@reports list|resolve{reset}        Work the abuse report queue (moderator)
+  {fg_green}@submissions list|approve|reject{reset} Review submitted blueprints (moderator)
+  {fg_green}@filter [on|off|reset]{reset}       Override the content filter of this realm (moderator)
+  {fg_green}@spectate <name>|stop{reset}        Watch a player's session in your realm (builder)
+  {fg_green}@spectate allow|deny{reset}         Allow or refuse builders watching you
+  {fg_green}@record [on|off]{reset}             Record your session for replaying later
+  {fg_green}@replay [id|next|prev|stop]{reset}  Step through a recorded session
 "#,
         bold = ansi::BOLD,
         fg_cyan = ansi::FG_CYAN,
@@ -305,6 +484,14 @@ fn permission_check(intent: &Intent, ctx: Arc<CmdCtx>) -> PermissionResult {
         }
     }
 
+    // Check for moderator-only commands
+    if MODERATOR_COMMANDS.contains(&intent.verb) {
+        let account = ctx.account().map_err(|_| PermissionError::NotLoggedIn)?;
+        if !account.is_moderator() {
+            return Err(PermissionError::PermissionDenied);
+        }
+    }
+
     Ok(())
 }
 
@@ -315,6 +502,9 @@ async fn process_interactive_state(st: InteractiveState, raw: &str, ctx: Arc<Cmd
             login::continue_with_password(ctx.clone(), username, raw).await
         }
         InteractiveState::Register(reg_state) => register::continue_register(ctx.clone(), reg_state, raw).await,
+        InteractiveState::Terminal { object, name } => terminal::input(ctx.clone(), object, name, raw).await,
+        InteractiveState::DeleteAccountConfirm => delete_account::confirm(ctx.clone(), raw).await,
+        InteractiveState::Editor(state) => editor::input(ctx.clone(), state, raw).await,
         InteractiveState::None => Ok(()),
     }
 }
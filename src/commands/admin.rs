@@ -0,0 +1,140 @@
+//! @admin export-player <name>
+//! @admin import-player <file>
+//! @admin delete-player <name> [confirm]
+//! @admin lua-queue
+//! @admin gc [purge]
+
+use crate::commands::{CmdCtx, CommandContext, CommandResult};
+use crate::models::maintenance::total;
+use crate::util::args::words_after;
+use std::path::PathBuf;
+
+const USAGE: &str = "Usage: @admin export-player <name> | @admin import-player <file> | @admin delete-player <name> [confirm] | @admin lua-queue | @admin gc [purge]";
+
+/// `raw` is the line as typed, so file names keep their case
+pub async fn admin(cmd: CommandContext) -> CommandResult {
+    let CommandContext { ctx, intent, raw } = cmd;
+    let args: Vec<&str> = intent.args.iter().map(String::as_str).collect();
+    let export_dir = PathBuf::from(&ctx.registry.config().content.export_dir);
+    let exports = &ctx.registry.services.player_export;
+
+    match args.as_slice() {
+        [_, "export-player", name] => {
+            let path = exports.export_to_file(name, &export_dir).await?;
+            ctx.output
+                .system(format!("[admin] {} exported to {}.", name, path.display()))
+                .await;
+        }
+        [_, "import-player", _] => {
+            let (bundle, recreated) = exports.import_from_file(&export_dir, words_after(&raw, 2)).await?;
+            let name = bundle.username().unwrap_or("?");
+            ctx.output
+                .system(format!(
+                    "[admin] {} restored from the export of {}.",
+                    name, bundle.exported_at
+                ))
+                .await;
+            if recreated {
+                ctx.output
+                    .system(format!("[admin] {} was recreated and has no password yet.", name))
+                    .await;
+            }
+        }
+        [_, "delete-player", name, rest @ ..] => delete_player(&ctx, name, rest == ["confirm"]).await?,
+        [_, "lua-queue"] => lua_queue(&ctx).await,
+        [_, "gc"] => gc(&ctx, false).await?,
+        [_, "gc", "purge"] => gc(&ctx, true).await?,
+        _ => ctx.output.system(USAGE).await,
+    }
+
+    Ok(())
+}
+
+/// Purges an account right away, without the cooldown of `delete account`. Its blueprints go to
+/// the admin.
+async fn delete_player(ctx: &CmdCtx, name: &str, confirmed: bool) -> CommandResult {
+    let admin = ctx.account()?;
+    let Some(account) = ctx.registry.services.account.get_by_username(name).await? else {
+        ctx.output
+            .system(format!("[admin] there is no player named '{}'.", name))
+            .await;
+        return Ok(());
+    };
+    if account.id == admin.id {
+        ctx.output
+            .system("[admin] use 'delete account' to delete your own account.")
+            .await;
+        return Ok(());
+    }
+    if ctx.registry.connected_by_name(&account.username).is_some() {
+        ctx.output
+            .system(format!(
+                "[admin] {} is online; they must be logged out first.",
+                account.username
+            ))
+            .await;
+        return Ok(());
+    }
+    if !confirmed {
+        ctx.output
+            .system(format!(
+                "[admin] this purges {} and everything linked to the account, and hands their blueprints to you. \
+                 Consider '@admin export-player {}' first, then repeat with 'confirm'.",
+                account.username, account.username
+            ))
+            .await;
+        return Ok(());
+    }
+
+    ctx.registry
+        .services
+        .account_deletion
+        .purge(account.id, Some(admin.id))
+        .await?;
+    ctx.output
+        .system(format!("[admin] {} has been deleted.", account.username))
+        .await;
+    Ok(())
+}
+
+/// Shows how busy the Lua workers are
+async fn lua_queue(ctx: &CmdCtx) {
+    let stats = ctx.lua_tx.stats();
+    let mut out = String::from("[admin] Lua workers (interactive / background jobs waiting):");
+    for (idx, (interactive, background)) in stats.depths.iter().enumerate() {
+        out.push_str(&format!("\n  worker {}: {} / {}", idx, interactive, background));
+    }
+    out.push_str(&format!(
+        "\n  {} job(s) in flight for {} session(s), {} turned away since startup",
+        stats.in_flight, stats.sessions, stats.shed
+    ));
+    ctx.output.system(out).await;
+}
+
+/// Reports the rows nothing points at anymore, or purges them
+async fn gc(ctx: &CmdCtx, purge: bool) -> CommandResult {
+    let maintenance = &ctx.registry.services.maintenance;
+    let garbage = if purge {
+        maintenance.purge_garbage().await?
+    } else {
+        maintenance.find_garbage().await?
+    };
+
+    if garbage.is_empty() {
+        ctx.output.system("[admin] no garbage found.").await;
+        return Ok(());
+    }
+    let mut out = if purge {
+        format!("[admin] purged {} row(s):", total(&garbage))
+    } else {
+        format!("[admin] found {} row(s) of garbage:", total(&garbage))
+    };
+    for g in &garbage {
+        out.push_str(&format!("\n  {}: {}", g.what, g.count));
+    }
+    if !purge {
+        out.push_str("\n  '@admin gc purge' deletes them.");
+    }
+    ctx.output.system(out).await;
+    Ok(())
+}
@@ -0,0 +1,71 @@
+//! alias                       list your aliases
+//! alias <name>                show what an alias expands to
+//! alias <name> <command>      make <name> a shorthand for <command>
+//! unalias <name>              remove an alias
+//!
+//! A line starting with an alias starts with its command instead, so with `alias tw take wrench
+//! from`, typing `tw crate` takes the wrench from the crate. Aliases are stored with the settings
+//! and apply to every session.
+
+use crate::commands::{CommandContext, CommandResult};
+use crate::error::DomainError;
+use crate::input::parser::Verb;
+use crate::util::args::words_after;
+
+pub async fn alias(cmd: CommandContext) -> CommandResult {
+    let CommandContext { ctx, intent, raw } = cmd;
+    let args: Vec<&str> = intent.args.iter().skip(1).map(String::as_str).collect();
+    let mut settings = ctx.account()?.settings.clone();
+
+    let changed = match (&intent.verb, args.as_slice()) {
+        (Verb::Alias, []) => {
+            if settings.aliases.is_empty() {
+                ctx.output
+                    .system("You have no aliases. Add one with 'alias <name> <command>'.")
+                    .await;
+                return Ok(());
+            }
+            let mut out = "Your aliases:".to_string();
+            for (name, expansion) in &settings.aliases {
+                out.push_str(&format!("\n  {:<16} {}", name, expansion));
+            }
+            ctx.output.system(out).await;
+            return Ok(());
+        }
+        (Verb::Alias, [name]) => {
+            let msg = match settings.aliases.get(&name.to_lowercase()) {
+                Some(expansion) => format!("{} is an alias for '{}'.", name, expansion),
+                None => format!("You have no alias '{}'.", name),
+            };
+            ctx.output.system(msg).await;
+            return Ok(());
+        }
+        (Verb::Alias, [name, ..]) => match settings.set_alias(name, words_after(&raw, 2)) {
+            Ok(()) => format!("{} is now an alias for '{}'.", name, words_after(&raw, 2)),
+            Err(DomainError::Validation { message, .. }) => {
+                ctx.output.system(format!("Cannot do that: {}.", message)).await;
+                return Ok(());
+            }
+            Err(e) => return Err(e.into()),
+        },
+        (_, [name]) if settings.remove_alias(name) => format!("Alias {} removed.", name),
+        (_, [name]) => {
+            ctx.output.system(format!("You have no alias '{}'.", name)).await;
+            return Ok(());
+        }
+        _ => {
+            ctx.output.system("Usage: unalias <name>").await;
+            return Ok(());
+        }
+    };
+
+    let account_id = ctx.account_id()?;
+    ctx.registry
+        .services
+        .account
+        .save_settings(account_id, &settings)
+        .await?;
+    ctx.sess.write().set_settings(settings);
+    ctx.output.system(changed).await;
+    Ok(())
+}
@@ -0,0 +1,58 @@
+//! take all [<item>]
+//! drop all [<item>]
+//!
+//! "all" is expanded to the things it covers before the command runs, and the command then runs
+//! for each of them, so every one reports on its own ("You take a coin. The console is bolted
+//! down."). A bare "all" covers the items on the floor (take) or in the inventory (drop); "all
+//! <noun>" covers every visible thing of that name, fixed objects included. Equipped items stay
+//! in the inventory. Taking all from a container is left to `take`.
+
+use crate::commands::resolve::{Found, Scope, resolve_all};
+use crate::commands::{CmdCtx, CommandResult, drop, take};
+use crate::input::parser::{Intent, Quantifier, Verb};
+use std::sync::Arc;
+
+/// Whether the intent is an "all" this module expands
+pub fn expands(intent: &Intent) -> bool {
+    intent.quantifier == Some(Quantifier::All)
+        && matches!(intent.verb, Verb::Take | Verb::Drop)
+        && intent.target.is_none()
+}
+
+pub async fn all(ctx: Arc<CmdCtx>, intent: Intent) -> CommandResult {
+    let np = intent.direct.as_ref();
+    let things = resolve_all(&ctx, np, Scope::for_verb(&intent.verb)).await?;
+
+    if things.is_empty() {
+        let msg = match (&intent.verb, np) {
+            (Verb::Take, Some(np)) => format!("There is no {} here.", np.head),
+            (Verb::Take, None) => "There is nothing here to take.".to_string(),
+            (_, Some(np)) => format!("You are not carrying any {}.", np.head),
+            (_, None) => "You are not carrying anything.".to_string(),
+        };
+        ctx.output.line(msg).await;
+        return Ok(());
+    }
+
+    for found in things {
+        match (&intent.verb, found) {
+            (Verb::Take, Found::Floor(item)) => take::take_item(&ctx, &item, None).await?,
+            (Verb::Drop, Found::Carried(item)) if item.is_equipped() => {
+                ctx.output
+                    .line(format!("You keep {}; you have it equipped.", item.short))
+                    .await;
+            }
+            (Verb::Drop, Found::Carried(item)) => drop::drop_item(&ctx, &item, None).await?,
+            (_, Found::Npc(npc)) => {
+                ctx.output
+                    .line(format!("The {} is not going anywhere with you.", npc.name))
+                    .await;
+            }
+            (_, Found::Object(obj)) => {
+                ctx.output.line(format!("The {} is bolted down.", obj.name)).await;
+            }
+            _ => {}
+        }
+    }
+    Ok(())
+}
@@ -1,31 +1,51 @@
+pub mod collab;
 pub mod entry;
 pub mod exit;
+pub mod feedback;
+pub mod fork;
+pub mod graph;
+pub mod grep;
 pub mod import;
+pub mod lint;
 pub mod new;
 pub mod room;
 pub mod submit;
 mod utils;
 
-use crate::commands::{CmdCtx, CommandResult};
-use crate::input::parser::Intent;
-use std::sync::Arc;
+use crate::commands::{CmdCtx, CommandContext, CommandResult};
+use crate::error::{AppResult, DomainError};
+use crate::models::blueprint::Blueprint;
+use crate::models::collaborator::BlueprintRight;
 
-#[allow(unused)]
-pub async fn blueprint(ctx: Arc<CmdCtx>, intent: Intent) -> CommandResult {
-    if intent.args.is_empty() {
-        ctx.output.system(USAGE).await;
+pub async fn blueprint(cmd: CommandContext) -> CommandResult {
+    let CommandContext { ctx, intent, .. } = cmd;
+    if !ctx.account()?.is_builder() {
+        ctx.output
+            .system("You do not have permission to use that command.")
+            .await;
         return Ok(());
     }
 
-    let head = intent.args[0].as_str();
+    // args layout: [ "@bp", <sub_cmd>, ... ]
+    let Some(head) = intent.args.get(1).map(String::as_str) else {
+        ctx.output.system(USAGE).await;
+        return Ok(());
+    };
     match head {
+        "collab" => collab::run(ctx, intent).await,
         "debug_cmd" => new::run(ctx, intent).await,
         "entry" => entry::run(ctx, intent).await,
         "exit" => exit::run(ctx, intent).await,
+        "feedback" => feedback::run(ctx, intent).await,
+        "fork" => fork::run(ctx, intent).await,
+        "graph" => graph::run(ctx, intent).await,
+        "grep" => grep::run(ctx, intent).await,
         "import" => import::run(ctx, intent).await,
+        "lint" => lint::run(ctx, intent).await,
         "new" => new::run(ctx, intent).await,
         "playtest" => new::run(ctx, intent).await,
         "room" => room::run(ctx, intent).await,
+        "status" => submit::status(ctx, intent).await,
         "script" => submit::run(ctx, intent).await,
         "submit" => submit::run(ctx, intent).await,
         _ => {
@@ -35,6 +55,29 @@ pub async fn blueprint(ctx: Arc<CmdCtx>, intent: Intent) -> CommandResult {
     }
 }
 
+/// The blueprint when the current account has `right` on it; otherwise tells them why not
+async fn require(ctx: &CmdCtx, bp_key: &str, right: BlueprintRight) -> AppResult<Option<Blueprint>> {
+    let account = ctx.account()?;
+    match ctx
+        .registry
+        .services
+        .collaborator
+        .require(&account, bp_key, right)
+        .await
+    {
+        Ok(bp) => Ok(Some(bp)),
+        Err(DomainError::NotFound(_)) => {
+            ctx.output.system(format!("[bp] no blueprint '{}'.", bp_key)).await;
+            Ok(None)
+        }
+        Err(DomainError::Validation { message, .. }) => {
+            ctx.output.system(format!("[bp] {}.", message)).await;
+            Ok(None)
+        }
+        Err(e) => Err(e),
+    }
+}
+
 #[allow(unused)]
 pub(super) const USAGE: &str = concat!(
     "\x1b[1;36mUsage:\x1b[0m\n",
@@ -57,6 +100,23 @@ pub(super) const USAGE: &str = concat!(
     "\x1b[36m<bp>\x1b[0m:\x1b[36m<room>\x1b[0m\n",
     "  \x1b[32m@bp\x1b[0m \x1b[1;33msubmit\x1b[0m ",
     "\x1b[36m<bp>\x1b[0m\n",
+    "  \x1b[32m@bp\x1b[0m \x1b[1;33mstatus\x1b[0m ",
+    "\x1b[36m<bp>\x1b[0m\n",
     "  \x1b[32m@bp\x1b[0m \x1b[1;33mimport\x1b[0m ",
     "\x1b[36m<bp>\x1b[0m \x1b[36m<dir>\x1b[0m\n",
+    "  \x1b[32m@bp\x1b[0m \x1b[1;33mlint\x1b[0m ",
+    "\x1b[36m<bp>\x1b[0m ",
+    "\x1b[2m[prose]\x1b[0m\n",
+    "  \x1b[32m@bp\x1b[0m \x1b[1;33mfeedback\x1b[0m ",
+    "\x1b[36m<bp>\x1b[0m\n",
+    "  \x1b[32m@bp\x1b[0m \x1b[1;33mfork\x1b[0m ",
+    "\x1b[36m<source>\x1b[0m \x1b[36m<newkey>\x1b[0m\n",
+    "  \x1b[32m@bp\x1b[0m \x1b[1;33mgraph\x1b[0m ",
+    "\x1b[36m<bp>\x1b[0m ",
+    "\x1b[2m[dot|mermaid] [file]\x1b[0m\n",
+    "  \x1b[32m@bp\x1b[0m \x1b[1;33mgrep\x1b[0m ",
+    "\x1b[36m<bp>\x1b[0m \x1b[36m<pattern>\x1b[0m\n",
+    "  \x1b[32m@bp\x1b[0m \x1b[1;33mcollab\x1b[0m ",
+    "\x1b[36m<bp>\x1b[0m ",
+    "\x1b[2m[add|remove|grant|revoke ...]\x1b[0m\n",
 );
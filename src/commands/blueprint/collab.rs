@@ -0,0 +1,94 @@
+//! @bp collab <bp>
+//! @bp collab <bp> add <player> editor|tester
+//! @bp collab <bp> remove <player>
+//! @bp collab <bp> grant|revoke <player> rooms|scripts|publish|playtest
+
+use crate::commands::{CmdCtx, CommandResult};
+use crate::error::{AppResult, DomainError};
+use crate::input::parser::Intent;
+use crate::models::collaborator::{BlueprintRight, BlueprintRole};
+use std::sync::Arc;
+
+const USAGE: &str = "Usage:
+  @bp collab <bp>
+  @bp collab <bp> add <player> editor|tester
+  @bp collab <bp> remove <player>
+  @bp collab <bp> grant|revoke <player> rooms|scripts|publish|playtest\n";
+
+pub async fn run(ctx: Arc<CmdCtx>, intent: Intent) -> CommandResult {
+    // args layout: [ "@bp", "collab", <bp>, <sub_cmd>, ... ]
+    let args: Vec<&str> = intent.args.iter().map(String::as_str).collect();
+    let account = ctx.account()?;
+    let collaborators = &ctx.registry.services.collaborator;
+
+    let result: AppResult<String> = match args.as_slice() {
+        [_, _, bp_key] => collaborators.list(&account, bp_key).await.map(|(bp, list)| {
+            let mut out = format!("[bp] '{}' collaborators:", bp.key);
+            if list.is_empty() {
+                out.push_str(" none.");
+            }
+            for c in list {
+                let rights: Vec<&str> = c.rights.iter().map(BlueprintRight::as_str).collect();
+                out.push_str(&format!(
+                    "\n  {:<16} {:<7} {}",
+                    c.username,
+                    c.role.as_str(),
+                    if rights.is_empty() {
+                        "-".to_string()
+                    } else {
+                        rights.join(", ")
+                    }
+                ));
+            }
+            out
+        }),
+        [_, _, bp_key, "add", player, role] => {
+            let Some(role) = BlueprintRole::parse(role) else {
+                ctx.output.system(USAGE).await;
+                return Ok(());
+            };
+            collaborators
+                .add(&account, bp_key, player, role)
+                .await
+                .map(|c| format!("[bp] {} is now {} on '{}'.", c.username, c.role.as_str(), bp_key))
+        }
+        [_, _, bp_key, "remove", player] => collaborators
+            .remove(&account, bp_key, player)
+            .await
+            .map(|_| format!("[bp] {} no longer works on '{}'.", player, bp_key)),
+        [_, _, bp_key, verb @ ("grant" | "revoke"), player, right] => {
+            let Some(right) = BlueprintRight::parse(right) else {
+                ctx.output.system(USAGE).await;
+                return Ok(());
+            };
+            collaborators
+                .set_right(&account, bp_key, player, right, *verb == "grant")
+                .await
+                .map(|rights| {
+                    let rights: Vec<&str> = rights.iter().map(BlueprintRight::as_str).collect();
+                    format!(
+                        "[bp] {} on '{}' may now: {}.",
+                        player,
+                        bp_key,
+                        if rights.is_empty() {
+                            "nothing".to_string()
+                        } else {
+                            rights.join(", ")
+                        }
+                    )
+                })
+        }
+        _ => {
+            ctx.output.system(USAGE).await;
+            return Ok(());
+        }
+    };
+
+    match result {
+        Ok(msg) => ctx.output.system(msg).await,
+        Err(DomainError::NotFound(what)) => ctx.output.system(format!("[bp] no {}.", what)).await,
+        Err(DomainError::Validation { message, .. }) => ctx.output.system(format!("[bp] {}.", message)).await,
+        Err(e) => return Err(e.into()),
+    }
+    Ok(())
+}
@@ -2,6 +2,7 @@
 
 use crate::commands::{CmdCtx, CommandError, CommandResult};
 use crate::input::parser::Intent;
+use crate::models::collaborator::BlueprintRight;
 use crate::util::args::{normalize_dir, parse_bp_room_key};
 use std::sync::Arc;
 
@@ -38,6 +39,12 @@ pub async fn run(ctx: Arc<CmdCtx>, intent: Intent) -> CommandResult {
                     .await;
                 return Ok(());
             }
+            if super::require(&ctx, &from_key.bp_key, BlueprintRight::EditRooms)
+                .await?
+                .is_none()
+            {
+                return Ok(());
+            }
 
             // optional trailing "locked"
             let want_locked = tail.first().map(|s| s.eq_ignore_ascii_case("locked")).unwrap_or(false);
@@ -0,0 +1,72 @@
+//! @bp feedback <bp>
+
+use crate::commands::{CmdCtx, CommandResult};
+use crate::error::DomainError;
+use crate::input::parser::Intent;
+use crate::models::review::stars_bar;
+use std::sync::Arc;
+
+pub async fn run(ctx: Arc<CmdCtx>, intent: Intent) -> CommandResult {
+    let Some(bp_key) = intent.args.get(2) else {
+        ctx.output.system(super::USAGE).await;
+        return Ok(());
+    };
+
+    let account = ctx.account()?;
+    let bp = match ctx
+        .registry
+        .services
+        .collaborator
+        .require_member(&account, bp_key)
+        .await
+    {
+        Ok((bp, _)) => bp,
+        Err(DomainError::NotFound(_)) => {
+            ctx.output.system(format!("[bp] no blueprint '{}'.", bp_key)).await;
+            return Ok(());
+        }
+        Err(DomainError::Validation { message, .. }) => {
+            ctx.output.system(format!("[bp] {}.", message)).await;
+            return Ok(());
+        }
+        Err(e) => return Err(e.into()),
+    };
+
+    let feedback = ctx.registry.services.review.feedback(bp.id).await?;
+    let Some(average) = feedback.average() else {
+        ctx.output
+            .system(format!("[bp] nobody has rated '{}' yet.", bp.key))
+            .await;
+        return Ok(());
+    };
+
+    let mut out = format!(
+        "[bp] feedback on '{}': {:.1} stars from {} rating(s)\n",
+        bp.key,
+        average,
+        feedback.ratings()
+    );
+    for (stars, n) in (1..=5u8).zip(feedback.histogram).rev() {
+        out.push_str(&format!("  {} {}\n", stars_bar(stars), n));
+    }
+    if !feedback.tags.is_empty() {
+        let tags: Vec<String> = feedback.tags.iter().map(|(t, n)| format!("{} ({})", t, n)).collect();
+        out.push_str(&format!("Tags: {}\n", tags.join(", ")));
+    }
+    for r in &feedback.reviews {
+        out.push_str(&format!(
+            "  {} {} in {} on {}",
+            stars_bar(r.rating.stars),
+            r.username.as_deref().unwrap_or("(deleted)"),
+            r.realm_key.as_deref().unwrap_or("?"),
+            r.rating.updated_at.format("%Y-%m-%d"),
+        ));
+        if let Some(review) = &r.rating.review {
+            out.push_str(&format!(": {}", review));
+        }
+        out.push('\n');
+    }
+    ctx.output.system(out.trim_end().to_string()).await;
+
+    Ok(())
+}
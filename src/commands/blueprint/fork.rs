@@ -0,0 +1,41 @@
+//! @bp fork <source> <newkey>
+
+use crate::commands::{CmdCtx, CommandResult};
+use crate::error::DomainError;
+use crate::input::parser::Intent;
+use std::sync::Arc;
+
+pub async fn run(ctx: Arc<CmdCtx>, intent: Intent) -> CommandResult {
+    let (Some(source_key), Some(new_key)) = (intent.args.get(2), intent.args.get(3)) else {
+        ctx.output.system(super::USAGE).await;
+        return Ok(());
+    };
+    let account = ctx.account()?;
+
+    let services = &ctx.registry.services;
+    let forked = async {
+        let source = services.collaborator.require_forkable(&account, source_key).await?;
+        let bp = services.blueprint.fork(&source, new_key, account.id).await?;
+        Ok::<_, DomainError>((source, bp))
+    }
+    .await;
+
+    match forked {
+        Ok((source, bp)) => {
+            ctx.output
+                .system(format!(
+                    "[bp] forked '{}' (version {}) into draft '{}'.",
+                    source.key, source.version, bp.key
+                ))
+                .await;
+        }
+        Err(DomainError::NotFound(_)) => {
+            ctx.output.system(format!("[bp] no blueprint '{}'.", source_key)).await;
+        }
+        Err(DomainError::Validation { message, .. } | DomainError::Conflict(message)) => {
+            ctx.output.system(format!("[bp] not forked: {}.", message)).await;
+        }
+        Err(e) => return Err(e.into()),
+    }
+    Ok(())
+}
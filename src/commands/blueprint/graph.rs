@@ -0,0 +1,64 @@
+//! @bp graph <bp> [dot|mermaid] [file]
+//!
+//! Draws the rooms and exits of the blueprint. Small blueprints are shown inline; bigger ones, or
+//! when asked with `file`, are written to the export directory.
+
+use crate::commands::{CmdCtx, CommandResult};
+use crate::input::parser::Intent;
+use crate::models::collaborator::BlueprintRight;
+use crate::models::room_graph::GraphFormat;
+use std::path::PathBuf;
+use std::sync::Arc;
+
+/// Most rooms shown inline
+const MAX_INLINE_ROOMS: usize = 25;
+
+pub async fn run(ctx: Arc<CmdCtx>, intent: Intent) -> CommandResult {
+    let Some(bp_key) = intent.args.get(2) else {
+        ctx.output.system(super::USAGE).await;
+        return Ok(());
+    };
+
+    let mut format = GraphFormat::Dot;
+    let mut to_file = false;
+    for opt in &intent.args[3..] {
+        match (opt.as_str(), GraphFormat::parse(opt)) {
+            (_, Some(f)) => format = f,
+            ("file", None) => to_file = true,
+            _ => {
+                ctx.output.system(super::USAGE).await;
+                return Ok(());
+            }
+        }
+    }
+
+    let Some(bp) = super::require(&ctx, bp_key, BlueprintRight::EditRooms).await? else {
+        return Ok(());
+    };
+    let blueprints = &ctx.registry.services.blueprint;
+    let graph = blueprints.room_graph(bp.id).await?;
+
+    let unreachable = graph.unreachable();
+    let mut summary = format!(
+        "[bp] '{}' has {} room(s) and {} exit(s)",
+        bp.key,
+        graph.rooms.len(),
+        graph.exits.len()
+    );
+    if !unreachable.is_empty() {
+        summary.push_str(&format!(", unreachable: {}", unreachable.join(", ")));
+    }
+
+    if to_file || graph.rooms.len() > MAX_INLINE_ROOMS {
+        let dir = PathBuf::from(&ctx.registry.config().content.export_dir);
+        let path = blueprints.room_graph_to_file(&bp, format, &dir).await?;
+        ctx.output
+            .system(format!("{}.\n[bp] graph written to {}.", summary, path.display()))
+            .await;
+    } else {
+        ctx.output
+            .system(format!("{}:\n{}", summary, graph.render(&bp.key, format)))
+            .await;
+    }
+    Ok(())
+}
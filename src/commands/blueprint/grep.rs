@@ -0,0 +1,54 @@
+//! @bp grep <bp> <pattern>
+//!
+//! Lists the lines of room and object texts, Lua scripts and Lua modules of the blueprint that
+//! contain the pattern, ignoring case, with where each one is.
+
+use crate::commands::{CmdCtx, CommandResult};
+use crate::error::DomainError;
+use crate::input::parser::Intent;
+use crate::models::collaborator::BlueprintRight;
+use std::sync::Arc;
+
+/// Lines shown before the rest is summarized
+const MAX_SHOWN: usize = 40;
+
+pub async fn run(ctx: Arc<CmdCtx>, intent: Intent) -> CommandResult {
+    let Some(bp_key) = intent.args.get(2) else {
+        ctx.output.system(super::USAGE).await;
+        return Ok(());
+    };
+    let pattern = intent.args[3..].join(" ");
+    let pattern = pattern.trim_matches('"');
+    if pattern.is_empty() {
+        ctx.output.system(super::USAGE).await;
+        return Ok(());
+    }
+    if super::require(&ctx, bp_key, BlueprintRight::EditRooms).await?.is_none() {
+        return Ok(());
+    }
+
+    let hits = match crate::import_blueprint::grep_blueprint(&ctx.registry.db, bp_key, pattern).await {
+        Ok(hits) => hits,
+        Err(DomainError::NotFound(_)) => {
+            ctx.output.system(format!("[bp] no blueprint '{}'.", bp_key)).await;
+            return Ok(());
+        }
+        Err(e) => return Err(e.into()),
+    };
+
+    if hits.is_empty() {
+        ctx.output
+            .system(format!("[bp] '{}' is not used in '{}'.", pattern, bp_key))
+            .await;
+        return Ok(());
+    }
+    let mut out = format!("[bp] {} line(s) with '{}' in '{}':", hits.len(), pattern, bp_key);
+    for hit in hits.iter().take(MAX_SHOWN) {
+        out.push_str(&format!("\n  {}", hit));
+    }
+    if hits.len() > MAX_SHOWN {
+        out.push_str(&format!("\n  ... and {} more", hits.len() - MAX_SHOWN));
+    }
+    ctx.output.system(out).await;
+    Ok(())
+}
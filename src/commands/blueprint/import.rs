@@ -2,6 +2,7 @@
 
 use crate::commands::{CmdCtx, CommandResult};
 use crate::input::parser::Intent;
+use crate::models::collaborator::BlueprintRight;
 use std::path::Path;
 use std::sync::Arc;
 
@@ -15,13 +16,19 @@ pub async fn run(ctx: Arc<CmdCtx>, intent: Intent) -> CommandResult {
     let bp_key = &intent.args[2];
     let subdir = &intent.args[3];
 
-    // If you want to enforce permissions later:
-    // if !ctx.sess.lock().await.is_admin() { return Ok("[bp] permission denied.\n".into()); }
-
-    let blueprint = ctx.registry.repos.room.blueprint_by_key(bp_key).await?;
+    // An import replaces rooms and scripts alike
+    if super::require(&ctx, bp_key, BlueprintRight::EditRooms).await?.is_none() {
+        return Ok(());
+    }
+    let Some(blueprint) = super::require(&ctx, bp_key, BlueprintRight::EditScripts).await? else {
+        return Ok(());
+    };
 
-    let base_path = Path::new(ctx.registry.config.import_dir.as_str());
-    match crate::import_blueprint::import_blueprint_sub_dir(blueprint.id, subdir, base_path, &ctx.registry.db).await {
+    let config = ctx.registry.config();
+    let base_path = Path::new(config.content.import_dir.as_str());
+    match crate::import_blueprint::import_blueprint_sub_dir(blueprint.id, subdir, base_path, &ctx.registry.db, false)
+        .await
+    {
         Ok(()) => {
             ctx.output
                 .system(format!(
@@ -0,0 +1,89 @@
+//! @bp lint <bp> [prose]
+//!
+//! Lists the Lua warnings of the blueprint and the rooms players cannot get to, followed by the
+//! notes its builders left. With `prose`, the texts are also spell-checked against the configured
+//! dictionary and the blueprint lexicon, and checked for long paragraphs and trailing whitespace.
+
+use crate::commands::{CmdCtx, CommandResult};
+use crate::error::DomainError;
+use crate::import_blueprint::Dictionary;
+use crate::input::parser::Intent;
+use crate::models::collaborator::BlueprintRight;
+use std::sync::Arc;
+
+/// Warnings shown before the rest is summarized
+const MAX_SHOWN: usize = 30;
+
+pub async fn run(ctx: Arc<CmdCtx>, intent: Intent) -> CommandResult {
+    let Some(bp_key) = intent.args.get(2) else {
+        ctx.output.system(super::USAGE).await;
+        return Ok(());
+    };
+    let Some(bp) = super::require(&ctx, bp_key, BlueprintRight::EditScripts).await? else {
+        return Ok(());
+    };
+
+    let warnings = match crate::import_blueprint::lint_blueprint(&ctx.registry.db, bp_key).await {
+        Ok(warnings) => warnings,
+        Err(DomainError::NotFound(_)) => {
+            ctx.output.system(format!("[bp] no blueprint '{}'.", bp_key)).await;
+            return Ok(());
+        }
+        Err(e) => return Err(e.into()),
+    };
+
+    let prose = intent.args.get(3).map(String::as_str) == Some("prose");
+
+    let mut out = if warnings.is_empty() {
+        format!("[bp] no warnings in '{}'.", bp_key)
+    } else {
+        let mut out = format!("[bp] {} warning(s) in '{}':", warnings.len(), bp_key);
+        for w in warnings.iter().take(MAX_SHOWN) {
+            out.push_str(&format!("\n  {}", w));
+        }
+        if warnings.len() > MAX_SHOWN {
+            out.push_str(&format!("\n  ... and {} more", warnings.len() - MAX_SHOWN));
+        }
+        out
+    };
+
+    if prose {
+        let dictionary = match &ctx.registry.config().content.dictionary_path {
+            Some(path) => Some(Dictionary::load(path)?),
+            None => None,
+        };
+        let warnings =
+            crate::import_blueprint::lint_blueprint_prose(&ctx.registry.db, bp_key, dictionary.as_ref()).await?;
+        if warnings.is_empty() {
+            out.push_str("\n[bp] no prose warnings.");
+        } else {
+            out.push_str(&format!("\n[bp] {} prose warning(s):", warnings.len()));
+            for w in warnings.iter().take(MAX_SHOWN) {
+                out.push_str(&format!("\n  {}", w));
+            }
+            if warnings.len() > MAX_SHOWN {
+                out.push_str(&format!("\n  ... and {} more", warnings.len() - MAX_SHOWN));
+            }
+        }
+        if dictionary.is_none() {
+            out.push_str("\n[bp] no dictionary configured (content.dictionary_path), spelling was not checked.");
+        }
+    }
+
+    let notes = ctx.registry.services.blueprint.builder_notes(bp.id, None).await?;
+    if !notes.is_empty() {
+        out.push_str(&format!("\n[bp] {} builder note(s):", notes.len()));
+        for note in notes.iter().take(MAX_SHOWN) {
+            out.push_str(&format!("\n  {}", note));
+        }
+        if notes.len() > MAX_SHOWN {
+            out.push_str(&format!(
+                "\n  ... and {} more, see '@notes {}'",
+                notes.len() - MAX_SHOWN,
+                bp_key
+            ));
+        }
+    }
+    ctx.output.system(out).await;
+    Ok(())
+}
@@ -4,7 +4,7 @@ use std::sync::Arc;
 
 #[allow(unused)]
 pub async fn run(ctx: Arc<CmdCtx>, intent: Intent) -> CommandResult {
-    if intent.args.len() < 3 {
+    if intent.args.len() < 4 {
         ctx.output.system(super::USAGE).await;
         return Ok(());
     }
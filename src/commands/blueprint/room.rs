@@ -1,5 +1,6 @@
 use crate::commands::{CmdCtx, CommandError, CommandResult};
 use crate::input::parser::Intent;
+use crate::models::collaborator::BlueprintRight;
 use crate::util::args::parse_bp_room_key;
 use std::sync::Arc;
 
@@ -11,13 +12,14 @@ const USAGE: &str = "Usage:
 
 #[allow(unused)]
 pub async fn run(ctx: Arc<CmdCtx>, intent: Intent) -> CommandResult {
-    if intent.args.len() < 2 {
+    // args layout: [ "@bp", "room", <sub_cmd>, ... ]
+    if intent.args.len() < 3 {
         ctx.output.system(USAGE).await;
         return Ok(());
     }
 
-    let sub_cmd = &intent.args[1];
-    let sub_args = &intent.args[2..];
+    let sub_cmd = &intent.args[2];
+    let sub_args = &intent.args[3..];
 
     match sub_cmd.as_str() {
         // @bp room add <bp>:<room> "Title" "Body"
@@ -28,6 +30,12 @@ pub async fn run(ctx: Arc<CmdCtx>, intent: Intent) -> CommandResult {
             }
 
             let key = parse_bp_room_key(&sub_args[0]).ok_or_else(|| CommandError::Custom("use <bp>:<room>".into()))?;
+            if super::require(&ctx, &key.bp_key, BlueprintRight::EditRooms)
+                .await?
+                .is_none()
+            {
+                return Ok(());
+            }
 
             let title = &sub_args[1];
             let body = &sub_args[2];
@@ -56,6 +64,12 @@ pub async fn run(ctx: Arc<CmdCtx>, intent: Intent) -> CommandResult {
             }
 
             let key = parse_bp_room_key(&sub_args[0]).ok_or_else(|| CommandError::Custom("use <bp>:<room>".into()))?;
+            if super::require(&ctx, &key.bp_key, BlueprintRight::EditRooms)
+                .await?
+                .is_none()
+            {
+                return Ok(());
+            }
 
             if ctx.registry.services.blueprint.set_locked(&key, true).await? {
                 ctx.output.system("[bp] blueprint/room set to LOCKED.").await;
@@ -73,6 +87,12 @@ pub async fn run(ctx: Arc<CmdCtx>, intent: Intent) -> CommandResult {
             }
 
             let key = parse_bp_room_key(&sub_args[0]).ok_or_else(|| CommandError::Custom("use <bp>:<room>".into()))?;
+            if super::require(&ctx, &key.bp_key, BlueprintRight::EditRooms)
+                .await?
+                .is_none()
+            {
+                return Ok(());
+            }
 
             if ctx.registry.services.blueprint.set_locked(&key, false).await? {
                 ctx.output.system("[bp] blueprint/room set to UNLOCKED.").await;
@@ -1,22 +1,92 @@
 //! @bp submit <bp>
+//! @bp status <bp>
 
 use crate::commands::{CmdCtx, CommandResult};
+use crate::error::DomainError;
 use crate::input::parser::Intent;
+use crate::models::submission::SubmissionStatus;
 use std::sync::Arc;
 
-#[allow(unused)]
 pub async fn run(ctx: Arc<CmdCtx>, intent: Intent) -> CommandResult {
-    if intent.args.is_empty() {
+    let Some(bp_key) = intent.args.get(2) else {
         ctx.output.system(super::USAGE).await;
         return Ok(());
-    }
+    };
+    let account = ctx.account()?;
 
-    let bp = &intent.args[0];
+    let (bp, id) = match ctx.registry.services.submission.submit(&account, bp_key).await {
+        Ok(v) => v,
+        Err(DomainError::NotFound(_)) => {
+            ctx.output.system(format!("[bp] no blueprint '{}'.", bp_key)).await;
+            return Ok(());
+        }
+        Err(DomainError::Validation { message, .. } | DomainError::Conflict(message)) => {
+            ctx.output.system(format!("[bp] not submitted: {}.", message)).await;
+            return Ok(());
+        }
+        Err(e) => return Err(e.into()),
+    };
 
-    if ctx.registry.services.blueprint.submit(bp).await? {
-        ctx.output.system("[bp] submitted for review.").await;
-    } else {
-        ctx.output.system("[bp] submission failed").await;
+    ctx.output
+        .system(format!(
+            "[bp] '{}' (version {}) submitted for review as #{}.",
+            bp.key, bp.version, id
+        ))
+        .await;
+    ctx.registry
+        .notify_moderators(&format!(
+            "[submissions] #{} {} submitted '{}' version {}",
+            id, account.username, bp.key, bp.version
+        ))
+        .await;
+    Ok(())
+}
+
+pub async fn status(ctx: Arc<CmdCtx>, intent: Intent) -> CommandResult {
+    let Some(bp_key) = intent.args.get(2) else {
+        ctx.output.system(super::USAGE).await;
+        return Ok(());
+    };
+    let account = ctx.account()?;
+
+    let (bp, latest) = match ctx.registry.services.submission.status(&account, bp_key).await {
+        Ok(v) => v,
+        Err(DomainError::NotFound(_)) => {
+            ctx.output.system(format!("[bp] no blueprint '{}'.", bp_key)).await;
+            return Ok(());
+        }
+        Err(DomainError::Validation { message, .. }) => {
+            ctx.output.system(format!("[bp] {}.", message)).await;
+            return Ok(());
+        }
+        Err(e) => return Err(e.into()),
+    };
+
+    let mut out = format!("[bp] '{}' is {} (version {}).", bp.key, bp.status.as_str(), bp.version);
+    if let Some(source_id) = bp.forked_from
+        && let Ok(source) = ctx.registry.services.blueprint.get_by_id(source_id).await
+    {
+        out.push_str(&format!(
+            "\nForked from '{}' version {}.",
+            source.key,
+            bp.forked_version.unwrap_or(0)
+        ));
+    }
+    if let Some(s) = latest {
+        out.push_str(&format!(
+            "\nLast submission #{}: version {} on {}, {}",
+            s.id,
+            s.version,
+            s.submitted_at.format("%Y-%m-%d %H:%M"),
+            s.status.as_str()
+        ));
+        if s.status != SubmissionStatus::Pending {
+            out.push_str(&format!(" by {}", s.decided_by.as_deref().unwrap_or("a moderator")));
+        }
+        if let Some(comment) = &s.comment {
+            out.push_str(&format!("\nComment: {}", comment));
+        }
     }
+    ctx.output.system(out).await;
     Ok(())
 }
@@ -0,0 +1,69 @@
+//! combine <item> with <item>
+//!
+//! Crafts a new item from two inventory items by one of the blueprint recipes.
+
+use crate::commands::{CommandContext, CommandResult};
+use crate::services::CraftOutcome;
+
+pub async fn combine(cmd: CommandContext) -> CommandResult {
+    let CommandContext { ctx, intent, .. } = cmd;
+    let (Some(a), Some(b)) = (intent.direct.as_ref(), intent.instrument.as_ref()) else {
+        ctx.output.system("Usage: combine <item> with <item>").await;
+        return Ok(());
+    };
+
+    let cursor = ctx.cursor()?;
+    let services = &ctx.registry.services;
+    let outcome = services
+        .crafting
+        .combine(cursor.realm_id, &cursor.account, &a.head, &b.head)
+        .await?;
+
+    match outcome {
+        CraftOutcome::Crafted { recipe, tool, .. } => {
+            let msg = match &recipe.message {
+                Some(msg) => msg.clone(),
+                None => {
+                    let output = services
+                        .inventory
+                        .get_item_by_key(cursor.realm_id, &recipe.output_key)
+                        .await?;
+                    format!("You combine the {} and the {} into {}.", a.raw, b.raw, output.short)
+                }
+            };
+            ctx.output.line(msg).await;
+            if let Some(msg) = tool.and_then(|t| t.wear_message()) {
+                ctx.output.line(msg).await;
+            }
+            services.room.lua_on_craft(ctx.clone(), &recipe).await?;
+        }
+        CraftOutcome::NotCarried(noun) => {
+            ctx.output.line(format!("You are not carrying any '{}'.", noun)).await;
+        }
+        CraftOutcome::NeedTwo(name) => {
+            ctx.output
+                .line(format!("You need two of the {} to combine them.", name))
+                .await;
+        }
+        CraftOutcome::NoRecipe => {
+            ctx.output
+                .line(format!("The {} and the {} don't go together.", a.raw, b.raw))
+                .await;
+        }
+        CraftOutcome::MissingTool(tool) => {
+            ctx.output.line(format!("You need a {} for that.", tool)).await;
+        }
+        CraftOutcome::BrokenTool(tool) => {
+            ctx.output
+                .line(format!("Your {} is broken. You'll have to repair it first.", tool))
+                .await;
+        }
+        CraftOutcome::LevelTooLow(level) => {
+            ctx.output
+                .line(format!("You need to be level {} to make that.", level))
+                .await;
+        }
+    }
+
+    Ok(())
+}
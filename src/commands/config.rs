@@ -0,0 +1,45 @@
+//! @config reload
+
+use crate::commands::{CmdCtx, CommandContext, CommandResult};
+use std::sync::Arc;
+
+const USAGE: &str = "Usage: @config reload";
+
+pub async fn config(cmd: CommandContext) -> CommandResult {
+    let CommandContext { ctx, intent, .. } = cmd;
+    match intent.args.get(1).map(String::as_str) {
+        Some("reload") => reload(ctx).await,
+        _ => {
+            ctx.output.system(USAGE).await;
+            Ok(())
+        }
+    }
+}
+
+async fn reload(ctx: Arc<CmdCtx>) -> CommandResult {
+    let report = match ctx.registry.reload_config() {
+        Ok(report) => report,
+        Err(e) => {
+            ctx.output
+                .system(format!("[config] reload failed, keeping current config: {e}"))
+                .await;
+            return Ok(());
+        }
+    };
+
+    if report.is_empty() {
+        ctx.output.system("[config] reloaded, nothing changed.").await;
+        return Ok(());
+    }
+
+    let mut out = String::from("[config] reloaded.");
+    for key in &report.applied {
+        out.push_str(&format!("\n  applied:          {key}"));
+    }
+    for key in &report.restart_required {
+        out.push_str(&format!("\n  requires restart: {key}"));
+    }
+    ctx.output.system(out).await;
+
+    Ok(())
+}
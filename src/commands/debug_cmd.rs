@@ -1,12 +1,13 @@
-use crate::commands::{CmdCtx, CommandResult};
-use crate::input::parser::Intent;
+use crate::commands::{CmdCtx, CommandContext, CommandResult};
+use crate::models::realm_directory::format_duration;
+use crate::net::stats::format_bytes;
+use crate::state::session::Protocol;
 use std::sync::Arc;
 
-#[allow(unused)]
-const USAGE: &str = "Usage: debug <where|col>\n";
+const USAGE: &str = "Usage: @debug <where|col|conn [<name>]>\n";
 
-#[allow(unused)]
-pub async fn debug_cmd(ctx: Arc<CmdCtx>, intent: Intent) -> CommandResult {
+pub async fn debug_cmd(cmd: CommandContext) -> CommandResult {
+    let CommandContext { ctx, intent, .. } = cmd;
     if intent.args.len() < 2 {
         ctx.output.system(USAGE).await;
         return Ok(());
@@ -66,6 +67,7 @@ pub async fn debug_cmd(ctx: Arc<CmdCtx>, intent: Intent) -> CommandResult {
                 ))
                 .await;
         }
+        "conn" => conn(ctx.clone(), intent.args.get(2).map(String::as_str)).await?,
         _ => {
             ctx.output.system("Unknown debug command.").await;
         }
@@ -73,3 +75,49 @@ pub async fn debug_cmd(ctx: Arc<CmdCtx>, intent: Intent) -> CommandResult {
 
     Ok(())
 }
+
+/// Traffic and queue stats of the own connection, or of a connected player for admins
+async fn conn(ctx: Arc<CmdCtx>, name: Option<&str>) -> CommandResult {
+    let (label, sess) = match name {
+        None => ("your connection".to_string(), ctx.sess.clone()),
+        Some(name) => {
+            if !ctx.account()?.is_admin() {
+                ctx.output
+                    .system("Only admins can see the connection of another player.")
+                    .await;
+                return Ok(());
+            }
+            let Some(player) = ctx.registry.connected_by_name(name) else {
+                ctx.output.system(format!("No player '{}' is connected.", name)).await;
+                return Ok(());
+            };
+            (format!("connection of {}", player.account.username), player.sess)
+        }
+    };
+
+    let (transport, peer, uptime, stats) = {
+        let s = sess.read();
+        let transport = match (s.protocol(), s.terminal()) {
+            (Protocol::Telnet, Some(t)) => format!("telnet ({})", t.client),
+            (Protocol::Telnet, None) => "telnet".to_string(),
+            (Protocol::WebSocket, _) => "websocket".to_string(),
+        };
+        let peer = s.peer().map(|p| p.to_string()).unwrap_or_else(|| "unknown".to_string());
+        (transport, peer, s.session_started.elapsed(), s.stats().snapshot())
+    };
+
+    ctx.output
+        .system(format!(
+            "[debug] {label}\n  transport: {transport} from {peer}, connected {}\n  traffic: {} in, {} out\n  commands: {}, Lua jobs: {}\n  output queue: {} waiting (peak {}), {} frame(s) dropped",
+            format_duration(uptime.as_secs() as i64),
+            format_bytes(stats.bytes_in),
+            format_bytes(stats.bytes_out),
+            stats.commands,
+            stats.lua_jobs,
+            stats.queue_depth,
+            stats.queue_peak,
+            stats.frames_dropped,
+        ))
+        .await;
+    Ok(())
+}
@@ -0,0 +1,86 @@
+//! delete account
+//! delete account cancel
+
+use crate::commands::{CmdCtx, CommandContext, CommandResult};
+use crate::error::DomainError;
+use crate::net::InputMode;
+use crate::state::interactive::InteractiveState;
+use std::sync::Arc;
+
+const CONFIRM_PROMPT: &str = "Type your password to delete your account (empty to keep it): ";
+
+pub async fn delete_account(cmd: CommandContext) -> CommandResult {
+    let CommandContext { ctx, intent, .. } = cmd;
+    let account_id = ctx.account_id()?;
+    let deletion = &ctx.registry.services.account_deletion;
+
+    if intent.args.get(2).map(String::as_str) == Some("cancel") {
+        if deletion.cancel(account_id).await? {
+            ctx.output.system("Your account will not be deleted.").await;
+        } else {
+            ctx.output.system("Your account is not scheduled for deletion.").await;
+        }
+        return Ok(());
+    }
+
+    if let Some(at) = deletion.pending(account_id).await? {
+        ctx.output
+            .system(format!(
+                "Your account is already scheduled for deletion on {}. Type 'delete account cancel' to keep it.",
+                at.format("%Y-%m-%d %H:%M UTC")
+            ))
+            .await;
+        return Ok(());
+    }
+
+    ctx.output
+        .system("This deletes your account, your characters, items and progress for good.")
+        .await;
+    ctx.set_interactive(InteractiveState::DeleteAccountConfirm);
+    ctx.output.set_prompt(CONFIRM_PROMPT).await;
+    ctx.output.input_mode(InputMode::Hidden('*')).await;
+    Ok(())
+}
+
+/// The password typed after `delete account`
+pub async fn confirm(ctx: Arc<CmdCtx>, raw: &str) -> CommandResult {
+    ctx.clear_interactive();
+    ctx.output.input_mode(InputMode::Normal).await;
+    ctx.output.restore_prompt().await;
+
+    let password = raw.trim();
+    if password.is_empty() {
+        ctx.output.system("Your account is kept.").await;
+        return Ok(());
+    }
+    let account = ctx.account()?;
+    if !ctx.registry.services.account.verify_password(&account, password) {
+        ctx.output.system("Wrong password. Your account is kept.").await;
+        return Ok(());
+    }
+
+    let cooldown = ctx.registry.config().accounts.deletion_cooldown();
+    match ctx
+        .registry
+        .services
+        .account_deletion
+        .request(account.id, cooldown)
+        .await
+    {
+        Ok(at) => {
+            ctx.output
+                .system(format!(
+                    "Your account will be deleted on {}. Log in and type 'delete account cancel' before then to keep it.",
+                    at.format("%Y-%m-%d %H:%M UTC")
+                ))
+                .await;
+        }
+        Err(DomainError::Validation { message, .. }) => {
+            ctx.output
+                .system(format!("Your account cannot be deleted: {}.", message))
+                .await;
+        }
+        Err(e) => return Err(e.into()),
+    }
+    Ok(())
+}
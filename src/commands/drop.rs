@@ -0,0 +1,43 @@
+//! drop [<count>] <item>
+//! drop all [<item>]
+//!
+//! Puts an item from the inventory on the floor of the room. For a stack, only `<count>` of it
+//! is dropped and the rest stays in the inventory.
+
+use crate::commands::resolve::{Found, Scope, resolve};
+use crate::commands::{CmdCtx, CommandContext, CommandResult};
+use crate::models::inventory::{ItemInstance, ItemLocation};
+
+pub async fn drop(cmd: CommandContext) -> CommandResult {
+    let CommandContext { ctx, intent, .. } = cmd;
+    let Some(np) = intent.direct.as_ref() else {
+        ctx.output.system("Usage: drop [<count>] <item>").await;
+        return Ok(());
+    };
+
+    let found = resolve(&ctx, np, Scope::for_verb(&intent.verb)).await?;
+    let Some(Found::Carried(item)) = found else {
+        ctx.output.line(format!("You are not carrying any {}.", np.head)).await;
+        return Ok(());
+    };
+    drop_item(&ctx, &item, np.quantity).await
+}
+
+/// Puts `quantity` of a carried stack, or all of it, on the floor
+pub async fn drop_item(ctx: &CmdCtx, item: &ItemInstance, quantity: Option<u32>) -> CommandResult {
+    let cursor = ctx.cursor()?;
+    let dropped = ctx
+        .registry
+        .services
+        .inventory
+        .move_quantity(item, quantity, ItemLocation::Room(cursor.room_id))
+        .await?;
+    ctx.output
+        .line(format!("You drop {}.", item.counted_text(dropped)))
+        .await;
+    if item.stackable && quantity.is_some_and(|n| i64::from(n) > i64::from(dropped)) {
+        ctx.output.line(format!("You only had {}.", dropped)).await;
+    }
+
+    Ok(())
+}
@@ -0,0 +1,57 @@
+//! Line editor for builders. Collects what they type until a line with only '.', or drops it
+//! all on '.abort'. Used for room descriptions (`@room set body`) and object scripts (`@obj`).
+
+use crate::commands::{CmdCtx, CommandResult, obj, room};
+use crate::error::AppResult;
+use crate::state::interactive::{EditTarget, EditorState, InteractiveState};
+use std::sync::Arc;
+
+/// Starts collecting lines for `target` in the current room
+pub async fn open(ctx: &CmdCtx, target: EditTarget, what: &str) -> AppResult<()> {
+    let room_id = ctx.cursor()?.room_id;
+    ctx.output
+        .system(format!(
+            "[edit] type the new {}. End with '.' on a line of its own, or '.abort' to cancel.",
+            what
+        ))
+        .await;
+    ctx.set_interactive(InteractiveState::Editor(EditorState {
+        room_id,
+        target,
+        lines: Vec::new(),
+    }));
+    ctx.output.set_prompt("edit> ").await;
+    Ok(())
+}
+
+/// A line typed while the editor is open
+pub async fn input(ctx: Arc<CmdCtx>, mut state: EditorState, raw: &str) -> CommandResult {
+    match raw.trim_end() {
+        ".abort" => {
+            ctx.clear_interactive();
+            ctx.output.restore_prompt().await;
+            ctx.output.system("[edit] cancelled, nothing changed.").await;
+            Ok(())
+        }
+        "." => {
+            ctx.clear_interactive();
+            ctx.output.restore_prompt().await;
+
+            // Moved away since opening the editor
+            if ctx.cursor()?.room_id != state.room_id {
+                ctx.output.system("[edit] you left the room, nothing was saved.").await;
+                return Ok(());
+            }
+            let text = state.lines.join("\n");
+            match state.target {
+                EditTarget::RoomBody => room::save_body(&ctx, &text).await,
+                EditTarget::ObjectScript { object, kind } => obj::save_script(&ctx, &object, &kind, &text).await,
+            }
+        }
+        line => {
+            state.lines.push(line.to_string());
+            ctx.set_interactive(InteractiveState::Editor(state));
+            Ok(())
+        }
+    }
+}
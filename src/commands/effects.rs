@@ -0,0 +1,76 @@
+//! Carries out effects (see `models::effect`) for the player running the command.
+//!
+//! Widgets, dialogue options and on_command scripts all end up here, so an unlocked exit or an
+//! item handed out behaves the same whoever asked for it.
+
+use crate::commands::{CmdCtx, CommandContext, CommandResult, go, widget};
+use crate::error::DomainError;
+use crate::input::parser::parse_command;
+use crate::models::effect::Effect;
+use crate::state::hazards::force_move;
+use std::sync::Arc;
+
+/// Carries out the effects in order and rebuilds the room view. Effects that do not fit the room
+/// the player is in by then are skipped; a missing item stops the rest.
+pub(crate) async fn apply(ctx: &Arc<CmdCtx>, effects: &[Effect]) -> CommandResult {
+    let rooms = &ctx.registry.services.room;
+    let inventory = &ctx.registry.services.inventory;
+    let realm_id = ctx.realm_id()?;
+    let account_id = ctx.account_id()?;
+    let mut rv = ctx.room_view()?;
+
+    let mut unlocked = false;
+    for effect in effects {
+        if let Err(reason) = effect.validate(&rv) {
+            tracing::warn!(room = %rv.blueprint.key, %reason, "effects: skipped");
+            continue;
+        }
+        match effect {
+            Effect::Output(text) => ctx.output.line(text).await,
+            Effect::Sound(cue) => ctx.output.sound(cue.clone()).await,
+            Effect::Move(dir) => {
+                go::go(CommandContext::new(ctx.clone(), parse_command(&format!("go {}", dir)))).await?;
+                rv = ctx.room_view()?;
+            }
+            Effect::Teleport(room_key) => {
+                let Some(room_id) = rooms.get_room_id_by_key(realm_id, room_key).await? else {
+                    tracing::warn!(room = %rv.blueprint.key, to = %room_key, "effects: unknown room");
+                    continue;
+                };
+                let Some(p) = ctx.registry.connected.get(&account_id).map(|p| p.clone()) else {
+                    continue;
+                };
+                force_move(&ctx.registry, &ctx.lua_tx, &p, &ctx.cursor()?, room_id).await?;
+                rv = ctx.room_view()?;
+            }
+            Effect::GiveItem { item, qty } => {
+                inventory.add_item(realm_id, account_id, item, *qty).await?;
+            }
+            Effect::TakeItem { item, qty } => {
+                match inventory.remove_item_by_key(realm_id, account_id, item, *qty).await {
+                    Ok(()) => {}
+                    Err(DomainError::NotFound(_) | DomainError::Validation { .. }) => {
+                        ctx.output.system("You do not have that.").await;
+                        break;
+                    }
+                    Err(e) => return Err(e.into()),
+                }
+            }
+            Effect::LockExit { dir, locked } => {
+                rooms
+                    .set_exit_locked(realm_id, rv.blueprint.id, account_id, dir.clone(), *locked)
+                    .await?;
+                unlocked |= !locked;
+            }
+            Effect::SetKv { object, key, value } => {
+                let obj = &rv.objects[rv.objects_by_key[object]];
+                rooms.set_object_state(realm_id, account_id, obj.id, key, value).await?;
+            }
+        }
+    }
+    if unlocked && let Some(cue) = rv.blueprint.sounds.unlock.clone() {
+        ctx.output.sound(cue).await;
+    }
+
+    widget::refresh_view(ctx).await
+}
@@ -0,0 +1,77 @@
+//! @event list
+//! @event add <min> <hour> <day> <month> <weekday> announce <text>
+//! @event add <min> <hour> <day> <month> <weekday> lua <code>
+//! @event remove <id>
+
+use crate::commands::{CommandContext, CommandResult};
+use crate::models::schedule::EventAction;
+use crate::util::args::words_after;
+
+const USAGE: &str =
+    "Usage: @event list | @event add <min> <hour> <day> <month> <weekday> announce|lua <text> | @event remove <id>";
+
+/// Longest part of a payload shown by `@event list`
+const PREVIEW_LEN: usize = 50;
+
+/// `raw` is the line as typed: the intent is lowercased, and announcements and Lua keep their case.
+pub async fn event(cmd: CommandContext) -> CommandResult {
+    let CommandContext { ctx, intent, raw } = cmd;
+    let args: Vec<&str> = intent.args.iter().map(String::as_str).collect();
+    let schedule = &ctx.registry.services.schedule;
+
+    match args.as_slice() {
+        [_, "list"] => {
+            let events = schedule.list().await?;
+            if events.is_empty() {
+                ctx.output.system("[event] no events scheduled.").await;
+                return Ok(());
+            }
+            let mut out = String::from("[event] scheduled events (UTC):");
+            for ev in events {
+                let mut payload: String = ev.action.payload().chars().take(PREVIEW_LEN).collect();
+                if payload.len() < ev.action.payload().len() {
+                    payload.push_str("...");
+                }
+                out.push_str(&format!(
+                    "\n  #{:<4} {:<20} {:<8} {}",
+                    ev.id,
+                    ev.schedule.to_string(),
+                    ev.action.kind(),
+                    payload
+                ));
+            }
+            ctx.output.system(out).await;
+        }
+        [_, "add", _, _, _, _, _, kind, _, ..] => {
+            let schedule_str = args[2..7].join(" ");
+            let payload = words_after(&raw, 8).to_string();
+            let (action, realm_id) = match *kind {
+                "announce" => (EventAction::Announce(payload), None),
+                // Lua runs in the realm the admin is in
+                "lua" => (EventAction::Lua(payload), ctx.cursor().ok().map(|c| c.realm_id)),
+                _ => {
+                    ctx.output.system(USAGE).await;
+                    return Ok(());
+                }
+            };
+            let id = schedule.add(&schedule_str, action, realm_id, ctx.account_id()?).await?;
+            ctx.output
+                .system(format!("[event] #{} scheduled at '{}'.", id, schedule_str))
+                .await;
+        }
+        [_, "remove", id] => {
+            let Ok(id) = id.trim_start_matches('#').parse::<i64>() else {
+                ctx.output.system(USAGE).await;
+                return Ok(());
+            };
+            if schedule.remove(id).await? {
+                ctx.output.system(format!("[event] #{} removed.", id)).await;
+            } else {
+                ctx.output.system(format!("[event] there is no event #{}.", id)).await;
+            }
+        }
+        _ => ctx.output.system(USAGE).await,
+    }
+
+    Ok(())
+}
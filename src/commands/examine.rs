@@ -1,8 +1,12 @@
-use crate::commands::{CmdCtx, CommandResult};
-use crate::input::parser::{Intent, NounPhrase};
+use crate::commands::resolve::{Found, Scope, resolve};
+use crate::commands::{CmdCtx, CommandContext, CommandResult};
+use crate::input::parser::{NounPhrase, Verb};
+use crate::models::room::Lock;
+use crate::services::describe_item;
 use std::sync::Arc;
 
-pub async fn examine(ctx: Arc<CmdCtx>, intent: Intent) -> CommandResult {
+pub async fn examine(cmd: CommandContext) -> CommandResult {
+    let CommandContext { ctx, intent, .. } = cmd;
     if let Some(noun) = intent.direct {
         // examine object
         match handle_examine_object(ctx.clone(), &noun).await {
@@ -17,8 +21,8 @@ pub async fn examine(ctx: Arc<CmdCtx>, intent: Intent) -> CommandResult {
 }
 
 async fn handle_examine_object(ctx: Arc<CmdCtx>, noun: &NounPhrase) -> anyhow::Result<()> {
-    let rv = ctx.room_view()?;
-    if let Some(obj) = rv.object_by_noun(&noun.head) {
+    let found = resolve(&ctx, noun, Scope::for_verb(&Verb::Examine)).await?;
+    if let Some(Found::Npc(obj) | Found::Object(obj)) = &found {
         match obj.examine.clone() {
             None => {
                 ctx.output
@@ -29,6 +33,19 @@ async fn handle_examine_object(ctx: Arc<CmdCtx>, noun: &NounPhrase) -> anyhow::R
                 ctx.output.line(message).await;
             }
         }
+        // Looking closely at a locked object shows what opens it
+        if obj.flags.locked
+            && let Some(feedback) = obj.lock.as_ref().and_then(Lock::feedback)
+        {
+            ctx.output.line(feedback).await;
+        }
+        return Ok(());
+    }
+
+    if let Some(Found::Carried(item) | Found::Floor(item)) = &found {
+        for line in describe_item(item) {
+            ctx.output.line(line).await;
+        }
         return Ok(());
     }
 
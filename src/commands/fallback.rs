@@ -1,11 +1,21 @@
-use crate::commands::{CmdCtx, CommandResult};
+use crate::commands::{CmdCtx, CommandError, CommandResult, effects};
 use crate::input::parser::Intent;
-use crate::lua::{LUA_CMD_TIMEOUT, LuaJob, LuaResult};
+use crate::lua::actions::command_actions;
+use crate::lua::{LuaJob, LuaResult};
 use std::sync::Arc;
 use tokio::sync::oneshot;
 use tokio::time::timeout;
 
-pub async fn fallback(ctx: Arc<CmdCtx>, intent: Intent) -> CommandResult {
+/// Answers a verb the game does not know, once the room passed on it
+pub async fn unknown(ctx: &CmdCtx) -> CommandResult {
+    let s = "{c:bright_red}Unknown command specified.{c}";
+    ctx.output.system(s).await;
+    Ok(())
+}
+
+/// Offers the command to the room's on_command script. Returns true when the script handled it,
+/// or failed and reported that already.
+pub async fn room_command(ctx: Arc<CmdCtx>, intent: Intent) -> Result<bool, CommandError> {
     // let account = ctx.account()?;
     let cursor = ctx.cursor()?;
     let account_id = ctx.account_id()?;
@@ -21,25 +31,26 @@ pub async fn fallback(ctx: Arc<CmdCtx>, intent: Intent) -> CommandResult {
             intent: Box::new(intent),
             reply: tx,
         })
-        .await
-        .map_err(Box::new)?;
+        .await?;
 
-    match timeout(LUA_CMD_TIMEOUT, rx).await {
+    match timeout(ctx.registry.config().lua.command_timeout(), rx).await {
         Ok(Ok(lua_result)) => match lua_result {
             LuaResult::Failed(msg) => {
                 let s = format!("{{c:yellow:bright_red}}Lua script failure: {msg}{{c}}");
                 ctx.output.system(s).await;
-                return Ok(());
             }
             LuaResult::Success(v) => {
-                if v.as_boolean().unwrap_or(false) {
-                    // Script handled the command
-                    return Ok(());
+                // A table of actions is handled by carrying them out
+                match command_actions(&v) {
+                    Some(Ok(effects)) => effects::apply(&ctx, &effects).await?,
+                    Some(Err(msg)) => {
+                        let s = format!("{{c:yellow:bright_red}}Lua script failure: invalid action: {msg}{{c}}");
+                        ctx.output.system(s).await;
+                    }
+                    // Only if returned "true" then we consider it handled; "pass", false and nil
+                    // leave the command to the engine
+                    None => return Ok(v.as_boolean().unwrap_or(false)),
                 }
-
-                // Script did not handle the command, for now, we just return "unknown command"
-                let s = "{c:bright_red}Unknown command specified.{c}";
-                ctx.output.system(s).await;
             }
         },
         Ok(Err(e)) => {
@@ -52,5 +63,5 @@ pub async fn fallback(ctx: Arc<CmdCtx>, intent: Intent) -> CommandResult {
         }
     }
 
-    Ok(())
+    Ok(true)
 }
@@ -0,0 +1,66 @@
+//! @feature list
+//! @feature set <name> on|off [bp]
+//! @feature clear <name> [bp]
+
+use crate::commands::{CommandContext, CommandResult};
+use crate::models::feature::FeatureScope;
+
+const USAGE: &str = "Usage: @feature list | @feature set <name> on|off [bp] | @feature clear <name> [bp]";
+
+pub async fn feature(cmd: CommandContext) -> CommandResult {
+    let CommandContext { ctx, intent, .. } = cmd;
+    let cursor = ctx.cursor()?;
+    let args: Vec<&str> = intent.args.iter().map(String::as_str).collect();
+
+    // Flags are set on the current realm, or on its blueprint with a trailing "bp"
+    let scope = |rest: &[&str]| match rest.first() {
+        Some(&"bp") => FeatureScope::Blueprint(cursor.realm.bp_id),
+        _ => FeatureScope::Realm(cursor.realm_id),
+    };
+
+    match args.as_slice() {
+        [_, "list"] => {
+            let flags = ctx.registry.features.list(cursor.realm_id).await?;
+            if flags.is_empty() {
+                ctx.output.system("[feature] no flags set for this realm.").await;
+                return Ok(());
+            }
+            let mut out = String::from("[feature] flags for this realm:");
+            for f in flags {
+                let state = if f.enabled { "on" } else { "off" };
+                out.push_str(&format!("\n  {:<20} {:<4} ({})", f.feature, state, f.scope));
+            }
+            ctx.output.system(out).await;
+        }
+        [_, "set", name, value, rest @ ..] => {
+            let enabled = match *value {
+                "on" | "true" => true,
+                "off" | "false" => false,
+                _ => {
+                    ctx.output.system(USAGE).await;
+                    return Ok(());
+                }
+            };
+            let scope = scope(rest);
+            ctx.registry.features.set(name, scope, enabled).await?;
+            ctx.output
+                .system(format!("[feature] {} set to {} for {}.", name, value, scope))
+                .await;
+        }
+        [_, "clear", name, rest @ ..] => {
+            let scope = scope(rest);
+            if ctx.registry.features.clear(name, scope).await? {
+                ctx.output
+                    .system(format!("[feature] {} cleared for {}.", name, scope))
+                    .await;
+            } else {
+                ctx.output
+                    .system(format!("[feature] {} was not set for {}.", name, scope))
+                    .await;
+            }
+        }
+        _ => ctx.output.system(USAGE).await,
+    }
+
+    Ok(())
+}
@@ -0,0 +1,50 @@
+//! @filter
+//! @filter on|off
+//! @filter reset
+
+use crate::commands::{CommandContext, CommandResult};
+use crate::models::feature::FeatureScope;
+use crate::services::CONTENT_FILTER_FEATURE;
+
+const USAGE: &str = "Usage: @filter | @filter on|off | @filter reset";
+
+/// Lets moderators override the content filter for the realm they are in. `reset` drops the
+/// override, so the blueprint or global setting applies again.
+pub async fn filter(cmd: CommandContext) -> CommandResult {
+    let CommandContext { ctx, intent, .. } = cmd;
+    let cursor = ctx.cursor()?;
+    let scope = FeatureScope::Realm(cursor.realm_id);
+    let features = &ctx.registry.features;
+
+    match intent.args.get(1).map(String::as_str) {
+        None => {}
+        Some(v @ ("on" | "off")) => {
+            features.set(CONTENT_FILTER_FEATURE, scope, v == "on").await?;
+            let account = ctx.account()?;
+            tracing::info!(
+                target: "port4k::filter",
+                moderator = %account.username,
+                realm = %cursor.realm_id,
+                enabled = v == "on",
+                "content filter override"
+            );
+        }
+        Some("reset") => {
+            features.clear(CONTENT_FILTER_FEATURE, scope).await?;
+        }
+        Some(_) => {
+            ctx.output.system(USAGE).await;
+            return Ok(());
+        }
+    }
+
+    let enabled = features.enabled(CONTENT_FILTER_FEATURE, cursor.realm_id).await?;
+    ctx.output
+        .system(format!(
+            "[filter] content filter is {} in this realm.",
+            if enabled { "on" } else { "off" }
+        ))
+        .await;
+
+    Ok(())
+}
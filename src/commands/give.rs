@@ -0,0 +1,163 @@
+//! give [<count>] <item> to <player|npc>
+//!
+//! Hands an item from the inventory to another player in the room, or to an NPC. Players take it
+//! unless they turned the `gifts` setting off. NPCs (and other objects) only take it when their
+//! on_receive script returns true; objects without one refuse everything.
+
+use crate::commands::resolve::{Found, Scope, resolve};
+use crate::commands::{CmdCtx, CommandContext, CommandResult};
+use crate::input::parser::NounPhrase;
+use crate::models::inventory::{ItemInstance, ItemLocation};
+use crate::models::room::ResolvedObject;
+use crate::models::types::AccountId;
+use crate::state::registry::ConnectedPlayer;
+use crate::state::session::Cursor;
+use std::sync::Arc;
+
+const USAGE: &str = "Usage: give [<count>] <item> to <player|npc>";
+
+pub async fn give(cmd: CommandContext) -> CommandResult {
+    let CommandContext { ctx, intent, .. } = cmd;
+    let (Some(np), Some(to)) = (intent.direct.as_ref(), intent.target.as_ref()) else {
+        ctx.output.system(USAGE).await;
+        return Ok(());
+    };
+
+    let cursor = ctx.cursor()?;
+    let found = resolve(&ctx, np, Scope::for_verb(&intent.verb)).await?;
+    let Some(Found::Carried(item)) = found else {
+        ctx.output.line(format!("You are not carrying any {}.", np.head)).await;
+        return Ok(());
+    };
+
+    let players = recipients(&ctx, &cursor, to);
+    if let Some(player) = players.first() {
+        if player.account.id == cursor.account_id {
+            ctx.output.line("You already have it.").await;
+            return Ok(());
+        }
+        return give_to_player(&ctx, &item, np, &players).await;
+    }
+
+    match resolve(&ctx, to, &[Scope::Npcs, Scope::Objects]).await? {
+        Some(Found::Npc(obj)) => give_to_object(ctx.clone(), &item, np, &obj).await,
+        Some(Found::Object(obj)) if obj.on_receive.is_some() => give_to_object(ctx.clone(), &item, np, &obj).await,
+        Some(Found::Object(obj)) => {
+            ctx.output
+                .line(format!("You can't give things to the {}.", obj.name))
+                .await;
+            Ok(())
+        }
+        _ => {
+            ctx.output.line(format!("There is no {} here.", to.head)).await;
+            Ok(())
+        }
+    }
+}
+
+/// Sessions in the room of the player called `to`; more than one when they are connected twice
+fn recipients(ctx: &CmdCtx, cursor: &Cursor, to: &NounPhrase) -> Vec<ConnectedPlayer> {
+    ctx.registry
+        .players_in_realm_room(cursor.realm_id, cursor.room_id)
+        .into_iter()
+        .filter(|p| p.account.username.eq_ignore_ascii_case(&to.head))
+        .collect()
+}
+
+/// Number of the item to give: the count asked for, up to the size of the stack
+fn offered(item: &ItemInstance, np: &NounPhrase) -> i32 {
+    match np.quantity {
+        Some(n) if item.stackable => i32::try_from(n).unwrap_or(i32::MAX).min(item.quantity),
+        _ => item.quantity,
+    }
+}
+
+async fn give_to_player(
+    ctx: &CmdCtx,
+    item: &ItemInstance,
+    np: &NounPhrase,
+    sessions: &[ConnectedPlayer],
+) -> CommandResult {
+    let recipient = &sessions[0].account;
+    let accepts = sessions[0]
+        .sess
+        .read()
+        .get_account()
+        .map_or(recipient.settings.gifts, |a| a.settings.gifts);
+    if !accepts {
+        ctx.output
+            .line(format!("{} does not accept gifts.", recipient.username))
+            .await;
+        return Ok(());
+    }
+
+    let given = ctx
+        .registry
+        .services
+        .inventory
+        .move_quantity(item, np.quantity, ItemLocation::Player(recipient.id))
+        .await?;
+    let text = item.counted_text(given);
+    let giver = ctx.account()?;
+
+    ctx.output
+        .line(format!("You give {} to {}.", text, recipient.username))
+        .await;
+    for p in sessions {
+        p.output.line(format!("{} gives you {}.", giver.username, text)).await;
+    }
+    tell_others(
+        ctx,
+        &[giver.id, recipient.id],
+        &format!("{} gives {} to {}.", giver.username, text, recipient.username),
+    )
+    .await
+}
+
+async fn give_to_object(ctx: Arc<CmdCtx>, item: &ItemInstance, np: &NounPhrase, obj: &ResolvedObject) -> CommandResult {
+    let quantity = offered(item, np);
+    let accepted = ctx
+        .registry
+        .services
+        .room
+        .lua_on_receive(ctx.clone(), obj, item, quantity)
+        .await?;
+    if !accepted {
+        ctx.output
+            .line(format!(
+                "The {} does not want {}.",
+                obj.name,
+                item.counted_text(quantity)
+            ))
+            .await;
+        return Ok(());
+    }
+
+    let given = ctx
+        .registry
+        .services
+        .inventory
+        .move_quantity(item, np.quantity, ItemLocation::Object(obj.id))
+        .await?;
+    let text = item.counted_text(given);
+    let giver = ctx.account()?;
+
+    ctx.output.line(format!("You give {} to the {}.", text, obj.name)).await;
+    tell_others(
+        &ctx,
+        &[giver.id],
+        &format!("{} gives {} to the {}.", giver.username, text, obj.name),
+    )
+    .await
+}
+
+/// Tells everyone else in the room, except the players in `skip`
+async fn tell_others(ctx: &CmdCtx, skip: &[AccountId], msg: &str) -> CommandResult {
+    let cursor = ctx.cursor()?;
+    for p in ctx.registry.players_in_realm_room(cursor.realm_id, cursor.room_id) {
+        if !skip.contains(&p.account.id) {
+            p.output.line(msg).await;
+        }
+    }
+    Ok(())
+}
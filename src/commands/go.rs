@@ -1,9 +1,11 @@
-use crate::commands::{CmdCtx, CommandResult};
-use crate::input::parser::Intent;
+use crate::commands::{CmdCtx, CommandContext, CommandResult};
+use crate::models::room::{Lock, locked_message};
 use crate::models::types::Direction;
+use crate::state::presence;
 use std::sync::Arc;
 
-pub async fn go(ctx: Arc<CmdCtx>, intent: Intent) -> CommandResult {
+pub async fn go(cmd: CommandContext) -> CommandResult {
+    let CommandContext { ctx, intent, .. } = cmd;
     // 1. parse direction
     let Some(dir) = intent.direction else {
         ctx.output.system("Usage: go <direction>").await;
@@ -25,8 +27,21 @@ pub async fn go(ctx: Arc<CmdCtx>, intent: Intent) -> CommandResult {
         Err(MoveError::NoSuchExit) => {
             ctx.output.line("You can't go that way.").await;
         }
-        Err(MoveError::ExitLocked) => {
-            ctx.output.line("The way is locked.").await;
+        Err(MoveError::ExitLocked(lock)) => {
+            ctx.output
+                .line(locked_message("The way is locked.", lock.as_ref()))
+                .await;
+        }
+        Err(MoveError::Lockdown(Some(secs))) => {
+            ctx.output
+                .line(format!(
+                    "The room is in lockdown. The exits unseal in {} seconds.",
+                    secs
+                ))
+                .await;
+        }
+        Err(MoveError::Lockdown(None)) => {
+            ctx.output.line("The room is in lockdown. The exits are sealed.").await;
         }
         Err(MoveError::Blocked(msg)) => {
             ctx.output.line(msg).await;
@@ -62,8 +77,22 @@ async fn try_move_player(ctx: Arc<CmdCtx>, dir: Direction) -> Result<(), MoveErr
     if !exit.is_visible_to() {
         return Err(MoveError::NoSuchExit); // pretend it doesn't exist
     }
+    // A lockdown seals every exit. The cached view can predate a timed lockdown, so ask for it.
+    if rv.blueprint.lockdown {
+        return Err(MoveError::Lockdown(None));
+    }
+    let remaining = ctx
+        .registry
+        .services
+        .room
+        .lockdown_remaining(c.realm_id, c.room_id)
+        .await
+        .map_err(|e| MoveError::Internal(format!("failed to get lockdown: {}", e)))?;
+    if remaining.is_some() {
+        return Err(MoveError::Lockdown(remaining));
+    }
     if exit.is_locked() {
-        return Err(MoveError::ExitLocked);
+        return Err(MoveError::ExitLocked(exit.lock.clone()));
     }
 
     // Exit the room
@@ -94,13 +123,21 @@ async fn try_move_player(ctx: Arc<CmdCtx>, dir: Direction) -> Result<(), MoveErr
         .enter_room(ctx.clone(), &cursor)
         .await
         .map_err(|e| MoveError::Internal(format!("failed to enter room: {e}")))?;
+
+    let name = &c.account.username;
+    presence::announce_departure(&ctx.registry, &c, name, Some(&dir)).await;
+    presence::announce_arrival(&ctx.registry, &cursor, name, Some(&dir)).await;
+    presence::show_occupants(&ctx)
+        .await
+        .map_err(|e| MoveError::Internal(format!("failed to show occupants: {e}")))?;
     Ok(())
 }
 
 #[derive(Debug)]
 enum MoveError {
     NoSuchExit,
-    ExitLocked,
-    Blocked(String),  // e.g. "The blast door won't budge."
-    Internal(String), // db errors, logic errors, etc.
+    ExitLocked(Option<Lock>), // what opens it, for the feedback
+    Lockdown(Option<u64>),    // seconds left of a timed lockdown
+    Blocked(String),          // e.g. "The blast door won't budge."
+    Internal(String),         // db errors, logic errors, etc.
 }
@@ -0,0 +1,26 @@
+//! hint
+
+use crate::commands::{CommandContext, CommandResult};
+use crate::services::HintOutcome;
+
+pub async fn hint(cmd: CommandContext) -> CommandResult {
+    let CommandContext { ctx, .. } = cmd;
+    let cursor = ctx.cursor()?;
+
+    match ctx.registry.services.room.hint_request(&cursor).await? {
+        HintOutcome::Hint(text) => {
+            ctx.output.line(text).await;
+            if let Some(cue) = &cursor.room.blueprint.sounds.hint {
+                ctx.output.sound(cue).await;
+            }
+        }
+        HintOutcome::CoolingDown(secs) => {
+            ctx.output
+                .line(format!("Think it over a little longer; ask again in {} seconds.", secs))
+                .await
+        }
+        HintOutcome::NoHints => ctx.output.line("There are no more hints for this room.").await,
+    }
+
+    Ok(())
+}
@@ -1,28 +1,41 @@
-use crate::commands::{CmdCtx, CommandResult};
-use crate::input::parser::Intent;
-use std::sync::Arc;
+//! inventory              list what you carry, with the contents of your containers
+//! inventory <filter>     only the items called <filter>, and the containers holding them
 
-pub async fn inventory(ctx: Arc<CmdCtx>, _intent: Intent) -> CommandResult {
+use crate::commands::{CommandContext, CommandResult};
+use crate::renderer::inventory::{filter_tree, inventory_tree, render_inventory};
+
+pub async fn inventory(cmd: CommandContext) -> CommandResult {
+    let CommandContext { ctx, intent, .. } = cmd;
     let realm_id = ctx.realm_id()?;
     let account_id = ctx.account_id()?;
+    let filter = intent.args.iter().skip(1).cloned().collect::<Vec<_>>().join(" ");
 
     let items = ctx
         .registry
         .services
         .inventory
-        .get_player_inventory(realm_id, account_id)
+        .get_carried_items(realm_id, account_id)
         .await?;
-    if items.is_empty() {
+
+    let mut tree = inventory_tree(&items, account_id);
+    if tree.is_empty() {
         ctx.output.line("Your inventory is empty.").await;
         return Ok(());
     }
+    if !filter.is_empty() {
+        tree = filter_tree(tree, &filter);
+        if tree.is_empty() {
+            ctx.output
+                .line(format!("You are not carrying anything called '{}'.", filter))
+                .await;
+            return Ok(());
+        }
+    }
 
-    let headers = vec!["Quantity".to_string(), "Item".to_string(), "Description".to_string()];
-    let rows: Vec<Vec<String>> = items
-        .iter()
-        .map(|item| vec![item.quantity.to_string(), item.name.clone(), item.short.clone()])
-        .collect();
-    ctx.output.table(headers, rows).await;
+    ctx.output.line("You are carrying:").await;
+    for line in render_inventory(&tree) {
+        ctx.output.line(line).await;
+    }
 
     Ok(())
 }
@@ -1,11 +1,12 @@
-use crate::commands::{CmdCtx, CommandError, CommandResult};
+use crate::commands::{CmdCtx, CommandContext, CommandError, CommandResult};
 use crate::error::{AppResult, DomainError, LoginError};
-use crate::input::parser::Intent;
 use crate::models::account::Account;
+use crate::models::login::LoginThrottle;
 use crate::models::realm::Realm;
 use crate::models::room::RoomView;
 use crate::models::types::{RealmId, RoomId};
 use crate::net::InputMode;
+use crate::services::{LoginSuccess, TutorialService};
 use crate::state::interactive::InteractiveState;
 use std::sync::Arc;
 
@@ -17,7 +18,7 @@ const MOTD: &str = r#"
 ** ==============  PORT4K INCOMING MESSAGE =================
 **
 **   Welcome back, {c:yellow}{v:account.name}{c}!  (last login: {v:last_login:Never logged in before})
-**   Server time: {c:white}{v:wall_time}{c}
+**   Server time: {c:white}{v:wall_time}{c}   Station time: {c:white}{v:world_date} {v:world_time}{c}
 **
 **   Account:
 **      HP    : {c:green:bold}{v:account.health:0}/100{c}
@@ -40,7 +41,8 @@ const MOTD: &str = r#"
 
 "#;
 
-pub async fn login(ctx: Arc<CmdCtx>, intent: Intent) -> CommandResult {
+pub async fn login(cmd: CommandContext) -> CommandResult {
+    let CommandContext { ctx, intent, .. } = cmd;
     if ctx.is_logged_in() {
         ctx.output
             .system("You are already logged in. Logout before logging in again.")
@@ -118,8 +120,19 @@ pub async fn continue_with_password(ctx: Arc<CmdCtx>, username: String, raw: &st
 
 async fn do_login(ctx: Arc<CmdCtx>, username: &str, password: &str) -> CommandResult {
     // Step 2: Attempt to login
-    let account = match ctx.registry.services.account.login(username, password).await {
-        Ok(account) => account,
+    let throttle = LoginThrottle::from_config(&ctx.registry.config().accounts);
+    let peer = ctx.sess.read().peer();
+    let login = ctx
+        .registry
+        .services
+        .account
+        .login(username, password, peer, &throttle)
+        .await;
+    let LoginSuccess {
+        account,
+        failed_attempts,
+    } = match login {
+        Ok(success) => success,
         Err(err) => {
             match err {
                 LoginError::UserNotFound => {
@@ -139,7 +152,15 @@ async fn do_login(ctx: Arc<CmdCtx>, username: &str, password: &str) -> CommandRe
                 }
                 LoginError::TooManyAttempts => {
                     ctx.output
-                        .system("This account has been tried too many times. Please contact admin for support")
+                        .system("Too many failed logins from your address. Try again later.")
+                        .await;
+                }
+                LoginError::LockedUntil(until) => {
+                    ctx.output
+                        .system(format!(
+                            "Too many failed logins. This account is locked until {}.",
+                            until.format("%H:%M UTC")
+                        ))
                         .await;
                 }
                 LoginError::InternalError(e) => {
@@ -152,23 +173,35 @@ async fn do_login(ctx: Arc<CmdCtx>, username: &str, password: &str) -> CommandRe
         }
     };
 
-    // Step 3: find realm and room to spawn into
-    let realm_id = resolve_realm_id(&ctx, &account).await.map_err(|e| {
-        CommandError::Custom(e.to_string())
-        // CommandError::Custom("Failed to resolve starting realm.".to_string())
-    })?;
-    let realm = load_realm(&ctx, realm_id)
-        .await
-        .map_err(|_| CommandError::Custom("Failed to load starting realm.".to_string()))?;
-    let room_id = resolve_room_id(&ctx, &account, realm.id)
-        .await
-        .map_err(|_| CommandError::Custom("Failed to resolve starting room.".to_string()))?;
+    // Step 3: find realm and room to spawn into; new players start in their tutorial
+    let tutorial = tutorial_start(&ctx, &account).await;
+    let in_tutorial = tutorial.is_some();
+    let (realm, room_id) = match tutorial {
+        Some(start) => start,
+        None => {
+            let realm_id = resolve_realm_id(&ctx, &account).await.map_err(|e| {
+                CommandError::Custom(e.to_string())
+                // CommandError::Custom("Failed to resolve starting realm.".to_string())
+            })?;
+            let realm = load_realm(&ctx, realm_id)
+                .await
+                .map_err(|_| CommandError::Custom("Failed to load starting realm.".to_string()))?;
+            let room_id = resolve_room_id(&ctx, &account, realm.id)
+                .await
+                .map_err(|_| CommandError::Custom("Failed to resolve starting room.".to_string()))?;
+            (realm, room_id)
+        }
+    };
     let room = load_room(&ctx, &account, realm.id, room_id)
         .await
         .map_err(|_| CommandError::Custom("Failed to load starting room.".to_string()))?;
 
     // Step 4: Log into the session at the realm/room
+    ctx.registry.services.realm.record_start(&realm, account.id).await?;
     ctx.sess.write().login(account, realm, room);
+    ctx.registry
+        .connect(ctx.account()?, ctx.output.clone(), ctx.sess.clone())
+        .await;
 
     ctx.output
         .system("You are logged in. Welcome to port4k!".to_string())
@@ -176,9 +209,53 @@ async fn do_login(ctx: Arc<CmdCtx>, username: &str, password: &str) -> CommandRe
 
     ctx.output.line("You have successfully logged in.").await;
 
+    if failed_attempts > 0 {
+        ctx.output
+            .system(format!(
+                "There were {} failed login attempts on your account since your last login.",
+                failed_attempts
+            ))
+            .await;
+    }
+
+    if in_tutorial {
+        ctx.output
+            .system("You start out in the tutorial. Finish it to unlock the other realms.")
+            .await;
+    }
+
+    if let Some(at) = ctx.account()?.delete_after {
+        ctx.output
+            .system(format!(
+                "Your account is scheduled for deletion on {}. Type 'delete account cancel' to keep it.",
+                at.format("%Y-%m-%d %H:%M UTC")
+            ))
+            .await;
+    }
+
+    // Moderation decisions on our blueprints made while we were away
+    let decided = ctx
+        .registry
+        .services
+        .submission
+        .take_undelivered(ctx.account()?.id)
+        .await?;
+    for submission in decided {
+        ctx.output.system(submission.decision_message()).await;
+    }
+
     // Step 5: Show MOTD if needed
     if ctx.account()?.show_motd {
-        ctx.output.system(MOTD).await;
+        // A MOTD file overrides the built-in message; fall back when it cannot be read
+        let motd = ctx
+            .registry
+            .config()
+            .content
+            .motd_path
+            .as_ref()
+            .and_then(|p| std::fs::read_to_string(p).ok())
+            .unwrap_or_else(|| MOTD.to_string());
+        ctx.output.system(motd).await;
     }
 
     // Step 6: "enter" the room
@@ -191,6 +268,23 @@ async fn do_login(ctx: Arc<CmdCtx>, username: &str, password: &str) -> CommandRe
     Ok(())
 }
 
+/// Where an account that has not finished the tutorial starts. None when there is no tutorial
+/// to play; a broken starter blueprint must not keep players out, so it only gets logged.
+async fn tutorial_start(ctx: &Arc<CmdCtx>, account: &Account) -> Option<(Realm, RoomId)> {
+    if TutorialService::is_completed(account) {
+        return None;
+    }
+    let starter_bp = ctx.registry.config().content.starter_blueprint.clone()?;
+
+    match ctx.registry.services.tutorial.enroll(account, &starter_bp).await {
+        Ok(start) => Some(start),
+        Err(e) => {
+            tracing::warn!(error = %e, blueprint = %starter_bp, "could not start the tutorial");
+            None
+        }
+    }
+}
+
 async fn resolve_realm_id(ctx: &Arc<CmdCtx>, account: &Account) -> AppResult<RealmId> {
     if let Some(rid) = account.current_realm_id {
         return Ok(rid);
@@ -1,13 +1,15 @@
-use crate::commands::{CmdCtx, CommandResult};
-use crate::input::parser::Intent;
-use std::sync::Arc;
+use crate::commands::{CommandContext, CommandResult};
 
-pub async fn logout(ctx: Arc<CmdCtx>, _intent: Intent) -> CommandResult {
+pub async fn logout(cmd: CommandContext) -> CommandResult {
+    let CommandContext { ctx, .. } = cmd;
     if !ctx.is_logged_in() {
         ctx.output.system("You must be logged in to log out.").await;
         return Ok(());
     }
 
+    if let Ok(account) = ctx.account() {
+        ctx.registry.set_online(&account, false).await;
+    }
     ctx.sess.write().logout();
 
     ctx.output.system("You have been logged out.").await;
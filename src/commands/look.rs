@@ -1,12 +1,30 @@
-use crate::commands::{CmdCtx, CommandResult};
-use crate::input::parser::Intent;
-use crate::renderer::room_view::render_room_view;
+use crate::commands::{CmdCtx, CommandContext, CommandResult};
+use crate::input::parser::{NounPhrase, Preposition};
+use crate::models::room::Place;
+use crate::state::presence::show_room;
 use std::sync::Arc;
 
-pub async fn look(ctx: Arc<CmdCtx>, intent: Intent) -> CommandResult {
+pub async fn look(cmd: CommandContext) -> CommandResult {
+    let CommandContext { ctx, intent, .. } = cmd;
+    let place = match intent.preposition {
+        Some(Preposition::Behind) => Some(Place::Behind),
+        Some(Preposition::Under) => Some(Place::Under),
+        Some(Preposition::Over) => Some(Place::Over),
+        _ => None,
+    };
+    if let Some(place) = place {
+        return match intent.target {
+            Some(noun) => look_at_place(ctx, place, &noun).await,
+            None => {
+                ctx.output.system(format!("Look {} what?", place.as_str())).await;
+                Ok(())
+            }
+        };
+    }
+
     let rv = ctx.room_view()?;
     if let Some(noun) = intent.direct {
-        return if let Some(obj) = rv.object_by_noun(&noun.head) {
+        return if let Some(obj) = rv.resolve_object(&noun) {
             // 1. Check Lua script
             // if let Some(lua_src) = obj.scripts.on_examine_lua.as_ref() {
             //     let reply = run_lua_script(ctx.clone(), lua_src, obj).await?;
@@ -30,6 +48,45 @@ pub async fn look(ctx: Arc<CmdCtx>, intent: Intent) -> CommandResult {
 
     // No direct noun -> show room description
     // let vars = RenderVars::new(ctx.sess.clone(), Some(&rv));
-    ctx.output.line(render_room_view()).await;
+    show_room(&ctx, false).await?;
+    Ok(())
+}
+
+/// look behind/under/over <object>: reveals what is stashed there
+async fn look_at_place(ctx: Arc<CmdCtx>, place: Place, noun: &NounPhrase) -> CommandResult {
+    let rv = ctx.room_view()?;
+    let Some(obj) = rv.resolve_object(noun) else {
+        ctx.output
+            .system(format!("You don't see any '{}' here.", noun.head))
+            .await;
+        return Ok(());
+    };
+
+    let cursor = ctx.cursor()?;
+    let rooms = &ctx.registry.services.room;
+    let found = rooms.look_at_place(&cursor, place, &obj.key).await?;
+    if found.is_empty() {
+        ctx.output
+            .line(format!("You find nothing {} the {}.", place.as_str(), obj.name))
+            .await;
+        return Ok(());
+    }
+
+    for short in found {
+        ctx.output
+            .line(format!(
+                "You look {} the {} and find: {}",
+                place.as_str(),
+                obj.name,
+                short
+            ))
+            .await;
+    }
+    // Stashed objects are revealed in the player's state, show them from now on
+    let rv = rooms
+        .build_room_view(cursor.realm_id, cursor.account_id, cursor.room_id)
+        .await?;
+    ctx.sess.write().replace_room(rv);
+
     Ok(())
 }
@@ -1,7 +1,7 @@
-use crate::commands::{CmdCtx, CommandResult};
-use std::sync::Arc;
+use crate::commands::{CommandContext, CommandResult};
 
-pub async fn repl(ctx: Arc<CmdCtx>) -> CommandResult {
+pub async fn repl(cmd: CommandContext) -> CommandResult {
+    let CommandContext { ctx, .. } = cmd;
     ctx.output
         .system("Entering Lua REPL... Type '.quit' or '.exit' to leave")
         .await;
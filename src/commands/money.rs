@@ -0,0 +1,106 @@
+//! balance                       how much money you have in this realm
+//! pay <player> <amount>         pay another player in this realm
+//!
+//! Money is a balance per realm (see `services::CurrencyService`). Coins picked up go into it.
+
+use crate::commands::{CommandContext, CommandResult};
+use crate::services::coins_text;
+
+const PAY_USAGE: &str = "Usage: pay <player> <amount>";
+
+pub async fn balance(cmd: CommandContext) -> CommandResult {
+    let CommandContext { ctx, .. } = cmd;
+    let cursor = ctx.cursor()?;
+    let balance = ctx
+        .registry
+        .services
+        .currency
+        .balance(cursor.realm_id, cursor.account_id)
+        .await?;
+    ctx.output.line(format!("You have {}.", coins_text(balance))).await;
+    Ok(())
+}
+
+pub async fn pay(cmd: CommandContext) -> CommandResult {
+    let CommandContext { ctx, intent, .. } = cmd;
+    let Some((name, amount)) = pay_args(&intent.args[1..]) else {
+        ctx.output.system(PAY_USAGE).await;
+        return Ok(());
+    };
+
+    let cursor = ctx.cursor()?;
+    let payee = ctx.registry.connected_by_name(name).filter(|p| {
+        p.sess
+            .read()
+            .get_cursor()
+            .is_some_and(|c| c.realm_id == cursor.realm_id)
+    });
+    let Some(payee) = payee else {
+        ctx.output.line(format!("There is no {} in this realm.", name)).await;
+        return Ok(());
+    };
+    if payee.account.id == cursor.account_id {
+        ctx.output
+            .line("You move the money from one pocket to the other.")
+            .await;
+        return Ok(());
+    }
+
+    let currency = &ctx.registry.services.currency;
+    let Some(left) = currency
+        .pay(cursor.realm_id, cursor.account_id, payee.account.id, amount)
+        .await?
+    else {
+        let balance = currency.balance(cursor.realm_id, cursor.account_id).await?;
+        ctx.output.line(format!("You only have {}.", coins_text(balance))).await;
+        return Ok(());
+    };
+
+    let payer = ctx.account()?;
+    ctx.output
+        .line(format!(
+            "You pay {} {}. You have {} left.",
+            payee.account.username,
+            coins_text(amount),
+            coins_text(left)
+        ))
+        .await;
+    payee
+        .output
+        .line(format!("{} pays you {}.", payer.username, coins_text(amount)))
+        .await;
+    Ok(())
+}
+
+/// The player and the amount, in either order: "pay bob 10" or "pay 10 bob"
+fn pay_args(args: &[String]) -> Option<(&str, i64)> {
+    let [a, b] = args else {
+        return None;
+    };
+    let (name, amount) = match (a.parse::<i64>(), b.parse::<i64>()) {
+        (Err(_), Ok(n)) => (a, n),
+        (Ok(n), Err(_)) => (b, n),
+        _ => return None,
+    };
+    (amount > 0).then_some((name.as_str(), amount))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn args(s: &str) -> Vec<String> {
+        s.split_whitespace().map(String::from).collect()
+    }
+
+    #[test]
+    fn t_pay_args() {
+        assert_eq!(pay_args(&args("bob 10")), Some(("bob", 10)));
+        assert_eq!(pay_args(&args("10 bob")), Some(("bob", 10)));
+        assert_eq!(pay_args(&args("bob 0")), None);
+        assert_eq!(pay_args(&args("bob -3")), None);
+        assert_eq!(pay_args(&args("bob")), None);
+        assert_eq!(pay_args(&args("bob alice")), None);
+        assert_eq!(pay_args(&args("bob 10 coins")), None);
+    }
+}
@@ -0,0 +1,47 @@
+//! @notes <bp>        list the builder notes and TODOs left on the rooms and objects of a blueprint
+
+use crate::commands::{CommandContext, CommandResult};
+use crate::error::DomainError;
+
+pub async fn notes(cmd: CommandContext) -> CommandResult {
+    let CommandContext { ctx, intent, .. } = cmd;
+    let account = ctx.account()?;
+    let Some(bp_key) = intent.args.get(1) else {
+        ctx.output.system("Usage: @notes <bp>").await;
+        return Ok(());
+    };
+
+    let bp = match ctx
+        .registry
+        .services
+        .collaborator
+        .require_member(&account, bp_key)
+        .await
+    {
+        Ok((bp, _)) => bp,
+        Err(DomainError::NotFound(_)) => {
+            ctx.output.system(format!("[notes] no blueprint '{}'.", bp_key)).await;
+            return Ok(());
+        }
+        Err(DomainError::Validation { message, .. }) => {
+            ctx.output.system(format!("[notes] {}.", message)).await;
+            return Ok(());
+        }
+        Err(e) => return Err(e.into()),
+    };
+
+    let notes = ctx.registry.services.blueprint.builder_notes(bp.id, None).await?;
+    if notes.is_empty() {
+        ctx.output
+            .system(format!("[notes] no builder notes in '{}'.", bp.key))
+            .await;
+        return Ok(());
+    }
+
+    let mut out = format!("[notes] {} builder note(s) in '{}':", notes.len(), bp.key);
+    for note in &notes {
+        out.push_str(&format!("\n  {}", note));
+    }
+    ctx.output.system(out).await;
+    Ok(())
+}
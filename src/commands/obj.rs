@@ -0,0 +1,212 @@
+//! @obj add <obj> "<short>"                          add an object to the room you are in
+//! @obj set <obj> short|description|examine <text>   change its texts (examine without text clears it)
+//! @obj set <obj> nouns <noun>...                    replace the nouns players call it by
+//! @obj set <obj> <flag> on|off                      switch a flag (takeable, hidden, locked, ...)
+//! @obj set <obj> on_use|on_read|on_terminal|on_receive
+//!                                                   write its Lua in the line editor (empty removes it)
+//! @obj remove <obj>                                 remove it from the room
+//!
+//! Like `@room`, edits go straight into the blueprint with the same checks as an import, and show
+//! right away for everyone in the room.
+
+use crate::commands::room::{editable_blueprint, refresh_room};
+use crate::commands::{CmdCtx, CommandContext, CommandResult, editor};
+use crate::error::{AppResult, DomainError};
+use crate::lua::lint::ChunkKind;
+use crate::models::collaborator::BlueprintRight;
+use crate::models::room::BlueprintObject;
+use crate::state::interactive::EditTarget;
+use crate::util::args::words_after;
+use std::sync::Arc;
+
+const USAGE: &str = "Usage: @obj add <obj> \"<short>\" | @obj set <obj> short|description|examine <text> | \
+     @obj set <obj> nouns <noun>... | @obj set <obj> <flag> on|off | @obj set <obj> on_use|on_read|on_terminal|on_receive | \
+     @obj remove <obj>";
+
+pub async fn obj(cmd: CommandContext) -> CommandResult {
+    let CommandContext { ctx, intent, raw } = cmd;
+    if !ctx.account()?.is_builder() {
+        ctx.output
+            .system("You do not have permission to use that command.")
+            .await;
+        return Ok(());
+    }
+
+    let args: Vec<&str> = intent.args.iter().map(String::as_str).collect();
+    let result = match args.as_slice() {
+        [_, "add", key, _, ..] => add(&ctx, key, words_after(&raw, 3).trim_matches('"')).await,
+        [_, "remove", key] => remove(&ctx, key).await,
+        [_, "set", key, field @ ("short" | "description" | "examine"), ..] => {
+            set_text(&ctx, key, field, words_after(&raw, 4).trim_matches('"')).await
+        }
+        [_, "set", key, "nouns", nouns @ ..] if !nouns.is_empty() => {
+            let nouns: Vec<String> = nouns.iter().map(|n| n.to_string()).collect();
+            set_nouns(&ctx, key, &nouns).await
+        }
+        [_, "set", key, flag, on @ ("on" | "off")] => set_flag(&ctx, key, flag, *on == "on").await,
+        [_, "set", key, script] => match script_kind(script) {
+            Some(kind) => open_script(&ctx, key, kind).await,
+            None => usage(&ctx).await,
+        },
+        _ => usage(&ctx).await,
+    };
+
+    match result {
+        Err(DomainError::Validation { message, .. }) => {
+            ctx.output.system(format!("[obj] {}.", message)).await;
+            Ok(())
+        }
+        other => Ok(other?),
+    }
+}
+
+async fn usage(ctx: &CmdCtx) -> AppResult<()> {
+    ctx.output.system(USAGE).await;
+    Ok(())
+}
+
+/// Saves the script written in the line editor
+pub(super) async fn save_script(ctx: &Arc<CmdCtx>, key: &str, kind: &ChunkKind, code: &str) -> CommandResult {
+    let Some(obj) = editable_object(ctx, key, BlueprintRight::EditScripts).await? else {
+        return Ok(());
+    };
+    let room = ctx.cursor()?.room.blueprint.clone();
+    let warnings = match ctx
+        .registry
+        .services
+        .blueprint
+        .set_object_script(&room, &obj, kind, code)
+        .await
+    {
+        Ok(warnings) => warnings,
+        Err(DomainError::Validation { message, .. }) => {
+            ctx.output.system(format!("[obj] {}.", message)).await;
+            return Ok(());
+        }
+        Err(e) => return Err(e.into()),
+    };
+
+    let mut out = if code.trim().is_empty() {
+        format!("[obj] {} of '{}' removed.", kind.as_str(), key)
+    } else {
+        format!("[obj] {} of '{}' saved.", kind.as_str(), key)
+    };
+    for w in &warnings {
+        out.push_str(&format!("\n  {}", w));
+    }
+    ctx.output.system(out).await;
+    Ok(refresh_room(ctx, room.id).await?)
+}
+
+async fn add(ctx: &CmdCtx, key: &str, short: &str) -> AppResult<()> {
+    if editable_blueprint(ctx, BlueprintRight::EditRooms).await?.is_none() {
+        return Ok(());
+    }
+    let room = ctx.cursor()?.room.blueprint.clone();
+    if ctx.registry.services.blueprint.add_object(&room, key, short).await? {
+        ctx.output
+            .system(format!("[obj] '{}' added to {}.", key, room.key))
+            .await;
+        refresh_room(ctx, room.id).await?;
+    } else {
+        ctx.output
+            .system(format!("[obj] {} already has an object '{}'.", room.key, key))
+            .await;
+    }
+    Ok(())
+}
+
+async fn remove(ctx: &CmdCtx, key: &str) -> AppResult<()> {
+    let Some(obj) = editable_object(ctx, key, BlueprintRight::EditRooms).await? else {
+        return Ok(());
+    };
+    let room = ctx.cursor()?.room.blueprint.clone();
+    ctx.registry.services.blueprint.remove_object(&room, &obj).await?;
+    ctx.output
+        .system(format!("[obj] '{}' removed from {}.", key, room.key))
+        .await;
+    refresh_room(ctx, room.id).await
+}
+
+async fn set_text(ctx: &CmdCtx, key: &str, field: &str, text: &str) -> AppResult<()> {
+    let Some(obj) = editable_object(ctx, key, BlueprintRight::EditRooms).await? else {
+        return Ok(());
+    };
+    let (mut short, mut description, mut examine) =
+        (obj.short.as_str(), obj.description.as_str(), obj.examine.as_deref());
+    match field {
+        "short" => short = text,
+        "description" => description = text,
+        _ => examine = Some(text).filter(|t| !t.is_empty()),
+    }
+    ctx.registry
+        .services
+        .blueprint
+        .update_object_text(&obj, short, description, examine)
+        .await?;
+    ctx.output
+        .system(format!("[obj] {} of '{}' updated.", field, key))
+        .await;
+    refresh_room(ctx, ctx.cursor()?.room_id).await
+}
+
+async fn set_nouns(ctx: &CmdCtx, key: &str, nouns: &[String]) -> AppResult<()> {
+    let Some(obj) = editable_object(ctx, key, BlueprintRight::EditRooms).await? else {
+        return Ok(());
+    };
+    let room = ctx.cursor()?.room.blueprint.clone();
+    ctx.registry
+        .services
+        .blueprint
+        .set_object_nouns(&room, &obj, nouns)
+        .await?;
+    ctx.output
+        .system(format!("[obj] '{}' is now called: {}.", key, nouns.join(", ")))
+        .await;
+    refresh_room(ctx, room.id).await
+}
+
+async fn set_flag(ctx: &CmdCtx, key: &str, flag: &str, on: bool) -> AppResult<()> {
+    let Some(obj) = editable_object(ctx, key, BlueprintRight::EditRooms).await? else {
+        return Ok(());
+    };
+    ctx.registry.services.blueprint.set_object_flag(&obj, flag, on).await?;
+    ctx.output
+        .system(format!("[obj] '{}' is {}{}.", key, if on { "" } else { "not " }, flag))
+        .await;
+    refresh_room(ctx, ctx.cursor()?.room_id).await
+}
+
+async fn open_script(ctx: &CmdCtx, key: &str, kind: ChunkKind) -> AppResult<()> {
+    let Some(obj) = editable_object(ctx, key, BlueprintRight::EditScripts).await? else {
+        return Ok(());
+    };
+    let what = format!("{} script of '{}' (an empty one removes it)", kind.as_str(), obj.name);
+    editor::open(ctx, EditTarget::ObjectScript { object: obj.name, kind }, &what).await
+}
+
+/// The object `key` of the current room, when the builder behind the session has `right` on its
+/// blueprint
+async fn editable_object(ctx: &CmdCtx, key: &str, right: BlueprintRight) -> AppResult<Option<BlueprintObject>> {
+    if editable_blueprint(ctx, right).await?.is_none() {
+        return Ok(None);
+    }
+    let room_id = ctx.cursor()?.room_id;
+    let obj = ctx.registry.services.blueprint.room_object(room_id, key).await?;
+    if obj.is_none() {
+        ctx.output
+            .system(format!("[obj] there is no object '{}' here.", key))
+            .await;
+    }
+    Ok(obj)
+}
+
+fn script_kind(s: &str) -> Option<ChunkKind> {
+    match s {
+        "on_use" => Some(ChunkKind::OnUse),
+        "on_read" => Some(ChunkKind::OnRead),
+        "on_terminal" => Some(ChunkKind::OnTerminal),
+        "on_receive" => Some(ChunkKind::OnReceive),
+        _ => None,
+    }
+}
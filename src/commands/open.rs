@@ -1,11 +1,11 @@
-use crate::commands::{CmdCtx, CommandResult};
+use crate::commands::{CommandContext, CommandResult};
 use crate::error::DomainError;
-use crate::input::parser::Intent;
 use crate::lua::{LuaJob, LuaResult};
-use std::sync::Arc;
+use crate::models::room::locked_message;
 use tokio::sync::oneshot;
 
-pub async fn open(ctx: Arc<CmdCtx>, intent: Intent) -> CommandResult {
+pub async fn open(cmd: CommandContext) -> CommandResult {
+    let CommandContext { ctx, intent, .. } = cmd;
     let rv = ctx.room_view()?;
 
     let Some(noun) = intent.direct.as_ref() else {
@@ -14,11 +14,25 @@ pub async fn open(ctx: Arc<CmdCtx>, intent: Intent) -> CommandResult {
     };
 
     let mut handled = false;
+    let obj = rv.resolve_object(noun);
 
     // Check if we are opening an object
-    if let Some(obj) = rv.object_by_noun(&noun.head) {
+    if let Some(obj) = obj {
         // Do we have a script attached? run that first
         if obj.on_use.as_ref().is_some() {
+            // A cooldown or use limit stops the script from running at all
+            let cursor = ctx.cursor()?;
+            if let Some(denied) = ctx
+                .registry
+                .services
+                .room
+                .claim_object_use(cursor.realm_id, cursor.room_id, ctx.account_id()?, obj)
+                .await?
+            {
+                ctx.output.line(denied.message(&obj.name)).await;
+                return Ok(());
+            }
+
             let (tx, rx) = oneshot::channel();
 
             let output_handle = ctx.output.clone();
@@ -32,8 +46,7 @@ pub async fn open(ctx: Arc<CmdCtx>, intent: Intent) -> CommandResult {
                     obj: Box::new(obj.clone()),
                     reply: tx,
                 })
-                .await
-                .map_err(|_| DomainError::InternalError("Failed to send Lua job".into()))?;
+                .await?;
 
             match rx
                 .await
@@ -56,7 +69,13 @@ pub async fn open(ctx: Arc<CmdCtx>, intent: Intent) -> CommandResult {
 
     if !handled {
         // Nothing has handled the open command
-        ctx.output.line("You try to open it, but nothing happens.").await;
+        match obj.filter(|o| o.flags.locked) {
+            Some(obj) => {
+                let locked = format!("The {} is locked.", obj.name);
+                ctx.output.line(locked_message(&locked, obj.lock.as_ref())).await;
+            }
+            None => ctx.output.line("You try to open it, but nothing happens.").await,
+        }
     }
 
     Ok(())
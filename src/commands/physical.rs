@@ -0,0 +1,216 @@
+//! push <object>                    push a movable object away
+//! pull <object>                    pull a movable object back
+//! turn <object>                    turn a turnable object one way, then back
+//! throw <item> [at <object>]       throw a carried item; it ends up on the floor
+//!
+//! Lever banks handle push, pull and throw themselves. Otherwise the object's on_use script gets
+//! the intent first and the room's on_command script after it; returning true from either skips
+//! the default handling below. By default push and pull move objects flagged `movable` between
+//! two positions and turn flips objects flagged `turnable`, both kept per player in the object
+//! state (`pushed` and `turned`).
+
+use crate::commands::resolve::{Found, Scope, resolve};
+use crate::commands::{CmdCtx, CommandContext, CommandError, CommandResult, fallback, widget};
+use crate::input::parser::Intent;
+use crate::lua::{LuaJob, LuaResult, ScriptHook};
+use crate::models::inventory::ItemLocation;
+use crate::models::room::ResolvedObject;
+use serde_json::Value;
+use std::sync::Arc;
+use tokio::sync::oneshot;
+use tokio::time::timeout;
+
+const PUSHED_KEY: &str = "pushed";
+const TURNED_KEY: &str = "turned";
+
+pub async fn push(cmd: CommandContext) -> CommandResult {
+    let CommandContext { ctx, intent, .. } = cmd;
+    let Some(obj) = find_object(&ctx, &intent, "Push what?").await? else {
+        return Ok(());
+    };
+    if !obj.flags.movable {
+        ctx.output
+            .line(format!("You push the {}, but it doesn't move.", obj.name))
+            .await;
+        return Ok(());
+    }
+    if is_set(&obj, PUSHED_KEY) {
+        ctx.output.line(format!("The {} won't go any further.", obj.name)).await;
+        return Ok(());
+    }
+
+    widget::set_state(&ctx, &obj, PUSHED_KEY, Value::Bool(true)).await?;
+    ctx.output.line(format!("You push the {}.", obj.name)).await;
+    widget::refresh_view(&ctx).await
+}
+
+pub async fn pull(cmd: CommandContext) -> CommandResult {
+    let CommandContext { ctx, intent, .. } = cmd;
+    let Some(obj) = find_object(&ctx, &intent, "Pull what?").await? else {
+        return Ok(());
+    };
+    if !obj.flags.movable {
+        ctx.output
+            .line(format!("You pull the {}, but it doesn't move.", obj.name))
+            .await;
+        return Ok(());
+    }
+    if !is_set(&obj, PUSHED_KEY) {
+        ctx.output
+            .line(format!("The {} won't come any closer.", obj.name))
+            .await;
+        return Ok(());
+    }
+
+    widget::set_state(&ctx, &obj, PUSHED_KEY, Value::Bool(false)).await?;
+    ctx.output.line(format!("You pull the {} back.", obj.name)).await;
+    widget::refresh_view(&ctx).await
+}
+
+pub async fn turn(cmd: CommandContext) -> CommandResult {
+    let CommandContext { ctx, intent, .. } = cmd;
+    let Some(obj) = find_object(&ctx, &intent, "Turn what?").await? else {
+        return Ok(());
+    };
+    if !obj.flags.turnable {
+        ctx.output.line(format!("The {} won't turn.", obj.name)).await;
+        return Ok(());
+    }
+
+    let turned = !is_set(&obj, TURNED_KEY);
+    widget::set_state(&ctx, &obj, TURNED_KEY, Value::Bool(turned)).await?;
+    let way = if turned { "" } else { " back" };
+    ctx.output.line(format!("You turn the {}{}.", obj.name, way)).await;
+    widget::refresh_view(&ctx).await
+}
+
+pub async fn throw(cmd: CommandContext) -> CommandResult {
+    let CommandContext { ctx, intent, .. } = cmd;
+    if widget::handle(ctx.clone(), &intent).await? {
+        return Ok(());
+    }
+    let Some(np) = intent.direct.as_ref() else {
+        ctx.output.system("Throw what?").await;
+        return Ok(());
+    };
+
+    let found = resolve(&ctx, np, Scope::for_verb(&intent.verb)).await?;
+    let Some(Found::Carried(item)) = found else {
+        ctx.output.line(format!("You are not carrying any {}.", np.head)).await;
+        return Ok(());
+    };
+
+    let target = match intent.target.as_ref() {
+        Some(to) => match resolve(&ctx, to, &[Scope::Objects, Scope::Npcs]).await? {
+            Some(Found::Object(obj) | Found::Npc(obj)) => Some(obj),
+            _ => {
+                ctx.output.line(format!("There is no {} here.", to.head)).await;
+                return Ok(());
+            }
+        },
+        None => None,
+    };
+    if overridden(&ctx, &intent, target.as_ref()).await? {
+        return Ok(());
+    }
+
+    let cursor = ctx.cursor()?;
+    ctx.registry
+        .services
+        .inventory
+        .move_quantity(&item, Some(1), ItemLocation::Room(cursor.room_id))
+        .await?;
+    let line = match target {
+        Some(obj) => format!(
+            "You throw {} at the {}. It bounces off and drops to the floor.",
+            item.short, obj.name
+        ),
+        None => format!("You throw {}. It lands on the floor.", item.short),
+    };
+    ctx.output.line(line).await;
+    Ok(())
+}
+
+/// The object the push, pull or turn is about, when the default handling is up. None when the
+/// command was handled (or answered) already.
+async fn find_object(ctx: &Arc<CmdCtx>, intent: &Intent, usage: &str) -> Result<Option<ResolvedObject>, CommandError> {
+    if widget::handle(ctx.clone(), intent).await? {
+        return Ok(None);
+    }
+    let Some(np) = intent.direct.as_ref() else {
+        ctx.output.system(usage).await;
+        return Ok(None);
+    };
+
+    let rv = ctx.room_view()?;
+    let obj = rv.resolve_object(np);
+    if overridden(ctx, intent, obj).await? {
+        return Ok(None);
+    }
+    if obj.is_none() {
+        ctx.output
+            .system(format!("You don't see any '{}' here.", np.head))
+            .await;
+    }
+    Ok(obj.cloned())
+}
+
+/// Offers the command to the object's on_use script, then to the room's on_command script.
+/// Returns true when one of them handled it.
+async fn overridden(ctx: &Arc<CmdCtx>, intent: &Intent, obj: Option<&ResolvedObject>) -> Result<bool, CommandError> {
+    if let Some(obj) = obj.filter(|o| o.on_use.is_some())
+        && object_script(ctx, intent, obj).await?
+    {
+        return Ok(true);
+    }
+    let rv = ctx.room_view()?;
+    Ok(rv.scripts.get(&ScriptHook::OnCommand).is_some() && fallback::room_command(ctx.clone(), intent.clone()).await?)
+}
+
+/// Runs the object's on_use script for the intent. Returns true when it handled the command, or
+/// when the use limits refused it (which is reported).
+async fn object_script(ctx: &Arc<CmdCtx>, intent: &Intent, obj: &ResolvedObject) -> Result<bool, CommandError> {
+    let cursor = ctx.cursor()?;
+    if let Some(denied) = ctx
+        .registry
+        .services
+        .room
+        .claim_object_use(cursor.realm_id, cursor.room_id, ctx.account_id()?, obj)
+        .await?
+    {
+        ctx.output.line(denied.message(&obj.name)).await;
+        return Ok(true);
+    }
+
+    let (tx, rx) = oneshot::channel();
+    ctx.lua_tx
+        .send(LuaJob::OnObject {
+            output_handle: ctx.output.clone(),
+            account_id: ctx.account_id()?,
+            cursor: Box::new(cursor),
+            intent: Box::new(intent.clone()),
+            obj: Box::new(obj.clone()),
+            reply: tx,
+        })
+        .await?;
+
+    match timeout(ctx.registry.config().lua.command_timeout(), rx).await {
+        Ok(Ok(LuaResult::Success(v))) => Ok(v.as_boolean().unwrap_or(false)),
+        Ok(Ok(LuaResult::Failed(msg))) => {
+            let s = format!("{{c:yellow:bright_red}}Lua script failure: {msg}{{c}}");
+            ctx.output.system(s).await;
+            Ok(true)
+        }
+        Ok(Err(_)) => Ok(false),
+        Err(_elapsed) => {
+            let verb = intent.verb.as_str();
+            let s = format!("{{c:yellow:bright_red}}Nothing happens when you {verb} it (script timed out){{c}}");
+            ctx.output.system(s).await;
+            Ok(true)
+        }
+    }
+}
+
+fn is_set(obj: &ResolvedObject, key: &str) -> bool {
+    obj.kv.get(key).and_then(Value::as_bool).unwrap_or(false)
+}
@@ -0,0 +1,381 @@
+//! The stages a typed line goes through before a command handler runs.
+//!
+//! shell → interactive → rate_limit → talk → alias → parse → permission → room_script → all →
+//! builtin → fallback
+//!
+//! Each stage sees the line (and the intent, once parsed) and either passes it on or handles it,
+//! which ends the run. Subsystems hook in with `register_before` / `register_after` on the
+//! registry's pipeline instead of growing `run_command`; a stage before `parse` may rewrite the
+//! line, as `alias` does.
+
+use crate::commands::{
+    CmdCtx, CommandContext, CommandError, CommandResult, PermissionError, all, dispatch, fallback, permission_check,
+    process_interactive_state, talk, widget,
+};
+use crate::input::parser::{Intent, Verb, parse_command};
+use crate::input::shell::{handle_shell_cmd, parse_shell_cmd};
+use crate::state::interactive::InteractiveState;
+use async_trait::async_trait;
+use parking_lot::RwLock;
+use std::sync::Arc;
+
+/// What a stage did with the command
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Flow {
+    /// Pass the command to the next stage
+    Next,
+    /// The command is dealt with; later stages do not see it
+    Handled,
+}
+
+/// A command on its way through the pipeline
+#[derive(Debug, Clone)]
+pub struct CommandInput {
+    /// The line as typed, or as rewritten by an earlier stage
+    pub raw: String,
+    /// Set by the `parse` stage
+    pub intent: Option<Intent>,
+    /// Forced on the player by a script rather than typed
+    pub forced: bool,
+}
+
+impl CommandInput {
+    /// The parsed intent, parsing the line now when no stage did yet
+    pub fn intent(&mut self) -> &Intent {
+        self.intent.get_or_insert_with(|| parse_command(&self.raw))
+    }
+}
+
+#[async_trait]
+pub trait Middleware: Send + Sync {
+    async fn handle(&self, ctx: &Arc<CmdCtx>, input: &mut CommandInput) -> Result<Flow, CommandError>;
+}
+
+struct Stage {
+    name: &'static str,
+    middleware: Arc<dyn Middleware>,
+}
+
+/// The stages, in order. Registered stages are kept until the server stops.
+pub struct Pipeline {
+    stages: RwLock<Vec<Stage>>,
+}
+
+impl Default for Pipeline {
+    fn default() -> Self {
+        let builtin: [(&'static str, Arc<dyn Middleware>); 11] = [
+            ("shell", Arc::new(Shell)),
+            ("interactive", Arc::new(Interactive)),
+            ("rate_limit", Arc::new(RateLimit)),
+            ("talk", Arc::new(Talk)),
+            ("alias", Arc::new(Alias)),
+            ("parse", Arc::new(Parse)),
+            ("permission", Arc::new(Permission)),
+            ("room_script", Arc::new(RoomScript)),
+            ("all", Arc::new(All)),
+            ("builtin", Arc::new(Builtin)),
+            ("fallback", Arc::new(Fallback)),
+        ];
+        Self {
+            stages: RwLock::new(
+                builtin
+                    .into_iter()
+                    .map(|(name, middleware)| Stage { name, middleware })
+                    .collect(),
+            ),
+        }
+    }
+}
+
+impl Pipeline {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Names of the stages, in the order they run
+    pub fn stages(&self) -> Vec<&'static str> {
+        self.stages.read().iter().map(|s| s.name).collect()
+    }
+
+    /// Adds a stage right before the stage `before`. False when there is no such stage or the name
+    /// is taken.
+    pub fn register_before(&self, before: &str, name: &'static str, middleware: Arc<dyn Middleware>) -> bool {
+        self.insert(before, 0, name, middleware)
+    }
+
+    /// Adds a stage right after the stage `after`. False when there is no such stage or the name is
+    /// taken.
+    pub fn register_after(&self, after: &str, name: &'static str, middleware: Arc<dyn Middleware>) -> bool {
+        self.insert(after, 1, name, middleware)
+    }
+
+    fn insert(&self, anchor: &str, offset: usize, name: &'static str, middleware: Arc<dyn Middleware>) -> bool {
+        let mut stages = self.stages.write();
+        if stages.iter().any(|s| s.name == name) {
+            return false;
+        }
+        let Some(pos) = stages.iter().position(|s| s.name == anchor) else {
+            return false;
+        };
+        stages.insert(pos + offset, Stage { name, middleware });
+        true
+    }
+
+    /// Runs the line through the stages until one handles it
+    pub async fn run(&self, ctx: &Arc<CmdCtx>, raw: &str) -> CommandResult {
+        self.run_input(ctx, raw, false).await
+    }
+
+    /// Runs a command a script forces on the player
+    pub async fn run_forced(&self, ctx: &Arc<CmdCtx>, raw: &str) -> CommandResult {
+        self.run_input(ctx, raw, true).await
+    }
+
+    async fn run_input(&self, ctx: &Arc<CmdCtx>, raw: &str, forced: bool) -> CommandResult {
+        let stages: Vec<Arc<dyn Middleware>> = self.stages.read().iter().map(|s| s.middleware.clone()).collect();
+        let mut input = CommandInput {
+            raw: raw.to_string(),
+            intent: None,
+            forced,
+        };
+        for middleware in stages {
+            if middleware.handle(ctx, &mut input).await? == Flow::Handled {
+                break;
+            }
+        }
+        Ok(())
+    }
+}
+
+/// Backslash commands of the shell
+struct Shell;
+
+#[async_trait]
+impl Middleware for Shell {
+    async fn handle(&self, ctx: &Arc<CmdCtx>, input: &mut CommandInput) -> Result<Flow, CommandError> {
+        let Some(shell) = parse_shell_cmd(&input.raw) else {
+            return Ok(Flow::Next);
+        };
+        handle_shell_cmd(shell, ctx.clone()).await?;
+        Ok(Flow::Handled)
+    }
+}
+
+/// Answers to a wizard or prompt (registration, editors)
+struct Interactive;
+
+#[async_trait]
+impl Middleware for Interactive {
+    async fn handle(&self, ctx: &Arc<CmdCtx>, input: &mut CommandInput) -> Result<Flow, CommandError> {
+        match ctx.get_interactive() {
+            InteractiveState::None => Ok(Flow::Next),
+            st => {
+                process_interactive_state(st, &input.raw, ctx.clone()).await?;
+                Ok(Flow::Handled)
+            }
+        }
+    }
+}
+
+/// Turns away typed commands past `rate_limit.commands_per_second` (plus the `burst`), per
+/// connection. Commands forced by scripts are not counted, and neither are sessions without a
+/// client, like scenario runs.
+struct RateLimit;
+
+#[async_trait]
+impl Middleware for RateLimit {
+    async fn handle(&self, ctx: &Arc<CmdCtx>, input: &mut CommandInput) -> Result<Flow, CommandError> {
+        if input.forced {
+            return Ok(Flow::Next);
+        }
+        let limits = ctx.registry.config().rate_limit.clone();
+        let allowed = {
+            let mut sess = ctx.sess.write();
+            sess.peer().is_none() || sess.take_command(limits.commands_per_second, limits.burst)
+        };
+        if allowed {
+            return Ok(Flow::Next);
+        }
+        ctx.output.system("You are typing too fast; slow down a little.").await;
+        Ok(Flow::Handled)
+    }
+}
+
+/// Numbered answers (and goodbyes) while talking to an NPC
+struct Talk;
+
+#[async_trait]
+impl Middleware for Talk {
+    async fn handle(&self, ctx: &Arc<CmdCtx>, input: &mut CommandInput) -> Result<Flow, CommandError> {
+        let handled = if let Some(choice) = talk::answer_choice(&input.raw) {
+            talk::answer(ctx.clone(), choice).await?
+        } else {
+            talk::is_goodbye(&input.raw) && talk::goodbye(ctx.clone()).await?
+        };
+        Ok(if handled { Flow::Handled } else { Flow::Next })
+    }
+}
+
+/// The player's own shorthands (`alias`), expanded once
+struct Alias;
+
+#[async_trait]
+impl Middleware for Alias {
+    async fn handle(&self, ctx: &Arc<CmdCtx>, input: &mut CommandInput) -> Result<Flow, CommandError> {
+        if let Ok(account) = ctx.account()
+            && let Some(line) = account.settings.expand_alias(&input.raw)
+        {
+            input.raw = line;
+        }
+        Ok(Flow::Next)
+    }
+}
+
+struct Parse;
+
+#[async_trait]
+impl Middleware for Parse {
+    async fn handle(&self, _ctx: &Arc<CmdCtx>, input: &mut CommandInput) -> Result<Flow, CommandError> {
+        input.intent = Some(parse_command(&input.raw));
+        Ok(Flow::Next)
+    }
+}
+
+struct Permission;
+
+#[async_trait]
+impl Middleware for Permission {
+    async fn handle(&self, ctx: &Arc<CmdCtx>, input: &mut CommandInput) -> Result<Flow, CommandError> {
+        match permission_check(input.intent(), ctx.clone()) {
+            Ok(_) => Ok(Flow::Next),
+            Err(PermissionError::NotLoggedIn) => {
+                ctx.output.system("You must be logged in to use that command.").await;
+                Ok(Flow::Handled)
+            }
+            Err(PermissionError::PermissionDenied) => {
+                ctx.output
+                    .system("You do not have permission to use that command.")
+                    .await;
+                Ok(Flow::Handled)
+            }
+        }
+    }
+}
+
+/// The room gets the command before the engine: verbs the game does not know go to its widgets
+/// and on_command script, built-in verbs only when the room lists them in `script_first`. When
+/// the room does not take the command ("pass"), the later stages handle it.
+struct RoomScript;
+
+#[async_trait]
+impl Middleware for RoomScript {
+    async fn handle(&self, ctx: &Arc<CmdCtx>, input: &mut CommandInput) -> Result<Flow, CommandError> {
+        if !ctx.has_cursor() {
+            return Ok(Flow::Next);
+        }
+        let intent = input.intent();
+        let offer = matches!(intent.verb, Verb::Custom(_)) || {
+            let rv = ctx.room_view()?;
+            rv.blueprint.script_first.covers(intent, &rv)
+        };
+        if !offer {
+            return Ok(Flow::Next);
+        }
+        if matches!(intent.verb, Verb::Custom(_)) && widget::handle(ctx.clone(), intent).await? {
+            return Ok(Flow::Handled);
+        }
+        if fallback::room_command(ctx.clone(), intent.clone()).await? {
+            return Ok(Flow::Handled);
+        }
+        Ok(Flow::Next)
+    }
+}
+
+/// `take all` and friends, one command per item
+struct All;
+
+#[async_trait]
+impl Middleware for All {
+    async fn handle(&self, ctx: &Arc<CmdCtx>, input: &mut CommandInput) -> Result<Flow, CommandError> {
+        let intent = input.intent();
+        if !all::expands(intent) {
+            return Ok(Flow::Next);
+        }
+        all::all(ctx.clone(), intent.clone()).await?;
+        Ok(Flow::Handled)
+    }
+}
+
+/// The command handlers of the verbs the game knows
+struct Builtin;
+
+#[async_trait]
+impl Middleware for Builtin {
+    async fn handle(&self, ctx: &Arc<CmdCtx>, input: &mut CommandInput) -> Result<Flow, CommandError> {
+        let intent = input.intent().clone();
+        if matches!(intent.verb, Verb::Custom(_)) {
+            return Ok(Flow::Next);
+        }
+        dispatch(CommandContext {
+            ctx: ctx.clone(),
+            intent,
+            raw: input.raw.clone(),
+        })
+        .await?;
+        Ok(Flow::Handled)
+    }
+}
+
+/// A verb the game does not know and the room did not take
+struct Fallback;
+
+#[async_trait]
+impl Middleware for Fallback {
+    async fn handle(&self, ctx: &Arc<CmdCtx>, _input: &mut CommandInput) -> Result<Flow, CommandError> {
+        fallback::unknown(ctx).await?;
+        Ok(Flow::Handled)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    struct Nothing;
+
+    #[async_trait]
+    impl Middleware for Nothing {
+        async fn handle(&self, _ctx: &Arc<CmdCtx>, _input: &mut CommandInput) -> Result<Flow, CommandError> {
+            Ok(Flow::Next)
+        }
+    }
+
+    #[test]
+    fn t_register_stages() {
+        let pipeline = Pipeline::new();
+        assert!(pipeline.register_before("builtin", "emotes", Arc::new(Nothing)));
+        assert!(pipeline.register_after("shell", "log", Arc::new(Nothing)));
+        assert_eq!(
+            pipeline.stages(),
+            vec![
+                "shell",
+                "log",
+                "interactive",
+                "rate_limit",
+                "talk",
+                "alias",
+                "parse",
+                "permission",
+                "room_script",
+                "all",
+                "emotes",
+                "builtin",
+                "fallback"
+            ]
+        );
+
+        assert!(!pipeline.register_before("parse", "alias", Arc::new(Nothing)));
+        assert!(!pipeline.register_after("nowhere", "trace", Arc::new(Nothing)));
+        assert_eq!(pipeline.stages().len(), 13);
+    }
+}
@@ -0,0 +1,163 @@
+//! @playtest <realm>               enter the entry room of a realm as yourself
+//! @playtest [<realm>] as guest    play the realm (default: current) as a fresh guest persona
+//! @playtest stop                  stop playtesting, return to your own account and position
+//! @playtest seed [<n>|reset]      show or change the random seed of the current realm
+//!
+//! While playtesting, rooms show the builder notes left on them and their objects.
+
+use crate::commands::{CmdCtx, CommandContext, CommandResult};
+use crate::error::AppResult;
+use crate::models::account::Account;
+use crate::models::blueprint::Blueprint;
+use crate::models::collaborator::BlueprintRight;
+use crate::models::realm::Realm;
+use crate::state::presence::show_room;
+
+const USAGE: &str =
+    "Usage: @playtest <realm> | @playtest [<realm>] as guest | @playtest stop | @playtest seed [<n>|reset]";
+
+pub async fn playtest(cmd: CommandContext) -> CommandResult {
+    let CommandContext { ctx, intent, .. } = cmd;
+    let args: Vec<&str> = intent.args.iter().map(String::as_str).collect();
+
+    if let [_, "stop"] = args.as_slice() {
+        let (origin, playtesting) = {
+            let mut sess = ctx.sess.write();
+            let playtesting = sess.is_playtesting();
+            sess.set_playtesting(false);
+            (sess.end_persona(), playtesting)
+        };
+        match origin {
+            Some(origin) => {
+                ctx.output
+                    .system(format!("[playtest] back as {}.", origin.account.username))
+                    .await;
+                if origin.cursor.is_some() {
+                    show_room(&ctx, true).await?;
+                }
+            }
+            None if playtesting => ctx.output.system("[playtest] stopped playtesting.").await,
+            None => ctx.output.system("[playtest] you are not playing a persona.").await,
+        }
+        return Ok(());
+    }
+
+    if let [_, "seed", rest @ ..] = args.as_slice() {
+        return seed(&ctx, rest).await;
+    }
+
+    // Everything else needs someone playing as themselves
+    if ctx.sess.read().persona_origin().is_some() {
+        ctx.output
+            .system("[playtest] you are already playing a persona. Use '@playtest stop' first.")
+            .await;
+        return Ok(());
+    }
+    let author = ctx.account()?;
+
+    let (realm_key, as_guest) = match args.as_slice() {
+        [_, "as", "guest"] => (None, true),
+        [_, key, "as", "guest"] => (Some(*key), true),
+        [_, key] => (Some(*key), false),
+        _ => {
+            ctx.output.system(USAGE).await;
+            return Ok(());
+        }
+    };
+
+    let realm: Realm = match realm_key {
+        Some(key) => match ctx.registry.services.realm.get_by_key(key).await? {
+            Some(realm) => realm,
+            None => {
+                ctx.output.system(format!("[playtest] no realm '{}'.", key)).await;
+                return Ok(());
+            }
+        },
+        None => (*ctx.cursor()?.realm).clone(),
+    };
+
+    let bp = ctx.registry.services.blueprint.get_by_id(realm.bp_id).await?;
+    if !may_playtest(&ctx, &author, &bp).await? {
+        return Ok(());
+    }
+
+    let account_id = if as_guest {
+        let persona = ctx.registry.services.playtest.fresh_guest(&author).await?;
+        let id = persona.id;
+        ctx.sess.write().begin_persona(persona);
+        ctx.output
+            .system(format!(
+                "[playtest] playing '{}' as a fresh guest. Use '@playtest stop' to return.",
+                realm.title
+            ))
+            .await;
+        id
+    } else {
+        ctx.output
+            .system(format!("[playtest] entering '{}'.", realm.title))
+            .await;
+        author.id
+    };
+
+    let cursor = ctx
+        .registry
+        .services
+        .room
+        .create_cursor(realm.id, bp.entry_room_id, account_id)
+        .await?;
+    ctx.sess.write().set_playtesting(true);
+    ctx.registry.services.room.enter_room(ctx.clone(), &cursor).await?;
+    show_room(&ctx, true).await?;
+
+    Ok(())
+}
+
+/// Shows the seed of the current realm's random numbers, or restarts them from another seed.
+/// Works while playing a persona, with the rights of the author behind it.
+async fn seed(ctx: &CmdCtx, args: &[&str]) -> CommandResult {
+    let origin = ctx.sess.read().persona_origin().map(|o| o.account.clone());
+    let author = match origin {
+        Some(account) => account,
+        None => ctx.account()?,
+    };
+    let realm = ctx.cursor()?.realm;
+    let bp = ctx.registry.services.blueprint.get_by_id(realm.bp_id).await?;
+    if !may_playtest(ctx, &author, &bp).await? {
+        return Ok(());
+    }
+
+    let random = &ctx.registry.random;
+    let msg = match args {
+        [] => format!(
+            "[playtest] random seed of '{}' is {}.",
+            realm.title,
+            random.seed(realm.id)
+        ),
+        ["reset"] => format!(
+            "[playtest] random numbers restart from the realm's own seed {}.",
+            random.reseed(realm.id, None)
+        ),
+        [n] => match n.parse::<u64>() {
+            Ok(n) => format!(
+                "[playtest] random numbers restart from seed {}.",
+                random.reseed(realm.id, Some(n))
+            ),
+            Err(_) => "[playtest] the seed must be a whole number.".to_string(),
+        },
+        _ => USAGE.to_string(),
+    };
+    ctx.output.system(msg).await;
+    Ok(())
+}
+
+/// Owners, and the editors and testers they gave the right, may playtest the blueprint's realms
+async fn may_playtest(ctx: &CmdCtx, author: &Account, bp: &Blueprint) -> AppResult<bool> {
+    let access = ctx.registry.services.collaborator.access(author, bp).await?;
+    if !access.allows(BlueprintRight::Playtest) {
+        ctx.output
+            .system("[playtest] you can only playtest realms of blueprints you work on.")
+            .await;
+        return Ok(false);
+    }
+    Ok(true)
+}
@@ -0,0 +1,91 @@
+//! rate                      show your rating and tags of this realm
+//! rate <1-5> [review]       rate this realm, with an optional short review
+//! rate tag <tag>...         tag this realm
+//! rate untag <tag>          remove one of your tags
+//!
+//! Only realms you have completed can be rated and tagged.
+
+use crate::commands::{CmdCtx, CommandContext, CommandResult};
+use crate::error::{AppResult, DomainError};
+use crate::models::review::stars_bar;
+use crate::services::FilterOutcome;
+use crate::util::args::words_after;
+
+const USAGE: &str = "Usage: rate [<1-5> [review]] | rate tag <tag>... | rate untag <tag>";
+
+pub async fn rate(cmd: CommandContext) -> CommandResult {
+    let CommandContext { ctx, intent, raw } = cmd;
+    let args: Vec<&str> = intent.args.iter().skip(1).map(String::as_str).collect();
+
+    match run(&ctx, &args, &raw).await {
+        Ok(msg) => ctx.output.system(msg).await,
+        Err(DomainError::Validation { message, .. }) => {
+            ctx.output.system(format!("Cannot do that: {}.", message)).await;
+        }
+        Err(e) => return Err(e.into()),
+    }
+    Ok(())
+}
+
+async fn run(ctx: &CmdCtx, args: &[&str], raw: &str) -> AppResult<String> {
+    let account_id = ctx.account_id()?;
+    let realm = ctx.cursor()?.realm.clone();
+    let reviews = &ctx.registry.services.review;
+
+    match args {
+        [] => {
+            let (rating, tags) = reviews.mine(realm.id, account_id).await?;
+            let mut out = match rating {
+                Some(r) => format!(
+                    "You rated {} {}{}",
+                    realm.title,
+                    stars_bar(r.stars),
+                    r.review.map(|t| format!(": {}", t)).unwrap_or_default()
+                ),
+                None => format!("You have not rated {}. {}", realm.title, USAGE),
+            };
+            if !tags.is_empty() {
+                out.push_str(&format!("\nYour tags: {}", tags.join(", ")));
+            }
+            Ok(out)
+        }
+        ["tag", tags @ ..] if !tags.is_empty() => {
+            let mut added = Vec::new();
+            for tag in tags {
+                if let Some(tag) = reviews.tag(&realm, account_id, tag).await? {
+                    added.push(tag);
+                }
+            }
+            match added.is_empty() {
+                true => Ok("You already used those tags.".to_string()),
+                false => Ok(format!("Tagged {} with {}.", realm.title, added.join(", "))),
+            }
+        }
+        ["untag", tag] => match reviews.untag(realm.id, account_id, tag).await? {
+            true => Ok(format!("Removed the tag {}.", tag)),
+            false => Ok(format!("You did not tag {} with {}.", realm.title, tag)),
+        },
+        ["tag" | "untag", ..] => Ok(USAGE.to_string()),
+        [stars, ..] => {
+            // Builders read reviews in `@bp feedback`, so they go through the filter like `say`
+            let review = words_after(raw, 2);
+            let review = match review.is_empty() {
+                true => review.to_string(),
+                false => match ctx
+                    .registry
+                    .filter
+                    .apply(&*ctx.account()?, realm.id, "review", review)
+                    .await?
+                {
+                    FilterOutcome::Clean => review.to_string(),
+                    FilterOutcome::Masked(masked) => masked,
+                    FilterOutcome::Blocked(_) => {
+                        return Ok("Your review was blocked by the content filter.".to_string());
+                    }
+                },
+            };
+            let stars = reviews.rate(&realm, account_id, stars, &review).await?;
+            Ok(format!("You rated {} {}. Thanks!", realm.title, stars_bar(stars)))
+        }
+    }
+}
@@ -0,0 +1,143 @@
+//! read <thing> [page]       open a book, log or datapad at the first or the given page
+//! read next|prev            turn the page of the open readable
+//! read <page>               jump to a page of the open readable
+//!
+//! Readables are inventory items or room objects with pages. Objects can generate their pages
+//! with an on_read Lua script.
+
+use crate::commands::{CmdCtx, CommandContext, CommandError, CommandResult, fallback};
+use crate::input::parser::{Intent, NounPhrase};
+use crate::lua::ScriptHook;
+use crate::models::readable::Readable;
+use std::sync::Arc;
+
+#[derive(Debug, PartialEq, Eq)]
+enum ReadTarget<'a> {
+    /// Turn the page of the open readable (+1 or -1)
+    Turn(isize),
+    /// Jump to a page (1-based) of the open readable
+    Page(usize),
+    /// Open a readable, optionally at a page (1-based)
+    Open(&'a str, Option<usize>),
+}
+
+fn read_target(np: Option<&NounPhrase>) -> ReadTarget<'_> {
+    let Some(np) = np else {
+        return ReadTarget::Turn(1);
+    };
+
+    // "read log 2" parses as the noun phrase "log 2" with head "2"
+    if let Ok(page) = np.head.parse::<usize>() {
+        return match np.adjectives.iter().rfind(|a| a.as_str() != "page") {
+            Some(noun) => ReadTarget::Open(noun, Some(page)),
+            None => ReadTarget::Page(page),
+        };
+    }
+
+    match np.head.as_str() {
+        "next" | "more" if np.adjectives.is_empty() => ReadTarget::Turn(1),
+        "prev" | "previous" | "back" if np.adjectives.is_empty() => ReadTarget::Turn(-1),
+        noun => ReadTarget::Open(noun, None),
+    }
+}
+
+pub async fn read(cmd: CommandContext) -> CommandResult {
+    let CommandContext { ctx, intent, .. } = cmd;
+    match read_target(intent.direct.as_ref()) {
+        ReadTarget::Turn(delta) => {
+            let Some((readable, page)) = ctx.sess.read().reading() else {
+                ctx.output.system("Read what?").await;
+                return Ok(());
+            };
+            show_page(&ctx, readable, page.saturating_add_signed(delta)).await;
+        }
+        ReadTarget::Page(page) => {
+            let Some((readable, _)) = ctx.sess.read().reading() else {
+                ctx.output.system("Read what?").await;
+                return Ok(());
+            };
+            show_page(&ctx, readable, page.saturating_sub(1)).await;
+        }
+        ReadTarget::Open(noun, page) => {
+            let Some(readable) = find_readable(&ctx, noun, &intent).await? else {
+                return Ok(());
+            };
+            show_page(&ctx, Arc::new(readable), page.unwrap_or(1).saturating_sub(1)).await;
+        }
+    }
+
+    Ok(())
+}
+
+/// Looks for the readable in the inventory first, then among the room's objects, and finally
+/// offers the command to the room's script. Reports to the player and returns None when there
+/// is nothing to read.
+async fn find_readable(ctx: &Arc<CmdCtx>, noun: &str, intent: &Intent) -> Result<Option<Readable>, CommandError> {
+    let services = &ctx.registry.services;
+    let realm_id = ctx.realm_id()?;
+
+    if let Some(item) = services
+        .inventory
+        .find_in_inventory(realm_id, ctx.account_id()?, noun)
+        .await?
+    {
+        let pages = services
+            .inventory
+            .get_item_by_key(realm_id, &item.item_key)
+            .await?
+            .pages;
+        return Ok(readable_or_report(ctx, &item.name, pages).await);
+    }
+
+    let rv = ctx.room_view()?;
+    if let Some(obj) = rv.object_by_noun(noun) {
+        let pages = match services.room.lua_on_read(ctx.clone(), obj).await? {
+            Some(pages) => pages,
+            None => obj.pages.clone(),
+        };
+        return Ok(readable_or_report(ctx, &obj.name, pages).await);
+    }
+
+    if rv.scripts.get(&ScriptHook::OnCommand).is_some() && fallback::room_command(ctx.clone(), intent.clone()).await? {
+        return Ok(None);
+    }
+    ctx.output.line(format!("You see no {} here to read.", noun)).await;
+    Ok(None)
+}
+
+async fn readable_or_report(ctx: &CmdCtx, name: &str, pages: Vec<String>) -> Option<Readable> {
+    if pages.is_empty() {
+        ctx.output
+            .line(format!("There is nothing to read on the {}.", name))
+            .await;
+        return None;
+    }
+    Some(Readable::new(name, pages))
+}
+
+async fn show_page(ctx: &CmdCtx, readable: Arc<Readable>, page: usize) {
+    let (page, text) = readable.render(page);
+    ctx.sess.write().set_reading(Some((readable, page)));
+    ctx.output.line(text).await;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::input::parser::parse_command;
+
+    fn target(input: &str) -> String {
+        format!("{:?}", read_target(parse_command(input).direct.as_ref()))
+    }
+
+    #[test]
+    fn t_read_target() {
+        assert_eq!(target("read"), "Turn(1)");
+        assert_eq!(target("read next"), "Turn(1)");
+        assert_eq!(target("read back"), "Turn(-1)");
+        assert_eq!(target("read page 3"), "Page(3)");
+        assert_eq!(target("read the log"), "Open(\"log\", None)");
+        assert_eq!(target("read log 2"), "Open(\"log\", Some(2))");
+        assert_eq!(target("read log page 2"), "Open(\"log\", Some(2))");
+    }
+}
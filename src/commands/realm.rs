@@ -0,0 +1,90 @@
+//! @realm reset <realm> [confirm]
+//!
+//! Resets a realm to how its blueprint describes it: zone and per-player state, items, loot and
+//! counters are wiped, accounts are left alone. Players in the realm are moved to its entry room.
+
+use crate::commands::{CmdCtx, CommandContext, CommandResult};
+use crate::error::AppResult;
+use crate::models::realm::Realm;
+use crate::models::types::RealmId;
+use crate::state::hazards::force_move;
+
+const USAGE: &str = "Usage: @realm reset <realm> [confirm]";
+
+const ANNOUNCEMENT: &str = "The world around you flickers and resets. You find yourself back at the start.";
+
+pub async fn realm(cmd: CommandContext) -> CommandResult {
+    let CommandContext { ctx, intent, .. } = cmd;
+    let args: Vec<&str> = intent.args.iter().map(String::as_str).collect();
+
+    match args.as_slice() {
+        [_, "reset", realm, rest @ ..] => reset(&ctx, realm, rest == ["confirm"]).await?,
+        _ => ctx.output.system(USAGE).await,
+    }
+
+    Ok(())
+}
+
+async fn reset(ctx: &CmdCtx, name: &str, confirmed: bool) -> CommandResult {
+    let Some(realm) = find_realm(ctx, name).await? else {
+        ctx.output
+            .system(format!("[realm] there is no realm '{}'.", name))
+            .await;
+        return Ok(());
+    };
+
+    let present = ctx.registry.players_in_realm(realm.id);
+    if !confirmed {
+        ctx.output
+            .system(format!(
+                "[realm] this wipes all state of '{}' ({} player(s) present will be moved to the entry room). \
+                 Repeat with 'confirm' to go ahead.",
+                realm.title,
+                present.len()
+            ))
+            .await;
+        return Ok(());
+    }
+
+    if !ctx.registry.services.realm.reset_realm(realm.id).await? {
+        ctx.output
+            .system(format!("[realm] there is no realm '{}'.", name))
+            .await;
+        return Ok(());
+    }
+
+    let bp = ctx.registry.services.blueprint.get_by_id(realm.bp_id).await?;
+    let mut moved = 0;
+    for p in present {
+        let Some(cursor) = p.sess.read().get_cursor() else {
+            continue;
+        };
+        p.output.system(ANNOUNCEMENT).await;
+        match force_move(&ctx.registry, &ctx.lua_tx, &p, &cursor, bp.entry_room_id).await {
+            Ok(()) => moved += 1,
+            Err(e) => {
+                tracing::warn!(realm_id = %realm.id, account = %p.account.username, error = %e, "realm reset: cannot move player")
+            }
+        }
+    }
+
+    ctx.output
+        .system(format!(
+            "[realm] '{}' has been reset, {} player(s) moved to the entry room.",
+            realm.title, moved
+        ))
+        .await;
+    Ok(())
+}
+
+/// Looks the realm up by key, or by id
+async fn find_realm(ctx: &CmdCtx, name: &str) -> AppResult<Option<Realm>> {
+    let realms = &ctx.registry.services.realm;
+    if let Some(realm) = realms.get_by_key(name).await? {
+        return Ok(Some(realm));
+    }
+    match name.parse::<RealmId>() {
+        Ok(id) => realms.get_by_id(id).await,
+        Err(_) => Ok(None),
+    }
+}
@@ -0,0 +1,123 @@
+//! realms [page] [<n>] [tag <tag>] [by <author>]   browse the published realms
+//! realms <key>                                    travel to the entry room of a realm
+//!
+//! Players that have not finished the starter tutorial cannot use it yet.
+
+use crate::commands::{CmdCtx, CommandContext, CommandResult};
+use crate::models::realm_directory::{DirectoryQuery, format_duration};
+use crate::services::TutorialService;
+use crate::state::presence;
+use std::sync::Arc;
+
+pub async fn realms(cmd: CommandContext) -> CommandResult {
+    let CommandContext { ctx, intent, .. } = cmd;
+    // The session's copy of the account does not see a tutorial completed by a script
+    let account_id = ctx.account_id()?;
+    let Some(account) = ctx.registry.services.account.get_by_id(account_id).await? else {
+        return Ok(());
+    };
+    if ctx.registry.config().content.starter_blueprint.is_some() && !TutorialService::is_completed(&account) {
+        ctx.output
+            .system("Finish the tutorial first, then the other realms open up to you.")
+            .await;
+        return Ok(());
+    }
+
+    let args: Vec<&str> = intent.args.iter().skip(1).map(String::as_str).collect();
+    if DirectoryQuery::is_listing(&args) {
+        return list(ctx, &args).await;
+    }
+    let key = args[0];
+
+    let realms = ctx.registry.services.realm.list_public().await?;
+    let Some((_, realm)) = realms.into_iter().find(|(k, _)| k == key) else {
+        ctx.output.system(format!("There is no realm '{}'.", key)).await;
+        return Ok(());
+    };
+    if realm.id == ctx.realm_id()? {
+        ctx.output.system(format!("You are already in {}.", realm.title)).await;
+        return Ok(());
+    }
+
+    let from = ctx.cursor()?;
+    let services = &ctx.registry.services;
+    let bp = services.blueprint.get_by_id(realm.bp_id).await?;
+    let cursor = services
+        .room
+        .create_cursor(realm.id, bp.entry_room_id, account_id)
+        .await?;
+    services
+        .tutorial
+        .set_position(account_id, realm.id, bp.entry_room_id)
+        .await?;
+    services.realm.record_start(&realm, account_id).await?;
+
+    ctx.output.system(format!("You travel to {}.", realm.title)).await;
+    services.room.enter_room(ctx.clone(), &cursor).await?;
+    presence::announce_departure(&ctx.registry, &from, &account.username, None).await;
+    presence::announce_arrival(&ctx.registry, &cursor, &account.username, None).await;
+    presence::show_room(&ctx, true).await?;
+
+    // Once out of it, the tutorial instance is of no use anymore
+    if TutorialService::is_completed(&account) {
+        services.tutorial.discard_instance(account_id).await?;
+    }
+
+    Ok(())
+}
+
+async fn list(ctx: Arc<CmdCtx>, args: &[&str]) -> CommandResult {
+    let mut query = match DirectoryQuery::parse(args) {
+        Ok(query) => query,
+        Err(e) => {
+            ctx.output.system(format!("{}. {}", e, USAGE)).await;
+            return Ok(());
+        }
+    };
+
+    query.page_size = ctx.account()?.settings.page_size;
+    let page = ctx.registry.db.realm_directory(&query).await?;
+    if page.entries.is_empty() {
+        let msg = if query.page > 1 {
+            format!("There is no page {} of realms.", query.page)
+        } else {
+            "No realms match.".to_string()
+        };
+        ctx.output.system(msg).await;
+        return Ok(());
+    }
+
+    let current = ctx.realm_id()?;
+    let mut out = format!("Realms (page {} of {}):\n", query.page, page.pages());
+    for e in &page.entries {
+        let players = ctx.registry.players_in_realm(e.realm_id).len();
+        let here = if e.realm_id == current { " (you are here)" } else { "" };
+        out.push_str(&format!(
+            "  {:<16} {}{}{}\n      by {}, {}, {} playing, avg. completion {}{}\n",
+            e.key,
+            e.title,
+            e.avg_stars
+                .map(|avg| format!("  {:.1}* ({})", avg, e.ratings))
+                .unwrap_or_default(),
+            here,
+            e.author.as_deref().unwrap_or("unknown"),
+            e.difficulty.as_deref().unwrap_or("unrated"),
+            players,
+            e.avg_completion_secs.map(format_duration).as_deref().unwrap_or("-"),
+            if e.tags.is_empty() {
+                String::new()
+            } else {
+                format!(" [{}]", e.tags.join(", "))
+            },
+        ));
+    }
+    if query.page < page.pages() {
+        out.push_str(&format!("Type 'realms page {}' for more. ", query.page + 1));
+    }
+    out.push_str("Type 'realms <key>' to travel there.");
+    ctx.output.system(out).await;
+
+    Ok(())
+}
+
+const USAGE: &str = "Usage: realms [page <n>] [tag <tag>] [by <author>] | realms <key>";
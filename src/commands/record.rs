@@ -0,0 +1,96 @@
+//! @record                   show whether your session is being recorded
+//! @record on|off            start / stop recording your session
+//! @record <player> on|off   record the session of another player (moderators)
+
+use crate::commands::{CommandContext, CommandResult};
+
+const USAGE: &str = "Usage: @record [on|off] | @record <player> on|off";
+
+pub async fn record(cmd: CommandContext) -> CommandResult {
+    let CommandContext { ctx, intent, .. } = cmd;
+    let account = ctx.account()?;
+    let args: Vec<&str> = intent.args.iter().map(String::as_str).collect();
+
+    match args.as_slice() {
+        [_] => {
+            let status = match ctx.sess.read().recorder() {
+                Some(r) => format!(
+                    "[record] recording since {} ({} events).",
+                    r.started_at.format("%H:%M:%S"),
+                    r.len()
+                ),
+                None => "[record] your session is not being recorded.".to_string(),
+            };
+            ctx.output.system(status).await;
+        }
+        [_, "on"] => {
+            if ctx.sess.write().start_recording(account.id) {
+                ctx.output
+                    .system("[record] recording your session. Use '@record off' to stop and save it.")
+                    .await;
+            } else {
+                ctx.output
+                    .system("[record] your session is already being recorded.")
+                    .await;
+            }
+        }
+        [_, "off"] => match ctx.registry.save_recording(account.id, &ctx.sess).await? {
+            Some(id) => {
+                ctx.output
+                    .system(format!(
+                        "[record] saved as recording #{}. View it with '@replay {}'.",
+                        id, id
+                    ))
+                    .await
+            }
+            None => ctx.output.system("[record] your session is not being recorded.").await,
+        },
+        [_, name, toggle @ ("on" | "off")] => {
+            if !account.is_moderator() {
+                ctx.output
+                    .system("[record] only moderators can record other players.")
+                    .await;
+                return Ok(());
+            }
+            let Some(target) = ctx.registry.connected_by_name(name) else {
+                ctx.output.system(format!("[record] {} is not online.", name)).await;
+                return Ok(());
+            };
+            let who = target.account.username.clone();
+
+            if *toggle == "on" {
+                if !target.sess.write().start_recording(account.id) {
+                    ctx.output
+                        .system(format!("[record] {} is already being recorded.", who))
+                        .await;
+                    return Ok(());
+                }
+                target
+                    .output
+                    .system(format!("[record] {} is recording your session.", account.username))
+                    .await;
+                ctx.output.system(format!("[record] recording {}.", who)).await;
+            } else {
+                match ctx.registry.save_recording(target.account.id, &target.sess).await? {
+                    Some(id) => {
+                        target
+                            .output
+                            .system("[record] your session is no longer being recorded.")
+                            .await;
+                        ctx.output
+                            .system(format!("[record] saved the session of {} as recording #{}.", who, id))
+                            .await;
+                    }
+                    None => {
+                        ctx.output
+                            .system(format!("[record] {} is not being recorded.", who))
+                            .await
+                    }
+                }
+            }
+        }
+        _ => ctx.output.system(USAGE).await,
+    }
+
+    Ok(())
+}
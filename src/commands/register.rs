@@ -1,11 +1,11 @@
-use crate::commands::{CmdCtx, CommandResult};
-use crate::input::parser::Intent;
+use crate::commands::{CmdCtx, CommandContext, CommandResult};
 use crate::models::account::Account;
 use crate::net::InputMode;
 use crate::state::interactive::{InteractiveState, RegisterState};
 use std::sync::Arc;
 
-pub async fn register(ctx: Arc<CmdCtx>, intent: Intent) -> CommandResult {
+pub async fn register(cmd: CommandContext) -> CommandResult {
+    let CommandContext { ctx, intent, .. } = cmd;
     if let Some(username) = intent.args.first() {
         let st = RegisterState {
             username: Some(username.to_string()),
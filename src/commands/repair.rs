@@ -0,0 +1,60 @@
+//! repair <item> [with <tool>]
+//!
+//! Restores a worn or broken inventory item to full durability. Items can require a repair tool
+//! in the inventory, which wears down in the process.
+
+use crate::commands::{CommandContext, CommandResult, fallback};
+use crate::lua::ScriptHook;
+use crate::services::RepairOutcome;
+
+pub async fn repair(cmd: CommandContext) -> CommandResult {
+    let CommandContext { ctx, intent, .. } = cmd;
+    let Some(item) = intent.direct.as_ref() else {
+        ctx.output.system("Usage: repair <item> [with <tool>]").await;
+        return Ok(());
+    };
+
+    let realm_id = ctx.realm_id()?;
+    let account_id = ctx.account_id()?;
+    let outcome = ctx
+        .registry
+        .services
+        .inventory
+        .repair(
+            realm_id,
+            account_id,
+            &item.head,
+            intent.instrument.as_ref().map(|t| t.head.as_str()),
+        )
+        .await?;
+
+    let msg = match outcome {
+        RepairOutcome::Repaired { item, tool } => {
+            ctx.output
+                .line(format!("You repair the {}. It's as good as new.", item.name))
+                .await;
+            match tool.and_then(|t| t.wear_message()) {
+                Some(msg) => msg,
+                None => return Ok(()),
+            }
+        }
+        RepairOutcome::NotCarried(noun) => {
+            // Room scripts may handle repairing things in the room
+            if noun == item.head
+                && ctx.room_view()?.scripts.get(&ScriptHook::OnCommand).is_some()
+                && fallback::room_command(ctx.clone(), intent.clone()).await?
+            {
+                return Ok(());
+            }
+            format!("You are not carrying any '{}'.", noun)
+        }
+        RepairOutcome::CannotRepair(name) => format!("The {} doesn't need any repairs.", name),
+        RepairOutcome::NotDamaged(name) => format!("The {} is in perfect condition.", name),
+        RepairOutcome::MissingTool(tool) => format!("You need a {} to repair that.", tool),
+        RepairOutcome::BrokenTool(tool) => format!("Your {} is broken too.", tool),
+        RepairOutcome::WrongTool(name) => format!("The {} won't help with that.", name),
+    };
+    ctx.output.line(msg).await;
+
+    Ok(())
+}
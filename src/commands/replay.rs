@@ -0,0 +1,107 @@
+//! @replay                   list the recordings you can replay
+//! @replay <id> [step]       open a recording, at the first or the given step
+//! @replay next|prev         step through the open recording
+//! @replay stop              close the recording
+
+use crate::commands::{CmdCtx, CommandContext, CommandResult};
+use crate::models::recording::Recording;
+use std::sync::Arc;
+
+const USAGE: &str = "Usage: @replay [<id> [step] | next | prev | stop]";
+
+pub async fn replay(cmd: CommandContext) -> CommandResult {
+    let CommandContext { ctx, intent, .. } = cmd;
+    let account = ctx.account()?;
+    let args: Vec<&str> = intent.args.iter().map(String::as_str).collect();
+
+    match args.as_slice() {
+        [_] => {
+            let recordings = ctx.registry.services.recording.list(&account).await?;
+            if recordings.is_empty() {
+                ctx.output
+                    .system("[replay] no recordings. Record your session with '@record on'.")
+                    .await;
+                return Ok(());
+            }
+            let mut out = String::from("[replay] recordings:");
+            for r in recordings {
+                out.push_str(&format!(
+                    "\n  #{:<5} {} {:<16} {} events, {} min",
+                    r.id,
+                    r.started_at.format("%Y-%m-%d %H:%M"),
+                    r.username,
+                    r.events,
+                    (r.ended_at - r.started_at).num_minutes()
+                ));
+            }
+            ctx.output.system(out).await;
+        }
+        [_, "stop"] => {
+            ctx.sess.write().set_replay(None);
+            ctx.output.system("[replay] closed.").await;
+        }
+        [_, dir @ ("next" | "prev")] => {
+            let Some((recording, step)) = ctx.sess.read().replay() else {
+                ctx.output
+                    .system("[replay] no recording open, use '@replay <id>'.")
+                    .await;
+                return Ok(());
+            };
+            let step = if *dir == "next" {
+                step + 1
+            } else {
+                step.saturating_sub(1)
+            };
+            show_step(&ctx, recording, step).await;
+        }
+        [_, id, rest @ ..] if rest.len() <= 1 => {
+            let id = id.trim_start_matches('#').parse::<i64>();
+            let step = rest.first().map(|s| s.parse::<usize>());
+            let (Ok(id), None | Some(Ok(1..))) = (id, &step) else {
+                ctx.output.system(USAGE).await;
+                return Ok(());
+            };
+            let step = step.and_then(Result::ok).map_or(0, |s| s - 1);
+
+            let recording = ctx.registry.services.recording.get(id, &account).await?;
+            show_step(&ctx, Arc::new(recording), step).await;
+        }
+        _ => ctx.output.system(USAGE).await,
+    }
+
+    Ok(())
+}
+
+async fn show_step(ctx: &CmdCtx, recording: Arc<Recording>, step: usize) {
+    let steps = recording.steps();
+    if steps.is_empty() {
+        ctx.output
+            .system(format!("[replay] recording #{} is empty.", recording.id))
+            .await;
+        return;
+    }
+    let step = step.min(steps.len() - 1);
+    let s = &steps[step];
+
+    let mut out = format!(
+        "[replay #{} {} step {}/{} +{:.1}s]",
+        recording.id,
+        recording.username,
+        step + 1,
+        steps.len(),
+        s.at_ms as f64 / 1000.0
+    );
+    if let Some(input) = &s.input {
+        out.push_str(&format!("\n> {}", input));
+    }
+    for line in &s.output {
+        out.push('\n');
+        out.push_str(line);
+    }
+    if step + 1 == steps.len() {
+        out.push_str("\n[replay] end of recording.");
+    }
+
+    ctx.sess.write().set_replay(Some((recording, step)));
+    ctx.output.system(out).await;
+}
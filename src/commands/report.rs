@@ -0,0 +1,61 @@
+//! report <player> <reason>
+
+use crate::commands::{CommandContext, CommandResult};
+use crate::error::DomainError;
+
+const USAGE: &str = "Usage: report <player> <reason>";
+
+pub async fn report(cmd: CommandContext) -> CommandResult {
+    let CommandContext { ctx, intent, .. } = cmd;
+    let (Some(target), true) = (intent.args.get(1), intent.args.len() > 2) else {
+        ctx.output.system(USAGE).await;
+        return Ok(());
+    };
+    let reason = intent.args[2..].join(" ");
+
+    let account = ctx.account()?;
+    let cursor = ctx.cursor().ok();
+
+    let filed = ctx
+        .registry
+        .services
+        .moderation
+        .file_report(
+            &account,
+            target,
+            &reason,
+            cursor.as_ref().map(|c| c.realm_id),
+            cursor.as_ref().map(|c| c.room_id),
+        )
+        .await;
+
+    let (id, target) = match filed {
+        Ok(v) => v,
+        Err(DomainError::NotFound(_)) => {
+            ctx.output
+                .system(format!("There is no player named '{}'.", target))
+                .await;
+            return Ok(());
+        }
+        Err(DomainError::Validation { message, .. } | DomainError::Conflict(message)) => {
+            ctx.output.system(format!("Report not filed: {}.", message)).await;
+            return Ok(());
+        }
+        Err(e) => return Err(e.into()),
+    };
+
+    ctx.output
+        .system(format!(
+            "Thank you. Your report against {} has been filed (#{}).",
+            target, id
+        ))
+        .await;
+    ctx.registry
+        .notify_moderators(&format!(
+            "[reports] #{} {} reported {}: {}",
+            id, account.username, target, reason
+        ))
+        .await;
+
+    Ok(())
+}
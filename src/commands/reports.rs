@@ -0,0 +1,54 @@
+//! @reports list
+//! @reports resolve <id> [note]
+
+use crate::commands::{CommandContext, CommandResult};
+
+const USAGE: &str = "Usage: @reports list | @reports resolve <id> [note]";
+
+pub async fn reports(cmd: CommandContext) -> CommandResult {
+    let CommandContext { ctx, intent, .. } = cmd;
+    let args: Vec<&str> = intent.args.iter().map(String::as_str).collect();
+
+    match args.as_slice() {
+        [_] | [_, "list"] => {
+            let reports = ctx.registry.services.moderation.open_reports().await?;
+            if reports.is_empty() {
+                ctx.output.system("[reports] no open reports.").await;
+                return Ok(());
+            }
+            let mut out = format!("[reports] {} open:", reports.len());
+            for r in reports {
+                out.push_str(&format!(
+                    "\n  #{:<5} {} {} -> {}: {}",
+                    r.id,
+                    r.created_at.format("%Y-%m-%d %H:%M"),
+                    r.reporter,
+                    r.target,
+                    r.reason
+                ));
+            }
+            ctx.output.system(out).await;
+        }
+        [_, "resolve", id, note @ ..] => {
+            let Ok(id) = id.trim_start_matches('#').parse::<i64>() else {
+                ctx.output.system(USAGE).await;
+                return Ok(());
+            };
+            let account = ctx.account()?;
+            let note = note.join(" ");
+            let note = (!note.is_empty()).then_some(note.as_str());
+
+            if ctx.registry.services.moderation.resolve(id, &account, note).await? {
+                ctx.output.system(format!("[reports] #{} resolved.", id)).await;
+                ctx.registry
+                    .notify_moderators(&format!("[reports] #{} resolved by {}.", id, account.username))
+                    .await;
+            } else {
+                ctx.output.system(format!("[reports] no open report #{}.", id)).await;
+            }
+        }
+        _ => ctx.output.system(USAGE).await,
+    }
+
+    Ok(())
+}
@@ -0,0 +1,268 @@
+//! What a noun in a command refers to.
+//!
+//! A noun can name an item the player carries, an NPC (an object with a dialogue), another
+//! object in the room, or an item lying on the floor. Each verb looks in some of these scopes,
+//! in an order of its own (`Scope::for_verb`). The candidates of all its scopes are scored
+//! together (see `input::matcher`), so "key" picks the key on the floor over the keypad on the
+//! wall; when things in two scopes match equally well, the earlier scope wins. An ordinal
+//! ("second key") counts through the scopes in that order.
+//!
+//! Unless a verb says otherwise the order is NPCs, objects, inventory, floor: the room is looked
+//! at before the pockets.
+
+use crate::commands::{CmdCtx, CommandError};
+use crate::input::matcher;
+use crate::input::parser::{NounPhrase, Verb};
+use crate::models::inventory::ItemInstance;
+use crate::models::room::ResolvedObject;
+use crate::state::session::Cursor;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum Scope {
+    Inventory,
+    Npcs,
+    Objects,
+    Floor,
+}
+
+const DEFAULT: &[Scope] = &[Scope::Npcs, Scope::Objects, Scope::Inventory, Scope::Floor];
+
+impl Scope {
+    /// Where `verb` looks for its direct object, most preferred first
+    pub fn for_verb(verb: &Verb) -> &'static [Scope] {
+        match verb {
+            Verb::Drop | Verb::Give | Verb::Throw | Verb::Deposit => &[Scope::Inventory],
+            Verb::Take => &[Scope::Floor, Scope::Objects, Scope::Npcs],
+            Verb::Talk => &[Scope::Npcs],
+            _ => DEFAULT,
+        }
+    }
+}
+
+/// The thing a noun refers to, by the scope it was found in
+#[derive(Debug, Clone)]
+pub enum Found {
+    Carried(ItemInstance),
+    Npc(ResolvedObject),
+    Object(ResolvedObject),
+    Floor(ItemInstance),
+}
+
+/// The thing `np` refers to, looking in `scopes`. None when nothing fits, or when a guess fits
+/// several things equally well.
+pub async fn resolve(ctx: &CmdCtx, np: &NounPhrase, scopes: &[Scope]) -> Result<Option<Found>, CommandError> {
+    let cursor = ctx.cursor()?;
+    let inventory = &ctx.registry.services.inventory;
+
+    let carried = match scopes.contains(&Scope::Inventory) {
+        true => {
+            inventory
+                .get_player_inventory(cursor.realm_id, cursor.account_id)
+                .await?
+        }
+        false => vec![],
+    };
+    let floor = match scopes.contains(&Scope::Floor) {
+        true => inventory.get_room_items(cursor.realm_id, cursor.room_id).await?,
+        false => vec![],
+    };
+
+    let found = pick(np, scopes, &cursor.room.objects, &carried, &floor);
+    if found.is_some() || np.ordinal.is_some() {
+        return Ok(found);
+    }
+
+    // Objects that are not on view still answer to their exact name
+    Ok(cursor
+        .room
+        .object_by_noun(&np.head)
+        .and_then(|obj| in_scope(obj.clone(), scopes)))
+}
+
+/// Everything "all" covers in `scopes`: with a noun ("all coins") every visible thing of that
+/// name, without one every item in the item scopes. Fixed objects only go along when named.
+pub async fn resolve_all(ctx: &CmdCtx, np: Option<&NounPhrase>, scopes: &[Scope]) -> Result<Vec<Found>, CommandError> {
+    let cursor = ctx.cursor()?;
+    let (carried, floor) = items_in(ctx, &cursor, scopes).await?;
+    Ok(pick_all(np, scopes, &cursor.room.objects, &carried, &floor))
+}
+
+async fn items_in(
+    ctx: &CmdCtx,
+    cursor: &Cursor,
+    scopes: &[Scope],
+) -> Result<(Vec<ItemInstance>, Vec<ItemInstance>), CommandError> {
+    let inventory = &ctx.registry.services.inventory;
+    let carried = match scopes.contains(&Scope::Inventory) {
+        true => {
+            inventory
+                .get_player_inventory(cursor.realm_id, cursor.account_id)
+                .await?
+        }
+        false => vec![],
+    };
+    let floor = match scopes.contains(&Scope::Floor) {
+        true => inventory.get_room_items(cursor.realm_id, cursor.room_id).await?,
+        false => vec![],
+    };
+    Ok((carried, floor))
+}
+
+/// Everything in `scopes` a noun could refer to, in scope order
+fn candidates(
+    scopes: &[Scope],
+    objects: &[ResolvedObject],
+    carried: &[ItemInstance],
+    floor: &[ItemInstance],
+) -> Vec<Found> {
+    let mut found = Vec::new();
+    for scope in scopes {
+        match scope {
+            Scope::Inventory => found.extend(carried.iter().cloned().map(Found::Carried)),
+            Scope::Floor => found.extend(floor.iter().cloned().map(Found::Floor)),
+            Scope::Npcs | Scope::Objects => found.extend(
+                objects
+                    .iter()
+                    .filter(|o| o.flags.is_visible())
+                    .filter_map(|o| in_scope(o.clone(), &[*scope])),
+            ),
+        }
+    }
+    found
+}
+
+fn pick(
+    np: &NounPhrase,
+    scopes: &[Scope],
+    objects: &[ResolvedObject],
+    carried: &[ItemInstance],
+    floor: &[ItemInstance],
+) -> Option<Found> {
+    let scored = candidates(scopes, objects, carried, floor).into_iter().map(|f| {
+        let score = f.match_score(np);
+        (f, score)
+    });
+    matcher::pick(scored, np.ordinal)
+}
+
+fn pick_all(
+    np: Option<&NounPhrase>,
+    scopes: &[Scope],
+    objects: &[ResolvedObject],
+    carried: &[ItemInstance],
+    floor: &[ItemInstance],
+) -> Vec<Found> {
+    candidates(scopes, objects, carried, floor)
+        .into_iter()
+        .filter(|f| match np {
+            Some(np) => f.match_score(np) >= matcher::PLURAL,
+            None => matches!(f, Found::Carried(_) | Found::Floor(_)),
+        })
+        .collect()
+}
+
+impl Found {
+    fn match_score(&self, np: &NounPhrase) -> u32 {
+        match self {
+            Found::Carried(item) | Found::Floor(item) => item.match_score(np),
+            Found::Npc(obj) | Found::Object(obj) => obj.match_score(np),
+        }
+    }
+}
+
+/// The object as found in one of `scopes`, if it belongs to one
+fn in_scope(obj: ResolvedObject, scopes: &[Scope]) -> Option<Found> {
+    let (scope, found) = match obj.dialogue.is_some() {
+        true => (Scope::Npcs, Found::Npc(obj)),
+        false => (Scope::Objects, Found::Object(obj)),
+    };
+    scopes.contains(&scope).then_some(found)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::input::parser::parse_command;
+    use crate::models::inventory::ItemLocation;
+    use crate::models::types::{AccountId, ItemId, RealmId};
+
+    fn item(key: &str, short: &str) -> ItemInstance {
+        ItemInstance {
+            instance_id: ItemId::new(),
+            realm_id: RealmId::new(),
+            catalog_id: ItemId::new(),
+            location: ItemLocation::Player(AccountId::new()),
+            quantity: 1,
+            condition: None,
+            item_key: key.into(),
+            name: key.into(),
+            short: short.into(),
+            description: String::new(),
+            examine: None,
+            stackable: false,
+            nouns: key.rsplit('_').take(1).map(String::from).collect(),
+            max_durability: None,
+            wear_per_use: 0,
+            repair_tool: None,
+            weight: None,
+            is_coin: false,
+            created_at: chrono::Utc::now(),
+            updated_at: chrono::Utc::now(),
+        }
+    }
+
+    fn describe(found: Found) -> String {
+        match found {
+            Found::Carried(i) => format!("carried {}", i.item_key),
+            Found::Floor(i) => format!("floor {}", i.item_key),
+            Found::Npc(o) | Found::Object(o) => o.key,
+        }
+    }
+
+    fn carried() -> Vec<ItemInstance> {
+        vec![item("brass_key", "a brass key")]
+    }
+
+    fn floor() -> Vec<ItemInstance> {
+        vec![item("iron_key", "an iron key"), item("keycard", "a keycard")]
+    }
+
+    fn found(input: &str, scopes: &[Scope]) -> Option<String> {
+        let np = parse_command(&format!("take {input}")).direct.unwrap();
+        pick(&np, scopes, &[], &carried(), &floor()).map(describe)
+    }
+
+    fn found_all(input: &str, scopes: &[Scope]) -> Vec<String> {
+        let intent = parse_command(&format!("take {input}"));
+        pick_all(intent.direct.as_ref(), scopes, &[], &carried(), &floor())
+            .into_iter()
+            .map(describe)
+            .collect()
+    }
+
+    #[test]
+    fn t_scope_order() {
+        let take = Scope::for_verb(&Verb::Take);
+        let drop = Scope::for_verb(&Verb::Drop);
+        assert_eq!(found("key", DEFAULT).as_deref(), Some("carried brass_key"));
+        assert_eq!(found("key", take).as_deref(), Some("floor iron_key"));
+        assert_eq!(found("key", drop).as_deref(), Some("carried brass_key"));
+        assert_eq!(found("second key", DEFAULT).as_deref(), Some("floor iron_key"));
+        assert_eq!(found("second key", drop), None);
+        // A better match in a later scope beats a worse one in an earlier scope
+        assert_eq!(found("keycard", DEFAULT).as_deref(), Some("floor keycard"));
+        assert_eq!(found("iron key", DEFAULT).as_deref(), Some("floor iron_key"));
+        assert_eq!(found("keycard", drop), None);
+    }
+
+    #[test]
+    fn t_all_expansion() {
+        let take = Scope::for_verb(&Verb::Take);
+        let drop = Scope::for_verb(&Verb::Drop);
+        assert_eq!(found_all("all", take), ["floor iron_key", "floor keycard"]);
+        assert_eq!(found_all("all", drop), ["carried brass_key"]);
+        assert_eq!(found_all("all keys", DEFAULT), ["carried brass_key", "floor iron_key"]);
+        assert_eq!(found_all("everything", DEFAULT).len(), 3);
+        assert!(found_all("all lamps", take).is_empty());
+    }
+}
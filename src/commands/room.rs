@@ -0,0 +1,167 @@
+//! @room set title "<title>"       retitle the room you are in
+//! @room set body [<text>]         rewrite its description; without text, opens a line editor
+//! @room addexit <dir> <room>      add an exit to another room of the blueprint
+//!
+//! Edits go straight into the blueprint, with the same checks as an import. Everyone standing in
+//! the room, like the builder playtesting it, sees the change right away.
+
+use crate::commands::{CmdCtx, CommandContext, CommandResult, editor};
+use crate::error::{AppResult, DomainError};
+use crate::models::blueprint::Blueprint;
+use crate::models::collaborator::BlueprintRight;
+use crate::models::types::RoomId;
+use crate::state::interactive::EditTarget;
+use crate::state::presence::show_room;
+use crate::util::args::{normalize_dir, words_after};
+use std::sync::Arc;
+
+const USAGE: &str = "Usage: @room set title \"<title>\" | @room set body [<text>] | @room addexit <dir> <room>";
+
+pub async fn room(cmd: CommandContext) -> CommandResult {
+    let CommandContext { ctx, intent, raw } = cmd;
+    if !ctx.account()?.is_builder() {
+        ctx.output
+            .system("You do not have permission to use that command.")
+            .await;
+        return Ok(());
+    }
+
+    let args: Vec<&str> = intent.args.iter().map(String::as_str).collect();
+    match args.as_slice() {
+        [_, "set", "title", _, ..] => {
+            let title = words_after(&raw, 3).trim_matches('"');
+            let Some(bp) = editable_blueprint(&ctx, BlueprintRight::EditRooms).await? else {
+                return Ok(());
+            };
+            let body = ctx.cursor()?.room.blueprint.body.clone();
+            save_text(&ctx, &bp, title, &body).await
+        }
+        [_, "set", "body"] => {
+            if editable_blueprint(&ctx, BlueprintRight::EditRooms).await?.is_none() {
+                return Ok(());
+            }
+            editor::open(&ctx, EditTarget::RoomBody, "description").await?;
+            Ok(())
+        }
+        [_, "set", "body", _, ..] => {
+            let Some(bp) = editable_blueprint(&ctx, BlueprintRight::EditRooms).await? else {
+                return Ok(());
+            };
+            let title = ctx.cursor()?.room.blueprint.title.clone();
+            save_text(&ctx, &bp, &title, words_after(&raw, 3)).await
+        }
+        [_, "addexit", dir, to] => {
+            let Some(dir) = normalize_dir(dir) else {
+                ctx.output
+                    .system("[room] dir must be a valid direction (n, ne, e, se, s, sw, w, nw, up, down).")
+                    .await;
+                return Ok(());
+            };
+            let Some(bp) = editable_blueprint(&ctx, BlueprintRight::EditRooms).await? else {
+                return Ok(());
+            };
+            let from = ctx.cursor()?.room.blueprint.clone();
+            match ctx.registry.services.blueprint.add_room_exit(&bp, &from, dir, to).await {
+                Ok(_) => {
+                    ctx.output
+                        .system(format!("[room] exit {} --{}--> {} added.", from.key, dir, to))
+                        .await;
+                    refresh_room(&ctx, from.id).await?;
+                    Ok(())
+                }
+                Err(DomainError::NotFound(_)) => {
+                    ctx.output
+                        .system(format!("[room] no room '{}' in '{}'.", to, bp.key))
+                        .await;
+                    Ok(())
+                }
+                Err(DomainError::Validation { message, .. }) => {
+                    ctx.output.system(format!("[room] {}.", message)).await;
+                    Ok(())
+                }
+                Err(e) => Err(e.into()),
+            }
+        }
+        _ => {
+            ctx.output.system(USAGE).await;
+            Ok(())
+        }
+    }
+}
+
+/// Saves the description written in the line editor
+pub(super) async fn save_body(ctx: &Arc<CmdCtx>, body: &str) -> CommandResult {
+    let Some(bp) = editable_blueprint(ctx, BlueprintRight::EditRooms).await? else {
+        return Ok(());
+    };
+    let title = ctx.cursor()?.room.blueprint.title.clone();
+    save_text(ctx, &bp, &title, body).await
+}
+
+/// The blueprint of the current room, when the builder behind the session has `right` on it.
+/// While playing a persona, the rights are those of the author behind it.
+pub(super) async fn editable_blueprint(ctx: &CmdCtx, right: BlueprintRight) -> AppResult<Option<Blueprint>> {
+    let origin = ctx.sess.read().persona_origin().map(|o| o.account.clone());
+    let author = match origin {
+        Some(account) => account,
+        None => ctx.account()?,
+    };
+    let bp_id = ctx.cursor()?.room.blueprint.bp_id;
+    let bp = ctx.registry.services.blueprint.get_by_id(bp_id).await?;
+    match ctx
+        .registry
+        .services
+        .collaborator
+        .require_for(&author, &bp, right)
+        .await
+    {
+        Ok(()) => Ok(Some(bp)),
+        Err(DomainError::Validation { message, .. }) => {
+            ctx.output.system(format!("[edit] {}.", message)).await;
+            Ok(None)
+        }
+        Err(e) => Err(e),
+    }
+}
+
+async fn save_text(ctx: &Arc<CmdCtx>, bp: &Blueprint, title: &str, body: &str) -> CommandResult {
+    let room = ctx.cursor()?.room.blueprint.clone();
+    match ctx
+        .registry
+        .services
+        .blueprint
+        .update_room_text(&room, title, body)
+        .await
+    {
+        Ok(_) => {
+            ctx.output
+                .system(format!("[room] {}:{} updated.", bp.key, room.key))
+                .await;
+            refresh_room(ctx, room.id).await?;
+            show_room(ctx, false).await?;
+            Ok(())
+        }
+        Err(DomainError::Validation { message, .. }) => {
+            ctx.output.system(format!("[room] {}.", message)).await;
+            Ok(())
+        }
+        Err(e) => Err(e.into()),
+    }
+}
+
+/// Rebuilds the cached view of everyone in the room, so an edit shows without moving
+pub(super) async fn refresh_room(ctx: &CmdCtx, room_id: RoomId) -> AppResult<()> {
+    for player in ctx.registry.players_in_room(room_id) {
+        let Some(c) = player.sess.read().get_cursor() else {
+            continue;
+        };
+        let rv = ctx
+            .registry
+            .services
+            .room
+            .build_room_view(c.realm_id, c.account_id, c.room_id)
+            .await?;
+        player.sess.write().replace_room(rv);
+    }
+    Ok(())
+}
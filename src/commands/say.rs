@@ -0,0 +1,46 @@
+//! say <text>
+
+use crate::commands::{CommandContext, CommandResult};
+use crate::services::FilterOutcome;
+
+pub async fn say(cmd: CommandContext) -> CommandResult {
+    let CommandContext { ctx, intent, .. } = cmd;
+    // Take the text from the normalized input, so punctuation and quotes survive tokenizing
+    let text = intent
+        .original
+        .split_once(' ')
+        .map(|(_, rest)| rest.trim())
+        .unwrap_or_default();
+    if text.is_empty() {
+        ctx.output.system("Say what?").await;
+        return Ok(());
+    }
+
+    let account = ctx.account()?;
+    let cursor = ctx.cursor()?;
+
+    let text = match ctx
+        .registry
+        .filter
+        .apply(&account, cursor.realm_id, "say", text)
+        .await?
+    {
+        FilterOutcome::Clean => text.to_string(),
+        FilterOutcome::Masked(masked) => masked,
+        FilterOutcome::Blocked(_) => {
+            ctx.output
+                .system("Your message was blocked by the content filter.")
+                .await;
+            return Ok(());
+        }
+    };
+
+    ctx.output.line(format!("You say: {}", text)).await;
+    for p in ctx.registry.players_in_room(cursor.room_id) {
+        if p.account.id != account.id {
+            p.output.line(format!("{} says: {}", account.username, text)).await;
+        }
+    }
+
+    Ok(())
+}
@@ -1,8 +1,10 @@
-use crate::commands::{CmdCtx, CommandResult};
-use crate::input::parser::{Intent, NounPhrase};
+use crate::commands::{CmdCtx, CommandContext, CommandResult};
+use crate::input::parser::NounPhrase;
+use crate::services::SearchOutcome;
 use std::sync::Arc;
 
-pub async fn search(ctx: Arc<CmdCtx>, intent: Intent) -> CommandResult {
+pub async fn search(cmd: CommandContext) -> CommandResult {
+    let CommandContext { ctx, intent, .. } = cmd;
     if let Some(noun) = intent.direct {
         if let Err(e) = handle_search_object(ctx.clone(), &noun).await {
             // Helpers should already print normal output; we only surface failures.
@@ -21,7 +23,7 @@ pub async fn search(ctx: Arc<CmdCtx>, intent: Intent) -> CommandResult {
 async fn handle_search_object(ctx: Arc<CmdCtx>, noun: &NounPhrase) -> anyhow::Result<()> {
     let rv = ctx.room_view()?;
 
-    if let Some(obj) = rv.object_by_noun(&noun.head) {
+    if let Some(obj) = rv.resolve_object(noun) {
         ctx.output
             .line(format!("You search the {} but find nothing of interest.", obj.name))
             .await;
@@ -35,11 +37,42 @@ async fn handle_search_object(ctx: Arc<CmdCtx>, noun: &NounPhrase) -> anyhow::Re
 }
 
 async fn handle_search_room(ctx: Arc<CmdCtx>) -> anyhow::Result<()> {
-    let _rv = ctx.room_view()?;
+    let cursor = ctx.cursor()?;
+    let account = ctx.account()?;
+    let rooms = &ctx.registry.services.room;
 
-    ctx.output
-        .line("You search the area but find nothing of interest.")
-        .await;
+    match rooms.search_room(&cursor, account.perception).await? {
+        SearchOutcome::CoolingDown(secs) => {
+            ctx.output
+                .line(format!(
+                    "You have just searched here. Give it {} more seconds before searching again.",
+                    secs
+                ))
+                .await;
+            return Ok(());
+        }
+        SearchOutcome::Searched { found } if found.is_empty() => {
+            ctx.output
+                .line("You search the area but find nothing of interest.")
+                .await;
+        }
+        SearchOutcome::Searched { found } => {
+            for short in found {
+                ctx.output
+                    .line(format!("Your search turns up something: {}", short))
+                    .await;
+            }
+            // Found objects are revealed in the player's state, show them from now on
+            let rv = rooms
+                .build_room_view(cursor.realm_id, cursor.account_id, cursor.room_id)
+                .await?;
+            ctx.sess.write().replace_room(rv);
+        }
+    }
+
+    if let Some(hint) = rooms.hint_consider(&cursor, "search").await? {
+        ctx.output.line(hint).await;
+    }
 
     Ok(())
 }
@@ -0,0 +1,76 @@
+//! settings [list]                  show all your settings
+//! settings get <name>              show one setting
+//! settings set <name> <value>      change a setting
+//! settings reset <name>            put a setting back to its default
+//!
+//! Settings are stored on the account and apply to every session.
+
+use crate::commands::{CmdCtx, CommandContext, CommandResult};
+use crate::error::DomainError;
+use crate::models::settings::SETTINGS;
+use crate::renderer::prompt::PROMPT_CODES;
+use crate::util::args::words_after;
+
+const USAGE: &str =
+    "Usage: settings [list] | settings get <name> | settings set <name> <value> | settings reset <name>";
+
+pub async fn settings(cmd: CommandContext) -> CommandResult {
+    let CommandContext { ctx, intent, raw } = cmd;
+    let args: Vec<&str> = intent.args.iter().skip(1).map(String::as_str).collect();
+    let mut settings = ctx.account()?.settings.clone();
+
+    let changed = match args.as_slice() {
+        [] | ["list"] => {
+            let mut out = "Your settings:".to_string();
+            for (name, about) in SETTINGS {
+                out.push_str(&format!("\n  {:<10} {:<20} {}", name, settings.get(name)?, about));
+            }
+            let codes: Vec<String> = PROMPT_CODES
+                .iter()
+                .map(|(code, _, about)| format!("%{} {}", code, about))
+                .collect();
+            out.push_str(&format!("\nPrompt codes: {}.", codes.join(", ")));
+            out.push_str("\nChange one with 'settings set <name> <value>'.");
+            ctx.output.system(out).await;
+            return Ok(());
+        }
+        ["get", name] => {
+            match settings.get(name) {
+                Ok(value) => ctx.output.system(format!("{} is '{}'.", name, value)).await,
+                Err(e) => report(&ctx, e).await?,
+            }
+            return Ok(());
+        }
+        ["set", name, _, ..] => settings.set(name, words_after(&raw, 3)).map(|_| *name),
+        ["reset", name] => settings.reset(name).map(|_| *name),
+        _ => {
+            ctx.output.system(USAGE).await;
+            return Ok(());
+        }
+    };
+
+    let name = match changed {
+        Ok(name) => name,
+        Err(e) => return report(&ctx, e).await,
+    };
+    let account_id = ctx.account_id()?;
+    ctx.registry
+        .services
+        .account
+        .save_settings(account_id, &settings)
+        .await?;
+    let value = settings.get(name)?;
+    ctx.sess.write().set_settings(settings);
+    ctx.output.system(format!("{} set to '{}'.", name, value)).await;
+    Ok(())
+}
+
+async fn report(ctx: &CmdCtx, e: DomainError) -> CommandResult {
+    match e {
+        DomainError::Validation { message, .. } => {
+            ctx.output.system(format!("Cannot do that: {}.", message)).await;
+            Ok(())
+        }
+        e => Err(e.into()),
+    }
+}
@@ -0,0 +1,129 @@
+//! @spectate                 show who you watch and who watches you
+//! @spectate <player>        mirror the output of a player (blueprint owners and admins)
+//! @spectate stop
+//! @spectate allow|deny      allow or refuse builders spectating you
+
+use crate::commands::{CommandContext, CommandResult};
+
+/// Feature flag that must be on in the target's realm before builders can spectate there
+const SPECTATORS_FEATURE: &str = "spectators";
+
+pub async fn spectate(cmd: CommandContext) -> CommandResult {
+    let CommandContext { ctx, intent, .. } = cmd;
+    let account = ctx.account()?;
+    let registry = &ctx.registry;
+
+    match intent.args.get(1).map(String::as_str) {
+        None => {
+            let mut out = match registry.spectated_by(account.id) {
+                Some(t) => format!("[spectate] you are watching {}.", t.account.username),
+                None => "[spectate] you are not watching anyone.".to_string(),
+            };
+            let watchers: Vec<String> = registry
+                .spectators_of(account.id)
+                .into_iter()
+                .map(|p| p.account.username.clone())
+                .collect();
+            if !watchers.is_empty() {
+                out.push_str(&format!("\n[spectate] watching you: {}", watchers.join(", ")));
+            }
+            let allows = ctx.sess.read().allows_spectators();
+            out.push_str(&format!(
+                "\n[spectate] builders may {}spectate you.",
+                if allows { "" } else { "not " }
+            ));
+            ctx.output.system(out).await;
+        }
+        Some("stop") => match registry.stop_spectating(account.id) {
+            Some(target) => {
+                ctx.output
+                    .system(format!("[spectate] stopped watching {}.", target.account.username))
+                    .await;
+                target
+                    .output
+                    .system(format!("[spectate] {} stopped watching you.", account.username))
+                    .await;
+            }
+            None => ctx.output.system("[spectate] you are not watching anyone.").await,
+        },
+        Some("allow") => {
+            ctx.sess.write().set_allow_spectators(true);
+            ctx.output.system("[spectate] builders may spectate you.").await;
+        }
+        Some("deny") => {
+            ctx.sess.write().set_allow_spectators(false);
+            for p in registry.spectators_of(account.id) {
+                registry.stop_spectating(p.account.id);
+                p.output
+                    .system(format!("[spectate] {} no longer allows spectators.", account.username))
+                    .await;
+            }
+            ctx.output
+                .system("[spectate] builders may no longer spectate you.")
+                .await;
+        }
+        Some(name) => {
+            let Some(me) = registry.connected.get(&account.id).map(|e| e.value().clone()) else {
+                return Ok(());
+            };
+            let Some(target) = registry.connected_by_name(name) else {
+                ctx.output.system(format!("[spectate] {} is not online.", name)).await;
+                return Ok(());
+            };
+            if target.account.id == account.id {
+                ctx.output.system("[spectate] you cannot spectate yourself.").await;
+                return Ok(());
+            }
+
+            // Admins may always watch, builders only players in realms of their own blueprints,
+            // when the realm has spectating enabled and the player has not opted out.
+            if !account.is_admin() {
+                let Some(cursor) = target.sess.read().get_cursor() else {
+                    ctx.output
+                        .system(format!("[spectate] {} is not in a realm.", name))
+                        .await;
+                    return Ok(());
+                };
+                let bp = registry.services.blueprint.get_by_id(cursor.realm.bp_id).await?;
+                if bp.owner_id != account.id {
+                    ctx.output
+                        .system("[spectate] you can only spectate players in realms of your own blueprints.")
+                        .await;
+                    return Ok(());
+                }
+                if !registry.features.enabled(SPECTATORS_FEATURE, cursor.realm_id).await? {
+                    ctx.output
+                        .system("[spectate] spectating is not enabled in this realm.")
+                        .await;
+                    return Ok(());
+                }
+                if !target.sess.read().allows_spectators() {
+                    ctx.output
+                        .system(format!(
+                            "[spectate] {} does not allow spectators.",
+                            target.account.username
+                        ))
+                        .await;
+                    return Ok(());
+                }
+            }
+
+            registry.start_spectating(&me, &target);
+            ctx.output
+                .system(format!(
+                    "[spectate] now watching {}. Use '@spectate stop' to stop.",
+                    target.account.username
+                ))
+                .await;
+            target
+                .output
+                .system(format!(
+                    "[spectate] {} is now watching your session. Use '@spectate deny' to refuse.",
+                    account.username
+                ))
+                .await;
+        }
+    }
+
+    Ok(())
+}
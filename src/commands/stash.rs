@@ -0,0 +1,149 @@
+//! deposit [<count>] <item> [in <locker>]
+//! withdraw [<count>] <item> [from <locker>]
+//! withdraw [from <locker>]
+//!
+//! Storage objects (lockers, bank boxes) keep a stash for each player, see
+//! `models::room::Storage`. Without a locker named, the one in the room is used. `withdraw`
+//! without an item lists what the player keeps in it.
+
+use crate::commands::resolve::{Found, Scope, resolve};
+use crate::commands::{CmdCtx, CommandContext, CommandError, CommandResult};
+use crate::input::matcher;
+use crate::input::parser::NounPhrase;
+use crate::models::room::{ResolvedObject, locked_message};
+use crate::services::StashOutcome;
+
+const DEPOSIT_USAGE: &str = "Usage: deposit [<count>] <item> [in <locker>]";
+
+pub async fn deposit(cmd: CommandContext) -> CommandResult {
+    let CommandContext { ctx, intent, .. } = cmd;
+    let Some(np) = intent.direct.as_ref() else {
+        ctx.output.system(DEPOSIT_USAGE).await;
+        return Ok(());
+    };
+    let Some(locker) = locker(&ctx, intent.target.as_ref()).await? else {
+        return Ok(());
+    };
+
+    let cursor = ctx.cursor()?;
+    let Some(Found::Carried(item)) = resolve(&ctx, np, Scope::for_verb(&intent.verb)).await? else {
+        ctx.output.line(format!("You are not carrying any {}.", np.head)).await;
+        return Ok(());
+    };
+
+    let outcome = ctx
+        .registry
+        .services
+        .inventory
+        .deposit(cursor.account_id, &locker, &item, np.quantity)
+        .await?;
+    let msg = match outcome {
+        StashOutcome::Stored(0) => format!("You are not carrying any {}.", np.head),
+        StashOutcome::Stored(n) => format!("You put {} in the {}.", item.counted_text(n), locker.name),
+        StashOutcome::Full { capacity } => format!(
+            "The {} is full. It keeps {} things for you; withdraw something first.",
+            locker.name, capacity
+        ),
+        StashOutcome::Equipped => format!("You keep {}; you have it equipped.", item.short),
+        StashOutcome::NotEmpty => format!("Empty {} first.", item.short),
+    };
+    ctx.output.line(msg).await;
+    Ok(())
+}
+
+pub async fn withdraw(cmd: CommandContext) -> CommandResult {
+    let CommandContext { ctx, intent, .. } = cmd;
+    let Some(locker) = locker(&ctx, intent.target.as_ref()).await? else {
+        return Ok(());
+    };
+
+    let cursor = ctx.cursor()?;
+    let inventory = &ctx.registry.services.inventory;
+    let stash = inventory.get_stash(cursor.realm_id, cursor.account_id, &locker).await?;
+
+    let Some(np) = intent.direct.as_ref() else {
+        if stash.is_empty() {
+            ctx.output
+                .line(format!("You keep nothing in the {}.", locker.name))
+                .await;
+            return Ok(());
+        }
+        let capacity = locker.storage.map_or(0, |st| st.capacity);
+        ctx.output
+            .line(format!(
+                "In the {} you keep ({} of {} slots):",
+                locker.name,
+                stash.len(),
+                capacity
+            ))
+            .await;
+        for stashed in &stash {
+            ctx.output.line(format!("  {}", stashed.display_text())).await;
+        }
+        return Ok(());
+    };
+
+    let scored = stash.iter().map(|s| (s, s.match_score(np)));
+    let Some(stashed) = matcher::pick(scored, np.ordinal) else {
+        ctx.output
+            .line(format!("You keep no {} in the {}.", np.head, locker.name))
+            .await;
+        return Ok(());
+    };
+
+    let withdrawn = inventory
+        .withdraw(cursor.realm_id, cursor.account_id, stashed, np.quantity)
+        .await?;
+    let text = match withdrawn {
+        0 => format!("You keep no {} in the {}.", np.head, locker.name),
+        1 => format!("You take {} from the {}.", stashed.short, locker.name),
+        n => format!("You take {} (x{}) from the {}.", stashed.short, n, locker.name),
+    };
+    ctx.output.line(text).await;
+    Ok(())
+}
+
+/// The storage object the player named, or else the one in the room. When there is none to use
+/// the player is told why and None is returned.
+async fn locker(ctx: &CmdCtx, named: Option<&NounPhrase>) -> Result<Option<ResolvedObject>, CommandError> {
+    let rv = ctx.room_view()?;
+    let obj = match named {
+        Some(np) => match rv.resolve_object(np) {
+            Some(obj) if obj.storage.is_some() => obj,
+            Some(obj) => {
+                ctx.output
+                    .line(format!("You can't keep things in the {}.", obj.name))
+                    .await;
+                return Ok(None);
+            }
+            None => {
+                ctx.output.line(format!("There is no {} here.", np.head)).await;
+                return Ok(None);
+            }
+        },
+        None => {
+            let mut lockers = rv
+                .objects
+                .iter()
+                .filter(|o| o.storage.is_some() && o.flags.is_visible());
+            match (lockers.next(), lockers.next()) {
+                (Some(obj), None) => obj,
+                (None, _) => {
+                    ctx.output.line("There is nothing here to keep things in.").await;
+                    return Ok(None);
+                }
+                (Some(_), Some(_)) => {
+                    ctx.output.line("Which one? Name the locker you mean.").await;
+                    return Ok(None);
+                }
+            }
+        }
+    };
+
+    if obj.flags.locked {
+        let locked = format!("The {} is locked.", obj.name);
+        ctx.output.line(locked_message(&locked, obj.lock.as_ref())).await;
+        return Ok(None);
+    }
+    Ok(Some(obj.clone()))
+}
@@ -0,0 +1,89 @@
+//! @submissions list
+//! @submissions approve <id> [comment]
+//! @submissions reject <id> <comment>
+
+use crate::commands::{CommandContext, CommandResult};
+use crate::error::DomainError;
+use crate::util::args::words_after;
+
+const USAGE: &str =
+    "Usage: @submissions list | @submissions approve <id> [comment] | @submissions reject <id> <comment>";
+
+pub async fn submissions(cmd: CommandContext) -> CommandResult {
+    let CommandContext { ctx, intent, raw } = cmd;
+    let args: Vec<&str> = intent.args.iter().map(String::as_str).collect();
+
+    match args.as_slice() {
+        [_] | [_, "list"] => {
+            let pending = ctx.registry.services.submission.pending().await?;
+            if pending.is_empty() {
+                ctx.output.system("[submissions] nothing waiting for review.").await;
+                return Ok(());
+            }
+            let mut out = format!("[submissions] {} pending:", pending.len());
+            for s in pending {
+                out.push_str(&format!(
+                    "\n  #{:<5} {} {} v{} \"{}\" by {}",
+                    s.id,
+                    s.submitted_at.format("%Y-%m-%d %H:%M"),
+                    s.bp_key,
+                    s.version,
+                    s.bp_title,
+                    s.submitted_by.as_deref().unwrap_or("(deleted)")
+                ));
+            }
+            ctx.output.system(out).await;
+        }
+        [_, verb @ ("approve" | "reject"), id, ..] => {
+            let Ok(id) = id.trim_start_matches('#').parse::<i64>() else {
+                ctx.output.system(USAGE).await;
+                return Ok(());
+            };
+            let approve = *verb == "approve";
+            let account = ctx.account()?;
+
+            let decided = ctx
+                .registry
+                .services
+                .submission
+                .decide(id, &account, approve, words_after(&raw, 3))
+                .await;
+            let submission = match decided {
+                Ok(Some(s)) => s,
+                Ok(None) => {
+                    ctx.output
+                        .system(format!("[submissions] no pending submission #{}.", id))
+                        .await;
+                    return Ok(());
+                }
+                Err(DomainError::Validation { message, .. }) => {
+                    ctx.output.system(format!("[submissions] {}.", message)).await;
+                    return Ok(());
+                }
+                Err(e) => return Err(e.into()),
+            };
+
+            let verdict = if approve { "approved" } else { "rejected" };
+            ctx.registry
+                .notify_moderators(&format!(
+                    "[submissions] #{} '{}' {} by {}.",
+                    id, submission.bp_key, verdict, account.username
+                ))
+                .await;
+
+            // Offline authors hear about it at their next login
+            let author = ctx
+                .registry
+                .connected
+                .get(&submission.owner_id)
+                .map(|p| p.output.clone());
+            if let Some(output) = author {
+                output.system(submission.decision_message()).await;
+                ctx.registry.services.submission.mark_delivered(id).await?;
+            }
+        }
+        _ => ctx.output.system(USAGE).await,
+    }
+
+    Ok(())
+}
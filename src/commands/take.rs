@@ -1,16 +1,21 @@
-use crate::commands::{CmdCtx, CommandResult};
-use crate::input::parser::Intent;
+use crate::commands::resolve::{Found, Scope, resolve};
+use crate::commands::{CmdCtx, CommandContext, CommandResult};
+use crate::error::AppResult;
 use crate::input::parser::Preposition;
+use crate::models::inventory::{ItemInstance, ItemLocation};
+use crate::services::coins_text;
 use rand::Rng;
 use std::sync::Arc;
 
-pub async fn take(ctx: Arc<CmdCtx>, intent: Intent) -> CommandResult {
-    if intent.args.is_empty() {
-        ctx.output.system("Usage: take <item> [from <container>]").await;
+pub async fn take(cmd: CommandContext) -> CommandResult {
+    let CommandContext { ctx, intent, .. } = cmd;
+    let Some(np) = intent.direct.as_ref() else {
+        ctx.output
+            .system("Usage: take [<count>] <item> [from <container>]")
+            .await;
         return Ok(());
-    }
-
-    let what = &intent.direct.as_ref().unwrap().head;
+    };
+    let what = &np.head;
 
     // Case 1: "take X from Y" - taking from a container
     if let Some(Preposition::From) = intent.preposition
@@ -20,20 +25,17 @@ pub async fn take(ctx: Arc<CmdCtx>, intent: Intent) -> CommandResult {
     }
 
     // Case 2: Regular "take X" - from room or ground
-    // (Your existing logic here)
-
-    let Ok(room_view) = ctx.room_view() else {
+    if !ctx.has_cursor() {
         ctx.output.system("You are not in a world.").await;
         return Ok(());
-    };
+    }
 
-    // Check if this thing exists as an object in the room
-    let is_known_object = room_view
-        .objects
-        .iter()
-        .any(|obj| obj.name.to_ascii_lowercase().contains(what));
+    let found = resolve(&ctx, np, Scope::for_verb(&intent.verb)).await?;
+    if let Some(Found::Floor(item)) = found {
+        return Ok(take_item(&ctx, &item, np.quantity).await?);
+    }
 
-    if is_known_object {
+    if found.is_some() {
         // It exists but can't be taken
         let messages = [
             "You can't take that.",
@@ -90,6 +92,42 @@ pub async fn take(ctx: Arc<CmdCtx>, intent: Intent) -> CommandResult {
     Ok(())
 }
 
+/// Picks up an item lying in the room: `quantity` of a stack, or all of it. Coins go into the
+/// balance instead of the inventory.
+pub async fn take_item(ctx: &CmdCtx, item: &ItemInstance, quantity: Option<u32>) -> AppResult<()> {
+    let cursor = ctx.cursor()?;
+    if item.is_coin {
+        let deposit = ctx
+            .registry
+            .services
+            .currency
+            .deposit_coins(cursor.account_id, item, quantity)
+            .await?;
+        let line = match deposit {
+            Some(d) => format!(
+                "You pick up {}. You now have {}.",
+                item.counted_text(d.coins),
+                coins_text(d.balance)
+            ),
+            None => format!("Someone beat you to {}.", item.short),
+        };
+        ctx.output.line(line).await;
+        return Ok(());
+    }
+
+    let taken = ctx
+        .registry
+        .services
+        .inventory
+        .move_quantity(item, quantity, ItemLocation::Player(cursor.account_id))
+        .await?;
+    ctx.output.line(format!("You take {}.", item.counted_text(taken))).await;
+    if item.stackable && quantity.is_some_and(|n| i64::from(n) > i64::from(taken)) {
+        ctx.output.line(format!("There were only {}.", taken)).await;
+    }
+    Ok(())
+}
+
 async fn take_from_container(ctx: Arc<CmdCtx>, item_name: &str, container_name: &str) -> CommandResult {
     let Ok(room_view) = ctx.room_view() else {
         ctx.output.system("You are not in a world.").await;
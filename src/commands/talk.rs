@@ -0,0 +1,190 @@
+//! talk to <npc>             start a conversation with an NPC
+//! <number>                  answer with one of the numbered options
+//! bye                       walk away from the conversation
+//!
+//! NPCs are objects with a `dialogue` tree (see `models::dialogue`). Where the player is in the
+//! conversation is kept in the session; it ends when the tree does, or when the player leaves.
+
+use crate::commands::resolve::{Found, Scope, resolve};
+use crate::commands::{CmdCtx, CommandContext, CommandError, CommandResult, fallback, widget};
+use crate::lua::ScriptHook;
+use crate::models::dialogue::{DialogueNode, DialogueOption, render_node};
+use crate::models::inventory::ItemLocation;
+use crate::models::room::ResolvedObject;
+use crate::state::session::Conversation;
+use std::collections::HashSet;
+use std::sync::Arc;
+
+pub async fn talk(cmd: CommandContext) -> CommandResult {
+    let CommandContext { ctx, intent, .. } = cmd;
+    let Some(noun) = intent.target.as_ref().or(intent.direct.as_ref()) else {
+        ctx.output.system("Talk to whom?").await;
+        return Ok(());
+    };
+
+    let Some(Found::Npc(npc)) = resolve(&ctx, noun, Scope::for_verb(&intent.verb)).await? else {
+        let rv = ctx.room_view()?;
+        let handled = rv.scripts.get(&ScriptHook::OnCommand).is_some()
+            && fallback::room_command(ctx.clone(), intent.clone()).await?;
+        if !handled {
+            ctx.output.line(format!("The {} doesn't answer.", noun.head)).await;
+        }
+        return Ok(());
+    };
+
+    let start = npc.dialogue.as_ref().map(|d| d.start.clone()).unwrap_or_default();
+    show_node(&ctx, &npc, &start).await
+}
+
+/// A bare number typed during a conversation is an answer
+pub fn answer_choice(raw: &str) -> Option<usize> {
+    raw.trim().parse().ok()
+}
+
+/// Whether the typed line ends the conversation
+pub fn is_goodbye(raw: &str) -> bool {
+    matches!(raw.trim().to_ascii_lowercase().as_str(), "bye" | "goodbye")
+}
+
+/// Answers the current dialogue node with option `choice` (1-based). Returns false when there is
+/// no conversation going on here, so the line is a normal command.
+pub async fn answer(ctx: Arc<CmdCtx>, choice: usize) -> Result<bool, CommandError> {
+    let Some((conv, npc)) = current(&ctx)? else {
+        return Ok(false);
+    };
+    let Some(node) = npc.dialogue.as_ref().and_then(|d| d.node(&conv.node)) else {
+        ctx.sess.write().set_conversation(None);
+        return Ok(false);
+    };
+
+    let options = available(&ctx, &npc, node).await?;
+    let Some(option) = choice.checked_sub(1).and_then(|i| options.get(i)) else {
+        ctx.output
+            .line(format!("Answer with a number from 1 to {}.", options.len()))
+            .await;
+        return Ok(true);
+    };
+
+    ctx.output.line(format!("You: \"{}\"", option.text)).await;
+    widget::apply_effects(&ctx, &option.effects).await?;
+    if let Some(item) = &option.give {
+        give(&ctx, &npc, item).await?;
+    }
+
+    match &option.goto {
+        Some(next) => {
+            // Effects may have changed the NPC's state, so look at it again
+            let rv = ctx.room_view()?;
+            if let Some(npc) = rv.objects.iter().find(|o| o.key == conv.npc) {
+                show_node(&ctx, npc, next).await?;
+            }
+        }
+        None => end(&ctx, &npc.name).await,
+    }
+    Ok(true)
+}
+
+/// Walks away from the conversation. Returns false when there is none.
+pub async fn goodbye(ctx: Arc<CmdCtx>) -> Result<bool, CommandError> {
+    let Some((_, npc)) = current(&ctx)? else {
+        return Ok(false);
+    };
+    end(&ctx, &npc.name).await;
+    Ok(true)
+}
+
+/// The conversation in progress and the NPC, when the player is still in the NPC's room
+fn current(ctx: &Arc<CmdCtx>) -> Result<Option<(Conversation, ResolvedObject)>, CommandError> {
+    let Some(conv) = ctx.sess.read().conversation() else {
+        return Ok(None);
+    };
+    let rv = ctx.room_view()?;
+    match rv.objects.iter().find(|o| o.key == conv.npc) {
+        Some(npc) if rv.blueprint.id == conv.room_id => Ok(Some((conv, npc.clone()))),
+        _ => {
+            ctx.sess.write().set_conversation(None);
+            Ok(None)
+        }
+    }
+}
+
+async fn show_node(ctx: &Arc<CmdCtx>, npc: &ResolvedObject, node_id: &str) -> CommandResult {
+    let Some(node) = npc.dialogue.as_ref().and_then(|d| d.node(node_id)) else {
+        tracing::warn!(npc = %npc.key, node = %node_id, "dialogue: unknown node");
+        end(ctx, &npc.name).await;
+        return Ok(());
+    };
+
+    let options = available(ctx, npc, node).await?;
+    ctx.output.line(render_node(&npc.name, node, &options)).await;
+    if options.is_empty() {
+        end(ctx, &npc.name).await;
+        return Ok(());
+    }
+
+    ctx.sess.write().set_conversation(Some(Conversation {
+        room_id: ctx.room_id()?,
+        npc: npc.key.clone(),
+        node: node_id.to_string(),
+    }));
+    Ok(())
+}
+
+/// The options whose conditions hold for the player
+async fn available<'a>(
+    ctx: &Arc<CmdCtx>,
+    npc: &ResolvedObject,
+    node: &'a DialogueNode,
+) -> Result<Vec<&'a DialogueOption>, CommandError> {
+    let realm_id = ctx.realm_id()?;
+    let account_id = ctx.account_id()?;
+    let inventory = &ctx.registry.services.inventory;
+
+    let mut carried = HashSet::new();
+    for item in node
+        .options
+        .iter()
+        .filter_map(|o| o.when.as_ref())
+        .flat_map(|c| c.items())
+    {
+        if inventory.has_item_by_key(realm_id, account_id, item).await? {
+            carried.insert(item.to_string());
+        }
+    }
+
+    Ok(node.available(&npc.kv, |i| carried.contains(i)))
+}
+
+async fn give(ctx: &Arc<CmdCtx>, npc: &ResolvedObject, item_key: &str) -> CommandResult {
+    let inventory = &ctx.registry.services.inventory;
+    let realm_id = ctx.realm_id()?;
+    inventory
+        .spawn_item(realm_id, item_key, ItemLocation::Player(ctx.account_id()?), 1)
+        .await?;
+    let item = inventory.get_item_by_key(realm_id, item_key).await?;
+    ctx.output
+        .line(format!("The {} hands you {}.", npc.name, item.name))
+        .await;
+    Ok(())
+}
+
+async fn end(ctx: &Arc<CmdCtx>, npc: &str) {
+    ctx.sess.write().set_conversation(None);
+    ctx.output
+        .line(format!("The {} turns back to what they were doing.", npc))
+        .await;
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn t_answer_input() {
+        assert_eq!(answer_choice(" 2 "), Some(2));
+        assert_eq!(answer_choice("2 north"), None);
+        assert_eq!(answer_choice("look"), None);
+        assert!(is_goodbye("Bye"));
+        assert!(!is_goodbye("bye bye"));
+    }
+}
@@ -0,0 +1,66 @@
+//! use <terminal>            switch on an in-game computer
+//! .exit                     step away from it again
+//!
+//! Objects with an on_terminal script are terminals. While one is in use, every line the player
+//! types goes to the script as is, instead of through the parser. The script is first called
+//! without a line to draw its screen, and keeps the terminal open until it returns false.
+
+use crate::commands::{CmdCtx, CommandResult};
+use crate::models::room::ResolvedObject;
+use crate::state::interactive::InteractiveState;
+use std::sync::Arc;
+
+/// Switches the terminal on and hands the player's input to it
+pub async fn open(ctx: Arc<CmdCtx>, obj: &ResolvedObject) -> CommandResult {
+    ctx.output
+        .system(format!("You use the {}. Type '.exit' to step away.", obj.name))
+        .await;
+    if !ctx
+        .registry
+        .services
+        .room
+        .lua_on_terminal(ctx.clone(), obj, None)
+        .await?
+    {
+        return Ok(());
+    }
+
+    ctx.set_interactive(InteractiveState::Terminal {
+        object: obj.key.clone(),
+        name: obj.name.clone(),
+    });
+    ctx.output.set_prompt(format!("{}> ", obj.name)).await;
+    Ok(())
+}
+
+/// A line typed while the terminal `object` (shown as `name`) is in use
+pub async fn input(ctx: Arc<CmdCtx>, object: String, name: String, raw: &str) -> CommandResult {
+    let line = raw.trim();
+    if matches!(line, ".exit" | ".quit" | ".q") {
+        return close(&ctx, &name).await;
+    }
+
+    // Moved away (or the object is gone) since switching it on
+    let rv = ctx.room_view()?;
+    let Some(obj) = rv.objects.iter().find(|o| o.key == object) else {
+        return close(&ctx, &name).await;
+    };
+
+    if !ctx
+        .registry
+        .services
+        .room
+        .lua_on_terminal(ctx.clone(), obj, Some(line))
+        .await?
+    {
+        return close(&ctx, &name).await;
+    }
+    Ok(())
+}
+
+async fn close(ctx: &Arc<CmdCtx>, name: &str) -> CommandResult {
+    ctx.clear_interactive();
+    ctx.output.system(format!("You step away from the {}.", name)).await;
+    ctx.output.restore_prompt().await;
+    Ok(())
+}
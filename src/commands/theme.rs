@@ -0,0 +1,51 @@
+//! theme            show your theme and a preview of all themes
+//! theme <name>     switch to another theme
+
+use crate::commands::{CommandContext, CommandResult};
+use crate::error::DomainError;
+use crate::renderer::theme::Theme;
+use crate::renderer::{RenderVars, render_template};
+
+const PREVIEW: &str =
+    "{c:@title}Room title{c}  {c:@items}items{c}  {c:@exits}exits{c}  {c:@object}object{c}  {c:@hint}Hint{c}";
+
+pub async fn theme(cmd: CommandContext) -> CommandResult {
+    let CommandContext { ctx, intent, .. } = cmd;
+    let Some(name) = intent.args.get(1) else {
+        let current = ctx.account()?.settings.theme;
+        let mut out = format!("Your theme is '{}'. Switch with 'theme <name>':", current);
+        for theme in Theme::ALL {
+            let vars = RenderVars {
+                theme,
+                ..RenderVars::default()
+            };
+            out.push_str(&format!(
+                "\n  {:<9} {}",
+                theme.as_str(),
+                render_template(PREVIEW, &vars, 0)
+            ));
+        }
+        ctx.output.system(out).await;
+        return Ok(());
+    };
+
+    let theme: Theme = match name.parse() {
+        Ok(theme) => theme,
+        Err(DomainError::Validation { message, .. }) => {
+            ctx.output.system(format!("Cannot do that: {}.", message)).await;
+            return Ok(());
+        }
+        Err(e) => return Err(e.into()),
+    };
+    let account_id = ctx.account_id()?;
+    let mut settings = ctx.account()?.settings.clone();
+    settings.theme = theme;
+    ctx.registry
+        .services
+        .account
+        .save_settings(account_id, &settings)
+        .await?;
+    ctx.sess.write().set_settings(settings);
+    ctx.output.system(format!("Theme set to '{}'.", theme)).await;
+    Ok(())
+}
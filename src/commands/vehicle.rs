@@ -0,0 +1,228 @@
+//! board <thing>             step into a vehicle (elevator, shuttle) docked here, or call it
+//! disembark                 step out of a docked vehicle
+//! press <button>            send the vehicle you are in to a stop (or work a widget elsewhere)
+//!
+//! Vehicles are rooms with a `vehicle` block; objects with `board` lead into them. Outside a
+//! vehicle, and for things that are not boardable, the commands go to the room's script.
+
+use crate::commands::{CmdCtx, CommandContext, CommandError, CommandResult, fallback, widget};
+use crate::input::parser::{Intent, NounPhrase};
+use crate::lua::ScriptHook;
+use crate::models::types::RoomId;
+use crate::models::vehicle::Vehicle;
+use crate::state::{presence, vehicles};
+use std::sync::Arc;
+
+pub async fn board(cmd: CommandContext) -> CommandResult {
+    let CommandContext { ctx, intent, .. } = cmd;
+    let Some(noun) = intent.direct.as_ref() else {
+        ctx.output.system("Board what?").await;
+        return Ok(());
+    };
+
+    let rv = ctx.room_view()?;
+    let Some(key) = rv.resolve_object(noun).and_then(|o| o.board.clone()) else {
+        if !script_handled(&ctx, &intent).await? {
+            ctx.output.line(format!("You can't board the {}.", noun.head)).await;
+        }
+        return Ok(());
+    };
+
+    let services = &ctx.registry.services;
+    let realm_id = ctx.realm_id()?;
+    let Some(vehicle_room_id) = services.room.get_room_id_by_key(realm_id, &key).await? else {
+        ctx.output
+            .system(format!("The vehicle room '{}' does not exist.", key))
+            .await;
+        return Ok(());
+    };
+    let vehicle_room = services.room.blueprint_room(realm_id, vehicle_room_id).await?;
+    let Some(vehicle) = &vehicle_room.vehicle else {
+        ctx.output.system(format!("The room '{}' is not a vehicle.", key)).await;
+        return Ok(());
+    };
+    let Some(here) = vehicle.stop_in_room(&rv.blueprint.key) else {
+        ctx.output
+            .line(format!("The {} doesn't stop here.", vehicle_room.title))
+            .await;
+        return Ok(());
+    };
+
+    let state = services.room.vehicle_state(realm_id, vehicle_room_id).await?;
+    if state.docked_at(here) {
+        ctx.output
+            .line(format!("You step into the {}.", vehicle_room.title))
+            .await;
+        return move_to(&ctx, vehicle_room_id).await;
+    }
+    if state.to == Some(here) {
+        ctx.output
+            .line(format!("The {} is on its way here.", vehicle_room.title))
+            .await;
+        return Ok(());
+    }
+    if state.in_transit() {
+        ctx.output
+            .line(format!(
+                "The {} is not here. It is on its way elsewhere.",
+                vehicle_room.title
+            ))
+            .await;
+        return Ok(());
+    }
+
+    // Docked at another stop: call it over
+    vehicles::depart(&ctx.registry, realm_id, &vehicle_room, here).await?;
+    ctx.output
+        .line(format!("You call the {}. It is on its way.", vehicle_room.title))
+        .await;
+    Ok(())
+}
+
+pub async fn disembark(cmd: CommandContext) -> CommandResult {
+    let CommandContext { ctx, intent, .. } = cmd;
+    let rv = ctx.room_view()?;
+    let Some(vehicle) = &rv.blueprint.vehicle else {
+        if !script_handled(&ctx, &intent).await? {
+            ctx.output.line("You are not aboard anything.").await;
+        }
+        return Ok(());
+    };
+
+    let realm_id = ctx.realm_id()?;
+    let state = ctx
+        .registry
+        .services
+        .room
+        .vehicle_state(realm_id, rv.blueprint.id)
+        .await?;
+    if state.in_transit() {
+        ctx.output
+            .line(format!("You can't get out while the {} is moving.", rv.blueprint.title))
+            .await;
+        return Ok(());
+    }
+    let Some(stop) = vehicle.stops.get(state.stop) else {
+        ctx.output.line("The doors won't open.").await;
+        return Ok(());
+    };
+    let Some(stop_room_id) = ctx
+        .registry
+        .services
+        .room
+        .get_room_id_by_key(realm_id, &stop.room)
+        .await?
+    else {
+        ctx.output
+            .system(format!("The stop room '{}' does not exist.", stop.room))
+            .await;
+        return Ok(());
+    };
+
+    ctx.output
+        .line(format!("You step out of the {}.", rv.blueprint.title))
+        .await;
+    move_to(&ctx, stop_room_id).await
+}
+
+pub async fn press(cmd: CommandContext) -> CommandResult {
+    let CommandContext { ctx, intent, .. } = cmd;
+    let rv = ctx.room_view()?;
+    let Some(vehicle) = &rv.blueprint.vehicle else {
+        if !widget::handle(ctx.clone(), &intent).await? && !script_handled(&ctx, &intent).await? {
+            ctx.output.line("Nothing happens.").await;
+        }
+        return Ok(());
+    };
+
+    let Some(to) = intent.direct.as_ref().and_then(|np| stop_for(vehicle, np)) else {
+        if !script_handled(&ctx, &intent).await? {
+            ctx.output
+                .line(format!("The buttons read: {}.", vehicle.buttons()))
+                .await;
+        }
+        return Ok(());
+    };
+
+    let realm_id = ctx.realm_id()?;
+    let state = ctx
+        .registry
+        .services
+        .room
+        .vehicle_state(realm_id, rv.blueprint.id)
+        .await?;
+    if state.in_transit() {
+        ctx.output
+            .line(format!("The {} is already moving.", rv.blueprint.title))
+            .await;
+        return Ok(());
+    }
+    if state.stop == to {
+        ctx.output
+            .line(format!("You are already at {}.", vehicle.stops[to].name()))
+            .await;
+        return Ok(());
+    }
+
+    if vehicles::depart(&ctx.registry, realm_id, &rv.blueprint, to)
+        .await?
+        .is_none()
+    {
+        ctx.output.line("Nothing happens.").await;
+    }
+    Ok(())
+}
+
+/// The stop a pressed button refers to: "press 2", "press button 2" or "press lobby button"
+fn stop_for(vehicle: &Vehicle, np: &NounPhrase) -> Option<usize> {
+    std::iter::once(&np.head)
+        .chain(np.adjectives.iter().rev())
+        .filter(|w| w.as_str() != "button")
+        .find_map(|w| vehicle.stop_by_button(w))
+}
+
+/// Offers the command to the room's on_command script, when it has one
+async fn script_handled(ctx: &Arc<CmdCtx>, intent: &Intent) -> Result<bool, CommandError> {
+    if ctx.room_view()?.scripts.get(&ScriptHook::OnCommand).is_none() {
+        return Ok(false);
+    }
+    fallback::room_command(ctx.clone(), intent.clone()).await
+}
+
+/// Moves the player like `go` does, and shows the new room
+async fn move_to(ctx: &Arc<CmdCtx>, room_id: RoomId) -> CommandResult {
+    let room = &ctx.registry.services.room;
+    let c = ctx.cursor()?;
+
+    if let Err(e) = room.exit_room(ctx.clone()).await {
+        ctx.output.line(format!("You can't seem to leave: {}", e)).await;
+        return Ok(());
+    }
+    let new_cursor = room.create_cursor(c.realm_id, room_id, c.account_id).await?;
+    ctx.sess.write().set_cursor(Some(new_cursor.clone()));
+    room.enter_room(ctx.clone(), &new_cursor).await?;
+    presence::announce_departure(&ctx.registry, &c, &c.account.username, None).await;
+    presence::announce_arrival(&ctx.registry, &new_cursor, &c.account.username, None).await;
+    presence::show_room(ctx, true).await?;
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::input::parser::parse_command;
+
+    #[test]
+    fn t_stop_for() {
+        let vehicle: Vehicle = serde_yaml::from_str(
+            "stops:\n  - { button: '1', room: lobby, name: Lobby }\n  - { button: '2', room: engineering }\n",
+        )
+        .unwrap();
+        let stop = |input: &str| stop_for(&vehicle, parse_command(input).direct.as_ref().unwrap());
+
+        assert_eq!(stop("press 2"), Some(1));
+        assert_eq!(stop("press button 2"), Some(1));
+        assert_eq!(stop("press lobby button"), Some(0));
+        assert_eq!(stop("press 7"), None);
+    }
+}
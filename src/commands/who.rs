@@ -1,7 +1,7 @@
-use crate::commands::{CmdCtx, CommandResult};
-use std::sync::Arc;
+use crate::commands::{CommandContext, CommandResult};
 
-pub async fn who(ctx: Arc<CmdCtx>) -> CommandResult {
+pub async fn who(cmd: CommandContext) -> CommandResult {
+    let CommandContext { ctx, .. } = cmd;
     let list = ctx.registry.who().await;
     if list.is_empty() {
         ctx.output.system("No one is online.").await;
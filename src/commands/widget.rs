@@ -0,0 +1,239 @@
+//! enter <code> [on <keypad>]     type a code into a keypad widget
+//! pull <lever> [on <bank>]       flip a lever of a lever bank (also push, flip, toggle, switch)
+//! select <n|label> [on <menu>]   choose an option of a terminal menu (also choose)
+//! use <widget>                   show the keypad, levers or menu
+//!
+//! Widgets are objects with a `widget` block (see `models::widget`). They are offered the command
+//! before the room's on_command script; anything that does not clearly address a widget is left to
+//! the script.
+
+use crate::commands::{CmdCtx, CommandError, effects};
+use crate::input::parser::{Intent, NounPhrase};
+use crate::models::room::{ResolvedObject, RoomView};
+use crate::models::widget::{LEVERS_UP_KEY, SOLVED_KEY, Widget, WidgetEffects, flip_lever, levers_solved};
+use serde_json::Value;
+use std::sync::Arc;
+
+const KEYPAD_VERBS: &[&str] = &["enter", "type", "input", "key", "punch", "press"];
+const LEVER_VERBS: &[&str] = &["pull", "push", "flip", "toggle", "switch", "throw"];
+const MENU_VERBS: &[&str] = &["select", "choose", "press"];
+
+/// Handles the command when it addresses a widget in the room. Returns false when it does not.
+pub async fn handle(ctx: Arc<CmdCtx>, intent: &Intent) -> Result<bool, CommandError> {
+    let verb = intent.verb.as_str();
+    let rv = ctx.room_view()?;
+
+    if KEYPAD_VERBS.contains(&verb)
+        && let Some((obj, code)) = find_keypad(&rv, intent)
+    {
+        enter_code(&ctx, obj, &code).await?;
+        return Ok(true);
+    }
+    if LEVER_VERBS.contains(&verb)
+        && let Some((obj, lever)) = find_lever(&rv, intent)
+    {
+        pull_lever(&ctx, obj, lever).await?;
+        return Ok(true);
+    }
+    if MENU_VERBS.contains(&verb)
+        && let Some((obj, choice)) = find_menu(&rv, intent)
+    {
+        select_option(&ctx, obj, &choice).await?;
+        return Ok(true);
+    }
+    Ok(false)
+}
+
+/// `use <widget>`: shows how to work it. Returns false when the thing is not a widget.
+pub async fn use_widget(ctx: Arc<CmdCtx>, intent: &Intent) -> Result<bool, CommandError> {
+    let rv = ctx.room_view()?;
+    let Some((obj, widget)) = intent
+        .direct
+        .as_ref()
+        .and_then(|np| rv.resolve_object(np))
+        .and_then(|o| o.widget.as_ref().map(|w| (o, w)))
+    else {
+        return Ok(false);
+    };
+
+    ctx.output.line(widget.describe(&obj.name, &levers_up(obj))).await;
+    Ok(true)
+}
+
+/// A keypad named as the target ("enter 1234 on keypad"), or the only keypad in the room when the
+/// code looks like one ("enter 1234")
+fn find_keypad<'a>(rv: &'a RoomView, intent: &Intent) -> Option<(&'a ResolvedObject, String)> {
+    let is_keypad = |o: &&ResolvedObject| matches!(o.widget, Some(Widget::Keypad { .. }));
+    let obj = match intent.target.as_ref() {
+        Some(np) => rv.resolve_object(np).filter(is_keypad)?,
+        None if intent.direct_raw.is_some() => only_widget(rv, is_keypad)?,
+        None => return None,
+    };
+    let code = intent
+        .direct_raw
+        .clone()
+        .or_else(|| intent.direct.as_ref().map(|np| np.raw.clone()))?;
+    Some((obj, code))
+}
+
+/// The lever named in the direct noun ("pull red lever"), on the named bank or the one bank in the
+/// room that has it. None as lever when only the bank itself is named.
+fn find_lever<'a>(rv: &'a RoomView, intent: &Intent) -> Option<(&'a ResolvedObject, Option<&'a str>)> {
+    let np = intent.direct.as_ref()?;
+    let is_bank = |o: &&ResolvedObject| matches!(o.widget, Some(Widget::LeverBank { .. }));
+    let banks: Vec<&ResolvedObject> = match intent.target.as_ref() {
+        Some(t) => vec![rv.resolve_object(t).filter(is_bank)?],
+        None => rv.objects.iter().filter(is_bank).collect(),
+    };
+
+    for bank in &banks {
+        if let Some(lever) = lever_named(bank, np) {
+            return Some((bank, Some(lever)));
+        }
+    }
+    let bank = rv.resolve_object(np).filter(is_bank)?;
+    Some((bank, None))
+}
+
+fn lever_named<'a>(bank: &'a ResolvedObject, np: &NounPhrase) -> Option<&'a str> {
+    let widget = bank.widget.as_ref()?;
+    std::iter::once(&np.raw)
+        .chain(std::iter::once(&np.head))
+        .chain(np.adjectives.iter())
+        .find_map(|w| widget.lever(w))
+}
+
+/// A menu named as the target ("select 2 on terminal"), or the only menu in the room
+fn find_menu<'a>(rv: &'a RoomView, intent: &Intent) -> Option<(&'a ResolvedObject, String)> {
+    let is_menu = |o: &&ResolvedObject| matches!(o.widget, Some(Widget::Menu { .. }));
+    let obj = match intent.target.as_ref() {
+        Some(np) => rv.resolve_object(np).filter(is_menu)?,
+        None => only_widget(rv, is_menu)?,
+    };
+    let choice = intent
+        .direct_raw
+        .clone()
+        .or_else(|| intent.direct.as_ref().map(|np| np.raw.clone()))?;
+    obj.widget.as_ref()?.option(&choice)?;
+    Some((obj, choice))
+}
+
+fn only_widget(rv: &RoomView, pred: impl Fn(&&ResolvedObject) -> bool) -> Option<&ResolvedObject> {
+    let mut found = rv.objects.iter().filter(pred);
+    match (found.next(), found.next()) {
+        (Some(obj), None) => Some(obj),
+        _ => None,
+    }
+}
+
+fn is_solved(obj: &ResolvedObject) -> bool {
+    obj.kv.get(SOLVED_KEY).and_then(Value::as_bool).unwrap_or(false)
+}
+
+fn levers_up(obj: &ResolvedObject) -> Vec<String> {
+    obj.kv
+        .get(LEVERS_UP_KEY)
+        .and_then(|v| serde_json::from_value(v.clone()).ok())
+        .unwrap_or_default()
+}
+
+async fn enter_code(ctx: &Arc<CmdCtx>, obj: &ResolvedObject, code: &str) -> Result<(), CommandError> {
+    let Some(widget @ Widget::Keypad { failure, effects, .. }) = &obj.widget else {
+        return Ok(());
+    };
+    if is_solved(obj) {
+        ctx.output
+            .line(format!("The {} has already accepted its code.", obj.name))
+            .await;
+        return Ok(());
+    }
+    if !widget.code_matches(code) {
+        let msg = failure
+            .clone()
+            .unwrap_or_else(|| format!("The {} beeps: wrong code.", obj.name));
+        ctx.output.line(msg).await;
+        return Ok(());
+    }
+
+    set_state(ctx, obj, SOLVED_KEY, Value::Bool(true)).await?;
+    apply_effects(ctx, effects).await
+}
+
+async fn pull_lever(ctx: &Arc<CmdCtx>, obj: &ResolvedObject, lever: Option<&str>) -> Result<(), CommandError> {
+    let Some(
+        widget @ Widget::LeverBank {
+            levers,
+            solution,
+            effects,
+        },
+    ) = &obj.widget
+    else {
+        return Ok(());
+    };
+    let up = levers_up(obj);
+    let Some(lever) = lever else {
+        ctx.output
+            .line(format!("Which lever? {}", widget.describe(&obj.name, &up)))
+            .await;
+        return Ok(());
+    };
+    if is_solved(obj) {
+        ctx.output.line("The levers are locked in place.").await;
+        return Ok(());
+    }
+
+    let up = flip_lever(levers, &up, lever);
+    let pos = if up.iter().any(|l| l == lever) { "up" } else { "down" };
+    ctx.output.line(format!("You flip the {} lever {}.", lever, pos)).await;
+    set_state(ctx, obj, LEVERS_UP_KEY, Value::from(up.clone())).await?;
+
+    if levers_solved(&up, solution) {
+        set_state(ctx, obj, SOLVED_KEY, Value::Bool(true)).await?;
+        apply_effects(ctx, effects).await?;
+    } else {
+        refresh_view(ctx).await?;
+    }
+    Ok(())
+}
+
+async fn select_option(ctx: &Arc<CmdCtx>, obj: &ResolvedObject, choice: &str) -> Result<(), CommandError> {
+    let Some(option) = obj.widget.as_ref().and_then(|w| w.option(choice)) else {
+        return Ok(());
+    };
+    if option.effects.message.is_none() {
+        ctx.output.line(format!("You select \"{}\".", option.label)).await;
+    }
+    apply_effects(ctx, &option.effects).await
+}
+
+pub(crate) async fn set_state(
+    ctx: &Arc<CmdCtx>,
+    obj: &ResolvedObject,
+    key: &str,
+    val: Value,
+) -> Result<(), CommandError> {
+    ctx.registry
+        .services
+        .room
+        .set_object_state(ctx.realm_id()?, ctx.account_id()?, obj.id, key, &val)
+        .await?;
+    Ok(())
+}
+
+/// Shows the message, locks and unlocks exits and sets object state for the player
+pub(crate) async fn apply_effects(ctx: &Arc<CmdCtx>, effects: &WidgetEffects) -> Result<(), CommandError> {
+    effects::apply(ctx, &effects.to_effects()).await
+}
+
+/// Rebuilds the room view so the changed state shows
+pub(crate) async fn refresh_view(ctx: &Arc<CmdCtx>) -> Result<(), CommandError> {
+    let c = ctx.cursor()?;
+    let rv = ctx
+        .registry
+        .services
+        .room
+        .build_room_view(c.realm_id, c.account_id, c.room_id)
+        .await?;
+    ctx.sess.write().replace_room(rv);
+    Ok(())
+}
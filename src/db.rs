@@ -9,6 +9,7 @@ mod pool;
 pub mod blueprint;
 pub mod characters;
 pub mod loot;
+pub mod realm_directory;
 
 pub mod error;
 pub mod repo;
@@ -17,7 +17,7 @@ impl Db {
                 &[],
             )
             .await?;
-        Ok(row.get(0))
+        Ok(row.get("id"))
     }
 
     pub async fn get_or_create_character(
@@ -37,8 +37,8 @@ impl Db {
             )
             .await?
         {
-            let character_id: CharacterId = row.get(0);
-            let loc: Option<RoomId> = row.get(1);
+            let character_id: CharacterId = row.get("id");
+            let loc: Option<RoomId> = row.get("room_id");
             let loc = if let Some(l) = loc {
                 l
             } else {
@@ -62,7 +62,7 @@ impl Db {
             )
             .await?;
 
-        Ok((row.get(0), row.get(1)))
+        Ok((row.get("id"), row.get("room_id")))
     }
 
     pub async fn move_character(&self, account_id: AccountId, dir: &str) -> DbResult<Option<RoomId>> {
@@ -72,7 +72,7 @@ impl Db {
                 "SELECT c.id, c.room_id
                  FROM characters c
                  WHERE c.account_id=$1
-                 ORDER BY c.id
+                 ORDER BY c.created_at
                  LIMIT 1",
                 &[&account_id],
             )
@@ -80,21 +80,23 @@ impl Db {
         let Some(row) = row else {
             return Ok(None);
         };
-        let character_id: i64 = row.get(0);
-        let cur_room: i64 = row.get(1);
+        let character_id: CharacterId = row.get("id");
+        let Some(cur_room): Option<RoomId> = row.get("room_id") else {
+            return Ok(None);
+        };
 
         let to = client
             .query_opt(
-                "SELECT to_room
-                 FROM exits
-                 WHERE from_room=$1 AND dir=LOWER($2)",
+                "SELECT to_room_id
+                 FROM bp_exits
+                 WHERE from_room_id=$1 AND dir=LOWER($2)",
                 &[&cur_room, &dir],
             )
             .await?;
         let Some(to_row) = to else {
             return Ok(None);
         };
-        let new_room: RoomId = to_row.get(0);
+        let new_room: RoomId = to_row.get("to_room_id");
 
         client
             .execute(
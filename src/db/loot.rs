@@ -1,6 +1,6 @@
 use super::{Db, DbResult};
 use crate::models::account::Account;
-use crate::models::types::RoomId;
+use crate::models::types::{LootId, LootSpawnId, RealmId, RoomId};
 use password_hash::rand_core::{OsRng, RngCore};
 
 impl Db {
@@ -14,7 +14,7 @@ impl Db {
         // NOTE: your original query had a small typo `sFOR` → `FOR`
         let rows = tx
             .query(
-                "SELECT id, room_id, item, qty_min, qty_max, interval_ms, max_instances
+                "SELECT id, realm_id, room_id, item, qty_min, qty_max, interval_ms, max_instances
                  FROM loot_spawns
                  WHERE next_spawn_at <= now()
                  FOR UPDATE SKIP LOCKED",
@@ -23,23 +23,24 @@ impl Db {
             .await?;
 
         for row in rows {
-            let spawn_id: i64 = row.get(0);
-            let room_id: i64 = row.get(1);
-            let item: String = row.get(2);
-            let qty_min: i32 = row.get(3);
-            let qty_max: i32 = row.get(4);
-            let interval_ms: i32 = row.get(5);
-            let max_instances: i32 = row.get(6);
+            let spawn_id: LootSpawnId = row.get("id");
+            let realm_id: RealmId = row.get("realm_id");
+            let room_id: RoomId = row.get("room_id");
+            let item: String = row.get("item");
+            let qty_min: i32 = row.get("qty_min");
+            let qty_max: i32 = row.get("qty_max");
+            let interval_ms: i32 = row.get("interval_ms");
+            let max_instances: i32 = row.get("max_instances");
 
             let cur_count: i64 = tx
                 .query_one(
-                    "SELECT COUNT(*)
+                    "SELECT COUNT(*) AS n
                      FROM room_loot
-                     WHERE room_id = $1 AND item = $2 AND picked_by IS NULL",
-                    &[&room_id, &item],
+                     WHERE realm_id = $1 AND room_id = $2 AND item = $3 AND picked_by IS NULL",
+                    &[&realm_id, &room_id, &item],
                 )
                 .await?
-                .get(0);
+                .get("n");
 
             if cur_count < max_instances as i64 {
                 let span = (qty_max - qty_min + 1).max(1) as u32;
@@ -47,8 +48,8 @@ impl Db {
                 let qty = qty_min + r;
 
                 tx.execute(
-                    "INSERT INTO room_loot (room_id, item, qty) VALUES ($1, $2, $3)",
-                    &[&room_id, &item, &qty],
+                    "INSERT INTO room_loot (realm_id, room_id, item, qty) VALUES ($1, $2, $3, $4)",
+                    &[&realm_id, &room_id, &item, &qty],
                 )
                 .await?;
                 spawned += 1;
@@ -67,8 +68,15 @@ impl Db {
         Ok(spawned)
     }
 
-    /// Atomically pick up to `want_qty` coins from the room. Returns actually picked.
-    pub async fn pickup_coins(&self, account: &Account, room_id: RoomId, want_qty: i32) -> DbResult<i32> {
+    /// Atomically pick up to `want_qty` coins from the room into the realm balance. Returns actually
+    /// picked.
+    pub async fn pickup_coins(
+        &self,
+        account: &Account,
+        realm_id: RealmId,
+        room_id: RoomId,
+        want_qty: i32,
+    ) -> DbResult<i32> {
         let mut client = self.pool.get().await?;
         let tx = client.build_transaction().start().await?;
 
@@ -76,11 +84,11 @@ impl Db {
             .query_opt(
                 "SELECT id, qty
                  FROM room_loot
-                 WHERE room_id = $1 AND item = 'coin' AND picked_by IS NULL
+                 WHERE realm_id = $1 AND room_id = $2 AND item = 'coin' AND picked_by IS NULL
                  ORDER BY qty DESC
                  FOR UPDATE SKIP LOCKED
                  LIMIT 1",
-                &[&room_id],
+                &[&realm_id, &room_id],
             )
             .await?;
 
@@ -89,8 +97,8 @@ impl Db {
             return Ok(0);
         };
 
-        let loot_id: i64 = row.get(0);
-        let qty: i32 = row.get(1);
+        let loot_id: LootId = row.get("id");
+        let qty: i32 = row.get("qty");
         let take = qty.min(want_qty.max(1));
 
         if qty > take {
@@ -106,8 +114,11 @@ impl Db {
 
         let take64 = i64::from(take);
         tx.execute(
-            "UPDATE accounts SET balance = balance + $1 WHERE username = $2",
-            &[&take64, &account.id],
+            "INSERT INTO realm_balances (realm_id, account_id, amount, updated_at)
+             VALUES ($1, $2, $3, now())
+             ON CONFLICT (realm_id, account_id)
+             DO UPDATE SET amount = realm_balances.amount + EXCLUDED.amount, updated_at = now()",
+            &[&realm_id, &account.id, &take64],
         )
         .await?;
 
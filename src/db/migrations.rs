@@ -8,9 +8,15 @@ use super::{Db, DbResult};
 impl Db {
     /// Run embedded SQL migrations (idempotent).
     pub async fn init(&self) -> DbResult<()> {
+        self.migrate().await?;
+        Ok(())
+    }
+
+    /// Run embedded SQL migrations and return the names of the ones that were applied ("V2__kv_versioning").
+    pub async fn migrate(&self) -> DbResult<Vec<String>> {
         let mut client = self.pool.get().await?;
-        embedded::migrations::runner().run_async(&mut **client).await?;
+        let report = embedded::migrations::runner().run_async(&mut **client).await?;
 
-        Ok(())
+        Ok(report.applied_migrations().iter().map(|m| m.to_string()).collect())
     }
 }
@@ -5,8 +5,11 @@ use tokio_postgres::NoTls;
 
 impl Db {
     pub fn new(url: &str) -> DbResult<Self> {
-        let cfg = tokio_postgres::Config::from_str(url)?;
+        Self::from_config(tokio_postgres::Config::from_str(url)?)
+    }
 
+    /// Pool for an already parsed configuration, e.g. one with the database name swapped out
+    pub fn from_config(cfg: tokio_postgres::Config) -> DbResult<Self> {
         let mgr = Manager::from_config(
             cfg,
             NoTls,
@@ -0,0 +1,65 @@
+use super::{Db, DbResult};
+use crate::models::realm_directory::{DirectoryEntry, DirectoryPage, DirectoryQuery};
+
+impl Db {
+    /// Published realms open to every player, filtered and paged as the query asks. A tag matches
+    /// the blueprint's own tags as well as those players gave the realm.
+    pub async fn realm_directory(&self, query: &DirectoryQuery) -> DbResult<DirectoryPage> {
+        let client = self.get_client().await?;
+
+        let rows = client
+            .query(
+                r#"
+            SELECT r.id,
+                   r.key,
+                   COALESCE(r.title, b.title) AS title,
+                   a.username                 AS author,
+                   b.difficulty,
+                   b.tags,
+                   (SELECT EXTRACT(EPOCH FROM AVG(c.completed_at - c.started_at))::bigint
+                    FROM realm_completions c
+                    WHERE c.realm_id = r.id AND c.completed_at IS NOT NULL) AS avg_completion_secs,
+                   v.avg_stars,
+                   COALESCE(v.ratings, 0)     AS ratings,
+                   COUNT(*) OVER ()           AS total
+            FROM realms r
+            JOIN blueprints b ON b.id = r.bp_id
+            LEFT JOIN accounts a ON a.id = b.owner_id
+            LEFT JOIN (SELECT realm_id, AVG(stars)::float8 AS avg_stars, COUNT(*) AS ratings
+                       FROM realm_reviews
+                       GROUP BY realm_id) v ON v.realm_id = r.id
+            WHERE r.kind = 'live'
+              AND r.key IS NOT NULL
+              AND r.owner_id IS NULL
+              AND b.status IN ('published', 'live')
+              AND ($1::text IS NULL OR $1 = ANY (b.tags)
+                   OR EXISTS (SELECT 1 FROM realm_player_tags t WHERE t.realm_id = r.id AND t.tag = $1))
+              AND ($2::text IS NULL OR a.username ILIKE $2)
+            ORDER BY r.key
+            LIMIT $3 OFFSET $4
+            "#,
+                &[
+                    &query.filter.tag,
+                    &query.filter.author,
+                    &i64::from(query.page_size),
+                    &query.offset(),
+                ],
+            )
+            .await?;
+
+        let total = match rows.first() {
+            Some(row) => row.try_get::<_, i64>("total")? as u32,
+            None => 0,
+        };
+        let entries = rows
+            .iter()
+            .map(DirectoryEntry::try_from_row)
+            .collect::<DbResult<Vec<_>>>()?;
+
+        Ok(DirectoryPage {
+            entries,
+            total,
+            page_size: query.page_size,
+        })
+    }
+}
@@ -1,24 +1,92 @@
 mod account;
 mod account_db;
+mod account_deletion;
+mod account_deletion_db;
+mod ambience;
+mod ambience_db;
+mod clock;
+mod clock_db;
+mod collaborator;
+mod collaborator_db;
+mod crafting;
+mod crafting_db;
+mod currency;
+mod currency_db;
+mod feature;
+mod feature_db;
 mod inventory;
 mod inventory_db;
+mod login_attempt;
+mod login_attempt_db;
+mod maintenance;
+mod maintenance_db;
+mod moderation;
+mod moderation_db;
+mod player_export;
+mod player_export_db;
+mod playtest;
+mod playtest_db;
 mod realm;
 mod realm_db;
+mod recording;
+mod recording_db;
+mod review;
+mod review_db;
 mod room;
 mod room_db;
+mod schedule;
+mod schedule_db;
+mod submission;
+mod submission_db;
+mod tutorial;
+mod tutorial_db;
 mod user;
 mod user_db;
 
 pub use account_db::AccountRepository;
+pub use account_deletion_db::AccountDeletionRepository;
+pub use ambience_db::AmbienceRepository;
+pub use clock_db::ClockRepository;
+pub use collaborator_db::CollaboratorRepository;
+pub use crafting_db::CraftingRepository;
+pub use currency_db::CurrencyRepository;
+pub use feature_db::FeatureRepository;
 pub use inventory_db::InventoryRepository;
+pub use login_attempt_db::LoginAttemptRepository;
+pub use maintenance_db::MaintenanceRepository;
+pub use moderation_db::ModerationRepository;
+pub use player_export_db::PlayerExportRepository;
+pub use playtest_db::PlaytestRepository;
 pub use realm_db::RealmRepository;
+pub use recording_db::RecordingRepository;
+pub use review_db::ReviewRepository;
 pub use room_db::RoomRepository;
+pub use schedule_db::ScheduleRepository;
+pub use submission_db::SubmissionRepository;
+pub use tutorial_db::TutorialRepository;
 pub use user_db::UserRepository;
 
 pub use account::AccountRepo;
+pub use account_deletion::AccountDeletionRepo;
+pub use ambience::AmbienceRepo;
+pub use clock::ClockRepo;
+pub use collaborator::CollaboratorRepo;
+pub use crafting::CraftingRepo;
+pub use currency::CurrencyRepo;
+pub use feature::FeatureRepo;
 pub use inventory::InventoryRepo;
+pub use login_attempt::LoginAttemptRepo;
+pub use maintenance::MaintenanceRepo;
+pub use moderation::ModerationRepo;
+pub use player_export::PlayerExportRepo;
+pub use playtest::PlaytestRepo;
 pub use realm::RealmRepo;
+pub use recording::RecordingRepo;
+pub use review::ReviewRepo;
 pub use room::RoomRepo;
+pub use schedule::ScheduleRepo;
+pub use submission::SubmissionRepo;
+pub use tutorial::TutorialRepo;
 pub use user::UserRepo;
 
 /// Even though room_ids are globally unique, we still use a combination of
@@ -1,5 +1,6 @@
 use crate::db::DbResult;
 use crate::models::account::Account;
+use crate::models::settings::PlayerSettings;
 use crate::models::types::AccountId;
 
 #[async_trait::async_trait]
@@ -10,4 +11,7 @@ pub trait AccountRepo: Send + Sync {
 
     async fn insert_account(&self, account: Account) -> DbResult<Account>;
     async fn update_last_login(&self, account_id: AccountId) -> DbResult<()>;
+    /// Adds `delta` to the health, kept within 0..=`max`. Returns the new health.
+    async fn adjust_health(&self, account_id: AccountId, delta: i32, max: u32) -> DbResult<u32>;
+    async fn save_settings(&self, account_id: AccountId, settings: &PlayerSettings) -> DbResult<()>;
 }
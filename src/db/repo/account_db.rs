@@ -1,7 +1,9 @@
 use crate::db::repo::account::AccountRepo;
 use crate::db::{Db, DbResult, map_row_opt};
 use crate::models::account::Account;
+use crate::models::settings::PlayerSettings;
 use crate::models::types::AccountId;
+use postgres_types::Json;
 use std::sync::Arc;
 
 pub struct AccountRepository {
@@ -62,32 +64,20 @@ impl AccountRepo for AccountRepository {
     async fn insert_account(&self, account: Account) -> DbResult<Account> {
         let client = self.db.get_client().await?;
 
-        let stmt = client.prepare_cached(
-            r#"
-            INSERT INTO accounts (username, email, password_hash, role, current_realm_id, current_room_id, xp, health, coins, inventory, flags)
-            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11)
-            RETURNING id, username, role, created_at, last_login,
-                current_realm_id, current_room_id, xp, health, coins,
-                inventory, flags
+        let stmt = client
+            .prepare_cached(
+                r#"
+            INSERT INTO accounts (username, email, password_hash, role)
+            VALUES ($1, $2, $3, $4)
+            RETURNING *
             "#,
-        ).await?;
+            )
+            .await?;
 
         let row = client
             .query_one(
                 &stmt,
-                &[
-                    &account.username,
-                    &account.email,
-                    &account.password_hash,
-                    &account.role,
-                    // &account.zone_id,
-                    // &account.current_room_id,
-                    // &(account.xp as i64),
-                    // &(account.health as i64),
-                    // &(account.coins as i64),
-                    // &serde_json::to_value(&account.inventory)?,
-                    // &serde_json::to_value(&account.flags)?,
-                ],
+                &[&account.username, &account.email, &account.password_hash, &account.role],
             )
             .await?;
 
@@ -104,4 +94,29 @@ impl AccountRepo for AccountRepository {
 
         Ok(())
     }
+
+    async fn adjust_health(&self, id: AccountId, delta: i32, max: u32) -> DbResult<u32> {
+        let client = self.db.get_client().await?;
+
+        let row = client
+            .query_one(
+                "UPDATE accounts SET health = GREATEST(0, LEAST($3, health + $2)) WHERE id = $1 RETURNING health",
+                &[&id, &delta, &(max as i32)],
+            )
+            .await?;
+        let health: i32 = row.try_get("health")?;
+
+        Ok(health.max(0) as u32)
+    }
+
+    async fn save_settings(&self, id: AccountId, settings: &PlayerSettings) -> DbResult<()> {
+        let client = self.db.get_client().await?;
+        client
+            .execute(
+                "UPDATE accounts SET settings = $2 WHERE id = $1",
+                &[&id, &Json(settings)],
+            )
+            .await?;
+        Ok(())
+    }
 }
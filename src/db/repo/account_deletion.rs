@@ -0,0 +1,19 @@
+use crate::db::DbResult;
+use crate::models::types::AccountId;
+use chrono::{DateTime, Utc};
+
+#[async_trait::async_trait]
+pub trait AccountDeletionRepo: Send + Sync {
+    /// Sets or clears (None) when the account is purged
+    async fn set_delete_after(&self, account_id: AccountId, at: Option<DateTime<Utc>>) -> DbResult<()>;
+
+    /// Accounts whose deletion is due at `now`
+    async fn due(&self, now: DateTime<Utc>) -> DbResult<Vec<AccountId>>;
+
+    /// Keys of the blueprints the account owns
+    async fn owned_blueprints(&self, account_id: AccountId) -> DbResult<Vec<String>>;
+
+    /// Removes the account, its playtest personas and everything linked to them, in one
+    /// transaction. Owned blueprints go to `heir`; without one the account must own none.
+    async fn purge(&self, account_id: AccountId, heir: Option<AccountId>) -> DbResult<()>;
+}
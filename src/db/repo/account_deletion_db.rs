@@ -0,0 +1,132 @@
+use crate::db::repo::account_deletion::AccountDeletionRepo;
+use crate::db::{Db, DbResult};
+use crate::models::types::{AccountId, RealmId};
+use chrono::{DateTime, Utc};
+use std::sync::Arc;
+
+pub struct AccountDeletionRepository {
+    db: Arc<Db>,
+}
+
+impl AccountDeletionRepository {
+    pub fn new(db: Arc<Db>) -> Self {
+        Self { db }
+    }
+}
+
+#[async_trait::async_trait]
+impl AccountDeletionRepo for AccountDeletionRepository {
+    async fn set_delete_after(&self, account_id: AccountId, at: Option<DateTime<Utc>>) -> DbResult<()> {
+        let client = self.db.get_client().await?;
+
+        client
+            .execute(
+                "UPDATE accounts SET delete_after = $2 WHERE id = $1",
+                &[&account_id, &at],
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    async fn due(&self, now: DateTime<Utc>) -> DbResult<Vec<AccountId>> {
+        let client = self.db.get_client().await?;
+
+        let rows = client
+            .query(
+                "SELECT id FROM accounts WHERE delete_after <= $1 ORDER BY delete_after",
+                &[&now],
+            )
+            .await?;
+
+        Ok(rows.iter().map(|r| r.try_get("id")).collect::<Result<_, _>>()?)
+    }
+
+    async fn owned_blueprints(&self, account_id: AccountId) -> DbResult<Vec<String>> {
+        let client = self.db.get_client().await?;
+
+        let rows = client
+            .query(
+                "SELECT key FROM blueprints WHERE owner_id = $1 ORDER BY key",
+                &[&account_id],
+            )
+            .await?;
+
+        Ok(rows.iter().map(|r| r.try_get("key")).collect::<Result<_, _>>()?)
+    }
+
+    async fn purge(&self, account_id: AccountId, heir: Option<AccountId>) -> DbResult<()> {
+        let mut client = self.db.get_client().await?;
+        let tx = client.transaction().await?;
+
+        // The account and its playtest personas
+        let ids: Vec<AccountId> = tx
+            .query(
+                "SELECT id FROM accounts WHERE id = $1 OR flags->>'persona_of' = $1::text",
+                &[&account_id],
+            )
+            .await?
+            .iter()
+            .map(|r| r.try_get("id"))
+            .collect::<Result<_, _>>()?;
+
+        // Items and loot state have no foreign key on the account
+        tx.execute(
+            r#"
+            WITH RECURSIVE carried AS (
+                SELECT instance_id FROM item_instances WHERE account_id = ANY($1)
+                UNION ALL
+                SELECT i.instance_id FROM item_instances i JOIN carried c ON i.container_item_id = c.instance_id
+            )
+            DELETE FROM item_instances WHERE instance_id IN (SELECT instance_id FROM carried)
+            "#,
+            &[&ids],
+        )
+        .await?;
+        tx.execute(
+            "DELETE FROM loot_instantiation_state WHERE account_id = ANY($1)",
+            &[&ids],
+        )
+        .await?;
+
+        // Kept, without who did it
+        tx.execute(
+            "UPDATE room_loot SET picked_by = NULL WHERE picked_by = ANY($1)",
+            &[&ids],
+        )
+        .await?;
+        if let Some(heir) = heir {
+            tx.execute(
+                "UPDATE blueprints SET owner_id = $2 WHERE owner_id = ANY($1)",
+                &[&ids, &heir],
+            )
+            .await?;
+        }
+
+        // Tutorial instances are private, so they go with the account
+        let tutorials: Vec<RealmId> = tx
+            .query(
+                "SELECT id FROM realms WHERE owner_id = ANY($1) AND key LIKE 'tutorial-%'",
+                &[&ids],
+            )
+            .await?
+            .iter()
+            .map(|r| r.try_get("id"))
+            .collect::<Result<_, _>>()?;
+        tx.execute("DELETE FROM item_instances WHERE realm_id = ANY($1)", &[&tutorials])
+            .await?;
+        tx.execute(
+            "DELETE FROM loot_instantiation_state WHERE realm_id = ANY($1)",
+            &[&tutorials],
+        )
+        .await?;
+        tx.execute("DELETE FROM realms WHERE id = ANY($1)", &[&tutorials])
+            .await?;
+
+        // Characters, private state, reports and recordings cascade; other realms lose their owner
+        tx.execute("DELETE FROM accounts WHERE id = ANY($1)", &[&ids]).await?;
+
+        tx.commit().await?;
+        Ok(())
+    }
+}
@@ -0,0 +1,29 @@
+use crate::db::DbResult;
+use crate::models::ambience::AmbientEvent;
+use crate::models::room::Kv;
+use crate::models::types::RealmId;
+use std::time::Duration;
+
+#[async_trait::async_trait]
+pub trait AmbienceRepo: Send + Sync {
+    /// Ambient events of the blueprint the realm runs
+    async fn events_for_realm(&self, realm_id: RealmId) -> DbResult<Vec<AmbientEvent>>;
+
+    /// All zone state of the realm, without expired entries
+    async fn zone_kv(&self, realm_id: RealmId) -> DbResult<Kv>;
+
+    /// Sets a zone state entry that does not expire
+    async fn set_zone_kv(&self, realm_id: RealmId, key: &str, value: &serde_json::Value) -> DbResult<()>;
+
+    /// Sets a zone state entry that expires after `ttl`
+    async fn set_zone_kv_expiring(
+        &self,
+        realm_id: RealmId,
+        key: &str,
+        value: &serde_json::Value,
+        ttl: Duration,
+    ) -> DbResult<()>;
+
+    /// Deletes expired zone state entries of all realms, returns how many
+    async fn delete_expired_zone_kv(&self) -> DbResult<u64>;
+}
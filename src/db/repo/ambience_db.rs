@@ -0,0 +1,107 @@
+use crate::db::error::DbError;
+use crate::db::repo::ambience::AmbienceRepo;
+use crate::db::{Db, DbResult};
+use crate::models::ambience::AmbientEvent;
+use crate::models::room::Kv;
+use crate::models::types::RealmId;
+use serde_json::Value;
+use std::sync::Arc;
+use std::time::Duration;
+
+pub struct AmbienceRepository {
+    db: Arc<Db>,
+}
+
+impl AmbienceRepository {
+    pub fn new(db: Arc<Db>) -> Self {
+        Self { db }
+    }
+}
+
+#[async_trait::async_trait]
+impl AmbienceRepo for AmbienceRepository {
+    async fn events_for_realm(&self, realm_id: RealmId) -> DbResult<Vec<AmbientEvent>> {
+        let client = self.db.get_client().await?;
+
+        let rows = client
+            .query(
+                r#"
+                SELECT a.event
+                FROM bp_ambience a
+                JOIN realms rl ON rl.bp_id = a.bp_id
+                WHERE rl.id = $1
+                ORDER BY a.event_key
+                "#,
+                &[&realm_id],
+            )
+            .await?;
+
+        rows.iter()
+            .map(|row| {
+                serde_json::from_value(row.try_get::<_, Value>("event")?)
+                    .map_err(|e| DbError::Decode(format!("invalid bp_ambience.event: {}", e)))
+            })
+            .collect()
+    }
+
+    async fn zone_kv(&self, realm_id: RealmId) -> DbResult<Kv> {
+        let client = self.db.get_client().await?;
+
+        let rows = client
+            .query(
+                "SELECT key, value FROM realm_kv WHERE realm_id = $1 AND (expires_at IS NULL OR expires_at > NOW())",
+                &[&realm_id],
+            )
+            .await?;
+
+        Kv::try_from_rows(&rows)
+    }
+
+    async fn set_zone_kv(&self, realm_id: RealmId, key: &str, value: &Value) -> DbResult<()> {
+        let client = self.db.get_client().await?;
+
+        client
+            .execute(
+                r#"
+                INSERT INTO realm_kv (realm_id, key, value)
+                VALUES ($1, $2, $3)
+                ON CONFLICT (realm_id, key)
+                DO UPDATE SET value = EXCLUDED.value, version = realm_kv.version + 1, updated_at = NOW(),
+                              expires_at = NULL
+                "#,
+                &[&realm_id, &key, &value],
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    async fn set_zone_kv_expiring(&self, realm_id: RealmId, key: &str, value: &Value, ttl: Duration) -> DbResult<()> {
+        let client = self.db.get_client().await?;
+
+        client
+            .execute(
+                r#"
+                INSERT INTO realm_kv (realm_id, key, value, expires_at)
+                VALUES ($1, $2, $3, NOW() + make_interval(secs => $4))
+                ON CONFLICT (realm_id, key)
+                DO UPDATE SET value = EXCLUDED.value, version = realm_kv.version + 1, updated_at = NOW(),
+                              expires_at = EXCLUDED.expires_at
+                "#,
+                &[&realm_id, &key, &value, &ttl.as_secs_f64()],
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    async fn delete_expired_zone_kv(&self) -> DbResult<u64> {
+        let client = self.db.get_client().await?;
+
+        let n = client
+            .execute("DELETE FROM realm_kv WHERE expires_at <= NOW()", &[])
+            .await?;
+
+        Ok(n)
+    }
+}
@@ -0,0 +1,10 @@
+use crate::db::DbResult;
+use chrono::{DateTime, Utc};
+
+#[async_trait::async_trait]
+pub trait ClockRepo: Send + Sync {
+    /// The last saved world time, None before the clock was ever saved
+    async fn load_world_time(&self) -> DbResult<Option<DateTime<Utc>>>;
+
+    async fn save_world_time(&self, world_time: DateTime<Utc>) -> DbResult<()>;
+}
@@ -0,0 +1,45 @@
+use crate::db::repo::clock::ClockRepo;
+use crate::db::{Db, DbResult};
+use chrono::{DateTime, Utc};
+use std::sync::Arc;
+
+pub struct ClockRepository {
+    db: Arc<Db>,
+}
+
+impl ClockRepository {
+    pub fn new(db: Arc<Db>) -> Self {
+        Self { db }
+    }
+}
+
+#[async_trait::async_trait]
+impl ClockRepo for ClockRepository {
+    async fn load_world_time(&self) -> DbResult<Option<DateTime<Utc>>> {
+        let client = self.db.get_client().await?;
+
+        let row = client
+            .query_opt("SELECT world_time FROM world_clock WHERE id = 1", &[])
+            .await?;
+
+        Ok(row.map(|r| r.get("world_time")))
+    }
+
+    async fn save_world_time(&self, world_time: DateTime<Utc>) -> DbResult<()> {
+        let client = self.db.get_client().await?;
+
+        client
+            .execute(
+                r#"
+                INSERT INTO world_clock (id, world_time, saved_at)
+                VALUES (1, $1, NOW())
+                ON CONFLICT (id)
+                DO UPDATE SET world_time = EXCLUDED.world_time, saved_at = NOW()
+                "#,
+                &[&world_time],
+            )
+            .await?;
+
+        Ok(())
+    }
+}
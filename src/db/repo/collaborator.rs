@@ -0,0 +1,23 @@
+use crate::db::DbResult;
+use crate::models::collaborator::{BlueprintRight, BlueprintRole, Collaborator};
+use crate::models::types::{AccountId, BlueprintId};
+
+#[async_trait::async_trait]
+pub trait CollaboratorRepo: Send + Sync {
+    /// Collaborators of the blueprint by username, the owner not included
+    async fn list(&self, bp_id: BlueprintId) -> DbResult<Vec<Collaborator>>;
+    async fn get(&self, bp_id: BlueprintId, account_id: AccountId) -> DbResult<Option<Collaborator>>;
+    /// Adds a collaborator, or changes the role and rights of an existing one
+    async fn upsert(
+        &self,
+        bp_id: BlueprintId,
+        account_id: AccountId,
+        role: BlueprintRole,
+        rights: &[BlueprintRight],
+        added_by: AccountId,
+    ) -> DbResult<()>;
+    /// Replaces the rights of a collaborator, returns false when there is no such collaborator
+    async fn set_rights(&self, bp_id: BlueprintId, account_id: AccountId, rights: &[BlueprintRight]) -> DbResult<bool>;
+    /// Returns false when there was no such collaborator
+    async fn remove(&self, bp_id: BlueprintId, account_id: AccountId) -> DbResult<bool>;
+}
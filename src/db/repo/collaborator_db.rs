@@ -0,0 +1,111 @@
+use crate::db::repo::collaborator::CollaboratorRepo;
+use crate::db::{Db, DbResult, map_row, map_row_opt};
+use crate::models::collaborator::{BlueprintRight, BlueprintRole, Collaborator};
+use crate::models::types::{AccountId, BlueprintId};
+use std::sync::Arc;
+
+pub struct CollaboratorRepository {
+    db: Arc<Db>,
+}
+
+impl CollaboratorRepository {
+    pub fn new(db: Arc<Db>) -> Self {
+        Self { db }
+    }
+}
+
+fn rights_to_db(rights: &[BlueprintRight]) -> Vec<&'static str> {
+    rights.iter().map(BlueprintRight::as_str).collect()
+}
+
+#[async_trait::async_trait]
+impl CollaboratorRepo for CollaboratorRepository {
+    async fn list(&self, bp_id: BlueprintId) -> DbResult<Vec<Collaborator>> {
+        let client = self.db.get_client().await?;
+
+        let rows = client
+            .query(
+                r#"
+            SELECT c.account_id, a.username, c.role, c.rights
+            FROM blueprint_collaborators c
+            JOIN accounts a ON a.id = c.account_id
+            WHERE c.bp_id = $1
+            ORDER BY a.username
+        "#,
+                &[&bp_id],
+            )
+            .await?;
+
+        rows.iter()
+            .map(|row| map_row(row, Collaborator::try_from_row, "CollaboratorRepo::list"))
+            .collect()
+    }
+
+    async fn get(&self, bp_id: BlueprintId, account_id: AccountId) -> DbResult<Option<Collaborator>> {
+        let client = self.db.get_client().await?;
+
+        let row = client
+            .query_opt(
+                r#"
+            SELECT c.account_id, a.username, c.role, c.rights
+            FROM blueprint_collaborators c
+            JOIN accounts a ON a.id = c.account_id
+            WHERE c.bp_id = $1 AND c.account_id = $2
+        "#,
+                &[&bp_id, &account_id],
+            )
+            .await?;
+
+        map_row_opt(row, Collaborator::try_from_row, "CollaboratorRepo::get")
+    }
+
+    async fn upsert(
+        &self,
+        bp_id: BlueprintId,
+        account_id: AccountId,
+        role: BlueprintRole,
+        rights: &[BlueprintRight],
+        added_by: AccountId,
+    ) -> DbResult<()> {
+        let client = self.db.get_client().await?;
+
+        client
+            .execute(
+                r#"
+            INSERT INTO blueprint_collaborators (bp_id, account_id, role, rights, added_by)
+            VALUES ($1, $2, $3, $4, $5)
+            ON CONFLICT (bp_id, account_id) DO UPDATE SET role = EXCLUDED.role, rights = EXCLUDED.rights
+        "#,
+                &[&bp_id, &account_id, &role.as_str(), &rights_to_db(rights), &added_by],
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    async fn set_rights(&self, bp_id: BlueprintId, account_id: AccountId, rights: &[BlueprintRight]) -> DbResult<bool> {
+        let client = self.db.get_client().await?;
+
+        let n = client
+            .execute(
+                "UPDATE blueprint_collaborators SET rights = $3 WHERE bp_id = $1 AND account_id = $2",
+                &[&bp_id, &account_id, &rights_to_db(rights)],
+            )
+            .await?;
+
+        Ok(n == 1)
+    }
+
+    async fn remove(&self, bp_id: BlueprintId, account_id: AccountId) -> DbResult<bool> {
+        let client = self.db.get_client().await?;
+
+        let n = client
+            .execute(
+                "DELETE FROM blueprint_collaborators WHERE bp_id = $1 AND account_id = $2",
+                &[&bp_id, &account_id],
+            )
+            .await?;
+
+        Ok(n == 1)
+    }
+}
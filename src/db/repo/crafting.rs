@@ -0,0 +1,21 @@
+use crate::db::DbResult;
+use crate::models::inventory::Recipe;
+use crate::models::types::{AccountId, ItemId, RealmId};
+
+#[async_trait::async_trait]
+pub trait CraftingRepo: Send + Sync {
+    /// Recipes of the blueprint the realm runs
+    async fn recipes_for_realm(&self, realm_id: RealmId) -> DbResult<Vec<Recipe>>;
+
+    /// Consumes one of each input instance from the player's inventory and adds the output, in a
+    /// single transaction. An instance listed twice is consumed twice. Returns None (and changes
+    /// nothing) when an input is no longer in the inventory.
+    async fn craft(
+        &self,
+        realm_id: RealmId,
+        account_id: AccountId,
+        inputs: &[ItemId],
+        output_key: &str,
+        output_qty: i32,
+    ) -> DbResult<Option<ItemId>>;
+}
@@ -0,0 +1,93 @@
+use crate::db::repo::crafting::CraftingRepo;
+use crate::db::repo::inventory_db::spawn_item_tx;
+use crate::db::{Db, DbResult, map_row};
+use crate::models::inventory::{ItemLocation, Recipe};
+use crate::models::types::{AccountId, ItemId, RealmId};
+use std::sync::Arc;
+
+pub struct CraftingRepository {
+    db: Arc<Db>,
+}
+
+impl CraftingRepository {
+    pub fn new(db: Arc<Db>) -> Self {
+        Self { db }
+    }
+}
+
+#[async_trait::async_trait]
+impl CraftingRepo for CraftingRepository {
+    async fn recipes_for_realm(&self, realm_id: RealmId) -> DbResult<Vec<Recipe>> {
+        let client = self.db.get_client().await?;
+
+        let rows = client
+            .query(
+                r#"
+                SELECT r.recipe_key, r.inputs, r.output_key, r.output_qty, r.tool_key, r.min_level, r.message
+                FROM bp_recipes r
+                JOIN realms rl ON rl.bp_id = r.bp_id
+                WHERE rl.id = $1
+                ORDER BY r.recipe_key
+                "#,
+                &[&realm_id],
+            )
+            .await?;
+
+        rows.iter()
+            .map(|row| {
+                map_row(
+                    row,
+                    Recipe::try_from_row,
+                    &format!("CraftingRepo::recipes_for_realm realm_id={}", realm_id),
+                )
+            })
+            .collect()
+    }
+
+    async fn craft(
+        &self,
+        realm_id: RealmId,
+        account_id: AccountId,
+        inputs: &[ItemId],
+        output_key: &str,
+        output_qty: i32,
+    ) -> DbResult<Option<ItemId>> {
+        let mut client = self.db.pool.get().await?;
+        let tx = client.transaction().await?;
+
+        for instance_id in inputs {
+            // Lock the stack, so a concurrent drop or give cannot take it from under us
+            let Some(row) = tx
+                .query_opt(
+                    r#"
+                    SELECT quantity FROM item_instances
+                    WHERE instance_id = $1 AND realm_id = $2 AND account_id = $3
+                    FOR UPDATE
+                    "#,
+                    &[instance_id, &realm_id, &account_id],
+                )
+                .await?
+            else {
+                // Dropping the transaction rolls back what was consumed so far
+                return Ok(None);
+            };
+
+            let quantity: i32 = row.get("quantity");
+            if quantity <= 1 {
+                tx.execute("DELETE FROM item_instances WHERE instance_id = $1", &[instance_id])
+                    .await?;
+            } else {
+                tx.execute(
+                    "UPDATE item_instances SET quantity = quantity - 1, updated_at = NOW() WHERE instance_id = $1",
+                    &[instance_id],
+                )
+                .await?;
+            }
+        }
+
+        let output = spawn_item_tx(&tx, realm_id, output_key, ItemLocation::Player(account_id), output_qty).await?;
+
+        tx.commit().await?;
+        Ok(Some(output))
+    }
+}
@@ -0,0 +1,31 @@
+use crate::db::DbResult;
+use crate::models::types::{AccountId, ItemId, RealmId, RoomId};
+
+#[async_trait::async_trait]
+pub trait CurrencyRepo: Send + Sync {
+    /// The player's balance in the realm, 0 when they never had money there
+    async fn balance(&self, realm_id: RealmId, account_id: AccountId) -> DbResult<i64>;
+
+    /// Adds `amount` to the balance. Returns the new balance.
+    async fn credit(&self, realm_id: RealmId, account_id: AccountId, amount: i64) -> DbResult<i64>;
+
+    /// Takes `amount` off the balance. Returns the new balance, or None (and changes nothing)
+    /// when the balance is short.
+    async fn debit(&self, realm_id: RealmId, account_id: AccountId, amount: i64) -> DbResult<Option<i64>>;
+
+    /// Moves `amount` from one balance to the other in a single transaction. Returns the payer's
+    /// new balance, or None (and changes nothing) when the payer's balance is short.
+    async fn transfer(&self, realm_id: RealmId, from: AccountId, to: AccountId, amount: i64) -> DbResult<Option<i64>>;
+
+    /// Removes up to `quantity` coins from the coin stack lying in `room_id` and adds them to the
+    /// player's balance, in a single transaction. Returns the number of coins and the new balance,
+    /// or None when the stack is no longer in that room.
+    async fn deposit_coins(
+        &self,
+        realm_id: RealmId,
+        account_id: AccountId,
+        room_id: RoomId,
+        instance_id: ItemId,
+        quantity: i32,
+    ) -> DbResult<Option<(i32, i64)>>;
+}
@@ -0,0 +1,143 @@
+use crate::db::repo::currency::CurrencyRepo;
+use crate::db::{Db, DbResult};
+use crate::models::types::{AccountId, ItemId, RealmId, RoomId};
+use std::sync::Arc;
+use tokio_postgres::Transaction;
+
+pub struct CurrencyRepository {
+    db: Arc<Db>,
+}
+
+impl CurrencyRepository {
+    pub fn new(db: Arc<Db>) -> Self {
+        Self { db }
+    }
+}
+
+#[async_trait::async_trait]
+impl CurrencyRepo for CurrencyRepository {
+    async fn balance(&self, realm_id: RealmId, account_id: AccountId) -> DbResult<i64> {
+        let client = self.db.get_client().await?;
+
+        let row = client
+            .query_opt(
+                "SELECT amount FROM realm_balances WHERE realm_id = $1 AND account_id = $2",
+                &[&realm_id, &account_id],
+            )
+            .await?;
+
+        Ok(row.map_or(0, |r| r.get("amount")))
+    }
+
+    async fn credit(&self, realm_id: RealmId, account_id: AccountId, amount: i64) -> DbResult<i64> {
+        let mut client = self.db.pool.get().await?;
+        let tx = client.transaction().await?;
+
+        let balance = credit_tx(&tx, realm_id, account_id, amount).await?;
+
+        tx.commit().await?;
+        Ok(balance)
+    }
+
+    async fn debit(&self, realm_id: RealmId, account_id: AccountId, amount: i64) -> DbResult<Option<i64>> {
+        let client = self.db.get_client().await?;
+
+        // The condition keeps the balance from going below zero without a separate lock
+        let row = client
+            .query_opt(
+                r#"
+                UPDATE realm_balances SET amount = amount - $3, updated_at = NOW()
+                WHERE realm_id = $1 AND account_id = $2 AND amount >= $3
+                RETURNING amount
+                "#,
+                &[&realm_id, &account_id, &amount],
+            )
+            .await?;
+
+        Ok(row.map(|r| r.get("amount")))
+    }
+
+    async fn transfer(&self, realm_id: RealmId, from: AccountId, to: AccountId, amount: i64) -> DbResult<Option<i64>> {
+        let mut client = self.db.pool.get().await?;
+        let tx = client.transaction().await?;
+
+        let Some(row) = tx
+            .query_opt(
+                r#"
+                UPDATE realm_balances SET amount = amount - $3, updated_at = NOW()
+                WHERE realm_id = $1 AND account_id = $2 AND amount >= $3
+                RETURNING amount
+                "#,
+                &[&realm_id, &from, &amount],
+            )
+            .await?
+        else {
+            return Ok(None);
+        };
+        credit_tx(&tx, realm_id, to, amount).await?;
+
+        tx.commit().await?;
+        Ok(Some(row.get("amount")))
+    }
+
+    async fn deposit_coins(
+        &self,
+        realm_id: RealmId,
+        account_id: AccountId,
+        room_id: RoomId,
+        instance_id: ItemId,
+        quantity: i32,
+    ) -> DbResult<Option<(i32, i64)>> {
+        let mut client = self.db.pool.get().await?;
+        let tx = client.transaction().await?;
+
+        // Lock the stack, so two players picking it up cannot both get the same coins. A stack
+        // that was moved out of the room in the meantime counts as gone.
+        let Some(row) = tx
+            .query_opt(
+                r#"
+                SELECT quantity FROM item_instances
+                WHERE instance_id = $1 AND realm_id = $2 AND room_id = $3
+                FOR UPDATE
+                "#,
+                &[&instance_id, &realm_id, &room_id],
+            )
+            .await?
+        else {
+            return Ok(None);
+        };
+
+        let available: i32 = row.get("quantity");
+        let taken = quantity.min(available);
+        if taken == available {
+            tx.execute("DELETE FROM item_instances WHERE instance_id = $1", &[&instance_id])
+                .await?;
+        } else {
+            tx.execute(
+                "UPDATE item_instances SET quantity = quantity - $1, updated_at = NOW() WHERE instance_id = $2",
+                &[&taken, &instance_id],
+            )
+            .await?;
+        }
+        let balance = credit_tx(&tx, realm_id, account_id, i64::from(taken)).await?;
+
+        tx.commit().await?;
+        Ok(Some((taken, balance)))
+    }
+}
+
+async fn credit_tx(tx: &Transaction<'_>, realm_id: RealmId, account_id: AccountId, amount: i64) -> DbResult<i64> {
+    let row = tx
+        .query_one(
+            r#"
+            INSERT INTO realm_balances (realm_id, account_id, amount, updated_at)
+            VALUES ($1, $2, $3, NOW())
+            ON CONFLICT (realm_id, account_id)
+            DO UPDATE SET amount = realm_balances.amount + EXCLUDED.amount, updated_at = NOW()
+            RETURNING amount
+            "#,
+            &[&realm_id, &account_id, &amount],
+        )
+        .await?;
+    Ok(row.get("amount"))
+}
@@ -0,0 +1,15 @@
+use crate::db::DbResult;
+use crate::models::feature::{FeatureFlag, FeatureScope};
+use crate::models::types::RealmId;
+
+#[async_trait::async_trait]
+pub trait FeatureRepo: Send + Sync {
+    /// Resolves a flag for a realm: the realm flag if set, else the flag of the realm's blueprint
+    async fn resolve(&self, feature: &str, realm_id: RealmId) -> DbResult<Option<bool>>;
+    /// All flags that apply to a realm, both realm and blueprint scoped
+    async fn list_for_realm(&self, realm_id: RealmId) -> DbResult<Vec<FeatureFlag>>;
+
+    async fn set(&self, feature: &str, scope: FeatureScope, enabled: bool) -> DbResult<()>;
+    /// Removes a flag, returns false when it was not set
+    async fn clear(&self, feature: &str, scope: FeatureScope) -> DbResult<bool>;
+}
@@ -0,0 +1,124 @@
+use crate::db::repo::feature::FeatureRepo;
+use crate::db::{Db, DbResult, map_row};
+use crate::models::feature::{FeatureFlag, FeatureScope};
+use crate::models::types::{BlueprintId, RealmId};
+use std::sync::Arc;
+
+pub struct FeatureRepository {
+    db: Arc<Db>,
+}
+
+impl FeatureRepository {
+    pub fn new(db: Arc<Db>) -> Self {
+        Self { db: db.clone() }
+    }
+}
+
+fn scope_ids(scope: FeatureScope) -> (Option<RealmId>, Option<BlueprintId>) {
+    match scope {
+        FeatureScope::Realm(id) => (Some(id), None),
+        FeatureScope::Blueprint(id) => (None, Some(id)),
+    }
+}
+
+#[async_trait::async_trait]
+impl FeatureRepo for FeatureRepository {
+    async fn resolve(&self, feature: &str, realm_id: RealmId) -> DbResult<Option<bool>> {
+        let client = self.db.get_client().await?;
+
+        let row = client
+            .query_opt(
+                r#"
+                SELECT f.enabled
+                FROM feature_flags f
+                JOIN realms r ON r.id = $2
+                WHERE f.feature = $1
+                  AND (f.realm_id = r.id OR f.bp_id = r.bp_id)
+                ORDER BY (f.realm_id IS NOT NULL) DESC
+                LIMIT 1
+                "#,
+                &[&feature, &realm_id],
+            )
+            .await?;
+
+        Ok(row.map(|r| r.get("enabled")))
+    }
+
+    async fn list_for_realm(&self, realm_id: RealmId) -> DbResult<Vec<FeatureFlag>> {
+        let client = self.db.get_client().await?;
+
+        let rows = client
+            .query(
+                r#"
+                SELECT f.feature, f.realm_id, f.bp_id, f.enabled
+                FROM feature_flags f
+                JOIN realms r ON r.id = $1
+                WHERE f.realm_id = r.id OR f.bp_id = r.bp_id
+                ORDER BY f.feature, (f.realm_id IS NOT NULL) DESC
+                "#,
+                &[&realm_id],
+            )
+            .await?;
+
+        rows.iter()
+            .map(|row| {
+                map_row(
+                    row,
+                    FeatureFlag::try_from_row,
+                    &format!("FeatureRepo::list_for_realm realm_id={}", realm_id),
+                )
+            })
+            .collect()
+    }
+
+    async fn set(&self, feature: &str, scope: FeatureScope, enabled: bool) -> DbResult<()> {
+        let client = self.db.get_client().await?;
+        let (realm_id, bp_id) = scope_ids(scope);
+
+        // Two partial unique indexes, so upsert by hand
+        let updated = client
+            .execute(
+                r#"
+                UPDATE feature_flags SET enabled = $4, updated_at = now()
+                WHERE feature = $1
+                  AND realm_id IS NOT DISTINCT FROM $2
+                  AND bp_id IS NOT DISTINCT FROM $3
+                "#,
+                &[&feature, &realm_id, &bp_id, &enabled],
+            )
+            .await?;
+
+        if updated == 0 {
+            client
+                .execute(
+                    r#"
+                    INSERT INTO feature_flags (feature, realm_id, bp_id, enabled)
+                    VALUES ($1, $2, $3, $4)
+                    "#,
+                    &[&feature, &realm_id, &bp_id, &enabled],
+                )
+                .await?;
+        }
+
+        Ok(())
+    }
+
+    async fn clear(&self, feature: &str, scope: FeatureScope) -> DbResult<bool> {
+        let client = self.db.get_client().await?;
+        let (realm_id, bp_id) = scope_ids(scope);
+
+        let deleted = client
+            .execute(
+                r#"
+                DELETE FROM feature_flags
+                WHERE feature = $1
+                  AND realm_id IS NOT DISTINCT FROM $2
+                  AND bp_id IS NOT DISTINCT FROM $3
+                "#,
+                &[&feature, &realm_id, &bp_id],
+            )
+            .await?;
+
+        Ok(deleted > 0)
+    }
+}
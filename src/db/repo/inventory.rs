@@ -1,5 +1,5 @@
 use crate::db::DbResult;
-use crate::models::inventory::{Item, ItemInstance, ItemLocation};
+use crate::models::inventory::{ClearedItem, Item, ItemInstance, ItemLocation, StashedItem};
 use crate::models::types::{AccountId, ItemId, ObjectId, RealmId, RoomId};
 
 #[async_trait::async_trait]
@@ -43,6 +43,10 @@ pub trait InventoryRepo: Send + Sync {
     /// Get all items in player's inventory
     async fn get_player_inventory(&self, realm_id: RealmId, account_id: AccountId) -> DbResult<Vec<ItemInstance>>;
 
+    /// Get all items the player carries: the inventory and, nested at any depth, whatever is inside
+    /// the carried containers
+    async fn get_carried_items(&self, realm_id: RealmId, account_id: AccountId) -> DbResult<Vec<ItemInstance>>;
+
     /// Find item in player inventory by noun
     async fn find_item_in_player_inventory(
         &self,
@@ -129,6 +133,54 @@ pub trait InventoryRepo: Send + Sync {
     /// Automatically merges with existing stacks if applicable
     async fn move_item(&self, instance_id: ItemId, new_location: ItemLocation) -> DbResult<()>;
 
+    /// Move up to `quantity` of a stack to a new location, splitting the stack when only part of
+    /// it moves. Returns how many were moved.
+    async fn move_item_quantity(&self, instance_id: ItemId, quantity: i32, new_location: ItemLocation)
+    -> DbResult<i32>;
+
+    // ========================================================================
+    // STASHES
+    // ========================================================================
+
+    /// Get the player's stash in a storage object. `realm_id` is None for a persistent stash.
+    async fn get_stash(
+        &self,
+        object_id: ObjectId,
+        account_id: AccountId,
+        realm_id: Option<RealmId>,
+    ) -> DbResult<Vec<StashedItem>>;
+
+    /// Move up to `quantity` of an inventory stack into the player's stash, merging stackable
+    /// items into the slot they already have. Returns how many were stored (0 when the stack is
+    /// no longer carried), or None when all `capacity` slots are taken.
+    async fn stash_item(
+        &self,
+        instance_id: ItemId,
+        quantity: i32,
+        object_id: ObjectId,
+        account_id: AccountId,
+        realm_id: Option<RealmId>,
+        capacity: i32,
+    ) -> DbResult<Option<i32>>;
+
+    /// Move up to `quantity` of a stash slot into the player's inventory in `realm_id`. Returns
+    /// how many were withdrawn, 0 when the slot is gone.
+    async fn unstash_item(
+        &self,
+        stash_id: ItemId,
+        quantity: i32,
+        realm_id: RealmId,
+        account_id: AccountId,
+    ) -> DbResult<i32>;
+
+    // ========================================================================
+    // DROPPED ITEMS
+    // ========================================================================
+
+    /// Clear away the dropped items that are due under the drop policy of their blueprint: they
+    /// despawn, or go back to where they were spawned. Returns what left which room.
+    async fn clear_dropped_items(&self) -> DbResult<Vec<ClearedItem>>;
+
     // ========================================================================
     // ITEM MODIFICATION
     // ========================================================================
@@ -142,6 +194,10 @@ pub trait InventoryRepo: Send + Sync {
     /// Update item condition (durability, charges, custom state)
     async fn set_item_condition(&self, instance_id: ItemId, condition: serde_json::Value) -> DbResult<()>;
 
+    /// Changes the durability in the item's condition by `delta`, clamped to 0..=max. A missing
+    /// durability counts as `max`. Returns the new durability.
+    async fn adjust_durability(&self, instance_id: ItemId, delta: i32, max: i32) -> DbResult<i32>;
+
     /// Delete item instance entirely
     async fn delete_item(&self, instance_id: ItemId) -> DbResult<()>;
 
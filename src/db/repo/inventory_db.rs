@@ -1,8 +1,9 @@
 use crate::db::repo::inventory::InventoryRepo;
-use crate::db::{Db, DbError, DbResult, map_row, map_row_opt};
-use crate::models::inventory::{Item, ItemInstance, ItemLocation};
+use crate::db::{Db, DbResult, map_row, map_row_opt};
+use crate::models::inventory::{ClearedItem, DURABILITY_KEY, Item, ItemInstance, ItemLocation, StashedItem};
 use crate::models::types::{AccountId, BlueprintId, ItemId, ObjectId, RealmId, RoomId};
 use std::sync::Arc;
+use tokio_postgres::Transaction;
 
 pub struct InventoryRepository {
     db: Arc<Db>,
@@ -19,7 +20,7 @@ impl InventoryRepository {
         let row = client
             .query_one("SELECT bp_id FROM realms WHERE id = $1", &[&realm_id])
             .await?;
-        Ok(row.get(0))
+        Ok(row.get("bp_id"))
     }
 }
 
@@ -35,16 +36,7 @@ impl InventoryRepo for InventoryRepository {
 
         let row = client
             .query_one(
-                r#"
-                SELECT
-                    c.id, c.bp_id, c.item_key, c.name, c.short,
-                    c.description, c.examine, c.stackable,
-                    COALESCE(array_agg(n.noun ORDER BY n.noun) FILTER (WHERE n.noun IS NOT NULL), ARRAY[]::TEXT[]) as nouns
-                FROM bp_items_catalog c
-                LEFT JOIN bp_item_nouns n ON n.item_id = c.id
-                WHERE c.bp_id = $1 AND c.item_key = $2
-                GROUP BY c.id
-                "#,
+                &catalog_item_query("c.bp_id = $1 AND c.item_key = $2", ""),
                 &[&bp_id, &item_key],
             )
             .await?;
@@ -63,19 +55,7 @@ impl InventoryRepo for InventoryRepository {
         let client = self.db.pool.get().await?;
 
         let row = client
-            .query_one(
-                r#"
-                SELECT
-                    c.id, c.bp_id, c.item_key, c.name, c.short,
-                    c.description, c.examine, c.stackable,
-                    COALESCE(array_agg(n.noun ORDER BY n.noun) FILTER (WHERE n.noun IS NOT NULL), ARRAY[]::TEXT[]) as nouns
-                FROM bp_items_catalog c
-                LEFT JOIN bp_item_nouns n ON n.item_id = c.id
-                WHERE c.id = $1
-                GROUP BY c.id
-                "#,
-                &[&catalog_id],
-            )
+            .query_one(&catalog_item_query("c.id = $1", ""), &[&catalog_id])
             .await?;
 
         map_row(
@@ -91,17 +71,7 @@ impl InventoryRepo for InventoryRepository {
 
         let row = client
             .query_opt(
-                r#"
-                SELECT
-                    c.id, c.bp_id, c.item_key, c.name, c.short,
-                    c.description, c.examine, c.stackable,
-                    COALESCE(array_agg(n2.noun ORDER BY n2.noun) FILTER (WHERE n2.noun IS NOT NULL), ARRAY[]::TEXT[]) as nouns
-                FROM bp_items_catalog c
-                JOIN bp_item_nouns n ON n.item_id = c.id AND LOWER(n.noun) = LOWER($2)
-                LEFT JOIN bp_item_nouns n2 ON n2.item_id = c.id
-                WHERE c.bp_id = $1
-                GROUP BY c.id
-                "#,
+                &catalog_item_query(&format!("c.bp_id = $1 AND {}", has_noun("c", "$2")), ""),
                 &[&bp_id, &noun],
             )
             .await?;
@@ -118,20 +88,7 @@ impl InventoryRepo for InventoryRepository {
         let client = self.db.pool.get().await?;
 
         let rows = client
-            .query(
-                r#"
-                SELECT
-                    c.id, c.bp_id, c.item_key, c.name, c.short,
-                    c.description, c.examine, c.stackable,
-                    COALESCE(array_agg(n.noun ORDER BY n.noun) FILTER (WHERE n.noun IS NOT NULL), ARRAY[]::TEXT[]) as nouns
-                FROM bp_items_catalog c
-                LEFT JOIN bp_item_nouns n ON n.item_id = c.id
-                WHERE c.bp_id = $1
-                GROUP BY c.id
-                ORDER BY c.name
-                "#,
-                &[&bp_id],
-            )
+            .query(&catalog_item_query("c.bp_id = $1", "ORDER BY c.name"), &[&bp_id])
             .await?;
 
         let items: DbResult<Vec<Item>> = rows
@@ -156,44 +113,10 @@ impl InventoryRepo for InventoryRepository {
         let client = self.db.pool.get().await?;
 
         let row = client
-            .query_one(
-                r#"
-                SELECT
-                    i.id, i.realm_id, i.catalog_id,
-                    i.room_id, i.account_id, i.object_id, i.container_item_id,
-                    i.quantity, i.condition, i.created_at, i.updated_at,
-                    c.item_key, c.name, c.short, c.description, c.examine, c.stackable,
-                    COALESCE(array_agg(n.noun ORDER BY n.noun) FILTER (WHERE n.noun IS NOT NULL), ARRAY[]::TEXT[]) as nouns
-                FROM items i
-                JOIN bp_items_catalog c ON i.catalog_id = c.id
-                LEFT JOIN bp_item_nouns n ON n.item_id = c.id
-                WHERE i.id = $1
-                GROUP BY i.id, c.id
-                "#,
-                &[&instance_id],
-            )
+            .query_one(&item_instance_query("ii.instance_id = $1", ""), &[&instance_id])
             .await?;
 
-        let location = ItemLocation::from_db_columns(row.get(3), row.get(4), row.get(5), row.get(6))
-            .map_err(DbError::DataError)?;
-
-        Ok(ItemInstance {
-            instance_id: row.get(0),
-            realm_id: row.get(1),
-            catalog_id: row.get(2),
-            location,
-            quantity: row.get(7),
-            condition: row.get(8),
-            created_at: row.get(9),
-            updated_at: row.get(10),
-            item_key: row.get(11),
-            name: row.get(12),
-            short: row.get(13),
-            description: row.get(14),
-            examine: row.get(15),
-            stackable: row.get(16),
-            nouns: row.get(17),
-        })
+        map_row(&row, ItemInstance::try_from_row, "InventoryRepo::get_item_instance")
     }
 
     async fn has_item(&self, realm_id: RealmId, account_id: AccountId, instance_id: ItemId) -> DbResult<bool> {
@@ -201,12 +124,12 @@ impl InventoryRepo for InventoryRepository {
 
         let row = client
             .query_one(
-                "SELECT EXISTS(SELECT 1 FROM item_instances WHERE instance_id = $1 AND realm_id = $2 AND account_id = $3)",
+                "SELECT EXISTS(SELECT 1 FROM item_instances WHERE instance_id = $1 AND realm_id = $2 AND account_id = $3) AS found",
                 &[&instance_id, &realm_id, &account_id],
             )
             .await?;
 
-        Ok(row.get(0))
+        Ok(row.get("found"))
     }
 
     async fn has_item_by_key(&self, realm_id: RealmId, account_id: AccountId, item_key: &str) -> DbResult<bool> {
@@ -221,13 +144,13 @@ impl InventoryRepo for InventoryRepository {
                 WHERE ii.realm_id = $1
                     AND ii.account_id = $2
                     AND bp.item_key = $3
-            )
+            ) AS found
             "#,
                 &[&realm_id, &account_id, &item_key],
             )
             .await?;
 
-        Ok(row.get(0))
+        Ok(row.get("found"))
     }
 
     async fn has_item_by_noun(&self, realm_id: RealmId, account_id: AccountId, noun: &str) -> DbResult<bool> {
@@ -243,13 +166,13 @@ impl InventoryRepo for InventoryRepository {
                 WHERE ii.realm_id = $1
                     AND ii.account_id = $2
                     AND LOWER(n.noun) = LOWER($3)
-            )
+            ) AS found
             "#,
                 &[&realm_id, &account_id, &noun],
             )
             .await?;
 
-        Ok(row.get(0))
+        Ok(row.get("found"))
     }
 
     // ========================================================================
@@ -261,47 +184,48 @@ impl InventoryRepo for InventoryRepository {
 
         let rows = client
             .query(
-                r#"
-            SELECT
-                ii.instance_id, ii.realm_id, ii.catalog_id,
-                ii.room_id, ii.account_id, ii.object_id, ii.container_item_id,
-                ii.quantity, ii.condition, ii.created_at, ii.updated_at,
-                bp.item_key, bp.name, bp.short, bp.description, bp.examine, bp.stackable,
-                COALESCE(array_agg(n.noun ORDER BY n.noun) FILTER (WHERE n.noun IS NOT NULL), ARRAY[]::TEXT[]) as nouns
-            FROM item_instances ii
-            JOIN bp_items_catalog bp ON ii.catalog_id = bp.id
-            LEFT JOIN bp_item_nouns n ON n.item_id = bp.id
-            WHERE ii.realm_id = $1 AND ii.account_id = $2
-            GROUP BY ii.instance_id, bp.id
-            ORDER BY bp.name
-            "#,
+                &item_instance_query(
+                    "ii.realm_id = $1 AND ii.account_id = $2",
+                    "ORDER BY bp.name, ii.created_at, ii.instance_id",
+                ),
                 &[&realm_id, &account_id],
             )
             .await?;
 
-        rows.into_iter()
-            .map(|row| {
-                let location = ItemLocation::from_db_columns(row.get(3), row.get(4), row.get(5), row.get(6))
-                    .map_err(DbError::DataError)?;
-
-                Ok(ItemInstance {
-                    instance_id: row.get(0),
-                    realm_id: row.get(1),
-                    catalog_id: row.get(2),
-                    location,
-                    quantity: row.get(7),
-                    condition: row.get(8),
-                    created_at: row.get(9),
-                    updated_at: row.get(10),
-                    item_key: row.get(11),
-                    name: row.get(12),
-                    short: row.get(13),
-                    description: row.get(14),
-                    examine: row.get(15),
-                    stackable: row.get(16),
-                    nouns: row.get(17),
-                })
-            })
+        rows.iter()
+            .map(|row| map_row(row, ItemInstance::try_from_row, "InventoryRepo::get_player_inventory"))
+            .collect()
+    }
+
+    async fn get_carried_items(&self, realm_id: RealmId, account_id: AccountId) -> DbResult<Vec<ItemInstance>> {
+        let client = self.db.pool.get().await?;
+
+        // UNION (not UNION ALL) so a container accidentally inside itself cannot loop forever
+        let rows = client
+            .query(
+                &format!(
+                    r#"
+                    WITH RECURSIVE carried AS (
+                        SELECT instance_id FROM item_instances
+                        WHERE realm_id = $1 AND account_id = $2
+                        UNION
+                        SELECT ii.instance_id FROM item_instances ii
+                        JOIN carried c ON ii.container_item_id = c.instance_id
+                        WHERE ii.realm_id = $1
+                    )
+                    {}
+                    "#,
+                    item_instance_query(
+                        "ii.instance_id IN (SELECT instance_id FROM carried)",
+                        "ORDER BY bp.name, ii.created_at, ii.instance_id",
+                    )
+                ),
+                &[&realm_id, &account_id],
+            )
+            .await?;
+
+        rows.iter()
+            .map(|row| map_row(row, ItemInstance::try_from_row, "InventoryRepo::get_carried_items"))
             .collect()
     }
 
@@ -315,48 +239,19 @@ impl InventoryRepo for InventoryRepository {
 
         let row = client
             .query_opt(
-                r#"
-                SELECT
-                    i.id, i.realm_id, i.catalog_id,
-                    i.room_id, i.account_id, i.object_id, i.container_item_id,
-                    i.quantity, i.condition, i.created_at, i.updated_at,
-                    c.item_key, c.name, c.short, c.description, c.examine, c.stackable,
-                    COALESCE(array_agg(n2.noun ORDER BY n2.noun) FILTER (WHERE n2.noun IS NOT NULL), ARRAY[]::TEXT[]) as nouns
-                FROM items i
-                JOIN bp_items_catalog c ON i.catalog_id = c.id
-                JOIN bp_item_nouns n ON n.item_id = c.id AND LOWER(n.noun) = LOWER($3)
-                LEFT JOIN bp_item_nouns n2 ON n2.item_id = c.id
-                WHERE i.realm_id = $1 AND i.account_id = $2
-                GROUP BY i.id, c.id
-                LIMIT 1
-                "#,
+                &item_instance_query(
+                    &format!("ii.realm_id = $1 AND ii.account_id = $2 AND {}", has_noun("bp", "$3")),
+                    "LIMIT 1",
+                ),
                 &[&realm_id, &account_id, &noun],
             )
             .await?;
 
-        row.map(|r| {
-            let location =
-                ItemLocation::from_db_columns(r.get(3), r.get(4), r.get(5), r.get(6)).map_err(DbError::DataError)?;
-
-            Ok(ItemInstance {
-                instance_id: r.get(0),
-                realm_id: r.get(1),
-                catalog_id: r.get(2),
-                location,
-                quantity: r.get(7),
-                condition: r.get(8),
-                created_at: r.get(9),
-                updated_at: r.get(10),
-                item_key: r.get(11),
-                name: r.get(12),
-                short: r.get(13),
-                description: r.get(14),
-                examine: r.get(15),
-                stackable: r.get(16),
-                nouns: r.get(17),
-            })
-        })
-        .transpose()
+        map_row_opt(
+            row,
+            ItemInstance::try_from_row,
+            "InventoryRepo::find_item_in_player_inventory",
+        )
     }
 
     async fn find_item_by_key_in_inventory(
@@ -369,47 +264,19 @@ impl InventoryRepo for InventoryRepository {
 
         let row = client
             .query_opt(
-                r#"
-            SELECT
-                ii.instance_id, ii.realm_id, ii.catalog_id,
-                ii.room_id, ii.account_id, ii.object_id, ii.container_item_id,
-                ii.quantity, ii.condition, ii.created_at, ii.updated_at,
-                bp.item_key, bp.name, bp.short, bp.description, bp.examine, bp.stackable,
-                COALESCE(array_agg(n.noun ORDER BY n.noun) FILTER (WHERE n.noun IS NOT NULL), ARRAY[]::TEXT[]) as nouns
-            FROM item_instances ii
-            JOIN bp_items_catalog bp ON ii.catalog_id = bp.id
-            LEFT JOIN bp_item_nouns n ON n.item_id = bp.id
-            WHERE ii.realm_id = $1 AND ii.account_id = $2 AND bp.item_key = $3
-            GROUP BY ii.instance_id, bp.id
-            LIMIT 1
-            "#,
+                &item_instance_query(
+                    "ii.realm_id = $1 AND ii.account_id = $2 AND bp.item_key = $3",
+                    "LIMIT 1",
+                ),
                 &[&realm_id, &account_id, &item_key],
             )
             .await?;
 
-        row.map(|r| {
-            let location =
-                ItemLocation::from_db_columns(r.get(3), r.get(4), r.get(5), r.get(6)).map_err(DbError::DataError)?;
-
-            Ok(ItemInstance {
-                instance_id: r.get(0),
-                realm_id: r.get(1),
-                catalog_id: r.get(2),
-                location,
-                quantity: r.get(7),
-                condition: r.get(8),
-                created_at: r.get(9),
-                updated_at: r.get(10),
-                item_key: r.get(11),
-                name: r.get(12),
-                short: r.get(13),
-                description: r.get(14),
-                examine: r.get(15),
-                stackable: r.get(16),
-                nouns: r.get(17),
-            })
-        })
-        .transpose()
+        map_row_opt(
+            row,
+            ItemInstance::try_from_row,
+            "InventoryRepo::find_item_by_key_in_inventory",
+        )
     }
     // ========================================================================
     // ROOM QUERIES
@@ -420,47 +287,16 @@ impl InventoryRepo for InventoryRepository {
 
         let rows = client
             .query(
-                r#"
-            SELECT
-                ii.instance_id, ii.realm_id, ii.catalog_id,
-                ii.room_id, ii.account_id, ii.object_id, ii.container_item_id,
-                ii.quantity, ii.condition, ii.created_at, ii.updated_at,
-                bp.item_key, bp.name, bp.short, bp.description, bp.examine, bp.stackable,
-                COALESCE(array_agg(n.noun ORDER BY n.noun) FILTER (WHERE n.noun IS NOT NULL), ARRAY[]::TEXT[]) as nouns
-            FROM item_instances ii
-            JOIN bp_items_catalog bp ON ii.catalog_id = bp.id
-            LEFT JOIN bp_item_nouns n ON n.item_id = bp.id
-            WHERE ii.realm_id = $1 AND ii.room_id = $2
-            GROUP BY ii.instance_id, bp.id
-            ORDER BY bp.name
-            "#,
+                &item_instance_query(
+                    "ii.realm_id = $1 AND ii.room_id = $2",
+                    "ORDER BY bp.name, ii.created_at, ii.instance_id",
+                ),
                 &[&realm_id, &room_id],
             )
             .await?;
 
-        rows.into_iter()
-            .map(|row| {
-                let location = ItemLocation::from_db_columns(row.get(3), row.get(4), row.get(5), row.get(6))
-                    .map_err(DbError::DataError)?;
-
-                Ok(ItemInstance {
-                    instance_id: row.get(0),
-                    realm_id: row.get(1),
-                    catalog_id: row.get(2),
-                    location,
-                    quantity: row.get(7),
-                    condition: row.get(8),
-                    created_at: row.get(9),
-                    updated_at: row.get(10),
-                    item_key: row.get(11),
-                    name: row.get(12),
-                    short: row.get(13),
-                    description: row.get(14),
-                    examine: row.get(15),
-                    stackable: row.get(16),
-                    nouns: row.get(17),
-                })
-            })
+        rows.iter()
+            .map(|row| map_row(row, ItemInstance::try_from_row, "InventoryRepo::get_room_items"))
             .collect()
     }
 
@@ -474,48 +310,15 @@ impl InventoryRepo for InventoryRepository {
 
         let row = client
             .query_opt(
-                r#"
-            SELECT
-                ii.instance_id, ii.realm_id, ii.catalog_id,
-                ii.room_id, ii.account_id, ii.object_id, ii.container_item_id,
-                ii.quantity, ii.condition, ii.created_at, ii.updated_at,
-                bp.item_key, bp.name, bp.short, bp.description, bp.examine, bp.stackable,
-                COALESCE(array_agg(n2.noun ORDER BY n2.noun) FILTER (WHERE n2.noun IS NOT NULL), ARRAY[]::TEXT[]) as nouns
-            FROM item_instances ii
-            JOIN bp_items_catalog bp ON ii.catalog_id = bp.id
-            JOIN bp_item_nouns n ON n.item_id = bp.id AND LOWER(n.noun) = LOWER($3)
-            LEFT JOIN bp_item_nouns n2 ON n2.item_id = bp.id
-            WHERE ii.realm_id = $1 AND ii.room_id = $2
-            GROUP BY ii.instance_id, bp.id
-            LIMIT 1
-            "#,
+                &item_instance_query(
+                    &format!("ii.realm_id = $1 AND ii.room_id = $2 AND {}", has_noun("bp", "$3")),
+                    "LIMIT 1",
+                ),
                 &[&realm_id, &room_id, &noun],
             )
             .await?;
 
-        row.map(|r| {
-            let location =
-                ItemLocation::from_db_columns(r.get(3), r.get(4), r.get(5), r.get(6)).map_err(DbError::DataError)?;
-
-            Ok(ItemInstance {
-                instance_id: r.get(0),
-                realm_id: r.get(1),
-                catalog_id: r.get(2),
-                location,
-                quantity: r.get(7),
-                condition: r.get(8),
-                created_at: r.get(9),
-                updated_at: r.get(10),
-                item_key: r.get(11),
-                name: r.get(12),
-                short: r.get(13),
-                description: r.get(14),
-                examine: r.get(15),
-                stackable: r.get(16),
-                nouns: r.get(17),
-            })
-        })
-        .transpose()
+        map_row_opt(row, ItemInstance::try_from_row, "InventoryRepo::find_item_in_room")
     }
 
     // ========================================================================
@@ -527,47 +330,16 @@ impl InventoryRepo for InventoryRepository {
 
         let rows = client
             .query(
-                r#"
-            SELECT
-                ii.instance_id, ii.realm_id, ii.catalog_id,
-                ii.room_id, ii.account_id, ii.object_id, ii.container_item_id,
-                ii.quantity, ii.condition, ii.created_at, ii.updated_at,
-                bp.item_key, bp.name, bp.short, bp.description, bp.examine, bp.stackable,
-                COALESCE(array_agg(n.noun ORDER BY n.noun) FILTER (WHERE n.noun IS NOT NULL), ARRAY[]::TEXT[]) as nouns
-            FROM item_instances ii
-            JOIN bp_items_catalog bp ON ii.catalog_id = bp.id
-            LEFT JOIN bp_item_nouns n ON n.item_id = bp.id
-            WHERE ii.realm_id = $1 AND ii.object_id = $2
-            GROUP BY ii.instance_id, bp.id
-            ORDER BY bp.name
-            "#,
+                &item_instance_query(
+                    "ii.realm_id = $1 AND ii.object_id = $2",
+                    "ORDER BY bp.name, ii.created_at, ii.instance_id",
+                ),
                 &[&realm_id, &object_id],
             )
             .await?;
 
-        rows.into_iter()
-            .map(|row| {
-                let location = ItemLocation::from_db_columns(row.get(3), row.get(4), row.get(5), row.get(6))
-                    .map_err(DbError::DataError)?;
-
-                Ok(ItemInstance {
-                    instance_id: row.get(0),
-                    realm_id: row.get(1),
-                    catalog_id: row.get(2),
-                    location,
-                    quantity: row.get(7),
-                    condition: row.get(8),
-                    created_at: row.get(9),
-                    updated_at: row.get(10),
-                    item_key: row.get(11),
-                    name: row.get(12),
-                    short: row.get(13),
-                    description: row.get(14),
-                    examine: row.get(15),
-                    stackable: row.get(16),
-                    nouns: row.get(17),
-                })
-            })
+        rows.iter()
+            .map(|row| map_row(row, ItemInstance::try_from_row, "InventoryRepo::get_object_items"))
             .collect()
     }
 
@@ -581,48 +353,15 @@ impl InventoryRepo for InventoryRepository {
 
         let row = client
             .query_opt(
-                r#"
-            SELECT
-                ii.instance_id, ii.realm_id, ii.catalog_id,
-                ii.room_id, ii.account_id, ii.object_id, ii.container_item_id,
-                ii.quantity, ii.condition, ii.created_at, ii.updated_at,
-                bp.item_key, bp.name, bp.short, bp.description, bp.examine, bp.stackable,
-                COALESCE(array_agg(n2.noun ORDER BY n2.noun) FILTER (WHERE n2.noun IS NOT NULL), ARRAY[]::TEXT[]) as nouns
-            FROM item_instances ii
-            JOIN bp_items_catalog bp ON ii.catalog_id = bp.id
-            JOIN bp_item_nouns n ON n.item_id = bp.id AND LOWER(n.noun) = LOWER($3)
-            LEFT JOIN bp_item_nouns n2 ON n2.item_id = bp.id
-            WHERE ii.realm_id = $1 AND ii.object_id = $2
-            GROUP BY ii.instance_id, bp.id
-            LIMIT 1
-            "#,
+                &item_instance_query(
+                    &format!("ii.realm_id = $1 AND ii.object_id = $2 AND {}", has_noun("bp", "$3")),
+                    "LIMIT 1",
+                ),
                 &[&realm_id, &object_id, &noun],
             )
             .await?;
 
-        row.map(|r| {
-            let location =
-                ItemLocation::from_db_columns(r.get(3), r.get(4), r.get(5), r.get(6)).map_err(DbError::DataError)?;
-
-            Ok(ItemInstance {
-                instance_id: r.get(0),
-                realm_id: r.get(1),
-                catalog_id: r.get(2),
-                location,
-                quantity: r.get(7),
-                condition: r.get(8),
-                created_at: r.get(9),
-                updated_at: r.get(10),
-                item_key: r.get(11),
-                name: r.get(12),
-                short: r.get(13),
-                description: r.get(14),
-                examine: r.get(15),
-                stackable: r.get(16),
-                nouns: r.get(17),
-            })
-        })
-        .transpose()
+        map_row_opt(row, ItemInstance::try_from_row, "InventoryRepo::find_item_in_object")
     }
 
     // ========================================================================
@@ -645,13 +384,13 @@ impl InventoryRepo for InventoryRepository {
                 WHERE realm_id = $1
                     AND object_id = $2
                     AND account_id IS NOT DISTINCT FROM $3
-            )
+            ) AS found
             "#,
                 &[&realm_id, &object_id, &account_id],
             )
             .await?;
 
-        Ok(row.get(0))
+        Ok(row.get("found"))
     }
 
     async fn mark_loot_instantiated(
@@ -690,187 +429,357 @@ impl InventoryRepo for InventoryRepository {
         let mut client = self.db.pool.get().await?;
         let transaction = client.transaction().await?;
 
-        // 1. Get bp_id for the realm
-        let bp_id: BlueprintId = transaction
-            .query_one("SELECT bp_id FROM realms WHERE id = $1", &[&realm_id])
-            .await?
-            .get(0);
-
-        // 1. Get item definition from bp_items_catalog
-        let catalog_row = transaction
-            .query_one(
-                "SELECT id, name, short, stackable FROM bp_items_catalog WHERE bp_id = $1 AND item_key = $2",
-                &[&bp_id, &item_key],
-            )
-            .await?;
+        let instance_id = spawn_item_tx(&transaction, realm_id, item_key, location, quantity).await?;
 
-        let catalog_id: ItemId = catalog_row.get("id");
-        let stackable: bool = catalog_row.get("stackable");
+        transaction.commit().await?;
+        Ok(instance_id)
+    }
 
-        let (room_id, account_id, object_id, container_item_id) = location.to_db_columns();
+    // ========================================================================
+    // ITEM MOVEMENT
+    // ========================================================================
 
-        // 2. If stackable, try to find existing stack at this location
-        if stackable {
-            let existing = transaction
-                .query_opt(
-                    "SELECT instance_id, quantity
-                FROM item_instances
-                WHERE realm_id = $1
-                    AND catalog_id = $2
-                    AND room_id IS NOT DISTINCT FROM $3
-                    AND account_id IS NOT DISTINCT FROM $4
-                    AND object_id IS NOT DISTINCT FROM $5
-                    AND container_item_id IS NOT DISTINCT FROM $6
-                LIMIT 1",
-                    &[
-                        &realm_id,
-                        &catalog_id,
-                        &room_id,
-                        &account_id,
-                        &object_id,
-                        &container_item_id,
-                    ],
-                )
-                .await?;
+    async fn move_item(&self, instance_id: ItemId, new_location: ItemLocation) -> DbResult<()> {
+        let mut client = self.db.pool.get().await?;
+        let transaction = client.transaction().await?;
 
-            if let Some(row) = existing {
-                // Stack exists - update quantity
-                let instance_id: ItemId = row.get(0);
-                let current_quantity: i32 = row.get(1);
-                let new_quantity = current_quantity + quantity;
+        move_item_tx(&transaction, instance_id, new_location).await?;
 
-                transaction
-                    .execute(
-                        "UPDATE item_instances
-                    SET quantity = $1, updated_at = NOW()
-                    WHERE instance_id = $2",
-                        &[&new_quantity, &instance_id],
-                    )
-                    .await?;
+        transaction.commit().await?;
+        Ok(())
+    }
 
-                transaction.commit().await?;
-                return Ok(instance_id);
-            }
-        }
+    async fn move_item_quantity(
+        &self,
+        instance_id: ItemId,
+        quantity: i32,
+        new_location: ItemLocation,
+    ) -> DbResult<i32> {
+        let mut client = self.db.pool.get().await?;
+        let transaction = client.transaction().await?;
 
-        // 3. No existing stack (or not stackable) - create new instance
+        // Lock the stack so two players splitting it cannot both take the last ones
         let row = transaction
             .query_one(
-                "INSERT INTO item_instances (
-                realm_id, catalog_id, item_key,
-                room_id, account_id, object_id, container_item_id,
-                quantity, created_at, updated_at
-            )
-            VALUES ($1, $2, $3, $4, $5, $6, $7, $8, NOW(), NOW())
-            RETURNING instance_id",
-                &[
-                    &realm_id,
-                    &catalog_id,
-                    &item_key,
-                    &room_id,
-                    &account_id,
-                    &object_id,
-                    &container_item_id,
-                    &quantity,
-                ],
+                "SELECT realm_id, item_key, quantity FROM item_instances WHERE instance_id = $1 FOR UPDATE",
+                &[&instance_id],
             )
             .await?;
+        let realm_id: RealmId = row.get("realm_id");
+        let item_key: String = row.get("item_key");
+        let available: i32 = row.get("quantity");
 
-        let instance_id: ItemId = row.get(0);
+        let moved = quantity.min(available);
+        if moved == available {
+            move_item_tx(&transaction, instance_id, new_location).await?;
+        } else {
+            transaction
+                .execute(
+                    "UPDATE item_instances SET quantity = quantity - $1, updated_at = NOW() WHERE instance_id = $2",
+                    &[&moved, &instance_id],
+                )
+                .await?;
+            let split = spawn_item_tx(&transaction, realm_id, &item_key, new_location, moved).await?;
+            // A new stack (created in this transaction, so not one it merged into) comes from the
+            // same place as the one it was split off, and is dropped when it lands in a room
+            transaction
+                .execute(
+                    "UPDATE item_instances n
+                    SET home_room_id = s.home_room_id, home_object_id = s.home_object_id,
+                        dropped_at = CASE WHEN n.room_id IS NOT NULL THEN NOW() END
+                    FROM item_instances s
+                    WHERE n.instance_id = $1 AND s.instance_id = $2 AND n.created_at = NOW()",
+                    &[&split, &instance_id],
+                )
+                .await?;
+        }
 
         transaction.commit().await?;
-        Ok(instance_id)
+        Ok(moved)
     }
 
     // ========================================================================
-    // ITEM MOVEMENT
+    // STASHES
     // ========================================================================
 
-    async fn move_item(&self, instance_id: ItemId, new_location: ItemLocation) -> DbResult<()> {
-        let mut client = self.db.pool.get().await?;
-        let transaction = client.transaction().await?;
+    async fn get_stash(
+        &self,
+        object_id: ObjectId,
+        account_id: AccountId,
+        realm_id: Option<RealmId>,
+    ) -> DbResult<Vec<StashedItem>> {
+        let client = self.db.pool.get().await?;
 
-        // Get item info
-        let item_row = transaction
-            .query_one(
-                "SELECT realm_id, catalog_id, quantity FROM item_instances WHERE instance_id = $1",
-                &[&instance_id],
+        let rows = client
+            .query(
+                r#"
+            SELECT
+                s.id, s.object_id, s.account_id, s.realm_id, s.catalog_id, s.quantity, s.condition,
+                c.item_key, c.name, c.short, c.stackable,
+                COALESCE(array_agg(n.noun ORDER BY n.noun) FILTER (WHERE n.noun IS NOT NULL), ARRAY[]::TEXT[]) as nouns
+            FROM object_stashes s
+            JOIN bp_items_catalog c ON s.catalog_id = c.id
+            LEFT JOIN bp_item_nouns n ON n.item_id = c.id
+            WHERE s.object_id = $1 AND s.account_id = $2 AND s.realm_id IS NOT DISTINCT FROM $3
+            GROUP BY s.id, c.id
+            ORDER BY c.name, s.stored_at, s.id
+            "#,
+                &[&object_id, &account_id, &realm_id],
             )
             .await?;
 
-        let realm_id: RealmId = item_row.get(0);
-        let catalog_id: ItemId = item_row.get(1);
-        let quantity: i32 = item_row.get(2);
-
-        // Check if stackable
-        let stackable: bool = transaction
-            .query_one("SELECT stackable FROM bp_items_catalog WHERE id = $1", &[&catalog_id])
-            .await?
-            .get(0);
-
-        let (room_id, account_id, object_id, container_item_id) = new_location.to_db_columns();
-
-        // If stackable, try to merge with existing stack at destination
-        if stackable {
-            let existing = transaction
-                .query_opt(
-                    "SELECT instance_id, quantity
-                FROM item_instances
-                WHERE realm_id = $1
-                    AND catalog_id = $2
-                    AND instance_id != $3
-                    AND room_id IS NOT DISTINCT FROM $4
-                    AND account_id IS NOT DISTINCT FROM $5
-                    AND object_id IS NOT DISTINCT FROM $6
-                    AND container_item_id IS NOT DISTINCT FROM $7
-                LIMIT 1",
-                    &[
-                        &realm_id,
-                        &catalog_id,
-                        &instance_id,
-                        &room_id,
-                        &account_id,
-                        &object_id,
-                        &container_item_id,
-                    ],
+        rows.into_iter()
+            .map(|row| {
+                map_row(
+                    &row,
+                    StashedItem::try_from_row,
+                    &format!("InventoryRepo::get_stash object_id={}", object_id),
                 )
-                .await?;
+            })
+            .collect()
+    }
+
+    async fn stash_item(
+        &self,
+        instance_id: ItemId,
+        quantity: i32,
+        object_id: ObjectId,
+        account_id: AccountId,
+        realm_id: Option<RealmId>,
+        capacity: i32,
+    ) -> DbResult<Option<i32>> {
+        let mut client = self.db.pool.get().await?;
+        let transaction = client.transaction().await?;
 
-            if let Some(row) = existing {
-                // Merge into existing stack
-                let existing_id: ItemId = row.get(0);
-                let existing_quantity: i32 = row.get(1);
+        // Lock the stack so it cannot be dropped or given away while it is being stored
+        let Some(row) = transaction
+            .query_opt(
+                "SELECT ii.catalog_id, ii.item_key, ii.quantity, ii.condition, c.stackable
+                FROM item_instances ii
+                JOIN bp_items_catalog c ON ii.catalog_id = c.id
+                WHERE ii.instance_id = $1 AND ii.account_id = $2
+                FOR UPDATE OF ii",
+                &[&instance_id, &account_id],
+            )
+            .await?
+        else {
+            return Ok(Some(0));
+        };
+        let catalog_id: ItemId = row.get("catalog_id");
+        let item_key: String = row.get("item_key");
+        let available: i32 = row.get("quantity");
+        let condition: Option<serde_json::Value> = row.get("condition");
+        let stackable: bool = row.get("stackable");
+
+        let stored = quantity.min(available);
+
+        // The player's slots, locked so two deposits cannot both take the last free one
+        let slots = transaction
+            .query(
+                "SELECT id, catalog_id FROM object_stashes
+                WHERE object_id = $1 AND account_id = $2 AND realm_id IS NOT DISTINCT FROM $3
+                FOR UPDATE",
+                &[&object_id, &account_id, &realm_id],
+            )
+            .await?;
+        let stack = slots
+            .iter()
+            .find(|slot| stackable && slot.get::<_, ItemId>(1) == catalog_id)
+            .map(|slot| slot.get::<_, ItemId>(0));
 
-                // Update existing stack
+        match stack {
+            Some(stash_id) => {
                 transaction
                     .execute(
-                        "UPDATE item_instances SET quantity = $1, updated_at = NOW() WHERE instance_id = $2",
-                        &[&(existing_quantity + quantity), &existing_id],
+                        "UPDATE object_stashes SET quantity = quantity + $1 WHERE id = $2",
+                        &[&stored, &stash_id],
                     )
                     .await?;
-
-                // Delete moved item
+            }
+            None if slots.len() >= capacity as usize => return Ok(None),
+            None => {
                 transaction
-                    .execute("DELETE FROM item_instances WHERE instance_id = $1", &[&instance_id])
+                    .execute(
+                        "INSERT INTO object_stashes
+                            (object_id, account_id, realm_id, catalog_id, item_key, quantity, condition)
+                        VALUES ($1, $2, $3, $4, $5, $6, COALESCE($7::jsonb, '{}'::jsonb))",
+                        &[
+                            &object_id,
+                            &account_id,
+                            &realm_id,
+                            &catalog_id,
+                            &item_key,
+                            &stored,
+                            &condition,
+                        ],
+                    )
                     .await?;
-
-                transaction.commit().await?;
-                return Ok(());
             }
         }
 
-        // No merge - just update location
-        transaction
-            .execute(
-                "UPDATE item_instances
-            SET room_id = $1, account_id = $2, object_id = $3, container_item_id = $4, updated_at = NOW()
-            WHERE instance_id = $5",
-                &[&room_id, &account_id, &object_id, &container_item_id, &instance_id],
+        if stored == available {
+            transaction
+                .execute("DELETE FROM item_instances WHERE instance_id = $1", &[&instance_id])
+                .await?;
+        } else {
+            transaction
+                .execute(
+                    "UPDATE item_instances SET quantity = quantity - $1, updated_at = NOW() WHERE instance_id = $2",
+                    &[&stored, &instance_id],
+                )
+                .await?;
+        }
+
+        transaction.commit().await?;
+        Ok(Some(stored))
+    }
+
+    async fn unstash_item(
+        &self,
+        stash_id: ItemId,
+        quantity: i32,
+        realm_id: RealmId,
+        account_id: AccountId,
+    ) -> DbResult<i32> {
+        let mut client = self.db.pool.get().await?;
+        let transaction = client.transaction().await?;
+
+        let Some(row) = transaction
+            .query_opt(
+                "SELECT item_key, quantity, condition FROM object_stashes WHERE id = $1 AND account_id = $2 FOR UPDATE",
+                &[&stash_id, &account_id],
+            )
+            .await?
+        else {
+            return Ok(0);
+        };
+        let item_key: String = row.get("item_key");
+        let available: i32 = row.get("quantity");
+        let condition: serde_json::Value = row.get("condition");
+
+        let moved = quantity.min(available);
+        if moved == available {
+            transaction
+                .execute("DELETE FROM object_stashes WHERE id = $1", &[&stash_id])
+                .await?;
+        } else {
+            transaction
+                .execute(
+                    "UPDATE object_stashes SET quantity = quantity - $1 WHERE id = $2",
+                    &[&moved, &stash_id],
+                )
+                .await?;
+        }
+
+        let instance_id = spawn_item_tx(
+            &transaction,
+            realm_id,
+            &item_key,
+            ItemLocation::Player(account_id),
+            moved,
+        )
+        .await?;
+        // Durability and the like come back with the item
+        if condition.as_object().is_some_and(|c| !c.is_empty()) {
+            transaction
+                .execute(
+                    "UPDATE item_instances SET condition = $1, updated_at = NOW() WHERE instance_id = $2",
+                    &[&condition, &instance_id],
+                )
+                .await?;
+        }
+
+        transaction.commit().await?;
+        Ok(moved)
+    }
+
+    // ========================================================================
+    // DROPPED ITEMS
+    // ========================================================================
+
+    async fn clear_dropped_items(&self) -> DbResult<Vec<ClearedItem>> {
+        let mut client = self.db.pool.get().await?;
+        let transaction = client.transaction().await?;
+
+        // Skip stacks someone is taking right now; they are looked at again on the next run
+        let rows = transaction
+            .query(
+                r#"
+            SELECT ii.instance_id, ii.realm_id, ii.room_id, ii.quantity, ii.home_room_id, ii.home_object_id,
+                   c.short, b.drop_policy
+            FROM item_instances ii
+            JOIN realms r ON ii.realm_id = r.id
+            JOIN blueprints b ON r.bp_id = b.id
+            JOIN bp_items_catalog c ON ii.catalog_id = c.id
+            WHERE ii.room_id IS NOT NULL
+                AND ii.dropped_at IS NOT NULL
+                AND b.drop_policy <> 'persist'
+                AND ii.dropped_at < NOW() - make_interval(mins => b.drop_after_minutes)
+            FOR UPDATE OF ii SKIP LOCKED
+            "#,
+                &[],
             )
             .await?;
 
+        let mut cleared = Vec::with_capacity(rows.len());
+        for row in rows {
+            let instance_id: ItemId = row.get("instance_id");
+            let room_id: RoomId = row.get("room_id");
+            let quantity: i32 = row.get("quantity");
+            let home_room_id: Option<RoomId> = row.get("home_room_id");
+            let home_object_id: Option<ObjectId> = row.get("home_object_id");
+            let short: String = row.get("short");
+            let policy: &str = row.get("drop_policy");
+
+            let returned = match (policy, home_object_id, home_room_id) {
+                ("return", Some(object_id), _) => {
+                    move_item_tx(&transaction, instance_id, ItemLocation::Object(object_id)).await?;
+                    true
+                }
+                ("return", None, Some(home)) => {
+                    transaction
+                        .execute(
+                            "UPDATE item_instances SET room_id = $1, dropped_at = NULL, updated_at = NOW() WHERE instance_id = $2",
+                            &[&home, &instance_id],
+                        )
+                        .await?;
+                    // Already home: nothing to see
+                    if home == room_id {
+                        continue;
+                    }
+                    true
+                }
+                _ => {
+                    // Along with whatever is inside it
+                    transaction
+                        .execute(
+                            "WITH RECURSIVE inside AS (
+                                SELECT instance_id FROM item_instances WHERE instance_id = $1
+                                UNION ALL
+                                SELECT i.instance_id FROM item_instances i JOIN inside ON i.container_item_id = inside.instance_id
+                            )
+                            DELETE FROM item_instances WHERE instance_id IN (SELECT instance_id FROM inside)",
+                            &[&instance_id],
+                        )
+                        .await?;
+                    false
+                }
+            };
+
+            cleared.push(ClearedItem {
+                realm_id: row.get("realm_id"),
+                room_id,
+                text: if quantity > 1 {
+                    format!("{} (x{})", short, quantity)
+                } else {
+                    short
+                },
+                returned,
+            });
+        }
+
         transaction.commit().await?;
-        Ok(())
+        Ok(cleared)
     }
 
     // ========================================================================
@@ -888,7 +797,7 @@ impl InventoryRepo for InventoryRepository {
             )
             .await?;
 
-        let quantity: i32 = row.get(0);
+        let quantity: i32 = row.get("quantity");
 
         if quantity <= 1 {
             // Delete item
@@ -934,6 +843,29 @@ impl InventoryRepo for InventoryRepository {
         Ok(())
     }
 
+    async fn adjust_durability(&self, instance_id: ItemId, delta: i32, max: i32) -> DbResult<i32> {
+        let client = self.db.pool.get().await?;
+
+        let row = client
+            .query_one(
+                r#"
+                UPDATE item_instances
+                SET condition = jsonb_set(
+                        COALESCE(condition, '{}'::jsonb),
+                        ARRAY[$4::text],
+                        to_jsonb(GREATEST(0, LEAST($3, COALESCE((condition->>$4::text)::int, $3) + $2)))
+                    ),
+                    updated_at = NOW()
+                WHERE instance_id = $1
+                RETURNING (condition->>$4::text)::int AS durability
+                "#,
+                &[&instance_id, &delta, &max, &DURABILITY_KEY],
+            )
+            .await?;
+
+        Ok(row.try_get("durability")?)
+    }
+
     async fn delete_item(&self, instance_id: ItemId) -> DbResult<()> {
         let client = self.db.pool.get().await?;
 
@@ -944,3 +876,241 @@ impl InventoryRepo for InventoryRepository {
         Ok(())
     }
 }
+
+/// Moves a whole item instance inside a running transaction, merging it into an existing stack at
+/// the destination when the item is stackable
+/// Condition on the catalog item aliased `item` having the noun bound to `param`, case-insensitive
+fn has_noun(item: &str, param: &str) -> String {
+    format!(
+        "EXISTS (SELECT 1 FROM bp_item_nouns m WHERE m.item_id = {}.id AND LOWER(m.noun) = LOWER({}))",
+        item, param
+    )
+}
+
+/// Catalog items (as `c`) with their nouns, in the shape `Item::try_from_row` reads. `where_clause`
+/// filters the catalog rows, `tail` goes after the GROUP BY (ORDER BY, LIMIT).
+fn catalog_item_query(where_clause: &str, tail: &str) -> String {
+    format!(
+        r#"
+        SELECT
+            c.id, c.bp_id, c.item_key, c.name, c.short,
+            c.description, c.examine, c.stackable,
+            c.max_durability, c.wear_per_use, c.repair_tool, c.weight, c.pages, c.is_coin,
+            COALESCE(array_agg(n.noun ORDER BY n.noun) FILTER (WHERE n.noun IS NOT NULL), ARRAY[]::TEXT[]) as nouns
+        FROM bp_items_catalog c
+        LEFT JOIN bp_item_nouns n ON n.item_id = c.id
+        WHERE {}
+        GROUP BY c.id
+        {}
+        "#,
+        where_clause, tail
+    )
+}
+
+/// Item instances (as `ii`) with their catalog entry (as `bp`) and nouns, in the shape
+/// `ItemInstance::try_from_row` reads. `where_clause` filters the instances, `tail` goes after the
+/// GROUP BY (ORDER BY, LIMIT).
+fn item_instance_query(where_clause: &str, tail: &str) -> String {
+    format!(
+        r#"
+        SELECT
+            ii.instance_id, ii.realm_id, ii.catalog_id,
+            ii.room_id, ii.account_id, ii.object_id, ii.container_item_id,
+            ii.quantity, ii.condition, ii.created_at, ii.updated_at,
+            bp.item_key, bp.name, bp.short, bp.description, bp.examine, bp.stackable,
+            COALESCE(array_agg(n.noun ORDER BY n.noun) FILTER (WHERE n.noun IS NOT NULL), ARRAY[]::TEXT[]) as nouns,
+            bp.max_durability, bp.wear_per_use, bp.repair_tool, bp.weight, bp.is_coin
+        FROM item_instances ii
+        JOIN bp_items_catalog bp ON ii.catalog_id = bp.id
+        LEFT JOIN bp_item_nouns n ON n.item_id = bp.id
+        WHERE {}
+        GROUP BY ii.instance_id, bp.id
+        {}
+        "#,
+        where_clause, tail
+    )
+}
+
+async fn move_item_tx(transaction: &Transaction<'_>, instance_id: ItemId, new_location: ItemLocation) -> DbResult<()> {
+    // Get item info
+    let item_row = transaction
+        .query_one(
+            "SELECT realm_id, catalog_id, quantity FROM item_instances WHERE instance_id = $1",
+            &[&instance_id],
+        )
+        .await?;
+
+    let realm_id: RealmId = item_row.get("realm_id");
+    let catalog_id: ItemId = item_row.get("catalog_id");
+    let quantity: i32 = item_row.get("quantity");
+
+    // Check if stackable
+    let stackable: bool = transaction
+        .query_one("SELECT stackable FROM bp_items_catalog WHERE id = $1", &[&catalog_id])
+        .await?
+        .get("stackable");
+
+    let (room_id, account_id, object_id, container_item_id) = new_location.to_db_columns();
+
+    // If stackable, try to merge with existing stack at destination
+    if stackable {
+        let existing = transaction
+            .query_opt(
+                "SELECT instance_id, quantity
+            FROM item_instances
+            WHERE realm_id = $1
+                AND catalog_id = $2
+                AND instance_id != $3
+                AND room_id IS NOT DISTINCT FROM $4
+                AND account_id IS NOT DISTINCT FROM $5
+                AND object_id IS NOT DISTINCT FROM $6
+                AND container_item_id IS NOT DISTINCT FROM $7
+            LIMIT 1",
+                &[
+                    &realm_id,
+                    &catalog_id,
+                    &instance_id,
+                    &room_id,
+                    &account_id,
+                    &object_id,
+                    &container_item_id,
+                ],
+            )
+            .await?;
+
+        if let Some(row) = existing {
+            // Merge into existing stack
+            let existing_id: ItemId = row.get("instance_id");
+            let existing_quantity: i32 = row.get("quantity");
+
+            // Update existing stack. A stack that was dropped counts as dropped again; one that
+            // was spawned here stays put
+            transaction
+                .execute(
+                    "UPDATE item_instances
+                    SET quantity = $1, updated_at = NOW(), dropped_at = CASE WHEN dropped_at IS NOT NULL THEN NOW() END
+                    WHERE instance_id = $2",
+                    &[&(existing_quantity + quantity), &existing_id],
+                )
+                .await?;
+
+            // Delete moved item
+            transaction
+                .execute("DELETE FROM item_instances WHERE instance_id = $1", &[&instance_id])
+                .await?;
+
+            return Ok(());
+        }
+    }
+
+    // No merge - just update location
+    transaction
+        .execute(
+            "UPDATE item_instances
+        SET room_id = $1, account_id = $2, object_id = $3, container_item_id = $4, updated_at = NOW(),
+            dropped_at = CASE WHEN $1::uuid IS NOT NULL THEN NOW() END
+        WHERE instance_id = $5",
+            &[&room_id, &account_id, &object_id, &container_item_id, &instance_id],
+        )
+        .await?;
+
+    Ok(())
+}
+
+/// Spawns an item inside a running transaction, merging into an existing stack at the location
+/// when the item is stackable. Used by `spawn_item` and by crafting, which has to consume the
+/// inputs in the same transaction.
+pub(super) async fn spawn_item_tx(
+    transaction: &Transaction<'_>,
+    realm_id: RealmId,
+    item_key: &str,
+    location: ItemLocation,
+    quantity: i32,
+) -> DbResult<ItemId> {
+    // 1. Get bp_id for the realm
+    let bp_id: BlueprintId = transaction
+        .query_one("SELECT bp_id FROM realms WHERE id = $1", &[&realm_id])
+        .await?
+        .get("bp_id");
+
+    // 1. Get item definition from bp_items_catalog
+    let catalog_row = transaction
+        .query_one(
+            "SELECT id, name, short, stackable FROM bp_items_catalog WHERE bp_id = $1 AND item_key = $2",
+            &[&bp_id, &item_key],
+        )
+        .await?;
+
+    let catalog_id: ItemId = catalog_row.get("id");
+    let stackable: bool = catalog_row.get("stackable");
+
+    let (room_id, account_id, object_id, container_item_id) = location.to_db_columns();
+
+    // 2. If stackable, try to find existing stack at this location
+    if stackable {
+        let existing = transaction
+            .query_opt(
+                "SELECT instance_id, quantity
+            FROM item_instances
+            WHERE realm_id = $1
+                AND catalog_id = $2
+                AND room_id IS NOT DISTINCT FROM $3
+                AND account_id IS NOT DISTINCT FROM $4
+                AND object_id IS NOT DISTINCT FROM $5
+                AND container_item_id IS NOT DISTINCT FROM $6
+            LIMIT 1",
+                &[
+                    &realm_id,
+                    &catalog_id,
+                    &room_id,
+                    &account_id,
+                    &object_id,
+                    &container_item_id,
+                ],
+            )
+            .await?;
+
+        if let Some(row) = existing {
+            // Stack exists - update quantity
+            let instance_id: ItemId = row.get("instance_id");
+            let current_quantity: i32 = row.get("quantity");
+            let new_quantity = current_quantity + quantity;
+
+            transaction
+                .execute(
+                    "UPDATE item_instances
+                SET quantity = $1, updated_at = NOW()
+                WHERE instance_id = $2",
+                    &[&new_quantity, &instance_id],
+                )
+                .await?;
+
+            return Ok(instance_id);
+        }
+    }
+
+    // 3. No existing stack (or not stackable) - create new instance
+    let row = transaction
+        .query_one(
+            "INSERT INTO item_instances (
+            realm_id, catalog_id, item_key,
+            room_id, account_id, object_id, container_item_id,
+            quantity, created_at, updated_at, home_room_id, home_object_id
+        )
+        VALUES ($1, $2, $3, $4, $5, $6, $7, $8, NOW(), NOW(), $4, $6)
+        RETURNING instance_id",
+            &[
+                &realm_id,
+                &catalog_id,
+                &item_key,
+                &room_id,
+                &account_id,
+                &object_id,
+                &container_item_id,
+                &quantity,
+            ],
+        )
+        .await?;
+
+    Ok(row.get("instance_id"))
+}
@@ -0,0 +1,19 @@
+use crate::db::DbResult;
+use crate::models::types::AccountId;
+use chrono::{DateTime, Utc};
+use std::net::IpAddr;
+
+#[async_trait::async_trait]
+pub trait LoginAttemptRepo: Send + Sync {
+    /// Records a failed login; the account is None when the name does not exist
+    async fn record_failure(&self, account_id: Option<AccountId>, ip: Option<IpAddr>) -> DbResult<()>;
+
+    async fn failures_for_account(&self, account_id: AccountId, since: DateTime<Utc>) -> DbResult<u32>;
+
+    async fn failures_for_ip(&self, ip: IpAddr, since: DateTime<Utc>) -> DbResult<u32>;
+
+    async fn lock_until(&self, account_id: AccountId, until: DateTime<Utc>) -> DbResult<()>;
+
+    /// Forgets failures older than `before`
+    async fn prune(&self, before: DateTime<Utc>) -> DbResult<()>;
+}
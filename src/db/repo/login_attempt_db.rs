@@ -0,0 +1,81 @@
+use crate::db::repo::login_attempt::LoginAttemptRepo;
+use crate::db::{Db, DbResult};
+use crate::models::types::AccountId;
+use chrono::{DateTime, Utc};
+use std::net::IpAddr;
+use std::sync::Arc;
+
+pub struct LoginAttemptRepository {
+    db: Arc<Db>,
+}
+
+impl LoginAttemptRepository {
+    pub fn new(db: Arc<Db>) -> Self {
+        Self { db }
+    }
+}
+
+#[async_trait::async_trait]
+impl LoginAttemptRepo for LoginAttemptRepository {
+    async fn record_failure(&self, account_id: Option<AccountId>, ip: Option<IpAddr>) -> DbResult<()> {
+        let client = self.db.get_client().await?;
+
+        client
+            .execute(
+                "INSERT INTO login_failures (account_id, ip) VALUES ($1, $2)",
+                &[&account_id, &ip],
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    async fn failures_for_account(&self, account_id: AccountId, since: DateTime<Utc>) -> DbResult<u32> {
+        let client = self.db.get_client().await?;
+
+        let row = client
+            .query_one(
+                "SELECT count(*) AS n FROM login_failures WHERE account_id = $1 AND attempted_at > $2",
+                &[&account_id, &since],
+            )
+            .await?;
+
+        Ok(row.try_get::<_, i64>("n")? as u32)
+    }
+
+    async fn failures_for_ip(&self, ip: IpAddr, since: DateTime<Utc>) -> DbResult<u32> {
+        let client = self.db.get_client().await?;
+
+        let row = client
+            .query_one(
+                "SELECT count(*) AS n FROM login_failures WHERE ip = $1 AND attempted_at > $2",
+                &[&ip, &since],
+            )
+            .await?;
+
+        Ok(row.try_get::<_, i64>("n")? as u32)
+    }
+
+    async fn lock_until(&self, account_id: AccountId, until: DateTime<Utc>) -> DbResult<()> {
+        let client = self.db.get_client().await?;
+
+        client
+            .execute(
+                "UPDATE accounts SET locked_until = $2 WHERE id = $1",
+                &[&account_id, &until],
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    async fn prune(&self, before: DateTime<Utc>) -> DbResult<()> {
+        let client = self.db.get_client().await?;
+
+        client
+            .execute("DELETE FROM login_failures WHERE attempted_at < $1", &[&before])
+            .await?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,11 @@
+use crate::db::DbResult;
+use crate::models::maintenance::Garbage;
+
+#[async_trait::async_trait]
+pub trait MaintenanceRepo: Send + Sync {
+    /// Counts the garbage of every kind, including kinds with none
+    async fn count_garbage(&self) -> DbResult<Vec<Garbage>>;
+
+    /// Deletes the garbage of every kind in one transaction. Returns how much of each kind went.
+    async fn purge_garbage(&self) -> DbResult<Vec<Garbage>>;
+}
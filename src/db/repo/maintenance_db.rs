@@ -0,0 +1,142 @@
+use crate::db::repo::maintenance::MaintenanceRepo;
+use crate::db::{Db, DbResult};
+use crate::models::maintenance::Garbage;
+use std::sync::Arc;
+
+/// One kind of garbage: the rows of `table` (aliased `t`) matching `condition`
+struct Check {
+    what: &'static str,
+    table: &'static str,
+    condition: &'static str,
+}
+
+/// Item instances and loot state have no foreign keys, so nothing removes them along with the
+/// realm, room, object or account they point at. The other overlays cascade, but go stale when
+/// they point at a room or object of another blueprint than their realm's. Items go first:
+/// items inside a purged container are found by the next run.
+const CHECKS: &[Check] = &[
+    Check {
+        what: "items in deleted realms",
+        table: "item_instances",
+        condition: "NOT EXISTS (SELECT 1 FROM realms r WHERE r.id = t.realm_id)",
+    },
+    Check {
+        what: "items in deleted rooms",
+        table: "item_instances",
+        condition: "t.room_id IS NOT NULL AND NOT EXISTS (SELECT 1 FROM bp_rooms r WHERE r.id = t.room_id)",
+    },
+    Check {
+        what: "items in deleted objects",
+        table: "item_instances",
+        condition: "t.object_id IS NOT NULL AND NOT EXISTS (SELECT 1 FROM bp_objects o WHERE o.id = t.object_id)",
+    },
+    Check {
+        what: "items of deleted accounts",
+        table: "item_instances",
+        condition: "t.account_id IS NOT NULL AND NOT EXISTS (SELECT 1 FROM accounts a WHERE a.id = t.account_id)",
+    },
+    Check {
+        what: "items in deleted containers",
+        table: "item_instances",
+        condition: "t.container_item_id IS NOT NULL
+            AND NOT EXISTS (SELECT 1 FROM item_instances c WHERE c.instance_id = t.container_item_id)",
+    },
+    Check {
+        what: "items in rooms of another blueprint",
+        table: "item_instances",
+        condition: "EXISTS (SELECT 1 FROM bp_rooms r JOIN realms re ON re.id = t.realm_id
+            WHERE r.id = t.room_id AND r.bp_id <> re.bp_id)",
+    },
+    Check {
+        what: "items in objects of another blueprint",
+        table: "item_instances",
+        condition:
+            "EXISTS (SELECT 1 FROM bp_objects o JOIN bp_rooms r ON r.id = o.room_id JOIN realms re ON re.id = t.realm_id
+            WHERE o.id = t.object_id AND r.bp_id <> re.bp_id)",
+    },
+    Check {
+        what: "loot state of deleted realms, objects or accounts",
+        table: "loot_instantiation_state",
+        condition: "NOT EXISTS (SELECT 1 FROM realms r WHERE r.id = t.realm_id)
+            OR NOT EXISTS (SELECT 1 FROM bp_objects o WHERE o.id = t.object_id)
+            OR NOT EXISTS (SELECT 1 FROM accounts a WHERE a.id = t.account_id)",
+    },
+    Check {
+        what: "player room state of another blueprint",
+        table: "user_room_kv",
+        condition: "EXISTS (SELECT 1 FROM bp_rooms r JOIN realms re ON re.id = t.realm_id
+            WHERE r.id = t.room_id AND r.bp_id <> re.bp_id)",
+    },
+    Check {
+        what: "player object state of another blueprint",
+        table: "user_object_kv",
+        condition:
+            "EXISTS (SELECT 1 FROM bp_objects o JOIN bp_rooms r ON r.id = o.room_id JOIN realms re ON re.id = t.realm_id
+            WHERE o.id = t.object_id AND r.bp_id <> re.bp_id)",
+    },
+    Check {
+        what: "player exit state of another blueprint",
+        table: "user_exits",
+        condition: "EXISTS (SELECT 1 FROM bp_rooms r JOIN realms re ON re.id = t.realm_id
+            WHERE r.id = t.room_id AND r.bp_id <> re.bp_id)",
+    },
+    Check {
+        what: "realm room state of another blueprint",
+        table: "realm_room_kv",
+        condition: "EXISTS (SELECT 1 FROM bp_rooms r JOIN realms re ON re.id = t.realm_id
+            WHERE r.id = t.room_id AND r.bp_id <> re.bp_id)",
+    },
+    Check {
+        what: "realm object state of another blueprint",
+        table: "realm_object_kv",
+        condition:
+            "EXISTS (SELECT 1 FROM bp_objects o JOIN bp_rooms r ON r.id = o.room_id JOIN realms re ON re.id = t.realm_id
+            WHERE o.id = t.object_id AND r.bp_id <> re.bp_id)",
+    },
+];
+
+pub struct MaintenanceRepository {
+    db: Arc<Db>,
+}
+
+impl MaintenanceRepository {
+    pub fn new(db: Arc<Db>) -> Self {
+        Self { db }
+    }
+}
+
+#[async_trait::async_trait]
+impl MaintenanceRepo for MaintenanceRepository {
+    async fn count_garbage(&self) -> DbResult<Vec<Garbage>> {
+        let client = self.db.get_client().await?;
+
+        let mut garbage = Vec::with_capacity(CHECKS.len());
+        for check in CHECKS {
+            let sql = format!("SELECT count(*) AS n FROM {} t WHERE {}", check.table, check.condition);
+            let count: i64 = client.query_one(&sql, &[]).await?.get("n");
+            garbage.push(Garbage {
+                what: check.what,
+                count: count as u64,
+            });
+        }
+        Ok(garbage)
+    }
+
+    async fn purge_garbage(&self) -> DbResult<Vec<Garbage>> {
+        let mut client = self.db.get_client().await?;
+        let tx = client.transaction().await?;
+
+        let mut garbage = Vec::with_capacity(CHECKS.len());
+        for check in CHECKS {
+            let sql = format!("DELETE FROM {} t WHERE {}", check.table, check.condition);
+            let count = tx.execute(&sql, &[]).await?;
+            garbage.push(Garbage {
+                what: check.what,
+                count,
+            });
+        }
+
+        tx.commit().await?;
+        Ok(garbage)
+    }
+}
@@ -0,0 +1,15 @@
+use crate::db::DbResult;
+use crate::models::report::{NewReport, Report};
+use crate::models::types::AccountId;
+
+#[async_trait::async_trait]
+pub trait ModerationRepo: Send + Sync {
+    /// Files a report, returns its id
+    async fn file_report(&self, report: &NewReport) -> DbResult<i64>;
+    /// True when the reporter already has an open report against the target
+    async fn has_open_report(&self, reporter_id: AccountId, target_id: AccountId) -> DbResult<bool>;
+    /// Open reports, oldest first
+    async fn open_reports(&self) -> DbResult<Vec<Report>>;
+    /// Resolves an open report, returns false when there was no such open report
+    async fn resolve(&self, report_id: i64, moderator_id: AccountId, resolution: Option<&str>) -> DbResult<bool>;
+}
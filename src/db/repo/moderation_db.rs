@@ -0,0 +1,98 @@
+use crate::db::repo::moderation::ModerationRepo;
+use crate::db::{Db, DbResult, map_row};
+use crate::models::report::{NewReport, Report};
+use crate::models::types::AccountId;
+use std::sync::Arc;
+
+pub struct ModerationRepository {
+    db: Arc<Db>,
+}
+
+impl ModerationRepository {
+    pub fn new(db: Arc<Db>) -> Self {
+        Self { db: db.clone() }
+    }
+}
+
+#[async_trait::async_trait]
+impl ModerationRepo for ModerationRepository {
+    async fn file_report(&self, report: &NewReport) -> DbResult<i64> {
+        let client = self.db.get_client().await?;
+
+        let row = client
+            .query_one(
+                r#"
+                INSERT INTO reports (reporter_id, target_id, reason, realm_id, room_id)
+                VALUES ($1, $2, $3, $4, $5)
+                RETURNING id
+                "#,
+                &[
+                    &report.reporter_id,
+                    &report.target_id,
+                    &report.reason,
+                    &report.realm_id,
+                    &report.room_id,
+                ],
+            )
+            .await?;
+
+        Ok(row.get("id"))
+    }
+
+    async fn has_open_report(&self, reporter_id: AccountId, target_id: AccountId) -> DbResult<bool> {
+        let client = self.db.get_client().await?;
+
+        let row = client
+            .query_opt(
+                r#"
+                SELECT 1 FROM reports
+                WHERE reporter_id = $1 AND target_id = $2 AND status = 'open'
+                LIMIT 1
+                "#,
+                &[&reporter_id, &target_id],
+            )
+            .await?;
+
+        Ok(row.is_some())
+    }
+
+    async fn open_reports(&self) -> DbResult<Vec<Report>> {
+        let client = self.db.get_client().await?;
+
+        let rows = client
+            .query(
+                r#"
+                SELECT r.id, r.reporter_id, a.username AS reporter, r.target_id, t.username AS target,
+                       r.reason, r.realm_id, r.room_id, r.created_at
+                FROM reports r
+                JOIN accounts a ON a.id = r.reporter_id
+                JOIN accounts t ON t.id = r.target_id
+                WHERE r.status = 'open'
+                ORDER BY r.created_at, r.id
+                "#,
+                &[],
+            )
+            .await?;
+
+        rows.iter()
+            .map(|row| map_row(row, Report::try_from_row, "ModerationRepo::open_reports"))
+            .collect()
+    }
+
+    async fn resolve(&self, report_id: i64, moderator_id: AccountId, resolution: Option<&str>) -> DbResult<bool> {
+        let client = self.db.get_client().await?;
+
+        let updated = client
+            .execute(
+                r#"
+                UPDATE reports
+                SET status = 'resolved', resolved_by = $2, resolved_at = now(), resolution = $3
+                WHERE id = $1 AND status = 'open'
+                "#,
+                &[&report_id, &moderator_id, &resolution],
+            )
+            .await?;
+
+        Ok(updated > 0)
+    }
+}
@@ -0,0 +1,13 @@
+use crate::db::DbResult;
+use crate::models::player_export::PlayerBundle;
+use crate::models::types::AccountId;
+
+#[async_trait::async_trait]
+pub trait PlayerExportRepo: Send + Sync {
+    /// Everything stored for the account, None when there is no such account
+    async fn export(&self, account_id: AccountId) -> DbResult<Option<PlayerBundle>>;
+
+    /// Replaces the account's state by the bundle's, in one transaction. The bundle must have
+    /// been validated for `account_id`.
+    async fn import(&self, account_id: AccountId, bundle: &PlayerBundle) -> DbResult<()>;
+}
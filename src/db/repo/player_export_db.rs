@@ -0,0 +1,174 @@
+use crate::db::repo::player_export::PlayerExportRepo;
+use crate::db::{Db, DbResult};
+use crate::models::player_export::{BUNDLE_FORMAT, PlayerBundle};
+use crate::models::types::AccountId;
+use chrono::{SecondsFormat, Utc};
+use serde_json::Value;
+use std::sync::Arc;
+
+/// Items carried by account $1, followed by everything inside them
+const CARRIED_ITEMS: &str = r#"
+    WITH RECURSIVE carried AS (
+        SELECT * FROM item_instances WHERE account_id = $1
+        UNION ALL
+        SELECT i.* FROM item_instances i JOIN carried c ON i.container_item_id = c.instance_id
+    )
+"#;
+
+/// Per-account tables other than items, in the order they are restored
+const ACCOUNT_TABLES: [&str; 5] = [
+    "characters",
+    "user_room_kv",
+    "user_object_kv",
+    "user_exits",
+    "loot_instantiation_state",
+];
+
+pub struct PlayerExportRepository {
+    db: Arc<Db>,
+}
+
+impl PlayerExportRepository {
+    pub fn new(db: Arc<Db>) -> Self {
+        Self { db }
+    }
+}
+
+#[async_trait::async_trait]
+impl PlayerExportRepo for PlayerExportRepository {
+    async fn export(&self, account_id: AccountId) -> DbResult<Option<PlayerBundle>> {
+        let mut client = self.db.get_client().await?;
+        // One snapshot for all tables
+        let tx = client.build_transaction().read_only(true).start().await?;
+
+        let Some(account) = tx
+            .query_opt(
+                "SELECT to_jsonb(a) - 'password_hash' AS row FROM accounts a WHERE id = $1",
+                &[&account_id],
+            )
+            .await?
+        else {
+            return Ok(None);
+        };
+
+        let mut tables = Vec::with_capacity(ACCOUNT_TABLES.len());
+        for table in ACCOUNT_TABLES {
+            let rows = tx
+                .query(
+                    &format!("SELECT to_jsonb(t) AS row FROM {} t WHERE account_id = $1", table),
+                    &[&account_id],
+                )
+                .await?;
+            tables.push(
+                rows.iter()
+                    .map(|r| r.try_get("row"))
+                    .collect::<Result<Vec<Value>, _>>()?,
+            );
+        }
+        let items = tx
+            .query(
+                &format!("{} SELECT to_jsonb(carried) AS row FROM carried", CARRIED_ITEMS),
+                &[&account_id],
+            )
+            .await?
+            .iter()
+            .map(|r| r.try_get("row"))
+            .collect::<Result<Vec<Value>, _>>()?;
+        tx.commit().await?;
+
+        let [characters, room_kv, object_kv, exits, loot_state] = tables.try_into().expect("one entry per table");
+        Ok(Some(PlayerBundle {
+            format: BUNDLE_FORMAT,
+            exported_at: Utc::now().to_rfc3339_opts(SecondsFormat::Secs, true),
+            account: account.try_get("row")?,
+            characters,
+            items,
+            room_kv,
+            object_kv,
+            exits,
+            loot_state,
+        }))
+    }
+
+    async fn import(&self, account_id: AccountId, bundle: &PlayerBundle) -> DbResult<()> {
+        let mut client = self.db.get_client().await?;
+        let tx = client.transaction().await?;
+
+        // A recreated account gets an unusable password; an existing one keeps its own
+        tx.execute(
+            r#"
+            INSERT INTO accounts
+            SELECT * FROM jsonb_populate_record(NULL::accounts, $1::jsonb || '{"password_hash": "!"}'::jsonb)
+            ON CONFLICT (id) DO UPDATE SET
+                username = EXCLUDED.username,
+                email = EXCLUDED.email,
+                role = EXCLUDED.role,
+                last_login = EXCLUDED.last_login,
+                current_realm_id = EXCLUDED.current_realm_id,
+                current_room_id = EXCLUDED.current_room_id,
+                spawn_realm_id = EXCLUDED.spawn_realm_id,
+                spawn_room_id = EXCLUDED.spawn_room_id,
+                xp = EXCLUDED.xp,
+                health = EXCLUDED.health,
+                coins = EXCLUDED.coins,
+                locked_out = EXCLUDED.locked_out,
+                show_motd = EXCLUDED.show_motd,
+                flags = EXCLUDED.flags,
+                perception = EXCLUDED.perception,
+                delete_after = EXCLUDED.delete_after,
+                locked_until = EXCLUDED.locked_until,
+                tutorial_completed_at = EXCLUDED.tutorial_completed_at,
+                settings = EXCLUDED.settings
+            "#,
+            &[&bundle.account],
+        )
+        .await?;
+
+        // Items: drop what is carried now, and the bundle's items wherever they ended up since
+        let items = Value::Array(bundle.items.clone());
+        tx.execute(
+            &format!(
+                "{} DELETE FROM item_instances WHERE instance_id IN (SELECT instance_id FROM carried)",
+                CARRIED_ITEMS
+            ),
+            &[&account_id],
+        )
+        .await?;
+        tx.execute(
+            r#"
+            DELETE FROM item_instances
+            WHERE instance_id IN (SELECT instance_id FROM jsonb_populate_recordset(NULL::item_instances, $1))
+            "#,
+            &[&items],
+        )
+        .await?;
+        tx.execute(
+            "INSERT INTO item_instances SELECT * FROM jsonb_populate_recordset(NULL::item_instances, $1)",
+            &[&items],
+        )
+        .await?;
+
+        let rows = [
+            &bundle.characters,
+            &bundle.room_kv,
+            &bundle.object_kv,
+            &bundle.exits,
+            &bundle.loot_state,
+        ];
+        for (table, rows) in ACCOUNT_TABLES.iter().zip(rows) {
+            tx.execute(&format!("DELETE FROM {} WHERE account_id = $1", table), &[&account_id])
+                .await?;
+            tx.execute(
+                &format!(
+                    "INSERT INTO {0} SELECT * FROM jsonb_populate_recordset(NULL::{0}, $1)",
+                    table
+                ),
+                &[&Value::Array(rows.clone())],
+            )
+            .await?;
+        }
+
+        tx.commit().await?;
+        Ok(())
+    }
+}
@@ -0,0 +1,17 @@
+use crate::db::DbResult;
+use crate::models::account::Account;
+use crate::models::types::AccountId;
+
+#[async_trait::async_trait]
+pub trait PlaytestRepo: Send + Sync {
+    /// Returns the guest persona of `owner`, creating it when needed
+    async fn persona_for(&self, owner: &Account) -> DbResult<Account>;
+    /// Drops all per-player state of a persona: inventory, discovered room/object/exit state and
+    /// position, so it plays like a brand new account
+    async fn reset_persona(&self, persona_id: AccountId) -> DbResult<()>;
+
+    /// Creates a throwaway account for automated playthroughs
+    async fn create_tester(&self, username: &str) -> DbResult<Account>;
+    /// Deletes a tester or persona account with all its state
+    async fn delete_persona(&self, persona_id: AccountId) -> DbResult<()>;
+}
@@ -0,0 +1,113 @@
+use crate::db::error::DbError;
+use crate::db::repo::playtest::PlaytestRepo;
+use crate::db::{Db, DbResult, map_row, map_row_opt};
+use crate::models::account::{Account, AccountRole};
+use crate::models::types::AccountId;
+use std::sync::Arc;
+
+pub struct PlaytestRepository {
+    db: Arc<Db>,
+}
+
+impl PlaytestRepository {
+    pub fn new(db: Arc<Db>) -> Self {
+        Self { db: db.clone() }
+    }
+}
+
+/// Usernames of real accounts cannot contain '~', so personas never clash with them
+fn persona_username(owner: &Account) -> String {
+    format!("{}~guest", owner.username)
+}
+
+#[async_trait::async_trait]
+impl PlaytestRepo for PlaytestRepository {
+    async fn persona_for(&self, owner: &Account) -> DbResult<Account> {
+        let client = self.db.get_client().await?;
+
+        // The password hash is not a valid PHC string, so nobody can log in as a persona. The
+        // update only matches an existing persona of the same owner.
+        let row = client
+            .query_opt(
+                r#"
+                INSERT INTO accounts (username, email, password_hash, role, show_motd, flags)
+                VALUES ($1, $2, '!', $3, false, jsonb_build_object('persona_of', $4::text))
+                ON CONFLICT (username) DO UPDATE SET username = EXCLUDED.username
+                    WHERE accounts.flags->>'persona_of' = $4::text
+                RETURNING *
+                "#,
+                &[
+                    &persona_username(owner),
+                    &format!("{}@persona.invalid", owner.id),
+                    &AccountRole::User,
+                    &owner.id.to_string(),
+                ],
+            )
+            .await?;
+
+        map_row_opt(
+            row,
+            Account::try_from_row,
+            &format!("PlaytestRepo::persona_for owner={}", owner.id),
+        )?
+        .ok_or_else(|| DbError::Decode(format!("username {} is taken", persona_username(owner))))
+    }
+
+    async fn reset_persona(&self, persona_id: AccountId) -> DbResult<()> {
+        let mut client = self.db.pool.get().await?;
+        let tx = client.transaction().await?;
+
+        for sql in [
+            "DELETE FROM item_instances WHERE account_id = $1",
+            "DELETE FROM loot_instantiation_state WHERE account_id = $1",
+            "DELETE FROM user_room_kv WHERE account_id = $1",
+            "DELETE FROM user_object_kv WHERE account_id = $1",
+            "DELETE FROM user_exits WHERE account_id = $1",
+            r#"
+            UPDATE accounts
+            SET current_realm_id = NULL, current_room_id = NULL, xp = 0, health = 100, coins = 0
+            WHERE id = $1
+            "#,
+        ] {
+            tx.execute(sql, &[&persona_id]).await?;
+        }
+
+        tx.commit().await?;
+        Ok(())
+    }
+
+    async fn create_tester(&self, username: &str) -> DbResult<Account> {
+        let client = self.db.get_client().await?;
+
+        let row = client
+            .query_one(
+                r#"
+                INSERT INTO accounts (username, email, password_hash, role, show_motd, flags)
+                VALUES ($1, $2, '!', $3, false, '{"tester": true}'::jsonb)
+                RETURNING *
+                "#,
+                &[&username, &format!("{}@tester.invalid", username), &AccountRole::User],
+            )
+            .await?;
+
+        map_row(
+            &row,
+            Account::try_from_row,
+            &format!("PlaytestRepo::create_tester username={}", username),
+        )
+    }
+
+    async fn delete_persona(&self, persona_id: AccountId) -> DbResult<()> {
+        self.reset_persona(persona_id).await?;
+
+        let client = self.db.get_client().await?;
+        client
+            .execute(
+                "DELETE FROM accounts WHERE id = $1 AND (flags ? 'persona_of' OR flags ? 'tester')",
+                &[&persona_id],
+            )
+            .await?;
+
+        Ok(())
+    }
+}
@@ -1,6 +1,6 @@
 use crate::db::DbResult;
 use crate::models::realm::Realm;
-use crate::models::room::Kv;
+use crate::models::room::{Kv, SpawnedObject, VersionedValue};
 use crate::models::types::{AccountId, ExitId, ObjectId, RealmId, RoomId};
 use std::collections::HashMap;
 
@@ -10,6 +10,22 @@ pub trait RealmRepo: Send + Sync {
     async fn get_by_key(&self, key: &str) -> DbResult<Option<Realm>>;
     async fn create(&self, realm: Realm) -> DbResult<Realm>;
     async fn find_by_owner(&self, owner_id: AccountId) -> DbResult<Vec<Realm>>;
+    /// Live realms of published blueprints, open to every player, with their keys
+    async fn list_public(&self) -> DbResult<Vec<(String, Realm)>>;
+    /// Remembers when the account first entered the realm; later calls keep the first time
+    async fn record_start(&self, realm_id: RealmId, account_id: AccountId) -> DbResult<()>;
+    /// Marks the realm as completed by the account. Returns false when it already was, or the
+    /// account was never recorded entering it.
+    async fn complete(&self, realm_id: RealmId, account_id: AccountId) -> DbResult<bool>;
+    /// Deletes a realm with all its state, returns false when it did not exist
+    async fn delete(&self, realm_id: RealmId) -> DbResult<bool>;
+    /// Wipes the zone and per-player state of a realm, its items, loot and counters, and moves the
+    /// stored position of everyone in it to the entry room. Accounts, completions, reviews and
+    /// persistent stashes are kept. Completions are not quest progress but the record that a player
+    /// finished the realm: the directory averages completion times over them and they are what lets
+    /// a player rate it, so wiping them would orphan existing reviews. Returns false when the realm
+    /// does not exist.
+    async fn reset(&self, realm_id: RealmId) -> DbResult<bool>;
 
     async fn room_kv(&self, realm_id: RealmId, room_id: RoomId) -> DbResult<Kv>;
     async fn obj_kv(&self, realm_id: RealmId, room_id: RoomId) -> DbResult<HashMap<String, Kv>>;
@@ -30,5 +46,73 @@ pub trait RealmRepo: Send + Sync {
         value: &serde_json::Value,
     ) -> DbResult<()>;
 
+    /// Deletes a shared room KV value, but only while it still holds `expected`. Returns false when
+    /// it was changed or removed in between.
+    async fn delete_room_kv_if(
+        &self,
+        realm_id: RealmId,
+        room_id: RoomId,
+        key: &str,
+        expected: &serde_json::Value,
+    ) -> DbResult<bool>;
+
+    /// All shared room KV values stored under `key`, across realms
+    async fn room_kv_by_key(&self, key: &str) -> DbResult<Vec<(RealmId, RoomId, serde_json::Value)>>;
+
+    /// Gets a single shared room KV value together with its version
+    async fn room_kv_versioned(
+        &self,
+        realm_id: RealmId,
+        room_id: RoomId,
+        key: &str,
+    ) -> DbResult<Option<VersionedValue>>;
+
+    /// Compare-and-swap a shared room KV value. When `expected_version` is None, the key must not exist yet.
+    /// Returns false when the stored version did not match (someone else wrote in between).
+    async fn cas_room_kv(
+        &self,
+        realm_id: RealmId,
+        room_id: RoomId,
+        key: &str,
+        value: &serde_json::Value,
+        expected_version: Option<i64>,
+    ) -> DbResult<bool>;
+
+    /// Gets a single shared object KV value together with its version
+    async fn object_kv_versioned(
+        &self,
+        realm_id: RealmId,
+        object_id: ObjectId,
+        key: &str,
+    ) -> DbResult<Option<VersionedValue>>;
+
+    /// Compare-and-swap a shared object KV value. When `expected_version` is None, the key must not exist yet.
+    /// Returns false when the stored version did not match (someone else wrote in between).
+    async fn cas_object_kv(
+        &self,
+        realm_id: RealmId,
+        object_id: ObjectId,
+        key: &str,
+        value: &serde_json::Value,
+        expected_version: Option<i64>,
+    ) -> DbResult<bool>;
+
     async fn set_exit_locked(&self, realm_id: RealmId, room_id: RoomId, exit_id: ExitId, locked: bool) -> DbResult<()>;
+
+    /// Atomically adds `by` to a counter and returns the new value. A missing counter starts at 0.
+    /// With an account the counter belongs to that player, otherwise it is shared by the realm.
+    async fn incr_counter(&self, realm_id: RealmId, account_id: Option<AccountId>, key: &str, by: i64)
+    -> DbResult<i64>;
+
+    /// Current value of a counter, 0 when it was never incremented
+    async fn counter(&self, realm_id: RealmId, account_id: Option<AccountId>, key: &str) -> DbResult<i64>;
+
+    /// Objects scripts spawned into the room, oldest first
+    async fn spawned_objects(&self, realm_id: RealmId, room_id: RoomId) -> DbResult<Vec<SpawnedObject>>;
+
+    /// Stores a spawned object. One with the same key in the room is replaced, keeping its id.
+    async fn upsert_spawned_object(&self, realm_id: RealmId, room_id: RoomId, obj: &SpawnedObject) -> DbResult<()>;
+
+    /// Removes a spawned object, returns false when there was none with that key
+    async fn delete_spawned_object(&self, realm_id: RealmId, room_id: RoomId, key: &str) -> DbResult<bool>;
 }
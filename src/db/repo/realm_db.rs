@@ -2,7 +2,7 @@ use crate::db::error::DbError;
 use crate::db::repo::realm::RealmRepo;
 use crate::db::{Db, DbResult, map_row, map_row_opt};
 use crate::models::realm::Realm;
-use crate::models::room::Kv;
+use crate::models::room::{Kv, SpawnedObject, VersionedValue};
 use crate::models::types::{AccountId, ExitId, ObjectId, RealmId, RoomId};
 use serde_json::Value;
 use std::collections::HashMap;
@@ -25,9 +25,10 @@ impl RealmRepo for RealmRepository {
         let stmt = client
             .prepare_cached(
                 r#"
-            SELECT id, bp_id, title, kind, created_at
-            FROM realms
-            WHERE id = $1
+            SELECT r.id, r.bp_id, r.title, r.kind, r.created_at, b.prompt
+            FROM realms r
+            JOIN blueprints b ON b.id = r.bp_id
+            WHERE r.id = $1
         "#,
             )
             .await?;
@@ -41,9 +42,10 @@ impl RealmRepo for RealmRepository {
         let rows = client
             .query_opt(
                 r#"
-                    SELECT id, bp_id, key, title, kind, created_at, owner_id
-                    FROM realms
-                    WHERE key = $1
+                    SELECT r.id, r.bp_id, r.key, r.title, r.kind, r.created_at, r.owner_id, b.prompt
+                    FROM realms r
+                    JOIN blueprints b ON b.id = r.bp_id
+                    WHERE r.key = $1
                 "#,
                 &[&key],
             )
@@ -74,15 +76,80 @@ impl RealmRepo for RealmRepository {
         Ok(realm)
     }
 
+    async fn delete(&self, realm_id: RealmId) -> DbResult<bool> {
+        let mut client = self.db.pool.get().await?;
+        let tx = client.transaction().await?;
+
+        // Item and loot state have no foreign key on the realm, the rest cascades
+        tx.execute("DELETE FROM item_instances WHERE realm_id = $1", &[&realm_id])
+            .await?;
+        tx.execute("DELETE FROM loot_instantiation_state WHERE realm_id = $1", &[&realm_id])
+            .await?;
+        let deleted = tx.execute("DELETE FROM realms WHERE id = $1", &[&realm_id]).await?;
+
+        tx.commit().await?;
+        Ok(deleted > 0)
+    }
+
+    async fn reset(&self, realm_id: RealmId) -> DbResult<bool> {
+        let mut client = self.db.pool.get().await?;
+        let tx = client.transaction().await?;
+
+        let exists = tx
+            .query_opt("SELECT 1 FROM realms WHERE id = $1 FOR UPDATE", &[&realm_id])
+            .await?;
+        if exists.is_none() {
+            return Ok(false);
+        }
+
+        // realm_completions is left alone, it backs the directory stats and review eligibility
+        for table in [
+            "item_instances",
+            "loot_instantiation_state",
+            "loot_spawns",
+            "room_loot",
+            "realm_kv",
+            "realm_room_kv",
+            "user_room_kv",
+            "realm_object_kv",
+            "user_object_kv",
+            "realm_exits",
+            "user_exits",
+            "realm_counters",
+            "user_counters",
+            "realm_balances",
+            "object_stashes",
+            "realm_spawned_objects",
+        ] {
+            tx.execute(&format!("DELETE FROM {} WHERE realm_id = $1", table), &[&realm_id])
+                .await?;
+        }
+        tx.execute(
+            r#"
+            UPDATE accounts a
+            SET current_room_id = b.entry_room_id
+            FROM realms r
+            JOIN blueprints b ON b.id = r.bp_id
+            WHERE r.id = $1 AND a.current_realm_id = r.id
+        "#,
+            &[&realm_id],
+        )
+        .await?;
+
+        tx.commit().await?;
+        Ok(true)
+    }
+
     async fn find_by_owner(&self, owner_id: AccountId) -> DbResult<Vec<Realm>> {
         let client = self.db.get_client().await?;
 
         let rows = client
             .query(
                 r#"
-            SELECT id, bp_id, title, kind, created_at
-            FROM realms
-            WHERE kind->>'owner' = $1
+            SELECT r.id, r.bp_id, r.title, r.kind, r.created_at, b.prompt
+            FROM realms r
+            JOIN blueprints b ON b.id = r.bp_id
+            WHERE r.owner_id = $1
         "#,
                 &[&owner_id],
             )
@@ -102,6 +169,62 @@ impl RealmRepo for RealmRepository {
         realms
     }
 
+    async fn list_public(&self) -> DbResult<Vec<(String, Realm)>> {
+        let client = self.db.get_client().await?;
+
+        let rows = client
+            .query(
+                r#"
+            SELECT r.id, r.bp_id, r.key, r.title, r.kind, r.created_at, b.prompt
+            FROM realms r
+            JOIN blueprints b ON b.id = r.bp_id
+            WHERE r.kind = 'live' AND r.key IS NOT NULL AND r.owner_id IS NULL
+              AND b.status IN ('published', 'live')
+            ORDER BY r.key
+        "#,
+                &[],
+            )
+            .await?;
+
+        rows.iter()
+            .map(|row| -> DbResult<(String, Realm)> { Ok((row.try_get("key")?, Realm::try_from_row(row)?)) })
+            .collect()
+    }
+
+    async fn record_start(&self, realm_id: RealmId, account_id: AccountId) -> DbResult<()> {
+        let client = self.db.get_client().await?;
+
+        client
+            .execute(
+                r#"
+            INSERT INTO realm_completions (realm_id, account_id)
+            VALUES ($1, $2)
+            ON CONFLICT (realm_id, account_id) DO NOTHING
+        "#,
+                &[&realm_id, &account_id],
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    async fn complete(&self, realm_id: RealmId, account_id: AccountId) -> DbResult<bool> {
+        let client = self.db.get_client().await?;
+
+        let updated = client
+            .execute(
+                r#"
+            UPDATE realm_completions
+            SET completed_at = NOW()
+            WHERE realm_id = $1 AND account_id = $2 AND completed_at IS NULL
+        "#,
+                &[&realm_id, &account_id],
+            )
+            .await?;
+
+        Ok(updated > 0)
+    }
+
     async fn room_kv(&self, realm_id: RealmId, room_id: RoomId) -> DbResult<Kv> {
         let client = self.db.get_client().await?;
 
@@ -124,7 +247,7 @@ impl RealmRepo for RealmRepository {
         let rows = client
             .query(
                 r#"
-                SELECT o.name AS object_key, key, value FROM realm_object_kv
+                SELECT o.name AS object_key, key AS kv_key, value FROM realm_object_kv
                 JOIN bp_objects o ON o.id = realm_object_kv.object_id
                 WHERE realm_id = $1 AND room_id = $2
                 "#,
@@ -154,7 +277,7 @@ impl RealmRepo for RealmRepository {
                 INSERT INTO realm_room_kv (realm_id, room_id, key, value)
                 VALUES ($1, $2, $3, $4)
                 ON CONFLICT (realm_id, room_id, key)
-                DO UPDATE SET value = EXCLUDED.value
+                DO UPDATE SET value = EXCLUDED.value, version = realm_room_kv.version + 1
                 "#,
                 &[&realm_id, &room_id, &key, &value],
             )
@@ -163,6 +286,44 @@ impl RealmRepo for RealmRepository {
         Ok(())
     }
 
+    async fn delete_room_kv_if(
+        &self,
+        realm_id: RealmId,
+        room_id: RoomId,
+        key: &str,
+        expected: &Value,
+    ) -> DbResult<bool> {
+        let client = self.db.get_client().await?;
+
+        let affected = client
+            .execute(
+                r#"
+                DELETE FROM realm_room_kv
+                WHERE realm_id = $1 AND room_id = $2 AND key = $3 AND value = $4
+                "#,
+                &[&realm_id, &room_id, &key, &expected],
+            )
+            .await?;
+
+        Ok(affected > 0)
+    }
+
+    async fn room_kv_by_key(&self, key: &str) -> DbResult<Vec<(RealmId, RoomId, Value)>> {
+        let client = self.db.get_client().await?;
+
+        let rows = client
+            .query(
+                "SELECT realm_id, room_id, value FROM realm_room_kv WHERE key = $1",
+                &[&key],
+            )
+            .await?;
+
+        Ok(rows
+            .iter()
+            .map(|row| (row.get("realm_id"), row.get("room_id"), row.get("value")))
+            .collect())
+    }
+
     async fn set_object_kv(&self, realm_id: RealmId, object_id: ObjectId, key: &str, value: &Value) -> DbResult<()> {
         let client = self.db.get_client().await?;
 
@@ -171,8 +332,8 @@ impl RealmRepo for RealmRepository {
                 r#"
                 INSERT INTO realm_object_kv (realm_id, object_id, key, value)
                 VALUES ($1, $2, $3, $4)
-                ON CONFLICT (realm_id, account_id, object_id, key)
-                DO UPDATE SET value = EXCLUDED.value
+                ON CONFLICT (realm_id, object_id, key)
+                DO UPDATE SET value = EXCLUDED.value, version = realm_object_kv.version + 1
                 "#,
                 &[&realm_id, &object_id, &key, &value],
             )
@@ -181,6 +342,136 @@ impl RealmRepo for RealmRepository {
         Ok(())
     }
 
+    async fn room_kv_versioned(
+        &self,
+        realm_id: RealmId,
+        room_id: RoomId,
+        key: &str,
+    ) -> DbResult<Option<VersionedValue>> {
+        let client = self.db.get_client().await?;
+
+        let row = client
+            .query_opt(
+                r#"
+                SELECT value, version FROM realm_room_kv
+                WHERE realm_id = $1 AND room_id = $2 AND key = $3
+                "#,
+                &[&realm_id, &room_id, &key],
+            )
+            .await?;
+
+        map_row_opt(
+            row,
+            VersionedValue::try_from_row,
+            &format!("RealmRepo::room_kv_versioned room_id={} key={}", room_id, key),
+        )
+    }
+
+    async fn cas_room_kv(
+        &self,
+        realm_id: RealmId,
+        room_id: RoomId,
+        key: &str,
+        value: &Value,
+        expected_version: Option<i64>,
+    ) -> DbResult<bool> {
+        let client = self.db.get_client().await?;
+
+        let affected = match expected_version {
+            None => {
+                client
+                    .execute(
+                        r#"
+                        INSERT INTO realm_room_kv (realm_id, room_id, key, value, version)
+                        VALUES ($1, $2, $3, $4, 0)
+                        ON CONFLICT (realm_id, room_id, key) DO NOTHING
+                        "#,
+                        &[&realm_id, &room_id, &key, &value],
+                    )
+                    .await?
+            }
+            Some(version) => {
+                client
+                    .execute(
+                        r#"
+                        UPDATE realm_room_kv
+                        SET value = $4, version = version + 1
+                        WHERE realm_id = $1 AND room_id = $2 AND key = $3 AND version = $5
+                        "#,
+                        &[&realm_id, &room_id, &key, &value, &version],
+                    )
+                    .await?
+            }
+        };
+
+        Ok(affected == 1)
+    }
+
+    async fn object_kv_versioned(
+        &self,
+        realm_id: RealmId,
+        object_id: ObjectId,
+        key: &str,
+    ) -> DbResult<Option<VersionedValue>> {
+        let client = self.db.get_client().await?;
+
+        let row = client
+            .query_opt(
+                r#"
+                SELECT value, version FROM realm_object_kv
+                WHERE realm_id = $1 AND object_id = $2 AND key = $3
+                "#,
+                &[&realm_id, &object_id, &key],
+            )
+            .await?;
+
+        map_row_opt(
+            row,
+            VersionedValue::try_from_row,
+            &format!("RealmRepo::object_kv_versioned object_id={} key={}", object_id, key),
+        )
+    }
+
+    async fn cas_object_kv(
+        &self,
+        realm_id: RealmId,
+        object_id: ObjectId,
+        key: &str,
+        value: &Value,
+        expected_version: Option<i64>,
+    ) -> DbResult<bool> {
+        let client = self.db.get_client().await?;
+
+        let affected = match expected_version {
+            None => {
+                client
+                    .execute(
+                        r#"
+                        INSERT INTO realm_object_kv (realm_id, object_id, key, value, version)
+                        VALUES ($1, $2, $3, $4, 0)
+                        ON CONFLICT (realm_id, object_id, key) DO NOTHING
+                        "#,
+                        &[&realm_id, &object_id, &key, &value],
+                    )
+                    .await?
+            }
+            Some(version) => {
+                client
+                    .execute(
+                        r#"
+                        UPDATE realm_object_kv
+                        SET value = $4, version = version + 1
+                        WHERE realm_id = $1 AND object_id = $2 AND key = $3 AND version = $5
+                        "#,
+                        &[&realm_id, &object_id, &key, &value, &version],
+                    )
+                    .await?
+            }
+        };
+
+        Ok(affected == 1)
+    }
+
     async fn set_exit_locked(&self, realm_id: RealmId, room_id: RoomId, exit_id: ExitId, locked: bool) -> DbResult<()> {
         let client = self.db.get_client().await?;
 
@@ -190,7 +481,7 @@ impl RealmRepo for RealmRepository {
                 INSERT INTO realm_exits (realm_id, room_id, exit_id, locked)
                 VALUES ($1, $2, $3, $4)
                 ON CONFLICT (realm_id, room_id, exit_id)
-                DO UPDATE SET value = EXCLUDED.value
+                DO UPDATE SET locked = EXCLUDED.locked
                 "#,
                 &[&realm_id, &room_id, &exit_id, &locked],
             )
@@ -198,4 +489,142 @@ impl RealmRepo for RealmRepository {
 
         Ok(())
     }
+
+    async fn incr_counter(
+        &self,
+        realm_id: RealmId,
+        account_id: Option<AccountId>,
+        key: &str,
+        by: i64,
+    ) -> DbResult<i64> {
+        let client = self.db.get_client().await?;
+
+        let row = match account_id {
+            Some(account_id) => {
+                client
+                    .query_one(
+                        r#"
+                        INSERT INTO user_counters (realm_id, account_id, key, value)
+                        VALUES ($1, $2, $3, $4)
+                        ON CONFLICT (realm_id, account_id, key)
+                        DO UPDATE SET value = user_counters.value + EXCLUDED.value, updated_at = NOW()
+                        RETURNING value
+                        "#,
+                        &[&realm_id, &account_id, &key, &by],
+                    )
+                    .await?
+            }
+            None => {
+                client
+                    .query_one(
+                        r#"
+                        INSERT INTO realm_counters (realm_id, key, value)
+                        VALUES ($1, $2, $3)
+                        ON CONFLICT (realm_id, key)
+                        DO UPDATE SET value = realm_counters.value + EXCLUDED.value, updated_at = NOW()
+                        RETURNING value
+                        "#,
+                        &[&realm_id, &key, &by],
+                    )
+                    .await?
+            }
+        };
+
+        Ok(row.try_get("value")?)
+    }
+
+    async fn counter(&self, realm_id: RealmId, account_id: Option<AccountId>, key: &str) -> DbResult<i64> {
+        let client = self.db.get_client().await?;
+
+        let row = match account_id {
+            Some(account_id) => {
+                client
+                    .query_opt(
+                        "SELECT value FROM user_counters WHERE realm_id = $1 AND account_id = $2 AND key = $3",
+                        &[&realm_id, &account_id, &key],
+                    )
+                    .await?
+            }
+            None => {
+                client
+                    .query_opt(
+                        "SELECT value FROM realm_counters WHERE realm_id = $1 AND key = $2",
+                        &[&realm_id, &key],
+                    )
+                    .await?
+            }
+        };
+
+        match row {
+            Some(row) => Ok(row.try_get("value")?),
+            None => Ok(0),
+        }
+    }
+
+    async fn spawned_objects(&self, realm_id: RealmId, room_id: RoomId) -> DbResult<Vec<SpawnedObject>> {
+        let client = self.db.get_client().await?;
+
+        let rows = client
+            .query(
+                r#"
+                SELECT id, key, short, description, nouns
+                FROM realm_spawned_objects
+                WHERE realm_id = $1 AND room_id = $2
+                ORDER BY created_at, key
+                "#,
+                &[&realm_id, &room_id],
+            )
+            .await?;
+
+        rows.iter()
+            .map(|row| {
+                Ok(SpawnedObject {
+                    id: row.try_get("id")?,
+                    key: row.try_get("key")?,
+                    short: row.try_get("short")?,
+                    description: row.try_get("description")?,
+                    nouns: row.try_get("nouns")?,
+                })
+            })
+            .collect()
+    }
+
+    async fn upsert_spawned_object(&self, realm_id: RealmId, room_id: RoomId, obj: &SpawnedObject) -> DbResult<()> {
+        let client = self.db.get_client().await?;
+
+        client
+            .execute(
+                r#"
+                INSERT INTO realm_spawned_objects (id, realm_id, room_id, key, short, description, nouns)
+                VALUES ($1, $2, $3, $4, $5, $6, $7)
+                ON CONFLICT (realm_id, room_id, key)
+                DO UPDATE SET short = EXCLUDED.short, description = EXCLUDED.description, nouns = EXCLUDED.nouns
+                "#,
+                &[
+                    &obj.id,
+                    &realm_id,
+                    &room_id,
+                    &obj.key,
+                    &obj.short,
+                    &obj.description,
+                    &obj.nouns,
+                ],
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    async fn delete_spawned_object(&self, realm_id: RealmId, room_id: RoomId, key: &str) -> DbResult<bool> {
+        let client = self.db.get_client().await?;
+
+        let n = client
+            .execute(
+                "DELETE FROM realm_spawned_objects WHERE realm_id = $1 AND room_id = $2 AND key = $3",
+                &[&realm_id, &room_id, &key],
+            )
+            .await?;
+
+        Ok(n > 0)
+    }
 }
@@ -0,0 +1,12 @@
+use crate::db::DbResult;
+use crate::models::recording::{NewRecording, Recording, RecordingSummary};
+use crate::models::types::AccountId;
+
+#[async_trait::async_trait]
+pub trait RecordingRepo: Send + Sync {
+    /// Stores a finished recording, returns its id
+    async fn save(&self, recording: &NewRecording) -> DbResult<i64>;
+    /// Most recent recordings first, optionally only those of one account
+    async fn list(&self, account_id: Option<AccountId>, limit: i64) -> DbResult<Vec<RecordingSummary>>;
+    async fn get(&self, recording_id: i64) -> DbResult<Option<Recording>>;
+}
@@ -0,0 +1,81 @@
+use crate::db::repo::recording::RecordingRepo;
+use crate::db::{Db, DbResult, map_row, map_row_opt};
+use crate::models::recording::{NewRecording, Recording, RecordingSummary};
+use crate::models::types::AccountId;
+use std::sync::Arc;
+
+pub struct RecordingRepository {
+    db: Arc<Db>,
+}
+
+impl RecordingRepository {
+    pub fn new(db: Arc<Db>) -> Self {
+        Self { db: db.clone() }
+    }
+}
+
+#[async_trait::async_trait]
+impl RecordingRepo for RecordingRepository {
+    async fn save(&self, recording: &NewRecording) -> DbResult<i64> {
+        let client = self.db.get_client().await?;
+        let events = serde_json::to_value(&recording.events)?;
+
+        let row = client
+            .query_one(
+                r#"
+                INSERT INTO session_recordings (account_id, recorded_by, started_at, events)
+                VALUES ($1, $2, $3, $4)
+                RETURNING id
+                "#,
+                &[
+                    &recording.account_id,
+                    &recording.recorded_by,
+                    &recording.started_at,
+                    &events,
+                ],
+            )
+            .await?;
+
+        Ok(row.get("id"))
+    }
+
+    async fn list(&self, account_id: Option<AccountId>, limit: i64) -> DbResult<Vec<RecordingSummary>> {
+        let client = self.db.get_client().await?;
+
+        let rows = client
+            .query(
+                r#"
+                SELECT r.id, a.username, r.started_at, r.ended_at, jsonb_array_length(r.events) AS events
+                FROM session_recordings r
+                JOIN accounts a ON a.id = r.account_id
+                WHERE $1::uuid IS NULL OR r.account_id = $1
+                ORDER BY r.started_at DESC, r.id DESC
+                LIMIT $2
+                "#,
+                &[&account_id, &limit],
+            )
+            .await?;
+
+        rows.iter()
+            .map(|row| map_row(row, RecordingSummary::try_from_row, "RecordingRepo::list"))
+            .collect()
+    }
+
+    async fn get(&self, recording_id: i64) -> DbResult<Option<Recording>> {
+        let client = self.db.get_client().await?;
+
+        let row = client
+            .query_opt(
+                r#"
+                SELECT r.id, r.account_id, a.username, r.recorded_by, r.started_at, r.ended_at, r.events
+                FROM session_recordings r
+                JOIN accounts a ON a.id = r.account_id
+                WHERE r.id = $1
+                "#,
+                &[&recording_id],
+            )
+            .await?;
+
+        map_row_opt(row, Recording::try_from_row, "RecordingRepo::get")
+    }
+}
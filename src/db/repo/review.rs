@@ -0,0 +1,29 @@
+use crate::db::DbResult;
+use crate::models::review::{BlueprintFeedback, Rating};
+use crate::models::types::{AccountId, BlueprintId, RealmId};
+
+#[async_trait::async_trait]
+pub trait ReviewRepo: Send + Sync {
+    /// Whether the account has completed the realm
+    async fn has_completed(&self, realm_id: RealmId, account_id: AccountId) -> DbResult<bool>;
+
+    async fn rating(&self, realm_id: RealmId, account_id: AccountId) -> DbResult<Option<Rating>>;
+    /// Stores the rating, replacing an earlier one of the same player
+    async fn set_rating(
+        &self,
+        realm_id: RealmId,
+        account_id: AccountId,
+        stars: u8,
+        review: Option<&str>,
+    ) -> DbResult<()>;
+
+    /// Tags the account put on the realm
+    async fn tags(&self, realm_id: RealmId, account_id: AccountId) -> DbResult<Vec<String>>;
+    /// Returns false when the account already used the tag
+    async fn add_tag(&self, realm_id: RealmId, account_id: AccountId, tag: &str) -> DbResult<bool>;
+    /// Returns false when the account did not use the tag
+    async fn remove_tag(&self, realm_id: RealmId, account_id: AccountId, tag: &str) -> DbResult<bool>;
+
+    /// Ratings, tags and up to `limit` reviews over all realms of the blueprint
+    async fn feedback(&self, bp_id: BlueprintId, limit: i64) -> DbResult<BlueprintFeedback>;
+}
@@ -0,0 +1,178 @@
+use crate::db::repo::review::ReviewRepo;
+use crate::db::{Db, DbResult};
+use crate::models::review::{BlueprintFeedback, Rating, ReviewEntry};
+use crate::models::types::{AccountId, BlueprintId, RealmId};
+use std::sync::Arc;
+
+pub struct ReviewRepository {
+    db: Arc<Db>,
+}
+
+impl ReviewRepository {
+    pub fn new(db: Arc<Db>) -> Self {
+        Self { db }
+    }
+}
+
+#[async_trait::async_trait]
+impl ReviewRepo for ReviewRepository {
+    async fn has_completed(&self, realm_id: RealmId, account_id: AccountId) -> DbResult<bool> {
+        let client = self.db.get_client().await?;
+
+        let row = client
+            .query_opt(
+                r#"
+            SELECT 1 FROM realm_completions
+            WHERE realm_id = $1 AND account_id = $2 AND completed_at IS NOT NULL
+        "#,
+                &[&realm_id, &account_id],
+            )
+            .await?;
+
+        Ok(row.is_some())
+    }
+
+    async fn rating(&self, realm_id: RealmId, account_id: AccountId) -> DbResult<Option<Rating>> {
+        let client = self.db.get_client().await?;
+
+        let row = client
+            .query_opt(
+                "SELECT stars, review, updated_at FROM realm_reviews WHERE realm_id = $1 AND account_id = $2",
+                &[&realm_id, &account_id],
+            )
+            .await?;
+
+        row.as_ref().map(Rating::try_from_row).transpose()
+    }
+
+    async fn set_rating(
+        &self,
+        realm_id: RealmId,
+        account_id: AccountId,
+        stars: u8,
+        review: Option<&str>,
+    ) -> DbResult<()> {
+        let client = self.db.get_client().await?;
+
+        client
+            .execute(
+                r#"
+            INSERT INTO realm_reviews (realm_id, account_id, stars, review)
+            VALUES ($1, $2, $3, $4)
+            ON CONFLICT (realm_id, account_id)
+            DO UPDATE SET stars = EXCLUDED.stars, review = EXCLUDED.review, updated_at = NOW()
+        "#,
+                &[&realm_id, &account_id, &i16::from(stars), &review],
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    async fn tags(&self, realm_id: RealmId, account_id: AccountId) -> DbResult<Vec<String>> {
+        let client = self.db.get_client().await?;
+
+        let rows = client
+            .query(
+                "SELECT tag FROM realm_player_tags WHERE realm_id = $1 AND account_id = $2 ORDER BY tag",
+                &[&realm_id, &account_id],
+            )
+            .await?;
+
+        Ok(rows.iter().map(|r| r.try_get("tag")).collect::<Result<_, _>>()?)
+    }
+
+    async fn add_tag(&self, realm_id: RealmId, account_id: AccountId, tag: &str) -> DbResult<bool> {
+        let client = self.db.get_client().await?;
+
+        let inserted = client
+            .execute(
+                r#"
+            INSERT INTO realm_player_tags (realm_id, account_id, tag)
+            VALUES ($1, $2, $3)
+            ON CONFLICT DO NOTHING
+        "#,
+                &[&realm_id, &account_id, &tag],
+            )
+            .await?;
+
+        Ok(inserted > 0)
+    }
+
+    async fn remove_tag(&self, realm_id: RealmId, account_id: AccountId, tag: &str) -> DbResult<bool> {
+        let client = self.db.get_client().await?;
+
+        let deleted = client
+            .execute(
+                "DELETE FROM realm_player_tags WHERE realm_id = $1 AND account_id = $2 AND tag = $3",
+                &[&realm_id, &account_id, &tag],
+            )
+            .await?;
+
+        Ok(deleted > 0)
+    }
+
+    async fn feedback(&self, bp_id: BlueprintId, limit: i64) -> DbResult<BlueprintFeedback> {
+        let client = self.db.get_client().await?;
+        let mut feedback = BlueprintFeedback::default();
+
+        let rows = client
+            .query(
+                r#"
+            SELECT v.stars, COUNT(*) AS n
+            FROM realm_reviews v
+            JOIN realms r ON r.id = v.realm_id
+            WHERE r.bp_id = $1
+            GROUP BY v.stars
+        "#,
+                &[&bp_id],
+            )
+            .await?;
+        for row in &rows {
+            let stars: i16 = row.try_get("stars")?;
+            let n: i64 = row.try_get("n")?;
+            if let Some(slot) = feedback.histogram.get_mut((stars as usize).wrapping_sub(1)) {
+                *slot = n as u32;
+            }
+        }
+
+        let rows = client
+            .query(
+                r#"
+            SELECT t.tag, COUNT(DISTINCT t.account_id) AS n
+            FROM realm_player_tags t
+            JOIN realms r ON r.id = t.realm_id
+            WHERE r.bp_id = $1
+            GROUP BY t.tag
+            ORDER BY n DESC, t.tag
+        "#,
+                &[&bp_id],
+            )
+            .await?;
+        for row in &rows {
+            let n: i64 = row.try_get("n")?;
+            feedback.tags.push((row.try_get("tag")?, n as u32));
+        }
+
+        let rows = client
+            .query(
+                r#"
+            SELECT a.username, r.key AS realm_key, v.stars, v.review, v.updated_at
+            FROM realm_reviews v
+            JOIN realms r ON r.id = v.realm_id
+            LEFT JOIN accounts a ON a.id = v.account_id
+            WHERE r.bp_id = $1
+            ORDER BY (v.review IS NULL), v.updated_at DESC
+            LIMIT $2
+        "#,
+                &[&bp_id, &limit],
+            )
+            .await?;
+        feedback.reviews = rows
+            .iter()
+            .map(ReviewEntry::try_from_row)
+            .collect::<DbResult<Vec<_>>>()?;
+
+        Ok(feedback)
+    }
+}
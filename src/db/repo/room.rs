@@ -1,14 +1,17 @@
 use crate::db::DbResult;
 use crate::db::repo::BlueprintAndRoomKey;
+use crate::lua::lint::ChunkKind;
 use crate::models::blueprint::Blueprint;
-use crate::models::room::{BlueprintExit, BlueprintObject, BlueprintRoom, Kv, RoomScripts};
-use crate::models::types::{AccountId, BlueprintId, RoomId};
+use crate::models::room::{BlueprintExit, BlueprintObject, BlueprintRoom, BuilderNote, Kv, RoomScripts};
+use crate::models::room_graph::RoomGraph;
+use crate::models::types::{AccountId, BlueprintId, ObjectId, RoomId};
 
 // Since room_id's are globally unique, we don't really need the bp_key here, but we do it
 // anyway to ensure that the room belongs to the given blueprint.
 #[async_trait::async_trait]
 pub trait RoomRepo: Send + Sync {
     async fn blueprint_by_key(&self, bp_key: &str) -> DbResult<Blueprint>;
+    async fn blueprint_by_id(&self, bp_id: BlueprintId) -> DbResult<Blueprint>;
 
     async fn room_by_id(&self, bp_id: BlueprintId, room_id: RoomId) -> DbResult<BlueprintRoom>;
     async fn get_room_id_by_key(&self, bp_id: BlueprintId, room_key: &str) -> DbResult<Option<RoomId>>;
@@ -17,12 +20,44 @@ pub trait RoomRepo: Send + Sync {
     async fn room_objects(&self, room_id: RoomId) -> DbResult<Vec<BlueprintObject>>;
     async fn room_scripts(&self, room_id: RoomId) -> DbResult<RoomScripts>;
     async fn room_kv(&self, room_id: RoomId) -> DbResult<Kv>;
+    /// Builder notes on the rooms and objects of the blueprint, or of one room only
+    async fn builder_notes(&self, bp_id: BlueprintId, room_id: Option<RoomId>) -> DbResult<Vec<BuilderNote>>;
+    /// All rooms of the blueprint and the exits between them
+    async fn room_graph(&self, bp_id: BlueprintId) -> DbResult<RoomGraph>;
+    /// Source of the Lua module `name` from the blueprint's `lib/`
+    async fn lua_module(&self, bp_id: BlueprintId, name: &str) -> DbResult<Option<String>>;
 
     async fn set_entry(&self, key: &BlueprintAndRoomKey) -> DbResult<bool>;
     async fn add_exit(&self, from_key: &BlueprintAndRoomKey, dir: &str, to_key: &BlueprintAndRoomKey)
     -> DbResult<bool>;
     async fn set_locked(&self, key: &BlueprintAndRoomKey, locked: bool) -> DbResult<bool>;
     async fn insert_blueprint(&self, bp_key: &str, title: &str, account_id: AccountId) -> DbResult<bool>;
+    /// Deep-copies the blueprint into a new draft `new_key` owned by `owner_id`. Returns None when
+    /// the key is taken.
+    async fn fork_blueprint(
+        &self,
+        source: BlueprintId,
+        new_key: &str,
+        owner_id: AccountId,
+    ) -> DbResult<Option<BlueprintId>>;
     async fn insert_room(&self, key: &BlueprintAndRoomKey, title: &str, body: &str) -> DbResult<bool>;
-    async fn submit(&self, bp_key: &str) -> DbResult<bool>;
+    /// Replaces the title and description of a room, as builders edit it in-game
+    async fn update_room_text(&self, room_id: RoomId, title: &str, body: &str) -> DbResult<bool>;
+
+    /// Adds an object known by its key, which is also its noun. Returns false when the room
+    /// already has an object with that key.
+    async fn insert_object(&self, room_id: RoomId, name: &str, short: &str) -> DbResult<bool>;
+    async fn update_object_text(
+        &self,
+        obj_id: ObjectId,
+        short: &str,
+        description: &str,
+        examine: Option<&str>,
+    ) -> DbResult<bool>;
+    async fn set_object_flag(&self, obj_id: ObjectId, flag: &str, on: bool) -> DbResult<bool>;
+    /// Replaces the nouns of an object
+    async fn set_object_nouns(&self, room_id: RoomId, obj_id: ObjectId, nouns: &[String]) -> DbResult<()>;
+    /// Sets (or with None, removes) one of the Lua scripts of an object
+    async fn set_object_script(&self, obj_id: ObjectId, kind: &ChunkKind, code: Option<&str>) -> DbResult<bool>;
+    async fn delete_object(&self, obj_id: ObjectId) -> DbResult<bool>;
 }
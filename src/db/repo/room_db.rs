@@ -2,9 +2,11 @@ use crate::db::error::DbError;
 use crate::db::repo::{BlueprintAndRoomKey, RoomRepo};
 use crate::db::{Db, DbResult, map_row};
 use crate::lua::ScriptHook;
+use crate::lua::lint::ChunkKind;
 use crate::models::blueprint::Blueprint;
-use crate::models::room::{BlueprintExit, BlueprintObject, BlueprintRoom, Kv, RoomScripts};
-use crate::models::types::{AccountId, BlueprintId, RoomId};
+use crate::models::room::{BlueprintExit, BlueprintObject, BlueprintRoom, BuilderNote, Kv, ObjectFlags, RoomScripts};
+use crate::models::room_graph::{GraphExit, RoomGraph};
+use crate::models::types::{AccountId, BlueprintId, ObjectId, RoomId};
 use std::sync::Arc;
 
 pub struct RoomRepository {
@@ -25,7 +27,7 @@ impl RoomRepo for RoomRepository {
         let row = client
             .query_one(
                 r#"
-            SELECT id, key, title, owner_id, entry_room_id, status, created_at
+            SELECT id, key, title, owner_id, entry_room_id, status, version, forked_from, forked_version, prompt, created_at
             FROM blueprints
             WHERE key = $1
             "#,
@@ -40,13 +42,34 @@ impl RoomRepo for RoomRepository {
         )
     }
 
+    async fn blueprint_by_id(&self, bp_id: BlueprintId) -> DbResult<Blueprint> {
+        let client = self.db.get_client().await?;
+
+        let row = client
+            .query_one(
+                r#"
+            SELECT id, key, title, owner_id, entry_room_id, status, version, forked_from, forked_version, prompt, created_at
+            FROM blueprints
+            WHERE id = $1
+            "#,
+                &[&bp_id],
+            )
+            .await?;
+
+        map_row(
+            &row,
+            Blueprint::try_from_row,
+            &format!("RoomRepo::blueprint_by_id bp_id={}", bp_id),
+        )
+    }
+
     async fn room_by_id(&self, bp_id: BlueprintId, room_id: RoomId) -> DbResult<BlueprintRoom> {
         let client = self.db.get_client().await?;
 
         let row = client
             .query_one(
                 r#"
-            SELECT r.id, r.bp_id, r.key, r.title, r.body, r.lockdown, r.short, r.hints
+            SELECT r.id, r.bp_id, r.key, r.title, r.body, r.lockdown, r.short, r.hints, r.hazards, r.sounds, r.vehicle, r.script_first, r.api_version
             FROM bp_rooms r
             WHERE r.id = $1 AND r.bp_id = $2
             "#,
@@ -97,7 +120,8 @@ impl RoomRepo for RoomRepository {
                     tr.key AS to_room_key,
                     e.locked,
                     e.description,
-                    e.visible_when_locked
+                    e.visible_when_locked,
+                    e.lock_info
                 FROM bp_exits e
                 JOIN bp_rooms fr ON e.from_room_id = fr.id
                 JOIN bp_rooms tr ON e.to_room_id = tr.id
@@ -127,7 +151,8 @@ impl RoomRepo for RoomRepository {
         let rows = client
             .query(
                 r#"
-        SELECT o.id, o.room_id, o.name, o.short, o.description, o.examine, o.flags, o.state, o.use_lua, o.position, o.loot,
+        SELECT o.id, o.room_id, o.name, o.short, o.description, o.examine, o.flags, o.state, o.use_lua, o.position, o.loot, o.discovery, o.use_limits,
+            o.lock_info, o.pages, o.read_lua, o.terminal_lua, o.receive_lua, o.board, o.widget, o.dialogue, o.storage,
             COALESCE(n.nouns, ARRAY[]::text[]) AS nouns,
             COALESCE(k.kv, '{}'::jsonb) AS kv
         FROM bp_objects AS o
@@ -183,6 +208,18 @@ impl RoomRepo for RoomRepository {
         Ok(scripts)
     }
 
+    async fn lua_module(&self, bp_id: BlueprintId, name: &str) -> DbResult<Option<String>> {
+        let client = self.db.get_client().await?;
+
+        let row = client
+            .query_opt(
+                "SELECT source FROM bp_lua_modules WHERE bp_id = $1 AND name = $2",
+                &[&bp_id, &name],
+            )
+            .await?;
+        Ok(row.map(|r| r.get("source")))
+    }
+
     async fn room_kv(&self, room_id: RoomId) -> DbResult<Kv> {
         let client = self.db.get_client().await?;
 
@@ -200,6 +237,84 @@ impl RoomRepo for RoomRepository {
         Ok(Kv::try_from_rows(&rows).map_err(|_| DbError::Decode("Cannot decode row to kv".into()))?)
     }
 
+    async fn builder_notes(&self, bp_id: BlueprintId, room_id: Option<RoomId>) -> DbResult<Vec<BuilderNote>> {
+        let client = self.db.get_client().await?;
+
+        let rows = client
+            .query(
+                r#"
+            SELECT r.key AS room_key, NULL::text AS object_key, n.note, 0 AS position
+            FROM bp_rooms r, unnest(r.notes) AS n(note)
+            WHERE r.bp_id = $1 AND ($2::uuid IS NULL OR r.id = $2)
+            UNION ALL
+            SELECT r.key, o.name, n.note, COALESCE(o.position, 0) + 1
+            FROM bp_objects o
+            JOIN bp_rooms r ON r.id = o.room_id, unnest(o.notes) AS n(note)
+            WHERE r.bp_id = $1 AND ($2::uuid IS NULL OR r.id = $2)
+            ORDER BY room_key, position, object_key
+            "#,
+                &[&bp_id, &room_id],
+            )
+            .await?;
+
+        rows.iter()
+            .map(|row| {
+                map_row(
+                    row,
+                    BuilderNote::try_from_row,
+                    &format!("RoomRepo::builder_notes bp_id={}", bp_id),
+                )
+            })
+            .collect()
+    }
+
+    async fn room_graph(&self, bp_id: BlueprintId) -> DbResult<RoomGraph> {
+        let client = self.db.get_client().await?;
+
+        let entry = client
+            .query_opt(
+                r#"
+                SELECT r.key FROM blueprints b
+                JOIN bp_rooms r ON r.id = b.entry_room_id
+                WHERE b.id = $1
+                "#,
+                &[&bp_id],
+            )
+            .await?
+            .map(|row| row.get("key"));
+
+        let rooms = client
+            .query("SELECT key FROM bp_rooms WHERE bp_id = $1 ORDER BY key", &[&bp_id])
+            .await?
+            .iter()
+            .map(|row| row.get("key"))
+            .collect();
+
+        let exits = client
+            .query(
+                r#"
+                SELECT fr.key AS from_key, e.dir, tr.key AS to_key, e.locked
+                FROM bp_exits e
+                JOIN bp_rooms fr ON fr.id = e.from_room_id
+                JOIN bp_rooms tr ON tr.id = e.to_room_id
+                WHERE fr.bp_id = $1
+                ORDER BY fr.key, e.dir
+                "#,
+                &[&bp_id],
+            )
+            .await?
+            .iter()
+            .map(|row| GraphExit {
+                from: row.get("from_key"),
+                dir: row.get("dir"),
+                to: row.get("to_key"),
+                locked: row.get("locked"),
+            })
+            .collect();
+
+        Ok(RoomGraph { entry, rooms, exits })
+    }
+
     async fn set_entry(&self, key: &BlueprintAndRoomKey) -> DbResult<bool> {
         let c = self.db.get_client().await?;
 
@@ -257,7 +372,7 @@ impl RoomRepo for RoomRepository {
             .execute(
                 r#"
                 UPDATE bp_rooms AS r
-                    SET locked = $3
+                    SET lockdown = $3
                 FROM blueprints AS bp
                 WHERE bp.id = r.bp_id AND bp.key = $1 AND r.key = $2
             "#,
@@ -285,6 +400,130 @@ impl RoomRepo for RoomRepository {
         Ok(n == 1)
     }
 
+    async fn fork_blueprint(
+        &self,
+        source: BlueprintId,
+        new_key: &str,
+        owner_id: AccountId,
+    ) -> DbResult<Option<BlueprintId>> {
+        let mut c = self.db.get_client().await?;
+        let tx = c.transaction().await?;
+
+        let Some(row) = tx
+            .query_opt(
+                r#"
+            INSERT INTO blueprints (key, title, owner_id, status, difficulty, tags, prompt, forked_from, forked_version)
+            SELECT $2, title, $3, 'draft', difficulty, tags, prompt, id, version
+            FROM blueprints
+            WHERE id = $1
+            ON CONFLICT (key) DO NOTHING
+            RETURNING id
+            "#,
+                &[&source, &new_key, &owner_id],
+            )
+            .await?
+        else {
+            return Ok(None);
+        };
+        let bp_id: BlueprintId = row.try_get("id")?;
+
+        // Old id -> new id of everything that is referenced by id
+        tx.batch_execute("CREATE TEMP TABLE fork_ids (kind text, old_id uuid, new_id uuid) ON COMMIT DROP")
+            .await?;
+        tx.execute(
+            r#"
+            INSERT INTO fork_ids (kind, old_id, new_id)
+            SELECT 'room', r.id, gen_random_uuid() FROM bp_rooms r WHERE r.bp_id = $1
+            UNION ALL
+            SELECT 'object', o.id, gen_random_uuid() FROM bp_objects o JOIN bp_rooms r ON r.id = o.room_id WHERE r.bp_id = $1
+            UNION ALL
+            SELECT 'exit', e.id, gen_random_uuid() FROM bp_exits e JOIN bp_rooms r ON r.id = e.from_room_id WHERE r.bp_id = $1
+            UNION ALL
+            SELECT 'item', i.id, gen_random_uuid() FROM bp_items_catalog i WHERE i.bp_id = $1
+            "#,
+            &[&source],
+        )
+        .await?;
+
+        // Rows are copied whole through jsonb, overriding only the ids, so columns added to these
+        // tables later are copied as well.
+        let per_blueprint = [
+            r#"INSERT INTO bp_rooms
+               SELECT (jsonb_populate_record(NULL::bp_rooms, to_jsonb(r) || jsonb_build_object('id', m.new_id, 'bp_id', $2::uuid))).*
+               FROM bp_rooms r JOIN fork_ids m ON m.kind = 'room' AND m.old_id = r.id
+               WHERE r.bp_id = $1"#,
+            r#"INSERT INTO bp_items_catalog
+               SELECT (jsonb_populate_record(NULL::bp_items_catalog, to_jsonb(i) || jsonb_build_object('id', m.new_id, 'bp_id', $2::uuid))).*
+               FROM bp_items_catalog i JOIN fork_ids m ON m.kind = 'item' AND m.old_id = i.id
+               WHERE i.bp_id = $1"#,
+            r#"INSERT INTO bp_item_nouns
+               SELECT (jsonb_populate_record(NULL::bp_item_nouns, to_jsonb(n) || jsonb_build_object('id', gen_random_uuid(), 'bp_id', $2::uuid, 'item_id', m.new_id))).*
+               FROM bp_item_nouns n JOIN fork_ids m ON m.kind = 'item' AND m.old_id = n.item_id
+               WHERE n.bp_id = $1"#,
+            r#"INSERT INTO bp_recipes
+               SELECT (jsonb_populate_record(NULL::bp_recipes, to_jsonb(x) || jsonb_build_object('id', gen_random_uuid(), 'bp_id', $2::uuid))).*
+               FROM bp_recipes x WHERE x.bp_id = $1"#,
+            r#"INSERT INTO bp_ambience
+               SELECT (jsonb_populate_record(NULL::bp_ambience, to_jsonb(x) || jsonb_build_object('id', gen_random_uuid(), 'bp_id', $2::uuid))).*
+               FROM bp_ambience x WHERE x.bp_id = $1"#,
+            r#"INSERT INTO bp_lua_modules
+               SELECT (jsonb_populate_record(NULL::bp_lua_modules, to_jsonb(x) || jsonb_build_object('bp_id', $2::uuid))).*
+               FROM bp_lua_modules x WHERE x.bp_id = $1"#,
+            r#"INSERT INTO feature_flags
+               SELECT (jsonb_populate_record(NULL::feature_flags, to_jsonb(x) || jsonb_build_object('id', gen_random_uuid(), 'bp_id', $2::uuid))).*
+               FROM feature_flags x WHERE x.bp_id = $1"#,
+        ];
+        for sql in per_blueprint {
+            tx.execute(sql, &[&source, &bp_id]).await?;
+        }
+        // These only follow the copied rooms and objects
+        let per_row = [
+            r#"INSERT INTO bp_exits
+               SELECT (jsonb_populate_record(NULL::bp_exits, to_jsonb(e) || jsonb_build_object('id', m.new_id, 'from_room_id', f.new_id, 'to_room_id', t.new_id))).*
+               FROM bp_exits e
+               JOIN fork_ids m ON m.kind = 'exit' AND m.old_id = e.id
+               JOIN fork_ids f ON f.kind = 'room' AND f.old_id = e.from_room_id
+               JOIN fork_ids t ON t.kind = 'room' AND t.old_id = e.to_room_id"#,
+            r#"INSERT INTO bp_room_scripts
+               SELECT (jsonb_populate_record(NULL::bp_room_scripts, to_jsonb(s) || jsonb_build_object('room_id', m.new_id))).*
+               FROM bp_room_scripts s JOIN fork_ids m ON m.kind = 'room' AND m.old_id = s.room_id"#,
+            r#"INSERT INTO bp_room_kv
+               SELECT (jsonb_populate_record(NULL::bp_room_kv, to_jsonb(k) || jsonb_build_object('room_id', m.new_id))).*
+               FROM bp_room_kv k JOIN fork_ids m ON m.kind = 'room' AND m.old_id = k.room_id"#,
+            r#"INSERT INTO bp_objects
+               SELECT (jsonb_populate_record(NULL::bp_objects, to_jsonb(o) || jsonb_build_object('id', m.new_id, 'room_id', r.new_id))).*
+               FROM bp_objects o
+               JOIN fork_ids m ON m.kind = 'object' AND m.old_id = o.id
+               JOIN fork_ids r ON r.kind = 'room' AND r.old_id = o.room_id"#,
+            r#"INSERT INTO bp_object_nouns
+               SELECT (jsonb_populate_record(NULL::bp_object_nouns, to_jsonb(n) || jsonb_build_object('room_id', r.new_id, 'obj_id', o.new_id))).*
+               FROM bp_object_nouns n
+               JOIN fork_ids r ON r.kind = 'room' AND r.old_id = n.room_id
+               JOIN fork_ids o ON o.kind = 'object' AND o.old_id = n.obj_id"#,
+            r#"INSERT INTO bp_objects_kv
+               SELECT (jsonb_populate_record(NULL::bp_objects_kv, to_jsonb(k) || jsonb_build_object('object_id', m.new_id))).*
+               FROM bp_objects_kv k JOIN fork_ids m ON m.kind = 'object' AND m.old_id = k.object_id"#,
+        ];
+        for sql in per_row {
+            tx.execute(sql, &[]).await?;
+        }
+
+        tx.execute(
+            r#"
+            UPDATE blueprints b
+            SET entry_room_id = m.new_id
+            FROM blueprints src
+            JOIN fork_ids m ON m.kind = 'room' AND m.old_id = src.entry_room_id
+            WHERE src.id = $1 AND b.id = $2
+            "#,
+            &[&source, &bp_id],
+        )
+        .await?;
+
+        tx.commit().await?;
+        Ok(Some(bp_id))
+    }
+
     async fn insert_room(&self, key: &BlueprintAndRoomKey, title: &str, body: &str) -> DbResult<bool> {
         let c = self.db.get_client().await?;
 
@@ -292,12 +531,8 @@ impl RoomRepo for RoomRepository {
         let n = c
             .execute(
                 r#"
-            INSERT INTO bp_rooms (bp_id, key, title, body, locked, short, hints, scripts)
-            SELECT b.id, $2, $3, $4,
-                false,
-                ''::text,
-                ARRAY[]::text[],
-                '{}'::jsonb
+            INSERT INTO bp_rooms (bp_id, key, title, body)
+            SELECT b.id, $2, $3, $4
             FROM blueprints AS b
             WHERE b.key = $1
             ON CONFLICT (bp_id, key) DO NOTHING
@@ -309,20 +544,152 @@ impl RoomRepo for RoomRepository {
         Ok(n == 1)
     }
 
-    async fn submit(&self, bp_key: &str) -> DbResult<bool> {
+    async fn update_room_text(&self, room_id: RoomId, title: &str, body: &str) -> DbResult<bool> {
         let c = self.db.get_client().await?;
 
         let n = c
             .execute(
+                "UPDATE bp_rooms SET title = $2, body = $3 WHERE id = $1",
+                &[&room_id, &title, &body],
+            )
+            .await?;
+
+        Ok(n == 1)
+    }
+
+    async fn insert_object(&self, room_id: RoomId, name: &str, short: &str) -> DbResult<bool> {
+        let mut client = self.db.pool.get().await?;
+        let tx = client.transaction().await?;
+
+        let flags = serde_json::to_value(ObjectFlags {
+            revealed: true,
+            ..ObjectFlags::default()
+        })
+        .map_err(|e| DbError::Validation(e.to_string()))?;
+
+        // New objects go last in the room, described by their short text until edited
+        let Some(row) = tx
+            .query_opt(
                 r#"
-            UPDATE blueprints
-            SET status = 'pending'
-            WHERE key = $1 AND status = 'draft'
+                INSERT INTO bp_objects (room_id, name, short, description, position, flags)
+                SELECT $1, $2, $3, $3, COALESCE(MAX(position) + 1, 0), $4::jsonb
+                FROM bp_objects WHERE room_id = $1
+                ON CONFLICT (room_id, name) DO NOTHING
+                RETURNING id
+                "#,
+                &[&room_id, &name, &short, &flags],
+            )
+            .await?
+        else {
+            return Ok(false);
+        };
+        let obj_id: ObjectId = row.get("id");
+
+        tx.execute(
+            r#"
+            INSERT INTO bp_object_nouns (room_id, obj_id, noun)
+            VALUES ($1, $2, $3)
+            ON CONFLICT (room_id, noun) DO NOTHING
             "#,
-                &[&bp_key],
+            &[&room_id, &obj_id, &name],
+        )
+        .await?;
+
+        tx.commit().await?;
+        Ok(true)
+    }
+
+    async fn update_object_text(
+        &self,
+        obj_id: ObjectId,
+        short: &str,
+        description: &str,
+        examine: Option<&str>,
+    ) -> DbResult<bool> {
+        let c = self.db.get_client().await?;
+
+        let n = c
+            .execute(
+                r#"
+                UPDATE bp_objects SET short = $2, description = $3, examine = $4, updated_at = now()
+                WHERE id = $1
+                "#,
+                &[&obj_id, &short, &description, &examine],
             )
             .await?;
 
         Ok(n == 1)
     }
+
+    async fn set_object_flag(&self, obj_id: ObjectId, flag: &str, on: bool) -> DbResult<bool> {
+        let c = self.db.get_client().await?;
+
+        // Objects from before flags were an object start over from an empty one
+        let n = c
+            .execute(
+                r#"
+                UPDATE bp_objects
+                SET flags = jsonb_set(
+                        CASE WHEN jsonb_typeof(flags) = 'object' THEN flags ELSE '{}'::jsonb END,
+                        ARRAY[$2], to_jsonb($3::bool)),
+                    updated_at = now()
+                WHERE id = $1
+                "#,
+                &[&obj_id, &flag, &on],
+            )
+            .await?;
+
+        Ok(n == 1)
+    }
+
+    async fn set_object_nouns(&self, room_id: RoomId, obj_id: ObjectId, nouns: &[String]) -> DbResult<()> {
+        let mut client = self.db.pool.get().await?;
+        let tx = client.transaction().await?;
+
+        tx.execute("DELETE FROM bp_object_nouns WHERE obj_id = $1", &[&obj_id])
+            .await?;
+        for noun in nouns {
+            tx.execute(
+                "INSERT INTO bp_object_nouns (room_id, obj_id, noun) VALUES ($1, $2, $3)",
+                &[&room_id, &obj_id, noun],
+            )
+            .await?;
+        }
+
+        tx.commit().await?;
+        Ok(())
+    }
+
+    async fn set_object_script(&self, obj_id: ObjectId, kind: &ChunkKind, code: Option<&str>) -> DbResult<bool> {
+        let column = match kind {
+            ChunkKind::OnUse => "use_lua",
+            ChunkKind::OnRead => "read_lua",
+            ChunkKind::OnTerminal => "terminal_lua",
+            ChunkKind::OnReceive => "receive_lua",
+            ChunkKind::Room(_) => {
+                return Err(DbError::Validation("room scripts are not object scripts".into()));
+            }
+        };
+
+        let c = self.db.get_client().await?;
+        let n = c
+            .execute(
+                &format!(
+                    "UPDATE bp_objects SET {} = $2, updated_at = now() WHERE id = $1",
+                    column
+                ),
+                &[&obj_id, &code],
+            )
+            .await?;
+
+        Ok(n == 1)
+    }
+
+    async fn delete_object(&self, obj_id: ObjectId) -> DbResult<bool> {
+        let c = self.db.get_client().await?;
+
+        let n = c.execute("DELETE FROM bp_objects WHERE id = $1", &[&obj_id]).await?;
+
+        Ok(n == 1)
+    }
 }
@@ -0,0 +1,26 @@
+use crate::db::DbResult;
+use crate::models::schedule::{CronSchedule, EventAction, ScheduledEvent};
+use crate::models::types::{AccountId, RealmId};
+use chrono::{DateTime, Utc};
+
+#[async_trait::async_trait]
+pub trait ScheduleRepo: Send + Sync {
+    /// All scheduled events, oldest first
+    async fn list(&self) -> DbResult<Vec<ScheduledEvent>>;
+
+    /// Adds an event, returns its id
+    async fn insert(
+        &self,
+        schedule: &CronSchedule,
+        action: &EventAction,
+        realm_id: Option<RealmId>,
+        created_by: AccountId,
+    ) -> DbResult<i64>;
+
+    /// Returns false when there is no such event
+    async fn delete(&self, id: i64) -> DbResult<bool>;
+
+    /// Records that the event runs in `minute`. Returns false when it already ran then (or since),
+    /// so an event never runs twice for the same minute.
+    async fn mark_run(&self, id: i64, minute: DateTime<Utc>) -> DbResult<bool>;
+}
@@ -0,0 +1,93 @@
+use crate::db::repo::schedule::ScheduleRepo;
+use crate::db::{Db, DbResult, map_row};
+use crate::models::schedule::{CronSchedule, EventAction, ScheduledEvent};
+use crate::models::types::{AccountId, RealmId};
+use chrono::{DateTime, Utc};
+use std::sync::Arc;
+
+pub struct ScheduleRepository {
+    db: Arc<Db>,
+}
+
+impl ScheduleRepository {
+    pub fn new(db: Arc<Db>) -> Self {
+        Self { db }
+    }
+}
+
+#[async_trait::async_trait]
+impl ScheduleRepo for ScheduleRepository {
+    async fn list(&self) -> DbResult<Vec<ScheduledEvent>> {
+        let client = self.db.get_client().await?;
+
+        let rows = client
+            .query(
+                r#"
+                SELECT id, schedule, action, payload, realm_id, created_by, last_run_at
+                FROM scheduled_events
+                ORDER BY id
+                "#,
+                &[],
+            )
+            .await?;
+
+        rows.iter()
+            .map(|row| map_row(row, ScheduledEvent::try_from_row, "ScheduleRepo::list"))
+            .collect()
+    }
+
+    async fn insert(
+        &self,
+        schedule: &CronSchedule,
+        action: &EventAction,
+        realm_id: Option<RealmId>,
+        created_by: AccountId,
+    ) -> DbResult<i64> {
+        let client = self.db.get_client().await?;
+
+        let row = client
+            .query_one(
+                r#"
+                INSERT INTO scheduled_events (schedule, action, payload, realm_id, created_by)
+                VALUES ($1, $2, $3, $4, $5)
+                RETURNING id
+                "#,
+                &[
+                    &schedule.to_string(),
+                    &action.kind(),
+                    &action.payload(),
+                    &realm_id,
+                    &created_by,
+                ],
+            )
+            .await?;
+
+        Ok(row.get("id"))
+    }
+
+    async fn delete(&self, id: i64) -> DbResult<bool> {
+        let client = self.db.get_client().await?;
+
+        let deleted = client
+            .execute("DELETE FROM scheduled_events WHERE id = $1", &[&id])
+            .await?;
+
+        Ok(deleted > 0)
+    }
+
+    async fn mark_run(&self, id: i64, minute: DateTime<Utc>) -> DbResult<bool> {
+        let client = self.db.get_client().await?;
+
+        let updated = client
+            .execute(
+                r#"
+                UPDATE scheduled_events SET last_run_at = $2
+                WHERE id = $1 AND (last_run_at IS NULL OR last_run_at < $2)
+                "#,
+                &[&id, &minute],
+            )
+            .await?;
+
+        Ok(updated > 0)
+    }
+}
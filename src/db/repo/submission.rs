@@ -0,0 +1,21 @@
+use crate::db::DbResult;
+use crate::models::submission::Submission;
+use crate::models::types::{AccountId, BlueprintId};
+
+#[async_trait::async_trait]
+pub trait SubmissionRepo: Send + Sync {
+    /// Marks a draft or rejected blueprint pending and queues it, returns the submission id or None
+    /// when the blueprint cannot be submitted in its current status
+    async fn submit(&self, bp_id: BlueprintId, account_id: AccountId) -> DbResult<Option<i64>>;
+    /// Pending submissions, oldest first
+    async fn pending(&self) -> DbResult<Vec<Submission>>;
+    async fn get(&self, id: i64) -> DbResult<Option<Submission>>;
+    /// Most recent submission of a blueprint
+    async fn latest(&self, bp_id: BlueprintId) -> DbResult<Option<Submission>>;
+    /// Approves (publishes) or rejects a pending submission, returns false when there was no such
+    /// pending submission
+    async fn decide(&self, id: i64, moderator_id: AccountId, approve: bool, comment: Option<&str>) -> DbResult<bool>;
+    /// Decisions on blueprints of this owner that were not shown to them yet, oldest first
+    async fn undelivered(&self, owner_id: AccountId) -> DbResult<Vec<Submission>>;
+    async fn mark_delivered(&self, ids: &[i64]) -> DbResult<()>;
+}
@@ -0,0 +1,160 @@
+use crate::db::repo::submission::SubmissionRepo;
+use crate::db::{Db, DbResult, map_row, map_row_opt};
+use crate::models::submission::Submission;
+use crate::models::types::{AccountId, BlueprintId};
+use std::sync::Arc;
+
+const SELECT_SUBMISSION: &str = r#"
+    SELECT s.id, s.bp_id, b.key AS bp_key, b.title AS bp_title, b.owner_id, s.version,
+           sa.username AS submitted_by, s.submitted_at, s.status,
+           da.username AS decided_by, s.decided_at, s.comment
+    FROM blueprint_submissions s
+    JOIN blueprints b ON b.id = s.bp_id
+    LEFT JOIN accounts sa ON sa.id = s.submitted_by
+    LEFT JOIN accounts da ON da.id = s.decided_by
+"#;
+
+pub struct SubmissionRepository {
+    db: Arc<Db>,
+}
+
+impl SubmissionRepository {
+    pub fn new(db: Arc<Db>) -> Self {
+        Self { db }
+    }
+}
+
+#[async_trait::async_trait]
+impl SubmissionRepo for SubmissionRepository {
+    async fn submit(&self, bp_id: BlueprintId, account_id: AccountId) -> DbResult<Option<i64>> {
+        let mut client = self.db.get_client().await?;
+        let tx = client.transaction().await?;
+
+        let Some(row) = tx
+            .query_opt(
+                r#"
+                UPDATE blueprints SET status = 'pending'
+                WHERE id = $1 AND status IN ('draft', 'rejected')
+                RETURNING version
+                "#,
+                &[&bp_id],
+            )
+            .await?
+        else {
+            return Ok(None);
+        };
+        let version: i32 = row.try_get("version")?;
+
+        let row = tx
+            .query_one(
+                r#"
+                INSERT INTO blueprint_submissions (bp_id, version, submitted_by)
+                VALUES ($1, $2, $3)
+                RETURNING id
+                "#,
+                &[&bp_id, &version, &account_id],
+            )
+            .await?;
+
+        tx.commit().await?;
+        Ok(Some(row.try_get("id")?))
+    }
+
+    async fn pending(&self) -> DbResult<Vec<Submission>> {
+        let client = self.db.get_client().await?;
+
+        let sql = format!(
+            "{} WHERE s.status = 'pending' ORDER BY s.submitted_at, s.id",
+            SELECT_SUBMISSION
+        );
+        let rows = client.query(&sql, &[]).await?;
+
+        rows.iter()
+            .map(|row| map_row(row, Submission::try_from_row, "SubmissionRepo::pending"))
+            .collect()
+    }
+
+    async fn get(&self, id: i64) -> DbResult<Option<Submission>> {
+        let client = self.db.get_client().await?;
+
+        let sql = format!("{} WHERE s.id = $1", SELECT_SUBMISSION);
+        let row = client.query_opt(&sql, &[&id]).await?;
+
+        map_row_opt(row, Submission::try_from_row, "SubmissionRepo::get")
+    }
+
+    async fn latest(&self, bp_id: BlueprintId) -> DbResult<Option<Submission>> {
+        let client = self.db.get_client().await?;
+
+        let sql = format!(
+            "{} WHERE s.bp_id = $1 ORDER BY s.submitted_at DESC, s.id DESC LIMIT 1",
+            SELECT_SUBMISSION
+        );
+        let row = client.query_opt(&sql, &[&bp_id]).await?;
+
+        map_row_opt(row, Submission::try_from_row, "SubmissionRepo::latest")
+    }
+
+    async fn decide(&self, id: i64, moderator_id: AccountId, approve: bool, comment: Option<&str>) -> DbResult<bool> {
+        let mut client = self.db.get_client().await?;
+        let tx = client.transaction().await?;
+
+        let (status, bp_status) = if approve {
+            ("approved", "published")
+        } else {
+            ("rejected", "rejected")
+        };
+
+        let Some(row) = tx
+            .query_opt(
+                r#"
+                UPDATE blueprint_submissions
+                SET status = $2, decided_by = $3, decided_at = now(), comment = $4
+                WHERE id = $1 AND status = 'pending'
+                RETURNING bp_id
+                "#,
+                &[&id, &status, &moderator_id, &comment],
+            )
+            .await?
+        else {
+            return Ok(false);
+        };
+        let bp_id: BlueprintId = row.try_get("bp_id")?;
+
+        tx.execute(
+            "UPDATE blueprints SET status = $2 WHERE id = $1 AND status = 'pending'",
+            &[&bp_id, &bp_status],
+        )
+        .await?;
+
+        tx.commit().await?;
+        Ok(true)
+    }
+
+    async fn undelivered(&self, owner_id: AccountId) -> DbResult<Vec<Submission>> {
+        let client = self.db.get_client().await?;
+
+        let sql = format!(
+            "{} WHERE b.owner_id = $1 AND s.status <> 'pending' AND s.delivered_at IS NULL ORDER BY s.decided_at, s.id",
+            SELECT_SUBMISSION
+        );
+        let rows = client.query(&sql, &[&owner_id]).await?;
+
+        rows.iter()
+            .map(|row| map_row(row, Submission::try_from_row, "SubmissionRepo::undelivered"))
+            .collect()
+    }
+
+    async fn mark_delivered(&self, ids: &[i64]) -> DbResult<()> {
+        let client = self.db.get_client().await?;
+
+        client
+            .execute(
+                "UPDATE blueprint_submissions SET delivered_at = now() WHERE id = ANY($1) AND delivered_at IS NULL",
+                &[&ids],
+            )
+            .await?;
+
+        Ok(())
+    }
+}
@@ -0,0 +1,15 @@
+use crate::db::DbResult;
+use crate::models::realm::Realm;
+use crate::models::types::{AccountId, RealmId, RoomId};
+
+#[async_trait::async_trait]
+pub trait TutorialRepo: Send + Sync {
+    /// Stores a realm under `key`, owned by `owner`
+    async fn create_instance(&self, realm: Realm, key: &str, owner: AccountId) -> DbResult<Realm>;
+
+    /// Sets the realm and room the account logs in at
+    async fn set_position(&self, account_id: AccountId, realm_id: RealmId, room_id: RoomId) -> DbResult<()>;
+
+    /// Marks the tutorial as completed. Returns false when it already was.
+    async fn complete(&self, account_id: AccountId) -> DbResult<bool>;
+}
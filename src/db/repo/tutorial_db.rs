@@ -0,0 +1,68 @@
+use crate::db::repo::tutorial::TutorialRepo;
+use crate::db::{Db, DbResult};
+use crate::models::realm::Realm;
+use crate::models::types::{AccountId, RealmId, RoomId};
+use std::sync::Arc;
+
+pub struct TutorialRepository {
+    db: Arc<Db>,
+}
+
+impl TutorialRepository {
+    pub fn new(db: Arc<Db>) -> Self {
+        Self { db }
+    }
+}
+
+#[async_trait::async_trait]
+impl TutorialRepo for TutorialRepository {
+    async fn create_instance(&self, realm: Realm, key: &str, owner: AccountId) -> DbResult<Realm> {
+        let client = self.db.get_client().await?;
+
+        client
+            .execute(
+                r#"
+            INSERT INTO realms (id, bp_id, key, title, kind, owner_id, created_at)
+            VALUES ($1, $2, $3, $4, $5, $6, $7)
+        "#,
+                &[
+                    &realm.id,
+                    &realm.bp_id,
+                    &key,
+                    &realm.title,
+                    &realm.kind.to_string(),
+                    &owner,
+                    &realm.created_at,
+                ],
+            )
+            .await?;
+
+        Ok(realm)
+    }
+
+    async fn set_position(&self, account_id: AccountId, realm_id: RealmId, room_id: RoomId) -> DbResult<()> {
+        let client = self.db.get_client().await?;
+
+        client
+            .execute(
+                "UPDATE accounts SET current_realm_id = $2, current_room_id = $3 WHERE id = $1",
+                &[&account_id, &realm_id, &room_id],
+            )
+            .await?;
+
+        Ok(())
+    }
+
+    async fn complete(&self, account_id: AccountId) -> DbResult<bool> {
+        let client = self.db.get_client().await?;
+
+        let updated = client
+            .execute(
+                "UPDATE accounts SET tutorial_completed_at = NOW() WHERE id = $1 AND tutorial_completed_at IS NULL",
+                &[&account_id],
+            )
+            .await?;
+
+        Ok(updated > 0)
+    }
+}
@@ -41,7 +41,7 @@ impl UserRepo for UserRepository {
         let rows = client
             .query(
                 r#"
-                SELECT o.name AS object_key, u.key, u.value FROM user_object_kv AS u
+                SELECT o.name AS object_key, u.key AS kv_key, u.value FROM user_object_kv AS u
                 JOIN bp_objects o ON o.id = u.object_id
                 WHERE o.room_id = $2 AND u.realm_id = $1 AND u.account_id = $3
                 "#,
@@ -67,12 +67,7 @@ impl UserRepo for UserRepository {
 
         client
             .execute(
-                r#"
-                INSERT INTO user_current_room (realm_id, account_id, room_id)
-                VALUES ($1, $2, $3)
-                ON CONFLICT (realm_id, account_id)
-                DO UPDATE SET room_id = EXCLUDED.room_id
-                "#,
+                "UPDATE accounts SET current_realm_id = $1, current_room_id = $3 WHERE id = $2",
                 &[&realm_id, &account_id, &room_id],
             )
             .await?;
@@ -177,7 +172,7 @@ impl UserRepo for UserRepository {
                 INSERT INTO user_exits (realm_id, room_id, account_id, exit_id, locked)
                 VALUES ($1, $2, $3, $4, $5)
                 ON CONFLICT (realm_id, room_id, account_id, exit_id)
-                DO UPDATE SET value = EXCLUDED.value
+                DO UPDATE SET locked = EXCLUDED.locked
                 "#,
                 &[&realm_id, &room_id, &account_id, &exit_id, &locked],
             )
@@ -1,8 +1,7 @@
 use crate::db::error::DbError;
-use crate::lua::LuaJob;
+use crate::lua::queue::LuaSendError;
 use crate::models::types::{ObjectId, RoomId};
 use thiserror::Error;
-use tokio::sync::mpsc::error::SendError;
 
 pub type AppResult<T> = Result<T, DomainError>;
 
@@ -29,6 +28,10 @@ pub enum DomainError {
     #[error("permission denied")]
     PermissionDenied,
 
+    /// Concurrent update on shared state could not be applied
+    #[error("conflict: {0}")]
+    Conflict(String),
+
     /// Some precondition failed
     #[error("precondition failed: {0}")]
     PreconditionFailed(&'static str),
@@ -52,7 +55,7 @@ pub enum DomainError {
     Validation { field: &'static str, message: String },
 
     #[error(transparent)]
-    Send(#[from] Box<SendError<LuaJob>>),
+    Lua(#[from] LuaSendError),
 
     #[error(transparent)]
     Io(#[from] std::io::Error),
@@ -98,6 +101,9 @@ pub enum ConfigErrorKind {
 
     #[error("invalid environment variable {0}: {1}")]
     InvalidEnv(String, String),
+
+    #[error("invalid value for `{key}`: {message}")]
+    Invalid { key: String, message: String },
 }
 
 #[derive(Debug, Error)]
@@ -132,6 +138,8 @@ pub enum LoginError {
     AccountLocked,
     #[error("too many attempts")]
     TooManyAttempts,
+    #[error("locked until {0}")]
+    LockedUntil(chrono::DateTime<chrono::Utc>),
     #[error("internal error: {0}")]
     InternalError(String),
 }
@@ -24,3 +24,34 @@ pub const FORBIDDEN_LUA_TOKENS: &[&str] = &[
     // "require", "dofile", "loadfile", "loadstring", "package",
     // "io.", "os.", "debug.", "ffi", "collectgarbage", "setfenv", "getfenv"
 ];
+
+/// Maximum length of one line of player input in bytes; longer lines are cut
+pub const MAX_INPUT_LINE_BYTES: usize = 1024;
+
+/// Maximum length of a terminal escape sequence the line editor collects before dropping it
+pub const MAX_ESCAPE_BYTES: usize = 32;
+
+/// Maximum size of a WebSocket message from a client; bigger ones close the connection
+pub const MAX_WS_MESSAGE_BYTES: usize = 16 * 1024;
+
+/// Maximum number of output frames waiting for a slow client; past it the oldest text is dropped
+pub const MAX_OUTPUT_QUEUE: usize = 256;
+
+/// How long writing one frame to a client may take before it counts as no longer reading
+pub const OUTPUT_WRITE_TIMEOUT: std::time::Duration = std::time::Duration::from_secs(30);
+
+/// Maximum number of objects scripts may spawn into one room of a realm
+pub const MAX_SPAWNED_OBJECTS_PER_ROOM: usize = 50;
+
+/// Maximum number of moves and forced commands one script run may ask for
+pub const MAX_SCRIPT_ACTIONS: usize = 8;
+
+/// Maximum number of script runs in a row that move a player or force commands on them before the
+/// player types something again; stops scripts that keep triggering each other
+pub const MAX_SCRIPT_CHAIN: u32 = 10;
+
+/// Maximum number of steps in one `port4k.sequence`
+pub const MAX_SEQUENCE_STEPS: usize = 20;
+
+/// Maximum time from the start of a `port4k.sequence` to its last step
+pub const MAX_SEQUENCE_DURATION: std::time::Duration = std::time::Duration::from_secs(300);
@@ -2,7 +2,19 @@ use crate::db::error::DbError;
 use crate::error::{AppResult, DomainError, InfraError};
 use crate::hardening::{ALLOWED_DIRS, FORBIDDEN_LUA_TOKENS, MAX_LUA_BYTES};
 use crate::lua::ScriptHook;
-use crate::models::types::BlueprintId;
+use crate::lua::compat::{LEGACY_API_VERSION, LUA_API_VERSION};
+use crate::lua::lint::{ChunkKind, lint_chunk};
+use crate::models::ambience::AmbientEvent;
+use crate::models::blueprint::DropPolicy;
+use crate::models::dialogue::Dialogue;
+use crate::models::realm_directory::DIFFICULTIES;
+use crate::models::room::{
+    Discovery, Hazard, Lock, LockKind, RoomSounds, ScriptFirst, Storage, UseLimits, is_valid_sound_cue,
+};
+use crate::models::types::{AccountId, BlueprintId, ItemId, ObjectId, RoomId};
+use crate::models::vehicle::Vehicle;
+use crate::models::widget::{Widget, WidgetEffects};
+use crate::renderer::prompt::parse_prompt;
 use crate::util::{list_yaml_files_guarded, resolve_content_subdir};
 use mlua::Lua;
 use regex::Regex;
@@ -11,29 +23,64 @@ use std::collections::{HashMap, HashSet};
 use std::{fs, path::Path};
 use tokio_postgres::Transaction;
 
+mod export;
+mod include;
+mod lua_lib;
+mod migrate;
+mod prefab;
+mod prose;
+mod reachability;
+mod search;
+
+pub use export::export_blueprint;
+pub use prose::Dictionary;
+pub use search::GrepHit;
+
 // ====== v5 YAML models ======
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, Serialize)]
 struct RoomYaml {
-    pub version: u8,  // must be 5
-    pub id: String,   // "entry"
+    pub version: u8, // 5, or 6 when using includes
+    #[serde(default = "legacy_api_version")]
+    pub api_version: i16, // Lua API the scripts are written for, see lua::compat
+    pub id: String,  // "entry"
     pub name: String, // "Entry Hall"
     #[serde(default)]
     pub short: Option<String>,
     #[serde(rename = "description")]
     pub full_desc: String,
-    #[serde(default)]
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
     pub state: HashMap<String, serde_json::Value>,
-    #[serde(default)]
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
     pub hints: Vec<HintYaml>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub hazards: Vec<Hazard>,
+    #[serde(default, skip_serializing_if = "RoomSounds::is_empty")]
+    pub sounds: RoomSounds,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub vehicle: Option<Vehicle>,
+    #[serde(default, skip_serializing_if = "ScriptFirst::is_empty")]
+    pub script_first: ScriptFirst, // built-in verbs offered to on_command first
     #[serde(default)]
     pub objects: Vec<ObjectYaml>,
     #[serde(default)]
     pub exits: Vec<ExitYaml>,
-    #[serde(default)]
+    #[serde(default, skip_serializing_if = "ScriptYaml::is_empty")]
     pub scripts: ScriptYaml,
-    #[serde(default)]
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
     pub items_catalog: Vec<ItemCatalogYaml>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub recipes: Vec<RecipeYaml>, // blueprint-wide, like the items catalog
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub ambience: Vec<AmbientEvent>, // blueprint-wide, like recipes
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub renamed_from: Vec<String>, // previous room keys, keeps live realm state attached
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub notes: Vec<String>, // builder notes and TODOs, never shown to players
+}
+
+fn legacy_api_version() -> i16 {
+    LEGACY_API_VERSION
 }
 
 #[derive(Debug, Deserialize, Serialize, Clone)]
@@ -46,6 +93,34 @@ struct ItemCatalogYaml {
     #[serde(default)]
     pub examine: Option<String>,
     pub stackable: bool,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub durability: Option<i32>, // uses before it breaks; never wears when absent
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub wear: Option<i32>, // durability lost per use, defaults to 1
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub repair_tool: Option<String>, // item key needed to repair it
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub weight: Option<f32>, // in kilograms, shown by examine
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub pages: Vec<String>, // text shown page by page with `read`
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub is_coin: bool, // money: picked up into the realm balance
+}
+
+#[derive(Debug, Deserialize, Serialize, Clone, PartialEq)]
+#[serde(deny_unknown_fields)]
+struct RecipeYaml {
+    pub id: String,
+    pub inputs: Vec<String>, // exactly two item keys
+    pub output: String,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub qty: Option<i32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tool: Option<String>, // item key, needed in the inventory but not consumed
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub min_level: Option<i32>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub message: Option<String>,
 }
 
 #[derive(Debug, Deserialize, Serialize)]
@@ -58,6 +133,12 @@ struct HintYaml {
     pub cooldown: Option<i32>,
     #[serde(default)]
     pub once: Option<bool>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub limit: Option<u32>,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub tiers: Vec<String>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub tier_delay: Option<u32>,
 }
 
 #[derive(Debug, Deserialize, Serialize)]
@@ -67,6 +148,8 @@ struct FlagsYaml {
     pub revealed: Option<bool>,
     pub takeable: Option<bool>,
     pub stackable: Option<bool>,
+    pub movable: Option<bool>,
+    pub turnable: Option<bool>,
 }
 
 impl Default for FlagsYaml {
@@ -76,6 +159,8 @@ impl Default for FlagsYaml {
             revealed: Some(true),   // The object is revealed
             takeable: Some(false),  // Not takeable by user
             stackable: Some(false), // Not stackable (multiple copies)
+            movable: Some(false),   // Cannot be pushed or pulled
+            turnable: Some(false),  // Cannot be turned
         }
     }
 }
@@ -91,6 +176,10 @@ struct LootYaml {
     pub shared: bool, // NEW: false = per-player, true = global
 }
 
+fn is_unlimited(limits: &UseLimits) -> bool {
+    !limits.is_limited()
+}
+
 fn default_true() -> bool {
     true
 }
@@ -106,21 +195,49 @@ struct ObjectYaml {
     pub examine: Option<String>,
     #[serde(default)]
     pub flags: Option<FlagsYaml>,
-    #[serde(default)]
+    #[serde(default, skip_serializing_if = "HashMap::is_empty")]
     pub state: HashMap<String, serde_json::Value>, // arbitrary map (revealed, etc)
-    #[serde(default)]
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
     pub controls: Vec<String>, // ["exit:north.locked","object:door.locked"]
-
-    #[serde(default)]
+    #[serde(default, skip_serializing_if = "Discovery::is_visible")]
+    pub discovery: Discovery, // { mode: visible } | { mode: obscured, dc: 12 }
+    #[serde(default, skip_serializing_if = "is_unlimited")]
+    pub use_limits: UseLimits, // { cooldown: 30, max_uses: 3, scope: player | shared }
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub lock: Option<Lock>, // { kind: keypad, digits: 4 } | { message: "..." }
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub storage: Option<Storage>, // { capacity: 10, persistent: true }, a locker keeping a stash per player
+
+    #[serde(default, skip_serializing_if = "Option::is_none")]
     pub loot: Option<LootYaml>,
 
-    #[serde(default)]
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub pages: Vec<String>, // text shown page by page with `read`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub on_read: Option<String>, // Lua returning the pages (string or list of strings)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub on_terminal: Option<String>, // Lua receiving the lines typed while the object is used as a terminal
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub on_receive: Option<String>, // Lua deciding whether an item given to the object is accepted
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub board: Option<String>, // key of the vehicle room `board` leads into
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub widget: Option<Widget>, // keypad, lever bank or menu handled by the server
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub dialogue: Option<Dialogue>, // conversation tree, makes the object an NPC for `talk`
+
+    #[serde(default, skip_serializing)]
     pub on_use_: Option<String>, // Lua (key "on_use" in YAML)
-    #[serde(rename = "on_use", default)]
-    pub _on_use_compat: Option<String>, // compat alias
+    #[serde(rename = "on_use", default, skip_serializing_if = "Option::is_none")]
+    pub _on_use_compat: Option<String>, // compat alias, also used when exporting
+
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub renamed_from: Vec<String>, // previous object keys, keeps live realm state attached
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub notes: Vec<String>, // builder notes and TODOs, never shown to players
 }
 
-#[derive(Debug, Deserialize)]
+#[derive(Debug, Deserialize, Serialize)]
 struct ExitYaml {
     pub dir: String, // "north"
     pub to: String,  // "hallway_1"
@@ -130,11 +247,19 @@ struct ExitYaml {
     pub locked: Option<bool>,
     #[serde(default)]
     pub visible_when_locked: Option<bool>,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub lock: Option<Lock>, // what opens the exit, for the feedback when it is locked
 }
 
 #[derive(Debug, Serialize, Deserialize, Default)]
 struct ScriptYaml(HashMap<ScriptHook, String>);
 
+impl ScriptYaml {
+    fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+}
+
 // ====== Entry point ======
 
 pub async fn import_blueprint_sub_dir(
@@ -142,6 +267,7 @@ pub async fn import_blueprint_sub_dir(
     sub_dir: &str,
     content_base: &Path,
     db: &crate::db::Db,
+    dry_run: bool,
 ) -> AppResult<()> {
     println!("🚀 Starting blueprint import for '{}'", sub_dir);
 
@@ -151,13 +277,37 @@ pub async fn import_blueprint_sub_dir(
     let files = list_yaml_files_guarded(&dir)?;
     println!("📄 Found {} YAML file(s)", files.len());
 
+    let mut sources = include::SourceFiles::new(&files);
+    let prefabs = prefab::PrefabLibrary::load(content_base)?;
+    if !prefabs.is_empty() {
+        println!("🧩 Loaded {} prefab(s) from {}/", prefabs.len(), prefab::PREFAB_DIR);
+    }
+    let modules = lua_lib::load(&dir)?;
+    if !modules.is_empty() {
+        println!("📚 Loaded {} Lua module(s) from {}/", modules.len(), lua_lib::LIB_DIR);
+    }
+    let lexicon = prose::load_lexicon(&dir)?;
+    if !lexicon.is_empty() {
+        println!("📖 Loaded {} word(s) from {}", lexicon.len(), prose::LEXICON_FILE);
+    }
+
     // Parse first
     let mut rooms: Vec<RoomYaml> = Vec::new();
+    let lua = Lua::new();
+    let mut lint_warnings = 0;
     for (idx, path) in files.iter().enumerate() {
         println!("\n[{}/{}] Parsing: {}", idx + 1, files.len(), path.display());
 
         let text = fs::read_to_string(path).map_err(InfraError::from)?;
-        let mut room: RoomYaml = serde_yaml::from_str(&text)?;
+        let doc: serde_yaml::Value = serde_yaml::from_str(&text)?;
+        if include::is_fragment(&doc) {
+            println!("  ✓ Fragment, only used through includes");
+            continue;
+        }
+        let name = path.file_name().and_then(|n| n.to_str()).unwrap_or_default();
+        let doc = sources.resolve(name, doc)?;
+        let doc = prefabs.expand_room(doc)?;
+        let mut room: RoomYaml = serde_yaml::from_value(doc)?;
 
         // normalize "on_use"
         for o in &mut room.objects {
@@ -181,6 +331,12 @@ pub async fn import_blueprint_sub_dir(
         validate_lua_for_room(&room)?;
         println!(" ✓");
 
+        let warnings = lint_lua_for_room(&lua, &room);
+        for w in &warnings {
+            println!("  ⚠️  {}", w);
+        }
+        lint_warnings += warnings.len();
+
         rooms.push(room);
     }
 
@@ -196,6 +352,12 @@ pub async fn import_blueprint_sub_dir(
                     || existing.short != item.short
                     || existing.description != item.description
                     || existing.stackable != item.stackable
+                    || existing.durability != item.durability
+                    || existing.wear != item.wear
+                    || existing.repair_tool != item.repair_tool
+                    || existing.weight != item.weight
+                    || existing.pages != item.pages
+                    || existing.is_coin != item.is_coin
                 {
                     return Err(DomainError::Validation {
                         field: "items_catalog",
@@ -226,26 +388,67 @@ pub async fn import_blueprint_sub_dir(
     }
 
     println!("  ✓ Found {} unique item(s) across all rooms", all_items.len());
+    validate_item_durability(&all_items)?;
+
+    let all_recipes = collect_recipes(&rooms, &all_items)?;
+    if !all_recipes.is_empty() {
+        println!("  ✓ Found {} recipe(s)", all_recipes.len());
+    }
+    let all_ambience = collect_ambience(&rooms)?;
+    if !all_ambience.is_empty() {
+        println!("  ✓ Found {} ambient event(s)", all_ambience.len());
+    }
+    validate_vehicles(&rooms)?;
+    validate_dialogues(&rooms, &all_items)?;
+    reachability::validate_exit_targets(&rooms)?;
+
+    let entry = entry_room_key(db, blueprint_id).await?;
+    let warnings = reachability::lint_reachability(&rooms, entry.as_deref());
+    for w in &warnings {
+        println!("  ⚠️  {}", w);
+    }
+    lint_warnings += warnings.len();
+
+    if dry_run {
+        println!("\n🔎 Dry run: comparing against current blueprint state...");
+        let current = load_current_blueprint(db, blueprint_id).await?;
+        let diff = diff_blueprint(&current, &rooms)?;
+        print_import_diff(&diff);
+        print_lint_summary(lint_warnings);
+        println!("✨ Dry run complete, nothing was written.\n");
+        return Ok(());
+    }
 
     println!("\n💾 Starting database transaction...");
     let mut client = db.pool.get().await.map_err(DbError::from)?;
     let tx = client.build_transaction().start().await.map_err(DbError::from)?;
 
+    // Pass 0: reconcile renamed rooms with live realm state
+    println!("\n🧭 Pass 0: Reconciling renamed and removed rooms...");
+    let mut report = migrate::MigrationReport::default();
+    migrate::apply_room_renames(&tx, blueprint_id, &rooms, &mut report).await?;
+    migrate::collect_orphaned_rooms(&tx, blueprint_id, &rooms, &mut report).await?;
+
     // Pass 1: upsert rooms
     println!("\n📝 Pass 1: Creating room headers...");
-    let mut room_ids: HashMap<String, uuid::Uuid> = HashMap::new();
+    let mut room_ids: HashMap<String, RoomId> = HashMap::new();
     for (idx, r) in rooms.iter().enumerate() {
         print!("  [{}/{}] Upserting room '{}'...", idx + 1, rooms.len(), r.id);
         let room_id = upsert_room_header(&tx, blueprint_id, r).await?;
         room_ids.insert(r.id.clone(), room_id);
         println!(" ✓ ({})", room_id);
     }
+    validate_hazard_targets(&rooms, &room_ids)?;
 
     if !all_items.is_empty() {
         println!("\n📦 Pass 1b: Registering blueprint-level items catalog...");
         upsert_blueprint_items_catalog(&tx, blueprint_id, &all_items).await?;
         println!("  ✓ Registered {} item(s)", all_items.len());
     }
+    upsert_blueprint_recipes(&tx, blueprint_id, &all_recipes).await?;
+    upsert_blueprint_ambience(&tx, blueprint_id, &all_ambience).await?;
+    lua_lib::replace(&tx, blueprint_id, &modules).await?;
+    prose::replace_lexicon(&tx, blueprint_id, &lexicon).await?;
 
     // Pass 2: kv, objects, scripts, items_catalog
     println!("\n🔧 Pass 2: Adding objects, items, state, and scripts...");
@@ -259,11 +462,12 @@ pub async fn import_blueprint_sub_dir(
             println!(" ✓");
         }
 
-        if !r.objects.is_empty() {
-            print!("    • Creating {} object(s)...", r.objects.len());
-            upsert_objects(&tx, room_id, &r.objects).await?;
-            println!(" ✓");
-        }
+        migrate::apply_object_renames(&tx, room_id, &r.id, &r.objects, &mut report).await?;
+        migrate::collect_orphaned_objects(&tx, room_id, &r.id, &r.objects, &mut report).await?;
+
+        print!("    • Upserting {} object(s)...", r.objects.len());
+        upsert_objects(&tx, room_id, &r.objects).await?;
+        println!(" ✓");
 
         if !r.scripts.0.is_empty() {
             print!("    • Installing {} script hook(s)...", r.scripts.0.len());
@@ -289,288 +493,1500 @@ pub async fn import_blueprint_sub_dir(
         }
     }
 
+    println!("\n🏷  Recording blueprint version...");
+    let version = migrate::record_version(&tx, blueprint_id, &report).await?;
+    report.print();
+
     println!("\n💾 Committing transaction...");
     tx.commit().await.map_err(DbError::from)?;
 
-    println!("✨ Import complete! {} room(s) successfully imported.\n", rooms.len());
+    print_lint_summary(lint_warnings);
+    println!(
+        "✨ Import complete! {} room(s) successfully imported as version {}.\n",
+        rooms.len(),
+        version
+    );
     Ok(())
 }
 
-// ====== DB writers ======
+fn print_lint_summary(warnings: usize) {
+    if warnings > 0 {
+        println!("⚠️  {} lint warning(s), see above or run `@bp lint`", warnings);
+    }
+}
 
-async fn upsert_room_header(tx: &Transaction<'_>, bp_id: BlueprintId, r: &RoomYaml) -> AppResult<uuid::Uuid> {
-    let title = &r.name;
-    let short = r.short.as_deref().unwrap_or_default();
-    let body = &r.full_desc;
+// ====== Dry run diff ======
 
-    // Store hints as JSON (structured v3)
-    let hints_json = serde_json::to_value(&r.hints)?;
+/// Current blueprint state as stored in the database, keyed by room key
+#[derive(Debug, Default)]
+struct CurrentRoom {
+    header: RoomHeader,
+    kv: HashMap<String, serde_json::Value>,
+    scripts: HashMap<String, Option<String>>, // hook -> script
+    objects: HashMap<String, CurrentObject>,
+    exits: HashMap<String, CurrentExit>, // dir -> exit
+}
 
-    // Insert/update by (bp_id, key), return id
-    let row = tx
-        .query_one(
-            r#"
-            INSERT INTO bp_rooms (bp_id, key, title, short, body, hints)
-            VALUES ($1,$2,$3,$4,$5,$6::jsonb)
-            ON CONFLICT (bp_id, key) DO UPDATE
-            SET title = EXCLUDED.title,
-                short = EXCLUDED.short,
-                body  = EXCLUDED.body,
-                hints = EXCLUDED.hints
-            RETURNING id
-            "#,
-            &[&bp_id, &r.id, &title, &short, &body, &hints_json],
-        )
-        .await
-        .map_err(DbError::from)?;
-    Ok(row.get(0))
+/// The bp_rooms columns an import writes for a room, in the form they are stored
+#[derive(Debug, Default, PartialEq)]
+struct RoomHeader {
+    title: String,
+    short: String,
+    body: String,
+    hints: serde_json::Value,
+    hazards: serde_json::Value,
+    sounds: serde_json::Value,
+    vehicle: Option<serde_json::Value>,
+    api_version: i16,
+    notes: Vec<String>,
+    script_first: serde_json::Value,
 }
 
-async fn upsert_room_kv(
-    tx: &Transaction<'_>,
-    room_id: uuid::Uuid,
-    kv: &HashMap<String, serde_json::Value>,
-) -> AppResult<()> {
-    // Simple strategy: replace all kv for the room (small dataset)
-    tx.execute("DELETE FROM bp_room_kv WHERE room_id = $1", &[&room_id])
-        .await
-        .map_err(DbError::from)?;
-    for (k, v) in kv {
-        tx.execute(
-            r#"
-            INSERT INTO bp_room_kv (room_id, key, value)
-            VALUES ($1,$2,$3)
-            ON CONFLICT (room_id, key) DO UPDATE SET value = EXCLUDED.value
-            "#,
-            &[&room_id, k, v],
-        )
-        .await
-        .map_err(DbError::from)?;
+impl RoomHeader {
+    fn from_yaml(r: &RoomYaml) -> AppResult<Self> {
+        let script_first = r.script_first.normalized().map_err(|message| DomainError::Validation {
+            field: "room.script_first",
+            message,
+        })?;
+
+        Ok(RoomHeader {
+            title: r.name.clone(),
+            short: r.short.clone().unwrap_or_default(),
+            body: r.full_desc.clone(),
+            // Store hints as JSON (structured v3)
+            hints: serde_json::to_value(&r.hints)?,
+            hazards: serde_json::to_value(&r.hazards)?,
+            sounds: serde_json::to_value(&r.sounds)?,
+            vehicle: r.vehicle.as_ref().map(serde_json::to_value).transpose()?,
+            api_version: r.api_version,
+            notes: r.notes.clone(),
+            script_first: serde_json::to_value(&script_first)?,
+        })
     }
-    Ok(())
-}
 
-async fn upsert_objects(tx: &Transaction<'_>, room_id: uuid::Uuid, objects: &[ObjectYaml]) -> AppResult<()> {
-    // Replace all (keeps code simple & deterministic ordering via position)
-    tx.execute("DELETE FROM bp_object_nouns WHERE room_id = $1", &[&room_id])
-        .await
-        .map_err(DbError::from)?;
-    tx.execute("DELETE FROM bp_objects WHERE room_id = $1", &[&room_id])
-        .await
-        .map_err(DbError::from)?;
+    /// Names of the columns that differ from `other`
+    fn changed_fields(&self, other: &RoomHeader) -> Vec<&'static str> {
+        let mut changed = Vec::new();
+        if self.title != other.title {
+            changed.push("title");
+        }
+        if self.short != other.short {
+            changed.push("short");
+        }
+        if self.body != other.body {
+            changed.push("body");
+        }
+        if self.hints != other.hints {
+            changed.push("hints");
+        }
+        if self.hazards != other.hazards {
+            changed.push("hazards");
+        }
+        if self.sounds != other.sounds {
+            changed.push("sounds");
+        }
+        if self.vehicle != other.vehicle {
+            changed.push("vehicle");
+        }
+        if self.api_version != other.api_version {
+            changed.push("api_version");
+        }
+        if self.notes != other.notes {
+            changed.push("notes");
+        }
+        if self.script_first != other.script_first {
+            changed.push("script_first");
+        }
+        changed
+    }
+}
 
-    for (pos, o) in objects.iter().enumerate() {
-        let state_json = &o.state;
-        let flags_json = serde_json::to_value(o.flags.as_ref().unwrap_or(&FlagsYaml::default()))?;
-        let controls_json = serde_json::to_value(&o.controls)?;
-        let loot_json = serde_json::to_value(&o.loot)?;
+/// The bp_exits columns an import writes for an exit, in the form they are stored
+#[derive(Debug, Default, PartialEq)]
+struct CurrentExit {
+    to: String, // target room key
+    locked: bool,
+    description: Option<String>,
+    visible_when_locked: bool,
+    lock: Option<serde_json::Value>,
+}
 
-        let row = tx
-            .query_one(
-                r#"
-                INSERT INTO bp_objects
-                    (room_id, name, short, description, examine, use_lua,
-                    position, flags, controls, loot)
-                VALUES
-                    ($1,$2,$3,$4,$5,$6,$7,$8::jsonb,$9::jsonb,$10::jsonb)
-                RETURNING id
-                "#,
-                &[
-                    &room_id,
-                    &o.id,
-                    &o.short,
-                    &o.description,
-                    &o.examine,
-                    &o.on_use_,
-                    &(pos as i32),
-                    &flags_json,
-                    &controls_json,
-                    &loot_json,
-                ],
-            )
-            .await
-            .map_err(DbError::from)?;
-        let obj_id: uuid::Uuid = row.get(0);
+impl CurrentExit {
+    fn from_yaml(ex: &ExitYaml) -> AppResult<Self> {
+        Ok(CurrentExit {
+            to: ex.to.clone(),
+            locked: ex.locked.unwrap_or(false),
+            description: ex.description.clone(),
+            visible_when_locked: ex.visible_when_locked.unwrap_or(true),
+            lock: ex.lock.as_ref().map(serde_json::to_value).transpose()?,
+        })
+    }
 
-        // state
-        for (k, v) in state_json {
-            tx.execute(
-                r#"
-                INSERT INTO bp_objects_kv (object_id, key, value)
-                VALUES ($1,$2,$3)
-                ON CONFLICT (object_id, key) DO UPDATE SET value = EXCLUDED.value
-                "#,
-                &[&obj_id, k, v],
-            )
-            .await
-            .map_err(DbError::from)?;
+    /// Names of the columns that differ from `other`, the target shown as "old -> new"
+    fn changed_fields(&self, other: &CurrentExit) -> Vec<String> {
+        let mut changed = Vec::new();
+        if self.to != other.to {
+            changed.push(format!("{} -> {}", self.to, other.to));
         }
-
-        // nouns
-        for n in &o.nouns {
-            tx.execute(
-                r#"
-                INSERT INTO bp_object_nouns (room_id, obj_id, noun)
-                VALUES ($1,$2,$3)
-                ON CONFLICT (room_id, noun) DO UPDATE SET obj_id = EXCLUDED.obj_id
-                "#,
-                &[&room_id, &obj_id, n],
-            )
-            .await
-            .map_err(DbError::from)?;
+        if self.locked != other.locked {
+            changed.push("locked".into());
+        }
+        if self.description != other.description {
+            changed.push("description".into());
+        }
+        if self.visible_when_locked != other.visible_when_locked {
+            changed.push("visible_when_locked".into());
+        }
+        if self.lock != other.lock {
+            changed.push("lock".into());
         }
+        changed
     }
-
-    Ok(())
 }
 
-async fn upsert_blueprint_items_catalog(
-    tx: &Transaction<'_>,
-    bp_id: BlueprintId,
-    items: &HashMap<String, ItemCatalogYaml>,
-) -> AppResult<()> {
-    // Delete existing items for this blueprint
-    tx.execute("DELETE FROM bp_item_nouns WHERE bp_id = $1", &[&bp_id])
-        .await
-        .map_err(DbError::from)?;
-    tx.execute("DELETE FROM bp_items_catalog WHERE bp_id = $1", &[&bp_id])
-        .await
-        .map_err(DbError::from)?;
+#[derive(Debug, Default, PartialEq)]
+struct CurrentObject {
+    short: String,
+    description: String,
+    examine: Option<String>,
+    on_use: Option<String>,
+}
 
-    // Insert all items
-    for item in items.values() {
-        let row = tx
-            .query_one(
-                r#"
-                INSERT INTO bp_items_catalog
-                    (bp_id, item_key, name, short, description, examine, stackable)
-                VALUES
-                    ($1, $2, $3, $4, $5, $6, $7)
-                RETURNING id
-                "#,
-                &[
-                    &bp_id,
-                    &item.id,
-                    &item.name,
-                    &item.short,
-                    &item.description,
-                    &item.examine,
-                    &item.stackable,
-                ],
-            )
-            .await
-            .map_err(DbError::from)?;
-        let item_id: uuid::Uuid = row.get(0);
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum ChangeKind {
+    Added,
+    Updated,
+    Removed,
+    /// No longer in the YAML, but the import keeps it (rooms and exits are never deleted)
+    Orphaned,
+}
 
-        // Insert nouns for this item
-        for noun in &item.nouns {
-            tx.execute(
-                r#"
-                INSERT INTO bp_item_nouns (bp_id, item_id, noun)
-                VALUES ($1, $2, $3)
-                "#,
-                &[&bp_id, &item_id, noun],
-            )
-            .await
-            .map_err(DbError::from)?;
+impl ChangeKind {
+    fn marker(&self) -> &'static str {
+        match self {
+            ChangeKind::Added => "+",
+            ChangeKind::Updated => "~",
+            ChangeKind::Removed => "-",
+            ChangeKind::Orphaned => "!",
         }
     }
+}
 
-    Ok(())
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DiffEntry {
+    pub kind: ChangeKind,
+    /// Room key, "room/object" or "room:dir"
+    pub key: String,
+    pub detail: Option<String>,
 }
 
-async fn upsert_room_scripts(tx: &Transaction<'_>, room_id: uuid::Uuid, scripts: &ScriptYaml) -> AppResult<()> {
-    // single-row table keyed by room_id
-    for (hook, script) in scripts.0.iter() {
-        tx.execute(
+/// What an import would change compared to the current blueprint state
+#[derive(Debug, Default)]
+pub struct ImportDiff {
+    pub rooms: Vec<DiffEntry>,
+    pub objects: Vec<DiffEntry>,
+    pub exits: Vec<DiffEntry>,
+}
+
+impl ImportDiff {
+    pub fn is_empty(&self) -> bool {
+        self.rooms.is_empty() && self.objects.is_empty() && self.exits.is_empty()
+    }
+}
+
+async fn load_current_blueprint(db: &crate::db::Db, bp_id: BlueprintId) -> AppResult<HashMap<String, CurrentRoom>> {
+    // Plain reads, no transaction needed
+    let client = db.get_client().await?;
+    let mut rooms: HashMap<String, CurrentRoom> = HashMap::new();
+
+    let rows = client
+        .query(
             r#"
-            INSERT INTO bp_room_scripts (room_id, hook, script)
-            VALUES ($1,$2,$3)
-            ON CONFLICT (room_id, hook) DO UPDATE SET script = EXCLUDED.script
+            SELECT key, title, short, body, hints, hazards, sounds, vehicle, api_version, notes, script_first
+            FROM bp_rooms
+            WHERE bp_id = $1
             "#,
-            &[&room_id, &hook.as_str(), &script],
+            &[&bp_id],
         )
         .await
         .map_err(DbError::from)?;
+    for row in rows {
+        let short: Option<String> = row.get("short");
+        let hints: Option<serde_json::Value> = row.get("hints");
+        rooms.insert(
+            row.get("key"),
+            CurrentRoom {
+                header: RoomHeader {
+                    title: row.get("title"),
+                    short: short.unwrap_or_default(),
+                    body: row.get("body"),
+                    hints: hints.unwrap_or_default(),
+                    hazards: row.get("hazards"),
+                    sounds: row.get("sounds"),
+                    vehicle: row.get("vehicle"),
+                    api_version: row.get("api_version"),
+                    notes: row.get("notes"),
+                    script_first: row.get("script_first"),
+                },
+                ..Default::default()
+            },
+        );
     }
-    Ok(())
-}
 
-async fn upsert_exits(
-    tx: &Transaction<'_>,
-    from_room_id: uuid::Uuid,
-    exits: &Vec<ExitYaml>,
-    key_to_id: &HashMap<String, uuid::Uuid>,
-) -> AppResult<()> {
-    for ex in exits {
-        let d = ex.dir.to_ascii_lowercase();
-        let to_room_id = *key_to_id.get(&ex.to).ok_or_else(|| DomainError::Validation {
-            field: "exit",
-            message: format!("unknown target room key '{}'", ex.to),
-        })?;
+    let rows = client
+        .query(
+            r#"
+            SELECT r.key AS room_key, kv.key, kv.value
+            FROM bp_room_kv kv
+            JOIN bp_rooms r ON r.id = kv.room_id
+            WHERE r.bp_id = $1
+            "#,
+            &[&bp_id],
+        )
+        .await
+        .map_err(DbError::from)?;
+    for row in rows {
+        let room_key: String = row.get("room_key");
+        if let Some(room) = rooms.get_mut(&room_key) {
+            room.kv.insert(row.get("key"), row.get("value"));
+        }
+    }
 
-        tx.execute(
+    let rows = client
+        .query(
             r#"
-            INSERT INTO bp_exits (from_room_id, dir, to_room_id, locked, description, visible_when_locked)
-            VALUES ($1,$2,$3, COALESCE($4,false), $5, COALESCE($6,true))
-            ON CONFLICT (from_room_id, dir) DO UPDATE
-            SET to_room_id = EXCLUDED.to_room_id,
-                locked = EXCLUDED.locked,
-                description = EXCLUDED.description,
-                visible_when_locked = EXCLUDED.visible_when_locked
+            SELECT r.key AS room_key, s.hook, s.script
+            FROM bp_room_scripts s
+            JOIN bp_rooms r ON r.id = s.room_id
+            WHERE r.bp_id = $1
             "#,
-            &[
-                &from_room_id,
-                &d,
-                &to_room_id,
-                &ex.locked,
-                &ex.description,
-                &ex.visible_when_locked,
-            ],
+            &[&bp_id],
         )
         .await
         .map_err(DbError::from)?;
+    for row in rows {
+        let room_key: String = row.get("room_key");
+        if let Some(room) = rooms.get_mut(&room_key) {
+            room.scripts.insert(row.get("hook"), row.get("script"));
+        }
     }
-    Ok(())
-}
 
-// ====== Validation & Lua compile ======
+    let rows = client
+        .query(
+            r#"
+            SELECT r.key AS room_key, o.name, o.short, o.description, o.examine, o.use_lua
+            FROM bp_objects o
+            JOIN bp_rooms r ON r.id = o.room_id
+            WHERE r.bp_id = $1
+            "#,
+            &[&bp_id],
+        )
+        .await
+        .map_err(DbError::from)?;
+    for row in rows {
+        let room_key: String = row.get("room_key");
+        if let Some(room) = rooms.get_mut(&room_key) {
+            room.objects.insert(
+                row.get("name"),
+                CurrentObject {
+                    short: row.get("short"),
+                    description: row.get("description"),
+                    examine: row.get("examine"),
+                    on_use: row.get("use_lua"),
+                },
+            );
+        }
+    }
+
+    let rows = client
+        .query(
+            r#"
+            SELECT f.key AS from_key, e.dir, t.key AS to_key, e.locked, e.description, e.visible_when_locked,
+                   e.lock_info
+            FROM bp_exits e
+            JOIN bp_rooms f ON f.id = e.from_room_id
+            JOIN bp_rooms t ON t.id = e.to_room_id
+            WHERE f.bp_id = $1
+            "#,
+            &[&bp_id],
+        )
+        .await
+        .map_err(DbError::from)?;
+    for row in rows {
+        let from_key: String = row.get("from_key");
+        if let Some(room) = rooms.get_mut(&from_key) {
+            room.exits.insert(
+                row.get("dir"),
+                CurrentExit {
+                    to: row.get("to_key"),
+                    locked: row.get("locked"),
+                    description: row.get("description"),
+                    visible_when_locked: row.get("visible_when_locked"),
+                    lock: row.get("lock_info"),
+                },
+            );
+        }
+    }
+
+    Ok(rooms)
+}
+
+fn diff_blueprint(current: &HashMap<String, CurrentRoom>, rooms: &[RoomYaml]) -> AppResult<ImportDiff> {
+    let mut diff = ImportDiff::default();
+
+    // Old keys claimed by a rename are not reported as orphaned
+    let renamed_rooms: HashSet<&str> = rooms
+        .iter()
+        .flat_map(|r| r.renamed_from.iter().map(String::as_str))
+        .collect();
+
+    for r in rooms {
+        let renamed = r
+            .renamed_from
+            .iter()
+            .find(|k| current.contains_key(*k) && !current.contains_key(&r.id));
+        if let Some(old_key) = renamed {
+            diff.rooms.push(DiffEntry {
+                kind: ChangeKind::Updated,
+                key: r.id.clone(),
+                detail: Some(format!("renamed from {}", old_key)),
+            });
+        }
+
+        let Some(cur) = current.get(&r.id).or_else(|| renamed.and_then(|k| current.get(k))) else {
+            diff.rooms.push(DiffEntry {
+                kind: ChangeKind::Added,
+                key: r.id.clone(),
+                detail: None,
+            });
+            for o in &r.objects {
+                diff.objects.push(DiffEntry {
+                    kind: ChangeKind::Added,
+                    key: format!("{}/{}", r.id, o.id),
+                    detail: None,
+                });
+            }
+            for ex in &r.exits {
+                diff.exits.push(DiffEntry {
+                    kind: ChangeKind::Added,
+                    key: format!("{}:{}", r.id, ex.dir.to_ascii_lowercase()),
+                    detail: Some(format!("-> {}", ex.to)),
+                });
+            }
+            continue;
+        };
+
+        let mut changed = cur.header.changed_fields(&RoomHeader::from_yaml(r)?);
+        // State is only replaced when the YAML has any, script hooks are never removed
+        if !r.state.is_empty() && cur.kv != r.state {
+            changed.push("state");
+        }
+        if r.scripts
+            .0
+            .iter()
+            .any(|(hook, script)| cur.scripts.get(hook.as_str()).map(Option::as_deref) != Some(Some(script.as_str())))
+        {
+            changed.push("scripts");
+        }
+        if !changed.is_empty() && renamed.is_none() {
+            diff.rooms.push(DiffEntry {
+                kind: ChangeKind::Updated,
+                key: r.id.clone(),
+                detail: Some(changed.join(", ")),
+            });
+        }
+
+        // Objects
+        let mut seen = HashSet::new();
+        for o in &r.objects {
+            seen.insert(o.id.as_str());
+            let new_obj = CurrentObject {
+                short: o.short.clone(),
+                description: o.description.clone(),
+                examine: o.examine.clone(),
+                on_use: o.on_use_.clone(),
+            };
+
+            let renamed_obj = o
+                .renamed_from
+                .iter()
+                .find(|k| cur.objects.contains_key(*k) && !cur.objects.contains_key(&o.id));
+            if let Some(old_key) = renamed_obj {
+                seen.insert(old_key.as_str());
+                diff.objects.push(DiffEntry {
+                    kind: ChangeKind::Updated,
+                    key: format!("{}/{}", r.id, o.id),
+                    detail: Some(format!("renamed from {}", old_key)),
+                });
+                continue;
+            }
+
+            match cur.objects.get(&o.id) {
+                None => diff.objects.push(DiffEntry {
+                    kind: ChangeKind::Added,
+                    key: format!("{}/{}", r.id, o.id),
+                    detail: None,
+                }),
+                Some(existing) if *existing != new_obj => diff.objects.push(DiffEntry {
+                    kind: ChangeKind::Updated,
+                    key: format!("{}/{}", r.id, o.id),
+                    detail: None,
+                }),
+                Some(_) => {}
+            }
+        }
+        for name in cur.objects.keys().filter(|k| !seen.contains(k.as_str())) {
+            diff.objects.push(DiffEntry {
+                kind: ChangeKind::Removed,
+                key: format!("{}/{}", r.id, name),
+                detail: None,
+            });
+        }
+
+        // Exits
+        let mut seen = HashSet::new();
+        for ex in &r.exits {
+            let dir = ex.dir.to_ascii_lowercase();
+            match cur.exits.get(&dir) {
+                None => diff.exits.push(DiffEntry {
+                    kind: ChangeKind::Added,
+                    key: format!("{}:{}", r.id, dir),
+                    detail: Some(format!("-> {}", ex.to)),
+                }),
+                Some(existing) => {
+                    let changed = existing.changed_fields(&CurrentExit::from_yaml(ex)?);
+                    if !changed.is_empty() {
+                        diff.exits.push(DiffEntry {
+                            kind: ChangeKind::Updated,
+                            key: format!("{}:{}", r.id, dir),
+                            detail: Some(changed.join(", ")),
+                        });
+                    }
+                }
+            }
+            seen.insert(dir);
+        }
+        for (dir, ex) in cur.exits.iter().filter(|(d, _)| !seen.contains(*d)) {
+            diff.exits.push(DiffEntry {
+                kind: ChangeKind::Orphaned,
+                key: format!("{}:{}", r.id, dir),
+                detail: Some(format!("-> {}, orphaned, kept", ex.to)),
+            });
+        }
+    }
+
+    let imported: HashSet<&str> = rooms.iter().map(|r| r.id.as_str()).collect();
+    for key in current
+        .keys()
+        .filter(|k| !imported.contains(k.as_str()) && !renamed_rooms.contains(k.as_str()))
+    {
+        diff.rooms.push(DiffEntry {
+            kind: ChangeKind::Orphaned,
+            key: key.clone(),
+            detail: Some("orphaned, kept".into()),
+        });
+    }
+
+    diff.rooms.sort_by(|a, b| a.key.cmp(&b.key));
+    diff.objects.sort_by(|a, b| a.key.cmp(&b.key));
+    diff.exits.sort_by(|a, b| a.key.cmp(&b.key));
+    Ok(diff)
+}
+
+fn print_import_diff(diff: &ImportDiff) {
+    if diff.is_empty() {
+        println!("  ✓ No changes");
+        return;
+    }
+
+    for (title, entries) in [
+        ("Rooms", &diff.rooms),
+        ("Objects", &diff.objects),
+        ("Exits", &diff.exits),
+    ] {
+        if entries.is_empty() {
+            continue;
+        }
+        println!("  {} ({}):", title, entries.len());
+        for e in entries {
+            match &e.detail {
+                Some(d) => println!("    {} {} ({})", e.kind.marker(), e.key, d),
+                None => println!("    {} {}", e.kind.marker(), e.key),
+            }
+        }
+    }
+}
+
+// ====== Bulk import ======
+
+/// Name of the manifest file that describes a tree of blueprints
+pub const MANIFEST_FILE: &str = "manifest.yaml";
+
+#[derive(Debug, Deserialize)]
+struct ManifestYaml {
+    pub version: u8, // must be 1
+    #[serde(default)]
+    pub blueprints: Vec<ManifestEntryYaml>,
+}
+
+#[derive(Debug, Deserialize)]
+struct ManifestEntryYaml {
+    pub key: String, // blueprint key
+    pub dir: String, // sub-dir (relative to the manifest) with the room YAML files
+    #[serde(default)]
+    pub id: Option<BlueprintId>, // force a blueprint id when creating
+    #[serde(default)]
+    pub title: Option<String>,
+    #[serde(default)]
+    pub owner: Option<String>, // owner username, needed when the blueprint does not exist yet
+    #[serde(default)]
+    pub entry_room: Option<String>,
+    #[serde(default)]
+    pub difficulty: Option<String>, // one of DIFFICULTIES, shown in the realm directory
+    #[serde(default)]
+    pub tags: Vec<String>, // for filtering the realm directory
+    #[serde(default)]
+    pub prompt: Option<String>, // prompt for players in the realm, with %codes
+    #[serde(default)]
+    pub dropped_items: DropPolicy, // { policy: persist } | { policy: despawn | return, after: <minutes> }
+}
+
+/// Outcome of importing a single blueprint from a manifest
+#[derive(Debug)]
+pub struct BlueprintImportSummary {
+    pub key: String,
+    pub dir: String,
+    pub result: Result<BlueprintId, String>,
+}
+
+/// Imports all blueprints listed in the manifest found in `root_dir` (under `content_base`), in
+/// manifest order. A failing blueprint does not stop the others; each one is imported in its own
+/// transaction and reported in the returned summary.
+pub async fn import_blueprint_tree(
+    root_dir: &str,
+    content_base: &Path,
+    db: &crate::db::Db,
+    dry_run: bool,
+) -> AppResult<Vec<BlueprintImportSummary>> {
+    let root = resolve_content_subdir(content_base, root_dir)?;
+    let manifest_path = root.join(MANIFEST_FILE);
+    println!("📜 Reading manifest: {}", manifest_path.display());
+
+    let text = fs::read_to_string(&manifest_path).map_err(InfraError::from)?;
+    let manifest: ManifestYaml = serde_yaml::from_str(&text)?;
+    validate_manifest(&manifest)?;
+    println!("📚 Manifest lists {} blueprint(s)", manifest.blueprints.len());
+
+    let mut summary = Vec::with_capacity(manifest.blueprints.len());
+    for (idx, entry) in manifest.blueprints.iter().enumerate() {
+        println!(
+            "\n========== [{}/{}] Blueprint '{}' ({}) ==========",
+            idx + 1,
+            manifest.blueprints.len(),
+            entry.key,
+            entry.dir
+        );
+
+        let result = import_manifest_entry(entry, &root, db, dry_run)
+            .await
+            .map_err(|e| e.to_string());
+        if let Err(e) = &result {
+            println!("❌ Blueprint '{}' failed: {}", entry.key, e);
+        }
+
+        summary.push(BlueprintImportSummary {
+            key: entry.key.clone(),
+            dir: entry.dir.clone(),
+            result,
+        });
+    }
+
+    Ok(summary)
+}
+
+async fn import_manifest_entry(
+    entry: &ManifestEntryYaml,
+    root: &Path,
+    db: &crate::db::Db,
+    dry_run: bool,
+) -> AppResult<BlueprintId> {
+    if dry_run {
+        // Never create blueprints on a dry run; a missing blueprint diffs as all-new
+        let bp_id = find_blueprint(db, &entry.key)
+            .await?
+            .unwrap_or_else(|| entry.id.unwrap_or_default());
+        import_blueprint_sub_dir(bp_id, &entry.dir, root, db, true).await?;
+        return Ok(bp_id);
+    }
+
+    let title = entry.title.as_deref().unwrap_or(&entry.key);
+    let bp_id = ensure_blueprint(db, &entry.key, title, entry.owner.as_deref(), entry.id).await?;
+
+    import_blueprint_sub_dir(bp_id, &entry.dir, root, db, false).await?;
+
+    if let Some(entry_room) = entry.entry_room.as_deref() {
+        set_entry_room(db, bp_id, entry_room).await?;
+    }
+    if entry.difficulty.is_some() || !entry.tags.is_empty() {
+        set_directory_info(db, bp_id, entry.difficulty.as_deref(), &entry.tags).await?;
+    }
+    let prompt = match entry.prompt.as_deref() {
+        Some(prompt) => parse_prompt("manifest.blueprints.prompt", prompt)?,
+        None => None,
+    };
+    set_prompt(db, bp_id, prompt.as_deref()).await?;
+    set_drop_policy(db, bp_id, entry.dropped_items).await?;
+
+    Ok(bp_id)
+}
+
+fn validate_manifest(m: &ManifestYaml) -> AppResult<()> {
+    if m.version != 1 {
+        return Err(DomainError::Validation {
+            field: "manifest.version",
+            message: format!("unsupported manifest version {}, expected 1", m.version),
+        });
+    }
+
+    let mut keys = HashSet::new();
+    let mut ids = HashSet::new();
+    for e in &m.blueprints {
+        if e.key.trim().is_empty() {
+            return Err(DomainError::Validation {
+                field: "manifest.blueprints.key",
+                message: "blueprint key cannot be empty".into(),
+            });
+        }
+        if !keys.insert(e.key.as_str()) {
+            return Err(DomainError::Validation {
+                field: "manifest.blueprints.key",
+                message: format!("duplicate blueprint key '{}'", e.key),
+            });
+        }
+        if let Some(d) = e.difficulty.as_deref()
+            && !DIFFICULTIES.contains(&d)
+        {
+            return Err(DomainError::Validation {
+                field: "manifest.blueprints.difficulty",
+                message: format!(
+                    "unknown difficulty '{}' for '{}' ({})",
+                    d,
+                    e.key,
+                    DIFFICULTIES.join(", ")
+                ),
+            });
+        }
+        if let Some(tag) = e
+            .tags
+            .iter()
+            .find(|t| t.trim().is_empty() || t.contains(char::is_whitespace))
+        {
+            return Err(DomainError::Validation {
+                field: "manifest.blueprints.tags",
+                message: format!("invalid tag '{}' for '{}', tags are single words", tag, e.key),
+            });
+        }
+        if let Some(prompt) = e.prompt.as_deref() {
+            parse_prompt("manifest.blueprints.prompt", prompt)?;
+        }
+        if e.dropped_items.after_minutes() == Some(0) {
+            return Err(DomainError::Validation {
+                field: "manifest.blueprints.dropped_items",
+                message: format!("dropped items of '{}' need at least one minute", e.key),
+            });
+        }
+        if let Some(id) = e.id
+            && !ids.insert(id)
+        {
+            return Err(DomainError::Validation {
+                field: "manifest.blueprints.id",
+                message: format!("duplicate blueprint id '{}'", id),
+            });
+        }
+    }
+
+    Ok(())
+}
+
+/// Returns the id of the blueprint with the given key, if it exists.
+pub async fn find_blueprint(db: &crate::db::Db, key: &str) -> AppResult<Option<BlueprintId>> {
+    let client = db.get_client().await?;
+    let row = client
+        .query_opt("SELECT id FROM blueprints WHERE key = $1", &[&key])
+        .await
+        .map_err(DbError::from)?;
+    Ok(row.map(|r| r.get(0)))
+}
+
+/// Key of the entry room of a blueprint, when it has one
+async fn entry_room_key(db: &crate::db::Db, bp_id: BlueprintId) -> AppResult<Option<String>> {
+    let client = db.get_client().await?;
+    let row = client
+        .query_opt(
+            "SELECT r.key FROM blueprints b JOIN bp_rooms r ON r.id = b.entry_room_id WHERE b.id = $1",
+            &[&bp_id],
+        )
+        .await
+        .map_err(DbError::from)?;
+    Ok(row.map(|r| r.get(0)))
+}
+
+/// Returns the id of the blueprint with the given key, creating it (owned by `owner_username`) when
+/// it does not exist yet.
+pub async fn ensure_blueprint(
+    db: &crate::db::Db,
+    key: &str,
+    title: &str,
+    owner_username: Option<&str>,
+    id: Option<BlueprintId>,
+) -> AppResult<BlueprintId> {
+    if let Some(bp_id) = find_blueprint(db, key).await? {
+        return Ok(bp_id);
+    }
+
+    let client = db.get_client().await?;
+
+    let Some(owner) = owner_username else {
+        return Err(DomainError::NotFound(format!(
+            "blueprint '{}' not found; an owner is needed to create it",
+            key
+        )));
+    };
+    let Some(owner_row) = client
+        .query_opt("SELECT id FROM accounts WHERE username = $1", &[&owner])
+        .await
+        .map_err(DbError::from)?
+    else {
+        return Err(DomainError::NotFound(format!(
+            "owner '{}' not found in accounts",
+            owner
+        )));
+    };
+    let owner_id: AccountId = owner_row.get(0);
+
+    let row = client
+        .query_one(
+            r#"
+            INSERT INTO blueprints (id, key, title, owner_id, status)
+            VALUES (COALESCE($1, gen_random_uuid()), $2, $3, $4, 'draft')
+            ON CONFLICT (key) DO UPDATE SET title = EXCLUDED.title
+            RETURNING id
+            "#,
+            &[&id, &key, &title, &owner_id],
+        )
+        .await
+        .map_err(DbError::from)?;
+    Ok(row.get(0))
+}
+
+/// Sets how a blueprint shows up in the realm directory. Tags are stored lowercased.
+pub async fn set_directory_info(
+    db: &crate::db::Db,
+    bp_id: BlueprintId,
+    difficulty: Option<&str>,
+    tags: &[String],
+) -> AppResult<()> {
+    let client = db.get_client().await?;
+    let tags: Vec<String> = tags.iter().map(|t| t.to_lowercase()).collect();
+
+    client
+        .execute(
+            "UPDATE blueprints SET difficulty = $2, tags = $3 WHERE id = $1",
+            &[&bp_id, &difficulty, &tags],
+        )
+        .await
+        .map_err(DbError::from)?;
+    Ok(())
+}
+
+/// Sets the prompt of a blueprint's realms; None for the default prompt.
+pub async fn set_prompt(db: &crate::db::Db, bp_id: BlueprintId, prompt: Option<&str>) -> AppResult<()> {
+    let client = db.get_client().await?;
+
+    client
+        .execute("UPDATE blueprints SET prompt = $2 WHERE id = $1", &[&bp_id, &prompt])
+        .await
+        .map_err(DbError::from)?;
+    Ok(())
+}
+
+/// Sets what happens to items dropped in a blueprint's realms.
+pub async fn set_drop_policy(db: &crate::db::Db, bp_id: BlueprintId, policy: DropPolicy) -> AppResult<()> {
+    let client = db.get_client().await?;
+    let after = policy.after_minutes().map(|m| i32::try_from(m).unwrap_or(i32::MAX));
+
+    client
+        .execute(
+            "UPDATE blueprints SET drop_policy = $2, drop_after_minutes = $3 WHERE id = $1",
+            &[&bp_id, &policy.as_str(), &after],
+        )
+        .await
+        .map_err(DbError::from)?;
+    Ok(())
+}
+
+/// Sets the entry room of a blueprint by room key.
+pub async fn set_entry_room(db: &crate::db::Db, bp_id: BlueprintId, room_key: &str) -> AppResult<()> {
+    let client = db.get_client().await?;
+
+    let Some(row) = client
+        .query_opt(
+            "SELECT id FROM bp_rooms WHERE bp_id = $1 AND key = $2",
+            &[&bp_id, &room_key],
+        )
+        .await
+        .map_err(DbError::from)?
+    else {
+        return Err(DomainError::NotFound(format!(
+            "entry room '{}' not found in blueprint",
+            room_key
+        )));
+    };
+    let room_id: RoomId = row.get(0);
+
+    client
+        .execute(
+            "UPDATE blueprints SET entry_room_id = $1 WHERE id = $2",
+            &[&room_id, &bp_id],
+        )
+        .await
+        .map_err(DbError::from)?;
+    Ok(())
+}
+
+// ====== DB writers ======
+
+async fn upsert_room_header(tx: &Transaction<'_>, bp_id: BlueprintId, r: &RoomYaml) -> AppResult<RoomId> {
+    let h = RoomHeader::from_yaml(r)?;
+
+    // Insert/update by (bp_id, key), return id
+    let row = tx
+        .query_one(
+            r#"
+            INSERT INTO bp_rooms (bp_id, key, title, short, body, hints, hazards, sounds, vehicle, api_version, notes, script_first)
+            VALUES ($1,$2,$3,$4,$5,$6::jsonb,$7::jsonb,$8::jsonb,$9::jsonb,$10,$11,$12::jsonb)
+            ON CONFLICT (bp_id, key) DO UPDATE
+            SET title = EXCLUDED.title,
+                short = EXCLUDED.short,
+                body  = EXCLUDED.body,
+                hints = EXCLUDED.hints,
+                hazards = EXCLUDED.hazards,
+                sounds = EXCLUDED.sounds,
+                vehicle = EXCLUDED.vehicle,
+                api_version = EXCLUDED.api_version,
+                notes = EXCLUDED.notes,
+                script_first = EXCLUDED.script_first
+            RETURNING id
+            "#,
+            &[
+                &bp_id,
+                &r.id,
+                &h.title,
+                &h.short,
+                &h.body,
+                &h.hints,
+                &h.hazards,
+                &h.sounds,
+                &h.vehicle,
+                &h.api_version,
+                &h.notes,
+                &h.script_first,
+            ],
+        )
+        .await
+        .map_err(DbError::from)?;
+    Ok(row.get(0))
+}
+
+async fn upsert_room_kv(
+    tx: &Transaction<'_>,
+    room_id: RoomId,
+    kv: &HashMap<String, serde_json::Value>,
+) -> AppResult<()> {
+    // Simple strategy: replace all kv for the room (small dataset)
+    tx.execute("DELETE FROM bp_room_kv WHERE room_id = $1", &[&room_id])
+        .await
+        .map_err(DbError::from)?;
+    for (k, v) in kv {
+        tx.execute(
+            r#"
+            INSERT INTO bp_room_kv (room_id, key, value)
+            VALUES ($1,$2,$3)
+            ON CONFLICT (room_id, key) DO UPDATE SET value = EXCLUDED.value
+            "#,
+            &[&room_id, k, v],
+        )
+        .await
+        .map_err(DbError::from)?;
+    }
+    Ok(())
+}
+
+async fn upsert_objects(tx: &Transaction<'_>, room_id: RoomId, objects: &[ObjectYaml]) -> AppResult<()> {
+    // Upsert by (room_id, name) so object ids stay stable across imports; realm and user state
+    // is attached to these ids. Objects that are no longer in the room are removed.
+    tx.execute("DELETE FROM bp_object_nouns WHERE room_id = $1", &[&room_id])
+        .await
+        .map_err(DbError::from)?;
+
+    let names: Vec<&str> = objects.iter().map(|o| o.id.as_str()).collect();
+    tx.execute(
+        "DELETE FROM bp_objects WHERE room_id = $1 AND NOT (name = ANY($2))",
+        &[&room_id, &names],
+    )
+    .await
+    .map_err(DbError::from)?;
+
+    for (pos, o) in objects.iter().enumerate() {
+        let state_json = &o.state;
+        let flags_json = serde_json::to_value(o.flags.as_ref().unwrap_or(&FlagsYaml::default()))?;
+        let controls_json = serde_json::to_value(&o.controls)?;
+        let loot_json = serde_json::to_value(&o.loot)?;
+        let discovery_json = serde_json::to_value(&o.discovery)?;
+        let lock_json = o.lock.as_ref().map(serde_json::to_value).transpose()?;
+        let use_limits_json = serde_json::to_value(o.use_limits)?;
+        let widget_json = o.widget.as_ref().map(serde_json::to_value).transpose()?;
+        let dialogue_json = o.dialogue.as_ref().map(serde_json::to_value).transpose()?;
+        let storage_json = o.storage.as_ref().map(serde_json::to_value).transpose()?;
+
+        let row = tx
+            .query_one(
+                r#"
+                INSERT INTO bp_objects
+                    (room_id, name, short, description, examine, use_lua,
+                    position, flags, controls, loot, discovery, pages, read_lua, board, widget, terminal_lua,
+                    dialogue, use_limits, receive_lua, lock_info, storage, notes)
+                VALUES
+                    ($1,$2,$3,$4,$5,$6,$7,$8::jsonb,$9::jsonb,$10::jsonb,$11::jsonb,$12,$13,$14,$15::jsonb,$16,
+                    $17::jsonb,$18::jsonb,$19,$20::jsonb,$21::jsonb,$22)
+                ON CONFLICT (room_id, name) DO UPDATE
+                SET short       = EXCLUDED.short,
+                    description = EXCLUDED.description,
+                    examine     = EXCLUDED.examine,
+                    use_lua     = EXCLUDED.use_lua,
+                    position    = EXCLUDED.position,
+                    flags       = EXCLUDED.flags,
+                    controls    = EXCLUDED.controls,
+                    loot        = EXCLUDED.loot,
+                    discovery   = EXCLUDED.discovery,
+                    pages       = EXCLUDED.pages,
+                    read_lua    = EXCLUDED.read_lua,
+                    board       = EXCLUDED.board,
+                    widget      = EXCLUDED.widget,
+                    terminal_lua = EXCLUDED.terminal_lua,
+                    dialogue    = EXCLUDED.dialogue,
+                    use_limits  = EXCLUDED.use_limits,
+                    receive_lua = EXCLUDED.receive_lua,
+                    lock_info   = EXCLUDED.lock_info,
+                    storage     = EXCLUDED.storage,
+                    notes       = EXCLUDED.notes,
+                    updated_at  = now()
+                RETURNING id
+                "#,
+                &[
+                    &room_id,
+                    &o.id,
+                    &o.short,
+                    &o.description,
+                    &o.examine,
+                    &o.on_use_,
+                    &(pos as i32),
+                    &flags_json,
+                    &controls_json,
+                    &loot_json,
+                    &discovery_json,
+                    &o.pages,
+                    &o.on_read,
+                    &o.board,
+                    &widget_json,
+                    &o.on_terminal,
+                    &dialogue_json,
+                    &use_limits_json,
+                    &o.on_receive,
+                    &lock_json,
+                    &storage_json,
+                    &o.notes,
+                ],
+            )
+            .await
+            .map_err(DbError::from)?;
+        let obj_id: ObjectId = row.get(0);
+
+        // state (replace all)
+        tx.execute("DELETE FROM bp_objects_kv WHERE object_id = $1", &[&obj_id])
+            .await
+            .map_err(DbError::from)?;
+        for (k, v) in state_json {
+            tx.execute(
+                r#"
+                INSERT INTO bp_objects_kv (object_id, key, value)
+                VALUES ($1,$2,$3)
+                ON CONFLICT (object_id, key) DO UPDATE SET value = EXCLUDED.value
+                "#,
+                &[&obj_id, k, v],
+            )
+            .await
+            .map_err(DbError::from)?;
+        }
+
+        // nouns
+        for n in &o.nouns {
+            tx.execute(
+                r#"
+                INSERT INTO bp_object_nouns (room_id, obj_id, noun)
+                VALUES ($1,$2,$3)
+                ON CONFLICT (room_id, noun) DO UPDATE SET obj_id = EXCLUDED.obj_id
+                "#,
+                &[&room_id, &obj_id, n],
+            )
+            .await
+            .map_err(DbError::from)?;
+        }
+    }
+
+    Ok(())
+}
+
+async fn upsert_blueprint_items_catalog(
+    tx: &Transaction<'_>,
+    bp_id: BlueprintId,
+    items: &HashMap<String, ItemCatalogYaml>,
+) -> AppResult<()> {
+    // Delete existing items for this blueprint
+    tx.execute("DELETE FROM bp_item_nouns WHERE bp_id = $1", &[&bp_id])
+        .await
+        .map_err(DbError::from)?;
+    tx.execute("DELETE FROM bp_items_catalog WHERE bp_id = $1", &[&bp_id])
+        .await
+        .map_err(DbError::from)?;
+
+    // Insert all items
+    for item in items.values() {
+        let row = tx
+            .query_one(
+                r#"
+                INSERT INTO bp_items_catalog
+                    (bp_id, item_key, name, short, description, examine, stackable,
+                     max_durability, wear_per_use, repair_tool, weight, pages, is_coin)
+                VALUES
+                    ($1, $2, $3, $4, $5, $6, $7, $8, $9, $10, $11, $12, $13)
+                RETURNING id
+                "#,
+                &[
+                    &bp_id,
+                    &item.id,
+                    &item.name,
+                    &item.short,
+                    &item.description,
+                    &item.examine,
+                    &item.stackable,
+                    &item.durability,
+                    &item.wear.unwrap_or(1),
+                    &item.repair_tool,
+                    &item.weight,
+                    &item.pages,
+                    &item.is_coin,
+                ],
+            )
+            .await
+            .map_err(DbError::from)?;
+        let item_id: ItemId = row.get(0);
+
+        // Insert nouns for this item
+        for noun in &item.nouns {
+            tx.execute(
+                r#"
+                INSERT INTO bp_item_nouns (bp_id, item_id, noun)
+                VALUES ($1, $2, $3)
+                "#,
+                &[&bp_id, &item_id, noun],
+            )
+            .await
+            .map_err(DbError::from)?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Replaces the recipes of the blueprint
+async fn upsert_blueprint_recipes(tx: &Transaction<'_>, bp_id: BlueprintId, recipes: &[RecipeYaml]) -> AppResult<()> {
+    tx.execute("DELETE FROM bp_recipes WHERE bp_id = $1", &[&bp_id])
+        .await
+        .map_err(DbError::from)?;
+
+    for r in recipes {
+        tx.execute(
+            r#"
+            INSERT INTO bp_recipes (bp_id, recipe_key, inputs, output_key, output_qty, tool_key, min_level, message)
+            VALUES ($1, $2, $3, $4, $5, $6, $7, $8)
+            "#,
+            &[
+                &bp_id,
+                &r.id,
+                &r.inputs,
+                &r.output,
+                &r.qty.unwrap_or(1),
+                &r.tool,
+                &r.min_level,
+                &r.message,
+            ],
+        )
+        .await
+        .map_err(DbError::from)?;
+    }
+
+    Ok(())
+}
+
+/// Replaces the ambient events of the blueprint
+async fn upsert_blueprint_ambience(tx: &Transaction<'_>, bp_id: BlueprintId, events: &[AmbientEvent]) -> AppResult<()> {
+    tx.execute("DELETE FROM bp_ambience WHERE bp_id = $1", &[&bp_id])
+        .await
+        .map_err(DbError::from)?;
+
+    for e in events {
+        let event = serde_json::to_value(e)?;
+        tx.execute(
+            "INSERT INTO bp_ambience (bp_id, event_key, event) VALUES ($1, $2, $3)",
+            &[&bp_id, &e.id, &event],
+        )
+        .await
+        .map_err(DbError::from)?;
+    }
+
+    Ok(())
+}
+
+async fn upsert_room_scripts(tx: &Transaction<'_>, room_id: RoomId, scripts: &ScriptYaml) -> AppResult<()> {
+    // single-row table keyed by room_id
+    for (hook, script) in scripts.0.iter() {
+        tx.execute(
+            r#"
+            INSERT INTO bp_room_scripts (room_id, hook, script)
+            VALUES ($1,$2,$3)
+            ON CONFLICT (room_id, hook) DO UPDATE SET script = EXCLUDED.script
+            "#,
+            &[&room_id, &hook.as_str(), &script],
+        )
+        .await
+        .map_err(DbError::from)?;
+    }
+    Ok(())
+}
+
+async fn upsert_exits(
+    tx: &Transaction<'_>,
+    from_room_id: RoomId,
+    exits: &Vec<ExitYaml>,
+    key_to_id: &HashMap<String, RoomId>,
+) -> AppResult<()> {
+    for ex in exits {
+        let d = ex.dir.to_ascii_lowercase();
+        let to_room_id = *key_to_id.get(&ex.to).ok_or_else(|| DomainError::Validation {
+            field: "exit",
+            message: format!("unknown target room key '{}'", ex.to),
+        })?;
+
+        let e = CurrentExit::from_yaml(ex)?;
+
+        tx.execute(
+            r#"
+            INSERT INTO bp_exits (from_room_id, dir, to_room_id, locked, description, visible_when_locked, lock_info)
+            VALUES ($1,$2,$3,$4,$5,$6,$7::jsonb)
+            ON CONFLICT (from_room_id, dir) DO UPDATE
+            SET to_room_id = EXCLUDED.to_room_id,
+                locked = EXCLUDED.locked,
+                description = EXCLUDED.description,
+                visible_when_locked = EXCLUDED.visible_when_locked,
+                lock_info = EXCLUDED.lock_info
+            "#,
+            &[
+                &from_room_id,
+                &d,
+                &to_room_id,
+                &e.locked,
+                &e.description,
+                &e.visible_when_locked,
+                &e.lock,
+            ],
+        )
+        .await
+        .map_err(DbError::from)?;
+    }
+    Ok(())
+}
+
+// ====== Validation & Lua compile ======
+
+/// Durability only makes sense for single items, and the wear and repair tool need it
+fn validate_item_durability(items: &HashMap<String, ItemCatalogYaml>) -> AppResult<()> {
+    let err = |id: &str, message: &str| DomainError::Validation {
+        field: "items_catalog",
+        message: format!("item '{}' {}", id, message),
+    };
+
+    for item in items.values() {
+        match item.durability {
+            Some(d) if d < 1 => return Err(err(&item.id, "needs a durability of at least 1")),
+            Some(_) if item.stackable => return Err(err(&item.id, "is stackable and cannot have a durability")),
+            None if item.wear.is_some() || item.repair_tool.is_some() => {
+                return Err(err(&item.id, "has wear or a repair tool but no durability"));
+            }
+            _ => {}
+        }
+        if item.wear.is_some_and(|w| w < 1) {
+            return Err(err(&item.id, "needs a wear of at least 1"));
+        }
+        if item.weight.is_some_and(|w| !(w > 0.0 && w.is_finite())) {
+            return Err(err(&item.id, "needs a weight above 0"));
+        }
+        if let Some(tool) = &item.repair_tool
+            && !items.contains_key(tool)
+        {
+            return Err(err(
+                &item.id,
+                &format!("has repair tool '{}', which is not in the items catalog", tool),
+            ));
+        }
+    }
+
+    Ok(())
+}
+
+/// Collects the recipes of all rooms. A recipe may be repeated in several rooms, but only with the
+/// same definition. All item keys must be in the blueprint's items catalog.
+fn collect_recipes(rooms: &[RoomYaml], items: &HashMap<String, ItemCatalogYaml>) -> AppResult<Vec<RecipeYaml>> {
+    let err = |message: String| DomainError::Validation {
+        field: "recipes",
+        message,
+    };
+
+    let mut recipes: Vec<RecipeYaml> = Vec::new();
+    for recipe in rooms.iter().flat_map(|r| r.recipes.iter()) {
+        if let Some(existing) = recipes.iter().find(|r| r.id == recipe.id) {
+            if existing != recipe {
+                return Err(err(format!(
+                    "recipe '{}' has inconsistent definitions across rooms",
+                    recipe.id
+                )));
+            }
+            continue;
+        }
+
+        let [a, b] = recipe.inputs.as_slice() else {
+            return Err(err(format!("recipe '{}' needs exactly two inputs", recipe.id)));
+        };
+        for key in [a, b, &recipe.output].into_iter().chain(recipe.tool.as_ref()) {
+            if !items.contains_key(key) {
+                return Err(err(format!(
+                    "recipe '{}' references item '{}', which is not in the items catalog",
+                    recipe.id, key
+                )));
+            }
+        }
+        if recipe.qty.is_some_and(|q| q < 1) {
+            return Err(err(format!("recipe '{}' has a quantity below 1", recipe.id)));
+        }
+        if let Some(other) = recipes
+            .iter()
+            .find(|r| (&r.inputs[0] == a && &r.inputs[1] == b) || (&r.inputs[0] == b && &r.inputs[1] == a))
+        {
+            return Err(err(format!(
+                "recipes '{}' and '{}' combine the same items",
+                other.id, recipe.id
+            )));
+        }
+
+        recipes.push(recipe.clone());
+    }
+
+    Ok(recipes)
+}
+
+/// Collects the ambient events of all rooms. Like recipes, an event may be repeated in several
+/// rooms with the same definition. Events need steps, and their rooms must exist.
+fn collect_ambience(rooms: &[RoomYaml]) -> AppResult<Vec<AmbientEvent>> {
+    let err = |message: String| DomainError::Validation {
+        field: "ambience",
+        message,
+    };
+
+    let mut events: Vec<AmbientEvent> = Vec::new();
+    for event in rooms.iter().flat_map(|r| r.ambience.iter()) {
+        if let Some(existing) = events.iter().find(|e| e.id == event.id) {
+            if existing != event {
+                return Err(err(format!(
+                    "ambient event '{}' has inconsistent definitions across rooms",
+                    event.id
+                )));
+            }
+            continue;
+        }
+
+        if event.steps.is_empty() {
+            return Err(err(format!("ambient event '{}' has no steps", event.id)));
+        }
+        if event.every == 0 {
+            return Err(err(format!(
+                "ambient event '{}' needs an interval of at least 1",
+                event.id
+            )));
+        }
+        if !(event.chance > 0.0 && event.chance <= 1.0) {
+            return Err(err(format!(
+                "ambient event '{}' needs a chance above 0 and at most 1",
+                event.id
+            )));
+        }
+        if let Some(room) = event.rooms.iter().find(|k| !rooms.iter().any(|r| &r.id == *k)) {
+            return Err(err(format!(
+                "ambient event '{}' reaches unknown room '{}'",
+                event.id, room
+            )));
+        }
+
+        events.push(event.clone());
+    }
+
+    Ok(events)
+}
+
+/// Vehicles need distinct buttons and stops in other rooms of the blueprint. Objects can only be
+/// boarded into a vehicle stopping at their room.
+fn validate_vehicles(rooms: &[RoomYaml]) -> AppResult<()> {
+    let err = |message: String| DomainError::Validation {
+        field: "vehicle",
+        message,
+    };
+    let by_id: HashMap<&str, &RoomYaml> = rooms.iter().map(|r| (r.id.as_str(), r)).collect();
+
+    for r in rooms {
+        let Some(vehicle) = &r.vehicle else {
+            continue;
+        };
+        if vehicle.stops.is_empty() {
+            return Err(err(format!("vehicle '{}' has no stops", r.id)));
+        }
+        if vehicle.travel == 0 || vehicle.schedule == Some(0) {
+            return Err(err(format!(
+                "vehicle '{}' needs a travel time and schedule of at least 1 second",
+                r.id
+            )));
+        }
+        for (i, stop) in vehicle.stops.iter().enumerate() {
+            if vehicle.stop_by_button(&stop.button) != Some(i) {
+                return Err(err(format!(
+                    "vehicle '{}' has two stops with button '{}'",
+                    r.id, stop.button
+                )));
+            }
+            if stop.room == r.id || !by_id.contains_key(stop.room.as_str()) {
+                return Err(err(format!("vehicle '{}' stops at unknown room '{}'", r.id, stop.room)));
+            }
+        }
+    }
+
+    for r in rooms {
+        for (obj, target) in r.objects.iter().filter_map(|o| o.board.as_ref().map(|t| (o, t))) {
+            let Some(vehicle) = by_id.get(target.as_str()).and_then(|t| t.vehicle.as_ref()) else {
+                return Err(err(format!(
+                    "object '{}' boards '{}', which is not a vehicle",
+                    obj.id, target
+                )));
+            };
+            if vehicle.stop_in_room(&r.id).is_none() {
+                return Err(err(format!(
+                    "vehicle '{}' does not stop at room '{}' of object '{}'",
+                    target, r.id, obj.id
+                )));
+            }
+        }
+    }
+
+    Ok(())
+}
+
+/// Hazards can only force players into rooms of the same blueprint
+fn validate_hazard_targets(rooms: &[RoomYaml], room_ids: &HashMap<String, RoomId>) -> AppResult<()> {
+    for r in rooms {
+        for target in r.hazards.iter().filter_map(|h| h.move_to.as_ref()) {
+            if !room_ids.contains_key(target) {
+                return Err(DomainError::Validation {
+                    field: "room.hazards",
+                    message: format!("room '{}' has a hazard moving to unknown room '{}'", r.id, target),
+                });
+            }
+        }
+    }
+    Ok(())
+}
 
 fn validate_room_semantics(room: &RoomYaml) -> AppResult<()> {
-    if room.version != 5 {
+    if room.version != 5 && u64::from(room.version) != include::INCLUDE_VERSION {
         return Err(DomainError::Validation {
             field: "room.version",
-            message: "unsupported room schema version; expected 5".into(),
+            message: "unsupported room schema version; expected 5 or 6".into(),
         });
     }
-    if room.id.trim().is_empty() {
+    if !(LEGACY_API_VERSION..=LUA_API_VERSION).contains(&room.api_version) {
         return Err(DomainError::Validation {
-            field: "room",
-            message: "room id empty".into(),
+            field: "room.api_version",
+            message: format!(
+                "unsupported Lua api_version {}; expected {} to {}",
+                room.api_version, LEGACY_API_VERSION, LUA_API_VERSION
+            ),
         });
     }
-    if room.name.trim().is_empty() {
+    if room.id.trim().is_empty() {
         return Err(DomainError::Validation {
             field: "room",
-            message: "room name empty".into(),
+            message: "room id empty".into(),
         });
     }
-    if room.full_desc.trim().is_empty() {
+    if room.id.len() > 64 {
         return Err(DomainError::Validation {
             field: "room",
-            message: "room desc empty".into(),
+            message: "room id too long".into(),
         });
     }
-    if room.id.len() > 64 {
+    validate_room_title(&room.name)?;
+
+    for hazard in &room.hazards {
+        if hazard.interval == 0 {
+            return Err(DomainError::Validation {
+                field: "room.hazards",
+                message: format!("{:?} hazard has a zero interval", hazard.kind),
+            });
+        }
+    }
+
+    if let Some(cue) = room.sounds.cues().find(|c| !is_valid_sound_cue(c)) {
         return Err(DomainError::Validation {
-            field: "room",
-            message: "room id too long".into(),
+            field: "room.sounds",
+            message: format!("invalid sound cue '{}'", cue),
         });
     }
-    if room.name.len() > 128 {
+
+    let script_first = room
+        .script_first
+        .normalized()
+        .map_err(|message| DomainError::Validation {
+            field: "room.script_first",
+            message,
+        })?;
+    for (verb, objects) in &script_first.0 {
+        if let Some(key) = objects.iter().find(|k| !room.objects.iter().any(|o| &o.id == *k)) {
+            return Err(DomainError::Validation {
+                field: "room.script_first",
+                message: format!("'{}' names unknown object '{}'", verb, key),
+            });
+        }
+    }
+    if !script_first.is_empty() && !room.scripts.0.contains_key(&ScriptHook::OnCommand) {
         return Err(DomainError::Validation {
-            field: "room",
-            message: "room name too long".into(),
+            field: "room.script_first",
+            message: "room has no on_command script to offer the verbs to".into(),
         });
     }
 
@@ -648,7 +2064,7 @@ fn validate_room_semantics(room: &RoomYaml) -> AppResult<()> {
     }
 
     // object ids unique
-    let mut obj_ids = HashSet::new();
+    let mut obj_ids: HashSet<&str> = HashSet::new();
     for o in &room.objects {
         if o.id.trim().is_empty() {
             return Err(DomainError::Validation {
@@ -656,13 +2072,26 @@ fn validate_room_semantics(room: &RoomYaml) -> AppResult<()> {
                 message: "object with empty id".into(),
             });
         }
-        if !obj_ids.insert(&o.id) {
+        if !obj_ids.insert(o.id.as_str()) {
             return Err(DomainError::Validation {
                 field: "object",
                 message: format!("duplicate object id: {}", o.id),
             });
         }
+        validate_object_text(&o.id, &o.short, &o.description)?;
         // visible enum validated by serde; nothing to do here
+        if o.use_limits.cooldown == Some(0) || o.use_limits.max_uses == Some(0) {
+            return Err(DomainError::Validation {
+                field: "object.use_limits",
+                message: format!("object '{}' has a zero cooldown or max_uses", o.id),
+            });
+        }
+        if o.storage.is_some_and(|st| st.capacity == 0) {
+            return Err(DomainError::Validation {
+                field: "object.storage",
+                message: format!("object '{}' has a storage capacity of 0", o.id),
+            });
+        }
         // controls format: "exit:<dir>.<field>" or "object:<id>.<path>"
         for c in &o.controls {
             let ok = c.starts_with("exit:") || c.starts_with("object:");
@@ -675,35 +2104,228 @@ fn validate_room_semantics(room: &RoomYaml) -> AppResult<()> {
         }
     }
 
-    // {o:ID} placeholders must reference existing objects (check both description + optional 'o' field)
-    let re = Regex::new(r"\{o:([a-zA-Z0-9_\-]+)}").unwrap();
-    for src in [room.full_desc.as_str()].into_iter() {
-        for cap in re.captures_iter(src) {
-            let id = cap[1].to_string();
-            if !obj_ids.contains(&id) {
-                return Err(DomainError::Validation {
-                    field: "description",
-                    message: format!("text references unknown object id: {}", id),
-                });
-            }
+    // stashed objects hide behind/under/over another object of the same room
+    for o in &room.objects {
+        if let Discovery::Stashed { place, object } = &o.discovery
+            && (object == &o.id || !obj_ids.contains(object.as_str()))
+        {
+            return Err(DomainError::Validation {
+                field: "object.discovery",
+                message: format!(
+                    "object '{}' is stashed {} unknown object '{}'",
+                    o.id,
+                    place.as_str(),
+                    object
+                ),
+            });
         }
     }
 
-    // exits: dir whitelist, 'to' slug-ish
-    let slug = Regex::new(r"^[a-zA-Z0-9_\-:]+$").unwrap();
+    validate_room_body(&room.full_desc, &obj_ids)?;
     for ex in &room.exits {
-        let d = ex.dir.to_ascii_lowercase();
-        if !ALLOWED_DIRS.contains(&d.as_str()) {
+        validate_exit(&ex.dir, &ex.to)?;
+    }
+
+    validate_locks(room)?;
+    validate_widgets(room)
+}
+
+/// A room title must say something and fit the room header. Also checked when builders retitle
+/// a room in-game.
+pub(crate) fn validate_room_title(title: &str) -> AppResult<()> {
+    if title.trim().is_empty() {
+        return Err(DomainError::Validation {
+            field: "room",
+            message: "room name empty".into(),
+        });
+    }
+    if title.len() > 128 {
+        return Err(DomainError::Validation {
+            field: "room",
+            message: "room name too long".into(),
+        });
+    }
+    Ok(())
+}
+
+/// A room description must say something, and its {o:ID} placeholders must reference objects of
+/// the room
+pub(crate) fn validate_room_body(body: &str, object_ids: &HashSet<&str>) -> AppResult<()> {
+    if body.trim().is_empty() {
+        return Err(DomainError::Validation {
+            field: "room",
+            message: "room desc empty".into(),
+        });
+    }
+    let re = Regex::new(r"\{o:([a-zA-Z0-9_\-]+)}").unwrap();
+    for cap in re.captures_iter(body) {
+        let id = &cap[1];
+        if !object_ids.contains(id) {
             return Err(DomainError::Validation {
-                field: "exit",
-                message: format!("invalid exit dir '{}'", d),
+                field: "description",
+                message: format!("text references unknown object id: {}", id),
             });
         }
-        if ex.to.trim().is_empty() || !slug.is_match(&ex.to) {
-            return Err(DomainError::Validation {
-                field: "exit",
-                message: format!("invalid exit target '{}'", ex.to),
-            });
+    }
+    Ok(())
+}
+
+/// Objects need a short and a full description. Also checked when builders edit them in-game.
+pub(crate) fn validate_object_text(key: &str, short: &str, description: &str) -> AppResult<()> {
+    if short.trim().is_empty() {
+        return Err(DomainError::Validation {
+            field: "object",
+            message: format!("object '{}' has empty short description", key),
+        });
+    }
+    if description.trim().is_empty() {
+        return Err(DomainError::Validation {
+            field: "object",
+            message: format!("object '{}' has empty description", key),
+        });
+    }
+    Ok(())
+}
+
+/// Exits go in one of the known directions, to a slug-ish room key
+pub(crate) fn validate_exit(dir: &str, to: &str) -> AppResult<()> {
+    let d = dir.to_ascii_lowercase();
+    if !ALLOWED_DIRS.contains(&d.as_str()) {
+        return Err(DomainError::Validation {
+            field: "exit",
+            message: format!("invalid exit dir '{}'", d),
+        });
+    }
+    let slug = Regex::new(r"^[a-zA-Z0-9_\-:]+$").unwrap();
+    if to.trim().is_empty() || !slug.is_match(to) {
+        return Err(DomainError::Validation {
+            field: "exit",
+            message: format!("invalid exit target '{}'", to),
+        });
+    }
+    Ok(())
+}
+
+/// A lock's feedback must say something; only keypads have digits
+fn validate_locks(room: &RoomYaml) -> AppResult<()> {
+    let err = |what: String, message: &str| DomainError::Validation {
+        field: "lock",
+        message: format!("lock of {} {}", what, message),
+    };
+
+    let exits = room.exits.iter().map(|e| (format!("exit '{}'", e.dir), &e.lock));
+    let objects = room.objects.iter().map(|o| (format!("object '{}'", o.id), &o.lock));
+    for (what, lock) in exits.chain(objects) {
+        let Some(lock) = lock else {
+            continue;
+        };
+        if lock.message.as_deref().is_some_and(|m| m.trim().is_empty()) {
+            return Err(err(what, "has an empty message"));
+        }
+        match (lock.kind, lock.digits) {
+            (LockKind::Keypad, Some(0)) => return Err(err(what, "has a keypad without digits")),
+            (LockKind::Keypad, _) | (_, None) => {}
+            (_, Some(_)) => return Err(err(what, "has digits but is not a keypad")),
+        }
+    }
+    Ok(())
+}
+
+/// Widgets must be solvable, and their effects can only touch exits and objects of their room
+fn validate_widgets(room: &RoomYaml) -> AppResult<()> {
+    let err = |obj: &str, message: &str| DomainError::Validation {
+        field: "object.widget",
+        message: format!("widget of object '{}' {}", obj, message),
+    };
+
+    for o in &room.objects {
+        let Some(widget) = &o.widget else {
+            continue;
+        };
+        match widget {
+            Widget::Keypad { code, .. } => {
+                if code.trim().is_empty() {
+                    return Err(err(&o.id, "has an empty code"));
+                }
+            }
+            Widget::LeverBank { levers, solution, .. } => {
+                if levers.is_empty() || levers.iter().any(|l| l.trim().is_empty()) {
+                    return Err(err(&o.id, "needs named levers"));
+                }
+                let unique: HashSet<String> = levers.iter().map(|l| l.to_ascii_lowercase()).collect();
+                if unique.len() != levers.len() {
+                    return Err(err(&o.id, "has two levers with the same name"));
+                }
+                if let Some(s) = solution.iter().find(|s| !levers.contains(s)) {
+                    return Err(err(&o.id, &format!("has unknown lever '{}' in its solution", s)));
+                }
+            }
+            Widget::Menu { options, .. } => {
+                if options.is_empty() || options.iter().any(|opt| opt.label.trim().is_empty()) {
+                    return Err(err(&o.id, "needs labelled options"));
+                }
+            }
+        }
+
+        for effects in widget.effects() {
+            validate_effects(room, effects).map_err(|m| err(&o.id, &m))?;
+        }
+    }
+
+    Ok(())
+}
+
+/// Effects can only touch exits and objects of their room
+fn validate_effects(room: &RoomYaml, effects: &WidgetEffects) -> Result<(), String> {
+    for dir in effects.unlock.iter().chain(&effects.lock) {
+        if !room.exits.iter().any(|ex| ex.dir.eq_ignore_ascii_case(dir)) {
+            return Err(format!("changes unknown exit '{}'", dir));
+        }
+    }
+    if let Some(key) = effects.set.keys().find(|k| !room.objects.iter().any(|ro| &ro.id == *k)) {
+        return Err(format!("sets state of unknown object '{}'", key));
+    }
+    Ok(())
+}
+
+/// Dialogue trees need their start and every `goto` to be nodes of the tree, and can only use
+/// items of the blueprint
+fn validate_dialogues(rooms: &[RoomYaml], items: &HashMap<String, ItemCatalogYaml>) -> AppResult<()> {
+    let err = |room: &str, obj: &str, message: String| DomainError::Validation {
+        field: "object.dialogue",
+        message: format!("dialogue of object '{}' in room '{}' {}", obj, room, message),
+    };
+
+    for r in rooms {
+        for o in &r.objects {
+            let Some(dialogue) = &o.dialogue else {
+                continue;
+            };
+            if dialogue.node(&dialogue.start).is_none() {
+                return Err(err(
+                    &r.id,
+                    &o.id,
+                    format!("starts at unknown node '{}'", dialogue.start),
+                ));
+            }
+            for (id, node) in &dialogue.nodes {
+                if node.text.trim().is_empty() {
+                    return Err(err(&r.id, &o.id, format!("has node '{}' without text", id)));
+                }
+                for option in &node.options {
+                    if let Some(to) = option.goto.as_deref().filter(|to| dialogue.node(to).is_none()) {
+                        return Err(err(
+                            &r.id,
+                            &o.id,
+                            format!("goes from '{}' to unknown node '{}'", id, to),
+                        ));
+                    }
+                    validate_effects(r, &option.effects).map_err(|m| err(&r.id, &o.id, m))?;
+                }
+            }
+            if let Some(item) = dialogue.items().find(|i| !items.contains_key(*i)) {
+                return Err(err(&r.id, &o.id, format!("uses unknown item '{}'", item)));
+            }
         }
     }
 
@@ -722,11 +2344,93 @@ fn validate_lua_for_room(room: &RoomYaml) -> AppResult<()> {
         if let Some(code) = obj.on_use_.as_deref() {
             compile_lua_chunk(&lua, &format!("room:{}:object:{}:on_use", room.id, obj.id), code)?;
         }
+        if let Some(code) = obj.on_terminal.as_deref() {
+            compile_lua_chunk(&lua, &format!("room:{}:object:{}:on_terminal", room.id, obj.id), code)?;
+        }
+        if let Some(code) = obj.on_receive.as_deref() {
+            compile_lua_chunk(&lua, &format!("room:{}:object:{}:on_receive", room.id, obj.id), code)?;
+        }
     }
 
     Ok(())
 }
 
+/// The Lua chunks of a room, named like `room:<room>:object:<obj>:on_use`
+fn lua_chunks(room: &RoomYaml) -> Vec<(String, ChunkKind, &str)> {
+    let mut chunks: Vec<(String, ChunkKind, &str)> = Vec::new();
+
+    let mut hooks: Vec<_> = room.scripts.0.iter().collect();
+    hooks.sort_by_key(|(hook, _)| hook.as_str());
+    for (hook, code) in hooks {
+        chunks.push((
+            format!("room:{}:script:{}", room.id, hook.as_str()),
+            ChunkKind::Room(hook.clone()),
+            code,
+        ));
+    }
+    for obj in &room.objects {
+        let scripts = [
+            // Rooms read back from the database only have the export alias
+            (
+                ChunkKind::OnUse,
+                obj.on_use_.as_deref().or(obj._on_use_compat.as_deref()),
+            ),
+            (ChunkKind::OnRead, obj.on_read.as_deref()),
+            (ChunkKind::OnTerminal, obj.on_terminal.as_deref()),
+            (ChunkKind::OnReceive, obj.on_receive.as_deref()),
+        ];
+        for (kind, code) in scripts {
+            if let Some(code) = code {
+                chunks.push((
+                    format!("room:{}:object:{}:{}", room.id, obj.id, kind.as_str()),
+                    kind,
+                    code,
+                ));
+            }
+        }
+    }
+    chunks
+}
+
+/// Lint warnings for the Lua chunks of a room, each prefixed with the chunk it is about
+fn lint_lua_for_room(lua: &Lua, room: &RoomYaml) -> Vec<String> {
+    lua_chunks(room)
+        .into_iter()
+        .flat_map(|(name, kind, code)| {
+            lint_chunk(lua, code, &kind, room.api_version)
+                .into_iter()
+                .map(move |w| format!("{} {}", name, w))
+        })
+        .collect()
+}
+
+/// Lints the Lua and the map of the blueprint `bp_key` as it is stored now
+pub async fn lint_blueprint(db: &crate::db::Db, bp_key: &str) -> AppResult<Vec<String>> {
+    let (entry, rooms) = export::load_rooms(db, bp_key).await?;
+    let lua = Lua::new();
+    let mut warnings: Vec<String> = rooms.iter().flat_map(|r| lint_lua_for_room(&lua, r)).collect();
+    warnings.extend(reachability::lint_reachability(&rooms, entry.as_deref()));
+    Ok(warnings)
+}
+
+/// Lines of the texts, Lua and Lua modules of the blueprint `bp_key` containing `pattern`
+pub async fn grep_blueprint(db: &crate::db::Db, bp_key: &str, pattern: &str) -> AppResult<Vec<GrepHit>> {
+    let (_, rooms) = export::load_rooms(db, bp_key).await?;
+    let modules = lua_lib::stored(db, bp_key).await?;
+    Ok(search::grep(&rooms, &modules, pattern))
+}
+
+/// Checks the spelling (with a dictionary) and style of the texts of the blueprint `bp_key`
+pub async fn lint_blueprint_prose(
+    db: &crate::db::Db,
+    bp_key: &str,
+    dictionary: Option<&Dictionary>,
+) -> AppResult<Vec<String>> {
+    let (_, rooms) = export::load_rooms(db, bp_key).await?;
+    let lexicon = prose::stored_lexicon(db, bp_key).await?;
+    Ok(prose::lint_prose(&rooms, dictionary, &lexicon))
+}
+
 fn check_lua_string(name: &str, code: &str) -> AppResult<()> {
     let bytes = code.as_bytes();
     if bytes.len() > MAX_LUA_BYTES {
@@ -747,8 +2451,640 @@ fn check_lua_string(name: &str, code: &str) -> AppResult<()> {
     Ok(())
 }
 
-fn compile_lua_chunk(lua: &Lua, name: &str, code: &str) -> AppResult<()> {
+pub(crate) fn compile_lua_chunk(lua: &Lua, name: &str, code: &str) -> AppResult<()> {
     check_lua_string(name, code)?;
     lua.load(code).set_name(name).into_function()?;
     Ok(())
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn manifest(text: &str) -> ManifestYaml {
+        serde_yaml::from_str(text).expect("valid manifest yaml")
+    }
+
+    #[test]
+    fn t_manifest_ok() {
+        let m = manifest(
+            r#"
+version: 1
+blueprints:
+  - key: tutorial
+    dir: tutorial
+    owner: admin
+    entry_room: cell_block
+  - key: hub
+    dir: hub
+    id: 7f1c0b8e-4a8e-4f5e-9a55-6f2d0c3c8a10
+"#,
+        );
+        assert!(validate_manifest(&m).is_ok());
+        assert_eq!(m.blueprints[0].entry_room.as_deref(), Some("cell_block"));
+        assert!(m.blueprints[1].id.is_some());
+    }
+
+    #[test]
+    fn t_manifest_rejects_duplicate_keys() {
+        let m = manifest(
+            r#"
+version: 1
+blueprints:
+  - { key: hub, dir: a }
+  - { key: hub, dir: b }
+"#,
+        );
+        assert!(validate_manifest(&m).is_err());
+    }
+
+    #[test]
+    fn t_manifest_directory_info() {
+        let m = manifest(
+            r#"
+version: 1
+blueprints:
+  - { key: hub, dir: hub, difficulty: hard, tags: [horror, puzzle] }
+"#,
+        );
+        assert!(validate_manifest(&m).is_ok());
+        assert_eq!(m.blueprints[0].tags, vec!["horror", "puzzle"]);
+
+        let m = manifest("version: 1\nblueprints:\n  - { key: hub, dir: hub, difficulty: brutal }\n");
+        assert!(validate_manifest(&m).is_err());
+        let m = manifest("version: 1\nblueprints:\n  - { key: hub, dir: hub, tags: [\"two words\"] }\n");
+        assert!(validate_manifest(&m).is_err());
+    }
+
+    #[test]
+    fn t_manifest_dropped_items() {
+        let m = manifest("version: 1\nblueprints:\n  - { key: hub, dir: hub }\n");
+        assert_eq!(m.blueprints[0].dropped_items, DropPolicy::Persist);
+
+        let m = manifest(
+            "version: 1\nblueprints:\n  - { key: hub, dir: hub, dropped_items: { policy: return, after: 30 } }\n",
+        );
+        assert!(validate_manifest(&m).is_ok());
+        assert_eq!(m.blueprints[0].dropped_items, DropPolicy::Return { after: 30 });
+
+        let m = manifest(
+            "version: 1\nblueprints:\n  - { key: hub, dir: hub, dropped_items: { policy: despawn, after: 0 } }\n",
+        );
+        assert!(validate_manifest(&m).is_err());
+    }
+
+    fn room(text: &str) -> RoomYaml {
+        serde_yaml::from_str(text).expect("valid room yaml")
+    }
+
+    #[test]
+    fn t_diff_added_updated_removed_orphaned() {
+        let mut current = HashMap::new();
+        let mut hall = CurrentRoom {
+            header: RoomHeader {
+                title: "Old Hall".into(),
+                body: "A hall.".into(),
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        hall.objects.insert(
+            "lamp".into(),
+            CurrentObject {
+                short: "a lamp".into(),
+                description: "A lamp.".into(),
+                ..Default::default()
+            },
+        );
+        hall.objects.insert("crate".into(), CurrentObject::default());
+        hall.exits.insert(
+            "north".into(),
+            CurrentExit {
+                to: "vault".into(),
+                ..Default::default()
+            },
+        );
+        hall.exits.insert(
+            "up".into(),
+            CurrentExit {
+                to: "attic".into(),
+                ..Default::default()
+            },
+        );
+        current.insert("hall".to_string(), hall);
+        current.insert("attic".to_string(), CurrentRoom::default());
+
+        let rooms = vec![
+            room(
+                r#"
+version: 5
+id: hall
+name: Hall
+description: A hall.
+objects:
+  - id: lamp
+    short: a lamp
+    description: A lamp.
+exits:
+  - { dir: North, to: cellar }
+"#,
+            ),
+            room("version: 5\nid: cellar\nname: Cellar\ndescription: Dark.\n"),
+        ];
+
+        let diff = diff_blueprint(&current, &rooms).unwrap();
+        let kinds = |v: &Vec<DiffEntry>| v.iter().map(|e| (e.kind, e.key.clone())).collect::<Vec<_>>();
+
+        assert_eq!(
+            kinds(&diff.rooms),
+            vec![
+                (ChangeKind::Orphaned, "attic".to_string()),
+                (ChangeKind::Added, "cellar".to_string()),
+                (ChangeKind::Updated, "hall".to_string()),
+            ]
+        );
+        assert_eq!(
+            kinds(&diff.objects),
+            vec![(ChangeKind::Removed, "hall/crate".to_string())]
+        );
+        assert_eq!(
+            kinds(&diff.exits),
+            vec![
+                (ChangeKind::Updated, "hall:north".to_string()),
+                (ChangeKind::Orphaned, "hall:up".to_string()),
+            ]
+        );
+    }
+
+    #[test]
+    fn t_diff_renamed_room_and_object() {
+        let mut current = HashMap::new();
+        let mut hall = CurrentRoom {
+            header: RoomHeader {
+                title: "Hall".into(),
+                body: "A hall.".into(),
+                ..Default::default()
+            },
+            ..Default::default()
+        };
+        hall.objects.insert("lamp".into(), CurrentObject::default());
+        current.insert("hall".to_string(), hall);
+
+        let rooms = vec![room(
+            r#"
+version: 5
+id: great_hall
+renamed_from: [hall]
+name: Hall
+description: A hall.
+objects:
+  - id: lantern
+    renamed_from: [lamp]
+    short: a lantern
+    description: A lantern.
+"#,
+        )];
+
+        let diff = diff_blueprint(&current, &rooms).unwrap();
+        let kinds = |v: &Vec<DiffEntry>| v.iter().map(|e| (e.kind, e.key.clone())).collect::<Vec<_>>();
+
+        assert_eq!(
+            kinds(&diff.rooms),
+            vec![(ChangeKind::Updated, "great_hall".to_string())]
+        );
+        assert_eq!(
+            kinds(&diff.objects),
+            vec![(ChangeKind::Updated, "great_hall/lantern".to_string())]
+        );
+        assert!(diff.exits.is_empty());
+    }
+
+    #[test]
+    fn t_diff_compares_every_written_field() {
+        let hall = room(
+            r#"
+version: 5
+id: hall
+name: Hall
+description: A hall.
+state: { lights: false }
+hints:
+  - { id: look, text: Look around. }
+scripts:
+  on_enter: "port4k.say('hi')"
+exits:
+  - { dir: north, to: hall, locked: true }
+"#,
+        );
+        let mut current = HashMap::new();
+        current.insert(
+            "hall".to_string(),
+            CurrentRoom {
+                header: RoomHeader::from_yaml(&hall).unwrap(),
+                kv: hall.state.clone(),
+                scripts: HashMap::from([("on_enter".to_string(), Some("port4k.say('hi')".to_string()))]),
+                exits: HashMap::from([("north".to_string(), CurrentExit::from_yaml(&hall.exits[0]).unwrap())]),
+                ..Default::default()
+            },
+        );
+        assert!(diff_blueprint(&current, &[hall]).unwrap().is_empty());
+
+        let changed = room(
+            r#"
+version: 5
+id: hall
+name: Hall
+description: A hall.
+state: { lights: true }
+hints:
+  - { id: look, text: Look closer. }
+script_first: { take: [] }
+scripts:
+  on_enter: "port4k.say('hello')"
+exits:
+  - { dir: north, to: hall, visible_when_locked: false }
+"#,
+        );
+        let diff = diff_blueprint(&current, &[changed]).unwrap();
+        assert_eq!(
+            diff.rooms[0].detail.as_deref(),
+            Some("hints, script_first, state, scripts")
+        );
+        assert_eq!(diff.exits[0].detail.as_deref(), Some("locked, visible_when_locked"));
+    }
+
+    fn catalog(keys: &[&str]) -> HashMap<String, ItemCatalogYaml> {
+        keys.iter()
+            .map(|k| {
+                let item = ItemCatalogYaml {
+                    id: k.to_string(),
+                    name: k.to_string(),
+                    nouns: vec![k.to_string()],
+                    short: k.to_string(),
+                    description: k.to_string(),
+                    examine: None,
+                    stackable: false,
+                    durability: None,
+                    wear: None,
+                    repair_tool: None,
+                    weight: None,
+                    pages: Vec::new(),
+                    is_coin: false,
+                };
+                (k.to_string(), item)
+            })
+            .collect()
+    }
+
+    #[test]
+    fn t_collect_recipes() {
+        let items = catalog(&["stick", "rag", "torch", "knife"]);
+        let hall = room(
+            r#"
+version: 5
+id: hall
+name: Hall
+description: A hall.
+recipes:
+  - { id: torch, inputs: [stick, rag], output: torch, tool: knife }
+"#,
+        );
+        let recipes = collect_recipes(&[hall], &items).unwrap();
+        assert_eq!(recipes.len(), 1);
+        assert_eq!(recipes[0].tool.as_deref(), Some("knife"));
+
+        let bad = |recipes: &str| {
+            let r = room(&format!(
+                "version: 5\nid: hall\nname: Hall\ndescription: A hall.\nrecipes:\n{}",
+                recipes
+            ));
+            collect_recipes(&[r], &items).unwrap_err().to_string()
+        };
+        assert!(bad("  - { id: t, inputs: [stick], output: torch }").contains("exactly two"));
+        assert!(bad("  - { id: t, inputs: [stick, gem], output: torch }").contains("'gem'"));
+        assert!(
+            bad(
+                "  - { id: a, inputs: [stick, rag], output: torch }\n  - { id: b, inputs: [rag, stick], output: knife }"
+            )
+            .contains("same items")
+        );
+    }
+
+    #[test]
+    fn t_collect_ambience() {
+        let deck = room(
+            r#"
+version: 5
+id: deck
+name: Deck
+description: A deck.
+ambience:
+  - id: daylight
+    every: 600
+    rooms: [deck]
+    steps:
+      - { message: "Dawn.", set: { daylight: true } }
+      - { set: { daylight: false } }
+"#,
+        );
+        let events = collect_ambience(&[deck]).unwrap();
+        assert_eq!(events.len(), 1);
+        assert_eq!(events[0].steps.len(), 2);
+
+        let bad = |event: &str| {
+            let r = room(&format!(
+                "version: 5\nid: deck\nname: Deck\ndescription: A deck.\nambience:\n{}",
+                event
+            ));
+            collect_ambience(&[r]).unwrap_err().to_string()
+        };
+        assert!(bad("  - { id: e, every: 60, steps: [] }").contains("no steps"));
+        assert!(bad("  - { id: e, every: 0, steps: [{ message: x }] }").contains("interval"));
+        assert!(bad("  - { id: e, every: 60, chance: 1.5, steps: [{ message: x }] }").contains("chance"));
+        assert!(bad("  - { id: e, every: 60, rooms: [bridge], steps: [{ message: x }] }").contains("'bridge'"));
+    }
+
+    #[test]
+    fn t_validate_vehicles() {
+        let lobby = |board: &str| {
+            room(&format!(
+                "version: 5\nid: lobby\nname: Lobby\ndescription: A lobby.\nobjects:\n  - {{ id: doors, short: lift doors, description: Doors., board: {} }}\n",
+                board
+            ))
+        };
+        let lift = |stops: &str| {
+            room(&format!(
+                "version: 5\nid: lift\nname: Lift\ndescription: A lift.\nvehicle:\n  stops:\n{}",
+                stops
+            ))
+        };
+        let deck = || room("version: 5\nid: deck\nname: Deck\ndescription: A deck.\n");
+        let ok_stops = "    - { button: '1', room: lobby }\n    - { button: '2', room: deck }\n";
+
+        assert!(validate_vehicles(&[lobby("lift"), lift(ok_stops), deck()]).is_ok());
+
+        let err = |rooms: &[RoomYaml]| validate_vehicles(rooms).unwrap_err().to_string();
+        assert!(err(&[lobby("deck"), lift(ok_stops), deck()]).contains("not a vehicle"));
+        assert!(err(&[lobby("lift"), lift("    - { button: '1', room: deck }\n"), deck()]).contains("does not stop"));
+        assert!(
+            err(&[
+                lobby("lift"),
+                lift("    - { button: '1', room: lobby }\n    - { button: '1', room: deck }\n"),
+                deck()
+            ])
+            .contains("two stops")
+        );
+        assert!(err(&[lobby("lift"), lift("    - { button: '1', room: cellar }\n"), deck()]).contains("unknown room"));
+    }
+
+    #[test]
+    fn t_validate_widgets() {
+        let vault = |widget: &str| {
+            room(&format!(
+                "version: 5\nid: vault\nname: Vault\ndescription: A vault.\nexits:\n  - {{ dir: north, to: hall, locked: true }}\nobjects:\n  - {{ id: locker, short: locker, description: A locker. }}\n  - id: keypad\n    short: keypad\n    description: A keypad.\n    widget: {}\n",
+                widget
+            ))
+        };
+        let err = |widget: &str| validate_widgets(&vault(widget)).unwrap_err().to_string();
+
+        assert!(
+            validate_widgets(&vault(
+                "{ type: keypad, code: '1234', effects: { unlock: [north], set: { locker: { locked: false } } } }"
+            ))
+            .is_ok()
+        );
+        assert!(validate_widgets(&vault("{ type: lever_bank, levers: [a, b], solution: [b] }")).is_ok());
+        assert!(err("{ type: keypad, code: ' ' }").contains("empty code"));
+        assert!(err("{ type: keypad, code: '1', effects: { unlock: [south] } }").contains("unknown exit 'south'"));
+        assert!(err("{ type: keypad, code: '1', effects: { set: { safe: { open: true } } } }").contains("'safe'"));
+        assert!(err("{ type: lever_bank, levers: [a, A], solution: [] }").contains("same name"));
+        assert!(err("{ type: lever_bank, levers: [a, b], solution: [c] }").contains("unknown lever 'c'"));
+        assert!(err("{ type: menu, options: [] }").contains("labelled options"));
+    }
+
+    #[test]
+    fn t_validate_dialogues() {
+        let items = catalog(&["access_card"]);
+        let bar = |dialogue: &str| {
+            room(&format!(
+                "version: 5\nid: bar\nname: Bar\ndescription: A bar.\nexits:\n  - {{ dir: north, to: hall }}\nobjects:\n  - id: barkeep\n    short: barkeep\n    description: A barkeep.\n    dialogue: {}\n",
+                dialogue
+            ))
+        };
+        let err = |dialogue: &str| validate_dialogues(&[bar(dialogue)], &items).unwrap_err().to_string();
+
+        let ok = "{ start: hi, nodes: { hi: { text: Hi., options: [{ text: Card?, goto: card, when: { has_item: access_card } }] }, card: { text: Nice card., options: [{ text: Bye, effects: { unlock: [north] } }] } } }";
+        assert!(validate_dialogues(&[bar(ok)], &items).is_ok());
+        assert!(err("{ start: hello, nodes: { hi: { text: Hi. } } }").contains("unknown node 'hello'"));
+        assert!(
+            err("{ start: hi, nodes: { hi: { text: Hi., options: [{ text: Go, goto: gone }] } } }")
+                .contains("unknown node 'gone'")
+        );
+        assert!(
+            err("{ start: hi, nodes: { hi: { text: Hi., options: [{ text: Gift, give: sword }] } } }")
+                .contains("unknown item 'sword'")
+        );
+        assert!(
+            err("{ start: hi, nodes: { hi: { text: Hi., options: [{ text: Go, effects: { lock: [west] } }] } } }")
+                .contains("unknown exit 'west'")
+        );
+    }
+
+    #[test]
+    fn t_validate_item_durability() {
+        let mut items = catalog(&["spanner", "mask"]);
+        let mask = items.get_mut("mask").unwrap();
+        mask.durability = Some(10);
+        mask.repair_tool = Some("spanner".into());
+        assert!(validate_item_durability(&items).is_ok());
+
+        items.get_mut("mask").unwrap().repair_tool = Some("glue".into());
+        assert!(
+            validate_item_durability(&items)
+                .unwrap_err()
+                .to_string()
+                .contains("'glue'")
+        );
+
+        let mut items = catalog(&["coin"]);
+        let coin = items.get_mut("coin").unwrap();
+        coin.stackable = true;
+        coin.durability = Some(3);
+        assert!(
+            validate_item_durability(&items)
+                .unwrap_err()
+                .to_string()
+                .contains("stackable")
+        );
+
+        let mut items = catalog(&["rag"]);
+        items.get_mut("rag").unwrap().wear = Some(2);
+        assert!(
+            validate_item_durability(&items)
+                .unwrap_err()
+                .to_string()
+                .contains("no durability")
+        );
+
+        let mut items = catalog(&["anvil"]);
+        items.get_mut("anvil").unwrap().weight = Some(0.0);
+        assert!(
+            validate_item_durability(&items)
+                .unwrap_err()
+                .to_string()
+                .contains("weight")
+        );
+    }
+
+    #[test]
+    fn t_room_yaml_roundtrip() {
+        let r = room(
+            r#"
+version: 5
+id: hall
+name: Hall
+description: A hall.
+objects:
+  - id: lamp
+    nouns: [lamp]
+    short: a lamp
+    description: A lamp.
+    on_use: |
+      return true
+exits:
+  - { dir: north, to: cellar }
+"#,
+        );
+
+        let text = serde_yaml::to_string(&r).expect("serializes");
+        let back = room(&text);
+        assert_eq!(back.id, "hall");
+        assert_eq!(back.full_desc, "A hall.");
+        assert_eq!(back.objects[0]._on_use_compat.as_deref(), Some("return true\n"));
+        assert_eq!(back.exits[0].to, "cellar");
+        assert!(!text.contains("renamed_from"));
+    }
+
+    #[test]
+    fn t_validate_stashed_objects() {
+        let text = r#"
+version: 5
+id: bedroom
+name: Bedroom
+description: A bedroom.
+objects:
+  - id: bed
+    nouns: [bed]
+    short: a bed
+    description: A bed.
+  - id: letter
+    nouns: [letter]
+    short: a letter
+    description: A letter.
+    discovery: { mode: stashed, place: under, object: bed }
+"#;
+        assert!(validate_room_semantics(&room(text)).is_ok());
+
+        let err = validate_room_semantics(&room(&text.replace("object: bed", "object: wardrobe"))).unwrap_err();
+        assert!(err.to_string().contains("wardrobe"));
+        assert!(validate_room_semantics(&room(&text.replace("object: bed", "object: letter"))).is_err());
+    }
+
+    #[test]
+    fn t_validate_locks() {
+        let text = r#"
+version: 5
+id: vault
+name: Vault
+description: A vault.
+objects:
+  - id: safe
+    nouns: [safe]
+    short: a safe
+    description: A safe.
+    lock: { kind: keypad, digits: 4 }
+exits:
+  - { dir: north, to: hall, locked: true, lock: { kind: keycard } }
+"#;
+        assert!(validate_room_semantics(&room(text)).is_ok());
+
+        let err = validate_room_semantics(&room(&text.replace("kind: keypad", "kind: key"))).unwrap_err();
+        assert!(err.to_string().contains("object 'safe'"));
+        assert!(validate_room_semantics(&room(&text.replace("digits: 4", "digits: 0"))).is_err());
+        assert!(validate_room_semantics(&room(&text.replace("kind: keycard", "message: ''"))).is_err());
+    }
+
+    #[test]
+    fn t_validate_storage() {
+        let text = r#"
+version: 5
+id: depot
+name: Depot
+description: A depot.
+objects:
+  - id: locker
+    nouns: [locker]
+    short: a locker
+    description: A locker.
+    storage: { capacity: 4, persistent: true }
+"#;
+        let r = room(text);
+        assert!(validate_room_semantics(&r).is_ok());
+        assert_eq!(r.objects[0].storage.map(|st| st.capacity), Some(4));
+
+        let err = validate_room_semantics(&room(&text.replace("capacity: 4", "capacity: 0"))).unwrap_err();
+        assert!(err.to_string().contains("locker"));
+    }
+
+    #[test]
+    fn t_validate_script_first() {
+        let text = r#"
+version: 5
+id: shrine
+name: Shrine
+description: A shrine.
+script_first:
+  get: [idol]
+objects:
+  - id: idol
+    nouns: [idol]
+    short: an idol
+    description: A golden idol.
+scripts:
+  on_command: |
+    return function(ctx) return "pass" end
+"#;
+        assert!(validate_room_semantics(&room(text)).is_ok());
+
+        let err = validate_room_semantics(&room(&text.replace("get: [idol]", "get: [altar]"))).unwrap_err();
+        assert!(err.to_string().contains("altar"));
+        assert!(validate_room_semantics(&room(&text.replace("get: [idol]", "login: []"))).is_err());
+        let without_script = &text[..text.find("scripts:").unwrap()];
+        assert!(validate_room_semantics(&room(without_script)).is_err());
+    }
+
+    #[test]
+    fn t_validate_room_edits() {
+        assert!(validate_room_title("Hall").is_ok());
+        assert!(validate_room_title("  ").is_err());
+        assert!(validate_room_title(&"x".repeat(129)).is_err());
+
+        let objects: HashSet<&str> = ["door"].into_iter().collect();
+        assert!(validate_room_body("A hall with a {o:door}.", &objects).is_ok());
+        assert!(validate_room_body("A hall with a {o:window}.", &objects).is_err());
+        assert!(validate_room_body("", &objects).is_err());
+
+        assert!(validate_exit("North", "hall").is_ok());
+        assert!(validate_exit("sideways", "hall").is_err());
+        assert!(validate_exit("north", "the hall").is_err());
+
+        assert!(validate_object_text("door", "a door", "A heavy door.").is_ok());
+        assert!(validate_object_text("door", "a door", " ").is_err());
+    }
+
+    #[test]
+    fn t_manifest_rejects_unknown_version() {
+        let m = manifest("version: 2\nblueprints: []\n");
+        assert!(validate_manifest(&m).is_err());
+    }
+}
@@ -0,0 +1,353 @@
+//! Exports a blueprint from the database back to v5 room YAML files.
+//!
+//! The output is a directory with one `<room key>.yaml` file per room, which can be imported again
+//! with the regular importer. The blueprint-wide items catalog is written into the entry room (or
+//! the first room when no entry room is set), since the importer collects it from all rooms. The
+//! same goes for the recipes and the ambient events. Lua modules go into `lib/`, the lexicon into
+//! `lexicon.txt`.
+
+use super::{ExitYaml, FlagsYaml, HintYaml, ItemCatalogYaml, LootYaml, ObjectYaml, RecipeYaml, RoomYaml, ScriptYaml};
+use crate::db::error::DbError;
+use crate::error::{AppResult, DomainError, InfraError};
+use crate::lua::ScriptHook;
+use crate::models::ambience::AmbientEvent;
+use crate::models::dialogue::Dialogue;
+use crate::models::room::{Discovery, Hazard, Lock, RoomSounds, ScriptFirst, Storage, UseLimits};
+use crate::models::types::{BlueprintId, RoomId};
+use crate::models::vehicle::Vehicle;
+use crate::models::widget::Widget;
+use std::collections::HashMap;
+use std::{fs, path::Path};
+
+/// Exports all rooms of the blueprint `bp_key` into `out_dir`. Returns the number of rooms written.
+pub async fn export_blueprint(db: &crate::db::Db, bp_key: &str, out_dir: &Path) -> AppResult<usize> {
+    let (_, rooms) = load_rooms(db, bp_key).await?;
+
+    fs::create_dir_all(out_dir).map_err(InfraError::from)?;
+    for room in &rooms {
+        let path = out_dir.join(format!("{}.yaml", room.id));
+        let text = serde_yaml::to_string(room)?;
+        fs::write(&path, text).map_err(InfraError::from)?;
+        println!("  ✓ {}", path.display());
+    }
+
+    let modules = super::lua_lib::stored(db, bp_key).await?;
+    if !modules.is_empty() {
+        let lib = out_dir.join(super::lua_lib::LIB_DIR);
+        fs::create_dir_all(&lib).map_err(InfraError::from)?;
+        for m in modules {
+            let path = lib.join(format!("{}.lua", m.name));
+            fs::write(&path, m.source).map_err(InfraError::from)?;
+            println!("  ✓ {}", path.display());
+        }
+    }
+
+    let lexicon = super::prose::stored_lexicon(db, bp_key).await?;
+    if !lexicon.is_empty() {
+        let path = out_dir.join(super::prose::LEXICON_FILE);
+        fs::write(&path, lexicon.join("\n") + "\n").map_err(InfraError::from)?;
+        println!("  ✓ {}", path.display());
+    }
+
+    Ok(rooms.len())
+}
+
+/// Reads the rooms of the blueprint `bp_key` back into their YAML form, along with the key of its
+/// entry room
+pub(super) async fn load_rooms(db: &crate::db::Db, bp_key: &str) -> AppResult<(Option<String>, Vec<RoomYaml>)> {
+    let client = db.get_client().await?;
+
+    let Some(bp_row) = client
+        .query_opt(
+            r#"
+            SELECT b.id, r.key AS entry_key
+            FROM blueprints b
+            LEFT JOIN bp_rooms r ON r.id = b.entry_room_id
+            WHERE b.key = $1
+            "#,
+            &[&bp_key],
+        )
+        .await
+        .map_err(DbError::from)?
+    else {
+        return Err(DomainError::NotFound(format!("blueprint '{}'", bp_key)));
+    };
+    let bp_id: BlueprintId = bp_row.get("id");
+    let entry_key: Option<String> = bp_row.get("entry_key");
+
+    // Rooms, in a stable order
+    let rows = client
+        .query(
+            "SELECT id, key, title, short, body, hints, hazards, sounds, vehicle, script_first, api_version, notes FROM bp_rooms WHERE bp_id = $1 ORDER BY key",
+            &[&bp_id],
+        )
+        .await
+        .map_err(DbError::from)?;
+
+    let mut rooms: Vec<RoomYaml> = Vec::with_capacity(rows.len());
+    let mut room_idx: HashMap<RoomId, usize> = HashMap::new();
+    for row in rows {
+        let hints: Option<serde_json::Value> = row.get("hints");
+        let hints: Vec<HintYaml> = hints.and_then(|h| serde_json::from_value(h).ok()).unwrap_or_default();
+        let hazards: Vec<Hazard> = serde_json::from_value(row.get("hazards"))?;
+        let sounds: RoomSounds = serde_json::from_value(row.get("sounds"))?;
+        let script_first: ScriptFirst = serde_json::from_value(row.get("script_first"))?;
+        let vehicle: Option<Vehicle> = row
+            .get::<_, Option<serde_json::Value>>("vehicle")
+            .map(serde_json::from_value)
+            .transpose()?;
+        let short: Option<String> = row.get("short");
+
+        room_idx.insert(row.get("id"), rooms.len());
+        rooms.push(RoomYaml {
+            version: 5,
+            api_version: row.get("api_version"),
+            id: row.get("key"),
+            name: row.get("title"),
+            short: short.filter(|s| !s.is_empty()),
+            full_desc: row.get("body"),
+            state: HashMap::new(),
+            hints,
+            hazards,
+            sounds,
+            vehicle,
+            script_first,
+            objects: Vec::new(),
+            exits: Vec::new(),
+            scripts: ScriptYaml::default(),
+            items_catalog: Vec::new(),
+            recipes: Vec::new(),
+            ambience: Vec::new(),
+            renamed_from: Vec::new(),
+            notes: row.get("notes"),
+        });
+    }
+
+    // Room state
+    let rows = client
+        .query(
+            r#"
+            SELECT k.room_id, k.key, k.value
+            FROM bp_room_kv k
+            JOIN bp_rooms r ON r.id = k.room_id
+            WHERE r.bp_id = $1
+            "#,
+            &[&bp_id],
+        )
+        .await
+        .map_err(DbError::from)?;
+    for row in rows {
+        if let Some(&idx) = room_idx.get(&row.get::<_, RoomId>("room_id")) {
+            rooms[idx].state.insert(row.get("key"), row.get("value"));
+        }
+    }
+
+    // Scripts
+    let rows = client
+        .query(
+            r#"
+            SELECT s.room_id, s.hook, s.script
+            FROM bp_room_scripts s
+            JOIN bp_rooms r ON r.id = s.room_id
+            WHERE r.bp_id = $1 AND s.script IS NOT NULL
+            "#,
+            &[&bp_id],
+        )
+        .await
+        .map_err(DbError::from)?;
+    for row in rows {
+        if let Some(&idx) = room_idx.get(&row.get::<_, RoomId>("room_id")) {
+            let hook = ScriptHook::from_string(row.get("hook"))?;
+            rooms[idx].scripts.0.insert(hook, row.get("script"));
+        }
+    }
+
+    // Objects, with their nouns and state
+    let rows = client
+        .query(
+            r#"
+            SELECT o.id, o.room_id, o.name, o.short, o.description, o.examine, o.use_lua,
+                   o.flags, o.controls, o.loot, o.discovery, o.use_limits, o.lock_info, o.pages, o.read_lua, o.terminal_lua, o.receive_lua, o.board, o.widget, o.dialogue, o.storage, o.notes,
+                   COALESCE((SELECT array_agg(n.noun ORDER BY n.noun) FROM bp_object_nouns n WHERE n.obj_id = o.id), '{}') AS nouns,
+                   COALESCE((SELECT jsonb_object_agg(k.key, k.value) FROM bp_objects_kv k WHERE k.object_id = o.id), '{}') AS state
+            FROM bp_objects o
+            JOIN bp_rooms r ON r.id = o.room_id
+            WHERE r.bp_id = $1
+            ORDER BY o.room_id, o.position NULLS LAST, o.name
+            "#,
+            &[&bp_id],
+        )
+        .await
+        .map_err(DbError::from)?;
+    for row in rows {
+        let Some(&idx) = room_idx.get(&row.get::<_, RoomId>("room_id")) else {
+            continue;
+        };
+
+        let flags: Option<FlagsYaml> = serde_json::from_value(row.get("flags")).ok();
+        let controls: Vec<String> = serde_json::from_value(row.get("controls")).unwrap_or_default();
+        let loot: Option<LootYaml> = serde_json::from_value(row.get("loot")).ok().flatten();
+        let discovery: Discovery = serde_json::from_value(row.get("discovery")).unwrap_or_default();
+        let use_limits: UseLimits = serde_json::from_value(row.get("use_limits")).unwrap_or_default();
+        let lock: Option<Lock> = row
+            .get::<_, Option<serde_json::Value>>("lock_info")
+            .and_then(|l| serde_json::from_value(l).ok());
+        let storage: Option<Storage> = row
+            .get::<_, Option<serde_json::Value>>("storage")
+            .and_then(|st| serde_json::from_value(st).ok());
+        let state: HashMap<String, serde_json::Value> = serde_json::from_value(row.get("state")).unwrap_or_default();
+        let dialogue: Option<Dialogue> = row
+            .get::<_, Option<serde_json::Value>>("dialogue")
+            .and_then(|d| serde_json::from_value(d).ok());
+        let widget: Option<Widget> = row
+            .get::<_, Option<serde_json::Value>>("widget")
+            .and_then(|w| serde_json::from_value(w).ok());
+
+        rooms[idx].objects.push(ObjectYaml {
+            id: row.get("name"),
+            nouns: row.get("nouns"),
+            short: row.get("short"),
+            description: row.get("description"),
+            examine: row.get("examine"),
+            flags,
+            state,
+            controls,
+            discovery,
+            use_limits,
+            lock,
+            storage,
+            loot,
+            pages: row.get("pages"),
+            on_read: row.get("read_lua"),
+            on_terminal: row.get("terminal_lua"),
+            on_receive: row.get("receive_lua"),
+            board: row.get("board"),
+            widget,
+            dialogue,
+            on_use_: None,
+            _on_use_compat: row.get("use_lua"),
+            renamed_from: Vec::new(),
+            notes: row.get("notes"),
+        });
+    }
+
+    // Exits
+    let rows = client
+        .query(
+            r#"
+            SELECT e.from_room_id, e.dir, t.key AS to_key, e.locked, e.description, e.visible_when_locked, e.lock_info
+            FROM bp_exits e
+            JOIN bp_rooms f ON f.id = e.from_room_id
+            JOIN bp_rooms t ON t.id = e.to_room_id
+            WHERE f.bp_id = $1
+            ORDER BY e.from_room_id, e.dir
+            "#,
+            &[&bp_id],
+        )
+        .await
+        .map_err(DbError::from)?;
+    for row in rows {
+        if let Some(&idx) = room_idx.get(&row.get::<_, RoomId>("from_room_id")) {
+            rooms[idx].exits.push(ExitYaml {
+                dir: row.get("dir"),
+                to: row.get("to_key"),
+                description: row.get("description"),
+                locked: Some(row.get("locked")),
+                visible_when_locked: Some(row.get("visible_when_locked")),
+                lock: row
+                    .get::<_, Option<serde_json::Value>>("lock_info")
+                    .and_then(|l| serde_json::from_value(l).ok()),
+            });
+        }
+    }
+
+    // Blueprint-wide items catalog
+    let rows = client
+        .query(
+            r#"
+            SELECT c.item_key, c.name, c.short, c.description, c.examine, c.stackable,
+                   c.max_durability, c.wear_per_use, c.repair_tool, c.weight, c.pages, c.is_coin,
+                   COALESCE((SELECT array_agg(n.noun ORDER BY n.noun) FROM bp_item_nouns n WHERE n.item_id = c.id), '{}') AS nouns
+            FROM bp_items_catalog c
+            WHERE c.bp_id = $1
+            ORDER BY c.item_key
+            "#,
+            &[&bp_id],
+        )
+        .await
+        .map_err(DbError::from)?;
+    let catalog: Vec<ItemCatalogYaml> = rows
+        .iter()
+        .map(|row| ItemCatalogYaml {
+            id: row.get("item_key"),
+            name: row.get("name"),
+            nouns: row.get("nouns"),
+            short: row.get("short"),
+            description: row.get("description"),
+            examine: row.get("examine"),
+            stackable: row.get("stackable"),
+            durability: row.get("max_durability"),
+            wear: Some(row.get::<_, i32>("wear_per_use")).filter(|w| *w != 1),
+            repair_tool: row.get("repair_tool"),
+            weight: row.get("weight"),
+            pages: row.get("pages"),
+            is_coin: row.get("is_coin"),
+        })
+        .collect();
+
+    // Blueprint-wide recipes
+    let rows = client
+        .query(
+            r#"
+            SELECT recipe_key, inputs, output_key, output_qty, tool_key, min_level, message
+            FROM bp_recipes
+            WHERE bp_id = $1
+            ORDER BY recipe_key
+            "#,
+            &[&bp_id],
+        )
+        .await
+        .map_err(DbError::from)?;
+    let recipes: Vec<RecipeYaml> = rows
+        .iter()
+        .map(|row| {
+            let qty: i32 = row.get("output_qty");
+            RecipeYaml {
+                id: row.get("recipe_key"),
+                inputs: row.get("inputs"),
+                output: row.get("output_key"),
+                qty: (qty != 1).then_some(qty),
+                tool: row.get("tool_key"),
+                min_level: row.get("min_level"),
+                message: row.get("message"),
+            }
+        })
+        .collect();
+
+    // Blueprint-wide ambient events
+    let rows = client
+        .query(
+            "SELECT event FROM bp_ambience WHERE bp_id = $1 ORDER BY event_key",
+            &[&bp_id],
+        )
+        .await
+        .map_err(DbError::from)?;
+    let ambience = rows
+        .iter()
+        .map(|row| serde_json::from_value::<AmbientEvent>(row.get("event")))
+        .collect::<Result<Vec<_>, _>>()?;
+
+    if !catalog.is_empty() || !recipes.is_empty() || !ambience.is_empty() {
+        let idx = entry_key
+            .as_deref()
+            .and_then(|k| rooms.iter().position(|r| r.id == k))
+            .unwrap_or(0);
+        if let Some(room) = rooms.get_mut(idx) {
+            room.items_catalog = catalog;
+            room.recipes = recipes;
+            room.ambience = ambience;
+        }
+    }
+
+    Ok((entry_key, rooms))
+}
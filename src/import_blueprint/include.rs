@@ -0,0 +1,301 @@
+//! Includes and anchors of the v6 room format.
+//!
+//! A v6 room file can pull in fragments from sibling files:
+//!
+//! ```yaml
+//! version: 6
+//! id: vault
+//! include: [_items.yaml, _scripts.yaml]
+//! objects:
+//!   - id: door
+//!     on_use: !ref open_door
+//! ```
+//!
+//! A fragment is a file in the same directory with `fragment: 1` at the top. It is not a room
+//! itself; its keys are merged under those of the including file (lists are joined, the fragment's
+//! entries first, maps are merged, anything else is taken from the including file). Fragments can
+//! include other fragments. Named values under `anchors:` can be used anywhere in the including
+//! file, and in the files that include it, with `!ref <name>`.
+
+use crate::error::{AppResult, DomainError, InfraError};
+use serde_yaml::{Mapping, Value};
+use std::collections::HashMap;
+use std::fs;
+use std::path::PathBuf;
+
+/// Room format that knows about includes
+pub const INCLUDE_VERSION: u64 = 6;
+
+/// The YAML files of one blueprint directory, by file name
+pub struct SourceFiles {
+    paths: HashMap<String, PathBuf>,
+    parsed: HashMap<String, Value>,
+}
+
+impl SourceFiles {
+    pub fn new(files: &[PathBuf]) -> Self {
+        let paths = files
+            .iter()
+            .filter_map(|p| Some((p.file_name()?.to_str()?.to_string(), p.clone())))
+            .collect();
+        Self {
+            paths,
+            parsed: HashMap::new(),
+        }
+    }
+
+    fn load(&mut self, name: &str) -> AppResult<Value> {
+        let doc = match self.parsed.get(name) {
+            Some(doc) => doc.clone(),
+            None => {
+                let Some(path) = self.paths.get(name) else {
+                    return Err(invalid(format!(
+                        "included file '{}' is not in the blueprint directory",
+                        name
+                    )));
+                };
+                let text = fs::read_to_string(path).map_err(InfraError::from)?;
+                let doc: Value = serde_yaml::from_str(&text)?;
+                self.parsed.insert(name.to_string(), doc.clone());
+                doc
+            }
+        };
+        if !is_fragment(&doc) {
+            return Err(invalid(format!("'{}' is included but has no 'fragment: 1'", name)));
+        }
+        Ok(doc)
+    }
+
+    /// Resolves the includes and `!ref`s of the room file `name`
+    pub fn resolve(&mut self, name: &str, doc: Value) -> AppResult<Value> {
+        let version = doc.get("version").and_then(Value::as_u64);
+        if version != Some(INCLUDE_VERSION) {
+            if doc.get("include").is_some() || doc.get("anchors").is_some() {
+                return Err(invalid(format!(
+                    "'{}' uses include or anchors, which need version {}",
+                    name, INCLUDE_VERSION
+                )));
+            }
+            return Ok(doc);
+        }
+
+        let mut stack = vec![name.to_string()];
+        let (mut doc, anchors) = self.expand(doc, &mut stack)?;
+        replace_refs(&mut doc, &anchors).map_err(|e| invalid(format!("{}: {}", name, e)))?;
+        Ok(doc)
+    }
+
+    /// Merges the includes of `doc` into it; returns it with the anchors visible from it
+    fn expand(&mut self, doc: Value, stack: &mut Vec<String>) -> AppResult<(Value, Mapping)> {
+        let Value::Mapping(mut doc) = doc else {
+            return Ok((doc, Mapping::new()));
+        };
+        doc.remove("fragment");
+
+        let includes = match doc.remove("include") {
+            None => vec![],
+            Some(Value::String(s)) => vec![s],
+            Some(Value::Sequence(seq)) => seq
+                .into_iter()
+                .map(|v| match v {
+                    Value::String(s) => Ok(s),
+                    _ => Err(invalid(format!(
+                        "'include' of '{}' must list file names",
+                        current(stack)
+                    ))),
+                })
+                .collect::<AppResult<_>>()?,
+            Some(_) => {
+                return Err(invalid(format!(
+                    "'include' of '{}' must be a file name or a list of them",
+                    current(stack)
+                )));
+            }
+        };
+
+        let mut merged = Mapping::new();
+        let mut anchors = Mapping::new();
+        for inc in includes {
+            if stack.contains(&inc) {
+                stack.push(inc);
+                return Err(invalid(format!("include cycle: {}", stack.join(" -> "))));
+            }
+            let fragment = self.load(&inc)?;
+            stack.push(inc);
+            let (fragment, fragment_anchors) = self.expand(fragment, stack)?;
+            stack.pop();
+
+            add_anchors(&mut anchors, fragment_anchors, false).map_err(invalid)?;
+            if let Value::Mapping(fragment) = fragment {
+                merge(&mut merged, fragment);
+            }
+        }
+
+        match doc.remove("anchors") {
+            None => {}
+            Some(Value::Mapping(own)) => add_anchors(&mut anchors, own, true).map_err(invalid)?,
+            Some(_) => return Err(invalid(format!("'anchors' of '{}' must be a mapping", current(stack)))),
+        }
+
+        merge(&mut merged, doc);
+        Ok((Value::Mapping(merged), anchors))
+    }
+}
+
+/// Fragments are only ever included, never imported as rooms
+pub fn is_fragment(doc: &Value) -> bool {
+    doc.get("fragment").is_some()
+}
+
+fn current(stack: &[String]) -> &str {
+    stack.last().map(String::as_str).unwrap_or("?")
+}
+
+fn invalid(message: String) -> DomainError {
+    DomainError::Validation {
+        field: "include",
+        message,
+    }
+}
+
+/// Lays `over` on top of `base`: lists are joined, maps merged, the rest replaced
+fn merge(base: &mut Mapping, over: Mapping) {
+    for (k, v) in over {
+        match (base.get_mut(&k), v) {
+            (Some(Value::Sequence(b)), Value::Sequence(o)) => b.extend(o),
+            (Some(Value::Mapping(b)), Value::Mapping(o)) => merge(b, o),
+            (_, v) => {
+                base.insert(k, v);
+            }
+        }
+    }
+}
+
+/// A file's own anchors win over included ones; two included files must agree
+fn add_anchors(anchors: &mut Mapping, new: Mapping, own: bool) -> Result<(), String> {
+    for (k, v) in new {
+        if !own
+            && let Some(existing) = anchors.get(&k)
+            && *existing != v
+        {
+            return Err(format!(
+                "anchor '{}' is defined differently in two included files",
+                k.as_str().unwrap_or("?")
+            ));
+        }
+        anchors.insert(k, v);
+    }
+    Ok(())
+}
+
+fn replace_refs(value: &mut Value, anchors: &Mapping) -> Result<(), String> {
+    match value {
+        Value::Tagged(tagged) if tagged.tag == "ref" => {
+            let Some(name) = tagged.value.as_str() else {
+                return Err("!ref needs an anchor name".into());
+            };
+            let Some(target) = anchors.get(name) else {
+                return Err(format!("unknown anchor '{}'", name));
+            };
+            *value = target.clone();
+        }
+        Value::Tagged(tagged) => replace_refs(&mut tagged.value, anchors)?,
+        Value::Sequence(seq) => {
+            for v in seq {
+                replace_refs(v, anchors)?;
+            }
+        }
+        Value::Mapping(map) => {
+            for (_, v) in map.iter_mut() {
+                replace_refs(v, anchors)?;
+            }
+        }
+        _ => {}
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn sources(files: &[(&str, &str)]) -> SourceFiles {
+        let mut s = SourceFiles::new(&[]);
+        for (name, text) in files {
+            s.parsed.insert(name.to_string(), serde_yaml::from_str(text).unwrap());
+            s.paths.insert(name.to_string(), PathBuf::from(name));
+        }
+        s
+    }
+
+    fn yaml(text: &str) -> Value {
+        serde_yaml::from_str(text).unwrap()
+    }
+
+    #[test]
+    fn t_include_merges_fragments() {
+        let mut s = sources(&[
+            (
+                "_items.yaml",
+                "fragment: 1\ninclude: _scripts.yaml\nitems_catalog:\n  - { id: key, name: Key }\n",
+            ),
+            (
+                "_scripts.yaml",
+                "fragment: 1\nanchors:\n  open_door: \"return function(ctx) end\"\n",
+            ),
+        ]);
+        let doc = s
+            .resolve(
+                "vault.yaml",
+                yaml(
+                    r#"
+version: 6
+id: vault
+include: [_items.yaml]
+items_catalog:
+  - { id: coin, name: Coin }
+objects:
+  - { id: door, on_use: !ref open_door }
+"#,
+                ),
+            )
+            .unwrap();
+
+        assert_eq!(doc["items_catalog"][0]["id"], yaml("key"));
+        assert_eq!(doc["items_catalog"][1]["id"], yaml("coin"));
+        assert_eq!(doc["objects"][0]["on_use"], yaml("return function(ctx) end"));
+        assert!(doc.get("include").is_none() && doc.get("anchors").is_none());
+        assert!(doc.get("fragment").is_none());
+    }
+
+    #[test]
+    fn t_include_errors() {
+        let mut s = sources(&[
+            ("_a.yaml", "fragment: 1\ninclude: _b.yaml\n"),
+            ("_b.yaml", "fragment: 1\ninclude: _a.yaml\n"),
+            ("hall.yaml", "version: 6\nid: hall\n"),
+            ("_x.yaml", "fragment: 1\nanchors: { s: one }\n"),
+            ("_y.yaml", "fragment: 1\nanchors: { s: two }\n"),
+        ]);
+        let err = |s: &mut SourceFiles, doc: &str| s.resolve("room.yaml", yaml(doc)).unwrap_err().to_string();
+
+        assert!(
+            err(&mut s, "{ version: 6, include: _a.yaml }")
+                .contains("include cycle: room.yaml -> _a.yaml -> _b.yaml -> _a.yaml")
+        );
+        assert!(err(&mut s, "{ version: 6, include: _nope.yaml }").contains("not in the blueprint directory"));
+        assert!(err(&mut s, "{ version: 6, include: hall.yaml }").contains("no 'fragment: 1'"));
+        assert!(err(&mut s, "{ version: 5, include: _x.yaml }").contains("need version 6"));
+        assert!(err(&mut s, "{ version: 6, x: !ref nope }").contains("unknown anchor 'nope'"));
+        assert!(err(&mut s, "{ version: 6, include: [_x.yaml, _y.yaml] }").contains("defined differently"));
+
+        // A file's own anchor overrides an included one
+        let doc = s
+            .resolve(
+                "room.yaml",
+                yaml("{ version: 6, include: _x.yaml, anchors: { s: mine }, v: !ref s }"),
+            )
+            .unwrap();
+        assert_eq!(doc["v"], yaml("mine"));
+    }
+}
@@ -0,0 +1,93 @@
+//! The `lib/` folder of a blueprint: Lua modules its scripts load with `require`.
+
+use super::compile_lua_chunk;
+use crate::db::error::DbError;
+use crate::error::{AppResult, DomainError, InfraError};
+use crate::lua::modules::is_valid_name;
+use crate::models::types::BlueprintId;
+use crate::util::{list_files_guarded, resolve_content_subdir};
+use mlua::Lua;
+use std::fs;
+use std::path::Path;
+use tokio_postgres::Transaction;
+
+/// Directory in a blueprint directory with its Lua modules
+pub const LIB_DIR: &str = "lib";
+
+#[derive(Debug, Clone)]
+pub struct LuaModule {
+    pub name: String,
+    pub source: String,
+}
+
+/// Reads and compiles the modules in `<dir>/lib`; no directory means no modules
+pub fn load(dir: &Path) -> AppResult<Vec<LuaModule>> {
+    if !dir.join(LIB_DIR).is_dir() {
+        return Ok(Vec::new());
+    }
+
+    let lib = resolve_content_subdir(dir, LIB_DIR)?;
+    let lua = Lua::new();
+    let mut modules = Vec::new();
+    for path in list_files_guarded(&lib, &["lua"])? {
+        let name = path.file_stem().and_then(|s| s.to_str()).unwrap_or_default();
+        if !is_valid_name(name) {
+            return Err(DomainError::Validation {
+                field: "lib",
+                message: format!(
+                    "module file '{}' must be named with letters, digits and '_' only",
+                    path.display()
+                ),
+            });
+        }
+
+        let source = fs::read_to_string(&path).map_err(InfraError::from)?;
+        compile_lua_chunk(&lua, &format!("lib/{}.lua", name), &source)?;
+        modules.push(LuaModule {
+            name: name.to_string(),
+            source,
+        });
+    }
+    Ok(modules)
+}
+
+/// Replaces the modules of the blueprint with `modules`
+pub async fn replace(tx: &Transaction<'_>, bp_id: BlueprintId, modules: &[LuaModule]) -> AppResult<()> {
+    tx.execute("DELETE FROM bp_lua_modules WHERE bp_id = $1", &[&bp_id])
+        .await
+        .map_err(DbError::from)?;
+    for m in modules {
+        tx.execute(
+            "INSERT INTO bp_lua_modules (bp_id, name, source) VALUES ($1, $2, $3)",
+            &[&bp_id, &m.name, &m.source],
+        )
+        .await
+        .map_err(DbError::from)?;
+    }
+    Ok(())
+}
+
+/// The stored modules of the blueprint `bp_key`, by name
+pub async fn stored(db: &crate::db::Db, bp_key: &str) -> AppResult<Vec<LuaModule>> {
+    let client = db.get_client().await?;
+    let rows = client
+        .query(
+            r#"
+            SELECT m.name, m.source
+            FROM bp_lua_modules m
+            JOIN blueprints b ON b.id = m.bp_id
+            WHERE b.key = $1
+            ORDER BY m.name
+            "#,
+            &[&bp_key],
+        )
+        .await
+        .map_err(DbError::from)?;
+    Ok(rows
+        .iter()
+        .map(|row| LuaModule {
+            name: row.get("name"),
+            source: row.get("source"),
+        })
+        .collect())
+}
@@ -0,0 +1,222 @@
+//! Reconciles live realm state with a new blueprint version.
+//!
+//! Realm and user overlays (KV, exits, item instances, player locations) point at blueprint rooms
+//! and objects by id. Re-importing a blueprint keeps those ids stable for rooms and objects whose
+//! key did not change. Renamed rooms and objects must declare their old key(s) in `renamed_from`,
+//! so their rows can be renamed in place. Anything that is no longer part of the blueprint, but
+//! still has live state attached, is reported as orphaned.
+
+use super::{ObjectYaml, RoomYaml};
+use crate::db::error::DbError;
+use crate::error::AppResult;
+use crate::models::types::{BlueprintId, RoomId};
+use serde::Serialize;
+use tokio_postgres::Transaction;
+
+#[derive(Debug, Default, Serialize)]
+pub struct MigrationReport {
+    /// (old key, new key)
+    pub renamed_rooms: Vec<(String, String)>,
+    /// (room key, old object key, new object key)
+    pub renamed_objects: Vec<(String, String, String)>,
+    pub orphaned: Vec<OrphanedState>,
+}
+
+/// Live state still attached to a room or object that is no longer part of the blueprint
+#[derive(Debug, Serialize)]
+pub struct OrphanedState {
+    /// Room key, or "room/object" for objects
+    pub key: String,
+    /// Number of realm and user KV / exit overlay rows
+    pub overlays: i64,
+    /// Number of item instances located in the room or object
+    pub items: i64,
+    /// Number of players currently located in the room
+    pub players: i64,
+}
+
+impl MigrationReport {
+    pub fn print(&self) {
+        for (old, new) in &self.renamed_rooms {
+            println!("  ↪ room '{}' renamed to '{}'", old, new);
+        }
+        for (room, old, new) in &self.renamed_objects {
+            println!("  ↪ object '{}/{}' renamed to '{}'", room, old, new);
+        }
+        for o in &self.orphaned {
+            println!(
+                "  ⚠ orphaned '{}': {} overlay row(s), {} item(s), {} player(s)",
+                o.key, o.overlays, o.items, o.players
+            );
+        }
+        if self.renamed_rooms.is_empty() && self.renamed_objects.is_empty() && self.orphaned.is_empty() {
+            println!("  ✓ No renames, no orphaned state");
+        }
+    }
+}
+
+/// Renames existing rooms in place when the imported room lists their old key in `renamed_from`.
+/// This keeps the room id, and thus all realm/user state attached to it.
+pub async fn apply_room_renames(
+    tx: &Transaction<'_>,
+    bp_id: BlueprintId,
+    rooms: &[RoomYaml],
+    report: &mut MigrationReport,
+) -> AppResult<()> {
+    for r in rooms {
+        for old_key in &r.renamed_from {
+            let n = tx
+                .execute(
+                    r#"
+                    UPDATE bp_rooms SET key = $3
+                    WHERE bp_id = $1 AND key = $2
+                      AND NOT EXISTS (SELECT 1 FROM bp_rooms WHERE bp_id = $1 AND key = $3)
+                    "#,
+                    &[&bp_id, old_key, &r.id],
+                )
+                .await
+                .map_err(DbError::from)?;
+            if n > 0 {
+                report.renamed_rooms.push((old_key.clone(), r.id.clone()));
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Renames existing objects in place when the imported object lists their old key in `renamed_from`.
+pub async fn apply_object_renames(
+    tx: &Transaction<'_>,
+    room_id: RoomId,
+    room_key: &str,
+    objects: &[ObjectYaml],
+    report: &mut MigrationReport,
+) -> AppResult<()> {
+    for o in objects {
+        for old_key in &o.renamed_from {
+            let n = tx
+                .execute(
+                    r#"
+                    UPDATE bp_objects SET name = $3
+                    WHERE room_id = $1 AND name = $2
+                      AND NOT EXISTS (SELECT 1 FROM bp_objects WHERE room_id = $1 AND name = $3)
+                    "#,
+                    &[&room_id, old_key, &o.id],
+                )
+                .await
+                .map_err(DbError::from)?;
+            if n > 0 {
+                report
+                    .renamed_objects
+                    .push((room_key.to_string(), old_key.clone(), o.id.clone()));
+            }
+        }
+    }
+    Ok(())
+}
+
+/// Reports rooms that exist in the blueprint but are not part of this import, and still have live
+/// state attached. These rooms are kept, so running realms keep working.
+pub async fn collect_orphaned_rooms(
+    tx: &Transaction<'_>,
+    bp_id: BlueprintId,
+    rooms: &[RoomYaml],
+    report: &mut MigrationReport,
+) -> AppResult<()> {
+    let keys: Vec<&str> = rooms.iter().map(|r| r.id.as_str()).collect();
+
+    let rows = tx
+        .query(
+            r#"
+            SELECT r.key,
+                   (SELECT COUNT(*) FROM realm_room_kv k WHERE k.room_id = r.id)
+                 + (SELECT COUNT(*) FROM user_room_kv k WHERE k.room_id = r.id)
+                 + (SELECT COUNT(*) FROM realm_exits e WHERE e.room_id = r.id)
+                 + (SELECT COUNT(*) FROM user_exits e WHERE e.room_id = r.id) AS overlays,
+                   (SELECT COUNT(*) FROM item_instances i WHERE i.room_id = r.id) AS items,
+                   (SELECT COUNT(*) FROM accounts a WHERE a.current_room_id = r.id) AS players
+            FROM bp_rooms r
+            WHERE r.bp_id = $1 AND NOT (r.key = ANY($2))
+            "#,
+            &[&bp_id, &keys],
+        )
+        .await
+        .map_err(DbError::from)?;
+
+    for row in rows {
+        push_orphan(report, row.get("key"), &row);
+    }
+    Ok(())
+}
+
+/// Reports objects that will be removed from the room by this import, and still have live state
+/// attached. Their KV overlays are removed with the object; item instances are left in place.
+pub async fn collect_orphaned_objects(
+    tx: &Transaction<'_>,
+    room_id: RoomId,
+    room_key: &str,
+    objects: &[ObjectYaml],
+    report: &mut MigrationReport,
+) -> AppResult<()> {
+    let names: Vec<&str> = objects.iter().map(|o| o.id.as_str()).collect();
+
+    let rows = tx
+        .query(
+            r#"
+            SELECT o.name,
+                   (SELECT COUNT(*) FROM realm_object_kv k WHERE k.object_id = o.id)
+                 + (SELECT COUNT(*) FROM user_object_kv k WHERE k.object_id = o.id) AS overlays,
+                   (SELECT COUNT(*) FROM item_instances i WHERE i.object_id = o.id) AS items,
+                   0::bigint AS players
+            FROM bp_objects o
+            WHERE o.room_id = $1 AND NOT (o.name = ANY($2))
+            "#,
+            &[&room_id, &names],
+        )
+        .await
+        .map_err(DbError::from)?;
+
+    for row in rows {
+        let name: String = row.get("name");
+        push_orphan(report, format!("{}/{}", room_key, name), &row);
+    }
+    Ok(())
+}
+
+fn push_orphan(report: &mut MigrationReport, key: String, row: &tokio_postgres::Row) {
+    let orphan = OrphanedState {
+        key,
+        overlays: row.get("overlays"),
+        items: row.get("items"),
+        players: row.get("players"),
+    };
+    // Nothing live is attached, so nothing can break
+    if orphan.overlays + orphan.items + orphan.players > 0 {
+        report.orphaned.push(orphan);
+    }
+}
+
+/// Bumps the blueprint version and records the migration report for it. Returns the new version.
+pub async fn record_version(tx: &Transaction<'_>, bp_id: BlueprintId, report: &MigrationReport) -> AppResult<i32> {
+    let row = tx
+        .query_one(
+            "UPDATE blueprints SET version = version + 1 WHERE id = $1 RETURNING version",
+            &[&bp_id],
+        )
+        .await
+        .map_err(DbError::from)?;
+    let version: i32 = row.get(0);
+
+    let report_json = serde_json::to_value(report)?;
+    tx.execute(
+        r#"
+        INSERT INTO bp_versions (bp_id, version, report)
+        VALUES ($1, $2, $3::jsonb)
+        "#,
+        &[&bp_id, &version, &report_json],
+    )
+    .await
+    .map_err(DbError::from)?;
+
+    Ok(version)
+}
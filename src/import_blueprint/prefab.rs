@@ -0,0 +1,374 @@
+//! Prefabs: room and object templates shared by all blueprints under a content base.
+//!
+//! A prefab lives in `<content base>/prefabs/*.yaml`:
+//!
+//! ```yaml
+//! prefab: 1
+//! id: locked_door
+//! kind: object            # or room
+//! params:
+//!   name: { default: door }
+//!   key_item: {}          # no default, so it must be given
+//! template:
+//!   id: "{{name}}"
+//!   short: "A heavy {{name}}"
+//!   ...
+//! ```
+//!
+//! Room files use them as a whole (`prefab: <id>` at the top) or per object (an entry in
+//! `objects:` with `prefab: <id>`), passing parameters under `with:`. Every other key next to
+//! `prefab` overrides the same key of the expanded template. `{{param}}` is replaced in all
+//! strings; a string that is nothing but `{{param}}` takes the parameter's value with its type.
+
+use crate::error::{AppResult, DomainError, InfraError};
+use crate::util::{list_yaml_files_guarded, resolve_content_subdir};
+use serde::Deserialize;
+use serde_yaml::{Mapping, Value};
+use std::collections::{BTreeMap, HashMap};
+use std::fs;
+use std::path::Path;
+
+/// Directory under the content base with the prefab files
+pub const PREFAB_DIR: &str = "prefabs";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Deserialize)]
+#[serde(rename_all = "lowercase")]
+enum PrefabKind {
+    Room,
+    Object,
+}
+
+impl PrefabKind {
+    fn as_str(&self) -> &'static str {
+        match self {
+            PrefabKind::Room => "room",
+            PrefabKind::Object => "object",
+        }
+    }
+}
+
+#[derive(Debug, Default, Deserialize)]
+struct ParamYaml {
+    #[serde(default)]
+    default: Option<Value>,
+}
+
+#[derive(Debug, Deserialize)]
+struct PrefabYaml {
+    prefab: u8, // must be 1
+    id: String,
+    kind: PrefabKind,
+    #[serde(default)]
+    params: BTreeMap<String, ParamYaml>,
+    template: Value,
+}
+
+/// All prefabs available to an import, by id
+#[derive(Debug, Default)]
+pub struct PrefabLibrary {
+    prefabs: HashMap<String, PrefabYaml>,
+}
+
+impl PrefabLibrary {
+    /// Reads the prefab directory of `content_base`; no directory means no prefabs
+    pub fn load(content_base: &Path) -> AppResult<Self> {
+        if !content_base.join(PREFAB_DIR).is_dir() {
+            return Ok(Self::default());
+        }
+
+        let dir = resolve_content_subdir(content_base, PREFAB_DIR)?;
+        let mut library = Self::default();
+        for path in list_yaml_files_guarded(&dir)? {
+            let text = fs::read_to_string(&path).map_err(InfraError::from)?;
+            library.add(serde_yaml::from_str(&text)?)?;
+        }
+        Ok(library)
+    }
+
+    fn add(&mut self, prefab: PrefabYaml) -> AppResult<()> {
+        if prefab.prefab != 1 {
+            return Err(invalid(format!(
+                "prefab '{}' has unsupported version {}, expected 1",
+                prefab.id, prefab.prefab
+            )));
+        }
+        if !matches!(prefab.template, Value::Mapping(_)) {
+            return Err(invalid(format!("template of prefab '{}' must be a mapping", prefab.id)));
+        }
+        if self.prefabs.contains_key(&prefab.id) {
+            return Err(invalid(format!("prefab '{}' is defined twice", prefab.id)));
+        }
+        self.prefabs.insert(prefab.id.clone(), prefab);
+        Ok(())
+    }
+
+    pub fn len(&self) -> usize {
+        self.prefabs.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.prefabs.is_empty()
+    }
+
+    /// Expands the room prefab and the object prefabs a parsed room file refers to
+    pub fn expand_room(&self, doc: Value) -> AppResult<Value> {
+        let mut room = self.expand(doc, PrefabKind::Room)?;
+
+        if let Some(Value::Sequence(objects)) = room.get_mut("objects") {
+            for obj in objects.iter_mut() {
+                *obj = self.expand(std::mem::take(obj), PrefabKind::Object)?;
+            }
+        }
+        Ok(room)
+    }
+
+    /// Replaces `doc` by the expanded prefab it refers to, with its own keys laid over it. Leaves
+    /// documents without a `prefab` key alone.
+    fn expand(&self, doc: Value, kind: PrefabKind) -> AppResult<Value> {
+        let Value::Mapping(mut doc) = doc else {
+            return Ok(doc);
+        };
+        let Some(id) = doc.remove("prefab") else {
+            return Ok(Value::Mapping(doc));
+        };
+        let Value::String(id) = id else {
+            return Err(invalid("'prefab' must name a prefab".into()));
+        };
+        let args = match doc.remove("with") {
+            None => Mapping::new(),
+            Some(Value::Mapping(args)) => args,
+            Some(_) => return Err(invalid(format!("'with' of prefab '{}' must be a mapping", id))),
+        };
+
+        let Some(prefab) = self.prefabs.get(&id) else {
+            return Err(invalid(format!("unknown prefab '{}'", id)));
+        };
+        if prefab.kind != kind {
+            return Err(invalid(format!(
+                "prefab '{}' is for {}s, not {}s",
+                id,
+                prefab.kind.as_str(),
+                kind.as_str()
+            )));
+        }
+
+        let params = bind_params(prefab, &args)?;
+        let Value::Mapping(mut expanded) =
+            substitute(&prefab.template, &params).map_err(|e| invalid(format!("prefab '{}': {}", id, e)))?
+        else {
+            unreachable!("templates are checked to be mappings");
+        };
+        if expanded.contains_key("prefab") {
+            return Err(invalid(format!(
+                "prefab '{}' refers to another prefab, they cannot nest",
+                id
+            )));
+        }
+
+        for (k, v) in doc {
+            expanded.insert(k, v);
+        }
+        Ok(Value::Mapping(expanded))
+    }
+}
+
+fn invalid(message: String) -> DomainError {
+    DomainError::Validation {
+        field: "prefab",
+        message,
+    }
+}
+
+/// Parameter values for one use of a prefab: the given ones, defaults for the rest
+fn bind_params(prefab: &PrefabYaml, args: &Mapping) -> AppResult<HashMap<String, Value>> {
+    let mut bound = HashMap::new();
+    for (k, v) in args {
+        let Some(name) = k.as_str() else {
+            return Err(invalid(format!(
+                "parameter names of prefab '{}' must be strings",
+                prefab.id
+            )));
+        };
+        if !prefab.params.contains_key(name) {
+            return Err(invalid(format!("prefab '{}' has no parameter '{}'", prefab.id, name)));
+        }
+        bound.insert(name.to_string(), v.clone());
+    }
+
+    for (name, param) in &prefab.params {
+        if bound.contains_key(name) {
+            continue;
+        }
+        match &param.default {
+            Some(default) => {
+                bound.insert(name.clone(), default.clone());
+            }
+            None => {
+                return Err(invalid(format!("prefab '{}' needs parameter '{}'", prefab.id, name)));
+            }
+        }
+    }
+    Ok(bound)
+}
+
+fn substitute(value: &Value, params: &HashMap<String, Value>) -> Result<Value, String> {
+    Ok(match value {
+        Value::String(s) => substitute_str(s, params)?,
+        Value::Sequence(seq) => Value::Sequence(seq.iter().map(|v| substitute(v, params)).collect::<Result<_, _>>()?),
+        Value::Mapping(map) => {
+            let mut out = Mapping::new();
+            for (k, v) in map {
+                out.insert(substitute(k, params)?, substitute(v, params)?);
+            }
+            Value::Mapping(out)
+        }
+        other => other.clone(),
+    })
+}
+
+fn substitute_str(s: &str, params: &HashMap<String, Value>) -> Result<Value, String> {
+    // A lone placeholder keeps the type of the value
+    if let Some(name) = s.strip_prefix("{{").and_then(|r| r.strip_suffix("}}"))
+        && !name.contains("{{")
+    {
+        let name = name.trim();
+        return params
+            .get(name)
+            .cloned()
+            .ok_or_else(|| format!("unknown parameter '{}'", name));
+    }
+
+    let mut out = String::with_capacity(s.len());
+    let mut rest = s;
+    while let Some(start) = rest.find("{{") {
+        out.push_str(&rest[..start]);
+        let after = &rest[start + 2..];
+        let end = after
+            .find("}}")
+            .ok_or_else(|| format!("unclosed '{{{{' in \"{}\"", s))?;
+        let name = after[..end].trim();
+        let value = params
+            .get(name)
+            .ok_or_else(|| format!("unknown parameter '{}'", name))?;
+        match value {
+            Value::String(v) => out.push_str(v),
+            Value::Number(n) => out.push_str(&n.to_string()),
+            Value::Bool(b) => out.push_str(&b.to_string()),
+            _ => {
+                return Err(format!(
+                    "parameter '{}' is not text and cannot be part of a string",
+                    name
+                ));
+            }
+        }
+        rest = &after[end + 2..];
+    }
+    out.push_str(rest);
+    Ok(Value::String(out))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn library(prefabs: &[&str]) -> PrefabLibrary {
+        let mut lib = PrefabLibrary::default();
+        for text in prefabs {
+            lib.add(serde_yaml::from_str(text).expect("valid prefab yaml"))
+                .expect("valid prefab");
+        }
+        lib
+    }
+
+    const DOOR: &str = r#"
+prefab: 1
+id: locked_door
+kind: object
+params:
+  name: { default: door }
+  key_item: {}
+  locked: { default: true }
+template:
+  id: "{{name}}"
+  short: "A heavy {{ name }}, opened with {{key_item}}"
+  description: A door.
+  state:
+    locked: "{{locked}}"
+"#;
+
+    const CORRIDOR: &str = r#"
+prefab: 1
+id: corridor
+kind: room
+params:
+  name: {}
+template:
+  version: 5
+  id: placeholder
+  name: "{{name}}"
+  description: "A long {{name}}."
+  objects:
+    - { prefab: locked_door, with: { key_item: crowbar } }
+"#;
+
+    fn yaml(text: &str) -> Value {
+        serde_yaml::from_str(text).expect("valid yaml")
+    }
+
+    #[test]
+    fn t_expand_object_prefab() {
+        let lib = library(&[DOOR]);
+        let room = lib
+            .expand_room(yaml(
+                r#"
+version: 5
+id: vault
+name: Vault
+description: Cold.
+objects:
+  - { prefab: locked_door, with: { name: vault_door, key_item: keycard, locked: false }, examine: Scratched. }
+  - { id: desk, short: A desk, description: Wooden. }
+"#,
+            ))
+            .unwrap();
+
+        let door = &room["objects"][0];
+        assert_eq!(door["id"], yaml("vault_door"));
+        assert_eq!(door["short"], yaml("A heavy vault_door, opened with keycard"));
+        assert_eq!(door["state"]["locked"], yaml("false"));
+        assert_eq!(door["examine"], yaml("Scratched."));
+        assert!(door.get("prefab").is_none() && door.get("with").is_none());
+        assert_eq!(room["objects"][1]["id"], yaml("desk"));
+    }
+
+    #[test]
+    fn t_expand_room_prefab() {
+        let lib = library(&[DOOR, CORRIDOR]);
+        let room = lib
+            .expand_room(yaml("{ prefab: corridor, with: { name: hallway }, id: hall_1 }"))
+            .unwrap();
+
+        assert_eq!(room["id"], yaml("hall_1"));
+        assert_eq!(room["description"], yaml("A long hallway."));
+        // Object prefabs in a room prefab are expanded too, with their defaults
+        assert_eq!(room["objects"][0]["id"], yaml("door"));
+    }
+
+    #[test]
+    fn t_prefab_errors() {
+        let lib = library(&[DOOR, CORRIDOR]);
+        let err = |doc: &str| lib.expand_room(yaml(doc)).unwrap_err().to_string();
+
+        assert!(err("{ prefab: nope }").contains("unknown prefab"));
+        assert!(err("{ prefab: locked_door }").contains("is for objects, not rooms"));
+        assert!(err("{ prefab: corridor }").contains("needs parameter 'name'"));
+        assert!(err("{ prefab: corridor, with: { name: x, colour: red } }").contains("no parameter 'colour'"));
+        assert!(
+            err("{ id: a, objects: [ { prefab: locked_door, with: { key_item: [1, 2], name: \"{{x}}\" } } ] }")
+                .contains("not text")
+        );
+
+        let mut dup = library(&[DOOR]);
+        assert!(dup.add(serde_yaml::from_str(DOOR).unwrap()).is_err());
+    }
+}
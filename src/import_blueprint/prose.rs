@@ -0,0 +1,257 @@
+//! The prose pass of `@bp lint <bp> prose`: the texts players read are spell-checked against the
+//! dictionary of the server config plus the lexicon of the blueprint, and flagged for very long
+//! paragraphs and trailing whitespace. Everything it finds is a warning, never an import error.
+//!
+//! The lexicon is `lexicon.txt` in the blueprint directory, one word per line, for names and
+//! jargon no dictionary knows. Room keys, object keys, nouns and items count as known words too.
+
+use super::RoomYaml;
+use crate::db::error::DbError;
+use crate::error::{AppResult, InfraError};
+use crate::models::types::BlueprintId;
+use once_cell::sync::Lazy;
+use regex::Regex;
+use std::collections::HashSet;
+use std::fs;
+use std::path::Path;
+use tokio_postgres::Transaction;
+
+/// File in a blueprint directory with its own words
+pub const LEXICON_FILE: &str = "lexicon.txt";
+
+/// Words in a paragraph before it is flagged as too long to read comfortably
+const MAX_PARAGRAPH_WORDS: usize = 150;
+
+/// Markup like {o:key} or {v:name}, which is not prose
+static MARKUP_RE: Lazy<Regex> = Lazy::new(|| Regex::new(r"\{[^}]*}").unwrap());
+
+/// Known words, lowercased
+#[derive(Debug, Default)]
+pub struct Dictionary {
+    words: HashSet<String>,
+}
+
+impl Dictionary {
+    /// Reads a word list: one word per line, empty lines and lines starting with '#' are skipped
+    pub fn load(path: &Path) -> AppResult<Self> {
+        let text = fs::read_to_string(path).map_err(InfraError::from)?;
+        Ok(Self::from_words(word_lines(&text)))
+    }
+
+    pub fn from_words<S: AsRef<str>>(words: impl IntoIterator<Item = S>) -> Self {
+        Self {
+            words: words.into_iter().map(|w| w.as_ref().to_lowercase()).collect(),
+        }
+    }
+
+    fn contains(&self, word: &str) -> bool {
+        self.words.contains(&word.to_lowercase())
+    }
+}
+
+/// Reads `<dir>/lexicon.txt`; no file means no words
+pub fn load_lexicon(dir: &Path) -> AppResult<Vec<String>> {
+    let path = dir.join(LEXICON_FILE);
+    if !path.is_file() {
+        return Ok(Vec::new());
+    }
+    let text = fs::read_to_string(path).map_err(InfraError::from)?;
+    Ok(word_lines(&text).map(String::from).collect())
+}
+
+/// Replaces the lexicon of the blueprint with `words`
+pub async fn replace_lexicon(tx: &Transaction<'_>, bp_id: BlueprintId, words: &[String]) -> AppResult<()> {
+    tx.execute("UPDATE blueprints SET lexicon = $2 WHERE id = $1", &[&bp_id, &words])
+        .await
+        .map_err(DbError::from)?;
+    Ok(())
+}
+
+/// The stored lexicon of the blueprint `bp_key`
+pub async fn stored_lexicon(db: &crate::db::Db, bp_key: &str) -> AppResult<Vec<String>> {
+    let client = db.get_client().await?;
+    let row = client
+        .query_opt("SELECT lexicon FROM blueprints WHERE key = $1", &[&bp_key])
+        .await
+        .map_err(DbError::from)?;
+    Ok(row.map(|r| r.get(0)).unwrap_or_default())
+}
+
+fn word_lines(text: &str) -> impl Iterator<Item = &str> {
+    text.lines()
+        .map(str::trim)
+        .filter(|l| !l.is_empty() && !l.starts_with('#'))
+}
+
+/// Prose warnings for the texts of `rooms`, each prefixed with where the text is. Spelling is
+/// only checked with a dictionary.
+pub(super) fn lint_prose(rooms: &[RoomYaml], dictionary: Option<&Dictionary>, lexicon: &[String]) -> Vec<String> {
+    let known: HashSet<String> = blueprint_words(rooms)
+        .chain(lexicon.iter().map(String::as_str))
+        .map(str::to_lowercase)
+        .collect();
+
+    let mut warnings = Vec::new();
+    for (name, text) in rooms.iter().flat_map(texts) {
+        for (n, line) in text.lines().enumerate() {
+            if line != line.trim_end() {
+                warnings.push(format!("{} has trailing whitespace on line {}", name, n + 1));
+            }
+        }
+        for words in paragraphs(text).map(|p| p.split_whitespace().count()) {
+            if words > MAX_PARAGRAPH_WORDS {
+                warnings.push(format!(
+                    "{} has a paragraph of {} words, consider splitting it (at most {})",
+                    name, words, MAX_PARAGRAPH_WORDS
+                ));
+            }
+        }
+        if let Some(dictionary) = dictionary {
+            let mut unknown: Vec<String> = Vec::new();
+            for word in words(text) {
+                let known = dictionary.contains(&word) || known.contains(&word.to_lowercase());
+                if !known && !unknown.contains(&word) {
+                    unknown.push(word);
+                }
+            }
+            if !unknown.is_empty() {
+                warnings.push(format!("{} unknown word(s): {}", name, unknown.join(", ")));
+            }
+        }
+    }
+    warnings
+}
+
+/// The texts players read in a room, named like the Lua chunks in lint warnings
+pub(super) fn texts(room: &RoomYaml) -> Vec<(String, &str)> {
+    let prefix = format!("room:{}", room.id);
+    let mut out = vec![
+        (format!("{}:name", prefix), room.name.as_str()),
+        (format!("{}:description", prefix), room.full_desc.as_str()),
+    ];
+    if let Some(short) = &room.short {
+        out.push((format!("{}:short", prefix), short));
+    }
+    for hint in &room.hints {
+        out.push((format!("{}:hint:{}", prefix, hint.id), &hint.text));
+    }
+    for o in &room.objects {
+        let prefix = format!("{}:object:{}", prefix, o.id);
+        out.push((format!("{}:short", prefix), &o.short));
+        out.push((format!("{}:description", prefix), &o.description));
+        if let Some(examine) = &o.examine {
+            out.push((format!("{}:examine", prefix), examine));
+        }
+        for (i, page) in o.pages.iter().enumerate() {
+            out.push((format!("{}:page:{}", prefix, i + 1), page));
+        }
+        for (id, node) in o.dialogue.iter().flat_map(|d| &d.nodes) {
+            out.push((format!("{}:dialogue:{}", prefix, id), &node.text));
+            for opt in &node.options {
+                out.push((format!("{}:dialogue:{}", prefix, id), &opt.text));
+            }
+        }
+    }
+    for item in &room.items_catalog {
+        let prefix = format!("item:{}", item.id);
+        out.push((format!("{}:name", prefix), &item.name));
+        out.push((format!("{}:short", prefix), &item.short));
+        out.push((format!("{}:description", prefix), &item.description));
+        if let Some(examine) = &item.examine {
+            out.push((format!("{}:examine", prefix), examine));
+        }
+    }
+    out
+}
+
+/// Keys and nouns of the blueprint, split into words
+fn blueprint_words(rooms: &[RoomYaml]) -> impl Iterator<Item = &str> {
+    let keys = rooms.iter().flat_map(|r| {
+        let objects = r.objects.iter().flat_map(|o| std::iter::once(&o.id).chain(&o.nouns));
+        let items = r
+            .items_catalog
+            .iter()
+            .flat_map(|i| std::iter::once(&i.id).chain(&i.nouns));
+        std::iter::once(&r.id).chain(objects).chain(items)
+    });
+    keys.flat_map(|k| k.split(|c: char| !c.is_alphabetic()))
+}
+
+/// Blocks of text separated by empty lines
+fn paragraphs(text: &str) -> impl Iterator<Item = String> {
+    let mut out = vec![String::new()];
+    for line in text.lines() {
+        if line.trim().is_empty() {
+            out.push(String::new());
+        } else if let Some(p) = out.last_mut() {
+            p.push_str(line);
+            p.push('\n');
+        }
+    }
+    out.into_iter().filter(|p| !p.is_empty())
+}
+
+/// Words to spell-check: markup is skipped, as are acronyms and words with digits
+fn words(text: &str) -> Vec<String> {
+    MARKUP_RE
+        .replace_all(text, " ")
+        .split(|c: char| !(c.is_alphanumeric() || c == '\'' || c == '’'))
+        .filter_map(word)
+        .map(String::from)
+        .collect()
+}
+
+fn word(token: &str) -> Option<&str> {
+    let token = token.trim_matches(|c| c == '\'' || c == '’');
+    let token = token
+        .strip_suffix("'s")
+        .or_else(|| token.strip_suffix("’s"))
+        .unwrap_or(token);
+    let acronym = token.chars().count() > 1 && token.chars().all(|c| c.is_uppercase());
+    let skip = token.is_empty() || acronym || token.chars().any(|c| c.is_numeric());
+    (!skip).then_some(token)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn room(text: &str) -> RoomYaml {
+        serde_yaml::from_str(text).expect("valid room yaml")
+    }
+
+    #[test]
+    fn t_words() {
+        assert_eq!(
+            words("The {o:crate} holds Ada's 2nd NASA badge -- 'really'."),
+            vec!["The", "holds", "Ada", "badge", "really"]
+        );
+    }
+
+    #[test]
+    fn t_lint_prose() {
+        let long = vec!["word"; MAX_PARAGRAPH_WORDS + 1].join(" ");
+        let rooms = [room(&format!(
+            "version: 5\nid: boiler_room\nname: Boiler Room\ndescription: \"A hissing boiler stands in the {{o:boiler}}.  \\nZorblax was here.\\n\\n{}\"\nobjects:\n  - {{ id: boiler, nouns: [boiler, tank], short: a rusty tank, description: The tank is rusty. }}\n",
+            long
+        ))];
+        let dictionary =
+            Dictionary::from_words(["a", "the", "in", "was", "here", "is", "stands", "room", "rusty", "word"]);
+
+        let warnings = lint_prose(&rooms, Some(&dictionary), &[]);
+        assert_eq!(warnings.len(), 3, "{:?}", warnings);
+        assert_eq!(
+            warnings[0],
+            "room:boiler_room:description has trailing whitespace on line 1"
+        );
+        assert!(warnings[1].starts_with("room:boiler_room:description has a paragraph of 151 words"));
+        assert_eq!(
+            warnings[2],
+            "room:boiler_room:description unknown word(s): hissing, Zorblax"
+        );
+
+        let lexicon = vec!["hissing".to_string(), "zorblax".to_string()];
+        assert_eq!(lint_prose(&rooms, Some(&dictionary), &lexicon).len(), 2);
+        assert_eq!(lint_prose(&rooms, None, &[]).len(), 2);
+    }
+}
@@ -0,0 +1,244 @@
+//! Checks on the blueprint as a whole map: exits leading to rooms that do not exist, rooms
+//! players can never get to from the entry room, and rooms sealed off behind locked exits that
+//! nothing in the blueprint opens.
+//!
+//! Players move along exits and vehicles: an object that boards a vehicle leads into it, and a
+//! vehicle leads to each of its stops. A locked exit counts as openable when its room has a
+//! widget or dialogue effect unlocking it, an object controlling `exit:<dir>.locked`, or Lua
+//! calling `set_exit_locked` (or loading a module with `require`, which might).
+
+use super::{RoomYaml, lua_chunks};
+use crate::error::{AppResult, DomainError};
+use crate::input::matcher::edit_distance;
+use std::collections::{HashMap, HashSet};
+
+/// Most typos a missing room key may have for a suggestion
+const MAX_SUGGEST_DISTANCE: usize = 3;
+
+/// Every exit has to lead to a room of the blueprint
+pub(super) fn validate_exit_targets(rooms: &[RoomYaml]) -> AppResult<()> {
+    let keys: Vec<&str> = rooms.iter().map(|r| r.id.as_str()).collect();
+    for r in rooms {
+        if let Some(ex) = r.exits.iter().find(|ex| !keys.contains(&ex.to.as_str())) {
+            let mut message = format!("exit '{}' of room '{}' leads to unknown room '{}'", ex.dir, r.id, ex.to);
+            if let Some(key) = closest_key(&ex.to, &keys) {
+                message.push_str(&format!(", did you mean '{}'?", key));
+            }
+            return Err(DomainError::Validation { field: "exit", message });
+        }
+    }
+    Ok(())
+}
+
+/// Warnings about rooms that cannot be reached from `entry`, each with a suggested fix. Without
+/// an entry room there is nothing to start from, so nothing to report.
+pub(super) fn lint_reachability(rooms: &[RoomYaml], entry: Option<&str>) -> Vec<String> {
+    let Some(entry) = entry.filter(|e| rooms.iter().any(|r| r.id == *e)) else {
+        return Vec::new();
+    };
+    let edges = edges(rooms);
+
+    let reachable = walk(&edges, entry, |_| true);
+    let open = walk(&edges, entry, |e| !e.sealed);
+
+    let mut warnings = Vec::new();
+
+    // Locked exits nothing opens, on the border of what players can get to
+    let mut sealed_off: HashSet<&str> = HashSet::new();
+    for e in edges
+        .iter()
+        .filter(|e| e.sealed && open.contains(e.from) && !open.contains(e.to))
+    {
+        let behind = walk(&edges, e.to, |b| !open.contains(b.to));
+        let mut behind: Vec<&str> = behind.into_iter().filter(|r| !open.contains(r)).collect();
+        behind.sort_unstable();
+        sealed_off.extend(&behind);
+        warnings.push(format!(
+            "room:{} exit '{}' is locked and nothing in '{}' opens it, sealing off {}; add 'unlock: [{}]' to a widget or dialogue effect, a control 'exit:{}.locked', or call set_exit_locked from a script in '{}'",
+            e.from,
+            e.dir,
+            e.from,
+            list(&behind),
+            e.dir,
+            e.dir,
+            e.from
+        ));
+    }
+
+    let mut unreachable: Vec<&str> = rooms
+        .iter()
+        .map(|r| r.id.as_str())
+        .filter(|r| !reachable.contains(r) && !sealed_off.contains(r))
+        .collect();
+    unreachable.sort_unstable();
+    for room in unreachable {
+        warnings.push(format!(
+            "room:{} cannot be reached from the entry room '{}'; add an exit leading to it or remove the room",
+            room, entry
+        ));
+    }
+
+    warnings
+}
+
+/// One way to get from a room to another
+struct Edge<'a> {
+    from: &'a str,
+    /// Exit direction, or "board"/"stop" for vehicles
+    dir: &'a str,
+    to: &'a str,
+    /// Locked, with nothing in its room to open it
+    sealed: bool,
+}
+
+fn edges(rooms: &[RoomYaml]) -> Vec<Edge<'_>> {
+    let mut edges = Vec::new();
+    for r in rooms {
+        for ex in &r.exits {
+            edges.push(Edge {
+                from: &r.id,
+                dir: &ex.dir,
+                to: &ex.to,
+                sealed: ex.locked.unwrap_or(false) && !opens_exit(r, &ex.dir),
+            });
+        }
+        for target in r.objects.iter().filter_map(|o| o.board.as_deref()) {
+            edges.push(Edge {
+                from: &r.id,
+                dir: "board",
+                to: target,
+                sealed: false,
+            });
+        }
+        for stop in r.vehicle.iter().flat_map(|v| &v.stops) {
+            edges.push(Edge {
+                from: &r.id,
+                dir: "stop",
+                to: &stop.room,
+                sealed: false,
+            });
+        }
+    }
+    edges
+}
+
+/// Rooms reachable from `start` along the edges `follow` accepts
+fn walk<'a>(edges: &[Edge<'a>], start: &'a str, follow: impl Fn(&Edge<'a>) -> bool) -> HashSet<&'a str> {
+    let mut out: HashMap<&str, Vec<&Edge<'a>>> = HashMap::new();
+    for e in edges {
+        out.entry(e.from).or_default().push(e);
+    }
+    let mut seen = HashSet::new();
+    let mut todo = vec![start];
+    while let Some(room) = todo.pop() {
+        if seen.insert(room) {
+            todo.extend(out.get(room).into_iter().flatten().filter(|e| follow(e)).map(|e| e.to));
+        }
+    }
+    seen
+}
+
+/// Whether anything in `room` can unlock its exit `dir`
+fn opens_exit(room: &RoomYaml, dir: &str) -> bool {
+    let control = format!("exit:{}.locked", dir.to_ascii_lowercase());
+    let unlocks = |unlock: &[String]| unlock.iter().any(|d| d.eq_ignore_ascii_case(dir));
+
+    room.objects.iter().any(|o| {
+        o.controls.iter().any(|c| c.eq_ignore_ascii_case(&control))
+            || o.widget
+                .as_ref()
+                .is_some_and(|w| w.effects().iter().any(|e| unlocks(&e.unlock)))
+            || o.dialogue.as_ref().is_some_and(|d| {
+                d.nodes
+                    .values()
+                    .flat_map(|n| &n.options)
+                    .any(|opt| unlocks(&opt.effects.unlock))
+            })
+    }) || lua_chunks(room)
+        .iter()
+        .any(|(_, _, code)| code.contains("set_exit_locked") || code.contains("require"))
+}
+
+fn closest_key<'a>(missing: &str, keys: &[&'a str]) -> Option<&'a str> {
+    keys.iter()
+        .map(|k| (*k, edit_distance(missing, k)))
+        .filter(|(_, d)| *d <= MAX_SUGGEST_DISTANCE)
+        .min_by_key(|(_, d)| *d)
+        .map(|(k, _)| k)
+}
+
+/// "room 'a'" or "3 rooms: a, b, c"
+fn list(rooms: &[&str]) -> String {
+    match rooms {
+        [room] => format!("room '{}'", room),
+        _ => format!("{} rooms: {}", rooms.len(), rooms.join(", ")),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn room(id: &str, extra: &str) -> RoomYaml {
+        serde_yaml::from_str(&format!(
+            "version: 5\nid: {}\nname: {}\ndescription: A room.\n{}",
+            id, id, extra
+        ))
+        .expect("valid room yaml")
+    }
+
+    #[test]
+    fn t_validate_exit_targets() {
+        let rooms = [
+            room("hall", "exits:\n  - { dir: north, to: kitchen }\n"),
+            room("kitchen", "exits:\n  - { dir: south, to: hal }\n"),
+        ];
+        let err = validate_exit_targets(&rooms).unwrap_err().to_string();
+        assert!(err.contains("exit 'south' of room 'kitchen' leads to unknown room 'hal', did you mean 'hall'?"));
+
+        let rooms = [room("hall", "exits:\n  - { dir: north, to: hall }\n")];
+        assert!(validate_exit_targets(&rooms).is_ok());
+    }
+
+    #[test]
+    fn t_lint_reachability() {
+        let rooms = [
+            room(
+                "hall",
+                "exits:\n  - { dir: north, to: vault, locked: true }\n  - { dir: east, to: study, locked: true }\nobjects:\n  - { id: lever, short: a lever, description: A lever., controls: ['exit:east.locked'] }\n",
+            ),
+            room("study", "exits:\n  - { dir: west, to: hall }\n"),
+            room("vault", "exits:\n  - { dir: down, to: crypt }\n"),
+            room("crypt", ""),
+            room("attic", "exits:\n  - { dir: down, to: hall }\n"),
+        ];
+        let warnings = lint_reachability(&rooms, Some("hall"));
+        assert_eq!(warnings.len(), 2, "{:?}", warnings);
+        assert!(warnings[0].starts_with(
+            "room:hall exit 'north' is locked and nothing in 'hall' opens it, sealing off 2 rooms: crypt, vault;"
+        ));
+        assert!(warnings[1].starts_with("room:attic cannot be reached from the entry room 'hall'"));
+
+        assert!(lint_reachability(&rooms, None).is_empty());
+    }
+
+    #[test]
+    fn t_lint_reachability_vehicles_and_scripts() {
+        let rooms = [
+            room(
+                "lobby",
+                "objects:\n  - { id: doors, short: lift doors, description: Doors., board: lift }\n",
+            ),
+            room(
+                "lift",
+                "vehicle:\n  stops:\n    - { button: '1', room: lobby }\n    - { button: '2', room: deck }\n",
+            ),
+            room(
+                "deck",
+                "exits:\n  - { dir: north, to: bridge, locked: true }\nscripts:\n  on_enter: |\n    port4k.set_exit_locked(\"north\", false)\n",
+            ),
+            room("bridge", ""),
+        ];
+        assert!(lint_reachability(&rooms, Some("lobby")).is_empty());
+    }
+}
@@ -0,0 +1,110 @@
+//! `@bp grep`: finds a string in the texts and the Lua of a blueprint, so authors can see where a
+//! phrase, object or function is used. Matching ignores case.
+
+use super::lua_lib::LuaModule;
+use super::{RoomYaml, lua_chunks, prose};
+use std::fmt;
+
+/// Characters of a matching line shown around the match
+const EXCERPT_CHARS: usize = 80;
+
+/// A line containing the pattern
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GrepHit {
+    /// Where the line is, like `room:hall:object:desk:on_use` or `lib/doors.lua`
+    pub location: String,
+    /// Line number in the text, from 1
+    pub line: usize,
+    pub excerpt: String,
+}
+
+impl fmt::Display for GrepHit {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "{}:{}: {}", self.location, self.line, self.excerpt)
+    }
+}
+
+/// Lines of the rooms' texts and Lua, then of the modules, containing `pattern`
+pub(super) fn grep(rooms: &[RoomYaml], modules: &[LuaModule], pattern: &str) -> Vec<GrepHit> {
+    let pattern = pattern.to_lowercase();
+    let mut sources: Vec<(String, &str)> = Vec::new();
+    for room in rooms {
+        sources.extend(prose::texts(room));
+        sources.extend(lua_chunks(room).into_iter().map(|(name, _, code)| (name, code)));
+    }
+    for m in modules {
+        sources.push((format!("{}/{}.lua", super::lua_lib::LIB_DIR, m.name), &m.source));
+    }
+
+    let mut hits = Vec::new();
+    for (location, text) in sources {
+        for (n, line) in text.lines().enumerate() {
+            let line = line.trim();
+            let lower = line.to_lowercase();
+            if let Some(pos) = lower.find(&pattern) {
+                hits.push(GrepHit {
+                    location: location.clone(),
+                    line: n + 1,
+                    excerpt: excerpt(line, lower[..pos].chars().count()),
+                });
+            }
+        }
+    }
+    hits
+}
+
+/// The line cut down to `EXCERPT_CHARS` characters around the match starting at character `at`
+fn excerpt(line: &str, at: usize) -> String {
+    let chars: Vec<char> = line.chars().collect();
+    if chars.len() <= EXCERPT_CHARS {
+        return line.to_string();
+    }
+    let start = at.saturating_sub(EXCERPT_CHARS / 4).min(chars.len() - EXCERPT_CHARS);
+    let end = start + EXCERPT_CHARS;
+    let mut out = String::new();
+    if start > 0 {
+        out.push_str("...");
+    }
+    out.extend(&chars[start..end]);
+    if end < chars.len() {
+        out.push_str("...");
+    }
+    out
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn t_grep() {
+        let room: RoomYaml = serde_yaml::from_str(
+            "version: 5\nid: hall\nname: Hall\ndescription: \"A dusty hall.\\nA Brass Lamp hangs here.\"\nobjects:\n  - { id: lamp, short: a brass lamp, description: Old., on_use: \"port4k.say('The lamp glows')\" }\n",
+        )
+        .expect("valid room yaml");
+        let modules = [LuaModule {
+            name: "lights".into(),
+            source: "local M = {}\nfunction M.lamp() end\nreturn M\n".into(),
+        }];
+
+        let hits: Vec<String> = grep(&[room], &modules, "LAMP").iter().map(|h| h.to_string()).collect();
+        assert_eq!(
+            hits,
+            vec![
+                "room:hall:description:2: A Brass Lamp hangs here.",
+                "room:hall:object:lamp:short:1: a brass lamp",
+                "room:hall:object:lamp:on_use:1: port4k.say('The lamp glows')",
+                "lib/lights.lua:2: function M.lamp() end",
+            ]
+        );
+    }
+
+    #[test]
+    fn t_excerpt() {
+        let line = format!("{}needle{}", "a".repeat(100), "b".repeat(100));
+        let out = excerpt(&line, 100);
+        assert!(out.starts_with("...") && out.ends_with("..."));
+        assert!(out.contains("needle"));
+        assert_eq!(out.chars().count(), EXCERPT_CHARS + 6);
+    }
+}
@@ -1,3 +1,5 @@
+pub mod matcher;
 pub mod parser;
 pub mod readline;
+pub mod sanitize;
 pub mod shell;
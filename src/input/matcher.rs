@@ -0,0 +1,158 @@
+//! Scoring the nouns players type against the names of things.
+//!
+//! An exact name beats a plural ("coins" for a coin), which beats the start of a name ("wre" for
+//! a wrench), which beats a typo ("wrnech"). Adjectives typed in front of the noun count when
+//! they appear in the short description, so "red key" picks the red one of two keys. A guess is
+//! only picked when nothing else scores the same; exact names keep picking the first in line.
+
+use crate::input::parser::NounPhrase;
+
+pub const EXACT: u32 = 100;
+pub const PLURAL: u32 = 90;
+pub const PREFIX: u32 = 60;
+pub const TYPO: u32 = 40;
+/// Added per adjective found in the short description
+const ADJECTIVE: u32 = 5;
+/// Shortest typed noun matched as the start of a name
+const MIN_PREFIX: usize = 3;
+
+/// How well `np` names a thing called `names`, described by `short`. 0 when it does not.
+pub fn score<S: AsRef<str>>(np: &NounPhrase, names: &[S], short: &str) -> u32 {
+    let base = names.iter().map(|n| noun_score(np, n.as_ref())).max().unwrap_or(0);
+    if base == 0 {
+        return 0;
+    }
+
+    let words: Vec<String> = short
+        .split(|c: char| !c.is_alphanumeric())
+        .map(str::to_lowercase)
+        .collect();
+    let adjectives = np
+        .adjectives
+        .iter()
+        .filter(|a| words.contains(&a.to_lowercase()))
+        .count();
+    base + ADJECTIVE * adjectives as u32
+}
+
+fn noun_score(np: &NounPhrase, name: &str) -> u32 {
+    let head = np.head.to_lowercase();
+    let name = name.to_lowercase();
+    if head == name {
+        EXACT
+    } else if np.head_forms().iter().skip(1).any(|f| f.eq_ignore_ascii_case(&name)) {
+        PLURAL
+    } else if head.chars().count() >= MIN_PREFIX && name.starts_with(&head) {
+        PREFIX
+    } else if is_typo(&head, &name) {
+        TYPO
+    } else {
+        0
+    }
+}
+
+/// One slip in a word of four letters or more, two from eight letters on
+fn is_typo(typed: &str, name: &str) -> bool {
+    let allowed = match typed.chars().count() {
+        0..=3 => return false,
+        4..=7 => 1,
+        _ => 2,
+    };
+    edit_distance(typed, name) <= allowed
+}
+
+/// Insertions, deletions, substitutions and swaps of two neighbouring letters needed to turn `a`
+/// into `b`
+pub(crate) fn edit_distance(a: &str, b: &str) -> usize {
+    let a: Vec<char> = a.chars().collect();
+    let b: Vec<char> = b.chars().collect();
+    let mut d = vec![vec![0usize; b.len() + 1]; a.len() + 1];
+    for (i, row) in d.iter_mut().enumerate() {
+        row[0] = i;
+    }
+    d[0] = (0..=b.len()).collect();
+    for i in 1..=a.len() {
+        for j in 1..=b.len() {
+            let cost = usize::from(a[i - 1] != b[j - 1]);
+            d[i][j] = (d[i - 1][j] + 1).min(d[i][j - 1] + 1).min(d[i - 1][j - 1] + cost);
+            if i > 1 && j > 1 && a[i - 1] == b[j - 2] && a[i - 2] == b[j - 1] {
+                d[i][j] = d[i][j].min(d[i - 2][j - 2] + 1);
+            }
+        }
+    }
+    d[a.len()][b.len()]
+}
+
+/// Picks the best scored candidate, in listing order. With an ordinal ("second key") the nth of
+/// the best ones. Without, a guess that fits several things equally well picks none of them.
+pub fn pick<T>(scored: impl IntoIterator<Item = (T, u32)>, ordinal: Option<u32>) -> Option<T> {
+    let scored: Vec<(T, u32)> = scored.into_iter().filter(|(_, s)| *s > 0).collect();
+    let best = scored.iter().map(|(_, s)| *s).max()?;
+    let mut top = scored.into_iter().filter(|(_, s)| *s == best).map(|(t, _)| t);
+
+    match ordinal {
+        Some(n) => top.nth(n.checked_sub(1)? as usize),
+        None if best >= PLURAL => top.next(),
+        None => {
+            let first = top.next();
+            if top.next().is_some() { None } else { first }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::input::parser::parse_command;
+
+    fn np(input: &str) -> NounPhrase {
+        parse_command(&format!("take {}", input)).direct.unwrap()
+    }
+
+    #[test]
+    fn t_score_tiers() {
+        let wrench = ["wrench", "spanner"];
+        assert_eq!(score(&np("wrench"), &wrench, "a wrench"), EXACT);
+        assert_eq!(score(&np("Spanner"), &wrench, "a wrench"), EXACT);
+        assert_eq!(score(&np("wrenches"), &wrench, "a wrench"), PLURAL);
+        assert_eq!(score(&np("wre"), &wrench, "a wrench"), PREFIX);
+        assert_eq!(score(&np("wrnech"), &wrench, "a wrench"), TYPO);
+        assert_eq!(score(&np("wr"), &wrench, "a wrench"), 0);
+        assert_eq!(score(&np("key"), &wrench, "a wrench"), 0);
+        // Short words get no typo tolerance
+        assert_eq!(score(&np("kez"), &["key"], "a key"), 0);
+    }
+
+    #[test]
+    fn t_adjectives_break_ties() {
+        let keys = [("blue", "a blue key"), ("red", "a red key")];
+        let scored = |input: &str| {
+            let np = np(input);
+            keys.iter()
+                .map(|(k, short)| (*k, score(&np, &["key"], short)))
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(pick(scored("red key"), None), Some("red"));
+        assert_eq!(pick(scored("key"), None), Some("blue"));
+        assert_eq!(pick(scored("key"), Some(2)), Some("red"));
+        assert_eq!(pick(scored("key"), Some(3)), None);
+    }
+
+    #[test]
+    fn t_ambiguous_guess_picks_nothing() {
+        let things = [("panel", "panel"), ("pane", "pane")];
+        let scored = |input: &str| {
+            let np = np(input);
+            things
+                .iter()
+                .map(|(k, name)| (*k, score(&np, &[name], "")))
+                .collect::<Vec<_>>()
+        };
+
+        assert_eq!(pick(scored("pan"), None), None);
+        assert_eq!(pick(scored("panel"), None), Some("panel"));
+        assert_eq!(pick(scored("pannel"), None), Some("panel"));
+        assert_eq!(pick(scored("lamp"), None), None);
+    }
+}
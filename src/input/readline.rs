@@ -18,6 +18,7 @@
 //! - Treats bytes as single columns (ASCII). For full Unicode widths, integrate `unicode-width` later.
 //! - Designed for remote terminals (Telnet). You parse IAC/NAWS/etc. elsewhere; feed *post-negotiation* bytes here.
 
+use crate::hardening::{MAX_ESCAPE_BYTES, MAX_INPUT_LINE_BYTES};
 use std::cmp::min;
 
 /// Events produced by the editor as it processes input.
@@ -38,6 +39,8 @@ pub struct EditorConfig {
     pub max_history: usize,
     /// If true, prevent pushing duplicate consecutive history items.
     pub dedup_consecutive_history: bool,
+    /// Max length of a line in bytes; characters typed beyond it are dropped.
+    pub max_line_bytes: usize,
 }
 
 impl Default for EditorConfig {
@@ -45,6 +48,7 @@ impl Default for EditorConfig {
         Self {
             max_history: 200,
             dedup_consecutive_history: true,
+            max_line_bytes: MAX_INPUT_LINE_BYTES,
         }
     }
 }
@@ -180,7 +184,8 @@ impl LineEditor {
                 EditEvent::Redraw
             }
 
-            // Printable ASCII
+            // Printable ASCII, while the line has room
+            b if (0x20..=0x7E).contains(&b) && self.buf.len() >= self.cfg.max_line_bytes => EditEvent::None,
             b if (0x20..=0x7E).contains(&b) => {
                 self.buf.insert(self.cursor, b as char);
                 self.cursor += 1;
@@ -241,17 +246,23 @@ impl LineEditor {
         }
 
         // SS3 Home/End: ESC O H/F
-        if s == b"\x1B[OH" {
+        if s == b"\x1BOH" {
             self.esc.clear();
             self.cursor = 0;
             return EditEvent::Redraw;
         }
-        if s == b"\x1B[OF" {
+        if s == b"\x1BOF" {
             self.esc.clear();
             self.cursor = self.buf.len();
             return EditEvent::Redraw;
         }
 
+        // A sequence that never ends is dropped rather than collected forever
+        if s.len() > MAX_ESCAPE_BYTES {
+            self.esc.clear();
+            return EditEvent::None;
+        }
+
         if s.starts_with(b"\x1b[") {
             if let Some(&last) = s.last() {
                 let is_final = (last.is_ascii_alphabetic()) || last == b'~';
@@ -265,7 +276,10 @@ impl LineEditor {
             return EditEvent::None;
         }
 
-        // Otherwise ignore.
+        // ESC O <key> (other SS3 keys), or ESC <byte> (Alt+key): dropped once complete
+        if !matches!(s, [0x1B] | [0x1B, b'O']) {
+            self.esc.clear();
+        }
         EditEvent::None
     }
 
@@ -387,3 +401,42 @@ impl LineEditor {
         self.hist_ix = None;
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn typed(ed: &mut LineEditor, bytes: &[u8]) -> Option<String> {
+        let mut line = None;
+        for b in bytes {
+            if let EditEvent::Line(l) = ed.handle_byte(*b) {
+                line = Some(l);
+            }
+        }
+        line
+    }
+
+    #[test]
+    fn t_line_limit() {
+        let mut ed = LineEditor::with_config(
+            "> ",
+            EditorConfig {
+                max_line_bytes: 5,
+                ..EditorConfig::default()
+            },
+        );
+        assert_eq!(typed(&mut ed, b"look around\r").as_deref(), Some("look "));
+    }
+
+    #[test]
+    fn t_escape_sequences() {
+        let mut ed = LineEditor::new("> ");
+        // Alt+x, an SS3 key, a colour code and a CSI sequence that never ends
+        let mut input = b"\x1bxa\x1bOPb\x1b[1;31mc\x1b[".to_vec();
+        input.extend([b'1'; MAX_ESCAPE_BYTES - 1]);
+        input.extend(b"d\r");
+        assert_eq!(typed(&mut ed, &input).as_deref(), Some("abcd"));
+
+        assert_eq!(typed(&mut ed, b"xy\x1bOH>\r").as_deref(), Some(">xy"));
+    }
+}
@@ -0,0 +1,92 @@
+//! Cleaning up player input before it is parsed or logged.
+//!
+//! Terminal escape sequences (CSI, OSC and two-byte ESC sequences) and control characters are
+//! removed, including C1 controls and the bidirectional overrides that can make a logged line
+//! read differently than it was typed. Tabs become spaces. Lines are cut at
+//! `MAX_INPUT_LINE_BYTES`, on a character boundary.
+
+use crate::hardening::MAX_INPUT_LINE_BYTES;
+
+const ESC: char = '\x1b';
+const BEL: char = '\x07';
+/// Single character CSI of the C1 controls
+const C1_CSI: char = '\u{9b}';
+
+/// The line with escape sequences and control characters removed, at most
+/// `MAX_INPUT_LINE_BYTES` long
+pub fn sanitize_line(input: &str) -> String {
+    let mut out = String::with_capacity(input.len().min(MAX_INPUT_LINE_BYTES));
+    let mut chars = input.chars().peekable();
+    while let Some(c) = chars.next() {
+        let c = match c {
+            ESC => {
+                match chars.next() {
+                    Some('[') => skip_csi(&mut chars),
+                    Some(']') => skip_osc(&mut chars),
+                    _ => {}
+                }
+                continue;
+            }
+            C1_CSI => {
+                skip_csi(&mut chars);
+                continue;
+            }
+            '\t' => ' ',
+            c if c.is_control() || is_bidi_control(c) => continue,
+            c => c,
+        };
+        if out.len() + c.len_utf8() > MAX_INPUT_LINE_BYTES {
+            break;
+        }
+        out.push(c);
+    }
+    out
+}
+
+/// Parameters and intermediates up to and including the final byte
+fn skip_csi(chars: &mut impl Iterator<Item = char>) {
+    for c in chars.by_ref() {
+        if ('\x40'..='\x7e').contains(&c) {
+            break;
+        }
+    }
+}
+
+/// Up to BEL or ESC \
+fn skip_osc(chars: &mut std::iter::Peekable<impl Iterator<Item = char>>) {
+    while let Some(c) = chars.next() {
+        if c == BEL {
+            break;
+        }
+        if c == ESC {
+            chars.next_if_eq(&'\\');
+            break;
+        }
+    }
+}
+
+fn is_bidi_control(c: char) -> bool {
+    matches!(c, '\u{200e}' | '\u{200f}' | '\u{202a}'..='\u{202e}' | '\u{2066}'..='\u{2069}')
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn t_sanitize_line() {
+        assert_eq!(sanitize_line("look\tat  lamp"), "look at  lamp");
+        assert_eq!(sanitize_line("say \x1b[31mred\x1b[0m!"), "say red!");
+        assert_eq!(
+            sanitize_line("say \x1b]0;owned\x07hi \x1b]8;;x\x1b\\there"),
+            "say hi there"
+        );
+        assert_eq!(sanitize_line("n\0orth\x08\x7f\u{9b}2Jx\u{202e}y"), "northxy");
+        assert_eq!(sanitize_line("café ☕"), "café ☕");
+
+        let long = "é".repeat(MAX_INPUT_LINE_BYTES);
+        let cut = sanitize_line(&long);
+        assert_eq!(cut.len(), MAX_INPUT_LINE_BYTES);
+        assert!(cut.chars().all(|c| c == 'é'));
+    }
+}
@@ -13,6 +13,7 @@ pub mod models;
 pub mod net;
 pub mod realm_manager;
 pub mod renderer;
+pub mod scenario;
 pub mod services;
 pub mod state;
 pub mod util;
@@ -1,25 +1,39 @@
+pub mod actions;
+pub mod compat;
+pub mod lint;
+pub mod modules;
+pub mod queue;
 pub mod table;
 
 use crate::Registry;
 use crate::error::{AppResult, DomainError};
+use crate::hardening::MAX_SCRIPT_ACTIONS;
 use crate::input::parser::{Intent, NounPhrase, Preposition, Quantifier};
+use crate::input::sanitize::sanitize_line;
+use crate::lua::queue::{InFlight, JobSender, LuaPriority, LuaSendError, QueuedJob, job_queue};
 use crate::lua::table::format_lua_value;
 use crate::models::account::Account;
-use crate::models::room::{ObjectLoot, ResolvedExit, ResolvedObject, RoomView};
-use crate::models::types::{AccountId, Direction, ItemId};
-use crate::net::output::OutputHandle;
+use crate::models::inventory::{ItemInstance, Recipe};
+use crate::models::room::{ObjectLoot, ResolvedExit, ResolvedObject, RoomView, SpawnedObject, is_valid_sound_cue};
+use crate::models::types::{AccountId, Direction, ItemId, ObjectId, RealmId, RoomId};
+use crate::net::output::{OutputBuffer, OutputHandle};
+use crate::state::script_actions::{ScriptAction, SequenceStep, check_forced_command, check_sequence};
 use crate::state::session::Cursor;
+use crate::state::{clock, lockdown, vehicles};
+use chrono::{Datelike, SecondsFormat, Timelike};
 use mlua::prelude::LuaError;
 use mlua::{Function, Lua, Table};
 use serde::{Deserialize, Serialize};
+use std::collections::hash_map::DefaultHasher;
+use std::hash::{Hash, Hasher};
 use std::str::FromStr;
 use std::sync::Arc;
+use std::sync::atomic::{AtomicU64, Ordering};
 use std::time::Duration;
 use tokio::runtime::Handle;
-use tokio::sync::mpsc;
 use tokio::sync::oneshot::Sender;
+use tokio::time::timeout;
 
-pub const LUA_CMD_TIMEOUT: Duration = Duration::from_secs(5);
 const REPL_ENV_KEY: &str = "__repl_env";
 
 macro_rules! set_lua_table_readonly {
@@ -57,6 +71,9 @@ pub enum ScriptHook {
     /// When a player issues a command in the room that is not handled elsewhere
     #[serde(rename = "on_command")]
     OnCommand,
+    /// After a player crafted something in the room
+    #[serde(rename = "on_craft")]
+    OnCraft,
 }
 
 impl ScriptHook {
@@ -66,6 +83,7 @@ impl ScriptHook {
             ScriptHook::OnEnter => "on_enter",
             ScriptHook::OnLeave => "on_leave",
             ScriptHook::OnCommand => "on_command",
+            ScriptHook::OnCraft => "on_craft",
         }
     }
 
@@ -75,6 +93,7 @@ impl ScriptHook {
             "on_enter" => Ok(ScriptHook::OnEnter),
             "on_leave" => Ok(ScriptHook::OnLeave),
             "on_command" => Ok(ScriptHook::OnCommand),
+            "on_craft" => Ok(ScriptHook::OnCraft),
             _ => Err(DomainError::InvalidData(format!("unknown script hook: {}", s))),
         }
     }
@@ -160,6 +179,80 @@ pub enum LuaJob {
         reply: Sender<LuaResult>,
     },
 
+    /// Called after a player crafted something in a room
+    OnCraft {
+        /// Output handle for text,
+        output_handle: OutputHandle,
+        /// Account of the user
+        account_id: AccountId,
+        /// Cursor of the user
+        cursor: Box<Cursor>,
+        /// Recipe that was crafted
+        recipe: Box<Recipe>,
+        /// Return channel
+        reply: Sender<LuaResult>,
+    },
+
+    /// Called when a player reads an object with an on_read script
+    OnRead {
+        /// Output handle for text,
+        output_handle: OutputHandle,
+        /// Account of the user
+        account_id: AccountId,
+        /// Cursor of the user
+        cursor: Box<Cursor>,
+        /// Object being read
+        obj: Box<ResolvedObject>,
+        /// Return channel
+        reply: Sender<LuaResult>,
+    },
+
+    /// Called when a player gives an item to an object with an on_receive script
+    OnReceive {
+        /// Output handle for text,
+        output_handle: OutputHandle,
+        /// Account of the user
+        account_id: AccountId,
+        /// Cursor of the user
+        cursor: Box<Cursor>,
+        /// Object receiving the item
+        obj: Box<ResolvedObject>,
+        /// Item offered
+        item: Box<ItemInstance>,
+        /// Number of the item offered
+        quantity: i32,
+        /// Return channel
+        reply: Sender<LuaResult>,
+    },
+
+    /// Called with every line typed while a player uses an object as a terminal
+    OnTerminal {
+        /// Output handle for text,
+        output_handle: OutputHandle,
+        /// Account of the user
+        account_id: AccountId,
+        /// Cursor of the user
+        cursor: Box<Cursor>,
+        /// Terminal object
+        obj: Box<ResolvedObject>,
+        /// Line typed, None when the terminal is switched on
+        line: Option<String>,
+        /// Return channel
+        reply: Sender<LuaResult>,
+    },
+
+    /// A scheduled event's chunk, run in its realm without a player (see `models::schedule`)
+    Scheduled {
+        /// Realm the chunk runs in
+        realm_id: RealmId,
+        /// Chunk name for error messages
+        name: String,
+        /// Lua source
+        code: String,
+        /// Return channel
+        reply: Sender<LuaResult>,
+    },
+
     ReplEval {
         /// Output handle for text,
         output_handle: OutputHandle,
@@ -174,115 +267,340 @@ pub enum LuaJob {
     },
 }
 
-/// Start a dedicated Lua worker thread with its own Lua state.
-/// Pass the runtime `Handle` so the worker can run async DB calls with `handle.block_on(...)`.
-pub fn start_lua_worker(rt_handle: Handle, registry: Arc<Registry>) -> mpsc::Sender<LuaJob> {
-    let (tx, mut rx) = mpsc::channel::<LuaJob>(64);
-
-    std::thread::spawn(move || {
-        let lua = init_lua().expect("cannot init lua");
-        lua.sandbox(true).expect("cannot sandbox lua");
-
-        while let Some(job) = rx.blocking_recv() {
-            println!("*************** LUA JOB TRIGGERED ***************");
-            match job {
-                LuaJob::OnEnter {
-                    output_handle,
-                    cursor,
-                    account_id,
-                    reply,
-                } => {
-                    let ctx = rt_handle.block_on(LuaArgContext::new(
-                        output_handle.clone(),
-                        Some(*cursor),
-                        Some(account_id),
-                        registry.clone(),
-                        rt_handle.clone(),
-                    ));
-                    handle_room_script(&lua, &ctx, ScriptHook::OnEnter, reply);
-                }
-                LuaJob::OnFirstEnter {
-                    output_handle,
-                    cursor,
-                    account_id,
-                    reply,
-                } => {
-                    let ctx = rt_handle.block_on(LuaArgContext::new(
-                        output_handle.clone(),
-                        Some(*cursor),
-                        Some(account_id),
-                        registry.clone(),
-                        rt_handle.clone(),
-                    ));
-                    handle_room_script(&lua, &ctx, ScriptHook::OnFirstEnter, reply);
-                }
-                LuaJob::OnLeave {
-                    output_handle,
-                    cursor,
-                    account_id,
-                    reply,
-                } => {
-                    let ctx = rt_handle.block_on(LuaArgContext::new(
-                        output_handle.clone(),
-                        Some(*cursor),
-                        Some(account_id),
-                        registry.clone(),
-                        rt_handle.clone(),
-                    ));
-                    handle_room_script(&lua, &ctx, ScriptHook::OnLeave, reply);
-                }
-                LuaJob::OnObject {
-                    output_handle,
-                    cursor,
-                    account_id,
-                    intent,
-                    obj,
-                    reply,
-                } => {
-                    let ctx = rt_handle.block_on(LuaArgContext::new(
-                        output_handle.clone(),
-                        Some(*cursor),
-                        Some(account_id),
-                        registry.clone(),
-                        rt_handle.clone(),
-                    ));
-                    handle_object_script(&lua, &ctx, &intent, &obj, reply);
-                }
-                LuaJob::OnCommand {
-                    output_handle,
-                    cursor,
-                    account_id,
-                    intent,
-                    reply,
-                } => {
-                    let ctx = rt_handle.block_on(LuaArgContext::new(
-                        output_handle.clone(),
-                        Some(*cursor),
-                        Some(account_id),
-                        registry.clone(),
-                        rt_handle.clone(),
-                    ));
-                    handle_command_script(&lua, &ctx, &intent, reply);
-                }
-                LuaJob::ReplEval {
-                    output_handle,
-                    cursor,
-                    account_id,
-                    code,
-                    reply,
-                } => {
-                    let ctx = rt_handle.block_on(LuaArgContext::new(
-                        output_handle.clone(),
-                        Some(*cursor),
-                        Some(account_id),
-                        registry.clone(),
-                        rt_handle.clone(),
-                    ));
-                    _ = handle_repl_eval(&lua, &ctx, &code, reply);
+impl LuaJob {
+    /// Realm the job runs in, which decides its worker
+    fn realm_id(&self) -> RealmId {
+        match self {
+            LuaJob::OnFirstEnter { cursor, .. }
+            | LuaJob::OnEnter { cursor, .. }
+            | LuaJob::OnLeave { cursor, .. }
+            | LuaJob::OnCommand { cursor, .. }
+            | LuaJob::OnObject { cursor, .. }
+            | LuaJob::OnCraft { cursor, .. }
+            | LuaJob::OnRead { cursor, .. }
+            | LuaJob::OnReceive { cursor, .. }
+            | LuaJob::OnTerminal { cursor, .. }
+            | LuaJob::ReplEval { cursor, .. } => cursor.realm_id,
+            LuaJob::Scheduled { realm_id, .. } => *realm_id,
+        }
+    }
+
+    /// Account the job runs for; None for jobs without a player
+    fn account_id(&self) -> Option<AccountId> {
+        match self {
+            LuaJob::OnFirstEnter { account_id, .. }
+            | LuaJob::OnEnter { account_id, .. }
+            | LuaJob::OnLeave { account_id, .. }
+            | LuaJob::OnCommand { account_id, .. }
+            | LuaJob::OnObject { account_id, .. }
+            | LuaJob::OnCraft { account_id, .. }
+            | LuaJob::OnRead { account_id, .. }
+            | LuaJob::OnReceive { account_id, .. }
+            | LuaJob::OnTerminal { account_id, .. }
+            | LuaJob::ReplEval { account_id, .. } => Some(*account_id),
+            LuaJob::Scheduled { .. } => None,
+        }
+    }
+
+    /// Jobs without a player waiting on them yield to the ones with
+    pub fn priority(&self) -> LuaPriority {
+        match self {
+            LuaJob::Scheduled { .. } => LuaPriority::Background,
+            _ => LuaPriority::Interactive,
+        }
+    }
+}
+
+/// The Lua workers. All jobs of a realm go to the same worker, so they run in the order they
+/// were sent, while different realms run in parallel.
+#[derive(Clone)]
+pub struct LuaPool {
+    workers: Arc<[JobSender]>,
+    registry: Arc<Registry>,
+    in_flight: Arc<InFlight>,
+    /// Jobs turned away since startup
+    shed: Arc<AtomicU64>,
+}
+
+/// Snapshot of the load on the Lua workers
+pub struct LuaPoolStats {
+    /// Interactive and background jobs waiting, per worker
+    pub depths: Vec<(usize, usize)>,
+    /// Sessions with jobs in flight
+    pub sessions: usize,
+    /// Jobs of sessions queued or running
+    pub in_flight: usize,
+    /// Jobs turned away since startup
+    pub shed: u64,
+}
+
+impl LuaPool {
+    /// Queues `job` on the worker of its realm. Fails with [`LuaSendError::Busy`] when the
+    /// session has too many jobs in flight or the queue stays full for `lua.queue_timeout_ms`.
+    pub async fn send(&self, job: LuaJob) -> Result<(), LuaSendError> {
+        let config = self.registry.config();
+        let (realm_id, priority) = (job.realm_id(), job.priority());
+        let slot = match job.account_id() {
+            Some(account_id) => match self.in_flight.acquire(account_id, config.lua.max_in_flight) {
+                Some(slot) => Some(slot),
+                None => return Err(self.shed_load(realm_id, priority, "session has too many jobs in flight")),
+            },
+            None => None,
+        };
+
+        let account_id = job.account_id();
+        let worker = &self.workers[worker_index(realm_id, self.workers.len())];
+        match timeout(config.lua.queue_timeout(), worker.send(QueuedJob { job, slot })).await {
+            Ok(result) => {
+                // Counted against the player's connection, for `@debug conn`
+                if let Some(player) = account_id
+                    .filter(|_| result.is_ok())
+                    .and_then(|id| self.registry.connected.get(&id))
+                {
+                    player.sess.read().stats().add_lua_job();
                 }
-            };
+                result
+            }
+            Err(_elapsed) => Err(self.shed_load(realm_id, priority, "queue is full")),
         }
-    });
+    }
+
+    fn shed_load(&self, realm_id: RealmId, priority: LuaPriority, reason: &str) -> LuaSendError {
+        self.shed.fetch_add(1, Ordering::Relaxed);
+        tracing::warn!(realm = %realm_id, ?priority, "lua: {}, job dropped", reason);
+        LuaSendError::Busy
+    }
+
+    pub fn stats(&self) -> LuaPoolStats {
+        let (sessions, in_flight) = self.in_flight.totals();
+        LuaPoolStats {
+            depths: self.workers.iter().map(JobSender::depth).collect(),
+            sessions,
+            in_flight,
+            shed: self.shed.load(Ordering::Relaxed),
+        }
+    }
+}
+
+/// What a script sees when shared object state could not be written, e.g. a conflict after losing
+/// every compare-and-swap retry against other players
+fn shared_state_error(action: &str, e: DomainError) -> LuaError {
+    LuaError::external(format!("Failed to {} object state: {}", action, e))
+}
+
+fn worker_index(realm_id: RealmId, workers: usize) -> usize {
+    let mut hasher = DefaultHasher::new();
+    realm_id.hash(&mut hasher);
+    (hasher.finish() % workers.max(1) as u64) as usize
+}
+
+/// Starts `lua.workers` Lua worker threads, each with its own Lua state.
+/// Pass the runtime `Handle` so the workers can run async DB calls with `handle.block_on(...)`.
+pub fn start_lua_workers(rt_handle: Handle, registry: Arc<Registry>) -> LuaPool {
+    let count = registry.config().lua.workers.max(1);
+    let workers = (0..count)
+        .map(|idx| start_lua_worker(idx, rt_handle.clone(), registry.clone()))
+        .collect();
+    LuaPool {
+        workers,
+        registry,
+        in_flight: Arc::new(InFlight::default()),
+        shed: Arc::new(AtomicU64::new(0)),
+    }
+}
+
+/// Start a dedicated Lua worker thread with its own Lua state.
+fn start_lua_worker(idx: usize, rt_handle: Handle, registry: Arc<Registry>) -> JobSender {
+    let (tx, mut rx) = job_queue();
+
+    let builder = std::thread::Builder::new().name(format!("lua-worker-{}", idx));
+    builder
+        .spawn(move || {
+            let lua = init_lua().expect("cannot init lua");
+            lua.sandbox(true).expect("cannot sandbox lua");
+            lua.set_memory_limit(registry.config().lua.memory_limit_bytes)
+                .expect("cannot set lua memory limit");
+
+            // The slot is held until the job is done
+            while let Some(QueuedJob { job, slot: _slot }) = rx.blocking_recv(&rt_handle) {
+                // Output a previous job left behind belongs to nobody
+                _ = lua.remove_app_data::<OutputBuffer>();
+                _ = lua.remove_app_data::<PendingActions>();
+                println!("*************** LUA JOB TRIGGERED ***************");
+                match job {
+                    LuaJob::OnEnter {
+                        output_handle,
+                        cursor,
+                        account_id,
+                        reply,
+                    } => {
+                        let ctx = rt_handle.block_on(LuaArgContext::new(
+                            output_handle.clone(),
+                            Some(*cursor),
+                            Some(account_id),
+                            registry.clone(),
+                            rt_handle.clone(),
+                        ));
+                        handle_room_script(&lua, &ctx, ScriptHook::OnEnter, reply);
+                    }
+                    LuaJob::OnFirstEnter {
+                        output_handle,
+                        cursor,
+                        account_id,
+                        reply,
+                    } => {
+                        let ctx = rt_handle.block_on(LuaArgContext::new(
+                            output_handle.clone(),
+                            Some(*cursor),
+                            Some(account_id),
+                            registry.clone(),
+                            rt_handle.clone(),
+                        ));
+                        handle_room_script(&lua, &ctx, ScriptHook::OnFirstEnter, reply);
+                    }
+                    LuaJob::OnLeave {
+                        output_handle,
+                        cursor,
+                        account_id,
+                        reply,
+                    } => {
+                        let ctx = rt_handle.block_on(LuaArgContext::new(
+                            output_handle.clone(),
+                            Some(*cursor),
+                            Some(account_id),
+                            registry.clone(),
+                            rt_handle.clone(),
+                        ));
+                        handle_room_script(&lua, &ctx, ScriptHook::OnLeave, reply);
+                    }
+                    LuaJob::OnObject {
+                        output_handle,
+                        cursor,
+                        account_id,
+                        intent,
+                        obj,
+                        reply,
+                    } => {
+                        let ctx = rt_handle.block_on(LuaArgContext::new(
+                            output_handle.clone(),
+                            Some(*cursor),
+                            Some(account_id),
+                            registry.clone(),
+                            rt_handle.clone(),
+                        ));
+                        handle_object_script(&lua, &ctx, &intent, &obj, reply);
+                    }
+                    LuaJob::OnCommand {
+                        output_handle,
+                        cursor,
+                        account_id,
+                        intent,
+                        reply,
+                    } => {
+                        let ctx = rt_handle.block_on(LuaArgContext::new(
+                            output_handle.clone(),
+                            Some(*cursor),
+                            Some(account_id),
+                            registry.clone(),
+                            rt_handle.clone(),
+                        ));
+                        handle_command_script(&lua, &ctx, &intent, reply);
+                    }
+                    LuaJob::OnCraft {
+                        output_handle,
+                        cursor,
+                        account_id,
+                        recipe,
+                        reply,
+                    } => {
+                        let ctx = rt_handle.block_on(LuaArgContext::new(
+                            output_handle.clone(),
+                            Some(*cursor),
+                            Some(account_id),
+                            registry.clone(),
+                            rt_handle.clone(),
+                        ));
+                        handle_craft_script(&lua, &ctx, &recipe, reply);
+                    }
+                    LuaJob::OnRead {
+                        output_handle,
+                        cursor,
+                        account_id,
+                        obj,
+                        reply,
+                    } => {
+                        let ctx = rt_handle.block_on(LuaArgContext::new(
+                            output_handle.clone(),
+                            Some(*cursor),
+                            Some(account_id),
+                            registry.clone(),
+                            rt_handle.clone(),
+                        ));
+                        handle_read_script(&lua, &ctx, &obj, reply);
+                    }
+                    LuaJob::OnReceive {
+                        output_handle,
+                        cursor,
+                        account_id,
+                        obj,
+                        item,
+                        quantity,
+                        reply,
+                    } => {
+                        let ctx = rt_handle.block_on(LuaArgContext::new(
+                            output_handle.clone(),
+                            Some(*cursor),
+                            Some(account_id),
+                            registry.clone(),
+                            rt_handle.clone(),
+                        ));
+                        handle_receive_script(&lua, &ctx, &obj, &item, quantity, reply);
+                    }
+                    LuaJob::OnTerminal {
+                        output_handle,
+                        cursor,
+                        account_id,
+                        obj,
+                        line,
+                        reply,
+                    } => {
+                        let ctx = rt_handle.block_on(LuaArgContext::new(
+                            output_handle.clone(),
+                            Some(*cursor),
+                            Some(account_id),
+                            registry.clone(),
+                            rt_handle.clone(),
+                        ));
+                        handle_terminal_script(&lua, &ctx, &obj, line.as_deref(), reply);
+                    }
+                    LuaJob::ReplEval {
+                        output_handle,
+                        cursor,
+                        account_id,
+                        code,
+                        reply,
+                    } => {
+                        let ctx = rt_handle.block_on(LuaArgContext::new(
+                            output_handle.clone(),
+                            Some(*cursor),
+                            Some(account_id),
+                            registry.clone(),
+                            rt_handle.clone(),
+                        ));
+                        _ = handle_repl_eval(&lua, &ctx, &code, reply);
+                    }
+                    LuaJob::Scheduled {
+                        realm_id,
+                        name,
+                        code,
+                        reply,
+                    } => {
+                        handle_scheduled_script(&lua, &registry, &rt_handle, realm_id, &name, &code, reply);
+                    }
+                };
+            }
+        })
+        .expect("cannot spawn lua worker");
 
     tx
 }
@@ -341,11 +659,35 @@ impl Clone for LuaArgContext {
 fn create_lua_env(lua: &Lua, arg_ctx: &LuaArgContext) -> mlua::Result<Table> {
     let env = lua.create_table()?;
 
+    let port4k = create_port4k_function_table(lua, arg_ctx)?;
+
+    // Rooms written for an older API still get the names it had
     let mt = lua.create_table()?;
-    mt.set("__index", lua.globals())?;
+    match arg_ctx
+        .cursor
+        .as_ref()
+        .filter(|c| compat::needs_shims(c.room.blueprint.api_version))
+    {
+        Some(cursor) => compat::install(lua, &mt, &port4k, &cursor.room)?,
+        None => mt.set("__index", lua.globals())?,
+    }
     _ = env.set_metatable(Some(mt));
 
-    env.set("port4k", create_port4k_function_table(lua, arg_ctx)?)?;
+    env.set("port4k", port4k)?;
+
+    // Only the modules of the room's own blueprint can be required
+    let require = match arg_ctx.cursor.as_ref() {
+        Some(cursor) => {
+            modules::create_require(lua, &arg_ctx.registry, &arg_ctx.rt_handle, cursor.room.blueprint.bp_id)?
+        }
+        None => lua.create_function(|_, _: String| -> mlua::Result<()> {
+            Err(LuaError::RuntimeError(
+                "require is only available in room scripts".into(),
+            ))
+        })?,
+    };
+    env.set("require", require)?;
+    modules::set_current_env(lua, &env)?;
 
     // if let Some(account) = arg_ctx.account.as_ref() {
     //     env.set("account", create_lua_account_table(lua, &account)?)?;
@@ -364,45 +706,119 @@ pub fn init_lua() -> anyhow::Result<Lua> {
     Ok(lua)
 }
 
+/// Functions in the `port4k` table of room and object scripts. Keep in sync with
+/// `create_port4k_function_table`; the linter warns about calls to anything else.
+pub const PORT4K_FUNCTIONS: &[&str] = &[
+    "say",
+    "print",
+    "flush",
+    "play_sound",
+    "debug",
+    "broadcast",
+    "set_exit_locked",
+    "set_exit_hidden",
+    "redirect_exit",
+    "lockdown",
+    "move_vehicle",
+    "complete_tutorial",
+    "complete_realm",
+    "is_exit_locked",
+    "set_object_state",
+    "set_object_state_shared",
+    "cas_object_state_shared",
+    "toggle_object_state_shared",
+    "spawn_object",
+    "despawn_object",
+    "move_player",
+    "move_all_players",
+    "force_command",
+    "sequence",
+    "world_time",
+    "zone_state",
+    "set_zone_state",
+    "zone_set",
+    "feature_enabled",
+    "hint_trigger",
+    "hint_consider",
+    "matches_noun",
+    "current_room",
+    "players_in_room",
+    "send_to",
+    "counter",
+    "counter_incr",
+    "random",
+    "player_has_item",
+    "item_condition",
+    "wear_item",
+    "repair_item",
+    "consume_item",
+    "give_item_to_player",
+    "balance",
+    "credit",
+    "debit",
+];
+
 fn create_port4k_function_table(lua: &Lua, arg_ctx: &LuaArgContext) -> mlua::Result<Table> {
     let port4k = lua.create_table()?;
 
     // port4k.say(text)
-    let ctx = arg_ctx.clone();
     port4k.set(
         "say",
-        lua.create_function(move |_, msg: String| -> mlua::Result<()> {
-            let ctx = ctx.clone();
-            ctx.rt_handle.spawn(async move {
-                ctx.output_handle.line(msg).await;
-            });
+        lua.create_function(|lua, msg: String| -> mlua::Result<()> {
+            buffer_output(lua, |out| out.line(msg));
             Ok(())
         })?,
     )?;
 
-    // port4k.debug(var)
+    // port4k.print(...)
+    // Like Lua's print: all arguments on one line, separated by tabs
+    port4k.set(
+        "print",
+        lua.create_function(|lua, args: mlua::Variadic<mlua::Value>| -> mlua::Result<()> {
+            let parts = args.iter().map(|v| v.to_string()).collect::<mlua::Result<Vec<_>>>()?;
+            buffer_output(lua, |out| out.line(parts.join("\t")));
+            Ok(())
+        })?,
+    )?;
+
+    // port4k.flush()
+    // Sends the output so far, instead of waiting for the script to finish
     let ctx = arg_ctx.clone();
+    port4k.set(
+        "flush",
+        lua.create_function(move |lua, ()| -> mlua::Result<()> {
+            flush_output(lua, &ctx);
+            Ok(())
+        })?,
+    )?;
+
+    // port4k.play_sound(cue)
+    // Web clients play the cue, telnet clients ignore it
+    port4k.set(
+        "play_sound",
+        lua.create_function(|lua, cue: String| -> mlua::Result<()> {
+            if !is_valid_sound_cue(&cue) {
+                return Err(LuaError::external(format!("Invalid sound cue: {}", cue)));
+            }
+            buffer_output(lua, |out| out.sound(cue));
+            Ok(())
+        })?,
+    )?;
+
+    // port4k.debug(var)
     port4k.set(
         "debug",
-        lua.create_function(move |_, v: mlua::Value| {
-            let ctx = ctx.clone();
-            ctx.rt_handle.spawn(async move {
-                let dbg_out = format_lua_value(&v);
-                ctx.output_handle.line(dbg_out).await;
-            });
+        lua.create_function(|lua, v: mlua::Value| {
+            buffer_output(lua, |out| out.line(format_lua_value(&v)));
             Ok(())
         })?,
     )?;
 
     // port4k.broadcast(text)
-    let ctx = arg_ctx.clone();
     port4k.set(
         "broadcast",
-        lua.create_function(move |_, msg: String| -> mlua::Result<()> {
-            let ctx = ctx.clone();
-            ctx.rt_handle.spawn(async move {
-                ctx.output_handle.line(format!("BROADCAST: {}", msg)).await;
-            });
+        lua.create_function(|lua, msg: String| -> mlua::Result<()> {
+            buffer_output(lua, |out| out.line(format!("BROADCAST: {}", msg)));
             Ok(())
         })?,
     )?;
@@ -411,7 +827,7 @@ fn create_port4k_function_table(lua: &Lua, arg_ctx: &LuaArgContext) -> mlua::Res
     let ctx = arg_ctx.clone();
     port4k.set(
         "set_exit_locked",
-        lua.create_function(move |_, (dir, locked): (String, bool)| -> mlua::Result<()> {
+        lua.create_function(move |lua, (dir, locked): (String, bool)| -> mlua::Result<()> {
             let dir =
                 Direction::from_str(&dir).map_err(|_| LuaError::external(format!("Invalid direction: {}", dir)))?;
 
@@ -429,10 +845,200 @@ fn create_port4k_function_table(lua: &Lua, arg_ctx: &LuaArgContext) -> mlua::Res
                     .await
                     .map_err(|e| LuaError::external(format!("Failed to set exit lock: {}", e)))
             })?;
+
+            if !locked && let Some(cue) = ctx.cursor.as_ref().unwrap().room.blueprint.sounds.unlock.clone() {
+                buffer_output(lua, |out| out.sound(cue));
+            }
             Ok(())
         })?,
     )?;
 
+    // port4k.set_exit_hidden(exit: str, hidden: bool)
+    // Hides or reveals the exit for every player in the realm
+    let ctx = arg_ctx.clone();
+    port4k.set(
+        "set_exit_hidden",
+        lua.create_function(move |_, (dir, hidden): (String, bool)| -> mlua::Result<()> {
+            let dir =
+                Direction::from_str(&dir).map_err(|_| LuaError::external(format!("Invalid direction: {}", dir)))?;
+            let cursor = ctx.cursor.as_ref().unwrap();
+
+            ctx.rt_handle.block_on(async {
+                ctx.registry
+                    .services
+                    .room
+                    .set_exit_hidden(cursor.realm_id, cursor.room_id, dir, hidden)
+                    .await
+                    .map_err(|e| LuaError::external(format!("Failed to hide exit: {}", e)))?;
+                ctx.registry
+                    .refresh_room_views(cursor.realm_id, cursor.room_id)
+                    .await
+                    .map_err(|e| LuaError::external(format!("Failed to refresh room: {}", e)))
+            })
+        })?,
+    )?;
+
+    // port4k.redirect_exit(exit: str, room_key: str|nil)
+    // Lets the exit lead to another room of the realm for every player; nil restores where the
+    // blueprint has it lead
+    let ctx = arg_ctx.clone();
+    port4k.set(
+        "redirect_exit",
+        lua.create_function(
+            move |_, (dir, room_key): (String, Option<String>)| -> mlua::Result<()> {
+                let dir =
+                    Direction::from_str(&dir).map_err(|_| LuaError::external(format!("Invalid direction: {}", dir)))?;
+                let cursor = ctx.cursor.as_ref().unwrap();
+
+                ctx.rt_handle.block_on(async {
+                    ctx.registry
+                        .services
+                        .room
+                        .redirect_exit(cursor.realm_id, cursor.room_id, dir, room_key.as_deref())
+                        .await
+                        .map_err(|e| LuaError::external(format!("Failed to redirect exit: {}", e)))?;
+                    ctx.registry
+                        .refresh_room_views(cursor.realm_id, cursor.room_id)
+                        .await
+                        .map_err(|e| LuaError::external(format!("Failed to refresh room: {}", e)))
+                })
+            },
+        )?,
+    )?;
+
+    // port4k.lockdown(seconds: int) -> int|nil
+    // Seals all exits of the room for every player in the realm; 0 lifts the lockdown
+    let ctx = arg_ctx.clone();
+    port4k.set(
+        "lockdown",
+        lua.create_function(move |_, seconds: i64| -> mlua::Result<Option<i64>> {
+            if seconds < 0 {
+                return Err(LuaError::external("lockdown seconds cannot be negative"));
+            }
+
+            let realm_id = ctx.cursor.as_ref().unwrap().realm_id;
+            let room_id = ctx.cursor.as_ref().unwrap().room.blueprint.id;
+            let rt_handle = ctx.rt_handle.clone();
+            let ctx = ctx.clone();
+
+            let until = rt_handle.block_on(async {
+                ctx.registry
+                    .services
+                    .room
+                    .set_lockdown(realm_id, room_id, seconds as u64)
+                    .await
+                    .map_err(|e| LuaError::external(format!("Failed to set lockdown: {}", e)))
+            })?;
+
+            if let Some(until) = until {
+                rt_handle.spawn(lockdown::run_countdown(ctx.registry.clone(), realm_id, room_id, until));
+            }
+            Ok(until)
+        })?,
+    )?;
+
+    // port4k.move_vehicle(stop: str, vehicle: str|nil) -> bool
+    // Sends a vehicle (the current room unless a vehicle room key is given) to the stop with this
+    // button or name; false when it is already there or moving
+    let ctx = arg_ctx.clone();
+    port4k.set(
+        "move_vehicle",
+        lua.create_function(
+            move |_, (stop, vehicle_key): (String, Option<String>)| -> mlua::Result<bool> {
+                let cursor = ctx.cursor.as_ref().unwrap();
+                let realm_id = cursor.realm_id;
+                let here = cursor.room.blueprint.clone();
+                let rt_handle = ctx.rt_handle.clone();
+                let ctx = ctx.clone();
+
+                rt_handle.block_on(async {
+                    let services = &ctx.registry.services;
+                    let room = match vehicle_key {
+                        None => here,
+                        Some(key) => {
+                            let room_id = services
+                                .room
+                                .get_room_id_by_key(realm_id, &key)
+                                .await
+                                .map_err(LuaError::external)?
+                                .ok_or_else(|| LuaError::external(format!("Unknown vehicle room: {}", key)))?;
+                            services
+                                .room
+                                .blueprint_room(realm_id, room_id)
+                                .await
+                                .map_err(LuaError::external)?
+                        }
+                    };
+                    let to = room
+                        .vehicle
+                        .as_ref()
+                        .ok_or_else(|| LuaError::external(format!("Room {} is not a vehicle", room.key)))?
+                        .stop_by_button(&stop)
+                        .ok_or_else(|| LuaError::external(format!("Vehicle {} has no stop {}", room.key, stop)))?;
+
+                    let departed = vehicles::depart(&ctx.registry, realm_id, &room, to)
+                        .await
+                        .map_err(|e| LuaError::external(format!("Failed to move vehicle: {}", e)))?;
+                    Ok(departed.is_some())
+                })
+            },
+        )?,
+    )?;
+
+    // port4k.complete_tutorial() -> bool
+    // Marks the starter tutorial as finished for the player; false when it already was
+    let ctx = arg_ctx.clone();
+    port4k.set(
+        "complete_tutorial",
+        lua.create_function(move |lua, ()| -> mlua::Result<bool> {
+            let account_id = ctx
+                .account
+                .as_ref()
+                .ok_or_else(|| LuaError::external("No player to complete the tutorial for"))?
+                .id;
+            let rt_handle = ctx.rt_handle.clone();
+            let ctx = ctx.clone();
+
+            let completed = rt_handle.block_on(async {
+                ctx.registry
+                    .services
+                    .tutorial
+                    .complete(account_id)
+                    .await
+                    .map_err(|e| LuaError::external(format!("Failed to complete tutorial: {}", e)))
+            })?;
+
+            if completed {
+                buffer_output(lua, |out| {
+                    out.system("[tutorial] Tutorial completed! Type 'realms' to see where you can go next.")
+                });
+            }
+            Ok(completed)
+        })?,
+    )?;
+
+    // port4k.complete_realm() -> bool
+    // Marks the current realm as completed by the player; false when it already was
+    let ctx = arg_ctx.clone();
+    port4k.set(
+        "complete_realm",
+        lua.create_function(move |_, ()| -> mlua::Result<bool> {
+            let realm_id = ctx.cursor.as_ref().unwrap().realm_id;
+            let account_id = ctx.account.as_ref().unwrap().id;
+            let rt_handle = ctx.rt_handle.clone();
+            let ctx = ctx.clone();
+
+            rt_handle.block_on(async {
+                ctx.registry
+                    .services
+                    .realm
+                    .complete(realm_id, account_id)
+                    .await
+                    .map_err(|e| LuaError::external(format!("Failed to complete realm: {}", e)))
+            })
+        })?,
+    )?;
+
     // port4k.is_exit_locked(exit: str) -> bool
     let ctx = arg_ctx.clone();
     port4k.set(
@@ -525,20 +1131,280 @@ fn create_port4k_function_table(lua: &Lua, arg_ctx: &LuaArgContext) -> mlua::Res
         })?,
     )?;
 
+    // port4k.cas_object_state_shared(obj_key: str, key: str, expected: any, value: any) -> bool
+    // Sets shared object state only when the current value equals `expected` (nil when unset).
+    // Returns false when the value did not match. Raises a "conflict" error when concurrent
+    // updates keep racing this one.
+    let ctx = arg_ctx.clone();
+    port4k.set(
+        "cas_object_state_shared",
+        lua.create_function(
+            move |_, (obj_key, k, expected, v): (String, String, mlua::Value, mlua::Value)| {
+                let rv = &ctx.cursor.as_ref().unwrap().room;
+                let realm_id = ctx.cursor.as_ref().unwrap().realm_id;
+                let rt_handle = ctx.rt_handle.clone();
+                let ctx = ctx.clone();
+
+                rt_handle.block_on(async {
+                    let obj = rv
+                        .object_by_key(&obj_key)
+                        .ok_or_else(|| LuaError::external(format!("Object not found: {}", obj_key)))?;
+
+                    let expected = lua_value_to_json(&expected)?;
+                    let json_value = lua_value_to_json(&v)?;
+
+                    let res = ctx
+                        .registry
+                        .services
+                        .room
+                        .update_object_state_shared(realm_id, obj.id, &k, |current| {
+                            if current.unwrap_or(&serde_json::Value::Null) != &expected {
+                                return Err(DomainError::PreconditionFailed("expected value mismatch"));
+                            }
+                            Ok(json_value.clone())
+                        })
+                        .await;
+
+                    match res {
+                        Ok(_) => Ok(true),
+                        Err(DomainError::PreconditionFailed(_)) => Ok(false),
+                        Err(e) => Err(shared_state_error("set", e)),
+                    }
+                })
+            },
+        )?,
+    )?;
+
+    // port4k.toggle_object_state_shared(obj_key: str, key: str) -> bool
+    // Atomically flips a boolean shared object state (unset counts as false) and returns the new value
+    let ctx = arg_ctx.clone();
+    port4k.set(
+        "toggle_object_state_shared",
+        lua.create_function(move |_, (obj_key, k): (String, String)| {
+            let rv = &ctx.cursor.as_ref().unwrap().room;
+            let realm_id = ctx.cursor.as_ref().unwrap().realm_id;
+            let rt_handle = ctx.rt_handle.clone();
+            let ctx = ctx.clone();
+
+            rt_handle.block_on(async {
+                let obj = rv
+                    .object_by_key(&obj_key)
+                    .ok_or_else(|| LuaError::external(format!("Object not found: {}", obj_key)))?;
+
+                let new_value = ctx
+                    .registry
+                    .services
+                    .room
+                    .update_object_state_shared(realm_id, obj.id, &k, |current| {
+                        let cur = current.and_then(|v| v.as_bool()).unwrap_or(false);
+                        Ok(serde_json::Value::Bool(!cur))
+                    })
+                    .await
+                    .map_err(|e| shared_state_error("toggle", e))?;
+
+                Ok(new_value.as_bool().unwrap_or(false))
+            })
+        })?,
+    )?;
+
+    // port4k.spawn_object{ key = str, short = str, description = str?, nouns = { str }? }
+    // Puts an object into the room for everyone in the realm, until despawn_object removes it.
+    // The description defaults to the short one, the nouns to the key.
+    let ctx = arg_ctx.clone();
+    port4k.set(
+        "spawn_object",
+        lua.create_function(move |_, spec: Table| {
+            let cursor = ctx.cursor.as_ref().unwrap();
+            let key: String = spec.get("key")?;
+            let short: String = spec.get("short")?;
+            let description: Option<String> = spec.get("description")?;
+            let nouns: Option<Vec<String>> = spec.get("nouns")?;
+            let obj = SpawnedObject {
+                id: ObjectId::new(),
+                description: description.unwrap_or_else(|| short.clone()),
+                nouns: nouns.unwrap_or_else(|| vec![key.clone()]),
+                key,
+                short,
+            };
+
+            ctx.rt_handle.block_on(async {
+                ctx.registry
+                    .services
+                    .room
+                    .spawn_object(cursor.realm_id, cursor.room_id, &obj)
+                    .await
+                    .map_err(|e| LuaError::external(format!("Failed to spawn object: {}", e)))
+            })
+        })?,
+    )?;
+
+    // port4k.despawn_object(key: str) -> bool
+    // Removes an object spawned into the room; false when there was none with that key
+    let ctx = arg_ctx.clone();
+    port4k.set(
+        "despawn_object",
+        lua.create_function(move |_, key: String| {
+            let cursor = ctx.cursor.as_ref().unwrap();
+            ctx.rt_handle.block_on(async {
+                ctx.registry
+                    .services
+                    .room
+                    .despawn_object(cursor.realm_id, cursor.room_id, &key)
+                    .await
+                    .map_err(|e| LuaError::external(format!("Failed to remove object: {}", e)))
+            })
+        })?,
+    )?;
+
+    // port4k.move_player(dir_or_room: str)
+    // Moves the player through the exit in that direction, locked or not, or else to the room with
+    // that key. The move happens when the script is done and runs the room scripts as usual.
+    let ctx = arg_ctx.clone();
+    port4k.set(
+        "move_player",
+        lua.create_function(move |lua, target: String| -> mlua::Result<()> {
+            let room_id = move_target(&ctx, &target)?;
+            queue_action(lua, ScriptAction::Move(room_id))
+        })?,
+    )?;
+
+    // port4k.move_all_players(dir_or_room: str)
+    // Moves everyone in the room together, like move_player does the player (escape pods)
+    let ctx = arg_ctx.clone();
+    port4k.set(
+        "move_all_players",
+        lua.create_function(move |lua, target: String| -> mlua::Result<()> {
+            let room_id = move_target(&ctx, &target)?;
+            queue_action(lua, ScriptAction::MoveAll(room_id))
+        })?,
+    )?;
+
+    // port4k.force_command(command: str)
+    // Runs the command as if the player typed it, when the script is done. Only commands about the
+    // room itself are allowed (look, go, take, ...), never account, chat, money or admin ones.
+    port4k.set(
+        "force_command",
+        lua.create_function(|lua, raw: String| -> mlua::Result<()> {
+            check_forced_command(&raw).map_err(|e| LuaError::external(format!("Cannot force command: {}", e)))?;
+            queue_action(lua, ScriptAction::Command(sanitize_line(raw.trim())))
+        })?,
+    )?;
+
+    // port4k.sequence({ { delay = seconds?, text = str?, sound = str?, move = str?, move_all = str?, command = str? }, ... })
+    // Plays the steps one after the other once the script is done, each after waiting its delay,
+    // without holding up the script. Stops when the player leaves the room.
+    let ctx = arg_ctx.clone();
+    port4k.set(
+        "sequence",
+        lua.create_function(move |lua, spec: Vec<Table>| -> mlua::Result<()> {
+            let steps = spec
+                .iter()
+                .map(|t| sequence_step(&ctx, t))
+                .collect::<mlua::Result<Vec<_>>>()?;
+            check_sequence(&steps).map_err(|e| LuaError::external(format!("Invalid sequence: {}", e)))?;
+            queue_sequence(lua, steps)
+        })?,
+    )?;
+
+    // port4k.world_time() -> { year, month, day, hour, minute, second, weekday, timestamp, iso }
+    // The in-world time of the server clock
+    port4k.set("world_time", lua.create_function(|lua, ()| world_time_table(lua))?)?;
+
+    // port4k.zone_state(key: str) -> any
+    // Reads zone state (weather, day and night), shared by the whole realm; nil when unset
+    let ctx = arg_ctx.clone();
+    port4k.set(
+        "zone_state",
+        lua.create_function(move |lua, k: String| -> mlua::Result<mlua::Value> {
+            let realm_id = ctx.cursor.as_ref().unwrap().realm_id;
+            let rt_handle = ctx.rt_handle.clone();
+
+            let value = rt_handle.block_on(async {
+                ctx.registry
+                    .services
+                    .ambience
+                    .zone_value(realm_id, &k)
+                    .await
+                    .map_err(|e| LuaError::external(format!("Failed to read zone state: {}", e)))
+            })?;
+
+            match value {
+                Some(v) => json_to_lua(lua, &v),
+                None => Ok(mlua::Value::Nil),
+            }
+        })?,
+    )?;
+
+    // port4k.set_zone_state(key: str, value: any)
+    // Sets zone state for the whole realm; the next ambient step may overwrite it
+    let ctx = arg_ctx.clone();
+    port4k.set(
+        "set_zone_state",
+        lua.create_function(move |_, (k, v): (String, mlua::Value)| {
+            let realm_id = ctx.cursor.as_ref().unwrap().realm_id;
+            let rt_handle = ctx.rt_handle.clone();
+            let json_value = lua_value_to_json(&v)?;
+
+            rt_handle.block_on(async {
+                ctx.registry
+                    .services
+                    .ambience
+                    .set_zone_state(realm_id, &k, &json_value)
+                    .await
+                    .map_err(|e| LuaError::external(format!("Failed to set zone state: {}", e)))
+            })
+        })?,
+    )?;
+
+    // port4k.zone_set(key: str, value: any, ttl_seconds: number?)
+    // Sets zone state that reads as unset again after ttl_seconds; without a ttl it stays
+    let ctx = arg_ctx.clone();
+    port4k.set(
+        "zone_set",
+        lua.create_function(move |_, (k, v, ttl): (String, mlua::Value, Option<f64>)| {
+            let realm_id = ctx.cursor.as_ref().unwrap().realm_id;
+            let rt_handle = ctx.rt_handle.clone();
+            let json_value = lua_value_to_json(&v)?;
+            let ttl = zone_ttl(ttl)?;
+
+            rt_handle.block_on(set_zone_entry(&ctx.registry, realm_id, &k, &json_value, ttl))
+        })?,
+    )?;
+
+    // port4k.feature_enabled(name: str) -> bool
+    // Returns true when the feature flag is enabled for the current realm
+    let ctx = arg_ctx.clone();
+    port4k.set(
+        "feature_enabled",
+        lua.create_function(move |_, name: String| {
+            let realm_id = ctx.cursor.as_ref().unwrap().realm_id;
+            let rt_handle = ctx.rt_handle.clone();
+
+            rt_handle.block_on(async {
+                ctx.registry
+                    .features
+                    .enabled(&name, realm_id)
+                    .await
+                    .map_err(|e| LuaError::external(format!("Failed to read feature flag: {}", e)))
+            })
+        })?,
+    )?;
+
     // port4k.hint_trigger(hint_type: str) -> bool
     let ctx = arg_ctx.clone();
     port4k.set(
         "hint_trigger",
-        lua.create_function(move |_, trigger: String| -> mlua::Result<mlua::Value> {
+        lua.create_function(move |lua, trigger: String| -> mlua::Result<mlua::Value> {
             let rt_handle = ctx.rt_handle.clone();
             let cursor = ctx.cursor.clone();
 
-            rt_handle.block_on(async {
+            let hint = rt_handle.block_on(async {
                 let cursor = cursor.as_ref().unwrap();
-                if let Ok(Some(hint)) = ctx.registry.services.room.hint_trigger(cursor, trigger.as_str()).await {
-                    ctx.output_handle.line(hint).await;
-                }
+                ctx.registry.services.room.hint_trigger(cursor, trigger.as_str()).await
             });
+            if let Ok(Some(hint)) = hint {
+                buffer_output(lua, |out| out.line(hint));
+            }
             Ok(mlua::Value::Boolean(true))
         })?,
     )?;
@@ -546,17 +1412,18 @@ fn create_port4k_function_table(lua: &Lua, arg_ctx: &LuaArgContext) -> mlua::Res
     let ctx = arg_ctx.clone();
     port4k.set(
         "hint_consider",
-        lua.create_function(move |_, trigger: String| -> mlua::Result<mlua::Value> {
+        lua.create_function(move |lua, trigger: String| -> mlua::Result<mlua::Value> {
             let cursor = ctx.cursor.clone();
             let rt_handle = ctx.rt_handle.clone();
 
             let ctx = ctx.clone();
-            rt_handle.block_on(async {
+            let hint = rt_handle.block_on(async {
                 let cursor = cursor.as_ref().unwrap();
-                if let Ok(Some(hint)) = ctx.registry.services.room.hint_consider(cursor, trigger.as_str()).await {
-                    ctx.output_handle.line(hint).await;
-                }
+                ctx.registry.services.room.hint_consider(cursor, trigger.as_str()).await
             });
+            if let Ok(Some(hint)) = hint {
+                buffer_output(lua, |out| out.line(hint));
+            }
             Ok(mlua::Value::Boolean(true))
         })?,
     )?;
@@ -582,6 +1449,124 @@ fn create_port4k_function_table(lua: &Lua, arg_ctx: &LuaArgContext) -> mlua::Res
         })?,
     )?;
 
+    // port4k.players_in_room() -> { { name = "alice", you = true }, { name = "bob", you = false } }
+    // Players in this room of this realm, sorted by name; `you` marks the script's own player
+    let ctx = arg_ctx.clone();
+    port4k.set(
+        "players_in_room",
+        lua.create_function(move |lua, ()| -> mlua::Result<Table> {
+            let cursor = ctx
+                .cursor
+                .as_ref()
+                .ok_or_else(|| LuaError::external("players_in_room is only available in room scripts"))?;
+            let you = ctx.account.as_ref().map(|a| a.id);
+
+            let mut players = ctx.registry.players_in_realm_room(cursor.realm_id, cursor.room_id);
+            players.sort_by(|a, b| a.account.username.cmp(&b.account.username));
+            players.dedup_by_key(|p| p.account.id);
+
+            let t = lua.create_table()?;
+            for p in players {
+                let entry = lua.create_table()?;
+                entry.set("name", p.account.username.clone())?;
+                entry.set("you", Some(p.account.id) == you)?;
+                t.push(entry)?;
+            }
+            Ok(t)
+        })?,
+    )?;
+
+    // port4k.send_to(player, text) -> bool
+    // Sends a line to one player in this room; false when they are not here
+    let ctx = arg_ctx.clone();
+    port4k.set(
+        "send_to",
+        lua.create_function(move |lua, (name, msg): (String, String)| -> mlua::Result<bool> {
+            let cursor = ctx
+                .cursor
+                .as_ref()
+                .ok_or_else(|| LuaError::external("send_to is only available in room scripts"))?;
+
+            let Some(target) = ctx
+                .registry
+                .players_in_realm_room(cursor.realm_id, cursor.room_id)
+                .into_iter()
+                .find(|p| p.account.username.eq_ignore_ascii_case(&name))
+            else {
+                return Ok(false);
+            };
+
+            // The script's own player gets it in order with the rest of the script's output
+            if ctx.account.as_ref().is_some_and(|a| a.id == target.account.id) {
+                buffer_output(lua, |out| out.line(msg));
+            } else {
+                ctx.rt_handle.block_on(target.output.line(msg));
+            }
+            Ok(true)
+        })?,
+    )?;
+
+    // port4k.counter(key, scope?) -> number
+    // Current value of a realm counter, or the player's own with scope "player"; 0 when unset
+    let ctx = arg_ctx.clone();
+    port4k.set(
+        "counter",
+        lua.create_function(move |_, (key, scope): (String, Option<String>)| -> mlua::Result<i64> {
+            let (realm_id, account_id) = counter_target(&ctx, "counter", &key, scope.as_deref())?;
+
+            ctx.rt_handle.block_on(async {
+                ctx.registry
+                    .services
+                    .realm
+                    .counter(realm_id, account_id, &key)
+                    .await
+                    .map_err(|e| LuaError::external(format!("Failed to read counter: {}", e)))
+            })
+        })?,
+    )?;
+
+    // port4k.counter_incr(key, by?, scope?) -> number
+    // Adds `by` (default 1, may be negative) to a counter in one database statement, so players
+    // bumping the same counter at once never lose a count. Returns the new value.
+    let ctx = arg_ctx.clone();
+    port4k.set(
+        "counter_incr",
+        lua.create_function(
+            move |_, (key, by, scope): (String, Option<i64>, Option<String>)| -> mlua::Result<i64> {
+                let (realm_id, account_id) = counter_target(&ctx, "counter_incr", &key, scope.as_deref())?;
+
+                ctx.rt_handle.block_on(async {
+                    ctx.registry
+                        .services
+                        .realm
+                        .counter_incr(realm_id, account_id, &key, by.unwrap_or(1))
+                        .await
+                        .map_err(|e| LuaError::external(format!("Failed to increment counter: {}", e)))
+                })
+            },
+        )?,
+    )?;
+
+    // port4k.random(min, max) -> number, port4k.random(max) -> number from 1 to max
+    // Whole number from the realm's seeded generator, bounds included
+    let ctx = arg_ctx.clone();
+    port4k.set(
+        "random",
+        lua.create_function(move |_, (a, b): (i64, Option<i64>)| -> mlua::Result<i64> {
+            let realm_id = ctx
+                .cursor
+                .as_ref()
+                .ok_or_else(|| LuaError::external("random is only available in room scripts"))?
+                .realm_id;
+            let (min, max) = match b {
+                Some(b) => (a, b),
+                None if a >= 1 => (1, a),
+                None => return Err(LuaError::external("random: max must be at least 1")),
+            };
+            Ok(ctx.registry.random.range(realm_id, min, max))
+        })?,
+    )?;
+
     // port4k.player_has_item("microcell")
     let ctx = arg_ctx.clone();
     port4k.set(
@@ -605,6 +1590,66 @@ fn create_port4k_function_table(lua: &Lua, arg_ctx: &LuaArgContext) -> mlua::Res
         })?,
     )?;
 
+    // port4k.item_condition("multi_spanner") -> { durability = 3, max = 10, broken = false, ... } or nil
+    let ctx = arg_ctx.clone();
+    port4k.set(
+        "item_condition",
+        lua.create_function(move |lua, item_key: String| -> mlua::Result<mlua::Value> {
+            let realm_id = ctx.cursor.as_ref().unwrap().realm_id;
+            let account_id = ctx.account.as_ref().unwrap().id;
+            let rt_handle = ctx.rt_handle.clone();
+            let ctx = ctx.clone();
+
+            let item = rt_handle.block_on(async {
+                ctx.registry
+                    .services
+                    .inventory
+                    .find_by_key(realm_id, account_id, &item_key)
+                    .await
+                    .map_err(|e| LuaError::ExternalError(Arc::new(e)))
+            })?;
+            let Some(item) = item else {
+                return Ok(mlua::Value::Nil);
+            };
+
+            // Custom state first, so the durability fields below win
+            let t = match &item.condition {
+                Some(serde_json::Value::Object(map)) => {
+                    let t = lua.create_table()?;
+                    for (k, v) in map {
+                        t.set(k.as_str(), json_to_lua(lua, v)?)?;
+                    }
+                    t
+                }
+                _ => lua.create_table()?,
+            };
+            t.set("durability", item.durability())?;
+            t.set("max", item.max_durability)?;
+            t.set("broken", item.is_broken())?;
+            Ok(mlua::Value::Table(t))
+        })?,
+    )?;
+
+    // port4k.wear_item("multi_spanner", 2) -> remaining durability, nil when it never wears
+    let ctx = arg_ctx.clone();
+    port4k.set(
+        "wear_item",
+        lua.create_function(move |_, (item_key, amount): (String, Option<i32>)| {
+            adjust_item_durability(&ctx, &item_key, |item| -amount.unwrap_or(item.wear_per_use))
+        })?,
+    )?;
+
+    // port4k.repair_item("multi_spanner") -> remaining durability, nil when it never wears
+    let ctx = arg_ctx.clone();
+    port4k.set(
+        "repair_item",
+        lua.create_function(move |_, (item_key, amount): (String, Option<i32>)| {
+            adjust_item_durability(&ctx, &item_key, |item| {
+                amount.unwrap_or(item.max_durability.unwrap_or(0))
+            })
+        })?,
+    )?;
+
     // port4k.consume_item(id)
     let ctx = arg_ctx.clone();
     port4k.set(
@@ -654,19 +1699,142 @@ fn create_port4k_function_table(lua: &Lua, arg_ctx: &LuaArgContext) -> mlua::Res
             rt_handle.block_on(async {
                 ctx.registry
                     .services
-                    .inventory
-                    .add_item(realm_id, account_id, &item_key, qty)
+                    .inventory
+                    .add_item(realm_id, account_id, &item_key, qty)
+                    .await
+                    .map_err(|e| LuaError::external(format!("Failed to give item: {}", e)))
+            })?;
+
+            Ok(())
+        })?,
+    )?;
+
+    // port4k.balance() -> number
+    // The player's money in this realm
+    let ctx = arg_ctx.clone();
+    port4k.set(
+        "balance",
+        lua.create_function(move |_, ()| -> mlua::Result<i64> {
+            let (realm_id, account_id) = money_target(&ctx, "balance")?;
+            ctx.rt_handle.block_on(async {
+                ctx.registry
+                    .services
+                    .currency
+                    .balance(realm_id, account_id)
+                    .await
+                    .map_err(|e| LuaError::external(format!("Failed to read balance: {}", e)))
+            })
+        })?,
+    )?;
+
+    // port4k.credit(amount) -> number
+    // Adds money to the player's balance and returns the new balance
+    let ctx = arg_ctx.clone();
+    port4k.set(
+        "credit",
+        lua.create_function(move |_, amount: i64| -> mlua::Result<i64> {
+            let (realm_id, account_id) = money_target(&ctx, "credit")?;
+            ctx.rt_handle.block_on(async {
+                ctx.registry
+                    .services
+                    .currency
+                    .credit(realm_id, account_id, amount)
+                    .await
+                    .map_err(|e| LuaError::external(format!("Failed to credit: {}", e)))
+            })
+        })?,
+    )?;
+
+    // port4k.debit(amount) -> number or nil
+    // Takes money off the player's balance and returns the new balance; nil (and nothing taken)
+    // when the player cannot afford it
+    let ctx = arg_ctx.clone();
+    port4k.set(
+        "debit",
+        lua.create_function(move |_, amount: i64| -> mlua::Result<Option<i64>> {
+            let (realm_id, account_id) = money_target(&ctx, "debit")?;
+            ctx.rt_handle.block_on(async {
+                ctx.registry
+                    .services
+                    .currency
+                    .debit(realm_id, account_id, amount)
                     .await
-                    .map_err(|e| LuaError::external(format!("Failed to give item: {}", e)))
-            })?;
-
-            Ok(())
+                    .map_err(|e| LuaError::external(format!("Failed to debit: {}", e)))
+            })
         })?,
     )?;
 
     Ok(port4k)
 }
 
+/// The realm and player whose balance a money function works on
+fn money_target(ctx: &LuaArgContext, func: &str) -> mlua::Result<(RealmId, AccountId)> {
+    let realm_id = ctx
+        .cursor
+        .as_ref()
+        .ok_or_else(|| LuaError::external(format!("{} is only available in room scripts", func)))?
+        .realm_id;
+    let account = ctx
+        .account
+        .as_ref()
+        .ok_or_else(|| LuaError::external(format!("{}: no player to keep money for", func)))?;
+    Ok((realm_id, account.id))
+}
+
+/// Changes the durability of a carried item by the delta computed from it. Returns the new
+/// durability, or nil when the player does not carry the item or it never wears.
+fn adjust_item_durability(
+    ctx: &LuaArgContext,
+    item_key: &str,
+    delta: impl FnOnce(&ItemInstance) -> i32,
+) -> mlua::Result<Option<i32>> {
+    let realm_id = ctx.cursor.as_ref().unwrap().realm_id;
+    let account_id = ctx.account.as_ref().unwrap().id;
+    let inventory = ctx.registry.services.inventory.clone();
+
+    ctx.rt_handle
+        .block_on(async {
+            let Some(item) = inventory.find_by_key(realm_id, account_id, item_key).await? else {
+                return Ok(None);
+            };
+            let durability = inventory.adjust_durability(&item, delta(&item)).await?;
+            Ok(durability.map(|d| d.remaining))
+        })
+        .map_err(|e: DomainError| LuaError::ExternalError(Arc::new(e)))
+}
+
+/// Realm, and for the "player" scope the account, that a counter call works on
+fn counter_target(
+    ctx: &LuaArgContext,
+    func: &str,
+    key: &str,
+    scope: Option<&str>,
+) -> mlua::Result<(RealmId, Option<AccountId>)> {
+    if key.trim().is_empty() {
+        return Err(LuaError::external(format!("{}: counter key cannot be empty", func)));
+    }
+    let realm_id = ctx
+        .cursor
+        .as_ref()
+        .ok_or_else(|| LuaError::external(format!("{} is only available in room scripts", func)))?
+        .realm_id;
+
+    match scope.unwrap_or("realm") {
+        "realm" => Ok((realm_id, None)),
+        "player" => {
+            let account = ctx
+                .account
+                .as_ref()
+                .ok_or_else(|| LuaError::external(format!("{}: no player to keep a counter for", func)))?;
+            Ok((realm_id, Some(account.id)))
+        }
+        other => Err(LuaError::external(format!(
+            "{}: unknown scope '{}', expected \"realm\" or \"player\"",
+            func, other
+        ))),
+    }
+}
+
 fn create_lua_exit_table(lua: &Lua, exit: &ResolvedExit) -> mlua::Result<Table> {
     let et = lua.create_table()?;
     et.set("dir", exit.direction.to_string().as_str())?;
@@ -786,6 +1954,27 @@ fn create_lua_loot_table(lua: &Lua, loot: &ObjectLoot) -> mlua::Result<Table> {
     Ok(lt)
 }
 
+/// An item as offered to a script: `qty` is the number offered, not the size of the stack
+fn create_lua_item_table(lua: &Lua, item: &ItemInstance, quantity: i32) -> mlua::Result<Table> {
+    let t = lua.create_table()?;
+    t.set("key", item.item_key.as_str())?;
+    t.set("name", item.name.as_str())?;
+    t.set("short", item.short.as_str())?;
+    t.set("qty", quantity)?;
+    t.set("broken", item.is_broken())?;
+    set_lua_table_readonly!(t, lua);
+    Ok(t)
+}
+
+fn create_lua_recipe_table(lua: &Lua, recipe: &Recipe) -> mlua::Result<Table> {
+    let t = lua.create_table()?;
+    t.set("key", recipe.key.clone())?;
+    t.set("inputs", recipe.inputs.clone())?;
+    t.set("output", recipe.output_key.clone())?;
+    t.set("qty", recipe.output_qty)?;
+    Ok(t)
+}
+
 fn create_lua_intent_table(lua: &Lua, intent: &Intent) -> mlua::Result<Table> {
     let t = lua.create_table()?;
 
@@ -851,6 +2040,7 @@ fn handle_room_script(lua: &Lua, ctx: &LuaArgContext, hook: ScriptHook, reply: S
         let args = lua.create_table()?;
         args.set("account", create_lua_account_table(lua, ctx.account.as_ref().unwrap())?)?;
         args.set("room", create_lua_roomview_table(lua, &cursor.room)?)?;
+        compat::expose_args(&env, &args)?;
 
         let func: Function = lua
             .load(src)
@@ -862,7 +2052,7 @@ fn handle_room_script(lua: &Lua, ctx: &LuaArgContext, hook: ScriptHook, reply: S
         Ok(result)
     })();
 
-    send_lua_result(reply, result)
+    finish_script(lua, ctx, reply, result)
 }
 
 fn handle_object_script(
@@ -894,6 +2084,7 @@ fn handle_object_script(
         args.set("intent", create_lua_intent_table(lua, intent)?)?;
         args.set("object", create_lua_object_table(lua, obj)?)?;
         args.set("room", create_lua_roomview_table(lua, &cursor.room)?)?;
+        compat::expose_args(&env, &args)?;
 
         let func: Function = lua
             .load(src)
@@ -905,7 +2096,134 @@ fn handle_object_script(
         Ok(result)
     })();
 
-    send_lua_result(reply, result)
+    finish_script(lua, ctx, reply, result)
+}
+
+fn handle_read_script(lua: &Lua, ctx: &LuaArgContext, obj: &ResolvedObject, reply: Sender<LuaResult>) {
+    let Some(cursor) = ctx.cursor.as_ref() else {
+        let lua_result = LuaResult::Failed("No cursor available for object script".into());
+        _ = reply.send(lua_result);
+        return;
+    };
+
+    let result = (|| -> AppResult<mlua::Value> {
+        let src = obj.on_read.as_deref().unwrap_or("");
+        if src.is_empty() {
+            return Err(DomainError::Script("Empty object read script found".into()));
+        }
+
+        let env = create_lua_env(lua, ctx)?;
+
+        let args = lua.create_table()?;
+        args.set("account", create_lua_account_table(lua, ctx.account.as_ref().unwrap())?)?;
+        args.set("object", create_lua_object_table(lua, obj)?)?;
+        args.set("room", create_lua_roomview_table(lua, &cursor.room)?)?;
+        compat::expose_args(&env, &args)?;
+
+        let func: Function = lua
+            .load(src)
+            .set_name(format!("{}:on_read", obj.name))
+            .set_environment(env)
+            .eval()?;
+
+        let result = func.call(args)?;
+        Ok(result)
+    })();
+
+    finish_script(lua, ctx, reply, result)
+}
+
+fn handle_receive_script(
+    lua: &Lua,
+    ctx: &LuaArgContext,
+    obj: &ResolvedObject,
+    item: &ItemInstance,
+    quantity: i32,
+    reply: Sender<LuaResult>,
+) {
+    let Some(cursor) = ctx.cursor.as_ref() else {
+        let lua_result = LuaResult::Failed("No cursor available for object script".into());
+        _ = reply.send(lua_result);
+        return;
+    };
+
+    let result = (|| -> AppResult<mlua::Value> {
+        let src = obj.on_receive.as_deref().unwrap_or("");
+        if src.is_empty() {
+            return Err(DomainError::Script("Empty object receive script found".into()));
+        }
+
+        let env = create_lua_env(lua, ctx)?;
+
+        let args = lua.create_table()?;
+        args.set("account", create_lua_account_table(lua, ctx.account.as_ref().unwrap())?)?;
+        args.set("object", create_lua_object_table(lua, obj)?)?;
+        args.set("item", create_lua_item_table(lua, item, quantity)?)?;
+        args.set("room", create_lua_roomview_table(lua, &cursor.room)?)?;
+        compat::expose_args(&env, &args)?;
+
+        let func: Function = lua
+            .load(src)
+            .set_name(format!("{}:on_receive", obj.name))
+            .set_environment(env)
+            .eval()?;
+
+        let result = func.call(args)?;
+        Ok(result)
+    })();
+
+    finish_script(lua, ctx, reply, result)
+}
+
+fn handle_terminal_script(
+    lua: &Lua,
+    ctx: &LuaArgContext,
+    obj: &ResolvedObject,
+    line: Option<&str>,
+    reply: Sender<LuaResult>,
+) {
+    let Some(cursor) = ctx.cursor.as_ref() else {
+        let lua_result = LuaResult::Failed("No cursor available for object script".into());
+        _ = reply.send(lua_result);
+        return;
+    };
+
+    let result = (|| -> AppResult<mlua::Value> {
+        let src = obj.on_terminal.as_deref().unwrap_or("");
+        if src.is_empty() {
+            return Err(DomainError::Script("Empty object terminal script found".into()));
+        }
+
+        let env = create_lua_env(lua, ctx)?;
+
+        let args = lua.create_table()?;
+        args.set("account", create_lua_account_table(lua, ctx.account.as_ref().unwrap())?)?;
+        args.set("object", create_lua_object_table(lua, obj)?)?;
+        args.set("room", create_lua_roomview_table(lua, &cursor.room)?)?;
+        args.set("line", line)?;
+        compat::expose_args(&env, &args)?;
+
+        let func: Function = lua
+            .load(src)
+            .set_name(format!("{}:on_terminal", obj.name))
+            .set_environment(env)
+            .eval()?;
+
+        let result = func.call(args)?;
+        Ok(result)
+    })();
+
+    finish_script(lua, ctx, reply, result)
+}
+
+/// Pages returned by an on_read script: a string is a single page, a table a list of pages.
+/// Returns None for anything else (nil falls back to the static pages).
+pub fn lua_pages(value: &mlua::Value) -> Option<Vec<String>> {
+    match value {
+        mlua::Value::String(s) => Some(vec![s.to_string_lossy()]),
+        mlua::Value::Table(t) => Some(t.clone().sequence_values::<String>().filter_map(Result::ok).collect()),
+        _ => None,
+    }
 }
 
 fn handle_command_script(lua: &Lua, ctx: &LuaArgContext, intent: &Intent, reply: Sender<LuaResult>) {
@@ -928,6 +2246,7 @@ fn handle_command_script(lua: &Lua, ctx: &LuaArgContext, intent: &Intent, reply:
         let args = lua.create_table()?;
         args.set("intent", create_lua_intent_table(lua, intent)?)?;
         args.set("room", create_lua_roomview_table(lua, &cursor.room)?)?;
+        compat::expose_args(&env, &args)?;
 
         let func: Function = lua
             .load(src)
@@ -939,6 +2258,193 @@ fn handle_command_script(lua: &Lua, ctx: &LuaArgContext, intent: &Intent, reply:
         Ok(result)
     })();
 
+    finish_script(lua, ctx, reply, result)
+}
+
+fn handle_craft_script(lua: &Lua, ctx: &LuaArgContext, recipe: &Recipe, reply: Sender<LuaResult>) {
+    let Some(cursor) = ctx.cursor.as_ref() else {
+        let lua_result = LuaResult::Failed("No cursor available for room script".into());
+        _ = reply.send(lua_result);
+        return;
+    };
+
+    let result = (|| -> AppResult<mlua::Value> {
+        let binding = cursor.room.scripts.get(&ScriptHook::OnCraft);
+        let src = binding.map_or("", |s| s);
+
+        if src.is_empty() {
+            return Err(DomainError::Script("Empty craft script found".into()));
+        }
+
+        let env = create_lua_env(lua, ctx)?;
+
+        let args = lua.create_table()?;
+        args.set("account", create_lua_account_table(lua, ctx.account.as_ref().unwrap())?)?;
+        args.set("recipe", create_lua_recipe_table(lua, recipe)?)?;
+        args.set("room", create_lua_roomview_table(lua, &cursor.room)?)?;
+        compat::expose_args(&env, &args)?;
+
+        let func: Function = lua
+            .load(src)
+            .set_name(format!("{}:on_craft", cursor.room.blueprint.key))
+            .set_environment(env)
+            .eval()?;
+
+        let result = func.call(args)?;
+        Ok(result)
+    })();
+
+    finish_script(lua, ctx, reply, result)
+}
+
+/// The in-world time of the server clock as a Lua table
+fn world_time_table(lua: &Lua) -> mlua::Result<Table> {
+    let now = clock::world_time();
+    let t = lua.create_table()?;
+    t.set("year", now.year())?;
+    t.set("month", now.month())?;
+    t.set("day", now.day())?;
+    t.set("hour", now.hour())?;
+    t.set("minute", now.minute())?;
+    t.set("second", now.second())?;
+    t.set("weekday", now.weekday().number_from_monday())?;
+    t.set("timestamp", now.timestamp())?;
+    t.set("iso", now.to_rfc3339_opts(SecondsFormat::Secs, true))?;
+    Ok(t)
+}
+
+/// The `port4k` table of scheduled chunks. There is no player or room, so it only has the
+/// functions that work on the realm as a whole.
+fn create_scheduled_function_table(
+    lua: &Lua,
+    registry: &Arc<Registry>,
+    rt_handle: &Handle,
+    realm_id: RealmId,
+) -> mlua::Result<Table> {
+    let port4k = lua.create_table()?;
+
+    // port4k.announce(text)
+    // Sends a line to every player in the realm
+    let (reg, rt) = (registry.clone(), rt_handle.clone());
+    port4k.set(
+        "announce",
+        lua.create_function(move |_, msg: String| -> mlua::Result<()> {
+            let players = reg.connected_where(|_| true);
+            rt.spawn(async move {
+                for p in players {
+                    let in_realm = p.sess.read().get_cursor().is_some_and(|c| c.realm_id == realm_id);
+                    if in_realm {
+                        p.output.line(msg.clone()).await;
+                    }
+                }
+            });
+            Ok(())
+        })?,
+    )?;
+
+    port4k.set("world_time", lua.create_function(|lua, ()| world_time_table(lua))?)?;
+
+    let (reg, rt) = (registry.clone(), rt_handle.clone());
+    port4k.set(
+        "zone_state",
+        lua.create_function(move |lua, k: String| -> mlua::Result<mlua::Value> {
+            let value = rt.block_on(async {
+                reg.services
+                    .ambience
+                    .zone_value(realm_id, &k)
+                    .await
+                    .map_err(|e| LuaError::external(format!("Failed to read zone state: {}", e)))
+            })?;
+
+            match value {
+                Some(v) => json_to_lua(lua, &v),
+                None => Ok(mlua::Value::Nil),
+            }
+        })?,
+    )?;
+
+    let (reg, rt) = (registry.clone(), rt_handle.clone());
+    port4k.set(
+        "set_zone_state",
+        lua.create_function(move |_, (k, v): (String, mlua::Value)| {
+            let json_value = lua_value_to_json(&v)?;
+            rt.block_on(async {
+                reg.services
+                    .ambience
+                    .set_zone_state(realm_id, &k, &json_value)
+                    .await
+                    .map_err(|e| LuaError::external(format!("Failed to set zone state: {}", e)))
+            })
+        })?,
+    )?;
+
+    let (reg, rt) = (registry.clone(), rt_handle.clone());
+    port4k.set(
+        "zone_set",
+        lua.create_function(move |_, (k, v, ttl): (String, mlua::Value, Option<f64>)| {
+            let json_value = lua_value_to_json(&v)?;
+            let ttl = zone_ttl(ttl)?;
+            rt.block_on(set_zone_entry(&reg, realm_id, &k, &json_value, ttl))
+        })?,
+    )?;
+
+    Ok(port4k)
+}
+
+/// The ttl argument of `port4k.zone_set`: nil for no expiry, otherwise a positive number of seconds
+fn zone_ttl(ttl: Option<f64>) -> mlua::Result<Option<Duration>> {
+    ttl.map(|secs| {
+        Duration::try_from_secs_f64(secs)
+            .ok()
+            .filter(|d| !d.is_zero())
+            .ok_or_else(|| {
+                LuaError::external(format!(
+                    "zone_set: ttl must be a positive number of seconds, got {}",
+                    secs
+                ))
+            })
+    })
+    .transpose()
+}
+
+async fn set_zone_entry(
+    registry: &Registry,
+    realm_id: RealmId,
+    key: &str,
+    value: &serde_json::Value,
+    ttl: Option<Duration>,
+) -> mlua::Result<()> {
+    let ambience = &registry.services.ambience;
+    let set = match ttl {
+        Some(ttl) => ambience.set_zone_state_for(realm_id, key, value, ttl).await,
+        None => ambience.set_zone_state(realm_id, key, value).await,
+    };
+    set.map_err(|e| LuaError::external(format!("Failed to set zone state: {}", e)))
+}
+
+fn handle_scheduled_script(
+    lua: &Lua,
+    registry: &Arc<Registry>,
+    rt_handle: &Handle,
+    realm_id: RealmId,
+    name: &str,
+    code: &str,
+    reply: Sender<LuaResult>,
+) {
+    let result = (|| -> AppResult<mlua::Value> {
+        let env = lua.create_table()?;
+        let mt = lua.create_table()?;
+        mt.set("__index", lua.globals())?;
+        _ = env.set_metatable(Some(mt));
+        env.set(
+            "port4k",
+            create_scheduled_function_table(lua, registry, rt_handle, realm_id)?,
+        )?;
+        env.set("_ENV", env.clone())?;
+
+        Ok(lua.load(code).set_name(name).set_environment(env).eval()?)
+    })();
+
     send_lua_result(reply, result)
 }
 
@@ -949,6 +2455,7 @@ fn handle_repl_eval(lua: &Lua, ctx: &LuaArgContext, code: &str, reply: Sender<Lu
         lua.set_named_registry_value(REPL_ENV_KEY, env.clone())?;
         Ok::<_, mlua::Error>(env)
     })?;
+    modules::set_current_env(lua, &ctx_table)?;
 
     let result = {
         match lua
@@ -961,6 +2468,8 @@ fn handle_repl_eval(lua: &Lua, ctx: &LuaArgContext, code: &str, reply: Sender<Lu
         }
     };
 
+    flush_output(lua, ctx);
+    queue_pending_actions(lua, ctx);
     match result {
         Ok(value) => {
             _ = reply.send(LuaResult::Success(value));
@@ -973,6 +2482,137 @@ fn handle_repl_eval(lua: &Lua, ctx: &LuaArgContext, code: &str, reply: Sender<Lu
     }
 }
 
+/// Adds to the output of the running script, which is sent when the script is done
+fn buffer_output(lua: &Lua, f: impl FnOnce(&mut OutputBuffer)) {
+    if lua.app_data_ref::<OutputBuffer>().is_none() {
+        lua.set_app_data(OutputBuffer::default());
+    }
+    if let Some(mut buffer) = lua.app_data_mut::<OutputBuffer>() {
+        f(&mut buffer);
+    }
+}
+
+/// Sends the buffered output of the running script to its player
+fn flush_output(lua: &Lua, ctx: &LuaArgContext) {
+    if let Some(buffer) = lua.remove_app_data::<OutputBuffer>()
+        && !buffer.is_empty()
+    {
+        ctx.rt_handle.block_on(ctx.output_handle.flush(buffer));
+    }
+}
+
+/// Moves, commands and sequences the running script asked for (`port4k.move_player`,
+/// `port4k.force_command`, `port4k.sequence`)
+#[derive(Default)]
+struct PendingActions {
+    actions: Vec<ScriptAction>,
+    sequences: Vec<Vec<SequenceStep>>,
+}
+
+/// Adds to the pending actions of the running script, which are carried out when the script is
+/// done. There is a limit per run.
+fn add_pending(lua: &Lua, f: impl FnOnce(&mut PendingActions)) -> mlua::Result<()> {
+    let mut pending = lua.remove_app_data::<PendingActions>().unwrap_or_default();
+    let full = pending.actions.len() + pending.sequences.len() >= MAX_SCRIPT_ACTIONS;
+    if !full {
+        f(&mut pending);
+    }
+    lua.set_app_data(pending);
+    if full {
+        return Err(LuaError::external(format!(
+            "A script may move the player, force commands or start sequences at most {} times",
+            MAX_SCRIPT_ACTIONS
+        )));
+    }
+    Ok(())
+}
+
+fn queue_action(lua: &Lua, action: ScriptAction) -> mlua::Result<()> {
+    add_pending(lua, |p| p.actions.push(action))
+}
+
+fn queue_sequence(lua: &Lua, steps: Vec<SequenceStep>) -> mlua::Result<()> {
+    add_pending(lua, |p| p.sequences.push(steps))
+}
+
+/// Hands the pending actions of the running script over to be carried out for its player
+fn queue_pending_actions(lua: &Lua, ctx: &LuaArgContext) {
+    if let Some(pending) = lua.remove_app_data::<PendingActions>()
+        && let Some(cursor) = &ctx.cursor
+    {
+        let queue = &ctx.registry.script_actions;
+        queue.push(cursor.account_id, pending.actions);
+        for steps in pending.sequences {
+            queue.push_sequence(cursor.account_id, cursor.room_id, steps);
+        }
+    }
+}
+
+/// The room behind the exit in direction `target`, or else the room with key `target`
+fn move_target(ctx: &LuaArgContext, target: &str) -> mlua::Result<RoomId> {
+    let cursor = ctx.cursor.as_ref().unwrap();
+    let exit = Direction::parse(target)
+        .and_then(|dir| cursor.room.exits_by_dir.get(&dir))
+        .map(|&idx| cursor.room.exits[idx].to_room_id);
+    if let Some(room_id) = exit {
+        return Ok(room_id);
+    }
+    ctx.rt_handle
+        .block_on(async {
+            ctx.registry
+                .services
+                .room
+                .get_room_id_by_key(cursor.realm_id, target)
+                .await
+                .map_err(|e| LuaError::external(format!("Failed to find room: {}", e)))
+        })?
+        .ok_or_else(|| LuaError::external(format!("No exit or room '{}'", target)))
+}
+
+/// One step of `port4k.sequence`; moves and commands are checked now, not when they run
+fn sequence_step(ctx: &LuaArgContext, t: &Table) -> mlua::Result<SequenceStep> {
+    let delay = match t.get::<Option<f64>>("delay")? {
+        None => Duration::ZERO,
+        Some(secs) if secs.is_finite() && secs >= 0.0 => Duration::from_secs_f64(secs),
+        Some(secs) => return Err(LuaError::external(format!("Invalid delay: {}", secs))),
+    };
+    let sound: Option<String> = t.get("sound")?;
+    if let Some(cue) = sound.as_deref().filter(|cue| !is_valid_sound_cue(cue)) {
+        return Err(LuaError::external(format!("Invalid sound cue: {}", cue)));
+    }
+    let mut actions = Vec::new();
+    if let Some(target) = t.get::<Option<String>>("move")? {
+        actions.push(ScriptAction::Move(move_target(ctx, &target)?));
+    }
+    if let Some(target) = t.get::<Option<String>>("move_all")? {
+        actions.push(ScriptAction::MoveAll(move_target(ctx, &target)?));
+    }
+    if let Some(raw) = t.get::<Option<String>>("command")? {
+        check_forced_command(&raw).map_err(|e| LuaError::external(format!("Cannot force command: {}", e)))?;
+        actions.push(ScriptAction::Command(sanitize_line(raw.trim())));
+    }
+    if actions.len() > 1 {
+        return Err(LuaError::external(
+            "A sequence step either moves, moves everyone or runs a command, not more",
+        ));
+    }
+    let action = actions.pop();
+    Ok(SequenceStep {
+        delay,
+        text: t.get("text")?,
+        sound,
+        action,
+    })
+}
+
+/// Flushes the script's output and then replies, so the output comes before anything the
+/// caller sends after the reply. The script's actions come after both.
+fn finish_script(lua: &Lua, ctx: &LuaArgContext, reply: Sender<LuaResult>, result: AppResult<mlua::Value>) {
+    flush_output(lua, ctx);
+    queue_pending_actions(lua, ctx);
+    send_lua_result(reply, result)
+}
+
 fn send_lua_result(reply: Sender<LuaResult>, result: AppResult<mlua::Value>) {
     let lua_result = match result {
         // There was a value returned from lua (even if it's nil)
@@ -1072,3 +2712,35 @@ fn lua_value_to_json(value: &mlua::Value) -> mlua::Result<serde_json::Value> {
         _ => Err(LuaError::external(format!("Unsupported Lua type: {:?}", value))),
     }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn t_worker_index_realm_affinity() {
+        let realms: Vec<RealmId> = (0..64).map(|_| RealmId::new()).collect();
+        for workers in [2, 3, 8] {
+            for realm_id in &realms {
+                let idx = worker_index(*realm_id, workers);
+                assert!(idx < workers);
+                assert_eq!(worker_index(*realm_id, workers), idx);
+            }
+        }
+
+        for realm_id in &realms {
+            assert_eq!(worker_index(*realm_id, 0), 0);
+            assert_eq!(worker_index(*realm_id, 1), 0);
+        }
+    }
+
+    #[test]
+    fn t_shared_state_conflict_error() {
+        let e = DomainError::Conflict("object state 'lever' is being changed by someone else".into());
+        assert!(
+            shared_state_error("set", e).to_string().contains(
+                "Failed to set object state: conflict: object state 'lever' is being changed by someone else"
+            )
+        );
+    }
+}
@@ -0,0 +1,150 @@
+//! Actions on_command scripts hand back to the engine instead of doing the work themselves:
+//! `return { action = "move", dir = "north" }`, or a list of such tables. They become effects (see
+//! `models::effect`), carried out like those of widgets and dialogue.
+
+use crate::hardening::MAX_SCRIPT_ACTIONS;
+use crate::models::effect::Effect;
+use crate::models::types::Direction;
+use mlua::{Table, Value};
+
+/// The actions of an on_command result, None when it returned no table (true, false, nil)
+pub fn command_actions(value: &Value) -> Option<Result<Vec<Effect>, String>> {
+    let Value::Table(t) = value else {
+        return None;
+    };
+    let actions = if t.contains_key("action").unwrap_or(false) {
+        command_action(t).map(|a| vec![a])
+    } else {
+        t.clone()
+            .sequence_values::<Table>()
+            .map(|t| t.map_err(|e| e.to_string()).and_then(|t| command_action(&t)))
+            .collect()
+    };
+    Some(actions.and_then(|actions| {
+        if actions.len() > MAX_SCRIPT_ACTIONS {
+            return Err(format!("more than {} actions", MAX_SCRIPT_ACTIONS));
+        }
+        Ok(actions)
+    }))
+}
+
+fn command_action(t: &Table) -> Result<Effect, String> {
+    let field = |key: &str| -> Result<Option<String>, String> {
+        t.get::<Option<String>>(key).map_err(|e| format!("'{}': {}", key, e))
+    };
+    let required = |key: &str, action: &str| -> Result<String, String> {
+        field(key)?.ok_or_else(|| format!("'{}' needs '{}'", action, key))
+    };
+
+    let action = field("action")?.ok_or("missing 'action'")?;
+    match action.as_str() {
+        "move" => {
+            let dir = required("dir", &action)?;
+            Direction::parse(&dir)
+                .map(Effect::Move)
+                .ok_or_else(|| format!("unknown direction '{}'", dir))
+        }
+        "consume" | "give" => {
+            let item = required("item", &action)?;
+            let qty = t
+                .get::<Option<i32>>("qty")
+                .map_err(|e| format!("'qty': {}", e))?
+                .unwrap_or(1);
+            if qty < 1 {
+                return Err(format!("'qty' must be at least 1, not {}", qty));
+            }
+            Ok(match action.as_str() {
+                "consume" => Effect::TakeItem { item, qty },
+                _ => Effect::GiveItem { item, qty },
+            })
+        }
+        "say" => required("text", &action).map(Effect::Output),
+        "sound" => required("cue", &action).map(Effect::Sound),
+        "teleport" => required("room", &action).map(Effect::Teleport),
+        "lock" | "unlock" => {
+            let dir = required("dir", &action)?;
+            let dir = Direction::parse(&dir).ok_or_else(|| format!("unknown direction '{}'", dir))?;
+            Ok(Effect::LockExit {
+                dir,
+                locked: action == "lock",
+            })
+        }
+        "set" => {
+            let value: Value = t.get("value").map_err(|e| format!("'value': {}", e))?;
+            Ok(Effect::SetKv {
+                object: required("object", &action)?,
+                key: required("key", &action)?,
+                value: super::lua_value_to_json(&value).map_err(|e| format!("'value': {}", e))?,
+            })
+        }
+        other => Err(format!("unknown action '{}'", other)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use mlua::Lua;
+
+    fn actions(code: &str) -> Option<Result<Vec<Effect>, String>> {
+        let lua = Lua::new();
+        let value: Value = lua.load(code).eval().unwrap();
+        command_actions(&value)
+    }
+
+    #[test]
+    fn t_command_actions() {
+        assert_eq!(actions("return true"), None);
+        assert_eq!(
+            actions(r#"return { action = "move", dir = "n" }"#),
+            Some(Ok(vec![Effect::Move(Direction::North)]))
+        );
+        assert_eq!(
+            actions(r#"return { { action = "consume", item = "fuse" }, { action = "say", text = "Sparks fly." } }"#),
+            Some(Ok(vec![
+                Effect::TakeItem {
+                    item: "fuse".into(),
+                    qty: 1
+                },
+                Effect::Output("Sparks fly.".into()),
+            ]))
+        );
+
+        assert_eq!(
+            actions(r#"return { action = "unlock", dir = "east" }"#),
+            Some(Ok(vec![Effect::LockExit {
+                dir: Direction::East,
+                locked: false
+            }]))
+        );
+        assert_eq!(
+            actions(r#"return { action = "set", object = "lever", key = "pulled", value = true }"#),
+            Some(Ok(vec![Effect::SetKv {
+                object: "lever".into(),
+                key: "pulled".into(),
+                value: serde_json::Value::Bool(true)
+            }]))
+        );
+
+        assert_eq!(
+            actions(r#"return { action = "move", dir = "sideways" }"#),
+            Some(Err("unknown direction 'sideways'".into()))
+        );
+        assert_eq!(
+            actions(r#"return { action = "give" }"#),
+            Some(Err("'give' needs 'item'".into()))
+        );
+        assert_eq!(
+            actions(r#"return { action = "teleport" }"#),
+            Some(Err("'teleport' needs 'room'".into()))
+        );
+        assert_eq!(
+            actions(r#"return { action = "give", item = "coin", qty = 0 }"#),
+            Some(Err("'qty' must be at least 1, not 0".into()))
+        );
+        assert_eq!(
+            actions(r#"return { action = "explode" }"#),
+            Some(Err("unknown action 'explode'".into()))
+        );
+    }
+}
@@ -0,0 +1,130 @@
+//! Compatibility with older versions of the Lua API.
+//!
+//! Every room records the `api_version` its scripts were written for. Rooms on an older version
+//! get the names that have since been removed back through a shim table behind their environment.
+//! Each use is logged as a deprecation warning, once per blueprint and name, so the builder can
+//! be told what to update before the names go away for good.
+
+use crate::lua::create_lua_object_table;
+use crate::models::room::RoomView;
+use crate::models::types::BlueprintId;
+use mlua::{Lua, Table, Value};
+use parking_lot::Mutex;
+use std::collections::HashSet;
+use std::sync::LazyLock;
+
+/// Version of the Lua API this engine implements
+pub const LUA_API_VERSION: i16 = 2;
+
+/// API version of rooms that do not declare one
+pub const LEGACY_API_VERSION: i16 = 1;
+
+/// Names removed in API 2, with what to use instead
+pub const DEPRECATED_API: &[(&str, &str)] = &[
+    ("send", "port4k.say"),
+    ("say", "port4k.say"),
+    ("broadcast_room", "port4k.broadcast"),
+    ("set_exit_locked", "port4k.set_exit_locked"),
+    ("get_object", "args.room.objects[key]"),
+    ("account", "args.account"),
+    ("room", "args.room"),
+    ("intent", "args.intent"),
+    ("obj", "args.object"),
+    ("recipe", "args.recipe"),
+];
+
+/// Functions that are plain aliases of a `port4k` function
+const ALIASES: &[(&str, &str)] = &[
+    ("send", "say"),
+    ("say", "say"),
+    ("broadcast_room", "broadcast"),
+    ("set_exit_locked", "set_exit_locked"),
+];
+
+/// Fields of the `args` table that used to be globals, by their old name
+const ARGS_GLOBALS: &[(&str, &str)] = &[
+    ("account", "account"),
+    ("room", "room"),
+    ("intent", "intent"),
+    ("obj", "object"),
+    ("recipe", "recipe"),
+];
+
+/// Key in the environment's metatable under which the shim table is kept
+const SHIM_KEY: &str = "__shims";
+
+static WARNED: LazyLock<Mutex<HashSet<(BlueprintId, &'static str)>>> = LazyLock::new(Default::default);
+
+/// Whether scripts written for `api_version` need the shims
+pub fn needs_shims(api_version: i16) -> bool {
+    api_version < LUA_API_VERSION
+}
+
+/// Makes the deprecated names resolve for scripts of `room`, through the `__index` of the
+/// environment's metatable `mt`. Everything else still comes from the globals.
+pub fn install(lua: &Lua, mt: &Table, port4k: &Table, room: &RoomView) -> mlua::Result<()> {
+    let shims = lua.create_table()?;
+    for (old, new) in ALIASES {
+        shims.set(*old, port4k.get::<Value>(*new)?)?;
+    }
+
+    let objects = lua.create_table()?;
+    for obj in &room.objects {
+        objects.set(obj.key.as_str(), create_lua_object_table(lua, obj)?)?;
+    }
+    shims.set(
+        "get_object",
+        lua.create_function(move |_, key: String| objects.get::<Value>(key))?,
+    )?;
+
+    let globals = lua.globals();
+    let bp_id = room.blueprint.bp_id;
+    let room_key = room.blueprint.key.clone();
+    let lookup = shims.clone();
+    mt.set(
+        "__index",
+        lua.create_function(move |_, (_env, key): (Table, Value)| {
+            if let Value::String(name) = &key
+                && let Ok(name) = name.to_str()
+                && let Some((old, new)) = DEPRECATED_API.iter().find(|(old, _)| *old == &*name)
+            {
+                let value = lookup.raw_get::<Value>(*old)?;
+                if !value.is_nil() {
+                    warn_once(bp_id, &room_key, old, new);
+                    return Ok(value);
+                }
+            }
+            globals.raw_get::<Value>(key)
+        })?,
+    )?;
+    mt.set(SHIM_KEY, shims)?;
+    Ok(())
+}
+
+/// Makes the `args` of a script call available under their old global names, when `env` has the
+/// shims installed
+pub fn expose_args(env: &Table, args: &Table) -> mlua::Result<()> {
+    let Some(mt) = env.metatable() else {
+        return Ok(());
+    };
+    let Ok(shims) = mt.raw_get::<Table>(SHIM_KEY) else {
+        return Ok(());
+    };
+    for (old, field) in ARGS_GLOBALS {
+        shims.set(*old, args.get::<Value>(*field)?)?;
+    }
+    Ok(())
+}
+
+fn warn_once(bp_id: BlueprintId, room_key: &str, old: &'static str, new: &str) {
+    if WARNED.lock().insert((bp_id, old)) {
+        tracing::warn!(
+            blueprint = %bp_id,
+            room = room_key,
+            "Lua script uses '{}', which is deprecated; use {} and set api_version: {}",
+            old,
+            new,
+            LUA_API_VERSION
+        );
+    }
+}
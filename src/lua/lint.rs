@@ -0,0 +1,631 @@
+//! Static checks on Lua chunks, run at import and by `@bp lint`.
+//!
+//! This is not a Lua parser. A small tokenizer plus a scope tracker is enough to find the usual
+//! mistakes: globals that do not exist (typos, or the bare `send`/`room` of the old API),
+//! `port4k.*` functions that do not exist, and fields of the `args` table that are never passed to
+//! the hook. Everything it reports is a warning; whether a chunk compiles is checked elsewhere.
+
+use crate::lua::compat::{DEPRECATED_API, LUA_API_VERSION, needs_shims};
+use crate::lua::{PORT4K_FUNCTIONS, ScriptHook};
+use mlua::Lua;
+use std::collections::{HashMap, HashSet};
+use std::fmt;
+
+const KEYWORDS: &[&str] = &[
+    "and", "break", "continue", "do", "else", "elseif", "end", "false", "for", "function", "goto", "if", "in", "local",
+    "nil", "not", "or", "repeat", "return", "then", "true", "until", "while",
+];
+
+/// What a chunk is run as, which decides what is in its `args` table
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ChunkKind {
+    Room(ScriptHook),
+    OnUse,
+    OnRead,
+    OnReceive,
+    OnTerminal,
+}
+
+impl ChunkKind {
+    /// Fields of the `args` table, as set by the handlers in `lua.rs`
+    pub fn args(&self) -> &'static [&'static str] {
+        match self {
+            ChunkKind::Room(ScriptHook::OnCommand) => &["intent", "room"],
+            ChunkKind::Room(ScriptHook::OnCraft) => &["account", "recipe", "room"],
+            ChunkKind::Room(_) => &["account", "room"],
+            ChunkKind::OnUse => &["account", "intent", "object", "room"],
+            ChunkKind::OnRead => &["account", "object", "room"],
+            ChunkKind::OnReceive => &["account", "item", "object", "room"],
+            ChunkKind::OnTerminal => &["account", "line", "object", "room"],
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            ChunkKind::Room(hook) => hook.as_str(),
+            ChunkKind::OnUse => "on_use",
+            ChunkKind::OnRead => "on_read",
+            ChunkKind::OnReceive => "on_receive",
+            ChunkKind::OnTerminal => "on_terminal",
+        }
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct LintWarning {
+    pub line: usize,
+    pub message: String,
+}
+
+impl fmt::Display for LintWarning {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        write!(f, "line {}: {}", self.line, self.message)
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum Tok<'a> {
+    Name(&'a str),
+    Sym(&'a str),
+    /// Strings and numbers
+    Literal,
+}
+
+#[derive(Debug, Clone, Copy)]
+struct Token<'a> {
+    tok: Tok<'a>,
+    line: usize,
+}
+
+/// Checks `code` as a chunk run as `kind` in a room on `api_version`. The globals of `lua` count
+/// as defined.
+pub fn lint_chunk(lua: &Lua, code: &str, kind: &ChunkKind, api_version: i16) -> Vec<LintWarning> {
+    let mut known: HashSet<String> = lua
+        .globals()
+        .pairs::<String, mlua::Value>()
+        .filter_map(Result::ok)
+        .map(|(k, _)| k)
+        .collect();
+    known.extend(["port4k", "require", "_ENV"].map(String::from));
+
+    let tokens = tokenize(code);
+    Linter::new(&tokens, &known, kind, api_version).run()
+}
+
+fn tokenize(code: &str) -> Vec<Token<'_>> {
+    let bytes = code.as_bytes();
+    let mut tokens = Vec::new();
+    let mut line = 1;
+    let mut i = 0;
+
+    while i < bytes.len() {
+        let c = bytes[i];
+        let start_line = line;
+        match c {
+            b'\n' => {
+                line += 1;
+                i += 1;
+            }
+            _ if c.is_ascii_whitespace() => i += 1,
+            b'-' if bytes.get(i + 1) == Some(&b'-') => {
+                i += 2;
+                if let Some(level) = long_bracket(bytes, i) {
+                    i = skip_long(bytes, i, level, &mut line);
+                } else {
+                    while i < bytes.len() && bytes[i] != b'\n' {
+                        i += 1;
+                    }
+                }
+            }
+            b'"' | b'\'' => {
+                i += 1;
+                while i < bytes.len() && bytes[i] != c && bytes[i] != b'\n' {
+                    i += if bytes[i] == b'\\' { 2 } else { 1 };
+                }
+                i += 1;
+                tokens.push(Token {
+                    tok: Tok::Literal,
+                    line: start_line,
+                });
+            }
+            b'[' if long_bracket(bytes, i).is_some() => {
+                let level = long_bracket(bytes, i).unwrap_or_default();
+                i = skip_long(bytes, i, level, &mut line);
+                tokens.push(Token {
+                    tok: Tok::Literal,
+                    line: start_line,
+                });
+            }
+            _ if c.is_ascii_alphabetic() || c == b'_' => {
+                let start = i;
+                while i < bytes.len() && (bytes[i].is_ascii_alphanumeric() || bytes[i] == b'_') {
+                    i += 1;
+                }
+                tokens.push(Token {
+                    tok: Tok::Name(&code[start..i]),
+                    line,
+                });
+            }
+            _ if c.is_ascii_digit() || (c == b'.' && bytes.get(i + 1).is_some_and(u8::is_ascii_digit)) => {
+                while i < bytes.len() && (bytes[i].is_ascii_alphanumeric() || bytes[i] == b'.' || bytes[i] == b'_') {
+                    let exp = matches!(bytes[i], b'e' | b'E' | b'p' | b'P');
+                    i += 1;
+                    if exp && matches!(bytes.get(i), Some(b'+' | b'-')) {
+                        i += 1;
+                    }
+                }
+                tokens.push(Token {
+                    tok: Tok::Literal,
+                    line,
+                });
+            }
+            _ => {
+                let len = ["...", "..", "==", "~=", "<=", ">=", "::", "//", "<<", ">>"]
+                    .iter()
+                    .find(|s| bytes[i..].starts_with(s.as_bytes()))
+                    .map_or(1, |s| s.len());
+                // Anything outside ASCII is only valid in strings and comments
+                let len = if c.is_ascii() { len } else { 1 };
+                if let Some(sym) = code.get(i..i + len) {
+                    tokens.push(Token {
+                        tok: Tok::Sym(sym),
+                        line,
+                    });
+                }
+                i += len;
+            }
+        }
+    }
+    tokens
+}
+
+/// The level of the long bracket (`[[`, `[=[`, ...) starting at `i`
+fn long_bracket(bytes: &[u8], i: usize) -> Option<usize> {
+    if bytes.get(i) != Some(&b'[') {
+        return None;
+    }
+    let level = bytes[i + 1..].iter().take_while(|b| **b == b'=').count();
+    (bytes.get(i + 1 + level) == Some(&b'[')).then_some(level)
+}
+
+/// Skips the long string or comment at `i`; returns the index after its closing bracket
+fn skip_long(bytes: &[u8], i: usize, level: usize, line: &mut usize) -> usize {
+    let close: Vec<u8> = std::iter::once(b']')
+        .chain(std::iter::repeat_n(b'=', level))
+        .chain(std::iter::once(b']'))
+        .collect();
+    let mut j = i + level + 2;
+    while j < bytes.len() {
+        if bytes[j..].starts_with(&close) {
+            return j + close.len();
+        }
+        if bytes[j] == b'\n' {
+            *line += 1;
+        }
+        j += 1;
+    }
+    j
+}
+
+/// A local, and whether it is the `args` parameter of the returned function
+type Scope<'a> = HashMap<&'a str, bool>;
+
+struct Linter<'t, 'a> {
+    tokens: &'t [Token<'a>],
+    known: &'t HashSet<String>,
+    kind: &'t ChunkKind,
+    api_version: i16,
+    scopes: Vec<Scope<'a>>,
+    /// Globals the chunk assigns itself
+    assigned: HashSet<&'a str>,
+    /// Open brackets, to tell table fields from assignments
+    brackets: Vec<&'a str>,
+    /// `for` loops whose `do` has not been seen yet; their scope is already open
+    pending_for: usize,
+    reported: HashSet<String>,
+    warnings: Vec<LintWarning>,
+}
+
+impl<'t, 'a> Linter<'t, 'a> {
+    fn new(tokens: &'t [Token<'a>], known: &'t HashSet<String>, kind: &'t ChunkKind, api_version: i16) -> Self {
+        Self {
+            tokens,
+            known,
+            kind,
+            api_version,
+            scopes: vec![Scope::new()],
+            assigned: HashSet::new(),
+            brackets: Vec::new(),
+            pending_for: 0,
+            reported: HashSet::new(),
+            warnings: Vec::new(),
+        }
+    }
+
+    fn tok(&self, i: usize) -> Option<Tok<'a>> {
+        self.tokens.get(i).map(|t| t.tok)
+    }
+
+    fn is_sym(&self, i: usize, sym: &str) -> bool {
+        self.tok(i) == Some(Tok::Sym(sym))
+    }
+
+    fn name(&self, i: usize) -> Option<&'a str> {
+        match self.tok(i) {
+            Some(Tok::Name(n)) if !KEYWORDS.contains(&n) => Some(n),
+            _ => None,
+        }
+    }
+
+    fn warn(&mut self, i: usize, key: String, message: String) {
+        if self.reported.insert(key) {
+            let line = self.tokens.get(i).map_or(0, |t| t.line);
+            self.warnings.push(LintWarning { line, message });
+        }
+    }
+
+    fn declare(&mut self, name: &'a str, is_args: bool) {
+        if let Some(scope) = self.scopes.last_mut() {
+            scope.insert(name, is_args);
+        }
+    }
+
+    fn lookup(&self, name: &str) -> Option<bool> {
+        self.scopes.iter().rev().find_map(|s| s.get(name).copied())
+    }
+
+    fn pop_scope(&mut self) {
+        if self.scopes.len() > 1 {
+            self.scopes.pop();
+        }
+    }
+
+    /// Names assigned without `local` anywhere in the chunk: `x = ...`, `a, b = ...` and
+    /// `function x()`. Scripts may use them before the assignment, e.g. in a helper function.
+    fn collect_assigned(&mut self) {
+        let mut brackets: Vec<&str> = Vec::new();
+        for i in 0..self.tokens.len() {
+            match self.tok(i) {
+                Some(Tok::Sym(s @ ("{" | "(" | "["))) => brackets.push(s),
+                Some(Tok::Sym("}" | ")" | "]")) => {
+                    brackets.pop();
+                }
+                _ => {}
+            }
+            let Some(name) = self.name(i) else { continue };
+            if matches!(
+                self.tok(i.wrapping_sub(1)),
+                Some(Tok::Sym("." | ":" | "::")) | Some(Tok::Name("goto"))
+            ) {
+                continue;
+            }
+            if self.tok(i.wrapping_sub(1)) == Some(Tok::Name("function")) && self.is_sym(i + 1, "(") {
+                self.assigned.insert(name);
+                continue;
+            }
+            if brackets.last() == Some(&"{") {
+                continue;
+            }
+            // Skip over `, name` to find a multiple assignment
+            let mut j = i + 1;
+            while self.is_sym(j, ",") && self.name(j + 1).is_some() {
+                j += 2;
+            }
+            if self.is_sym(j, "=") {
+                self.assigned.insert(name);
+            }
+        }
+    }
+
+    fn run(mut self) -> Vec<LintWarning> {
+        self.collect_assigned();
+
+        let mut i = 0;
+        while i < self.tokens.len() {
+            i = self.step(i);
+        }
+        self.warnings
+    }
+
+    /// Handles the token at `i`; returns the index of the next one to look at
+    fn step(&mut self, i: usize) -> usize {
+        let Some(tok) = self.tok(i) else {
+            return i + 1;
+        };
+        match tok {
+            Tok::Literal => i + 1,
+            Tok::Sym(s) => {
+                match s {
+                    "{" | "(" | "[" => self.brackets.push(s),
+                    "}" | ")" | "]" => {
+                        self.brackets.pop();
+                    }
+                    "::" => return i + 3, // ::label::
+                    _ => {}
+                }
+                i + 1
+            }
+            Tok::Name(word) => match word {
+                "local" => self.local(i),
+                "function" => self.function(i),
+                "for" => {
+                    self.scopes.push(Scope::new());
+                    self.pending_for += 1;
+                    let mut j = i + 1;
+                    while let Some(name) = self.name(j) {
+                        self.declare(name, false);
+                        j += if self.is_sym(j + 1, ",") { 2 } else { 1 };
+                    }
+                    j
+                }
+                "do" => {
+                    if self.pending_for > 0 {
+                        self.pending_for -= 1;
+                    } else {
+                        self.scopes.push(Scope::new());
+                    }
+                    i + 1
+                }
+                "then" | "repeat" => {
+                    self.scopes.push(Scope::new());
+                    i + 1
+                }
+                "else" => {
+                    self.pop_scope();
+                    self.scopes.push(Scope::new());
+                    i + 1
+                }
+                "elseif" | "end" | "until" => {
+                    self.pop_scope();
+                    i + 1
+                }
+                "goto" => i + 2,
+                _ if KEYWORDS.contains(&word) => i + 1,
+                name => {
+                    self.reference(i, name);
+                    i + 1
+                }
+            },
+        }
+    }
+
+    fn local(&mut self, i: usize) -> usize {
+        if self.tok(i + 1) == Some(Tok::Name("function")) {
+            if let Some(name) = self.name(i + 2) {
+                self.declare(name, false);
+            }
+            return i + 1;
+        }
+
+        // local a <const>, b = ...
+        let mut j = i + 1;
+        let mut names = Vec::new();
+        while let Some(name) = self.name(j) {
+            names.push(name);
+            j += 1;
+            if self.is_sym(j, "<") {
+                j += 3;
+            }
+            if !self.is_sym(j, ",") {
+                break;
+            }
+            j += 1;
+        }
+
+        // The names are only in scope after the values, so look at those first
+        if self.is_sym(j, "=") {
+            j += 1;
+            let (depth, scopes) = (self.brackets.len(), self.scopes.len());
+            while j < self.tokens.len() {
+                if self.brackets.len() == depth && self.scopes.len() == scopes && self.ends_expression(j) {
+                    break;
+                }
+                j = self.step(j);
+            }
+        }
+        for name in names {
+            self.declare(name, false);
+        }
+        j
+    }
+
+    /// Whether the token at `j` starts a new statement after an expression list
+    fn ends_expression(&self, j: usize) -> bool {
+        match self.tok(j) {
+            Some(Tok::Name(n)) if KEYWORDS.contains(&n) => {
+                !matches!(n, "and" | "or" | "not" | "nil" | "true" | "false" | "function")
+            }
+            Some(Tok::Name(_)) => matches!(
+                self.tok(j - 1),
+                Some(Tok::Name(_) | Tok::Literal) | Some(Tok::Sym(")" | "]" | "}"))
+            ),
+            Some(Tok::Sym(";")) => true,
+            _ => false,
+        }
+    }
+
+    fn function(&mut self, i: usize) -> usize {
+        let returned = self.scopes.len() == 1 && self.tok(i.wrapping_sub(1)) == Some(Tok::Name("return"));
+
+        // function a.b.c:d(...) - only `a` is a reference
+        let mut j = i + 1;
+        let mut method = false;
+        if let Some(name) = self.name(j) {
+            if self.is_sym(j + 1, ".") || self.is_sym(j + 1, ":") {
+                self.reference(j, name);
+            }
+            j += 1;
+            while (self.is_sym(j, ".") || self.is_sym(j, ":")) && self.name(j + 1).is_some() {
+                method |= self.is_sym(j, ":");
+                j += 2;
+            }
+        }
+
+        self.scopes.push(Scope::new());
+        if method {
+            self.declare("self", false);
+        }
+        if self.is_sym(j, "(") {
+            j += 1;
+            let mut first = true;
+            while !self.is_sym(j, ")") && j < self.tokens.len() {
+                if let Some(param) = self.name(j) {
+                    self.declare(param, returned && first);
+                    first = false;
+                }
+                j += 1;
+            }
+            j += 1;
+        }
+        j
+    }
+
+    fn reference(&mut self, i: usize, name: &'a str) {
+        match self.tok(i.wrapping_sub(1)) {
+            Some(Tok::Sym(".")) | Some(Tok::Sym(":")) => return,
+            _ => {}
+        }
+        // Field names in table constructors: { name = ... }
+        if self.brackets.last() == Some(&"{") && self.is_sym(i + 1, "=") {
+            return;
+        }
+
+        match self.lookup(name) {
+            Some(true) => self.args_field(i, name),
+            Some(false) => {}
+            None if name == "port4k" => self.port4k_field(i),
+            None if self.assigned.contains(name) || self.known.contains(name) => {}
+            None => {
+                let message = match DEPRECATED_API.iter().find(|(old, _)| *old == name) {
+                    Some((_, new)) if needs_shims(self.api_version) => {
+                        format!("'{}' is deprecated, use {} instead", name, new)
+                    }
+                    Some((_, new)) => format!(
+                        "'{}' is not available in api_version {}, use {} instead",
+                        name, LUA_API_VERSION, new
+                    ),
+                    None => format!("undefined global '{}'", name),
+                };
+                self.warn(i, format!("global:{}", name), message);
+            }
+        }
+    }
+
+    fn port4k_field(&mut self, i: usize) {
+        let Some(Tok::Sym(sep @ ("." | ":"))) = self.tok(i + 1) else {
+            return;
+        };
+        let Some(Tok::Name(func)) = self.tok(i + 2) else {
+            return;
+        };
+        if !PORT4K_FUNCTIONS.contains(&func) {
+            self.warn(
+                i,
+                format!("port4k:{}", func),
+                format!("port4k.{} is not a port4k function", func),
+            );
+        } else if sep == ":" {
+            self.warn(
+                i,
+                format!("port4k:{}", func),
+                format!("call port4k.{} with '.', not ':'", func),
+            );
+        }
+    }
+
+    fn args_field(&mut self, i: usize, args: &str) {
+        if !self.is_sym(i + 1, ".") {
+            return;
+        }
+        let Some(Tok::Name(field)) = self.tok(i + 2) else {
+            return;
+        };
+        let fields = self.kind.args();
+        if !fields.contains(&field) {
+            self.warn(
+                i,
+                format!("args:{}", field),
+                format!(
+                    "{}.{} is not passed to {} scripts (they get {})",
+                    args,
+                    field,
+                    self.kind.as_str(),
+                    fields.join(", ")
+                ),
+            );
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn lint(code: &str, kind: ChunkKind) -> Vec<String> {
+        lint_chunk(&Lua::new(), code, &kind, LUA_API_VERSION)
+            .into_iter()
+            .map(|w| w.to_string())
+            .collect()
+    }
+
+    #[test]
+    fn t_lint_clean_chunk() {
+        let code = r#"
+            -- helpers may be global
+            function greet(name) return "hi " .. name end
+            local count = 0
+            return function(ctx)
+                local verb = ctx.intent.verb
+                local t = { say = 1, [verb] = true }
+                for i, v in ipairs(ctx.intent.args or {}) do
+                    count = count + i + #v
+                end
+                if verb == "use" then
+                    local s = [[ send(not_a_global) ]]
+                    port4k.say(greet(ctx.account.username) .. s .. 'room')
+                elseif t.say then
+                    port4k.set_object_state("lever", "pulled", true)
+                else
+                    goto done
+                end
+                ::done::
+                return string.format("%d", count) ~= nil
+            end
+        "#;
+        assert_eq!(lint(code, ChunkKind::OnUse), Vec::<String>::new());
+    }
+
+    #[test]
+    fn t_lint_reports_problems() {
+        let code = "return function(args)\n  send('hi')\n  port4k.teleport(args.obj)\n  local x = undefined_thing\n  port4k:say(room.key)\nend\n";
+        let warnings = lint(code, ChunkKind::OnUse);
+        assert_eq!(
+            warnings,
+            vec![
+                "line 2: 'send' is not available in api_version 2, use port4k.say instead",
+                "line 3: port4k.teleport is not a port4k function",
+                "line 3: args.obj is not passed to on_use scripts (they get account, intent, object, room)",
+                "line 4: undefined global 'undefined_thing'",
+                "line 5: call port4k.say with '.', not ':'",
+                "line 5: 'room' is not available in api_version 2, use args.room instead",
+            ]
+        );
+    }
+
+    #[test]
+    fn t_lint_legacy_api() {
+        let warnings = lint_chunk(&Lua::new(), "send('hi')", &ChunkKind::OnUse, 1);
+        assert_eq!(warnings[0].message, "'send' is deprecated, use port4k.say instead");
+    }
+
+    #[test]
+    fn t_lint_args_per_hook() {
+        let code = "return function(a) return a.intent.verb end";
+        assert!(lint(code, ChunkKind::Room(ScriptHook::OnCommand)).is_empty());
+        assert_eq!(lint(code, ChunkKind::Room(ScriptHook::OnEnter)).len(), 1);
+
+        // A local with the same name hides the args table
+        let code = "return function(a) do local a = {} ; return a.whatever end end";
+        assert!(lint(code, ChunkKind::OnRead).is_empty());
+    }
+}
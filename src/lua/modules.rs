@@ -0,0 +1,148 @@
+//! `require` for the Lua modules a blueprint ships in its `lib/` folder.
+//!
+//! Scripts only see the modules of their own blueprint. A module runs once per Lua worker and its
+//! return value is cached, until the module's source changes with a new import. Modules have
+//! their own environment for the globals they define; anything else, `port4k` included, is looked
+//! up in the environment of the script that is running at the time of the lookup.
+
+use crate::Registry;
+use crate::models::types::BlueprintId;
+use mlua::prelude::LuaError;
+use mlua::{Function, Lua, Table, Value};
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
+use tokio::runtime::Handle;
+
+/// Registry key of the environment of the script that is running
+const CURRENT_ENV_KEY: &str = "__current_env";
+
+/// Longest module name
+const MAX_NAME_LEN: usize = 64;
+
+#[derive(Default)]
+struct ModuleCache {
+    /// Source and return value of every module loaded on this worker
+    loaded: HashMap<(BlueprintId, String), (String, Value)>,
+    /// Modules that are being loaded, to catch require cycles
+    loading: HashSet<(BlueprintId, String)>,
+}
+
+/// Module names are what `require` takes: letters, digits and `_`, as the file stem in `lib/`
+pub fn is_valid_name(name: &str) -> bool {
+    !name.is_empty() && name.len() <= MAX_NAME_LEN && name.chars().all(|c| c.is_ascii_alphanumeric() || c == '_')
+}
+
+/// Marks `env` as the environment modules look up their globals in
+pub(super) fn set_current_env(lua: &Lua, env: &Table) -> mlua::Result<()> {
+    lua.set_named_registry_value(CURRENT_ENV_KEY, env.clone())
+}
+
+/// `require` for the scripts of blueprint `bp_id`
+pub(super) fn create_require(
+    lua: &Lua,
+    registry: &Arc<Registry>,
+    rt_handle: &Handle,
+    bp_id: BlueprintId,
+) -> mlua::Result<Function> {
+    let (reg, rt) = (registry.clone(), rt_handle.clone());
+    lua.create_function(move |lua, name: String| -> mlua::Result<Value> {
+        if !is_valid_name(&name) {
+            return Err(LuaError::RuntimeError(format!("invalid module name '{}'", name)));
+        }
+
+        let source = rt
+            .block_on(reg.services.blueprint.lua_module(bp_id, &name))
+            .map_err(|e| LuaError::external(format!("Failed to load module: {}", e)))?
+            .ok_or_else(|| LuaError::RuntimeError(format!("module '{}' not found in lib/", name)))?;
+
+        let key = (bp_id, name);
+        {
+            if lua.app_data_ref::<ModuleCache>().is_none() {
+                lua.set_app_data(ModuleCache::default());
+            }
+            let mut cache = lua
+                .app_data_mut::<ModuleCache>()
+                .ok_or_else(|| LuaError::RuntimeError("module cache unavailable".into()))?;
+            if let Some((cached_source, value)) = cache.loaded.get(&key)
+                && *cached_source == source
+            {
+                return Ok(value.clone());
+            }
+            if !cache.loading.insert(key.clone()) {
+                return Err(LuaError::RuntimeError(format!("require cycle at module '{}'", key.1)));
+            }
+        }
+
+        // The cache is not borrowed while the module runs, as it may require others
+        let result = run_module(lua, &key.1, &source);
+
+        let mut cache = lua
+            .app_data_mut::<ModuleCache>()
+            .ok_or_else(|| LuaError::RuntimeError("module cache unavailable".into()))?;
+        cache.loading.remove(&key);
+        let value = result?;
+        cache.loaded.insert(key, (source, value.clone()));
+        Ok(value)
+    })
+}
+
+fn run_module(lua: &Lua, name: &str, source: &str) -> mlua::Result<Value> {
+    let env = lua.create_table()?;
+    let mt = lua.create_table()?;
+    mt.set(
+        "__index",
+        lua.create_function(|lua, (_env, key): (Table, Value)| {
+            let current: Table = lua.named_registry_value(CURRENT_ENV_KEY)?;
+            current.get::<Value>(key)
+        })?,
+    )?;
+    _ = env.set_metatable(Some(mt));
+
+    let value: Value = lua
+        .load(source)
+        .set_name(format!("lib/{}.lua", name))
+        .set_environment(env)
+        .call(())?;
+
+    // Like Lua's require, a module that returns nothing is loaded as `true`
+    Ok(if value.is_nil() { Value::Boolean(true) } else { value })
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn t_module_names() {
+        assert!(is_valid_name("inventory_utils"));
+        assert!(is_valid_name("v2"));
+        assert!(!is_valid_name(""));
+        assert!(!is_valid_name("../secrets"));
+        assert!(!is_valid_name("a.b"));
+        assert!(!is_valid_name(&"x".repeat(MAX_NAME_LEN + 1)));
+    }
+
+    #[test]
+    fn t_module_environment() {
+        let lua = Lua::new();
+        let env = lua.create_table().unwrap();
+        env.set("greeting", "hi").unwrap();
+        set_current_env(&lua, &env).unwrap();
+
+        let module = run_module(
+            &lua,
+            "greeter",
+            "helper = 1\nreturn { hello = function() return greeting .. helper end }",
+        )
+        .unwrap();
+        let hello: Function = module.as_table().unwrap().get("hello").unwrap();
+        assert_eq!(hello.call::<String>(()).unwrap(), "hi1");
+        // The module's own globals stay out of the script's environment
+        assert!(env.get::<Value>("helper").unwrap().is_nil());
+
+        assert!(matches!(
+            run_module(&lua, "empty", "local x = 1").unwrap(),
+            Value::Boolean(true)
+        ));
+    }
+}
@@ -0,0 +1,208 @@
+//! Job queue of a Lua worker.
+//!
+//! Every worker has two channels: one for interactive jobs, run for a player who is waiting for
+//! the result, and one for background jobs like scheduled events. A worker always takes the next
+//! interactive job first, so a realm with a lot of background scripting does not slow down the
+//! commands of its players. A job that is already running is never interrupted.
+//!
+//! Queues are bounded. A job that finds no room in time, or whose session already has too many
+//! jobs queued or running, is turned away with [`LuaSendError::Busy`] instead of stalling the
+//! command that sent it.
+
+use crate::lua::LuaJob;
+use crate::models::types::AccountId;
+use parking_lot::Mutex;
+use std::collections::HashMap;
+use std::sync::Arc;
+use thiserror::Error;
+use tokio::runtime::Handle;
+use tokio::sync::mpsc;
+
+/// Jobs each channel holds before senders have to wait
+const QUEUE_SIZE: usize = 64;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum LuaPriority {
+    /// A player is waiting for the result
+    Interactive,
+    /// Nobody is waiting: timers, scheduled events, NPC ticks
+    Background,
+}
+
+#[derive(Debug, Error)]
+pub enum LuaSendError {
+    #[error("The world is busy, please try again in a moment.")]
+    Busy,
+    #[error("the Lua worker has stopped")]
+    Closed,
+}
+
+/// A job in a queue, with the in-flight slot of its session
+pub struct QueuedJob {
+    pub job: LuaJob,
+    pub slot: Option<InFlightSlot>,
+}
+
+/// Jobs each session has queued or running
+#[derive(Default)]
+pub struct InFlight {
+    per_account: Mutex<HashMap<AccountId, usize>>,
+}
+
+impl InFlight {
+    /// Takes a slot for `account_id`, None when it already holds `limit` of them
+    pub fn acquire(self: &Arc<Self>, account_id: AccountId, limit: usize) -> Option<InFlightSlot> {
+        let mut per_account = self.per_account.lock();
+        let count = per_account.entry(account_id).or_default();
+        if *count >= limit {
+            return None;
+        }
+        *count += 1;
+        Some(InFlightSlot {
+            in_flight: self.clone(),
+            account_id,
+        })
+    }
+
+    /// Number of sessions with jobs in flight, and the number of those jobs
+    pub fn totals(&self) -> (usize, usize) {
+        let per_account = self.per_account.lock();
+        (per_account.len(), per_account.values().sum())
+    }
+}
+
+/// Held while a job is queued or running; frees the slot when dropped
+pub struct InFlightSlot {
+    in_flight: Arc<InFlight>,
+    account_id: AccountId,
+}
+
+impl Drop for InFlightSlot {
+    fn drop(&mut self) {
+        let mut per_account = self.in_flight.per_account.lock();
+        if let Some(count) = per_account.get_mut(&self.account_id) {
+            *count -= 1;
+            if *count == 0 {
+                per_account.remove(&self.account_id);
+            }
+        }
+    }
+}
+
+/// Sending half of a worker's queue
+#[derive(Clone)]
+pub struct JobSender {
+    interactive: mpsc::Sender<QueuedJob>,
+    background: mpsc::Sender<QueuedJob>,
+}
+
+/// Receiving half of a worker's queue
+pub struct JobReceiver {
+    interactive: mpsc::Receiver<QueuedJob>,
+    background: mpsc::Receiver<QueuedJob>,
+}
+
+pub fn job_queue() -> (JobSender, JobReceiver) {
+    let (itx, irx) = mpsc::channel(QUEUE_SIZE);
+    let (btx, brx) = mpsc::channel(QUEUE_SIZE);
+    (
+        JobSender {
+            interactive: itx,
+            background: btx,
+        },
+        JobReceiver {
+            interactive: irx,
+            background: brx,
+        },
+    )
+}
+
+impl JobSender {
+    pub async fn send(&self, queued: QueuedJob) -> Result<(), LuaSendError> {
+        let tx = match queued.job.priority() {
+            LuaPriority::Interactive => &self.interactive,
+            LuaPriority::Background => &self.background,
+        };
+        tx.send(queued).await.map_err(|_| LuaSendError::Closed)
+    }
+
+    /// Jobs waiting in the interactive and the background queue
+    pub fn depth(&self) -> (usize, usize) {
+        let depth = |tx: &mpsc::Sender<QueuedJob>| tx.max_capacity() - tx.capacity();
+        (depth(&self.interactive), depth(&self.background))
+    }
+}
+
+impl JobReceiver {
+    /// Waits for the next job, interactive ones first. Returns None once all senders are gone.
+    pub fn blocking_recv(&mut self, rt_handle: &Handle) -> Option<QueuedJob> {
+        rt_handle.block_on(self.recv())
+    }
+
+    async fn recv(&mut self) -> Option<QueuedJob> {
+        tokio::select! {
+            biased;
+            Some(job) = self.interactive.recv() => Some(job),
+            Some(job) = self.background.recv() => Some(job),
+            else => None,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::types::RealmId;
+    use tokio::sync::oneshot;
+
+    fn scheduled(name: &str) -> QueuedJob {
+        QueuedJob {
+            job: LuaJob::Scheduled {
+                realm_id: RealmId::new(),
+                name: name.to_string(),
+                code: String::new(),
+                reply: oneshot::channel().0,
+            },
+            slot: None,
+        }
+    }
+
+    fn name(queued: Option<QueuedJob>) -> String {
+        match queued.map(|q| q.job) {
+            Some(LuaJob::Scheduled { name, .. }) => name,
+            _ => panic!("expected a scheduled job"),
+        }
+    }
+
+    #[tokio::test]
+    async fn t_interactive_jobs_first() {
+        let (tx, mut rx) = job_queue();
+        tx.send(scheduled("tick 1")).await.unwrap();
+        tx.send(scheduled("tick 2")).await.unwrap();
+        // Any job on the interactive channel goes first, whatever it is
+        tx.interactive.send(scheduled("command")).await.map_err(|_| ()).unwrap();
+        assert_eq!(tx.depth(), (1, 2));
+        drop(tx);
+
+        assert_eq!(name(rx.recv().await), "command");
+        assert_eq!(name(rx.recv().await), "tick 1");
+        assert_eq!(name(rx.recv().await), "tick 2");
+        assert!(rx.recv().await.is_none());
+    }
+
+    #[test]
+    fn t_in_flight_limit() {
+        let in_flight = Arc::new(InFlight::default());
+        let account = AccountId::new();
+
+        let first = in_flight.acquire(account, 2).unwrap();
+        let _second = in_flight.acquire(account, 2).unwrap();
+        assert!(in_flight.acquire(account, 2).is_none());
+        // Other sessions have their own slots
+        assert!(in_flight.acquire(AccountId::new(), 2).is_some());
+        assert_eq!(in_flight.totals(), (1, 2));
+
+        drop(first);
+        assert!(in_flight.acquire(account, 2).is_some());
+    }
+}
@@ -1,27 +1,245 @@
+use clap::{Parser, Subcommand};
 use port4k::{
     Registry, config, db,
-    lua::start_lua_worker,
+    import_blueprint::{
+        ensure_blueprint, export_blueprint, find_blueprint, import_blueprint_sub_dir, import_blueprint_tree,
+        set_entry_room,
+    },
+    lua::start_lua_workers,
+    models::account::AccountRole,
     net::{http, telnet},
+    scenario::{load_scenarios, run_scenarios},
+    state::{
+        ambience::run_ambience_tick, clock::run_clock_tick, deletions::run_deletion_tick,
+        dropped::run_dropped_item_tick, events::run_event_tick, hazards::run_hazard_tick, lockdown::resume_lockdowns,
+        maintenance::run_gc_tick, script_actions::run_script_actions, vehicles::run_vehicle_tick,
+        zone_kv::run_zone_kv_sweep,
+    },
+    util::resolve_content_subdir,
 };
+use std::io::Write;
 use std::net::SocketAddr;
+use std::path::{Path, PathBuf};
 use std::sync::Arc;
+use std::time::Duration;
 use tokio::runtime::Handle;
 
+/// How often the config file is checked for changes
+const CONFIG_WATCH_INTERVAL: Duration = Duration::from_secs(5);
+
+#[derive(Debug, Parser)]
+#[command(name = "port4k", version, about = "Port4k MUD server and operational tooling")]
+struct Cli {
+    /// Config file (defaults to $PORT4K_CONFIG, or ./port4k.toml when present)
+    #[arg(long, global = true)]
+    config: Option<PathBuf>,
+
+    /// Command to run (defaults to `serve`)
+    #[command(subcommand)]
+    command: Option<Command>,
+}
+
+#[derive(Debug, Subcommand)]
+enum Command {
+    /// Run the telnet and websocket servers
+    Serve,
+
+    /// Import blueprint YAML from a directory under IMPORT_DIR.
+    /// Without --bp-key, the directory must contain a manifest.yaml listing the blueprints.
+    Import {
+        /// Directory under IMPORT_DIR
+        dir: String,
+
+        /// Blueprint key to import the rooms into (created when missing)
+        #[arg(long)]
+        bp_key: Option<String>,
+
+        /// Owner username when the blueprint needs to be created
+        #[arg(long, requires = "bp_key")]
+        owner: Option<String>,
+
+        /// Set the blueprint's entry room by key after import
+        #[arg(long, requires = "bp_key")]
+        entry_room: Option<String>,
+
+        /// Validate and print what would change, without writing to the database
+        #[arg(long)]
+        dry_run: bool,
+    },
+
+    /// Manage accounts
+    Account {
+        #[command(subcommand)]
+        command: AccountCommand,
+    },
+
+    /// Run pending database migrations
+    Migrate,
+
+    /// Export a blueprint to v5 room YAML files
+    ExportBp {
+        /// Blueprint key
+        key: String,
+
+        /// Output directory (defaults to ./<key>)
+        #[arg(long)]
+        out: Option<PathBuf>,
+    },
+
+    /// Run the scenario files in <dir>/tests against an imported blueprint.
+    /// Exits with an error when a scenario fails, so it can run in CI.
+    TestBp {
+        /// Blueprint directory under IMPORT_DIR
+        dir: String,
+
+        /// Blueprint key to test
+        #[arg(long)]
+        bp_key: String,
+    },
+}
+
+#[derive(Debug, Subcommand)]
+enum AccountCommand {
+    /// Create a new account
+    Create {
+        username: String,
+        email: String,
+
+        /// Password; read from stdin when omitted
+        #[arg(long)]
+        password: Option<String>,
+
+        /// Account role: admin, moderator, builder or user
+        #[arg(long, default_value = "user")]
+        role: AccountRole,
+    },
+}
+
 #[tokio::main]
 async fn main() -> anyhow::Result<()> {
-    init_tracing();
-
-    let cfg = Arc::new(config::Config::from_env()?);
+    let cli = Cli::parse();
 
+    let cfg = Arc::new(config::Config::load_layered(cli.config.as_deref())?);
     let db = Arc::new(db::Db::new(&cfg.database_url)?);
-    db.init().await?;
 
+    match cli.command.unwrap_or(Command::Serve) {
+        Command::Serve => {
+            init_tracing();
+            db.init().await?;
+            serve(db, cfg).await
+        }
+        Command::Migrate => {
+            let applied = db.migrate().await?;
+            if applied.is_empty() {
+                println!("✓ Database is up to date");
+            }
+            for name in applied {
+                println!("✓ Applied {name}");
+            }
+            Ok(())
+        }
+        Command::Import {
+            dir,
+            bp_key,
+            owner,
+            entry_room,
+            dry_run,
+        } => {
+            db.init().await?;
+            let content_base = PathBuf::from(&cfg.content.import_dir);
+            match bp_key {
+                Some(key) => {
+                    import_one(
+                        &db,
+                        &content_base,
+                        &dir,
+                        &key,
+                        owner.as_deref(),
+                        entry_room.as_deref(),
+                        dry_run,
+                    )
+                    .await
+                }
+                None => import_tree(&db, &content_base, &dir, dry_run).await,
+            }
+        }
+        Command::Account {
+            command:
+                AccountCommand::Create {
+                    username,
+                    email,
+                    password,
+                    role,
+                },
+        } => {
+            db.init().await?;
+            let password = match password {
+                Some(p) => p,
+                None => read_password()?,
+            };
+
+            let registry = Registry::new(db.clone(), cfg.clone());
+            let account = registry
+                .services
+                .account
+                .create(&username, &email, &password, role)
+                .await
+                .map_err(|e| anyhow::anyhow!("cannot create account: {e}"))?;
+
+            println!(
+                "✓ Account '{}' created ({}, role {})",
+                account.username, account.id, account.role
+            );
+            Ok(())
+        }
+        Command::ExportBp { key, out } => {
+            db.init().await?;
+            let out = out.unwrap_or_else(|| PathBuf::from(&key));
+            let count = export_blueprint(&db, &key, &out)
+                .await
+                .map_err(|e| anyhow::anyhow!("export failed: {e}"))?;
+            println!("✓ Exported {count} room(s) of '{key}' to {}", out.display());
+            Ok(())
+        }
+        Command::TestBp { dir, bp_key } => {
+            db.init().await?;
+            test_bp(db, cfg, &dir, &bp_key).await
+        }
+    }
+}
+
+async fn serve(db: Arc<db::Db>, cfg: Arc<config::Config>) -> anyhow::Result<()> {
     let registry = Arc::new(Registry::new(db.clone(), cfg.clone()));
 
-    let lua_tx = start_lua_worker(Handle::current(), registry.clone());
+    let lua_tx = start_lua_workers(Handle::current(), registry.clone());
+
+    // Lockdowns survive restarts; pick their countdowns up again
+    match resume_lockdowns(registry.clone()).await {
+        Ok(0) => {}
+        Ok(n) => tracing::info!(count = n, "resumed room lockdowns"),
+        Err(e) => tracing::warn!(error = %e, "cannot resume room lockdowns"),
+    }
+    match registry.services.clock.resume(&cfg.clock).await {
+        Ok(world) => tracing::info!(%world, "world clock started"),
+        Err(e) => tracing::warn!(error = %e, "cannot resume the world clock, starting at the epoch"),
+    }
+    tokio::spawn(run_clock_tick(registry.clone()));
+    tokio::spawn(run_hazard_tick(registry.clone(), lua_tx.clone()));
+    tokio::spawn(run_vehicle_tick(registry.clone(), lua_tx.clone()));
+    tokio::spawn(run_ambience_tick(registry.clone()));
+    tokio::spawn(run_event_tick(registry.clone(), lua_tx.clone()));
+    tokio::spawn(run_deletion_tick(registry.clone()));
+    tokio::spawn(run_dropped_item_tick(registry.clone()));
+    tokio::spawn(run_gc_tick(registry.clone()));
+    tokio::spawn(run_zone_kv_sweep(registry.clone()));
+    tokio::spawn(run_script_actions(registry.clone(), lua_tx.clone()));
+
+    if let Some(path) = cfg.source.clone() {
+        spawn_config_watcher(registry.clone(), path);
+    }
 
     // HTTP (WebSocket) server
-    let ws_addr: SocketAddr = cfg.websocket_addr.parse()?;
+    let ws_addr: SocketAddr = cfg.listen.websocket.parse()?;
     let ws_registry = registry.clone();
     let ws_lua_tx = lua_tx.clone();
     let ws_jh = tokio::spawn(async move {
@@ -32,7 +250,7 @@ async fn main() -> anyhow::Result<()> {
     });
 
     // Telnet server
-    let tcp_addr: SocketAddr = cfg.tcp_addr.parse()?;
+    let tcp_addr: SocketAddr = cfg.listen.telnet.parse()?;
     let tcp_registry = registry.clone();
     let tcp_lua_tx = lua_tx.clone();
     let tcp_jh = tokio::spawn(async move {
@@ -50,6 +268,139 @@ async fn main() -> anyhow::Result<()> {
     Ok(())
 }
 
+async fn import_one(
+    db: &db::Db,
+    content_base: &Path,
+    dir: &str,
+    bp_key: &str,
+    owner: Option<&str>,
+    entry_room: Option<&str>,
+    dry_run: bool,
+) -> anyhow::Result<()> {
+    // A dry run never creates the blueprint; a missing one diffs against nothing
+    let bp_id = if dry_run {
+        find_blueprint(db, bp_key)
+            .await
+            .map_err(|e| anyhow::anyhow!("cannot resolve blueprint: {e}"))?
+            .unwrap_or_default()
+    } else {
+        ensure_blueprint(db, bp_key, bp_key, owner, None)
+            .await
+            .map_err(|e| anyhow::anyhow!("cannot resolve blueprint: {e}"))?
+    };
+
+    import_blueprint_sub_dir(bp_id, dir, content_base, db, dry_run)
+        .await
+        .map_err(|e| anyhow::anyhow!("import failed: {e}"))?;
+
+    if dry_run {
+        return Ok(());
+    }
+
+    if let Some(entry_key) = entry_room {
+        set_entry_room(db, bp_id, entry_key)
+            .await
+            .map_err(|e| anyhow::anyhow!("cannot set entry room: {e}"))?;
+    }
+
+    println!("✓ Import of '{dir}' complete into blueprint '{bp_key}' ({bp_id})");
+    Ok(())
+}
+
+async fn import_tree(db: &db::Db, content_base: &Path, root: &str, dry_run: bool) -> anyhow::Result<()> {
+    let summary = import_blueprint_tree(root, content_base, db, dry_run)
+        .await
+        .map_err(|e| anyhow::anyhow!("import failed: {e}"))?;
+
+    let failed = summary.iter().filter(|s| s.result.is_err()).count();
+    for s in &summary {
+        match &s.result {
+            Ok(bp_id) => println!("  ✓ {:<20} {:<20} {}", s.key, s.dir, bp_id),
+            Err(e) => println!("  ✗ {:<20} {:<20} {}", s.key, s.dir, e),
+        }
+    }
+    println!("  {} succeeded, {} failed", summary.len() - failed, failed);
+
+    if failed > 0 {
+        anyhow::bail!("{failed} blueprint(s) failed to import");
+    }
+    Ok(())
+}
+
+async fn test_bp(db: Arc<db::Db>, cfg: Arc<config::Config>, dir: &str, bp_key: &str) -> anyhow::Result<()> {
+    let bp_dir = resolve_content_subdir(Path::new(&cfg.content.import_dir), dir)
+        .map_err(|e| anyhow::anyhow!("cannot resolve '{dir}': {e}"))?;
+    let scenarios = load_scenarios(&bp_dir).map_err(|e| anyhow::anyhow!("cannot load scenarios: {e}"))?;
+    if scenarios.is_empty() {
+        println!("No scenarios found in {}", bp_dir.join("tests").display());
+        return Ok(());
+    }
+
+    let registry = Arc::new(Registry::new(db, cfg));
+    let lua_tx = start_lua_workers(Handle::current(), registry.clone());
+
+    let reports = run_scenarios(registry, lua_tx, bp_key, &scenarios)
+        .await
+        .map_err(|e| anyhow::anyhow!("scenario run failed: {e}"))?;
+
+    let failed = reports.iter().filter(|r| !r.passed()).count();
+    for r in &reports {
+        match &r.failure {
+            None => println!("  ✓ {}", r.name),
+            Some(f) => println!("  ✗ {} ({})\n      {}", r.name, r.file.display(), f),
+        }
+    }
+    println!("  {} passed, {} failed", reports.len() - failed, failed);
+
+    if failed > 0 {
+        anyhow::bail!("{failed} scenario(s) failed");
+    }
+    Ok(())
+}
+
+/// Reads a password from the first line of stdin, so it can be piped in
+fn read_password() -> anyhow::Result<String> {
+    print!("Password: ");
+    std::io::stdout().flush()?;
+
+    let mut line = String::new();
+    std::io::stdin().read_line(&mut line)?;
+    Ok(line.trim_end_matches(['\r', '\n']).to_string())
+}
+
+/// Polls the config file and reloads it when it changes
+fn spawn_config_watcher(registry: Arc<Registry>, path: PathBuf) {
+    let modified = |p: &Path| std::fs::metadata(p).and_then(|m| m.modified()).ok();
+
+    tokio::spawn(async move {
+        let mut last = modified(&path);
+        let mut interval = tokio::time::interval(CONFIG_WATCH_INTERVAL);
+        loop {
+            interval.tick().await;
+
+            let current = modified(&path);
+            if current == last {
+                continue;
+            }
+            last = current;
+
+            match registry.reload_config() {
+                Ok(report) => {
+                    tracing::info!(
+                        path = %path.display(),
+                        applied = ?report.applied,
+                        restart_required = ?report.restart_required,
+                        "config reloaded"
+                    );
+                }
+                Err(e) => {
+                    tracing::warn!(path = %path.display(), error = %e, "config reload failed, keeping current config")
+                }
+            }
+        }
+    });
+}
+
 #[allow(unused)]
 fn spawn_background_tasks(registry: Arc<Registry>) {
     let db_for_spawn = registry.db.clone();
@@ -1,9 +1,29 @@
 pub mod account;
+pub mod ambience;
 pub mod blueprint;
 pub mod character;
+pub mod clock;
+pub mod collaborator;
+pub mod dialogue;
+pub mod effect;
+pub mod feature;
 pub mod inventory;
+pub mod login;
+pub mod maintenance;
+pub mod player_export;
+pub mod readable;
 pub mod realm;
+pub mod realm_directory;
+pub mod recording;
+pub mod report;
+pub mod review;
 pub mod room;
+pub mod room_graph;
+pub mod schedule;
+pub mod settings;
+pub mod submission;
 pub mod types;
+pub mod vehicle;
+pub mod widget;
 
 mod room_helpers;
@@ -1,6 +1,7 @@
 use crate::db::DbResult;
 use crate::db::error::DbError;
 use crate::error::{AppResult, DomainError};
+use crate::models::settings::PlayerSettings;
 use crate::models::types::{AccountId, RealmId, RoomId};
 use postgres_types::private::BytesMut;
 use postgres_types::{FromSql, IsNull, ToSql, Type};
@@ -10,15 +11,17 @@ use tokio_postgres::Row;
 
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub enum AccountRole {
-    Admin,   // Can do everything
-    Builder, // Can build new rooms / blueprints
-    User,    // Regular user
+    Admin,     // Can do everything
+    Moderator, // Can work the moderation queue
+    Builder,   // Can build new rooms / blueprints
+    User,      // Regular user
 }
 
 impl ToSql for AccountRole {
     fn to_sql(&self, ty: &Type, out: &mut BytesMut) -> Result<IsNull, Box<dyn Error + Sync + Send>> {
         let s = match self {
             AccountRole::Admin => "admin",
+            AccountRole::Moderator => "moderator",
             AccountRole::Builder => "builder",
             AccountRole::User => "user",
         };
@@ -39,6 +42,7 @@ impl FromSql<'_> for AccountRole {
         let s = String::from_sql(ty, raw)?;
         match s.as_str() {
             "admin" => Ok(AccountRole::Admin),
+            "moderator" => Ok(AccountRole::Moderator),
             "builder" => Ok(AccountRole::Builder),
             "user" => Ok(AccountRole::User),
             _ => Err(format!("Unknown account role: {}", s).into()),
@@ -54,12 +58,33 @@ impl std::fmt::Display for AccountRole {
     fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
         match self {
             AccountRole::Admin => write!(f, "admin"),
+            AccountRole::Moderator => write!(f, "moderator"),
             AccountRole::Builder => write!(f, "builder"),
             AccountRole::User => write!(f, "user"),
         }
     }
 }
 
+impl std::str::FromStr for AccountRole {
+    type Err = DomainError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s {
+            "admin" => Ok(AccountRole::Admin),
+            "moderator" => Ok(AccountRole::Moderator),
+            "builder" => Ok(AccountRole::Builder),
+            "user" => Ok(AccountRole::User),
+            _ => Err(DomainError::Validation {
+                field: "role",
+                message: format!("unknown role '{}' (admin, moderator, builder or user)", s),
+            }),
+        }
+    }
+}
+
+/// Health of a fresh account, and the most it can heal up to
+pub const MAX_HEALTH: u32 = 100;
+
 #[derive(Debug, Clone)]
 pub struct Account {
     /// Unique Account ID
@@ -92,6 +117,16 @@ pub struct Account {
     pub health: u32,
     pub xp: u32,
     pub coins: u32,
+    /// Rolled against the difficulty of obscured objects when searching
+    pub perception: u32,
+    /// When a requested deletion is carried out, None when none is pending
+    pub delete_after: Option<chrono::DateTime<chrono::Utc>>,
+    /// Locked after too many failed logins until this time
+    pub locked_until: Option<chrono::DateTime<chrono::Utc>>,
+    /// When the starter tutorial was finished, None while the player still has to play it
+    pub tutorial_completed_at: Option<chrono::DateTime<chrono::Utc>>,
+    /// Player settings, see `models::settings`
+    pub settings: PlayerSettings,
 }
 
 impl Account {
@@ -122,6 +157,15 @@ impl Account {
                 .try_get::<_, i32>("coins")?
                 .try_into()
                 .map_err(|_| DbError::Decode("coins < 0".into()))?,
+            perception: row
+                .try_get::<_, i32>("perception")?
+                .try_into()
+                .map_err(|_| DbError::Decode("perception < 0".into()))?,
+            delete_after: row.try_get("delete_after")?,
+            locked_until: row.try_get("locked_until")?,
+            tutorial_completed_at: row.try_get("tutorial_completed_at")?,
+            // Settings that do not parse fall back to the defaults
+            settings: serde_json::from_value(row.try_get("settings")?).unwrap_or_default(),
         })
     }
 
@@ -145,6 +189,16 @@ impl Account {
     pub fn is_admin(&self) -> bool {
         matches!(self.role, AccountRole::Admin)
     }
+
+    /// Admins can build as well
+    pub fn is_builder(&self) -> bool {
+        matches!(self.role, AccountRole::Admin | AccountRole::Builder)
+    }
+
+    /// Admins are moderators as well
+    pub fn is_moderator(&self) -> bool {
+        matches!(self.role, AccountRole::Admin | AccountRole::Moderator)
+    }
 }
 
 pub struct UserRealmData {
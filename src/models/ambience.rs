@@ -0,0 +1,115 @@
+//! Zone ambience: weather, day and night, and other ambient events of a realm.
+//!
+//! Rooms can list ambient events in their YAML; like recipes, they apply to the whole blueprint.
+//! Every `every` seconds (and then only with the given `chance`) an event moves on to its next
+//! step, or a random one, sends the step's message to the players in its rooms and writes the
+//! step's `set` values to the zone state:
+//!
+//! ```yaml
+//! ambience:
+//!   - id: daylight
+//!     every: 600
+//!     rooms: [observation_deck]     # all rooms when empty
+//!     steps:
+//!       - message: "The planet's rim catches the first light."
+//!         set: { daylight: true }
+//!       - message: "The station swings into the planet's shadow."
+//!         set: { daylight: false }
+//! ```
+//!
+//! The zone state is shared by everyone in the realm and can be read from Lua with
+//! `port4k.zone_state(key)`, so descriptions can depend on it.
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::BTreeMap;
+
+/// Zone state key holding the step an event is at
+pub fn step_key(event: &str) -> String {
+    format!("__ambience.{}", event)
+}
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct AmbientStep {
+    /// Sent to the players in the event's rooms
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub message: Option<String>,
+    /// Zone state to set
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub set: BTreeMap<String, Value>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct AmbientEvent {
+    pub id: String,
+    /// Seconds between two rolls of the event
+    pub every: u32,
+    /// Chance (0..1) that the event happens when rolled
+    #[serde(default = "default_chance", skip_serializing_if = "is_certain")]
+    pub chance: f64,
+    /// Pick a random step instead of going through them in order
+    #[serde(default, skip_serializing_if = "std::ops::Not::not")]
+    pub random: bool,
+    /// Room keys that get the messages; all rooms when empty
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub rooms: Vec<String>,
+    pub steps: Vec<AmbientStep>,
+}
+
+fn default_chance() -> f64 {
+    1.0
+}
+
+fn is_certain(chance: &f64) -> bool {
+    *chance >= 1.0
+}
+
+impl AmbientEvent {
+    /// The step after `current` (None before the first one). Random events take `pick(len)`, which
+    /// must be below the number of steps.
+    pub fn next_step(&self, current: Option<usize>, pick: impl FnOnce(usize) -> usize) -> usize {
+        let len = self.steps.len().max(1);
+        if self.random {
+            return pick(len).min(len - 1);
+        }
+        current.map_or(0, |c| (c + 1) % len)
+    }
+
+    /// Whether players in this room get the event's messages
+    pub fn reaches(&self, room_key: &str) -> bool {
+        self.rooms.is_empty() || self.rooms.iter().any(|r| r == room_key)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn event(yaml: &str) -> AmbientEvent {
+        serde_yaml::from_str(yaml).unwrap()
+    }
+
+    #[test]
+    fn t_ambient_steps() {
+        let e = event(
+            "id: daylight\nevery: 600\nrooms: [deck]\nsteps:\n  - message: Dawn\n    set: { daylight: true }\n  - set: { daylight: false }\n",
+        );
+        assert_eq!(e.chance, 1.0);
+        assert_eq!(e.next_step(None, |_| 1), 0);
+        assert_eq!(e.next_step(Some(0), |_| 0), 1);
+        assert_eq!(e.next_step(Some(1), |_| 0), 0);
+        assert_eq!(e.steps[0].set["daylight"], Value::Bool(true));
+        assert!(e.reaches("deck"));
+        assert!(!e.reaches("corridor"));
+
+        let storm =
+            event("id: storm\nevery: 60\nchance: 0.25\nrandom: true\nsteps:\n  - message: Hail\n  - message: Wind\n");
+        assert_eq!(storm.next_step(Some(0), |n| n - 1), 1);
+        assert_eq!(storm.next_step(None, |_| 7), 1);
+        assert!(storm.reaches("anywhere"));
+
+        assert!(serde_yaml::from_str::<AmbientEvent>("id: x\nevery: 1\nsteps: []\nsky: red\n").is_err());
+    }
+}
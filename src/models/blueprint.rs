@@ -1,12 +1,17 @@
 use crate::db::DbResult;
 use crate::db::error::DbError;
 use crate::models::types::{AccountId, BlueprintId, RoomId};
+use serde::{Deserialize, Serialize};
 use tokio_postgres::Row;
 
 #[derive(Clone, Debug, PartialEq, Eq)]
 pub enum BlueprintStatus {
     Draft,
+    /// Submitted, waiting in the moderation queue
+    Pending,
     Published,
+    /// Sent back by a moderator; can be changed and submitted again
+    Rejected,
     Archived,
 }
 
@@ -15,11 +20,60 @@ impl BlueprintStatus {
         match s {
             "live" => Ok(BlueprintStatus::Published), // legacy support
             "draft" => Ok(BlueprintStatus::Draft),
+            "pending" => Ok(BlueprintStatus::Pending),
             "published" => Ok(BlueprintStatus::Published),
+            "rejected" => Ok(BlueprintStatus::Rejected),
             "archived" => Ok(BlueprintStatus::Archived),
             _ => Err(DbError::Decode("invalid blueprint.status".into())),
         }
     }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            BlueprintStatus::Draft => "draft",
+            BlueprintStatus::Pending => "pending",
+            BlueprintStatus::Published => "published",
+            BlueprintStatus::Rejected => "rejected",
+            BlueprintStatus::Archived => "archived",
+        }
+    }
+
+    /// Whether the author may submit the blueprint for review
+    pub fn can_submit(&self) -> bool {
+        matches!(self, BlueprintStatus::Draft | BlueprintStatus::Rejected)
+    }
+}
+
+/// What happens to items players leave lying in the rooms of a blueprint's realms
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "policy", rename_all = "snake_case", deny_unknown_fields)]
+pub enum DropPolicy {
+    /// They stay where they were dropped
+    #[default]
+    Persist,
+    /// They disappear `after` minutes
+    Despawn { after: u32 },
+    /// After `after` minutes they go back to the room or container they were spawned in; items
+    /// that were never anywhere (handed out by a script) disappear
+    Return { after: u32 },
+}
+
+impl DropPolicy {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            DropPolicy::Persist => "persist",
+            DropPolicy::Despawn { .. } => "despawn",
+            DropPolicy::Return { .. } => "return",
+        }
+    }
+
+    /// Minutes a dropped item is left alone, None when it is left alone forever
+    pub fn after_minutes(&self) -> Option<u32> {
+        match self {
+            DropPolicy::Persist => None,
+            DropPolicy::Despawn { after } | DropPolicy::Return { after } => Some(*after),
+        }
+    }
 }
 
 #[derive(Debug, Clone)]
@@ -30,6 +84,13 @@ pub struct Blueprint {
     pub owner_id: AccountId,
     pub status: BlueprintStatus,
     pub entry_room_id: RoomId,
+    /// Bumped on every import
+    pub version: i32,
+    /// Blueprint this one was forked from, and its version at the time
+    pub forked_from: Option<BlueprintId>,
+    pub forked_version: Option<i32>,
+    /// Prompt for players in realms of this blueprint that did not pick their own
+    pub prompt: Option<String>,
     pub created_at: chrono::DateTime<chrono::Utc>,
 }
 
@@ -45,6 +106,10 @@ impl Blueprint {
             owner_id: row.try_get::<_, AccountId>("owner_id")?,
             status,
             entry_room_id: row.try_get::<_, RoomId>("entry_room_id")?,
+            version: row.try_get("version")?,
+            forked_from: row.try_get("forked_from")?,
+            forked_version: row.try_get("forked_version")?,
+            prompt: row.try_get("prompt")?,
             created_at: row.try_get("created_at")?,
         })
     }
@@ -0,0 +1,67 @@
+//! The in-world clock.
+//!
+//! World time runs `speed` times as fast as real time. The clock keeps the world time it was last
+//! set to (at start, or when the speed changed) and the real time that happened at, and counts on
+//! from there.
+
+use chrono::{DateTime, Utc};
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct WorldClock {
+    /// World time at `real`
+    world: DateTime<Utc>,
+    real: DateTime<Utc>,
+    /// In-world seconds per real second
+    speed: f64,
+}
+
+impl WorldClock {
+    /// A clock showing `world` at real time `real`
+    pub fn new(world: DateTime<Utc>, real: DateTime<Utc>, speed: f64) -> Self {
+        Self { world, real, speed }
+    }
+
+    /// World time at real time `real`
+    pub fn at(&self, real: DateTime<Utc>) -> DateTime<Utc> {
+        let elapsed_ms = (real - self.real).num_milliseconds() as f64 * self.speed;
+        self.world + chrono::Duration::milliseconds(elapsed_ms as i64)
+    }
+
+    pub fn now(&self) -> DateTime<Utc> {
+        self.at(Utc::now())
+    }
+
+    pub fn speed(&self) -> f64 {
+        self.speed
+    }
+
+    /// Changes the speed from real time `real` on; the time shown so far stays
+    pub fn set_speed(&mut self, speed: f64, real: DateTime<Utc>) {
+        self.world = self.at(real);
+        self.real = real;
+        self.speed = speed;
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::{Duration, TimeZone};
+
+    #[test]
+    fn t_world_clock() {
+        let world = Utc.with_ymd_and_hms(2187, 1, 1, 6, 0, 0).unwrap();
+        let real = Utc.with_ymd_and_hms(2025, 5, 1, 12, 0, 0).unwrap();
+        let mut clock = WorldClock::new(world, real, 60.0);
+
+        // A real minute is an in-world hour
+        assert_eq!(clock.at(real + Duration::minutes(1)), world + Duration::hours(1));
+
+        clock.set_speed(1.0, real + Duration::minutes(2));
+        assert_eq!(clock.at(real + Duration::minutes(2)), world + Duration::hours(2));
+        assert_eq!(
+            clock.at(real + Duration::minutes(3)),
+            world + Duration::hours(2) + Duration::minutes(1)
+        );
+    }
+}
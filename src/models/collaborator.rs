@@ -0,0 +1,194 @@
+//! Who may work on a blueprint besides its owner.
+//!
+//! Collaborators are editors or testers. Their role gives them a default set of rights, which the
+//! owner can widen or narrow per person with `@bp collab grant|revoke`.
+
+use crate::db::DbResult;
+use crate::db::error::DbError;
+use crate::models::types::AccountId;
+use tokio_postgres::Row;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum BlueprintRole {
+    Owner,
+    Editor,
+    Tester,
+}
+
+impl BlueprintRole {
+    /// Roles that can be given to a collaborator; there is only one owner
+    pub fn parse(s: &str) -> Option<Self> {
+        match s {
+            "editor" => Some(BlueprintRole::Editor),
+            "tester" => Some(BlueprintRole::Tester),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            BlueprintRole::Owner => "owner",
+            BlueprintRole::Editor => "editor",
+            BlueprintRole::Tester => "tester",
+        }
+    }
+
+    /// Rights a new collaborator with this role gets
+    pub fn default_rights(&self) -> Vec<BlueprintRight> {
+        match self {
+            BlueprintRole::Owner => BlueprintRight::ALL.to_vec(),
+            BlueprintRole::Editor => vec![
+                BlueprintRight::EditRooms,
+                BlueprintRight::EditScripts,
+                BlueprintRight::Playtest,
+            ],
+            BlueprintRole::Tester => vec![BlueprintRight::Playtest],
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, PartialOrd, Ord)]
+pub enum BlueprintRight {
+    /// Add and change rooms, exits and the entry room
+    EditRooms,
+    /// Change scripts, which for now means importing the blueprint's YAML
+    EditScripts,
+    /// Submit the blueprint for review
+    Publish,
+    /// Playtest realms of the blueprint
+    Playtest,
+}
+
+impl BlueprintRight {
+    pub const ALL: [BlueprintRight; 4] = [
+        BlueprintRight::EditRooms,
+        BlueprintRight::EditScripts,
+        BlueprintRight::Publish,
+        BlueprintRight::Playtest,
+    ];
+
+    pub fn parse(s: &str) -> Option<Self> {
+        match s {
+            "rooms" => Some(BlueprintRight::EditRooms),
+            "scripts" => Some(BlueprintRight::EditScripts),
+            "publish" => Some(BlueprintRight::Publish),
+            "playtest" => Some(BlueprintRight::Playtest),
+            _ => None,
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            BlueprintRight::EditRooms => "rooms",
+            BlueprintRight::EditScripts => "scripts",
+            BlueprintRight::Publish => "publish",
+            BlueprintRight::Playtest => "playtest",
+        }
+    }
+
+    /// What the right lets you do, for refusals
+    pub fn describe(&self) -> &'static str {
+        match self {
+            BlueprintRight::EditRooms => "edit the rooms of",
+            BlueprintRight::EditScripts => "edit the scripts of",
+            BlueprintRight::Publish => "publish",
+            BlueprintRight::Playtest => "playtest",
+        }
+    }
+}
+
+/// Someone on the collaborator list of a blueprint
+#[derive(Debug, Clone)]
+pub struct Collaborator {
+    pub account_id: AccountId,
+    pub username: String,
+    pub role: BlueprintRole,
+    pub rights: Vec<BlueprintRight>,
+}
+
+impl Collaborator {
+    pub fn try_from_row(row: &Row) -> DbResult<Self> {
+        let role: &str = row.try_get("role")?;
+        let rights: Vec<String> = row.try_get("rights")?;
+
+        Ok(Self {
+            account_id: row.try_get("account_id")?,
+            username: row.try_get("username")?,
+            role: BlueprintRole::parse(role).ok_or(DbError::Decode("invalid blueprint_collaborators.role".into()))?,
+            rights: parse_rights(&rights)?,
+        })
+    }
+}
+
+fn parse_rights(rights: &[String]) -> DbResult<Vec<BlueprintRight>> {
+    let mut out = rights
+        .iter()
+        .map(|r| BlueprintRight::parse(r).ok_or(DbError::Decode("invalid blueprint_collaborators.rights".into())))
+        .collect::<DbResult<Vec<_>>>()?;
+    out.sort();
+    out.dedup();
+    Ok(out)
+}
+
+/// What one account may do with one blueprint
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BlueprintAccess {
+    /// None when the account is not involved with the blueprint
+    pub role: Option<BlueprintRole>,
+    pub rights: Vec<BlueprintRight>,
+    /// Admins may do everything, whatever their role
+    pub admin: bool,
+}
+
+impl BlueprintAccess {
+    pub fn allows(&self, right: BlueprintRight) -> bool {
+        self.admin || self.rights.contains(&right)
+    }
+
+    /// Owners and admins manage the collaborator list
+    pub fn can_manage(&self) -> bool {
+        self.admin || self.role == Some(BlueprintRole::Owner)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn t_role_rights() {
+        let editor = BlueprintAccess {
+            role: Some(BlueprintRole::Editor),
+            rights: BlueprintRole::Editor.default_rights(),
+            admin: false,
+        };
+        assert!(editor.allows(BlueprintRight::EditRooms));
+        assert!(editor.allows(BlueprintRight::Playtest));
+        assert!(!editor.allows(BlueprintRight::Publish));
+        assert!(!editor.can_manage());
+
+        let tester = BlueprintAccess {
+            role: Some(BlueprintRole::Tester),
+            rights: BlueprintRole::Tester.default_rights(),
+            admin: false,
+        };
+        assert!(!tester.allows(BlueprintRight::EditRooms));
+        assert!(tester.allows(BlueprintRight::Playtest));
+
+        let admin = BlueprintAccess {
+            role: None,
+            rights: vec![],
+            admin: true,
+        };
+        assert!(admin.allows(BlueprintRight::Publish));
+        assert!(admin.can_manage());
+
+        assert_eq!(BlueprintRole::parse("owner"), None);
+        assert_eq!(BlueprintRight::parse("scripts"), Some(BlueprintRight::EditScripts));
+        assert_eq!(
+            parse_rights(&["playtest".into(), "rooms".into(), "rooms".into()]).unwrap(),
+            vec![BlueprintRight::EditRooms, BlueprintRight::Playtest]
+        );
+        assert!(parse_rights(&["fly".into()]).is_err());
+    }
+}
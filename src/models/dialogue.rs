@@ -0,0 +1,192 @@
+//! Dialogue trees for NPCs.
+//!
+//! An object with a `dialogue` block can be talked to. The tree is a set of named nodes; each
+//! node has a line the NPC says and the numbered options the player can answer with:
+//!
+//! ```yaml
+//! dialogue:
+//!   start: greet
+//!   nodes:
+//!     greet:
+//!       text: "You again? What do you want?"
+//!       options:
+//!         - text: "Can you open the hatch?"
+//!           goto: hatch
+//!           when: { has_item: access_card }
+//!         - text: "Nothing. Bye."
+//!     hatch:
+//!       text: "Fine. Don't tell the chief."
+//!       options:
+//!         - text: "Thanks."
+//!           effects: { unlock: [north], set: { mechanic: { helped: true } } }
+//! ```
+//!
+//! Options without `goto` end the conversation, as do nodes without options. Conditions look at
+//! the player's inventory and the player's state of the NPC object; effects are the same as for
+//! widgets, plus an item handed to the player.
+
+use crate::models::room::Kv;
+use crate::models::widget::WidgetEffects;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::BTreeMap;
+
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct DialogueCondition {
+    /// Player carries this item (key)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub has_item: Option<String>,
+    /// Player does not carry this item (key)
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub lacks_item: Option<String>,
+    /// The player's state of the NPC object has these values (missing keys count as false)
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub state: BTreeMap<String, Value>,
+}
+
+impl DialogueCondition {
+    pub fn holds(&self, npc_state: &Kv, has_item: impl Fn(&str) -> bool) -> bool {
+        let state_ok = self.state.iter().all(|(k, want)| {
+            let have = npc_state.get(k).cloned().unwrap_or(Value::Bool(false));
+            &have == want
+        });
+        state_ok
+            && self.has_item.as_deref().is_none_or(&has_item)
+            && self.lacks_item.as_deref().is_none_or(|i| !has_item(i))
+    }
+
+    /// Item keys the condition looks at
+    pub fn items(&self) -> impl Iterator<Item = &str> {
+        self.has_item.iter().chain(self.lacks_item.iter()).map(String::as_str)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct DialogueOption {
+    /// What the player says
+    pub text: String,
+    /// Node the NPC answers with; the conversation ends without one
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub goto: Option<String>,
+    /// Only offered when this holds
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub when: Option<DialogueCondition>,
+    #[serde(default)]
+    pub effects: WidgetEffects,
+    /// Item (key) handed to the player
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub give: Option<String>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct DialogueNode {
+    /// What the NPC says
+    pub text: String,
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub options: Vec<DialogueOption>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Dialogue {
+    pub start: String,
+    pub nodes: BTreeMap<String, DialogueNode>,
+}
+
+impl Dialogue {
+    pub fn node(&self, id: &str) -> Option<&DialogueNode> {
+        self.nodes.get(id)
+    }
+
+    /// Item keys used by the conditions and gifts of the tree
+    pub fn items(&self) -> impl Iterator<Item = &str> {
+        self.nodes.values().flat_map(|n| n.options.iter()).flat_map(|o| {
+            o.when
+                .iter()
+                .flat_map(DialogueCondition::items)
+                .chain(o.give.as_deref())
+        })
+    }
+}
+
+impl DialogueNode {
+    /// The options offered to a player, in order
+    pub fn available(&self, npc_state: &Kv, has_item: impl Fn(&str) -> bool) -> Vec<&DialogueOption> {
+        self.options
+            .iter()
+            .filter(|o| o.when.as_ref().is_none_or(|c| c.holds(npc_state, &has_item)))
+            .collect()
+    }
+}
+
+/// The NPC's line followed by the numbered options
+pub fn render_node(npc: &str, node: &DialogueNode, options: &[&DialogueOption]) -> String {
+    let mut out = vec![format!("The {} says: \"{}\"", npc, node.text)];
+    for (i, o) in options.iter().enumerate() {
+        out.push(format!("  {}) {}", i + 1, o.text));
+    }
+    out.join("\n")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn tree() -> Dialogue {
+        serde_yaml::from_str(
+            r#"
+start: greet
+nodes:
+  greet:
+    text: "What do you want?"
+    options:
+      - text: "Open the hatch?"
+        goto: hatch
+        when: { has_item: access_card }
+      - text: "Any news?"
+        when: { state: { helped: false } }
+        goto: news
+      - text: "Bye."
+  hatch:
+    text: "Fine."
+    options:
+      - text: "Thanks."
+        give: wrench
+        effects: { unlock: [north], set: { mechanic: { helped: true } } }
+  news:
+    text: "None."
+"#,
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn t_dialogue_options() {
+        let d = tree();
+        let greet = d.node(&d.start).unwrap();
+        let mut state = Kv::default();
+
+        let texts = |opts: Vec<&DialogueOption>| opts.iter().map(|o| o.text.clone()).collect::<Vec<_>>();
+        assert_eq!(texts(greet.available(&state, |_| false)), vec!["Any news?", "Bye."]);
+        assert_eq!(
+            texts(greet.available(&state, |i| i == "access_card")),
+            vec!["Open the hatch?", "Any news?", "Bye."]
+        );
+
+        state.insert("helped".into(), json!(true));
+        let opts = greet.available(&state, |_| false);
+        assert_eq!(texts(opts.clone()), vec!["Bye."]);
+        assert_eq!(
+            render_node("mechanic", greet, &opts),
+            "The mechanic says: \"What do you want?\"\n  1) Bye."
+        );
+
+        assert!(d.node("news").unwrap().options.is_empty());
+        assert_eq!(d.items().collect::<Vec<_>>(), vec!["access_card", "wrench"]);
+        assert!(serde_yaml::from_str::<Dialogue>("start: a\nnodes: {}\nmood: grumpy\n").is_err());
+    }
+}
@@ -0,0 +1,102 @@
+//! Effects: the changes to the game that commands and scripts ask for, as data.
+//!
+//! Widgets, dialogue options and on_command scripts describe what should happen as a list of
+//! effects, and `commands::effects::apply` carries them out for the player through the usual
+//! services. Checking an effect against the room needs no database, so the rules are testable on
+//! their own.
+
+use crate::models::room::{RoomView, is_valid_sound_cue};
+use crate::models::types::Direction;
+use crate::models::widget::WidgetEffects;
+use serde_json::Value;
+
+#[derive(Debug, Clone, PartialEq)]
+pub enum Effect {
+    /// Show a line to the player
+    Output(String),
+    /// Play a sound cue on web clients
+    Sound(String),
+    /// Walk through the exit, like `go`
+    Move(Direction),
+    /// Move the player to the room with this key in the realm, like a hazard does
+    Teleport(String),
+    /// Put items into the player's inventory
+    GiveItem { item: String, qty: i32 },
+    /// Use up items from the player's inventory; the effects after it are skipped when the player
+    /// does not have them
+    TakeItem { item: String, qty: i32 },
+    /// Lock or unlock an exit of the room for the player
+    LockExit { dir: Direction, locked: bool },
+    /// Set state of an object of the room for the player
+    SetKv { object: String, key: String, value: Value },
+}
+
+impl Effect {
+    /// Why the effect cannot apply in the room, if it cannot
+    pub fn validate(&self, rv: &RoomView) -> Result<(), String> {
+        match self {
+            Effect::Sound(cue) if !is_valid_sound_cue(cue) => Err(format!("invalid sound cue '{}'", cue)),
+            Effect::Move(dir) | Effect::LockExit { dir, .. } if !rv.exits_by_dir.contains_key(dir) => {
+                Err(format!("no exit '{}'", dir))
+            }
+            Effect::GiveItem { qty, .. } | Effect::TakeItem { qty, .. } if *qty < 1 => {
+                Err(format!("quantity must be at least 1, not {}", qty))
+            }
+            Effect::SetKv { object, .. } if !rv.objects_by_key.contains_key(object) => {
+                Err(format!("no object '{}'", object))
+            }
+            _ => Ok(()),
+        }
+    }
+}
+
+impl WidgetEffects {
+    /// The message first, then the exits unlocked and locked, then the object state
+    pub fn to_effects(&self) -> Vec<Effect> {
+        let dir = |d: &String| Direction::parse(d).unwrap_or_else(|| Direction::Custom(d.clone()));
+
+        let mut out: Vec<Effect> = self.message.iter().cloned().map(Effect::Output).collect();
+        out.extend(self.unlock.iter().map(|d| Effect::LockExit {
+            dir: dir(d),
+            locked: false,
+        }));
+        out.extend(self.lock.iter().map(|d| Effect::LockExit {
+            dir: dir(d),
+            locked: true,
+        }));
+        for (object, state) in &self.set {
+            out.extend(state.iter().map(|(key, value)| Effect::SetKv {
+                object: object.clone(),
+                key: key.clone(),
+                value: value.clone(),
+            }));
+        }
+        out
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn t_widget_effects() {
+        let effects: WidgetEffects =
+            serde_yaml::from_str("message: Clunk.\nunlock: [north]\nset: { lever: { pulled: true } }\n").unwrap();
+        assert_eq!(
+            effects.to_effects(),
+            vec![
+                Effect::Output("Clunk.".into()),
+                Effect::LockExit {
+                    dir: Direction::North,
+                    locked: false
+                },
+                Effect::SetKv {
+                    object: "lever".into(),
+                    key: "pulled".into(),
+                    value: Value::Bool(true)
+                },
+            ]
+        );
+    }
+}
@@ -0,0 +1,46 @@
+use crate::db::DbResult;
+use crate::db::error::DbError;
+use crate::models::types::{BlueprintId, RealmId};
+use tokio_postgres::Row;
+
+/// Where a feature flag applies
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum FeatureScope {
+    Realm(RealmId),
+    Blueprint(BlueprintId),
+}
+
+impl std::fmt::Display for FeatureScope {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match self {
+            FeatureScope::Realm(id) => write!(f, "realm {}", id),
+            FeatureScope::Blueprint(id) => write!(f, "blueprint {}", id),
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct FeatureFlag {
+    pub feature: String,
+    pub scope: FeatureScope,
+    pub enabled: bool,
+}
+
+impl FeatureFlag {
+    pub fn try_from_row(row: &Row) -> DbResult<Self> {
+        let realm_id: Option<RealmId> = row.try_get("realm_id")?;
+        let bp_id: Option<BlueprintId> = row.try_get("bp_id")?;
+
+        let scope = match (realm_id, bp_id) {
+            (Some(realm_id), None) => FeatureScope::Realm(realm_id),
+            (None, Some(bp_id)) => FeatureScope::Blueprint(bp_id),
+            _ => return Err(DbError::Decode("feature flag must have exactly one scope".into())),
+        };
+
+        Ok(Self {
+            feature: row.try_get("feature")?,
+            scope,
+            enabled: row.try_get("enabled")?,
+        })
+    }
+}
@@ -1,4 +1,7 @@
 use crate::db::DbResult;
+use crate::db::error::DbError;
+use crate::input::matcher;
+use crate::input::parser::NounPhrase;
 use crate::models::types::{AccountId, BlueprintId, ItemId, ObjectId, RealmId, RoomId};
 use tokio_postgres::Row;
 
@@ -30,6 +33,24 @@ pub struct Item {
 
     /// Whether multiple instances can stack
     pub stackable: bool,
+
+    /// Uses before the item breaks, None when it never wears
+    pub max_durability: Option<i32>,
+
+    /// Durability lost per use
+    pub wear_per_use: i32,
+
+    /// Item key needed to repair it
+    pub repair_tool: Option<String>,
+
+    /// Weight in kilograms, None when not given
+    pub weight: Option<f32>,
+
+    /// Pages shown by `read`, empty when the item is not readable
+    pub pages: Vec<String>,
+
+    /// Money: picked up into the realm balance instead of the inventory
+    pub is_coin: bool,
 }
 
 impl Item {
@@ -44,6 +65,12 @@ impl Item {
             examine: row.try_get("examine")?,
             stackable: row.try_get("stackable")?,
             nouns: row.try_get("nouns")?,
+            max_durability: row.try_get("max_durability")?,
+            wear_per_use: row.try_get("wear_per_use")?,
+            repair_tool: row.try_get("repair_tool")?,
+            weight: row.try_get("weight")?,
+            pages: row.try_get("pages")?,
+            is_coin: row.try_get("is_coin")?,
         })
     }
 }
@@ -78,6 +105,11 @@ pub struct ItemInstance {
     pub examine: Option<String>,
     pub stackable: bool,
     pub nouns: Vec<String>,
+    pub max_durability: Option<i32>,
+    pub wear_per_use: i32,
+    pub repair_tool: Option<String>,
+    pub weight: Option<f32>,
+    pub is_coin: bool,
 
     /// Timestamps
     pub created_at: chrono::DateTime<chrono::Utc>,
@@ -85,6 +117,40 @@ pub struct ItemInstance {
 }
 
 impl ItemInstance {
+    /// Maps a row of `item_instances` joined with `bp_items_catalog`, by column name
+    pub(crate) fn try_from_row(row: &Row) -> DbResult<ItemInstance> {
+        let location = ItemLocation::from_db_columns(
+            row.try_get("room_id")?,
+            row.try_get("account_id")?,
+            row.try_get("object_id")?,
+            row.try_get("container_item_id")?,
+        )
+        .map_err(DbError::DataError)?;
+
+        Ok(ItemInstance {
+            instance_id: row.try_get("instance_id")?,
+            realm_id: row.try_get("realm_id")?,
+            catalog_id: row.try_get("catalog_id")?,
+            location,
+            quantity: row.try_get("quantity")?,
+            condition: row.try_get("condition")?,
+            item_key: row.try_get("item_key")?,
+            name: row.try_get("name")?,
+            short: row.try_get("short")?,
+            description: row.try_get("description")?,
+            examine: row.try_get("examine")?,
+            stackable: row.try_get("stackable")?,
+            nouns: row.try_get("nouns")?,
+            max_durability: row.try_get("max_durability")?,
+            wear_per_use: row.try_get("wear_per_use")?,
+            repair_tool: row.try_get("repair_tool")?,
+            weight: row.try_get("weight")?,
+            is_coin: row.try_get("is_coin")?,
+            created_at: row.try_get("created_at")?,
+            updated_at: row.try_get("updated_at")?,
+        })
+    }
+
     /// Check if this item is in a specific location
     pub fn is_at(&self, location: ItemLocation) -> bool {
         self.location == location
@@ -101,6 +167,105 @@ impl ItemInstance {
     }
 
     /// Get display text for inventory listing
+    pub fn display_text(&self) -> String {
+        if self.stackable && self.quantity > 1 {
+            format!("{} (x{})", self.short, self.quantity)
+        } else if self.is_broken() {
+            format!("{} (broken)", self.short)
+        } else {
+            self.short.clone()
+        }
+    }
+
+    /// The short description for `count` of this item, e.g. "a coin (x3)"
+    pub fn counted_text(&self, count: i32) -> String {
+        if count > 1 {
+            format!("{} (x{})", self.short, count)
+        } else {
+            self.short.clone()
+        }
+    }
+
+    /// How well `np` names this item, over its nouns and key (see `input::matcher`)
+    pub fn match_score(&self, np: &NounPhrase) -> u32 {
+        let names: Vec<&str> = self
+            .nouns
+            .iter()
+            .map(String::as_str)
+            .chain(std::iter::once(self.item_key.as_str()))
+            .collect();
+        matcher::score(np, &names, &self.short)
+    }
+
+    /// Remaining durability, None when the item never wears. Instances without a recorded
+    /// durability are in mint condition.
+    pub fn durability(&self) -> Option<i32> {
+        let max = self.max_durability?;
+        let current = self
+            .condition
+            .as_ref()
+            .and_then(|c| c.get(DURABILITY_KEY))
+            .and_then(|v| v.as_i64())
+            .map(|v| v.clamp(0, max as i64) as i32);
+        Some(current.unwrap_or(max))
+    }
+
+    pub fn is_broken(&self) -> bool {
+        self.durability() == Some(0)
+    }
+
+    /// Whether the instance is marked as worn or wielded in its condition
+    pub fn is_equipped(&self) -> bool {
+        self.condition
+            .as_ref()
+            .and_then(|c| c.get(EQUIPPED_KEY))
+            .and_then(|v| v.as_bool())
+            .unwrap_or(false)
+    }
+}
+
+/// A slot in a player's stash in a storage object (see `models::room::Storage`). Stashed items
+/// leave the realm: they are instances again once withdrawn.
+#[derive(Debug, Clone)]
+pub struct StashedItem {
+    /// Slot ID (from object_stashes.id)
+    pub id: ItemId,
+    pub object_id: ObjectId,
+    pub account_id: AccountId,
+    /// None when the stash is persistent and so kept across realms
+    pub realm_id: Option<RealmId>,
+    pub catalog_id: ItemId,
+    pub quantity: i32,
+    /// Condition of the instance when it was deposited
+    pub condition: serde_json::Value,
+
+    // Denormalized fields from catalog
+    pub item_key: String,
+    pub name: String,
+    pub short: String,
+    pub stackable: bool,
+    pub nouns: Vec<String>,
+}
+
+impl StashedItem {
+    pub(crate) fn try_from_row(row: &Row) -> DbResult<StashedItem> {
+        Ok(StashedItem {
+            id: row.try_get("id")?,
+            object_id: row.try_get("object_id")?,
+            account_id: row.try_get("account_id")?,
+            realm_id: row.try_get("realm_id")?,
+            catalog_id: row.try_get("catalog_id")?,
+            quantity: row.try_get("quantity")?,
+            condition: row.try_get("condition")?,
+            item_key: row.try_get("item_key")?,
+            name: row.try_get("name")?,
+            short: row.try_get("short")?,
+            stackable: row.try_get("stackable")?,
+            nouns: row.try_get("nouns")?,
+        })
+    }
+
+    /// Get display text for the stash listing
     pub fn display_text(&self) -> String {
         if self.stackable && self.quantity > 1 {
             format!("{} (x{})", self.short, self.quantity)
@@ -108,8 +273,38 @@ impl ItemInstance {
             self.short.clone()
         }
     }
+
+    /// How well `np` names this item, over its nouns and key (see `input::matcher`)
+    pub fn match_score(&self, np: &NounPhrase) -> u32 {
+        let names: Vec<&str> = self
+            .nouns
+            .iter()
+            .map(String::as_str)
+            .chain(std::iter::once(self.item_key.as_str()))
+            .collect();
+        matcher::score(np, &names, &self.short)
+    }
+}
+
+/// A dropped item cleared out of a room under its blueprint's drop policy
+/// (see `models::blueprint::DropPolicy`)
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ClearedItem {
+    pub realm_id: RealmId,
+    /// Room the item was lying in
+    pub room_id: RoomId,
+    /// Short description with the count, e.g. "a coin (x3)"
+    pub text: String,
+    /// Whether it went back to where it was spawned, rather than despawning
+    pub returned: bool,
 }
 
+/// Key in `item_instances.condition` holding the remaining durability
+pub const DURABILITY_KEY: &str = "durability";
+
+/// Key in `item_instances.condition` set to true while the item is worn or wielded
+pub const EQUIPPED_KEY: &str = "equipped";
+
 /// Represents where an item instance is located in the game world
 #[derive(Debug, Clone, Copy, PartialEq, Eq)]
 pub enum ItemLocation {
@@ -161,3 +356,106 @@ impl ItemLocation {
         matches!(self, ItemLocation::Room(id) if *id == room_id)
     }
 }
+
+/// Blueprint recipe: two input items combine into an output item
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Recipe {
+    pub key: String,
+    /// Item keys of the two inputs, in any order
+    pub inputs: Vec<String>,
+    pub output_key: String,
+    pub output_qty: i32,
+    /// Item that must be in the inventory; it is not consumed
+    pub tool_key: Option<String>,
+    /// Minimum player level (see `game::xp_to_level`)
+    pub min_level: Option<i32>,
+    /// Shown instead of the default message after crafting
+    pub message: Option<String>,
+}
+
+impl Recipe {
+    pub(crate) fn try_from_row(row: &Row) -> DbResult<Recipe> {
+        Ok(Recipe {
+            key: row.try_get("recipe_key")?,
+            inputs: row.try_get("inputs")?,
+            output_key: row.try_get("output_key")?,
+            output_qty: row.try_get("output_qty")?,
+            tool_key: row.try_get("tool_key")?,
+            min_level: row.try_get("min_level")?,
+            message: row.try_get("message")?,
+        })
+    }
+
+    /// Whether the two item keys are this recipe's inputs, in either order
+    pub fn matches(&self, a: &str, b: &str) -> bool {
+        match self.inputs.as_slice() {
+            [x, y] => (x == a && y == b) || (x == b && y == a),
+            _ => false,
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn t_recipe_matches_either_order() {
+        let recipe = Recipe {
+            key: "torch".into(),
+            inputs: vec!["stick".into(), "rag".into()],
+            output_key: "torch".into(),
+            output_qty: 1,
+            tool_key: None,
+            min_level: None,
+            message: None,
+        };
+        assert!(recipe.matches("stick", "rag"));
+        assert!(recipe.matches("rag", "stick"));
+        assert!(!recipe.matches("stick", "stick"));
+        assert!(!recipe.matches("rag", "wire"));
+    }
+
+    fn instance(max_durability: Option<i32>, condition: serde_json::Value) -> ItemInstance {
+        ItemInstance {
+            instance_id: ItemId::new(),
+            realm_id: RealmId::new(),
+            catalog_id: ItemId::new(),
+            location: ItemLocation::Player(AccountId::new()),
+            quantity: 1,
+            condition: Some(condition),
+            item_key: "spanner".into(),
+            name: "Spanner".into(),
+            short: "a spanner".into(),
+            description: "A spanner.".into(),
+            examine: None,
+            stackable: false,
+            nouns: vec!["spanner".into()],
+            max_durability,
+            wear_per_use: 1,
+            repair_tool: None,
+            weight: None,
+            is_coin: false,
+            created_at: chrono::Utc::now(),
+            updated_at: chrono::Utc::now(),
+        }
+    }
+
+    #[test]
+    fn t_durability_from_condition() {
+        assert_eq!(instance(None, serde_json::json!({"durability": 3})).durability(), None);
+        assert_eq!(instance(Some(5), serde_json::json!({})).durability(), Some(5));
+        assert_eq!(
+            instance(Some(5), serde_json::json!({"durability": 3})).durability(),
+            Some(3)
+        );
+        assert_eq!(
+            instance(Some(5), serde_json::json!({"durability": 9})).durability(),
+            Some(5)
+        );
+
+        let broken = instance(Some(5), serde_json::json!({"durability": 0}));
+        assert!(broken.is_broken());
+        assert_eq!(broken.display_text(), "a spanner (broken)");
+    }
+}
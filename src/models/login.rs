@@ -0,0 +1,69 @@
+//! Login throttling.
+//!
+//! Failed logins are counted per account and per IP address over the last `lockout_minutes`.
+//! Every failure is answered a little later than the one before, too many failures lock the
+//! account for a while, and an IP address failing across many accounts is refused outright.
+//! Failures from before the last successful login do not count against the account.
+
+use crate::config::AccountsConfig;
+use chrono::TimeDelta;
+use std::time::Duration;
+
+/// The delay stops doubling after this many failures
+const MAX_DOUBLINGS: u32 = 5;
+
+#[derive(Debug, Clone, PartialEq)]
+pub struct LoginThrottle {
+    pub lockout_threshold: u32,
+    pub ip_lockout_threshold: u32,
+    /// How far back failures count, and how long a lock lasts
+    pub window: TimeDelta,
+    pub base_delay: Duration,
+}
+
+impl LoginThrottle {
+    pub fn from_config(cfg: &AccountsConfig) -> Self {
+        Self {
+            lockout_threshold: cfg.lockout_threshold,
+            ip_lockout_threshold: cfg.ip_lockout_threshold,
+            window: TimeDelta::minutes(cfg.lockout_minutes as i64),
+            base_delay: Duration::from_millis(cfg.login_delay_ms),
+        }
+    }
+
+    /// How long to wait before answering, with `failures` recent failures including this one
+    pub fn delay(&self, failures: u32) -> Duration {
+        match failures {
+            0 => Duration::ZERO,
+            n => self.base_delay * 2u32.pow((n - 1).min(MAX_DOUBLINGS)),
+        }
+    }
+
+    pub fn locks_account(&self, failures: u32) -> bool {
+        failures >= self.lockout_threshold
+    }
+
+    pub fn blocks_ip(&self, failures: u32) -> bool {
+        failures >= self.ip_lockout_threshold
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn t_login_throttle() {
+        let t = LoginThrottle::from_config(&AccountsConfig::default());
+        assert_eq!(t.delay(0), Duration::ZERO);
+        assert_eq!(t.delay(1), Duration::from_millis(500));
+        assert_eq!(t.delay(3), Duration::from_millis(2_000));
+        assert_eq!(t.delay(40), Duration::from_millis(16_000));
+
+        assert!(!t.locks_account(4));
+        assert!(t.locks_account(5));
+        assert!(!t.blocks_ip(19));
+        assert!(t.blocks_ip(20));
+        assert_eq!(t.window, TimeDelta::minutes(15));
+    }
+}
@@ -0,0 +1,12 @@
+/// Rows of one kind of garbage that a garbage collection found, or purged
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Garbage {
+    /// What the rows are, e.g. "items in deleted realms"
+    pub what: &'static str,
+    pub count: u64,
+}
+
+/// Rows over all kinds of garbage
+pub fn total(garbage: &[Garbage]) -> u64 {
+    garbage.iter().map(|g| g.count).sum()
+}
@@ -0,0 +1,151 @@
+//! Export bundles of a player's full state.
+//!
+//! `@admin export-player` writes everything stored for an account to one JSON file: the account
+//! itself, its characters, the items it carries (with the contents of carried containers), its
+//! private room and object state (where quest progress is kept), its own exit locks and which
+//! loot it has been handed out. `@admin import-player` restores such a file, replacing whatever
+//! the account has now; an account that no longer exists is recreated.
+//!
+//! Rows are kept as the database has them, so a bundle stays readable without this code. The
+//! password hash is never exported.
+
+use crate::models::types::AccountId;
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::str::FromStr;
+
+/// Bumped when the bundle layout changes in a way older code cannot read
+pub const BUNDLE_FORMAT: u32 = 1;
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct PlayerBundle {
+    pub format: u32,
+    /// RFC 3339 time of the export
+    pub exported_at: String,
+    /// Row of `accounts`, without the password hash
+    pub account: Value,
+    /// Rows of `characters`
+    #[serde(default)]
+    pub characters: Vec<Value>,
+    /// Rows of `item_instances`: carried items and what is inside them
+    #[serde(default)]
+    pub items: Vec<Value>,
+    /// Rows of `user_room_kv`
+    #[serde(default)]
+    pub room_kv: Vec<Value>,
+    /// Rows of `user_object_kv`
+    #[serde(default)]
+    pub object_kv: Vec<Value>,
+    /// Rows of `user_exits`
+    #[serde(default)]
+    pub exits: Vec<Value>,
+    /// Rows of `loot_instantiation_state`
+    #[serde(default)]
+    pub loot_state: Vec<Value>,
+}
+
+impl PlayerBundle {
+    pub fn username(&self) -> Option<&str> {
+        self.account.get("username").and_then(Value::as_str)
+    }
+
+    /// Checks the bundle can be imported and returns the account it belongs to: every row must
+    /// be the account's, and contained items must sit in an item of the bundle.
+    pub fn validate(&self) -> Result<AccountId, String> {
+        if self.format != BUNDLE_FORMAT {
+            return Err(format!(
+                "bundle format {} is not supported (expected {})",
+                self.format, BUNDLE_FORMAT
+            ));
+        }
+        let id = self
+            .account
+            .get("id")
+            .and_then(Value::as_str)
+            .and_then(|s| AccountId::from_str(s).ok())
+            .ok_or("account has no valid id")?;
+        if self.username().is_none_or(str::is_empty) {
+            return Err("account has no username".into());
+        }
+
+        let owned = |rows: &[Value], what: &str| -> Result<(), String> {
+            match rows.iter().position(|r| account_of(r) != Some(id)) {
+                Some(i) => Err(format!("{} row {} is not the account's", what, i + 1)),
+                None => Ok(()),
+            }
+        };
+        owned(&self.characters, "characters")?;
+        owned(&self.room_kv, "room_kv")?;
+        owned(&self.object_kv, "object_kv")?;
+        owned(&self.exits, "exits")?;
+        owned(&self.loot_state, "loot_state")?;
+
+        let item_ids: Vec<&str> = self
+            .items
+            .iter()
+            .filter_map(|r| r.get("instance_id").and_then(Value::as_str))
+            .collect();
+        for (i, item) in self.items.iter().enumerate() {
+            let carried = account_of(item) == Some(id);
+            let contained = item
+                .get("container_item_id")
+                .and_then(Value::as_str)
+                .is_some_and(|c| item_ids.contains(&c));
+            if !carried && !contained {
+                return Err(format!("items row {} is not carried by the account", i + 1));
+            }
+        }
+
+        Ok(id)
+    }
+}
+
+fn account_of(row: &Value) -> Option<AccountId> {
+    row.get("account_id")
+        .and_then(Value::as_str)
+        .and_then(|s| AccountId::from_str(s).ok())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use serde_json::json;
+
+    fn bundle(id: AccountId) -> PlayerBundle {
+        PlayerBundle {
+            format: BUNDLE_FORMAT,
+            exported_at: "2025-05-02T18:30:00Z".into(),
+            account: json!({ "id": id.to_string(), "username": "joshua" }),
+            characters: vec![],
+            items: vec![
+                json!({ "instance_id": "a", "account_id": id.to_string(), "container_item_id": null }),
+                json!({ "instance_id": "b", "account_id": null, "container_item_id": "a" }),
+            ],
+            room_kv: vec![json!({ "account_id": id.to_string(), "key": "quest.stage", "value": 2 })],
+            object_kv: vec![],
+            exits: vec![],
+            loot_state: vec![],
+        }
+    }
+
+    #[test]
+    fn t_validate_bundle() {
+        let id = AccountId::new();
+        let b = bundle(id);
+        assert_eq!(b.validate(), Ok(id));
+        assert_eq!(b.username(), Some("joshua"));
+
+        let mut other = b.clone();
+        other.room_kv[0]["account_id"] = json!(AccountId::new().to_string());
+        assert!(other.validate().unwrap_err().contains("room_kv row 1"));
+
+        let mut loose = b.clone();
+        loose.items[1]["container_item_id"] = json!("elsewhere");
+        assert!(loose.validate().unwrap_err().contains("items row 2"));
+
+        let mut future = b;
+        future.format = BUNDLE_FORMAT + 1;
+        assert!(future.validate().is_err());
+    }
+}
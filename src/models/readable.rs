@@ -0,0 +1,72 @@
+/// Paginated text being read: a book, log or datapad
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Readable {
+    /// Name of the thing being read (e.g. "Maintenance Log")
+    pub title: String,
+    pub pages: Vec<String>,
+}
+
+impl Readable {
+    pub fn new(title: impl Into<String>, pages: Vec<String>) -> Self {
+        Self {
+            title: title.into(),
+            pages,
+        }
+    }
+
+    pub fn page_count(&self) -> usize {
+        self.pages.len()
+    }
+
+    /// Renders a page (0-based, clamped to the last page) with a header and, when there is more
+    /// to read, a footer pointing to the next page. Returns the page actually shown.
+    pub fn render(&self, page: usize) -> (usize, String) {
+        let count = self.page_count();
+        if count == 0 {
+            return (0, format!("There is nothing written on the {}.", self.title));
+        }
+
+        let page = page.min(count - 1);
+        let text = self.pages[page].trim_end();
+        if count == 1 {
+            return (page, text.to_string());
+        }
+
+        let mut out = format!(
+            "{{c:bright_white}}{} - page {}/{}{{c}}\n{}",
+            self.title,
+            page + 1,
+            count,
+            text
+        );
+        if page + 1 < count {
+            out.push_str("\n{c:bright_black}(type 'read next' to turn the page){c}");
+        }
+        (page, out)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn t_render_pages() {
+        let log = Readable::new("log", vec!["Day 1.".into(), "Day 2.\n".into()]);
+
+        let (page, out) = log.render(0);
+        assert_eq!(page, 0);
+        assert!(out.contains("page 1/2"));
+        assert!(out.contains("Day 1."));
+        assert!(out.contains("read next"));
+
+        let (page, out) = log.render(7);
+        assert_eq!(page, 1);
+        assert!(out.contains("Day 2."));
+        assert!(!out.contains("read next"));
+
+        let note = Readable::new("note", vec!["Hi.".into()]);
+        assert_eq!(note.render(0).1, "Hi.");
+        assert!(Readable::new("pad", vec![]).render(0).1.contains("nothing written"));
+    }
+}
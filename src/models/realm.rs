@@ -118,6 +118,8 @@ pub struct Realm {
     pub kind: RealmKind,
     /// Creation timestamp
     pub created_at: DateTime<Utc>,
+    /// Prompt of the blueprint, for players that did not pick their own
+    pub prompt: Option<String>,
 }
 
 impl Realm {
@@ -143,6 +145,7 @@ impl Realm {
             title: row.try_get("title")?,
             kind,
             created_at: row.try_get("created_at")?,
+            prompt: row.try_get("prompt")?,
         })
     }
 }
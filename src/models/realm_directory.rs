@@ -0,0 +1,182 @@
+//! The realm directory shown by `realms`.
+//!
+//! Lists the published realms with what their blueprint says about them (author, difficulty,
+//! tags), how many players are in them right now, how long players took to complete them and how
+//! they rated them.
+
+use crate::db::DbResult;
+use crate::models::types::RealmId;
+use tokio_postgres::Row;
+
+/// Realms shown per page, unless the player set another page size
+pub const PAGE_SIZE: u32 = 10;
+
+/// Difficulties a blueprint can declare, easiest first
+pub const DIFFICULTIES: [&str; 4] = ["easy", "normal", "hard", "expert"];
+
+/// Which realms to list
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct DirectoryFilter {
+    /// Only realms tagged with this, by their blueprint or by players
+    pub tag: Option<String>,
+    /// Only realms whose blueprint is owned by this user
+    pub author: Option<String>,
+}
+
+/// A page of the directory
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct DirectoryQuery {
+    pub filter: DirectoryFilter,
+    /// First page is 1
+    pub page: u32,
+    pub page_size: u32,
+}
+
+impl Default for DirectoryQuery {
+    fn default() -> Self {
+        Self {
+            filter: DirectoryFilter::default(),
+            page: 1,
+            page_size: PAGE_SIZE,
+        }
+    }
+}
+
+impl DirectoryQuery {
+    /// Parses the arguments of `realms`: `[page] <n>`, `tag <tag>` and `by <author>`, in any order
+    pub fn parse(args: &[&str]) -> Result<Self, String> {
+        let mut query = Self::default();
+
+        let mut it = args.iter();
+        while let Some(arg) = it.next() {
+            match *arg {
+                "page" => {
+                    let n = it.next().ok_or("'page' needs a number")?;
+                    query.page = parse_page(n)?;
+                }
+                "tag" => query.filter.tag = Some(it.next().ok_or("'tag' needs a tag")?.to_string()),
+                "by" => query.filter.author = Some(it.next().ok_or("'by' needs an author")?.to_string()),
+                n if n.chars().all(|c| c.is_ascii_digit()) => query.page = parse_page(n)?,
+                other => return Err(format!("unknown option '{}'", other)),
+            }
+        }
+
+        Ok(query)
+    }
+
+    /// Whether these arguments ask for a listing rather than name a realm
+    pub fn is_listing(args: &[&str]) -> bool {
+        match args.first() {
+            None => true,
+            Some(first) => matches!(*first, "page" | "tag" | "by") || first.chars().all(|c| c.is_ascii_digit()),
+        }
+    }
+
+    pub fn offset(&self) -> i64 {
+        i64::from(self.page.saturating_sub(1)) * i64::from(self.page_size)
+    }
+}
+
+fn parse_page(s: &str) -> Result<u32, String> {
+    match s.parse::<u32>() {
+        Ok(n) if n > 0 => Ok(n),
+        _ => Err(format!("'{}' is not a page number", s)),
+    }
+}
+
+/// One realm in the directory
+#[derive(Debug, Clone)]
+pub struct DirectoryEntry {
+    pub realm_id: RealmId,
+    pub key: String,
+    pub title: String,
+    /// Owner of the blueprint; None when the account was deleted
+    pub author: Option<String>,
+    pub difficulty: Option<String>,
+    pub tags: Vec<String>,
+    /// Average seconds between first entering and completing the realm; None until someone has
+    pub avg_completion_secs: Option<i64>,
+    /// Average player rating in stars; None until someone rated it
+    pub avg_stars: Option<f64>,
+    pub ratings: i64,
+}
+
+impl DirectoryEntry {
+    pub fn try_from_row(row: &Row) -> DbResult<Self> {
+        Ok(Self {
+            realm_id: row.try_get("id")?,
+            key: row.try_get("key")?,
+            title: row.try_get("title")?,
+            author: row.try_get("author")?,
+            difficulty: row.try_get("difficulty")?,
+            tags: row.try_get("tags")?,
+            avg_completion_secs: row.try_get("avg_completion_secs")?,
+            avg_stars: row.try_get("avg_stars")?,
+            ratings: row.try_get("ratings")?,
+        })
+    }
+}
+
+/// Matching realms on the requested page, with the number of matches over all pages
+#[derive(Debug, Clone)]
+pub struct DirectoryPage {
+    pub entries: Vec<DirectoryEntry>,
+    pub total: u32,
+    pub page_size: u32,
+}
+
+impl DirectoryPage {
+    pub fn pages(&self) -> u32 {
+        self.total.div_ceil(self.page_size.max(1)).max(1)
+    }
+}
+
+/// Short form of a completion time, e.g. `1h05m` or `12m`
+pub fn format_duration(secs: i64) -> String {
+    let mins = secs.max(0) / 60;
+    match mins {
+        0 => "<1m".to_string(),
+        m if m < 60 => format!("{}m", m),
+        m => format!("{}h{:02}m", m / 60, m % 60),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn t_parse_query() {
+        assert_eq!(DirectoryQuery::parse(&[]).unwrap(), DirectoryQuery::default());
+
+        let q = DirectoryQuery::parse(&["tag", "horror", "by", "joshua", "page", "3"]).unwrap();
+        assert_eq!(q.filter.tag.as_deref(), Some("horror"));
+        assert_eq!(q.filter.author.as_deref(), Some("joshua"));
+        assert_eq!(q.page, 3);
+        assert_eq!(q.offset(), 20);
+
+        assert_eq!(DirectoryQuery::parse(&["2"]).unwrap().page, 2);
+        assert!(DirectoryQuery::parse(&["page", "0"]).is_err());
+        assert!(DirectoryQuery::parse(&["tag"]).is_err());
+        assert!(DirectoryQuery::parse(&["sort", "name"]).is_err());
+
+        assert!(DirectoryQuery::is_listing(&[]));
+        assert!(DirectoryQuery::is_listing(&["2"]));
+        assert!(DirectoryQuery::is_listing(&["by", "joshua"]));
+        assert!(!DirectoryQuery::is_listing(&["live_world"]));
+
+        let q = DirectoryQuery {
+            page: 3,
+            page_size: 25,
+            ..DirectoryQuery::default()
+        };
+        assert_eq!(q.offset(), 50);
+    }
+
+    #[test]
+    fn t_format_duration() {
+        assert_eq!(format_duration(30), "<1m");
+        assert_eq!(format_duration(12 * 60 + 5), "12m");
+        assert_eq!(format_duration(3600 + 5 * 60), "1h05m");
+    }
+}
@@ -0,0 +1,238 @@
+use crate::db::DbResult;
+use crate::models::types::AccountId;
+use chrono::{DateTime, Utc};
+use serde::{Deserialize, Serialize};
+use std::time::Instant;
+use tokio_postgres::Row;
+
+/// Events kept per recording; a forgotten recorder should not grow without bounds
+pub const MAX_RECORDING_EVENTS: usize = 5_000;
+
+/// Stored instead of input typed while the input is hidden (passwords)
+const HIDDEN_INPUT: &str = "********";
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "lowercase")]
+pub enum RecordingEventKind {
+    Input,
+    Output,
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct RecordingEvent {
+    /// Milliseconds since the start of the recording
+    pub at_ms: u64,
+    pub kind: RecordingEventKind,
+    pub text: String,
+}
+
+/// A recording in progress, kept in the session until it is stopped or the player disconnects
+#[derive(Debug)]
+pub struct SessionRecorder {
+    pub started_at: DateTime<Utc>,
+    started: Instant,
+    /// Who started the recording (the player, or a moderator)
+    pub recorded_by: AccountId,
+    events: Vec<RecordingEvent>,
+    truncated: bool,
+}
+
+impl SessionRecorder {
+    pub fn new(recorded_by: AccountId) -> Self {
+        Self {
+            started_at: Utc::now(),
+            started: Instant::now(),
+            recorded_by,
+            events: Vec::new(),
+            truncated: false,
+        }
+    }
+
+    pub fn input(&mut self, text: &str, hidden: bool) {
+        self.push(RecordingEventKind::Input, if hidden { HIDDEN_INPUT } else { text });
+    }
+
+    pub fn output(&mut self, text: &str) {
+        self.push(RecordingEventKind::Output, text);
+    }
+
+    fn push(&mut self, kind: RecordingEventKind, text: &str) {
+        if self.truncated {
+            return;
+        }
+        let at_ms = self.started.elapsed().as_millis() as u64;
+        if self.events.len() + 1 >= MAX_RECORDING_EVENTS {
+            self.truncated = true;
+            self.events.push(RecordingEvent {
+                at_ms,
+                kind: RecordingEventKind::Output,
+                text: "[recording truncated]".into(),
+            });
+            return;
+        }
+        self.events.push(RecordingEvent {
+            at_ms,
+            kind,
+            text: text.to_string(),
+        });
+    }
+
+    pub fn len(&self) -> usize {
+        self.events.len()
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.events.is_empty()
+    }
+
+    pub fn into_events(self) -> Vec<RecordingEvent> {
+        self.events
+    }
+}
+
+/// A stored recording
+#[derive(Debug, Clone)]
+pub struct Recording {
+    pub id: i64,
+    pub account_id: AccountId,
+    pub username: String,
+    pub recorded_by: Option<AccountId>,
+    pub started_at: DateTime<Utc>,
+    pub ended_at: DateTime<Utc>,
+    pub events: Vec<RecordingEvent>,
+}
+
+impl Recording {
+    /// Expects the recorded player's username joined in as `username`
+    pub fn try_from_row(row: &Row) -> DbResult<Self> {
+        let events: serde_json::Value = row.try_get("events")?;
+        Ok(Self {
+            id: row.try_get("id")?,
+            account_id: row.try_get("account_id")?,
+            username: row.try_get("username")?,
+            recorded_by: row.try_get("recorded_by")?,
+            started_at: row.try_get("started_at")?,
+            ended_at: row.try_get("ended_at")?,
+            events: serde_json::from_value(events)?,
+        })
+    }
+
+    /// Groups the transcript into steps: each input with the output that followed it. Output
+    /// before the first input forms a step without input.
+    pub fn steps(&self) -> Vec<ReplayStep> {
+        let mut steps: Vec<ReplayStep> = Vec::new();
+        for ev in &self.events {
+            match ev.kind {
+                RecordingEventKind::Input => steps.push(ReplayStep {
+                    at_ms: ev.at_ms,
+                    input: Some(ev.text.clone()),
+                    output: Vec::new(),
+                }),
+                RecordingEventKind::Output => match steps.last_mut() {
+                    Some(step) => step.output.push(ev.text.clone()),
+                    None => steps.push(ReplayStep {
+                        at_ms: ev.at_ms,
+                        input: None,
+                        output: vec![ev.text.clone()],
+                    }),
+                },
+            }
+        }
+        steps
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct ReplayStep {
+    pub at_ms: u64,
+    pub input: Option<String>,
+    pub output: Vec<String>,
+}
+
+/// Listing entry, without the transcript itself
+#[derive(Debug, Clone)]
+pub struct RecordingSummary {
+    pub id: i64,
+    pub username: String,
+    pub started_at: DateTime<Utc>,
+    pub ended_at: DateTime<Utc>,
+    pub events: i32,
+}
+
+impl RecordingSummary {
+    pub fn try_from_row(row: &Row) -> DbResult<Self> {
+        Ok(Self {
+            id: row.try_get("id")?,
+            username: row.try_get("username")?,
+            started_at: row.try_get("started_at")?,
+            ended_at: row.try_get("ended_at")?,
+            events: row.try_get("events")?,
+        })
+    }
+}
+
+/// A recording as saved; id and end time are assigned by the database
+#[derive(Debug, Clone)]
+pub struct NewRecording {
+    pub account_id: AccountId,
+    pub recorded_by: AccountId,
+    pub started_at: DateTime<Utc>,
+    pub events: Vec<RecordingEvent>,
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn recording(rec: SessionRecorder) -> Recording {
+        Recording {
+            id: 1,
+            account_id: AccountId::new(),
+            username: "bob".into(),
+            recorded_by: None,
+            started_at: rec.started_at,
+            ended_at: Utc::now(),
+            events: rec.into_events(),
+        }
+    }
+
+    #[test]
+    fn t_steps_group_output_by_input() {
+        let mut rec = SessionRecorder::new(AccountId::new());
+        rec.output("welcome");
+        rec.input("look", false);
+        rec.output("a cell");
+        rec.output("a crate");
+        rec.input("secret", true);
+        let steps = recording(rec).steps();
+
+        assert_eq!(steps.len(), 3);
+        assert_eq!(steps[0].input, None);
+        assert_eq!(steps[1].input.as_deref(), Some("look"));
+        assert_eq!(steps[1].output, vec!["a cell", "a crate"]);
+        assert_eq!(steps[2].input.as_deref(), Some(HIDDEN_INPUT));
+    }
+
+    #[test]
+    fn t_recorder_truncates() {
+        let mut rec = SessionRecorder::new(AccountId::new());
+        for _ in 0..MAX_RECORDING_EVENTS + 10 {
+            rec.input("look", false);
+        }
+        assert_eq!(rec.len(), MAX_RECORDING_EVENTS);
+        let events = rec.into_events();
+        assert_eq!(events.last().unwrap().text, "[recording truncated]");
+    }
+
+    #[test]
+    fn t_event_json() {
+        let ev = RecordingEvent {
+            at_ms: 12,
+            kind: RecordingEventKind::Input,
+            text: "look".into(),
+        };
+        let json = serde_json::to_value(&ev).unwrap();
+        assert_eq!(json["kind"], "input");
+        assert_eq!(serde_json::from_value::<RecordingEvent>(json).unwrap(), ev);
+    }
+}
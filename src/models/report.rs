@@ -0,0 +1,46 @@
+use crate::db::DbResult;
+use crate::models::types::{AccountId, RealmId, RoomId};
+use chrono::{DateTime, Utc};
+use tokio_postgres::Row;
+
+/// An abuse report filed by one player against another
+#[derive(Debug, Clone)]
+pub struct Report {
+    pub id: i64,
+    pub reporter_id: AccountId,
+    pub reporter: String,
+    pub target_id: AccountId,
+    pub target: String,
+    pub reason: String,
+    /// Where the reporter was when filing
+    pub realm_id: Option<RealmId>,
+    pub room_id: Option<RoomId>,
+    pub created_at: DateTime<Utc>,
+}
+
+impl Report {
+    /// Expects the reporter and target usernames joined in as `reporter` and `target`
+    pub fn try_from_row(row: &Row) -> DbResult<Self> {
+        Ok(Self {
+            id: row.try_get("id")?,
+            reporter_id: row.try_get("reporter_id")?,
+            reporter: row.try_get("reporter")?,
+            target_id: row.try_get("target_id")?,
+            target: row.try_get("target")?,
+            reason: row.try_get("reason")?,
+            realm_id: row.try_get("realm_id")?,
+            room_id: row.try_get("room_id")?,
+            created_at: row.try_get("created_at")?,
+        })
+    }
+}
+
+/// A report as filed; id and timestamps are assigned by the database
+#[derive(Debug, Clone)]
+pub struct NewReport {
+    pub reporter_id: AccountId,
+    pub target_id: AccountId,
+    pub reason: String,
+    pub realm_id: Option<RealmId>,
+    pub room_id: Option<RoomId>,
+}
@@ -0,0 +1,146 @@
+//! Player ratings, reviews and tags of realms.
+//!
+//! Players that completed a realm can give it 1 to 5 stars with a short review, and tag it. The
+//! realm directory shows the average rating; blueprint authors see everything with `@bp feedback`.
+
+use crate::db::DbResult;
+use chrono::{DateTime, Utc};
+use tokio_postgres::Row;
+
+/// Longest review, in characters
+pub const MAX_REVIEW_LEN: usize = 280;
+/// Longest tag, in characters
+pub const MAX_TAG_LEN: usize = 24;
+/// Tags one player can put on a realm
+pub const MAX_TAGS_PER_PLAYER: usize = 5;
+
+/// A player's rating of a realm
+#[derive(Debug, Clone)]
+pub struct Rating {
+    pub stars: u8,
+    pub review: Option<String>,
+    pub updated_at: DateTime<Utc>,
+}
+
+impl Rating {
+    pub fn try_from_row(row: &Row) -> DbResult<Self> {
+        Ok(Self {
+            stars: row.try_get::<_, i16>("stars")? as u8,
+            review: row.try_get("review")?,
+            updated_at: row.try_get("updated_at")?,
+        })
+    }
+}
+
+pub fn parse_stars(s: &str) -> Result<u8, String> {
+    match s.parse::<u8>() {
+        Ok(n @ 1..=5) => Ok(n),
+        _ => Err(format!("'{}' is not a rating, use 1 to 5 stars", s)),
+    }
+}
+
+/// Trims the review; an empty review is no review
+pub fn normalize_review(s: &str) -> Result<Option<String>, String> {
+    let s = s.trim();
+    if s.is_empty() {
+        return Ok(None);
+    }
+    if s.chars().count() > MAX_REVIEW_LEN {
+        return Err(format!("a review is at most {} characters", MAX_REVIEW_LEN));
+    }
+    Ok(Some(s.to_string()))
+}
+
+/// Tags are single lowercase words of letters, digits and dashes
+pub fn normalize_tag(s: &str) -> Result<String, String> {
+    let tag = s.trim().trim_start_matches('#').to_lowercase();
+    if tag.is_empty() || tag.chars().count() > MAX_TAG_LEN {
+        return Err(format!("a tag is 1 to {} characters", MAX_TAG_LEN));
+    }
+    if !tag.chars().all(|c| c.is_alphanumeric() || c == '-') {
+        return Err(format!("'{}' is not a tag, use letters, digits and dashes", s.trim()));
+    }
+    Ok(tag)
+}
+
+/// Five characters wide, e.g. `★★★☆☆` for 3
+pub fn stars_bar(stars: u8) -> String {
+    (1..=5).map(|n| if n <= stars { '★' } else { '☆' }).collect()
+}
+
+/// A review as authors see it
+#[derive(Debug, Clone)]
+pub struct ReviewEntry {
+    /// None when the account was deleted
+    pub username: Option<String>,
+    pub realm_key: Option<String>,
+    pub rating: Rating,
+}
+
+impl ReviewEntry {
+    pub fn try_from_row(row: &Row) -> DbResult<Self> {
+        Ok(Self {
+            username: row.try_get("username")?,
+            realm_key: row.try_get("realm_key")?,
+            rating: Rating::try_from_row(row)?,
+        })
+    }
+}
+
+/// What players think of the realms of one blueprint
+#[derive(Debug, Clone, Default)]
+pub struct BlueprintFeedback {
+    /// Ratings with 1 to 5 stars
+    pub histogram: [u32; 5],
+    /// Player tags with how many players used them, most used first
+    pub tags: Vec<(String, u32)>,
+    /// Latest reviews with text first
+    pub reviews: Vec<ReviewEntry>,
+}
+
+impl BlueprintFeedback {
+    pub fn ratings(&self) -> u32 {
+        self.histogram.iter().sum()
+    }
+
+    pub fn average(&self) -> Option<f64> {
+        let total = self.ratings();
+        if total == 0 {
+            return None;
+        }
+        let sum: u32 = self.histogram.iter().zip(1..).map(|(n, stars)| n * stars).sum();
+        Some(f64::from(sum) / f64::from(total))
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn t_review_input() {
+        assert_eq!(parse_stars("4"), Ok(4));
+        assert!(parse_stars("0").is_err());
+        assert!(parse_stars("6").is_err());
+        assert!(parse_stars("four").is_err());
+
+        assert_eq!(normalize_review("  "), Ok(None));
+        assert_eq!(normalize_review(" Great fun "), Ok(Some("Great fun".to_string())));
+        assert!(normalize_review(&"x".repeat(MAX_REVIEW_LEN + 1)).is_err());
+
+        assert_eq!(normalize_tag("#Sci-Fi"), Ok("sci-fi".to_string()));
+        assert!(normalize_tag("two words").is_err());
+        assert!(normalize_tag("").is_err());
+
+        assert_eq!(stars_bar(3), "★★★☆☆");
+    }
+
+    #[test]
+    fn t_feedback_average() {
+        let mut f = BlueprintFeedback::default();
+        assert_eq!(f.average(), None);
+        f.histogram = [0, 0, 1, 0, 1];
+        assert_eq!(f.ratings(), 2);
+        assert_eq!(f.average(), Some(4.0));
+    }
+}
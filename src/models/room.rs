@@ -1,14 +1,22 @@
 use crate::db::DbResult;
 use crate::db::error::DbError;
+use crate::input::matcher;
+use crate::input::parser::{Intent, NUMBER_WORDS, NounPhrase, Verb, parse_command};
 use crate::lua::ScriptHook;
+use crate::models::dialogue::Dialogue;
 use crate::models::room_helpers::{compute_object_visible, merge_kv, resolve_bool, resolve_qty};
-use crate::models::types::{BlueprintId, Direction, ExitId, HintId, ObjectId, RoomId};
+use crate::models::types::{BlueprintId, Direction, ExitId, HintId, ObjectId, RealmId, RoomId};
+use crate::models::vehicle::Vehicle;
+use crate::models::widget::Widget;
 use serde::{Deserialize, Serialize};
 use serde_json::Value;
-use std::collections::HashMap;
+use std::collections::{BTreeMap, HashMap};
 use std::str::FromStr;
 use tokio_postgres::Row;
-use uuid::Uuid;
+
+/// Seconds a player has to wait before asking again escalates a tiered hint, unless the hint
+/// sets its own `tier_delay`
+pub const DEFAULT_HINT_TIER_DELAY: u32 = 30;
 
 /// Hints that a user can request
 #[derive(Debug, Clone, Serialize, Deserialize)]
@@ -18,6 +26,228 @@ pub struct Hint {
     pub text: String,
     pub when: String,          // first_look, enter, search, after_fail
     pub cooldown: Option<u32>, // seconds; null = no cooldown
+    #[serde(default)]
+    pub limit: Option<u32>, // Show at most this many times; null = unlimited
+    #[serde(default)]
+    pub tiers: Vec<String>, // Escalations after `text`: clearer clue .. explicit solution
+    #[serde(default)]
+    pub tier_delay: Option<u32>, // seconds before asking again escalates; null = default
+}
+
+impl Hint {
+    /// Key of the player's progress for this hint in the user room KV
+    pub fn state_key(&self) -> String {
+        format!("__hint_{}", self.id)
+    }
+
+    /// Index of the final tier; 0 for a hint without tiers
+    pub fn last_tier(&self) -> u32 {
+        self.tiers.len() as u32
+    }
+
+    /// Text of a tier, tier 0 being `text`
+    pub fn tier_text(&self, tier: u32) -> &str {
+        match tier {
+            0 => &self.text,
+            n => self.tiers.get(n as usize - 1).unwrap_or(&self.text),
+        }
+    }
+
+    /// The tier shown next to a player with the given progress
+    pub fn next_tier(&self, state: &HintState) -> u32 {
+        if state.seen == 0 {
+            0
+        } else {
+            (state.tier + 1).min(self.last_tier())
+        }
+    }
+
+    /// How many times the final tier may be shown to a player, `once` counting as a limit of 1
+    pub fn max_shows(&self) -> Option<u32> {
+        match (self.once.unwrap_or(false), self.limit) {
+            (true, Some(limit)) => Some(limit.min(1)),
+            (true, None) => Some(1),
+            (false, limit) => limit,
+        }
+    }
+
+    /// Whether the hint can be shown to a player with the given progress at `now` (unix seconds).
+    /// A tiered hint escalates one tier per request, but only after the tier delay; `once`,
+    /// `limit` and `cooldown` apply to the final tier.
+    pub fn availability(&self, state: &HintState, now: i64) -> HintAvailability {
+        let Some(last) = state.last_shown_at else {
+            return HintAvailability::Ready;
+        };
+
+        let wait = if state.tier < self.last_tier() {
+            self.tier_delay.unwrap_or(DEFAULT_HINT_TIER_DELAY)
+        } else {
+            if self.max_shows().is_some_and(|max| state.tier_seen >= max) {
+                return HintAvailability::Exhausted;
+            }
+            self.cooldown.unwrap_or(0)
+        };
+
+        let ready_at = last + wait as i64;
+        if now < ready_at {
+            return HintAvailability::CoolingDown((ready_at - now) as u64);
+        }
+        HintAvailability::Ready
+    }
+}
+
+/// A player's progress on a single hint
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct HintState {
+    /// Times the hint was shown, over all tiers
+    #[serde(default)]
+    pub seen: u32,
+    /// Highest tier shown so far
+    #[serde(default)]
+    pub tier: u32,
+    /// Times that tier was shown
+    #[serde(default)]
+    pub tier_seen: u32,
+    /// Unix timestamp of the last time it was shown
+    #[serde(default)]
+    pub last_shown_at: Option<i64>,
+}
+
+impl HintState {
+    /// Reads the progress of `hint` from the user room KV; missing or malformed entries start fresh
+    pub fn from_kv(kv: &Kv, hint: &Hint) -> Self {
+        kv.get(&hint.state_key())
+            .and_then(|v| serde_json::from_value(v.clone()).ok())
+            .unwrap_or_default()
+    }
+
+    /// Progress after showing `tier` at `now`
+    pub fn shown(&self, tier: u32, now: i64) -> Self {
+        let tier_seen = if self.seen > 0 && tier == self.tier {
+            self.tier_seen.saturating_add(1)
+        } else {
+            1
+        };
+        Self {
+            seen: self.seen.saturating_add(1),
+            tier,
+            tier_seen,
+            last_shown_at: Some(now),
+        }
+    }
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum HintAvailability {
+    Ready,
+    /// Seconds until the hint can be shown again
+    CoolingDown(u64),
+    /// Shown as often as allowed
+    Exhausted,
+}
+
+/// Sound cue identifiers for room events. Web clients map a cue to an audio file; telnet clients
+/// ignore them.
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct RoomSounds {
+    /// Played to a player entering the room
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub enter: Option<String>,
+    /// Played when a script unlocks an exit of the room
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub unlock: Option<String>,
+    /// Played along with a hint
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub hint: Option<String>,
+}
+
+impl RoomSounds {
+    pub fn is_empty(&self) -> bool {
+        self.enter.is_none() && self.unlock.is_none() && self.hint.is_none()
+    }
+
+    pub fn cues(&self) -> impl Iterator<Item = &str> {
+        [&self.enter, &self.unlock, &self.hint]
+            .into_iter()
+            .flatten()
+            .map(String::as_str)
+    }
+}
+
+/// Sound cues are short identifiers like `door_hiss` or `ambient/reactor`, not file names or URLs
+pub fn is_valid_sound_cue(cue: &str) -> bool {
+    (1..=64).contains(&cue.len())
+        && cue
+            .chars()
+            .all(|c| c.is_ascii_alphanumeric() || matches!(c, '_' | '-' | '.' | '/'))
+}
+
+/// Built-in verbs whose commands the room's on_command script sees before the engine does, with
+/// the object keys each applies to (none for every command with the verb)
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(transparent)]
+pub struct ScriptFirst(pub BTreeMap<String, Vec<String>>);
+
+/// Verbs a room may take over from the engine: things done in the room, not to the account
+pub const SCRIPT_FIRST_VERBS: [Verb; 21] = [
+    Verb::Look,
+    Verb::Examine,
+    Verb::Search,
+    Verb::Read,
+    Verb::Board,
+    Verb::Disembark,
+    Verb::Press,
+    Verb::Take,
+    Verb::Drop,
+    Verb::Give,
+    Verb::Push,
+    Verb::Pull,
+    Verb::Turn,
+    Verb::Throw,
+    Verb::Open,
+    Verb::Unlock,
+    Verb::Lock,
+    Verb::Use,
+    Verb::Put,
+    Verb::Talk,
+    Verb::Go,
+];
+
+impl ScriptFirst {
+    pub fn is_empty(&self) -> bool {
+        self.0.is_empty()
+    }
+
+    /// The same verbs under their canonical names (`get` becomes `take`); fails on a verb that is
+    /// not in `SCRIPT_FIRST_VERBS`
+    pub fn normalized(&self) -> Result<ScriptFirst, String> {
+        let mut out = BTreeMap::<String, Vec<String>>::new();
+        for (word, objects) in &self.0 {
+            let verb = parse_command(word).verb;
+            if !SCRIPT_FIRST_VERBS.contains(&verb) {
+                return Err(format!("'{}' is not a built-in verb a room script can take over", word));
+            }
+            let entry = out.entry(verb.as_str().to_string()).or_default();
+            entry.extend(objects.iter().cloned());
+            entry.sort();
+            entry.dedup();
+        }
+        Ok(ScriptFirst(out))
+    }
+
+    /// Whether the room's script sees the command before the engine
+    pub fn covers(&self, intent: &Intent, rv: &RoomView) -> bool {
+        let Some(objects) = self.0.get(intent.verb.as_str()) else {
+            return false;
+        };
+        objects.is_empty()
+            || intent
+                .direct
+                .as_ref()
+                .and_then(|np| rv.resolve_object(np))
+                .is_some_and(|obj| objects.contains(&obj.key))
+    }
 }
 
 /// Blueprint room model for `bp_rooms`. There are no zone or user overlays in here
@@ -31,6 +261,14 @@ pub struct BlueprintRoom {
     pub lockdown: bool,
     pub short: Option<String>,
     pub hints: Vec<Hint>,
+    pub hazards: Vec<Hazard>,
+    pub sounds: RoomSounds,
+    /// Set when the room is a vehicle travelling between stops
+    pub vehicle: Option<Vehicle>,
+    /// Built-in verbs offered to the on_command script first
+    pub script_first: ScriptFirst,
+    /// Version of the Lua API the room's scripts were written for
+    pub api_version: i16,
 }
 
 impl BlueprintRoom {
@@ -39,18 +277,58 @@ impl BlueprintRoom {
         let hints = parse_hints_value(hints_val)?;
 
         Ok(BlueprintRoom {
-            id: RoomId(row.try_get::<_, Uuid>("id")?),
-            bp_id: BlueprintId(row.try_get::<_, Uuid>("bp_id")?),
+            id: row.try_get("id")?,
+            bp_id: row.try_get("bp_id")?,
             key: row.try_get("key")?,
             title: row.try_get("title")?,
             body: row.try_get("body")?,
             lockdown: row.try_get("lockdown")?,
             short: row.try_get("short")?,
             hints,
+            hazards: serde_json::from_value(row.try_get("hazards")?)
+                .map_err(|e| DbError::Validation(format!("invalid hazards: {e}")))?,
+            sounds: serde_json::from_value(row.try_get("sounds")?)
+                .map_err(|e| DbError::Validation(format!("invalid sounds: {e}")))?,
+            vehicle: row
+                .try_get::<_, Option<Value>>("vehicle")?
+                .map(serde_json::from_value)
+                .transpose()
+                .map_err(|e| DbError::Validation(format!("invalid vehicle: {e}")))?,
+            script_first: serde_json::from_value(row.try_get("script_first")?)
+                .map_err(|e| DbError::Validation(format!("invalid script_first: {e}")))?,
+            api_version: row.try_get("api_version")?,
         })
     }
 }
 
+/// A note a builder left on a room or one of its objects. Never shown to players.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct BuilderNote {
+    pub room_key: String,
+    /// Set when the note is on an object of the room
+    pub object_key: Option<String>,
+    pub text: String,
+}
+
+impl BuilderNote {
+    pub fn try_from_row(row: &Row) -> DbResult<Self> {
+        Ok(BuilderNote {
+            room_key: row.try_get("room_key")?,
+            object_key: row.try_get("object_key")?,
+            text: row.try_get("note")?,
+        })
+    }
+}
+
+impl std::fmt::Display for BuilderNote {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        match &self.object_key {
+            Some(obj) => write!(f, "room:{}:object:{}: {}", self.room_key, obj, self.text),
+            None => write!(f, "room:{}: {}", self.room_key, self.text),
+        }
+    }
+}
+
 /// Blueprint exit model. Note these are not reciprocal; each exit is one-way.
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct BlueprintExit {
@@ -72,6 +350,8 @@ pub struct BlueprintExit {
     pub visible_when_locked: bool,
     /// Is the exit locked by default?
     pub default_locked: bool,
+    /// What opens the exit when it is locked
+    pub lock: Option<Lock>,
 }
 
 impl BlueprintExit {
@@ -81,7 +361,7 @@ impl BlueprintExit {
             .ok_or_else(|| DbError::Decode(format!("invalid direction in bp_exits: {}", dir_s)))?;
 
         Ok(Self {
-            id: ExitId(row.try_get::<_, Uuid>("id")?),
+            id: row.try_get("id")?,
             from_room_id: row.try_get("from_room_id")?,
             from_room_key: row.try_get("from_room_key")?,
             dir,
@@ -90,6 +370,11 @@ impl BlueprintExit {
             description: row.try_get("description")?,
             visible_when_locked: row.try_get("visible_when_locked")?,
             default_locked: row.try_get("locked")?,
+            lock: row
+                .try_get::<_, Option<Value>>("lock_info")?
+                .map(serde_json::from_value)
+                .transpose()
+                .map_err(|e| DbError::Decode(format!("Failed to deserialize lock: {}", e)))?,
         })
     }
 }
@@ -122,6 +407,20 @@ pub struct BlueprintObject {
     pub examine: Option<String>,
     /// Lua script to run when `use`
     pub on_use_lua: Option<String>,
+    /// Pages shown by `read`
+    pub pages: Vec<String>,
+    /// Lua script generating the pages when `read`
+    pub on_read_lua: Option<String>,
+    /// Lua script receiving the lines typed while the object is `use`d as a terminal
+    pub on_terminal_lua: Option<String>,
+    /// Lua script deciding whether the object accepts an item given to it
+    pub on_receive_lua: Option<String>,
+    /// Key of the vehicle room players step into with `board`
+    pub board: Option<String>,
+    /// Built-in device handling (keypad, lever bank, menu)
+    pub widget: Option<Widget>,
+    /// Conversation tree when the object is an NPC
+    pub dialogue: Option<Dialogue>,
     /// Position for ordering (optional)
     pub position: Option<i32>,
     /// Synonyms / alternate nouns (terminal, console, computer, screen)
@@ -139,8 +438,20 @@ pub struct BlueprintObject {
     pub takeable: bool,
     /// Is the object stackable?
     pub stackable: bool,
+    /// Can the object be pushed and pulled?
+    pub movable: bool,
+    /// Can the object be turned?
+    pub turnable: bool,
     /// Is the object a coin/currency?
     pub is_coin: bool,
+    /// Visible or obscured
+    pub discovery: Discovery,
+    /// Cooldown and maximum number of uses
+    pub use_limits: UseLimits,
+    /// What opens the object when it is locked
+    pub lock: Option<Lock>,
+    /// Per-player stash, when the object is a locker
+    pub storage: Option<Storage>,
 
     /// Loot configuration
     pub loot: Option<ObjectLoot>,
@@ -164,13 +475,43 @@ impl BlueprintObject {
         let flags = serde_json::from_value::<ObjectFlags>(flags_json)
             .map_err(|e| DbError::Decode(format!("Failed to deserialize flags: {}", e)))?;
 
+        let discovery = serde_json::from_value::<Discovery>(row.try_get("discovery")?)
+            .map_err(|e| DbError::Decode(format!("Failed to deserialize discovery: {}", e)))?;
+        let use_limits = serde_json::from_value::<UseLimits>(row.try_get("use_limits")?)
+            .map_err(|e| DbError::Decode(format!("Failed to deserialize use limits: {}", e)))?;
+        let lock = row
+            .try_get::<_, Option<Value>>("lock_info")?
+            .map(serde_json::from_value::<Lock>)
+            .transpose()
+            .map_err(|e| DbError::Decode(format!("Failed to deserialize lock: {}", e)))?;
+        let storage = row
+            .try_get::<_, Option<Value>>("storage")?
+            .map(serde_json::from_value::<Storage>)
+            .transpose()
+            .map_err(|e| DbError::Decode(format!("Failed to deserialize storage: {}", e)))?;
+
         Ok(Self {
-            id: ObjectId(row.try_get::<_, Uuid>("id")?),
+            id: row.try_get("id")?,
             name: row.try_get("name")?,
             short: row.try_get("short")?,
             description: row.try_get("description")?,
             examine: row.try_get("examine")?,
             on_use_lua: row.try_get("use_lua")?,
+            pages: row.try_get("pages")?,
+            on_read_lua: row.try_get("read_lua")?,
+            on_terminal_lua: row.try_get("terminal_lua")?,
+            on_receive_lua: row.try_get("receive_lua")?,
+            board: row.try_get("board")?,
+            widget: row
+                .try_get::<_, Option<Value>>("widget")?
+                .map(serde_json::from_value)
+                .transpose()
+                .map_err(|e| DbError::Decode(format!("Failed to deserialize widget: {}", e)))?,
+            dialogue: row
+                .try_get::<_, Option<Value>>("dialogue")?
+                .map(serde_json::from_value)
+                .transpose()
+                .map_err(|e| DbError::Decode(format!("Failed to deserialize dialogue: {}", e)))?,
             position: row.try_get("position")?,
             nouns: row.try_get("nouns")?,
 
@@ -181,12 +522,303 @@ impl BlueprintObject {
             default_revealed: flags.revealed,
             takeable: flags.takeable,
             stackable: flags.stackable,
+            movable: flags.movable,
+            turnable: flags.turnable,
             is_coin: false,
+            discovery,
+            use_limits,
+            lock,
+            storage,
             loot,
         })
     }
 }
 
+/// How a player finds an object
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(tag = "mode", rename_all = "snake_case")]
+pub enum Discovery {
+    /// In plain view (unless revealed is off)
+    #[default]
+    Visible,
+    /// Only found by a `search` whose perception roll meets the difficulty class
+    Obscured { dc: u32 },
+    /// Tucked away behind, under or over another object of the room (by its id), and found by
+    /// looking there: `look under bed`
+    Stashed { place: Place, object: String },
+}
+
+impl Discovery {
+    pub fn is_visible(&self) -> bool {
+        matches!(self, Discovery::Visible)
+    }
+
+    /// Whether the object is stashed at `place` of the object `key`
+    pub fn is_stashed(&self, place: Place, key: &str) -> bool {
+        matches!(self, Discovery::Stashed { place: p, object } if *p == place && object == key)
+    }
+}
+
+/// Where one object can be hidden relative to another
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum Place {
+    Behind,
+    Under,
+    Over,
+}
+
+impl Place {
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Place::Behind => "behind",
+            Place::Under => "under",
+            Place::Over => "over",
+        }
+    }
+}
+
+/// Whose uses count towards the limits of an object
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum UseScope {
+    /// Every player has their own cooldown and uses
+    #[default]
+    Player,
+    /// One cooldown and one count of uses for everyone in the realm
+    Shared,
+}
+
+/// What opens a locked exit or object, so a player bumping into it is told what they need
+#[derive(Debug, Clone, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct Lock {
+    #[serde(default)]
+    pub kind: LockKind,
+    /// Number of digits of a keypad
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub digits: Option<u32>,
+    /// Own feedback, replacing the one derived from the kind
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub message: Option<String>,
+}
+
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum LockKind {
+    /// Nothing to tell
+    #[default]
+    Plain,
+    Key,
+    Keycard,
+    Keypad,
+    Combination,
+    /// Opened from somewhere else: a lever, a console
+    Mechanism,
+}
+
+impl Lock {
+    /// What the player is told about the lock; None when there is nothing more to say than that it
+    /// is locked
+    pub fn feedback(&self) -> Option<String> {
+        if let Some(msg) = &self.message {
+            return Some(msg.clone());
+        }
+        let text = match self.kind {
+            LockKind::Plain => return None,
+            LockKind::Key => "It needs a key.".to_string(),
+            LockKind::Keycard => "It needs a keycard.".to_string(),
+            LockKind::Keypad => match self.digits {
+                Some(n) => format!("A {}-digit keypad blinks.", number_word(n)),
+                None => "A keypad blinks.".to_string(),
+            },
+            LockKind::Combination => "A combination dial is set into it.".to_string(),
+            LockKind::Mechanism => "It is held shut by a mechanism somewhere else.".to_string(),
+        };
+        Some(text)
+    }
+}
+
+fn number_word(n: u32) -> String {
+    match n.checked_sub(1).and_then(|i| NUMBER_WORDS.get(i as usize)) {
+        Some(word) => word.to_string(),
+        None => n.to_string(),
+    }
+}
+
+/// "The way is locked." or the lock's own feedback after `locked`
+pub fn locked_message(locked: &str, lock: Option<&Lock>) -> String {
+    match lock.and_then(Lock::feedback) {
+        Some(feedback) if lock.is_some_and(|l| l.message.is_some()) => feedback,
+        Some(feedback) => format!("{} {}", locked, feedback),
+        None => locked.to_string(),
+    }
+}
+
+/// Limits on using an object, checked before its on_use script runs
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct UseLimits {
+    /// Seconds before the object can be used again
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub cooldown: Option<u32>,
+    /// Uses before the object stops working
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub max_uses: Option<u32>,
+    #[serde(default)]
+    pub scope: UseScope,
+}
+
+/// Object KV key the uses of an object are stored under, per player or shared
+pub const OBJECT_USAGE_KEY: &str = "__usage";
+
+/// How often and when an object was last used
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ObjectUsage {
+    pub uses: u32,
+    /// Epoch seconds
+    pub last_used_at: Option<i64>,
+}
+
+/// Why an object cannot be used right now
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum UseDenied {
+    Cooldown { remaining_secs: u64 },
+    UsedUp,
+}
+
+impl UseDenied {
+    pub fn message(&self, object: &str) -> String {
+        match self {
+            UseDenied::Cooldown { remaining_secs: 1 } => {
+                format!("The {} is not ready yet. Try again in a second.", object)
+            }
+            UseDenied::Cooldown { remaining_secs } => {
+                format!(
+                    "The {} is not ready yet. Try again in {} seconds.",
+                    object, remaining_secs
+                )
+            }
+            UseDenied::UsedUp => format!("The {} does not work anymore.", object),
+        }
+    }
+}
+
+impl UseLimits {
+    pub fn is_limited(&self) -> bool {
+        self.cooldown.is_some() || self.max_uses.is_some()
+    }
+
+    /// Counts a use at `now` (epoch seconds), unless the limits refuse it
+    pub fn try_use(&self, usage: &ObjectUsage, now: i64) -> Result<ObjectUsage, UseDenied> {
+        if self.max_uses.is_some_and(|max| usage.uses >= max) {
+            return Err(UseDenied::UsedUp);
+        }
+        if let (Some(cooldown), Some(last)) = (self.cooldown, usage.last_used_at) {
+            let ready_at = last + i64::from(cooldown);
+            if now < ready_at {
+                return Err(UseDenied::Cooldown {
+                    remaining_secs: (ready_at - now) as u64,
+                });
+            }
+        }
+        Ok(ObjectUsage {
+            uses: usage.uses.saturating_add(1),
+            last_used_at: Some(now),
+        })
+    }
+}
+
+/// Slots in a storage object when it does not set `capacity`
+pub const DEFAULT_STORAGE_CAPACITY: u32 = 10;
+
+fn default_storage_capacity() -> u32 {
+    DEFAULT_STORAGE_CAPACITY
+}
+
+/// A storage object (locker, bank box) keeping a stash for each player that `deposit`s into it
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Storage {
+    /// Slots per player; a stack takes one slot
+    #[serde(default = "default_storage_capacity")]
+    pub capacity: u32,
+    /// Whether the stash outlives a reset of the realm
+    #[serde(default)]
+    pub persistent: bool,
+}
+
+impl Default for Storage {
+    fn default() -> Self {
+        Self {
+            capacity: DEFAULT_STORAGE_CAPACITY,
+            persistent: false,
+        }
+    }
+}
+
+impl Storage {
+    /// The realm the stash is kept in: None when it is persistent and so kept across all realms
+    /// of the blueprint
+    pub fn stash_realm(&self, realm_id: RealmId) -> Option<RealmId> {
+        (!self.persistent).then_some(realm_id)
+    }
+}
+
+/// Seconds between two applications of a hazard, unless it sets `interval`
+pub const DEFAULT_HAZARD_INTERVAL: u32 = 10;
+
+fn default_hazard_interval() -> u32 {
+    DEFAULT_HAZARD_INTERVAL
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(rename_all = "snake_case")]
+pub enum HazardKind {
+    Gas,
+    Radiation,
+    Vacuum,
+}
+
+impl HazardKind {
+    /// Shown when the hazard does not set its own message
+    pub fn default_message(&self) -> &'static str {
+        match self {
+            HazardKind::Gas => "The gas burns in your lungs.",
+            HazardKind::Radiation => "Your skin prickles from the radiation.",
+            HazardKind::Vacuum => "The vacuum tugs at you and the cold bites.",
+        }
+    }
+}
+
+/// An environmental hazard of a room, applied to every player in it once per `interval`
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Hazard {
+    pub kind: HazardKind,
+    /// Seconds between two applications; the first one comes a full interval after entering
+    #[serde(default = "default_hazard_interval")]
+    pub interval: u32,
+    /// Health lost per application
+    #[serde(default)]
+    pub damage: u32,
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub message: Option<String>,
+    /// Item keys; carrying any of them protects against the hazard
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub mitigated_by: Vec<String>,
+    /// Shown instead when an item protects the player
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub mitigated_message: Option<String>,
+    /// Room key the player is forced into
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub move_to: Option<String>,
+}
+
+impl Hazard {
+    pub fn message(&self) -> &str {
+        self.message.as_deref().unwrap_or(self.kind.default_message())
+    }
+}
+
 /// Blueprint LUA room scripts
 #[derive(Debug, Clone, Default, Serialize, Deserialize)]
 pub struct RoomScripts(HashMap<ScriptHook, String>);
@@ -219,10 +851,6 @@ impl Kv {
         Kv { inner: HashMap::new() }
     }
 
-    pub(crate) fn get_bool(&self, key: &str, default: bool) -> bool {
-        self.inner.get(key).and_then(|v| v.as_bool()).unwrap_or(default)
-    }
-
     pub(crate) fn get_num<T: FromStr>(&self, key: &str, default: T) -> T {
         self.inner
             .get(key)
@@ -262,6 +890,23 @@ impl Kv {
     }
 }
 
+/// A single shared KV value together with the version it was read at. The version is used for
+/// compare-and-swap updates on the realm level KV tables.
+#[derive(Debug, Clone, PartialEq)]
+pub struct VersionedValue {
+    pub value: Value,
+    pub version: i64,
+}
+
+impl VersionedValue {
+    pub fn try_from_row(row: &Row) -> DbResult<Self> {
+        Ok(Self {
+            value: row.try_get("value")?,
+            version: row.try_get("version")?,
+        })
+    }
+}
+
 /// Runtime view the engine uses. It contains all resolved data for the specific zone and user
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct RoomView {
@@ -301,20 +946,45 @@ impl RoomView {
     //     })
     // }
 
+    /// Adds the objects scripts spawned into the room, after the blueprint objects. A spawned
+    /// object never shadows a blueprint object with the same key.
+    pub fn add_spawned(&mut self, spawned: &[SpawnedObject]) {
+        for s in spawned {
+            if self.objects_by_key.contains_key(&s.key) {
+                continue;
+            }
+            self.objects_by_key.insert(s.key.clone(), self.objects.len());
+            self.objects.push(s.resolved());
+        }
+    }
+
     pub fn object_by_key(&self, obj_key: &str) -> Option<&ResolvedObject> {
         self.objects_by_key.get(obj_key).and_then(|&idx| self.objects.get(idx))
     }
 
     pub fn object_by_noun(&self, noun: &str) -> Option<&ResolvedObject> {
-        self.objects
+        self.objects.iter().find(|o| o.is_called(noun))
+    }
+
+    /// The object the player means by `np`, scored over the names of the visible objects (see
+    /// `input::matcher`). An ordinal ("second key") counts in the order the room lists them.
+    /// Without one, an exact name of a hidden object still finds it, as `object_by_noun` does.
+    pub fn resolve_object(&self, np: &NounPhrase) -> Option<&ResolvedObject> {
+        let scored = self
+            .objects
             .iter()
-            .find(|o| o.name.eq_ignore_ascii_case(noun) || o.nouns.iter().any(|n| n.eq_ignore_ascii_case(noun)))
+            .filter(|o| o.flags.is_visible())
+            .map(|o| (o, o.match_score(np)));
+        matcher::pick(scored, np.ordinal).or_else(|| match np.ordinal {
+            None => self.object_by_noun(&np.head),
+            Some(_) => None,
+        })
     }
 }
 
 /// Builds up a complete room view by assembling blueprint, zone, and user data.
 #[allow(clippy::too_many_arguments)]
-pub(crate) fn build_room_view_impl(
+pub fn build_room_view_impl(
     bp_room: &BlueprintRoom,
     bp_exits: &[BlueprintExit],
     bp_objs: &[BlueprintObject],
@@ -351,18 +1021,30 @@ pub(crate) fn build_room_view_impl(
             user_room_kv.get(&key_visible_when_locked).and_then(|v| v.as_bool()),
         );
 
+        // exit.north.to = { room_id, room_key } sends the exit somewhere else than the blueprint
+        let key_to = format!("exit.{}.to", e.dir);
+        let redirect = [user_room_kv, zone_room_kv]
+            .into_iter()
+            .filter_map(|kv| kv.get(&key_to))
+            .find_map(|v| serde_json::from_value::<ExitRedirect>(v.clone()).ok());
+        let (to_room_id, to_room_key) = match redirect {
+            Some(r) => (r.room_id, r.room_key),
+            None => (e.to_room_id, e.to_room_key.clone()),
+        };
+
         let idx = exits.len();
         exits.push(ResolvedExit {
             direction: e.dir.clone(),
             from_room_id: e.from_room_id,
             from_room_key: e.from_room_key.clone(),
-            to_room_id: e.to_room_id,
-            to_room_key: e.to_room_key.clone(),
+            to_room_id,
+            to_room_key,
             flags: ExitFlags {
                 locked,
                 hidden: !visible,
                 visible_when_locked: e.visible_when_locked,
             },
+            lock: e.lock.clone(),
         });
         exits_by_dir.insert(e.dir.clone(), idx);
     }
@@ -393,8 +1075,9 @@ pub(crate) fn build_room_view_impl(
                 .and_then(|s| s.as_bool()),
         );
 
+        // Obscured objects stay unrevealed until a search finds them
         let revealed = resolve_bool(
-            o.default_revealed,
+            o.default_revealed && o.discovery.is_visible(),
             zone_obj_kv
                 .get(&key)
                 .and_then(|kv| kv.get("revealed"))
@@ -416,6 +1099,13 @@ pub(crate) fn build_room_view_impl(
             description: o.description.clone(),
             examine: o.examine.clone(),
             on_use: o.on_use_lua.clone(),
+            pages: o.pages.clone(),
+            on_read: o.on_read_lua.clone(),
+            on_terminal: o.on_terminal_lua.clone(),
+            on_receive: o.on_receive_lua.clone(),
+            board: o.board.clone(),
+            widget: o.widget.clone(),
+            dialogue: o.dialogue.clone(),
             nouns: o.nouns.clone(),
             position: o.position,
             kv,
@@ -426,8 +1116,14 @@ pub(crate) fn build_room_view_impl(
                 revealed,
                 takeable: o.takeable,
                 stackable: o.stackable,
+                movable: o.movable,
+                turnable: o.turnable,
             },
             is_coin: o.is_coin,
+            discovery: o.discovery.clone(),
+            use_limits: o.use_limits,
+            lock: o.lock.clone(),
+            storage: o.storage,
             loot: o.loot.clone(),
         });
     }
@@ -482,6 +1178,9 @@ fn parse_hints_value(hints: Option<Value>) -> DbResult<Vec<Hint>> {
                         text,
                         when: "manual".to_string(),
                         cooldown: None,
+                        limit: None,
+                        tiers: Vec::new(),
+                        tier_delay: None,
                     })
                     .collect();
                 Ok(out)
@@ -544,14 +1243,35 @@ pub struct ObjectFlags {
     pub revealed: bool,  // Has been discovered by the player
     pub takeable: bool,  // Can be picked up
     pub stackable: bool, // Can be stacked in inventory (coins etc.)
+    pub movable: bool,   // Can be pushed and pulled
+    pub turnable: bool,  // Can be turned
 }
 
 impl ObjectFlags {
+    /// Flags builders can switch on and off with `@obj set`
+    pub const NAMES: [&str; 7] = [
+        "locked",
+        "hidden",
+        "revealed",
+        "takeable",
+        "stackable",
+        "movable",
+        "turnable",
+    ];
+
     pub fn is_visible(&self) -> bool {
         !self.hidden || self.revealed
     }
 }
 
+/// Where a script sent an exit instead of the room of the blueprint (`port4k.redirect_exit`),
+/// stored in the room KV as `exit.<dir>.to`
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+pub struct ExitRedirect {
+    pub room_id: RoomId,
+    pub room_key: String,
+}
+
 /// Resolved exit that takes into account the zone and the player's overlays
 #[derive(Debug, Clone, Serialize, Deserialize)]
 pub struct ResolvedExit {
@@ -561,6 +1281,7 @@ pub struct ResolvedExit {
     pub to_room_id: RoomId, // To Room ID
     pub to_room_key: String,
     pub flags: ExitFlags,
+    pub lock: Option<Lock>,
 }
 
 impl ResolvedExit {
@@ -591,6 +1312,13 @@ pub struct ResolvedObject {
     pub examine: Option<String>,
     pub nouns: Vec<String>,
     pub on_use: Option<String>,
+    pub pages: Vec<String>,
+    pub on_read: Option<String>,
+    pub on_terminal: Option<String>,
+    pub on_receive: Option<String>,
+    pub board: Option<String>,
+    pub widget: Option<Widget>,
+    pub dialogue: Option<Dialogue>,
     pub position: Option<i32>,
 
     pub kv: KvResolved,
@@ -598,14 +1326,78 @@ pub struct ResolvedObject {
 
     pub is_coin: bool,
     pub qty: i32,
+    pub discovery: Discovery,
+    pub use_limits: UseLimits,
+    pub lock: Option<Lock>,
+    pub storage: Option<Storage>,
 
     pub loot: Option<ObjectLoot>,
 }
 
+impl ResolvedObject {
+    /// Whether players can refer to the object as `noun`: its name or one of its nouns
+    pub fn is_called(&self, noun: &str) -> bool {
+        self.name.eq_ignore_ascii_case(noun) || self.nouns.iter().any(|n| n.eq_ignore_ascii_case(noun))
+    }
+
+    pub fn match_score(&self, np: &NounPhrase) -> u32 {
+        let names: Vec<&str> = std::iter::once(self.name.as_str())
+            .chain(self.nouns.iter().map(String::as_str))
+            .collect();
+        matcher::score(np, &names, &self.short)
+    }
+}
+
+/// Object a script put into a room at runtime (`port4k.spawn_object`). It belongs to the realm
+/// and stays until a script removes it. Spawned objects are scenery: they can be looked at and
+/// examined, but have no state, scripts or flags of their own.
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct SpawnedObject {
+    pub id: ObjectId,
+    pub key: String,
+    pub short: String,
+    pub description: String,
+    pub nouns: Vec<String>,
+}
+
+impl SpawnedObject {
+    fn resolved(&self) -> ResolvedObject {
+        ResolvedObject {
+            id: self.id,
+            key: self.key.clone(),
+            name: self.key.clone(),
+            short: self.short.clone(),
+            description: self.description.clone(),
+            examine: None,
+            nouns: self.nouns.clone(),
+            on_use: None,
+            pages: Vec::new(),
+            on_read: None,
+            on_terminal: None,
+            on_receive: None,
+            board: None,
+            widget: None,
+            dialogue: None,
+            position: None,
+            kv: KvResolved::default(),
+            flags: ObjectFlags::default(),
+            is_coin: false,
+            qty: 1,
+            discovery: Discovery::default(),
+            use_limits: UseLimits::default(),
+            lock: None,
+            storage: None,
+            loot: None,
+        }
+    }
+}
+
 #[cfg(test)]
 mod tests {
     use super::*;
+    use crate::input::parser::parse_command;
     use serde_json::json;
+    use uuid::Uuid;
 
     // ---------- test helpers (local to tests) ----------
     // Helper: make a RoomId/ObjectId with a stable UUID for deterministic snapshots
@@ -628,6 +1420,11 @@ mod tests {
             title: "Entry Hall".into(),
             body: "A brushed-steel corridor hums with power.".into(),
             lockdown: false,
+            hazards: vec![],
+            sounds: RoomSounds::default(),
+            vehicle: None,
+            script_first: ScriptFirst::default(),
+            api_version: 2,
             short: Some("The station’s entry hall.".into()),
             hints: vec![],
         }
@@ -645,6 +1442,7 @@ mod tests {
             description: Some("A heavy blast door to the north.".into()),
             visible_when_locked,
             default_locked,
+            lock: None,
         }
     }
 
@@ -657,6 +1455,13 @@ mod tests {
             description: "A titanium-alloy wrench with knurled grip.".into(),
             examine: Some("It’s scuffed but reliable.".into()),
             on_use_lua: None,
+            pages: Vec::new(),
+            on_read_lua: None,
+            on_terminal_lua: None,
+            on_receive_lua: None,
+            board: None,
+            widget: None,
+            dialogue: None,
             position: Some(10),
             nouns: vec!["tool".into(), "spanner".into()],
             object_kv: Kv { inner: HashMap::new() },
@@ -665,7 +1470,13 @@ mod tests {
             default_revealed: false,
             takeable: true,
             stackable: false,
+            movable: false,
+            turnable: false,
             is_coin: false,
+            discovery: Discovery::Visible,
+            use_limits: UseLimits::default(),
+            lock: None,
+            storage: None,
             loot: None,
         }
     }
@@ -676,6 +1487,75 @@ mod tests {
             inner: pairs.iter().map(|(k, v)| ((*k).to_string(), (*v).clone())).collect(),
         }
     }
+    // ---------- Hint::availability() ----------
+    fn mk_hint() -> Hint {
+        serde_json::from_value(json!({"id": "h", "text": "Look up", "when": "manual"})).unwrap()
+    }
+
+    #[test]
+    fn hint_availability_once_limit_and_cooldown() {
+        let mut hint = mk_hint();
+        hint.once = Some(true);
+        let fresh = HintState::default();
+        assert_eq!(hint.availability(&fresh, 100), HintAvailability::Ready);
+        assert_eq!(
+            hint.availability(&fresh.shown(0, 100), 200),
+            HintAvailability::Exhausted
+        );
+
+        hint.once = None;
+        hint.limit = Some(2);
+        hint.cooldown = Some(30);
+        let once = fresh.shown(0, 100);
+        assert_eq!(hint.availability(&once, 110), HintAvailability::CoolingDown(20));
+        assert_eq!(hint.availability(&once, 130), HintAvailability::Ready);
+        assert_eq!(hint.availability(&once.shown(0, 130), 500), HintAvailability::Exhausted);
+    }
+
+    #[test]
+    fn hint_tiers_escalate_after_delay() {
+        let mut hint = mk_hint();
+        hint.once = Some(true);
+        hint.tiers = vec!["Check the vent".into(), "Unscrew the vent with the wrench".into()];
+        hint.tier_delay = Some(60);
+
+        let mut state = HintState::default();
+        let mut now = 0;
+        for expected in ["Look up", "Check the vent", "Unscrew the vent with the wrench"] {
+            assert_eq!(hint.availability(&state, now), HintAvailability::Ready);
+            let tier = hint.next_tier(&state);
+            assert_eq!(hint.tier_text(tier), expected);
+            state = state.shown(tier, now);
+            if tier < hint.last_tier() {
+                assert_eq!(hint.availability(&state, now + 10), HintAvailability::CoolingDown(50));
+            }
+            now += 60;
+        }
+        assert_eq!(state.tier, 2);
+        assert_eq!(state.seen, 3);
+        assert_eq!(hint.availability(&state, now), HintAvailability::Exhausted);
+    }
+
+    #[test]
+    fn hint_state_from_kv() {
+        let hint = mk_hint();
+        assert_eq!(hint.limit, None);
+        assert!(hint.tiers.is_empty());
+
+        let kv = kv(&[("__hint_h", &json!({"seen": 2, "tier_seen": 2, "last_shown_at": 7}))]);
+        assert_eq!(
+            HintState::from_kv(&kv, &hint),
+            HintState {
+                seen: 2,
+                tier: 0,
+                tier_seen: 2,
+                last_shown_at: Some(7)
+            }
+        );
+        let bad = self::kv(&[("__hint_h", &json!("garbage"))]);
+        assert_eq!(HintState::from_kv(&bad, &hint), HintState::default());
+    }
+
     // ---------- normalize_when() ----------
     #[test]
     fn normalize_when_variants() {
@@ -769,6 +1649,8 @@ mod tests {
             revealed: false,
             takeable: true,
             stackable: false,
+            movable: false,
+            turnable: false,
         };
         assert!(f.is_visible(), "not hidden => visible");
 
@@ -809,6 +1691,49 @@ mod tests {
         assert!(view.object_by_noun("computer").is_none());
     }
 
+    #[test]
+    fn resolve_object_scores_visible_objects() {
+        let room = mk_room();
+        let wrench = |id: u128, revealed: bool| BlueprintObject {
+            id: ObjectId(Uuid::from_u128(id)),
+            default_revealed: revealed,
+            ..mk_object_wrench()
+        };
+        let objs = vec![wrench(1, true), wrench(2, false), wrench(3, true)];
+        let view = build_room_view_impl(
+            &room,
+            &[],
+            &objs,
+            &RoomScripts::default(),
+            &Kv::default(),
+            &Kv::default(),
+            &HashMap::new(),
+            &HashMap::new(),
+            &Kv::default(),
+            &HashMap::new(),
+            &HashMap::new(),
+        );
+
+        let id = |o: Option<&ResolvedObject>| o.map(|o| o.id);
+        let np = |s: &str| parse_command(&format!("take {s}")).direct.unwrap();
+        assert_eq!(
+            id(view.resolve_object(&np("wrench"))),
+            Some(ObjectId(Uuid::from_u128(1)))
+        );
+        assert_eq!(
+            id(view.resolve_object(&np("second spanner"))),
+            Some(ObjectId(Uuid::from_u128(3)))
+        );
+        assert!(view.resolve_object(&np("third wrench")).is_none());
+        // A guess that fits both wrenches equally well needs an ordinal
+        assert!(view.resolve_object(&np("wrenhc")).is_none());
+        assert_eq!(
+            id(view.resolve_object(&np("second span"))),
+            Some(ObjectId(Uuid::from_u128(3)))
+        );
+        assert!(view.resolve_object(&np("hammer")).is_none());
+    }
+
     // ---------- build_room_view(): exit overlays precedence ----------
     #[test]
     fn build_room_view_exit_overlay_precedence() {
@@ -960,6 +1885,131 @@ mod tests {
     }
 
     // ---------- build_room_view(): objects visibility flags vs revealed ----------
+    #[test]
+    fn build_room_view_obscured_object_until_found() {
+        let room = mk_room();
+        let mut obj = mk_object_wrench();
+        obj.discovery = Discovery::Obscured { dc: 14 };
+        let bp_objs = vec![obj];
+
+        let view_with = |user_obj_kv: &HashMap<String, Kv>| {
+            build_room_view_impl(
+                &room,
+                &[],
+                &bp_objs,
+                &RoomScripts::default(),
+                &Kv::default(),
+                &Kv::default(),
+                &HashMap::new(),
+                &HashMap::new(),
+                &Kv::default(),
+                user_obj_kv,
+                &HashMap::new(),
+            )
+        };
+
+        let view = view_with(&HashMap::new());
+        assert_eq!(view.objects[0].discovery, Discovery::Obscured { dc: 14 });
+        assert!(!view.objects[0].flags.revealed);
+        assert!(!view.objects[0].flags.is_visible());
+
+        // A successful search reveals it in the user overlay
+        let found = HashMap::from([("wrench".to_string(), kv(&[("revealed", &Value::Bool(true))]))]);
+        let view = view_with(&found);
+        assert!(view.objects[0].flags.revealed);
+        assert!(view.objects[0].flags.is_visible());
+    }
+
+    #[test]
+    fn lock_feedback() {
+        let lock = |yaml: &str| serde_yaml::from_str::<Lock>(yaml).unwrap();
+
+        assert_eq!(lock("{}").feedback(), None);
+        assert_eq!(lock("{ kind: keycard }").feedback().unwrap(), "It needs a keycard.");
+        assert_eq!(
+            lock("{ kind: keypad, digits: 4 }").feedback().unwrap(),
+            "A four-digit keypad blinks."
+        );
+        assert_eq!(
+            lock("{ kind: keypad, digits: 16 }").feedback().unwrap(),
+            "A 16-digit keypad blinks."
+        );
+
+        assert_eq!(locked_message("The way is locked.", None), "The way is locked.");
+        assert_eq!(
+            locked_message("The way is locked.", Some(&lock("{ kind: key }"))),
+            "The way is locked. It needs a key."
+        );
+        assert_eq!(
+            locked_message(
+                "The way is locked.",
+                Some(&lock("{ kind: key, message: Rust seals the hatch. }"))
+            ),
+            "Rust seals the hatch."
+        );
+    }
+
+    #[test]
+    fn discovery_yaml_forms() {
+        assert_eq!(
+            serde_yaml::from_str::<Discovery>("mode: visible").unwrap(),
+            Discovery::Visible
+        );
+        assert_eq!(
+            serde_yaml::from_str::<Discovery>("{ mode: obscured, dc: 12 }").unwrap(),
+            Discovery::Obscured { dc: 12 }
+        );
+        assert_eq!(
+            serde_json::to_value(Discovery::Obscured { dc: 12 }).unwrap(),
+            json!({"mode": "obscured", "dc": 12})
+        );
+
+        let stashed = serde_yaml::from_str::<Discovery>("{ mode: stashed, place: under, object: bed }").unwrap();
+        assert_eq!(
+            stashed,
+            Discovery::Stashed {
+                place: Place::Under,
+                object: "bed".into()
+            }
+        );
+        assert!(stashed.is_stashed(Place::Under, "bed"));
+        assert!(!stashed.is_stashed(Place::Behind, "bed"));
+        assert!(!stashed.is_visible());
+    }
+
+    #[test]
+    fn hazard_yaml_defaults() {
+        let h: Hazard = serde_yaml::from_str("kind: gas\ndamage: 5\nmitigated_by: [gas_mask]").unwrap();
+        assert_eq!(h.kind, HazardKind::Gas);
+        assert_eq!(h.interval, DEFAULT_HAZARD_INTERVAL);
+        assert_eq!(h.message(), HazardKind::Gas.default_message());
+        assert_eq!(h.mitigated_by, vec!["gas_mask"]);
+
+        let json = serde_json::to_value(&h).unwrap();
+        assert_eq!(
+            json,
+            json!({"kind": "gas", "interval": 10, "damage": 5, "mitigated_by": ["gas_mask"]})
+        );
+        assert!(serde_yaml::from_str::<Hazard>("kind: lava").is_err());
+    }
+
+    #[test]
+    fn room_sounds_yaml_and_cues() {
+        let s: RoomSounds = serde_yaml::from_str("enter: door_hiss\nhint: ui/chime").unwrap();
+        assert_eq!(s.cues().collect::<Vec<_>>(), vec!["door_hiss", "ui/chime"]);
+        assert_eq!(
+            serde_json::to_value(&s).unwrap(),
+            json!({"enter": "door_hiss", "hint": "ui/chime"})
+        );
+        assert!(RoomSounds::default().is_empty());
+        assert!(serde_yaml::from_str::<RoomSounds>("exit: whoosh").is_err());
+
+        assert!(is_valid_sound_cue("ambient/reactor-hum.v2"));
+        assert!(!is_valid_sound_cue(""));
+        assert!(!is_valid_sound_cue("https://example.com/a.mp3"));
+        assert!(!is_valid_sound_cue(&"a".repeat(65)));
+    }
+
     #[test]
     fn build_room_view_object_visibility_and_revealed() {
         // From: build_room_view() + ObjectFlags::is_visible()
@@ -1031,6 +2081,7 @@ mod tests {
                 description: None,
                 visible_when_locked: false,
                 default_locked: false,
+                lock: None,
             },
         ];
         let view = build_room_view_impl(
@@ -1049,4 +2100,203 @@ mod tests {
         assert_eq!(view.exits_by_dir.get(&Direction::North).copied(), Some(0));
         assert_eq!(view.exits_by_dir.get(&Direction::East).copied(), Some(1));
     }
+
+    #[test]
+    fn t_exit_overlays() {
+        let mut zone_kv = Kv::default();
+        zone_kv.inner.insert("exit.north.visible".into(), Value::Bool(false));
+        let redirect = ExitRedirect {
+            room_id: rid(),
+            room_key: "entry_hall".into(),
+        };
+        zone_kv
+            .inner
+            .insert("exit.north.to".into(), serde_json::to_value(&redirect).unwrap());
+
+        let view = build_room_view_impl(
+            &mk_room(),
+            &[mk_exit_north(rid_b(), false, false)],
+            &[],
+            &RoomScripts::default(),
+            &Kv::default(),
+            &zone_kv,
+            &HashMap::new(),
+            &HashMap::new(),
+            &Kv::default(),
+            &HashMap::new(),
+            &HashMap::new(),
+        );
+        let exit = &view.exits[0];
+        assert!(exit.flags.hidden);
+        assert_eq!(exit.to_room_id, rid());
+        assert_eq!(exit.to_room_key, "entry_hall");
+
+        // A cleared redirect leads where the blueprint says again
+        zone_kv.inner.insert("exit.north.to".into(), Value::Null);
+        let view = build_room_view_impl(
+            &mk_room(),
+            &[mk_exit_north(rid_b(), false, false)],
+            &[],
+            &RoomScripts::default(),
+            &Kv::default(),
+            &zone_kv,
+            &HashMap::new(),
+            &HashMap::new(),
+            &Kv::default(),
+            &HashMap::new(),
+            &HashMap::new(),
+        );
+        assert_eq!(view.exits[0].to_room_id, rid_b());
+    }
+
+    #[test]
+    fn t_script_first() {
+        let view = build_room_view_impl(
+            &mk_room(),
+            &[],
+            &[mk_object_wrench()],
+            &RoomScripts::default(),
+            &Kv::default(),
+            &Kv::default(),
+            &HashMap::new(),
+            &HashMap::new(),
+            &Kv::default(),
+            &HashMap::new(),
+            &HashMap::new(),
+        );
+        let yaml = "get: [wrench]\nopen: []\n";
+        let first = serde_yaml::from_str::<ScriptFirst>(yaml).unwrap().normalized().unwrap();
+        assert_eq!(first.0.keys().collect::<Vec<_>>(), vec!["open", "take"]);
+
+        assert!(first.covers(&parse_command("take wrench"), &view));
+        assert!(first.covers(&parse_command("get the spanner"), &view));
+        assert!(!first.covers(&parse_command("take"), &view));
+        assert!(first.covers(&parse_command("open"), &view));
+        assert!(!first.covers(&parse_command("drop wrench"), &view));
+
+        let err = serde_yaml::from_str::<ScriptFirst>("quit: []\n").unwrap().normalized();
+        assert!(err.is_err());
+        let err = serde_yaml::from_str::<ScriptFirst>("dance: []\n").unwrap().normalized();
+        assert!(err.is_err());
+    }
+
+    #[test]
+    fn t_validate_effect() {
+        use crate::models::effect::Effect;
+
+        let view = build_room_view_impl(
+            &mk_room(),
+            &[mk_exit_north(rid_b(), false, false)],
+            &[mk_object_wrench()],
+            &RoomScripts::default(),
+            &Kv::default(),
+            &Kv::default(),
+            &HashMap::new(),
+            &HashMap::new(),
+            &Kv::default(),
+            &HashMap::new(),
+            &HashMap::new(),
+        );
+        assert!(Effect::Move(Direction::North).validate(&view).is_ok());
+        assert_eq!(
+            Effect::LockExit {
+                dir: Direction::South,
+                locked: true
+            }
+            .validate(&view),
+            Err("no exit 'south'".into())
+        );
+        let set = |object: &str| Effect::SetKv {
+            object: object.into(),
+            key: "polished".into(),
+            value: json!(true),
+        };
+        assert!(set("wrench").validate(&view).is_ok());
+        assert_eq!(set("hammer").validate(&view), Err("no object 'hammer'".into()));
+        let take = Effect::TakeItem {
+            item: "fuse".into(),
+            qty: 0,
+        };
+        assert!(take.validate(&view).is_err());
+        assert!(Effect::Sound("not a cue!".into()).validate(&view).is_err());
+        assert!(Effect::Output("Click.".into()).validate(&view).is_ok());
+    }
+
+    #[test]
+    fn t_add_spawned() {
+        let mut view = build_room_view_impl(
+            &mk_room(),
+            &[],
+            &[mk_object_wrench()],
+            &RoomScripts::default(),
+            &Kv::default(),
+            &Kv::default(),
+            &HashMap::new(),
+            &HashMap::new(),
+            &Kv::default(),
+            &HashMap::new(),
+            &HashMap::new(),
+        );
+        let spawned = |key: &str, short: &str| SpawnedObject {
+            id: ObjectId::new(),
+            key: key.into(),
+            short: short.into(),
+            description: "Twisted metal.".into(),
+            nouns: vec!["debris".into()],
+        };
+        view.add_spawned(&[
+            spawned("debris", "a heap of debris"),
+            spawned("wrench", "a fake wrench"),
+        ]);
+
+        assert_eq!(view.objects.len(), 2);
+        let debris = view.object_by_key("debris").expect("spawned object");
+        assert_eq!(debris.short, "a heap of debris");
+        assert!(debris.flags.is_visible());
+        assert_eq!(view.object_by_noun("debris").map(|o| o.key.as_str()), Some("debris"));
+        assert_ne!(view.object_by_key("wrench").unwrap().short, "a fake wrench");
+    }
+
+    #[test]
+    fn t_use_limits() {
+        let limits: UseLimits = serde_json::from_value(json!({"cooldown": 30, "max_uses": 2})).unwrap();
+        assert_eq!(limits.scope, UseScope::Player);
+
+        let first = limits.try_use(&ObjectUsage::default(), 1000).unwrap();
+        assert_eq!(first.uses, 1);
+        assert_eq!(
+            limits.try_use(&first, 1010),
+            Err(UseDenied::Cooldown { remaining_secs: 20 })
+        );
+        let second = limits.try_use(&first, 1030).unwrap();
+        assert_eq!(limits.try_use(&second, 5000), Err(UseDenied::UsedUp));
+
+        assert!(!UseLimits::default().is_limited());
+        assert_eq!(
+            UseDenied::Cooldown { remaining_secs: 5 }.message("lever"),
+            "The lever is not ready yet. Try again in 5 seconds."
+        );
+    }
+
+    #[test]
+    fn t_storage() {
+        let realm = RealmId(Uuid::nil());
+        let storage: Storage = serde_json::from_value(json!({"persistent": true})).unwrap();
+        assert_eq!(storage.capacity, DEFAULT_STORAGE_CAPACITY);
+        assert_eq!(storage.stash_realm(realm), None);
+        assert_eq!(Storage::default().stash_realm(realm), Some(realm));
+        assert!(serde_json::from_value::<Storage>(json!({"slots": 3})).is_err());
+    }
+
+    #[test]
+    fn t_builder_note_display() {
+        let mut note = BuilderNote {
+            room_key: "hall".into(),
+            object_key: None,
+            text: "TODO: describe the smell".into(),
+        };
+        assert_eq!(note.to_string(), "room:hall: TODO: describe the smell");
+        note.object_key = Some("door".into());
+        assert_eq!(note.to_string(), "room:hall:object:door: TODO: describe the smell");
+    }
 }
@@ -0,0 +1,232 @@
+//! The rooms of a blueprint and the exits between them.
+//!
+//! `@bp graph` draws it as Graphviz DOT or a Mermaid flowchart, marking the entry room, locked and
+//! one-way exits, and rooms players can never get to from the entry.
+
+use std::collections::{HashMap, HashSet};
+use std::fmt::Write as _;
+
+/// One exit of the graph
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct GraphExit {
+    pub from: String,
+    pub dir: String,
+    pub to: String,
+    pub locked: bool,
+}
+
+#[derive(Debug, Clone, Default)]
+pub struct RoomGraph {
+    /// Key of the entry room, when the blueprint has one
+    pub entry: Option<String>,
+    /// Room keys, sorted
+    pub rooms: Vec<String>,
+    pub exits: Vec<GraphExit>,
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum GraphFormat {
+    Dot,
+    Mermaid,
+}
+
+impl GraphFormat {
+    pub fn parse(s: &str) -> Option<Self> {
+        match s {
+            "dot" | "graphviz" => Some(GraphFormat::Dot),
+            "mermaid" | "mmd" => Some(GraphFormat::Mermaid),
+            _ => None,
+        }
+    }
+
+    /// File extension for graphs written to disk
+    pub fn extension(&self) -> &'static str {
+        match self {
+            GraphFormat::Dot => "dot",
+            GraphFormat::Mermaid => "mmd",
+        }
+    }
+}
+
+impl RoomGraph {
+    /// Rooms that cannot be reached from the entry room through any exit, locked or not. Without
+    /// an entry room, that is all of them.
+    pub fn unreachable(&self) -> Vec<&str> {
+        let mut seen: HashSet<&str> = HashSet::new();
+        let mut todo: Vec<&str> = self.entry.iter().map(String::as_str).collect();
+        let mut out: HashMap<&str, Vec<&str>> = HashMap::new();
+        for e in &self.exits {
+            out.entry(e.from.as_str()).or_default().push(e.to.as_str());
+        }
+        while let Some(room) = todo.pop() {
+            if seen.insert(room) {
+                todo.extend(out.get(room).into_iter().flatten());
+            }
+        }
+        self.rooms
+            .iter()
+            .map(String::as_str)
+            .filter(|r| !seen.contains(r))
+            .collect()
+    }
+
+    /// True when there is no exit leading back from where `exit` goes
+    pub fn is_one_way(&self, exit: &GraphExit) -> bool {
+        !self.exits.iter().any(|e| e.from == exit.to && e.to == exit.from)
+    }
+
+    pub fn render(&self, name: &str, format: GraphFormat) -> String {
+        match format {
+            GraphFormat::Dot => self.to_dot(name),
+            GraphFormat::Mermaid => self.to_mermaid(),
+        }
+    }
+
+    pub fn to_dot(&self, name: &str) -> String {
+        let unreachable = self.unreachable();
+        let mut s = String::new();
+        let _ = writeln!(s, "digraph \"{}\" {{", escape(name));
+        let _ = writeln!(s, "  rankdir=LR;");
+        let _ = writeln!(s, "  node [shape=box];");
+        for room in &self.rooms {
+            let mut attrs = Vec::new();
+            if self.entry.as_ref() == Some(room) {
+                attrs.push("style=bold".to_string());
+                attrs.push(format!("label=\"{}\\n(entry)\"", escape(room)));
+            } else if unreachable.contains(&room.as_str()) {
+                attrs.push("style=dashed".to_string());
+                attrs.push("color=red".to_string());
+                attrs.push(format!("label=\"{}\\n(unreachable)\"", escape(room)));
+            }
+            if attrs.is_empty() {
+                let _ = writeln!(s, "  \"{}\";", escape(room));
+            } else {
+                let _ = writeln!(s, "  \"{}\" [{}];", escape(room), attrs.join(", "));
+            }
+        }
+        for e in &self.exits {
+            let mut attrs = vec![format!("label=\"{}\"", edge_label(e, self.is_one_way(e)))];
+            if e.locked {
+                attrs.push("style=dashed".to_string());
+                attrs.push("color=orange".to_string());
+            } else if self.is_one_way(e) {
+                attrs.push("color=blue".to_string());
+            }
+            let _ = writeln!(
+                s,
+                "  \"{}\" -> \"{}\" [{}];",
+                escape(&e.from),
+                escape(&e.to),
+                attrs.join(", ")
+            );
+        }
+        s.push_str("}\n");
+        s
+    }
+
+    /// Mermaid node ids are positional, as room keys may hold characters Mermaid reads as syntax
+    pub fn to_mermaid(&self) -> String {
+        let ids: HashMap<&str, String> = self
+            .rooms
+            .iter()
+            .enumerate()
+            .map(|(i, r)| (r.as_str(), format!("r{}", i)))
+            .collect();
+        let id = |room: &str| ids.get(room).cloned().unwrap_or_else(|| "missing".to_string());
+
+        let unreachable = self.unreachable();
+        let mut s = String::from("flowchart LR\n");
+        for room in &self.rooms {
+            let _ = writeln!(s, "  {}[\"{}\"]", id(room), room.replace('"', "'"));
+        }
+        for e in &self.exits {
+            let arrow = if e.locked { "-.->" } else { "-->" };
+            let _ = writeln!(
+                s,
+                "  {} {}|\"{}\"| {}",
+                id(&e.from),
+                arrow,
+                edge_label(e, self.is_one_way(e)),
+                id(&e.to)
+            );
+        }
+        if let Some(entry) = &self.entry {
+            let _ = writeln!(s, "  classDef entry stroke-width:3px");
+            let _ = writeln!(s, "  class {} entry", id(entry));
+        }
+        if !unreachable.is_empty() {
+            let ids: Vec<String> = unreachable.iter().map(|r| id(r)).collect();
+            let _ = writeln!(s, "  classDef unreachable stroke:#f00,stroke-dasharray:5");
+            let _ = writeln!(s, "  class {} unreachable", ids.join(","));
+        }
+        s
+    }
+}
+
+fn edge_label(e: &GraphExit, one_way: bool) -> String {
+    let mut label = e.dir.clone();
+    if e.locked {
+        label.push_str(", locked");
+    }
+    if one_way {
+        label.push_str(", one-way");
+    }
+    label
+}
+
+fn escape(s: &str) -> String {
+    s.replace('\\', "\\\\").replace('"', "\\\"")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn exit(from: &str, dir: &str, to: &str, locked: bool) -> GraphExit {
+        GraphExit {
+            from: from.into(),
+            dir: dir.into(),
+            to: to.into(),
+            locked,
+        }
+    }
+
+    fn graph() -> RoomGraph {
+        RoomGraph {
+            entry: Some("hall".into()),
+            rooms: vec!["attic".into(), "cellar".into(), "hall".into()],
+            exits: vec![
+                exit("hall", "down", "cellar", false),
+                exit("cellar", "up", "hall", false),
+                exit("attic", "down", "hall", true),
+            ],
+        }
+    }
+
+    #[test]
+    fn t_room_graph_analysis() {
+        let g = graph();
+        assert_eq!(g.unreachable(), vec!["attic"]);
+        assert!(!g.is_one_way(&g.exits[0]));
+        assert!(g.is_one_way(&g.exits[2]));
+
+        let no_entry = RoomGraph { entry: None, ..graph() };
+        assert_eq!(no_entry.unreachable().len(), 3);
+    }
+
+    #[test]
+    fn t_room_graph_render() {
+        let g = graph();
+        let dot = g.to_dot("manor");
+        assert!(dot.starts_with("digraph \"manor\" {"));
+        assert!(dot.contains("\"hall\" [style=bold, label=\"hall\\n(entry)\"];"));
+        assert!(dot.contains("\"attic\" [style=dashed, color=red, label=\"attic\\n(unreachable)\"];"));
+        assert!(dot.contains("\"attic\" -> \"hall\" [label=\"down, locked, one-way\", style=dashed, color=orange];"));
+
+        let mmd = g.to_mermaid();
+        assert!(mmd.starts_with("flowchart LR\n"));
+        assert!(mmd.contains("  r2 -->|\"down\"| r1\n"));
+        assert!(mmd.contains("  r0 -.->|\"down, locked, one-way\"| r2\n"));
+        assert!(mmd.contains("  class r0 unreachable\n"));
+    }
+}
@@ -0,0 +1,209 @@
+//! Scheduled server events.
+//!
+//! Admins add events with a cron-like schedule of five fields, `minute hour day month weekday`
+//! (UTC; weekday 0 is Sunday). Each field is `*`, a number, a range `a-b`, a step `*/n` or `a-b/n`,
+//! or a comma separated list of those. When the schedule matches, the event either broadcasts an
+//! announcement to everyone online or runs a Lua chunk in its realm.
+
+use crate::db::DbResult;
+use crate::db::error::DbError;
+use crate::models::types::{AccountId, RealmId};
+use chrono::{DateTime, Datelike, Timelike, Utc};
+use std::fmt;
+use tokio_postgres::Row;
+
+/// Allowed values of each field
+const FIELDS: [(&str, u32, u32); 5] = [
+    ("minute", 0, 59),
+    ("hour", 0, 23),
+    ("day", 1, 31),
+    ("month", 1, 12),
+    ("weekday", 0, 6),
+];
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct CronSchedule {
+    /// Matching values per field, as a bit set
+    fields: [u64; 5],
+    source: String,
+}
+
+impl CronSchedule {
+    pub fn parse(s: &str) -> Result<Self, String> {
+        let parts: Vec<&str> = s.split_whitespace().collect();
+        if parts.len() != FIELDS.len() {
+            return Err(format!("'{}' needs 5 fields (minute hour day month weekday)", s.trim()));
+        }
+
+        let mut fields = [0u64; 5];
+        for (i, (part, (name, min, max))) in parts.iter().zip(FIELDS).enumerate() {
+            fields[i] = parse_field(part, min, max).map_err(|e| format!("{} field '{}': {}", name, part, e))?;
+        }
+        Ok(Self {
+            fields,
+            source: parts.join(" "),
+        })
+    }
+
+    /// Whether the schedule matches the minute `t` falls in
+    pub fn matches(&self, t: DateTime<Utc>) -> bool {
+        let values = [
+            t.minute(),
+            t.hour(),
+            t.day(),
+            t.month(),
+            t.weekday().num_days_from_sunday(),
+        ];
+        values.iter().zip(self.fields).all(|(v, bits)| bits & (1 << v) != 0)
+    }
+}
+
+impl fmt::Display for CronSchedule {
+    fn fmt(&self, f: &mut fmt::Formatter<'_>) -> fmt::Result {
+        f.write_str(&self.source)
+    }
+}
+
+fn parse_field(s: &str, min: u32, max: u32) -> Result<u64, String> {
+    let num = |v: &str| -> Result<u32, String> {
+        let n: u32 = v.parse().map_err(|_| format!("'{}' is not a number", v))?;
+        if n < min || n > max {
+            return Err(format!("{} is not within {}-{}", n, min, max));
+        }
+        Ok(n)
+    };
+
+    let mut bits = 0u64;
+    for item in s.split(',') {
+        let (range, step) = match item.split_once('/') {
+            Some((r, st)) => {
+                let st: u32 = st.parse().map_err(|_| format!("'{}' is not a step", st))?;
+                if st == 0 {
+                    return Err("a step cannot be 0".into());
+                }
+                (r, st)
+            }
+            None => (item, 1),
+        };
+        let (from, to) = match range {
+            "*" => (min, max),
+            r => match r.split_once('-') {
+                Some((a, b)) => (num(a)?, num(b)?),
+                None if step > 1 => (num(r)?, max),
+                None => (num(r)?, num(r)?),
+            },
+        };
+        if from > to {
+            return Err(format!("range {}-{} runs backwards", from, to));
+        }
+        for v in (from..=to).step_by(step as usize) {
+            bits |= 1 << v;
+        }
+    }
+    Ok(bits)
+}
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum EventAction {
+    /// Broadcast to everyone online
+    Announce(String),
+    /// Lua chunk run in the event's realm
+    Lua(String),
+}
+
+impl EventAction {
+    pub fn kind(&self) -> &'static str {
+        match self {
+            EventAction::Announce(_) => "announce",
+            EventAction::Lua(_) => "lua",
+        }
+    }
+
+    pub fn payload(&self) -> &str {
+        match self {
+            EventAction::Announce(s) | EventAction::Lua(s) => s,
+        }
+    }
+}
+
+#[derive(Debug, Clone)]
+pub struct ScheduledEvent {
+    pub id: i64,
+    pub schedule: CronSchedule,
+    pub action: EventAction,
+    /// Realm a Lua event runs in
+    pub realm_id: Option<RealmId>,
+    pub created_by: AccountId,
+    pub last_run_at: Option<DateTime<Utc>>,
+}
+
+impl ScheduledEvent {
+    pub fn try_from_row(row: &Row) -> DbResult<Self> {
+        let schedule: String = row.try_get("schedule")?;
+        let schedule = CronSchedule::parse(&schedule).map_err(DbError::Decode)?;
+        let payload: String = row.try_get("payload")?;
+        let action = match row.try_get::<_, &str>("action")? {
+            "announce" => EventAction::Announce(payload),
+            "lua" => EventAction::Lua(payload),
+            _ => return Err(DbError::Decode("invalid scheduled_events.action".into())),
+        };
+
+        Ok(Self {
+            id: row.try_get("id")?,
+            schedule,
+            action,
+            realm_id: row.try_get("realm_id")?,
+            created_by: row.try_get("created_by")?,
+            last_run_at: row.try_get("last_run_at")?,
+        })
+    }
+
+    /// Whether the event should run in minute `minute` (the start of it)
+    pub fn due(&self, minute: DateTime<Utc>) -> bool {
+        self.schedule.matches(minute) && self.last_run_at.is_none_or(|t| t < minute)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    #[test]
+    fn t_cron_schedule() {
+        // Fridays at 18:30
+        let s = CronSchedule::parse("30 18 * * 5").unwrap();
+        let fri = Utc.with_ymd_and_hms(2025, 5, 2, 18, 30, 0).unwrap();
+        assert!(s.matches(fri));
+        assert!(!s.matches(fri + chrono::Duration::minutes(1)));
+        assert!(!s.matches(fri + chrono::Duration::days(1)));
+
+        let s = CronSchedule::parse("*/15 9-17 1,15 * *").unwrap();
+        assert!(s.matches(Utc.with_ymd_and_hms(2025, 5, 15, 9, 45, 0).unwrap()));
+        assert!(!s.matches(Utc.with_ymd_and_hms(2025, 5, 15, 9, 50, 0).unwrap()));
+        assert!(!s.matches(Utc.with_ymd_and_hms(2025, 5, 2, 9, 45, 0).unwrap()));
+        assert_eq!(s.to_string(), "*/15 9-17 1,15 * *");
+
+        assert!(CronSchedule::parse("* * * *").unwrap_err().contains("5 fields"));
+        assert!(CronSchedule::parse("60 * * * *").unwrap_err().contains("minute"));
+        assert!(CronSchedule::parse("* 5-2 * * *").unwrap_err().contains("backwards"));
+        assert!(CronSchedule::parse("*/0 * * * *").is_err());
+    }
+
+    #[test]
+    fn t_event_due_once_per_minute() {
+        let minute = Utc.with_ymd_and_hms(2025, 5, 2, 18, 30, 0).unwrap();
+        let mut ev = ScheduledEvent {
+            id: 1,
+            schedule: CronSchedule::parse("30 18 * * *").unwrap(),
+            action: EventAction::Announce("Happy hour".into()),
+            realm_id: None,
+            created_by: AccountId::new(),
+            last_run_at: None,
+        };
+        assert!(ev.due(minute));
+        ev.last_run_at = Some(minute);
+        assert!(!ev.due(minute));
+        assert!(ev.due(minute + chrono::Duration::days(1)));
+    }
+}
@@ -0,0 +1,268 @@
+//! Per-account player settings, shown and changed with `settings`.
+//!
+//! The settings are stored as one JSON document on the account, so adding a setting needs no
+//! migration. Settings missing from the document have their default value, and values this
+//! version does not understand fall back to the default as well.
+
+use crate::error::{AppResult, DomainError};
+use crate::models::realm_directory::PAGE_SIZE;
+use crate::renderer::prompt::parse_prompt;
+use crate::renderer::theme::Theme;
+use serde::{Deserialize, Deserializer, Serialize, Serializer};
+use std::collections::BTreeMap;
+
+/// Range of realms shown per page of the realm directory
+const PAGE_SIZES: std::ops::RangeInclusive<u32> = 5..=50;
+
+/// Maximum number of aliases a player may have
+pub const MAX_ALIASES: usize = 50;
+
+/// Maximum length of an alias name, and of what it expands to
+const MAX_ALIAS_NAME: usize = 16;
+const MAX_ALIAS_EXPANSION: usize = 200;
+
+/// Commands that cannot be aliased, so a player can always list and remove their aliases
+const UNALIASABLE: &[&str] = &["alias", "unalias"];
+
+/// Every setting with what it does, in the order `settings list` shows them
+pub const SETTINGS: &[(&str, &str)] = &[
+    ("theme", "color theme: default, contrast, mono or none"),
+    ("prompt", "prompt, like '%hp %room >', or 'default'"),
+    ("page_size", "realms per page in the realm directory (5-50)"),
+    ("brief", "short room descriptions on arrival: on or off"),
+    ("gifts", "accept items other players give you: on or off"),
+];
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(default)]
+pub struct PlayerSettings {
+    #[serde(serialize_with = "ser_theme", deserialize_with = "de_theme")]
+    pub theme: Theme,
+    /// Own prompt template; None for the default prompt
+    pub prompt: Option<String>,
+    pub page_size: u32,
+    /// Only the title and exits when arriving in a room; `look` still shows everything
+    pub brief: bool,
+    /// Items other players `give` go straight into the inventory; when off they are refused
+    pub gifts: bool,
+    /// Own shorthands, set with `alias`: a line starting with the name starts with the expansion
+    /// instead
+    pub aliases: BTreeMap<String, String>,
+}
+
+impl Default for PlayerSettings {
+    fn default() -> Self {
+        Self {
+            theme: Theme::default(),
+            prompt: None,
+            page_size: PAGE_SIZE,
+            brief: false,
+            gifts: true,
+            aliases: BTreeMap::new(),
+        }
+    }
+}
+
+impl PlayerSettings {
+    /// The value of setting `name` as shown to the player
+    pub fn get(&self, name: &str) -> AppResult<String> {
+        Ok(match name {
+            "theme" => self.theme.to_string(),
+            "prompt" => self.prompt.clone().unwrap_or_else(|| "default".to_string()),
+            "page_size" => self.page_size.to_string(),
+            "brief" => on_off(self.brief).to_string(),
+            "gifts" => on_off(self.gifts).to_string(),
+            _ => return Err(unknown(name)),
+        })
+    }
+
+    /// Changes setting `name` to `value`, as typed by the player
+    pub fn set(&mut self, name: &str, value: &str) -> AppResult<()> {
+        let value = value.trim();
+        match name {
+            "theme" => self.theme = value.parse()?,
+            "prompt" => self.prompt = parse_prompt("prompt", value)?,
+            "page_size" => {
+                self.page_size = value
+                    .parse()
+                    .ok()
+                    .filter(|n| PAGE_SIZES.contains(n))
+                    .ok_or_else(|| invalid("page_size", "must be a number from 5 to 50"))?;
+            }
+            "brief" => self.brief = parse_on_off("brief", value)?,
+            "gifts" => self.gifts = parse_on_off("gifts", value)?,
+            _ => return Err(unknown(name)),
+        }
+        Ok(())
+    }
+
+    /// Puts setting `name` back to its default value
+    pub fn reset(&mut self, name: &str) -> AppResult<()> {
+        let defaults = Self::default();
+        match name {
+            "theme" => self.theme = defaults.theme,
+            "prompt" => self.prompt = defaults.prompt,
+            "page_size" => self.page_size = defaults.page_size,
+            "brief" => self.brief = defaults.brief,
+            "gifts" => self.gifts = defaults.gifts,
+            _ => return Err(unknown(name)),
+        }
+        Ok(())
+    }
+
+    /// Adds alias `name`, or changes what it expands to
+    pub fn set_alias(&mut self, name: &str, expansion: &str) -> AppResult<()> {
+        let name = name.to_lowercase();
+        let expansion = expansion.trim();
+        if name.is_empty()
+            || name.chars().count() > MAX_ALIAS_NAME
+            || !name.chars().all(|c| c.is_alphanumeric() || c == '_' || c == '-')
+        {
+            return Err(invalid(
+                "alias",
+                "names are one word of letters, digits, '_' and '-', up to 16 long",
+            ));
+        }
+        if UNALIASABLE.contains(&name.as_str()) {
+            return Err(invalid("alias", &format!("cannot replace '{}'", name)));
+        }
+        if expansion.is_empty() || expansion.chars().count() > MAX_ALIAS_EXPANSION {
+            return Err(invalid("alias", "must expand to a command of up to 200 characters"));
+        }
+        if !self.aliases.contains_key(&name) && self.aliases.len() >= MAX_ALIASES {
+            return Err(invalid(
+                "alias",
+                "limit reached; remove one with 'unalias <name>' first",
+            ));
+        }
+        self.aliases.insert(name, expansion.to_string());
+        Ok(())
+    }
+
+    /// Removes alias `name`; false when there is no such alias
+    pub fn remove_alias(&mut self, name: &str) -> bool {
+        self.aliases.remove(&name.to_lowercase()).is_some()
+    }
+
+    /// The line with its first word expanded, when that word is an alias. Expansions are not
+    /// expanded again.
+    pub fn expand_alias(&self, line: &str) -> Option<String> {
+        let line = line.trim_start();
+        let (word, rest) = line.split_once(char::is_whitespace).unwrap_or((line, ""));
+        let expansion = self.aliases.get(&word.to_lowercase())?;
+        let rest = rest.trim();
+        Some(if rest.is_empty() {
+            expansion.clone()
+        } else {
+            format!("{} {}", expansion, rest)
+        })
+    }
+}
+
+fn on_off(b: bool) -> &'static str {
+    if b { "on" } else { "off" }
+}
+
+fn parse_on_off(field: &'static str, value: &str) -> AppResult<bool> {
+    match value {
+        "on" | "yes" | "true" => Ok(true),
+        "off" | "no" | "false" => Ok(false),
+        _ => Err(invalid(field, "must be on or off")),
+    }
+}
+
+fn unknown(name: &str) -> DomainError {
+    DomainError::Validation {
+        field: "settings",
+        message: format!(
+            "unknown setting '{}' ({})",
+            name,
+            SETTINGS.iter().map(|(n, _)| *n).collect::<Vec<_>>().join(", ")
+        ),
+    }
+}
+
+fn invalid(field: &'static str, message: &str) -> DomainError {
+    DomainError::Validation {
+        field,
+        message: format!("{} {}", field, message),
+    }
+}
+
+fn ser_theme<S: Serializer>(theme: &Theme, s: S) -> Result<S::Ok, S::Error> {
+    s.serialize_str(theme.as_str())
+}
+
+fn de_theme<'de, D: Deserializer<'de>>(d: D) -> Result<Theme, D::Error> {
+    let name = String::deserialize(d)?;
+    Ok(name.parse().unwrap_or_default())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn t_settings_set_and_reset() {
+        let mut s = PlayerSettings::default();
+        s.set("theme", "mono").unwrap();
+        s.set("brief", "on").unwrap();
+        s.set("page_size", "25").unwrap();
+        s.set("prompt", "{v:account.name} >").unwrap();
+        assert_eq!(s.get("theme").unwrap(), "mono");
+        assert_eq!(s.get("brief").unwrap(), "on");
+        assert_eq!(s.prompt.as_deref(), Some("{v:account.name} > "));
+
+        assert!(s.set("page_size", "500").is_err());
+        assert!(s.set("brief", "maybe").is_err());
+        assert_eq!(s.get("gifts").unwrap(), "on");
+        s.set("gifts", "off").unwrap();
+        assert!(!s.gifts);
+        assert!(s.set("gifts", "sometimes").is_err());
+        assert!(s.set("colour", "red").is_err());
+        assert_eq!(s.page_size, 25);
+
+        s.set("prompt", "default").unwrap();
+        assert_eq!(s.prompt, None);
+        s.reset("page_size").unwrap();
+        assert_eq!(s.page_size, PAGE_SIZE);
+    }
+
+    #[test]
+    fn t_aliases() {
+        let mut s = PlayerSettings::default();
+        s.set_alias("gn", "go north").unwrap();
+        s.set_alias("TW", "take wrench from").unwrap();
+        assert_eq!(s.expand_alias("gn").as_deref(), Some("go north"));
+        assert_eq!(s.expand_alias("tw  crate ").as_deref(), Some("take wrench from crate"));
+        assert_eq!(s.expand_alias("gnome"), None);
+        assert_eq!(s.expand_alias("look gn"), None);
+
+        assert!(s.set_alias("alias", "look").is_err());
+        assert!(s.set_alias("two words", "look").is_err());
+        assert!(s.set_alias("l", "  ").is_err());
+
+        assert!(s.remove_alias("GN"));
+        assert!(!s.remove_alias("gn"));
+        assert_eq!(s.expand_alias("gn"), None);
+
+        for i in 0..MAX_ALIASES - 1 {
+            s.set_alias(&format!("a{}", i), "look").unwrap();
+        }
+        assert!(s.set_alias("one_more", "look").is_err());
+        s.set_alias("tw", "take wrench").unwrap();
+    }
+
+    #[test]
+    fn t_settings_json() {
+        let s: PlayerSettings = serde_json::from_str(r#"{"theme": "none", "unknown": 1}"#).unwrap();
+        assert_eq!(s.theme, Theme::NoColor);
+        assert_eq!(s.page_size, PAGE_SIZE);
+
+        let s: PlayerSettings = serde_json::from_str(r#"{"theme": "sparkly"}"#).unwrap();
+        assert_eq!(s.theme, Theme::Default);
+
+        let json = serde_json::to_value(PlayerSettings::default()).unwrap();
+        assert_eq!(json["theme"], "default");
+    }
+}
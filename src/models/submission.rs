@@ -0,0 +1,134 @@
+//! Blueprint submissions: the moderation queue between a draft and a published blueprint.
+//!
+//! Authors submit with `@bp submit`, moderators decide with `@submissions approve|reject`.
+//! Approving publishes the blueprint and opens a live realm for it; rejecting sends it back to the
+//! author with a comment.
+
+use crate::db::DbResult;
+use crate::db::error::DbError;
+use crate::models::types::{AccountId, BlueprintId};
+use chrono::{DateTime, Utc};
+use tokio_postgres::Row;
+
+/// Longest moderator comment, in characters
+pub const MAX_COMMENT_LEN: usize = 500;
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum SubmissionStatus {
+    Pending,
+    Approved,
+    Rejected,
+}
+
+impl SubmissionStatus {
+    fn parse(s: &str) -> DbResult<Self> {
+        match s {
+            "pending" => Ok(SubmissionStatus::Pending),
+            "approved" => Ok(SubmissionStatus::Approved),
+            "rejected" => Ok(SubmissionStatus::Rejected),
+            _ => Err(DbError::Decode("invalid blueprint_submissions.status".into())),
+        }
+    }
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            SubmissionStatus::Pending => "pending",
+            SubmissionStatus::Approved => "approved",
+            SubmissionStatus::Rejected => "rejected",
+        }
+    }
+}
+
+/// One submission of a blueprint, with the names joined in
+#[derive(Debug, Clone)]
+pub struct Submission {
+    pub id: i64,
+    pub bp_id: BlueprintId,
+    pub bp_key: String,
+    pub bp_title: String,
+    pub owner_id: AccountId,
+    /// Blueprint version that was submitted
+    pub version: i32,
+    /// None when the account was deleted
+    pub submitted_by: Option<String>,
+    pub submitted_at: DateTime<Utc>,
+    pub status: SubmissionStatus,
+    pub decided_by: Option<String>,
+    pub decided_at: Option<DateTime<Utc>>,
+    pub comment: Option<String>,
+}
+
+impl Submission {
+    pub fn try_from_row(row: &Row) -> DbResult<Self> {
+        let status: &str = row.try_get("status")?;
+        Ok(Self {
+            id: row.try_get("id")?,
+            bp_id: row.try_get("bp_id")?,
+            bp_key: row.try_get("bp_key")?,
+            bp_title: row.try_get("bp_title")?,
+            owner_id: row.try_get("owner_id")?,
+            version: row.try_get("version")?,
+            submitted_by: row.try_get("submitted_by")?,
+            submitted_at: row.try_get("submitted_at")?,
+            status: SubmissionStatus::parse(status)?,
+            decided_by: row.try_get("decided_by")?,
+            decided_at: row.try_get("decided_at")?,
+            comment: row.try_get("comment")?,
+        })
+    }
+
+    /// What the author is told about the decision
+    pub fn decision_message(&self) -> String {
+        let verdict = match self.status {
+            SubmissionStatus::Pending => return format!("'{}' is waiting for review.", self.bp_key),
+            SubmissionStatus::Approved => "approved and published",
+            SubmissionStatus::Rejected => "rejected",
+        };
+        let mut msg = format!(
+            "Your blueprint '{}' (version {}) was {} by {}.",
+            self.bp_key,
+            self.version,
+            verdict,
+            self.decided_by.as_deref().unwrap_or("a moderator")
+        );
+        if let Some(comment) = &self.comment {
+            msg.push_str(&format!(" Comment: {}", comment));
+        }
+        msg
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn t_decision_message() {
+        let mut s = Submission {
+            id: 1,
+            bp_id: BlueprintId::new(),
+            bp_key: "hub".into(),
+            bp_title: "The Hub".into(),
+            owner_id: AccountId::new(),
+            version: 3,
+            submitted_by: Some("joshua".into()),
+            submitted_at: Utc::now(),
+            status: SubmissionStatus::Rejected,
+            decided_by: Some("mod".into()),
+            decided_at: Some(Utc::now()),
+            comment: Some("the exits of the vault are one-way".into()),
+        };
+        assert_eq!(
+            s.decision_message(),
+            "Your blueprint 'hub' (version 3) was rejected by mod. Comment: the exits of the vault are one-way"
+        );
+
+        s.status = SubmissionStatus::Approved;
+        s.comment = None;
+        s.decided_by = None;
+        assert_eq!(
+            s.decision_message(),
+            "Your blueprint 'hub' (version 3) was approved and published by a moderator."
+        );
+    }
+}
@@ -90,6 +90,7 @@ define_id!(RoomId);
 define_id!(ObjectId);
 define_id!(ExitId);
 define_id!(LootId);
+define_id!(LootSpawnId);
 define_id!(HintId);
 define_id!(ItemId);
 
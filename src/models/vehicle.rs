@@ -0,0 +1,186 @@
+//! Vehicles: rooms that travel between stops, like elevators and shuttles.
+//!
+//! A vehicle is a room with a `vehicle` block listing its stops. Objects in the stop rooms with
+//! `board: <vehicle room>` let players step aboard while it is docked there. When the vehicle
+//! arrives at another stop, everyone aboard steps out into that stop's room. Where the vehicle is
+//! (or where it is heading) is shared room state of the vehicle room, so it survives restarts.
+
+use serde::{Deserialize, Serialize};
+
+/// Shared room KV key of the vehicle room holding its `VehicleState`
+pub const VEHICLE_STATE_KEY: &str = "__vehicle";
+
+/// Seconds between two stops unless the vehicle says otherwise
+pub const DEFAULT_TRAVEL_SECS: u32 = 5;
+
+fn default_travel() -> u32 {
+    DEFAULT_TRAVEL_SECS
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct VehicleStop {
+    /// What to `press` to travel here, e.g. "2" or "lobby"
+    pub button: String,
+    /// Room key where the passengers step out
+    pub room: String,
+    /// Shown to the passengers, defaults to the button
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub name: Option<String>,
+}
+
+impl VehicleStop {
+    pub fn name(&self) -> &str {
+        self.name.as_deref().unwrap_or(&self.button)
+    }
+}
+
+#[derive(Debug, Clone, PartialEq, Eq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct Vehicle {
+    /// Stops in order; the vehicle starts docked at the first one
+    pub stops: Vec<VehicleStop>,
+    /// Seconds the trip to another stop takes
+    #[serde(default = "default_travel")]
+    pub travel: u32,
+    /// Leave for the next stop this many seconds after docking, while anyone is aboard
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub schedule: Option<u32>,
+}
+
+impl Vehicle {
+    /// Index of the stop with this button or name (case-insensitive)
+    pub fn stop_by_button(&self, button: &str) -> Option<usize> {
+        self.stops
+            .iter()
+            .position(|s| s.button.eq_ignore_ascii_case(button) || s.name().eq_ignore_ascii_case(button))
+    }
+
+    /// Index of the stop in this room
+    pub fn stop_in_room(&self, room_key: &str) -> Option<usize> {
+        self.stops.iter().position(|s| s.room == room_key)
+    }
+
+    /// The stop after `stop`, going round
+    pub fn next_stop(&self, stop: usize) -> usize {
+        (stop + 1) % self.stops.len().max(1)
+    }
+
+    /// "1 (Lobby), 2 (Engineering)" for telling passengers what they can press
+    pub fn buttons(&self) -> String {
+        self.stops
+            .iter()
+            .map(|s| match &s.name {
+                Some(name) => format!("{} ({})", s.button, name),
+                None => s.button.clone(),
+            })
+            .collect::<Vec<_>>()
+            .join(", ")
+    }
+}
+
+/// Where a vehicle is, stored as shared room state of the vehicle room
+#[derive(Debug, Clone, Copy, Default, PartialEq, Eq, Serialize, Deserialize)]
+pub struct VehicleState {
+    /// Stop it is docked at, or last left
+    pub stop: usize,
+    /// Unix time it docked at `stop`
+    pub since: i64,
+    /// Stop it is travelling to
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub to: Option<usize>,
+    /// Unix time it arrives at `to`
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub arrives: Option<i64>,
+}
+
+impl VehicleState {
+    pub fn in_transit(&self) -> bool {
+        self.to.is_some()
+    }
+
+    /// Docked at this stop, and not about to leave it
+    pub fn docked_at(&self, stop: usize) -> bool {
+        !self.in_transit() && self.stop == stop
+    }
+
+    /// The state after leaving for `to`, None when it is already there or on its way somewhere
+    pub fn depart(&self, to: usize, travel: u32, now: i64) -> Option<VehicleState> {
+        if self.in_transit() || self.stop == to {
+            return None;
+        }
+        Some(VehicleState {
+            stop: self.stop,
+            since: self.since,
+            to: Some(to),
+            arrives: Some(now + travel as i64),
+        })
+    }
+
+    /// The state after arriving, None when it is not travelling or not there yet
+    pub fn arrive(&self, now: i64) -> Option<VehicleState> {
+        let (to, arrives) = (self.to?, self.arrives?);
+        if arrives > now {
+            return None;
+        }
+        Some(VehicleState {
+            stop: to,
+            since: now,
+            to: None,
+            arrives: None,
+        })
+    }
+
+    /// Whether a scheduled vehicle should leave again
+    pub fn schedule_due(&self, schedule: u32, now: i64) -> bool {
+        !self.in_transit() && now - self.since >= schedule as i64
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn lift() -> Vehicle {
+        serde_yaml::from_str(
+            r#"
+stops:
+  - { button: "1", room: lobby, name: Lobby }
+  - { button: "2", room: engineering }
+"#,
+        )
+        .unwrap()
+    }
+
+    #[test]
+    fn t_vehicle_stops() {
+        let v = lift();
+        assert_eq!(v.travel, DEFAULT_TRAVEL_SECS);
+        assert_eq!(v.stop_by_button("2"), Some(1));
+        assert_eq!(v.stop_by_button("lobby"), Some(0));
+        assert_eq!(v.stop_by_button("3"), None);
+        assert_eq!(v.stop_in_room("engineering"), Some(1));
+        assert_eq!(v.next_stop(1), 0);
+        assert_eq!(v.buttons(), "1 (Lobby), 2");
+        assert!(serde_yaml::from_str::<Vehicle>("stops: []\nspeed: 3").is_err());
+    }
+
+    #[test]
+    fn t_vehicle_trip() {
+        let docked = VehicleState::default();
+        assert!(docked.docked_at(0));
+        assert_eq!(docked.depart(0, 5, 100), None);
+
+        let moving = docked.depart(1, 5, 100).unwrap();
+        assert!(moving.in_transit());
+        assert!(!moving.docked_at(0));
+        assert_eq!(moving.depart(0, 5, 101), None);
+        assert_eq!(moving.arrive(104), None);
+
+        let arrived = moving.arrive(105).unwrap();
+        assert!(arrived.docked_at(1));
+        assert_eq!(arrived.since, 105);
+        assert!(!arrived.schedule_due(30, 120));
+        assert!(arrived.schedule_due(30, 135));
+    }
+}
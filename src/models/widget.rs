@@ -0,0 +1,222 @@
+//! Widgets: common devices configured in YAML instead of scripted in Lua.
+//!
+//! An object with a `widget` block gets built-in handling for the verbs of its kind:
+//!
+//! ```yaml
+//! widget:
+//!   type: keypad             # enter <code> on keypad
+//!   code: "2134"
+//!   failure: "The keypad buzzes angrily."
+//!   effects:
+//!     message: "A chirp, then the lock releases."
+//!     unlock: [north]
+//!     set: { crew_locker: { locked: false } }
+//! ```
+//!
+//! A `lever_bank` is solved when exactly the levers in `solution` are pulled up, a `menu` runs the
+//! effects of the option the player selects. Widget state is kept per player in the object state.
+
+use serde::{Deserialize, Serialize};
+use serde_json::Value;
+use std::collections::BTreeMap;
+
+/// Object state key set once a keypad or lever bank is solved
+pub const SOLVED_KEY: &str = "solved";
+/// Object state key holding the levers of a lever bank that are up
+pub const LEVERS_UP_KEY: &str = "levers_up";
+
+/// What happens when a widget is solved or a menu option is chosen
+#[derive(Debug, Clone, Default, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct WidgetEffects {
+    /// Shown to the player
+    #[serde(default, skip_serializing_if = "Option::is_none")]
+    pub message: Option<String>,
+    /// Exits of the room unlocked for the player
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub unlock: Vec<String>,
+    /// Exits of the room locked for the player
+    #[serde(default, skip_serializing_if = "Vec::is_empty")]
+    pub lock: Vec<String>,
+    /// Object state of the room to set, by object key
+    #[serde(default, skip_serializing_if = "BTreeMap::is_empty")]
+    pub set: BTreeMap<String, BTreeMap<String, Value>>,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct MenuOption {
+    pub label: String,
+    #[serde(default)]
+    pub effects: WidgetEffects,
+}
+
+#[derive(Debug, Clone, PartialEq, Serialize, Deserialize)]
+#[serde(tag = "type", rename_all = "snake_case", deny_unknown_fields)]
+pub enum Widget {
+    Keypad {
+        code: String,
+        /// Shown for a wrong code
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        failure: Option<String>,
+        #[serde(default)]
+        effects: WidgetEffects,
+    },
+    LeverBank {
+        levers: Vec<String>,
+        /// Levers that must be up; all others must be down
+        solution: Vec<String>,
+        #[serde(default)]
+        effects: WidgetEffects,
+    },
+    Menu {
+        #[serde(default, skip_serializing_if = "Option::is_none")]
+        title: Option<String>,
+        options: Vec<MenuOption>,
+    },
+}
+
+impl Widget {
+    /// All effects of the widget, for validating them
+    pub fn effects(&self) -> Vec<&WidgetEffects> {
+        match self {
+            Widget::Keypad { effects, .. } | Widget::LeverBank { effects, .. } => vec![effects],
+            Widget::Menu { options, .. } => options.iter().map(|o| &o.effects).collect(),
+        }
+    }
+
+    /// Whether the typed code is the keypad's code. Case, spaces and dashes do not matter.
+    pub fn code_matches(&self, input: &str) -> bool {
+        match self {
+            Widget::Keypad { code, .. } => normalize_code(code) == normalize_code(input),
+            _ => false,
+        }
+    }
+
+    /// The lever with this name, as written in the widget
+    pub fn lever(&self, name: &str) -> Option<&str> {
+        match self {
+            Widget::LeverBank { levers, .. } => {
+                levers.iter().find(|l| l.eq_ignore_ascii_case(name)).map(String::as_str)
+            }
+            _ => None,
+        }
+    }
+
+    /// Menu option by number (1-based) or label
+    pub fn option(&self, choice: &str) -> Option<&MenuOption> {
+        let Widget::Menu { options, .. } = self else {
+            return None;
+        };
+        match choice.parse::<usize>() {
+            Ok(n) => n.checked_sub(1).and_then(|i| options.get(i)),
+            Err(_) => options.iter().find(|o| o.label.eq_ignore_ascii_case(choice)),
+        }
+    }
+
+    /// What the player sees when using the widget
+    pub fn describe(&self, name: &str, levers_up: &[String]) -> String {
+        match self {
+            Widget::Keypad { .. } => format!("The {} waits for a code. Try: enter <code> on {}", name, name),
+            Widget::LeverBank { levers, .. } => {
+                let positions: Vec<String> = levers
+                    .iter()
+                    .map(|l| {
+                        let pos = if levers_up.contains(l) { "up" } else { "down" };
+                        format!("{} ({})", l, pos)
+                    })
+                    .collect();
+                format!("Levers: {}. Pull one to flip it.", positions.join(", "))
+            }
+            Widget::Menu { title, options } => {
+                let mut out = vec![title.clone().unwrap_or_else(|| name.to_uppercase())];
+                for (i, o) in options.iter().enumerate() {
+                    out.push(format!("  {}) {}", i + 1, o.label));
+                }
+                out.push(format!("Choose with: select <number> on {}", name));
+                out.join("\n")
+            }
+        }
+    }
+}
+
+fn normalize_code(s: &str) -> String {
+    s.chars()
+        .filter(|c| !c.is_whitespace() && *c != '-')
+        .flat_map(char::to_lowercase)
+        .collect()
+}
+
+/// Flips a lever and returns the levers that are up afterwards, in the bank's order
+pub fn flip_lever(levers: &[String], up: &[String], lever: &str) -> Vec<String> {
+    levers
+        .iter()
+        .filter(|l| (l.as_str() == lever) != up.contains(l))
+        .cloned()
+        .collect()
+}
+
+/// Whether exactly the solution levers are up
+pub fn levers_solved(up: &[String], solution: &[String]) -> bool {
+    up.len() == solution.len() && solution.iter().all(|s| up.contains(s))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn widget(yaml: &str) -> Widget {
+        serde_yaml::from_str(yaml).unwrap()
+    }
+
+    #[test]
+    fn t_keypad() {
+        let w =
+            widget("type: keypad\ncode: '21-34'\neffects:\n  unlock: [north]\n  set: { locker: { locked: false } }\n");
+        assert!(w.code_matches("2134"));
+        assert!(w.code_matches("21 34"));
+        assert!(!w.code_matches("4312"));
+        let Widget::Keypad { effects, .. } = &w else {
+            panic!("not a keypad");
+        };
+        assert_eq!(effects.unlock, vec!["north"]);
+        assert_eq!(effects.set["locker"]["locked"], Value::Bool(false));
+
+        assert!(serde_yaml::from_str::<Widget>("type: keypad\ncode: '1'\ncolour: red\n").is_err());
+        assert!(serde_yaml::from_str::<Widget>("type: dial\n").is_err());
+    }
+
+    #[test]
+    fn t_lever_bank() {
+        let w = widget("type: lever_bank\nlevers: [red, green, blue]\nsolution: [blue, red]\n");
+        let Widget::LeverBank { levers, solution, .. } = &w else {
+            panic!("not a lever bank");
+        };
+        assert_eq!(w.lever("RED"), Some("red"));
+
+        let up = flip_lever(levers, &[], "blue");
+        let up = flip_lever(levers, &up, "green");
+        assert_eq!(up, vec!["green", "blue"]);
+        assert!(!levers_solved(&up, solution));
+        let up = flip_lever(levers, &up, "green");
+        let up = flip_lever(levers, &up, "red");
+        assert!(levers_solved(&up, solution));
+        assert_eq!(
+            w.describe("bank", &up),
+            "Levers: red (up), green (down), blue (up). Pull one to flip it."
+        );
+    }
+
+    #[test]
+    fn t_menu() {
+        let w = widget("type: menu\noptions:\n  - label: Open doors\n  - label: Vent\n");
+        assert_eq!(w.option("2").unwrap().label, "Vent");
+        assert_eq!(w.option("open doors").unwrap().label, "Open doors");
+        assert!(w.option("0").is_none());
+        assert!(w.option("3").is_none());
+        assert_eq!(
+            w.describe("terminal", &[]),
+            "TERMINAL\n  1) Open doors\n  2) Vent\nChoose with: select <number> on terminal"
+        );
+    }
+}
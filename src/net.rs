@@ -1,19 +1,20 @@
 use crate::Registry;
-use crate::lua::LuaJob;
+use crate::lua::LuaPool;
 use crate::net::output::OutputHandle;
 use std::sync::Arc;
-use tokio::sync::mpsc;
 
 pub mod http;
+pub mod limits;
 pub mod output;
 pub mod sink;
+pub mod stats;
 pub mod telnet;
 
 #[derive(Clone)]
 struct AppCtx {
     output: OutputHandle,
     registry: Arc<Registry>,
-    lua_tx: mpsc::Sender<LuaJob>,
+    lua_tx: LuaPool,
 }
 
 #[derive(Clone, Debug, Copy, PartialEq, Eq)]
@@ -1,50 +1,71 @@
 use axum::{
     Router,
-    extract::State,
     extract::ws::{Message, WebSocket, WebSocketUpgrade},
-    response::IntoResponse,
+    extract::{ConnectInfo, State},
+    http::StatusCode,
+    response::{IntoResponse, Response},
     routing::get,
 };
 use futures::StreamExt;
 use parking_lot::RwLock;
+use std::net::SocketAddr;
 use std::sync::Arc;
 use tower_http::cors::{Any, CorsLayer};
 
 use crate::banner::{BANNER, ENTRY};
 use crate::commands::CmdCtx;
 use crate::error::{AppResult, InfraError};
-use crate::lua::LuaJob;
+use crate::hardening::MAX_WS_MESSAGE_BYTES;
+use crate::input::sanitize::sanitize_line;
+use crate::lua::LuaPool;
 use crate::net::output::init_session_for_websocket;
 use crate::state::session::Protocol;
 use crate::{Registry, Session, process_command};
-use tokio::sync::mpsc;
 
 #[derive(Clone)]
 struct HttpAppCtx {
     registry: Arc<Registry>,
-    lua_tx: mpsc::Sender<LuaJob>,
+    lua_tx: LuaPool,
 }
 
 /// Run the HTTP server with WebSocket endpoint
-pub async fn serve(addr: std::net::SocketAddr, registry: Arc<Registry>, lua_tx: mpsc::Sender<LuaJob>) -> AppResult<()> {
+pub async fn serve(addr: std::net::SocketAddr, registry: Arc<Registry>, lua_tx: LuaPool) -> AppResult<()> {
     let app = Router::new()
         .route("/ws", get(ws_upgrade))
         .with_state(HttpAppCtx { registry, lua_tx })
         .layer(CorsLayer::new().allow_origin(Any).allow_methods(Any).allow_headers(Any));
 
     let listener = tokio::net::TcpListener::bind(&addr).await.map_err(InfraError::from)?;
-    axum::serve(listener, app).await.map_err(InfraError::from)?;
+    axum::serve(listener, app.into_make_service_with_connect_info::<SocketAddr>())
+        .await
+        .map_err(InfraError::from)?;
     Ok(())
 }
 
-async fn ws_upgrade(ws: WebSocketUpgrade, State(state): State<HttpAppCtx>) -> impl IntoResponse {
-    ws.on_upgrade(move |socket| ws_handler(socket, state.registry.clone(), state.lua_tx.clone()))
+async fn ws_upgrade(
+    ws: WebSocketUpgrade,
+    ConnectInfo(peer): ConnectInfo<SocketAddr>,
+    State(state): State<HttpAppCtx>,
+) -> Response {
+    let max = state.registry.config().rate_limit.connections_per_ip;
+    let Some(permit) = state.registry.connections.try_acquire(peer.ip(), max) else {
+        tracing::warn!(%peer, "too many connections from this address, refusing");
+        return StatusCode::TOO_MANY_REQUESTS.into_response();
+    };
+
+    ws.max_message_size(MAX_WS_MESSAGE_BYTES)
+        .max_frame_size(MAX_WS_MESSAGE_BYTES)
+        .on_upgrade(move |socket| async move {
+            let _permit = permit;
+            ws_handler(socket, peer, state.registry.clone(), state.lua_tx.clone()).await
+        })
 }
 
-async fn ws_handler(socket: WebSocket, registry: Arc<Registry>, lua_tx: mpsc::Sender<LuaJob>) {
+async fn ws_handler(socket: WebSocket, peer: SocketAddr, registry: Arc<Registry>, lua_tx: LuaPool) {
     let (ws_write, mut ws_read) = socket.split();
 
     let sess = Arc::new(RwLock::new(Session::new(Protocol::WebSocket)));
+    sess.write().set_peer(peer.ip());
 
     let io_bundle = init_session_for_websocket(ws_write, sess.clone()).await;
 
@@ -59,7 +80,17 @@ async fn ws_handler(socket: WebSocket, registry: Arc<Registry>, lua_tx: mpsc::Se
         output: io_bundle.output.clone(),
     });
 
-    while let Some(Ok(msg)) = ws_read.next().await {
+    loop {
+        let msg = tokio::select! {
+            msg = ws_read.next() => msg,
+            _ = ctx.output.closed() => {
+                tracing::info!(%peer, "client stopped reading output, disconnecting");
+                break;
+            }
+        };
+        let Some(Ok(msg)) = msg else {
+            break;
+        };
         let text = match msg {
             Message::Text(t) => t,
             Message::Binary(b) => String::from_utf8_lossy(&b).to_string().into(),
@@ -70,8 +101,10 @@ async fn ws_handler(socket: WebSocket, registry: Arc<Registry>, lua_tx: mpsc::Se
             Message::Pong(_) => continue,
             Message::Close(_) => break,
         };
+        sess.read().stats().add_bytes_in(text.len());
 
-        let cmd = text.trim();
+        let cmd = sanitize_line(&text);
+        let cmd = cmd.trim();
         if !cmd.is_empty() {
             _ = process_command(cmd, ctx.clone()).await;
         }
@@ -0,0 +1,139 @@
+//! Limits from the `[rate_limit]` config. Callers pass in the current value on every check, so a
+//! config reload applies to connections that are already open.
+
+use dashmap::DashMap;
+use dashmap::mapref::entry::Entry;
+use std::net::IpAddr;
+use std::sync::Arc;
+use std::time::Instant;
+
+/// Open connections per client address, over telnet and WebSocket together
+#[derive(Debug, Default)]
+pub struct ConnectionLimiter {
+    open: DashMap<IpAddr, u32>,
+}
+
+/// One counted connection; dropping it frees the slot
+#[derive(Debug)]
+pub struct ConnectionPermit {
+    limiter: Arc<ConnectionLimiter>,
+    ip: IpAddr,
+}
+
+impl ConnectionLimiter {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Counts a connection from `ip`, or None when it already has `max` open
+    pub fn try_acquire(self: &Arc<Self>, ip: IpAddr, max: u32) -> Option<ConnectionPermit> {
+        let mut open = self.open.entry(ip).or_insert(0);
+        if *open >= max {
+            return None;
+        }
+        *open += 1;
+        Some(ConnectionPermit {
+            limiter: self.clone(),
+            ip,
+        })
+    }
+
+    /// Connections open from `ip`
+    pub fn open(&self, ip: IpAddr) -> u32 {
+        self.open.get(&ip).map(|n| *n).unwrap_or(0)
+    }
+}
+
+impl Drop for ConnectionPermit {
+    fn drop(&mut self) {
+        if let Entry::Occupied(mut e) = self.limiter.open.entry(self.ip) {
+            *e.get_mut() -= 1;
+            if *e.get() == 0 {
+                e.remove();
+            }
+        }
+    }
+}
+
+/// Commands of one connection: up to `burst` at once, refilled at `per_second`
+#[derive(Debug)]
+pub struct CommandBucket {
+    tokens: f64,
+    refilled: Instant,
+}
+
+impl Default for CommandBucket {
+    fn default() -> Self {
+        Self {
+            // Clamped to the burst on the first take, so a new connection starts full
+            tokens: f64::MAX,
+            refilled: Instant::now(),
+        }
+    }
+}
+
+impl CommandBucket {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Takes one command, or false when the connection is over the limit
+    pub fn try_take(&mut self, per_second: u32, burst: u32, now: Instant) -> bool {
+        let elapsed = now.saturating_duration_since(self.refilled).as_secs_f64();
+        self.refilled = now;
+        self.tokens = (self.tokens + elapsed * per_second as f64).min(burst as f64);
+        if self.tokens < 1.0 {
+            return false;
+        }
+        self.tokens -= 1.0;
+        true
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use std::time::Duration;
+
+    #[test]
+    fn t_connection_limiter() {
+        let limiter = Arc::new(ConnectionLimiter::new());
+        let ip: IpAddr = "192.0.2.1".parse().unwrap();
+        let other: IpAddr = "192.0.2.2".parse().unwrap();
+
+        let a = limiter.try_acquire(ip, 2).unwrap();
+        let _b = limiter.try_acquire(ip, 2).unwrap();
+        assert!(limiter.try_acquire(ip, 2).is_none());
+        assert!(limiter.try_acquire(other, 2).is_some());
+
+        // A raised limit applies straight away, a lowered one to new connections
+        let c = limiter.try_acquire(ip, 3).unwrap();
+        assert!(limiter.try_acquire(ip, 1).is_none());
+
+        drop(a);
+        drop(c);
+        assert_eq!(limiter.open(ip), 1);
+        assert!(limiter.try_acquire(ip, 2).is_some());
+    }
+
+    #[test]
+    fn t_command_bucket() {
+        let mut bucket = CommandBucket::new();
+        let start = Instant::now();
+
+        for _ in 0..4 {
+            assert!(bucket.try_take(2, 4, start));
+        }
+        assert!(!bucket.try_take(2, 4, start));
+
+        // Half a second at 2/s buys one command back
+        let later = start + Duration::from_millis(500);
+        assert!(bucket.try_take(2, 4, later));
+        assert!(!bucket.try_take(2, 4, later));
+
+        // A reloaded, higher rate refills faster from then on
+        let reloaded = later + Duration::from_millis(500);
+        assert!(bucket.try_take(10, 20, reloaded));
+        assert_eq!((0..10).filter(|_| bucket.try_take(10, 20, reloaded)).count(), 4);
+    }
+}
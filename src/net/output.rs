@@ -1,17 +1,22 @@
+mod queue;
+
 use crate::Session;
+use crate::hardening::{MAX_OUTPUT_QUEUE, OUTPUT_WRITE_TIMEOUT};
+use crate::models::types::AccountId;
 use crate::net::InputMode;
 use crate::net::sink::ClientSink;
 use crate::net::sink::telnet::TelnetSink;
 use crate::net::sink::websocket::WebSocketSink;
-use crate::renderer::render_template;
+use crate::net::stats::ConnStats;
 use crate::renderer::vars::generate_render_vars;
+use crate::renderer::{render_template, strip_ansi};
 use axum::extract::ws::{Message, WebSocket};
 use futures::stream::SplitSink;
 use parking_lot::RwLock;
+use queue::OutQueue;
 use std::sync::Arc;
-use std::sync::atomic::AtomicU64;
 use tokio::io::AsyncWrite;
-use tokio::sync::mpsc;
+use tokio::sync::{Mutex, mpsc};
 
 const MAX_TERMINAL_WIDTH: usize = 80;
 
@@ -35,63 +40,139 @@ pub enum OutFrame {
     ClearScreen,
     /// Raw bytes for telnet IAC sequences
     Raw(Vec<u8>),
+    /// Sound cue for clients that can play audio; text-only clients drop it
+    Sound {
+        cue: String,
+    },
+}
+
+/// Numbers output events and queues them in one step. With the numbering and the queueing under
+/// the same lock, events reach the client in the order of their numbers, whichever task sent them.
+struct EventSender {
+    tx: mpsc::Sender<OutEvent>,
+    next_seq: u64,
+}
+
+impl EventSender {
+    async fn send(&mut self, event: impl FnOnce(u64) -> OutEvent) {
+        let seq = self.next_seq;
+        self.next_seq += 1;
+        let _ = self.tx.send(event(seq)).await;
+    }
 }
 
 #[derive(Clone)]
 pub struct OutputHandle {
-    /// Sender for output events
-    tx: mpsc::Sender<OutEvent>,
-    /// Next sequence number for output frames
-    next_seq: Arc<AtomicU64>,
+    /// Queue of output events to the client
+    out: Arc<Mutex<EventSender>>,
     /// Session pointer
     sess: Arc<RwLock<Session>>,
+    /// Spectators that receive a read-only copy of the text output
+    mirrors: Arc<RwLock<Vec<OutputMirror>>>,
+}
+
+/// Destination of mirrored output. Frames are sent straight into the spectator's channel, so
+/// mirrors never chain.
+#[derive(Clone)]
+struct OutputMirror {
+    id: AccountId,
+    label: String,
+    out: Arc<Mutex<EventSender>>,
 }
 
 impl OutputHandle {
     pub fn new(tx: mpsc::Sender<OutEvent>, session: Arc<RwLock<Session>>) -> Self {
         Self {
-            tx,
-            next_seq: Arc::new(AtomicU64::new(1)),
+            out: Arc::new(Mutex::new(EventSender { tx, next_seq: 1 })),
             sess: session.clone(),
+            mirrors: Arc::new(RwLock::new(Vec::new())),
         }
     }
 
-    #[inline]
-    pub fn next_seq(&self) -> u64 {
-        self.next_seq.fetch_add(1, std::sync::atomic::Ordering::Relaxed)
+    /// Mirrors all text output of this handle to `to`, each line prefixed with `[label]`.
+    /// Replaces an existing mirror for the same id.
+    pub fn add_mirror(&self, id: AccountId, label: impl Into<String>, to: &OutputHandle) {
+        let mut mirrors = self.mirrors.write();
+        mirrors.retain(|m| m.id != id);
+        mirrors.push(OutputMirror {
+            id,
+            label: label.into(),
+            out: to.out.clone(),
+        });
+    }
+
+    /// Stops mirroring to `id`, returns false when there was no such mirror
+    pub fn remove_mirror(&self, id: AccountId) -> bool {
+        let mut mirrors = self.mirrors.write();
+        let len = mirrors.len();
+        mirrors.retain(|m| m.id != id);
+        mirrors.len() != len
+    }
+
+    async fn mirror(&self, text: &str) {
+        let mirrors = self.mirrors.read().clone();
+        for m in mirrors {
+            let prefixed = text
+                .lines()
+                .map(|l| format!("[{}] {}", m.label, l))
+                .collect::<Vec<_>>()
+                .join("\n");
+            m.out
+                .lock()
+                .await
+                .send(|seq| OutEvent::Frame(OutFrame::Line(prefixed), seq))
+                .await;
+        }
+    }
+
+    /// Adds text output to the session recording, when the session is being recorded
+    fn record(&self, text: &str) {
+        let mut sess = self.sess.write();
+        if sess.recorder().is_some() {
+            sess.record_output(&strip_ansi(text));
+        }
+    }
+
+    async fn send(&self, event: impl FnOnce(u64) -> OutEvent) {
+        self.out.lock().await.send(event).await;
+    }
+
+    /// Resolves once output to the client has stopped, either because the client went away or
+    /// because it stopped reading
+    pub async fn closed(&self) {
+        let tx = self.out.lock().await.tx.clone();
+        tx.closed().await;
+    }
+
+    async fn send_frame(&self, frame: OutFrame) {
+        self.send(|seq| OutEvent::Frame(frame, seq)).await;
     }
 
     pub async fn line(&self, s: impl Into<String>) {
         let vars = generate_render_vars(self.sess.clone());
         let rendered = render_template(&s.into(), &vars, MAX_TERMINAL_WIDTH);
 
-        let _ = self
-            .tx
-            .send(OutEvent::Frame(OutFrame::Line(rendered), self.next_seq()))
-            .await;
+        self.record(&rendered);
+        self.mirror(&rendered).await;
+        self.send_frame(OutFrame::Line(rendered)).await;
     }
 
     pub async fn system(&self, s: impl Into<String>) {
         let vars = generate_render_vars(self.sess.clone());
         let rendered = render_template(&s.into(), &vars, MAX_TERMINAL_WIDTH);
 
-        let _ = self
-            .tx
-            .send(OutEvent::Frame(OutFrame::System(rendered), self.next_seq()))
-            .await;
+        self.record(&rendered);
+        self.mirror(&rendered).await;
+        self.send_frame(OutFrame::System(rendered)).await;
     }
 
     pub async fn room_view(&self, content: impl Into<String>) {
         let vars = generate_render_vars(self.sess.clone());
         let rendered = render_template(&content.into(), &vars, MAX_TERMINAL_WIDTH);
 
-        let _ = self
-            .tx
-            .send(OutEvent::Frame(
-                OutFrame::RoomView { content: rendered },
-                self.next_seq(),
-            ))
-            .await;
+        self.record(&rendered);
+        self.mirror(&rendered).await;
+        self.send_frame(OutFrame::RoomView { content: rendered }).await;
     }
 
     pub async fn input_mode(&self, mode: InputMode) {
@@ -100,25 +181,19 @@ impl OutputHandle {
             s.set_input_mode(mode);
         }
 
-        let _ = self
-            .tx
-            .send(OutEvent::Frame(OutFrame::InputMode(mode), self.next_seq()))
-            .await;
+        self.send_frame(OutFrame::InputMode(mode)).await;
     }
 
     pub async fn restore_prompt(&self) {
         let vars = generate_render_vars(self.sess.clone());
-        let rendered = render_template(self.sess.read().default_user_prompt(), &vars, MAX_TERMINAL_WIDTH);
+        let rendered = render_template(&self.sess.read().default_user_prompt(), &vars, MAX_TERMINAL_WIDTH);
 
         {
             let mut s = self.sess.write();
             s.set_prompt(rendered.clone());
         }
 
-        let _ = self
-            .tx
-            .send(OutEvent::Frame(OutFrame::Prompt(rendered), self.next_seq()))
-            .await;
+        self.send_frame(OutFrame::Prompt(rendered)).await;
     }
 
     pub async fn set_prompt(&self, prompt: impl Into<String>) {
@@ -130,14 +205,11 @@ impl OutputHandle {
             s.set_prompt(rendered.clone());
         }
 
-        let _ = self
-            .tx
-            .send(OutEvent::Frame(OutFrame::Prompt(rendered), self.next_seq()))
-            .await;
+        self.send_frame(OutFrame::Prompt(rendered)).await;
     }
 
     pub async fn raw(&self, bytes: Vec<u8>) {
-        let _ = self.tx.send(OutEvent::Raw(bytes, self.next_seq())).await;
+        self.send(|seq| OutEvent::Raw(bytes, seq)).await;
     }
 
     pub async fn table<S: AsRef<str>>(&self, headers: Vec<S>, rows: Vec<Vec<S>>) {
@@ -145,17 +217,70 @@ impl OutputHandle {
 
         let vars = generate_render_vars(self.sess.clone());
         let rendered = render_template(&table, &vars, MAX_TERMINAL_WIDTH);
-        let _ = self
-            .tx
-            .send(OutEvent::Frame(OutFrame::Line(rendered), self.next_seq()))
-            .await;
+        self.record(&rendered);
+        self.mirror(&rendered).await;
+        self.send_frame(OutFrame::Line(rendered)).await;
+    }
+
+    /// Asks the client to play a sound cue. Not recorded or mirrored, there is no text to show.
+    pub async fn sound(&self, cue: impl Into<String>) {
+        self.send_frame(OutFrame::Sound { cue: cue.into() }).await;
+    }
+
+    /// Sends everything in `buffer`, in the order it was added
+    pub async fn flush(&self, buffer: OutputBuffer) {
+        for frame in buffer.batched() {
+            match frame {
+                OutFrame::Line(text) => self.line(text).await,
+                OutFrame::System(text) => self.system(text).await,
+                OutFrame::Sound { cue } => self.sound(cue).await,
+                _ => {}
+            }
+        }
     }
 
     pub async fn draw_line(&self, s: impl Into<String>) {
-        let _ = self
-            .tx
-            .send(OutEvent::Frame(OutFrame::RepaintLine(s.into()), self.next_seq()))
-            .await;
+        self.send_frame(OutFrame::RepaintLine(s.into())).await;
+    }
+}
+
+/// Output collected while a script runs. Nothing is sent until [`OutputHandle::flush`].
+#[derive(Debug, Default)]
+pub struct OutputBuffer {
+    frames: Vec<OutFrame>,
+}
+
+impl OutputBuffer {
+    pub fn line(&mut self, s: impl Into<String>) {
+        self.frames.push(OutFrame::Line(s.into()));
+    }
+
+    pub fn system(&mut self, s: impl Into<String>) {
+        self.frames.push(OutFrame::System(s.into()));
+    }
+
+    pub fn sound(&mut self, cue: impl Into<String>) {
+        self.frames.push(OutFrame::Sound { cue: cue.into() });
+    }
+
+    pub fn is_empty(&self) -> bool {
+        self.frames.is_empty()
+    }
+
+    /// The buffered frames in order, with runs of lines of the same kind joined into one frame
+    fn batched(self) -> Vec<OutFrame> {
+        let mut batched: Vec<OutFrame> = Vec::with_capacity(self.frames.len());
+        for frame in self.frames {
+            match (batched.last_mut(), frame) {
+                (Some(OutFrame::Line(text)), OutFrame::Line(more))
+                | (Some(OutFrame::System(text)), OutFrame::System(more)) => {
+                    text.push('\n');
+                    text.push_str(&more);
+                }
+                (_, frame) => batched.push(frame),
+            }
+        }
+        batched
     }
 }
 
@@ -214,28 +339,56 @@ pub enum OutEvent {
 
 pub struct SessionOut {
     rx: mpsc::Receiver<OutEvent>,
+    stats: Arc<ConnStats>,
 }
 
 impl SessionOut {
-    pub fn new(rx: mpsc::Receiver<OutEvent>) -> Self {
-        Self { rx }
+    pub fn new(rx: mpsc::Receiver<OutEvent>, stats: Arc<ConnStats>) -> Self {
+        Self { rx, stats }
     }
 
+    /// Writes events to the client as fast as it reads them. Events keep being taken in while a
+    /// write is pending, so the game never waits on the client; they wait in an [`OutQueue`]
+    /// instead. A client that takes longer than [`OUTPUT_WRITE_TIMEOUT`] for one frame, or lets
+    /// the queue fill up with frames that cannot be dropped, ends the output.
     pub async fn run<C>(mut self, mut client: C) -> anyhow::Result<()>
     where
         C: ClientSink,
     {
-        while let Some(event) = self.rx.recv().await {
-            match event {
-                OutEvent::Frame(frame, seq_nr) => client.send_frame(frame, seq_nr).await?,
-                OutEvent::Raw(bytes, seq_nr) => {
-                    // For telnet IAC sequences, we wrap them in an OutFrame::Raw
-                    client.send_frame(OutFrame::Raw(bytes), seq_nr).await?
+        let mut queue = OutQueue::new(MAX_OUTPUT_QUEUE);
+        loop {
+            let Some(event) = queue.pop() else {
+                match self.rx.recv().await {
+                    Some(event) => queue.push(event)?,
+                    None => return Ok(()),
+                }
+                continue;
+            };
+            self.stats.set_queue(queue.len(), queue.dropped_total());
+
+            let write = tokio::time::timeout(OUTPUT_WRITE_TIMEOUT, write_event(&mut client, event));
+            tokio::pin!(write);
+            loop {
+                tokio::select! {
+                    written = &mut write => {
+                        written.map_err(|_| anyhow::Error::msg("client stopped reading output"))??;
+                        break;
+                    }
+                    Some(event) = self.rx.recv() => {
+                        queue.push(event)?;
+                        self.stats.set_queue(queue.len(), queue.dropped_total());
+                    }
                 }
             }
         }
+    }
+}
 
-        Ok(())
+async fn write_event<C: ClientSink>(client: &mut C, event: OutEvent) -> anyhow::Result<()> {
+    match event {
+        OutEvent::Frame(frame, seq_nr) => client.send_frame(frame, seq_nr).await,
+        // For telnet IAC sequences, we wrap them in an OutFrame::Raw
+        OutEvent::Raw(bytes, seq_nr) => client.send_frame(OutFrame::Raw(bytes), seq_nr).await,
     }
 }
 
@@ -249,7 +402,7 @@ where
 {
     let (tx, rx) = mpsc::channel::<OutEvent>(64);
     let output_handle = OutputHandle::new(tx, sess.clone());
-    let session_out = SessionOut::new(rx);
+    let session_out = SessionOut::new(rx, sess.read().stats());
     let sink = TelnetSink::new(telnet_writer);
 
     tokio::spawn(async move {
@@ -266,9 +419,9 @@ pub async fn init_session_for_websocket(
     sess: Arc<RwLock<Session>>,
 ) -> SessionIoBundle {
     let (tx, rx) = mpsc::channel::<OutEvent>(64);
+    let session_out = SessionOut::new(rx, sess.read().stats());
+    let sink = WebSocketSink::new(websocket_writer, sess.read().stats());
     let output_handle = OutputHandle::new(tx, sess);
-    let session_out = SessionOut::new(rx);
-    let sink = WebSocketSink::new(websocket_writer);
 
     tokio::spawn(async move {
         if let Err(e) = session_out.run(sink).await {
@@ -278,3 +431,102 @@ pub async fn init_session_for_websocket(
 
     SessionIoBundle { output: output_handle }
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::state::session::Protocol;
+
+    fn handle() -> (OutputHandle, mpsc::Receiver<OutEvent>) {
+        let (tx, rx) = mpsc::channel(8);
+        let sess = Arc::new(RwLock::new(Session::new(Protocol::Telnet)));
+        (OutputHandle::new(tx, sess), rx)
+    }
+
+    fn line(ev: OutEvent) -> String {
+        match ev {
+            OutEvent::Frame(OutFrame::Line(s) | OutFrame::System(s), _) => s,
+            _ => panic!("expected a text frame"),
+        }
+    }
+
+    #[tokio::test]
+    async fn t_mirror_prefixes_and_stops() {
+        let (player, mut player_rx) = handle();
+        let (spectator, mut spectator_rx) = handle();
+        let id = AccountId::new();
+
+        player.add_mirror(id, "bob", &spectator);
+        player.system("one\ntwo").await;
+        assert_eq!(line(player_rx.recv().await.unwrap()), "one\ntwo");
+        assert_eq!(line(spectator_rx.recv().await.unwrap()), "[bob] one\n[bob] two");
+
+        assert!(player.remove_mirror(id));
+        assert!(!player.remove_mirror(id));
+        player.line("three").await;
+        assert_eq!(line(player_rx.recv().await.unwrap()), "three");
+        assert!(spectator_rx.try_recv().is_err());
+    }
+
+    #[tokio::test]
+    async fn t_flush_batches_in_order() {
+        let (player, mut rx) = handle();
+        let mut buffer = OutputBuffer::default();
+        buffer.line("one");
+        buffer.line("two");
+        buffer.sound("door_open");
+        buffer.line("three");
+        buffer.system("done");
+        player.flush(buffer).await;
+
+        assert_eq!(line(rx.recv().await.unwrap()), "one\ntwo");
+        assert!(matches!(
+            rx.recv().await.unwrap(),
+            OutEvent::Frame(OutFrame::Sound { cue }, _) if cue == "door_open"
+        ));
+        assert_eq!(line(rx.recv().await.unwrap()), "three");
+        assert_eq!(line(rx.recv().await.unwrap()), "done");
+        assert!(rx.try_recv().is_err());
+    }
+
+    #[tokio::test(flavor = "multi_thread", worker_threads = 4)]
+    async fn t_frames_arrive_in_sequence() {
+        let (tx, mut rx) = mpsc::channel(512);
+        let sess = Arc::new(RwLock::new(Session::new(Protocol::Telnet)));
+        let player = OutputHandle::new(tx, sess);
+
+        let tasks: Vec<_> = (0..4)
+            .map(|_| {
+                let player = player.clone();
+                tokio::spawn(async move {
+                    for _ in 0..50 {
+                        player.sound("tick").await;
+                    }
+                })
+            })
+            .collect();
+        for task in tasks {
+            task.await.unwrap();
+        }
+        drop(player);
+
+        let mut expected = 1;
+        while let Some(OutEvent::Frame(_, seq) | OutEvent::Raw(_, seq)) = rx.recv().await {
+            assert_eq!(seq, expected);
+            expected += 1;
+        }
+        assert_eq!(expected, 201);
+    }
+
+    #[tokio::test]
+    async fn t_closed_when_output_stops() {
+        let (player, rx) = handle();
+        let closed = tokio::spawn({
+            let player = player.clone();
+            async move { player.closed().await }
+        });
+        assert!(!closed.is_finished());
+        drop(rx);
+        closed.await.unwrap();
+    }
+}
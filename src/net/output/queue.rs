@@ -0,0 +1,165 @@
+//! Frames waiting for a slow client. The game never waits on a client: frames pile up here while
+//! the client catches up, a message repeated back to back collapses into one, and a newer prompt
+//! replaces one still waiting. Once the queue is full the oldest text frames are dropped, and the
+//! client is told how many it missed. Only a queue full of frames that cannot be dropped, like
+//! telnet negotiation, fails.
+
+use super::{OutEvent, OutFrame};
+use std::collections::VecDeque;
+
+struct Queued {
+    event: OutEvent,
+    /// Times the same text was sent back to back
+    repeats: usize,
+}
+
+pub(super) struct OutQueue {
+    entries: VecDeque<Queued>,
+    capacity: usize,
+    /// Frames dropped since the last one taken out
+    dropped: usize,
+    /// Frames dropped since the queue was made
+    dropped_total: u64,
+}
+
+impl OutQueue {
+    pub(super) fn new(capacity: usize) -> Self {
+        Self {
+            entries: VecDeque::new(),
+            capacity,
+            dropped: 0,
+            dropped_total: 0,
+        }
+    }
+
+    pub(super) fn len(&self) -> usize {
+        self.entries.len()
+    }
+
+    pub(super) fn dropped_total(&self) -> u64 {
+        self.dropped_total
+    }
+
+    pub(super) fn push(&mut self, event: OutEvent) -> anyhow::Result<()> {
+        if let Some(last) = self.entries.back_mut() {
+            match (&last.event, &event) {
+                (OutEvent::Frame(OutFrame::Line(a), _), OutEvent::Frame(OutFrame::Line(b), _))
+                | (OutEvent::Frame(OutFrame::System(a), _), OutEvent::Frame(OutFrame::System(b), _))
+                    if a == b =>
+                {
+                    last.repeats += 1;
+                    return Ok(());
+                }
+                (OutEvent::Frame(OutFrame::Prompt(_), _), OutEvent::Frame(OutFrame::Prompt(_), _))
+                | (OutEvent::Frame(OutFrame::RepaintLine(_), _), OutEvent::Frame(OutFrame::RepaintLine(_), _)) => {
+                    last.event = event;
+                    return Ok(());
+                }
+                _ => {}
+            }
+        }
+
+        if self.entries.len() >= self.capacity {
+            let Some(i) = self.entries.iter().position(|q| droppable(&q.event)) else {
+                anyhow::bail!("output queue full, the client stopped reading");
+            };
+            self.entries.remove(i);
+            self.dropped += 1;
+            self.dropped_total += 1;
+        }
+        self.entries.push_back(Queued { event, repeats: 1 });
+        Ok(())
+    }
+
+    /// The next event to write. After drops, that is first a notice of how many frames were lost,
+    /// sharing the sequence number of the frame after it.
+    pub(super) fn pop(&mut self) -> Option<OutEvent> {
+        let front = self.entries.front()?;
+        if self.dropped > 0 {
+            let seq = match front.event {
+                OutEvent::Frame(_, seq) | OutEvent::Raw(_, seq) => seq,
+            };
+            let notice = format!("({} messages dropped, your connection is not keeping up)", self.dropped);
+            self.dropped = 0;
+            return Some(OutEvent::Frame(OutFrame::System(notice), seq));
+        }
+
+        let Queued { event, repeats } = self.entries.pop_front()?;
+        Some(match event {
+            OutEvent::Frame(OutFrame::Line(s), seq) if repeats > 1 => {
+                OutEvent::Frame(OutFrame::Line(collapsed(&s, repeats)), seq)
+            }
+            OutEvent::Frame(OutFrame::System(s), seq) if repeats > 1 => {
+                OutEvent::Frame(OutFrame::System(collapsed(&s, repeats)), seq)
+            }
+            event => event,
+        })
+    }
+}
+
+/// Text frames can go missing without breaking the client; modes, prompts and raw bytes cannot
+fn droppable(event: &OutEvent) -> bool {
+    matches!(
+        event,
+        OutEvent::Frame(
+            OutFrame::Line(_) | OutFrame::System(_) | OutFrame::RoomView { .. } | OutFrame::Sound { .. },
+            _
+        )
+    )
+}
+
+fn collapsed(text: &str, repeats: usize) -> String {
+    format!("{} (x{})", text.trim_end(), repeats)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn line(s: &str, seq: u64) -> OutEvent {
+        OutEvent::Frame(OutFrame::Line(s.into()), seq)
+    }
+
+    fn text(ev: Option<OutEvent>) -> String {
+        match ev {
+            Some(OutEvent::Frame(OutFrame::Line(s) | OutFrame::System(s) | OutFrame::Prompt(s), _)) => s,
+            _ => panic!("expected a text frame"),
+        }
+    }
+
+    #[test]
+    fn t_queue_collapses_repeats() {
+        let mut q = OutQueue::new(8);
+        for seq in 1..=3 {
+            q.push(line("A bird chirps.", seq)).unwrap();
+        }
+        q.push(OutEvent::Frame(OutFrame::Prompt("> ".into()), 4)).unwrap();
+        q.push(OutEvent::Frame(OutFrame::Prompt("hp 9> ".into()), 5)).unwrap();
+        q.push(line("A bird chirps.", 6)).unwrap();
+
+        assert_eq!(text(q.pop()), "A bird chirps. (x3)");
+        assert_eq!(text(q.pop()), "hp 9> ");
+        assert_eq!(text(q.pop()), "A bird chirps.");
+        assert!(q.pop().is_none());
+    }
+
+    #[test]
+    fn t_queue_drops_oldest_text() {
+        let mut q = OutQueue::new(3);
+        q.push(OutEvent::Raw(vec![255, 251, 1], 1)).unwrap();
+        for seq in 2..=5 {
+            q.push(line(&format!("line {}", seq), seq)).unwrap();
+        }
+
+        assert!(matches!(q.pop(), Some(OutEvent::Frame(OutFrame::System(_), 1))));
+        assert!(matches!(q.pop(), Some(OutEvent::Raw(_, 1))));
+        assert_eq!(text(q.pop()), "line 4");
+        assert_eq!(text(q.pop()), "line 5");
+        assert!(q.pop().is_none());
+
+        let mut q = OutQueue::new(2);
+        q.push(OutEvent::Raw(vec![1], 1)).unwrap();
+        q.push(OutEvent::Raw(vec![2], 2)).unwrap();
+        assert!(q.push(line("one more", 3)).is_err());
+    }
+}
@@ -80,6 +80,9 @@ where
                 self.writer.write_all(b"\r\x1b[0K").await?;
                 self.writer.write_all(line.as_bytes()).await?;
             }
+            OutFrame::Sound { .. } => {
+                // Telnet has no audio channel, cues are silently dropped
+            }
         }
 
         Ok(())
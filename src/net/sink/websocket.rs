@@ -1,18 +1,22 @@
 use crate::net::output::OutFrame;
 use crate::net::sink::ClientSink;
+use crate::net::stats::ConnStats;
 use async_trait::async_trait;
 use futures::SinkExt;
 use serde::Serialize;
+use std::sync::Arc;
 
 pub struct WebSocketSink<S, M> {
     ws: S,
+    stats: Arc<ConnStats>,
     _phantom: std::marker::PhantomData<M>,
 }
 
 impl<S, M> WebSocketSink<S, M> {
-    pub fn new(ws: S) -> Self {
+    pub fn new(ws: S, stats: Arc<ConnStats>) -> Self {
         Self {
             ws,
+            stats,
             _phantom: std::marker::PhantomData,
         }
     }
@@ -26,6 +30,7 @@ enum WsFrame<'a> {
     RoomView { content: &'a str },
     Prompt { text: &'a str },
     ClearScreen,
+    Sound { cue: &'a str },
 }
 
 #[derive(Serialize)]
@@ -55,10 +60,12 @@ where
                 return Err(anyhow::Error::msg("Raw frame not supported over WebSocket sink"));
             }
             OutFrame::RepaintLine(line) => WsFrame::Line { text: line },
+            OutFrame::Sound { cue } => WsFrame::Sound { cue },
         };
 
         let env = WsEnvelope { seq, frame: payload };
         let json = serde_json::to_string(&env)?;
+        self.stats.add_bytes_out(json.len());
 
         self.ws
             .send(json.into())
@@ -0,0 +1,99 @@
+//! Traffic counters of one connection, shown by `@debug conn`. They are bumped from the input
+//! loop, the output task and the Lua pool without taking the session lock.
+
+use std::sync::atomic::{AtomicU64, AtomicUsize, Ordering};
+
+#[derive(Debug, Default)]
+pub struct ConnStats {
+    bytes_in: AtomicU64,
+    bytes_out: AtomicU64,
+    commands: AtomicU64,
+    lua_jobs: AtomicU64,
+    /// Output frames waiting for the client, and the most there have been
+    queue_depth: AtomicUsize,
+    queue_peak: AtomicUsize,
+    /// Output frames dropped because the client did not keep up
+    frames_dropped: AtomicU64,
+}
+
+/// Counters of a connection at one moment
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct ConnStatsSnapshot {
+    pub bytes_in: u64,
+    pub bytes_out: u64,
+    pub commands: u64,
+    pub lua_jobs: u64,
+    pub queue_depth: usize,
+    pub queue_peak: usize,
+    pub frames_dropped: u64,
+}
+
+impl ConnStats {
+    pub fn add_bytes_in(&self, n: usize) {
+        self.bytes_in.fetch_add(n as u64, Ordering::Relaxed);
+    }
+
+    pub fn add_bytes_out(&self, n: usize) {
+        self.bytes_out.fetch_add(n as u64, Ordering::Relaxed);
+    }
+
+    pub fn add_command(&self) {
+        self.commands.fetch_add(1, Ordering::Relaxed);
+    }
+
+    pub fn add_lua_job(&self) {
+        self.lua_jobs.fetch_add(1, Ordering::Relaxed);
+    }
+
+    /// Updates the output queue depth, with `dropped` the frames dropped since startup
+    pub fn set_queue(&self, depth: usize, dropped: u64) {
+        self.queue_depth.store(depth, Ordering::Relaxed);
+        self.queue_peak.fetch_max(depth, Ordering::Relaxed);
+        self.frames_dropped.store(dropped, Ordering::Relaxed);
+    }
+
+    pub fn snapshot(&self) -> ConnStatsSnapshot {
+        ConnStatsSnapshot {
+            bytes_in: self.bytes_in.load(Ordering::Relaxed),
+            bytes_out: self.bytes_out.load(Ordering::Relaxed),
+            commands: self.commands.load(Ordering::Relaxed),
+            lua_jobs: self.lua_jobs.load(Ordering::Relaxed),
+            queue_depth: self.queue_depth.load(Ordering::Relaxed),
+            queue_peak: self.queue_peak.load(Ordering::Relaxed),
+            frames_dropped: self.frames_dropped.load(Ordering::Relaxed),
+        }
+    }
+}
+
+/// Byte counts the way `@debug conn` shows them, e.g. `812 B` or `34.5 KB`
+pub fn format_bytes(n: u64) -> String {
+    match n {
+        n if n < 1024 => format!("{} B", n),
+        n if n < 1024 * 1024 => format!("{:.1} KB", n as f64 / 1024.0),
+        n => format!("{:.1} MB", n as f64 / (1024.0 * 1024.0)),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn t_conn_stats() {
+        let stats = ConnStats::default();
+        stats.add_bytes_in(10);
+        stats.add_bytes_out(2048);
+        stats.add_command();
+        stats.add_lua_job();
+        stats.add_lua_job();
+        stats.set_queue(7, 0);
+        stats.set_queue(2, 3);
+
+        let s = stats.snapshot();
+        assert_eq!((s.bytes_in, s.bytes_out, s.commands, s.lua_jobs), (10, 2048, 1, 2));
+        assert_eq!((s.queue_depth, s.queue_peak, s.frames_dropped), (2, 7, 3));
+
+        assert_eq!(format_bytes(812), "812 B");
+        assert_eq!(format_bytes(35328), "34.5 KB");
+    }
+}
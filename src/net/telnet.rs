@@ -1,28 +1,38 @@
 mod connection;
+mod counting_writer;
 mod crlf_wrapper;
 mod slow_writer;
 
 use crate::banner::{BANNER, ENTRY};
 use crate::error::{AppResult, InfraError};
-use crate::lua::LuaJob;
+use crate::lua::LuaPool;
 use crate::net::AppCtx;
 use crate::net::output::init_session_for_telnet;
 use crate::net::telnet::connection::handle_connection;
+use crate::net::telnet::counting_writer::CountingWriter;
 use crate::net::telnet::crlf_wrapper::CrlfWriter;
 use crate::state::session::Protocol;
 use crate::util::telnet::TelnetMachine;
 use crate::{Registry, Session};
 use parking_lot::RwLock;
 use std::sync::Arc;
-use tokio::sync::mpsc;
+
+const TOO_MANY_CONNECTIONS: &str = "Too many connections from your address, try again later.\r\n";
 
 /// Run the telnet server
-pub async fn serve(addr: std::net::SocketAddr, registry: Arc<Registry>, lua_tx: mpsc::Sender<LuaJob>) -> AppResult<()> {
+pub async fn serve(addr: std::net::SocketAddr, registry: Arc<Registry>, lua_tx: LuaPool) -> AppResult<()> {
     let listener = tokio::net::TcpListener::bind(&addr).await.map_err(InfraError::from)?;
 
     loop {
         match listener.accept().await {
             Ok((stream, peer)) => {
+                let max = registry.config().rate_limit.connections_per_ip;
+                let Some(permit) = registry.connections.try_acquire(peer.ip(), max) else {
+                    tracing::warn!(%peer, "too many connections from this address, refusing");
+                    // Best effort; the accept loop does not wait for a client to read
+                    let _ = stream.try_write(TOO_MANY_CONNECTIONS.as_bytes());
+                    continue;
+                };
                 tracing::info!(%peer, "client connected");
 
                 // let ctx = Arc::new(AppCtx {
@@ -35,6 +45,7 @@ pub async fn serve(addr: std::net::SocketAddr, registry: Arc<Registry>, lua_tx:
                 let lua_tx = lua_tx.clone();
                 let registry = registry.clone();
                 tokio::spawn(async move {
+                    let _permit = permit;
                     if let Err(e) = handle_telnet_connection(stream, peer, registry.clone(), lua_tx.clone()).await {
                         tracing::error!(%peer, error=%e, "connection error");
                     }
@@ -51,14 +62,18 @@ pub async fn serve(addr: std::net::SocketAddr, registry: Arc<Registry>, lua_tx:
 
 async fn handle_telnet_connection(
     stream: tokio::net::TcpStream,
-    _peer: std::net::SocketAddr,
+    peer: std::net::SocketAddr,
     registry: Arc<Registry>,
-    lua_tx: mpsc::Sender<LuaJob>,
+    lua_tx: LuaPool,
 ) -> AppResult<()> {
     let (read_half, write_half) = stream.into_split();
 
-    // Wrap write half with CRLF conversion and pacing
-    let crlf_writer = CrlfWriter::new(write_half);
+    let sess = Arc::new(RwLock::new(Session::new(Protocol::Telnet)));
+    sess.write().set_peer(peer.ip());
+
+    // Wrap write half with byte counting, CRLF conversion and pacing
+    let stats = sess.read().stats();
+    let crlf_writer = CrlfWriter::new(CountingWriter::new(write_half, stats));
     // let mut paced_writer = SlowWriter::new(
     //     crlf_writer,
     //     Pace::PerWord {
@@ -71,8 +86,6 @@ async fn handle_telnet_connection(
     let mut telnet = TelnetMachine::new();
     telnet.start_negotiation(&mut wrapper_writer).await?;
 
-    let sess = Arc::new(RwLock::new(Session::new(Protocol::Telnet)));
-
     let io_bundle = init_session_for_telnet(wrapper_writer, sess.clone()).await;
 
     io_bundle.output.system(BANNER).await;
@@ -1,6 +1,7 @@
 use crate::commands::CmdCtx;
 use crate::error::AppResult;
 use crate::input::readline::{EditEvent, LineEditor};
+use crate::input::sanitize::sanitize_line;
 use crate::lua::table::format_lua_value;
 use crate::lua::{LuaJob, LuaResult};
 use crate::net::{AppCtx, InputMode};
@@ -38,12 +39,20 @@ async fn read_loop(
     sess: Arc<RwLock<Session>>,
 ) -> AppResult<()> {
     let mut one = [0u8; 1];
+    let stats = sess.read().stats();
 
     loop {
-        let n = reader.read(&mut one).await?;
+        let n = tokio::select! {
+            n = reader.read(&mut one) => n?,
+            _ = ctx.output.closed() => {
+                tracing::info!("client stopped reading output, disconnecting");
+                break;
+            }
+        };
         if n == 0 {
             break; // disconnect
         }
+        stats.add_bytes_in(n);
 
         let response = telnet.push(one[0]);
 
@@ -55,6 +64,13 @@ async fn read_loop(
             match evt {
                 TelnetIn::Data(b) => handle_data_byte(b, reader, telnet, editor, sess.clone(), ctx.clone()).await?,
                 TelnetIn::Naws { cols, rows } => handle_naws(cols, rows, sess.clone()).await,
+                TelnetIn::Terminal(info) => {
+                    tracing::debug!(client = %info.client, terminal = ?info.terminal, mtts = ?info.mtts, "terminal type");
+                    sess.write().set_terminal(info);
+                }
+                TelnetIn::Gmcp { package, data } => {
+                    tracing::debug!(%package, %data, "ignoring GMCP message");
+                }
             }
         }
     }
@@ -98,6 +114,7 @@ async fn handle_data_byte(
             ctx.output.draw_line(editor.repaint_line()).await;
         }
         EditEvent::Line(line) => {
+            let line = sanitize_line(&line);
             let raw = line.trim();
 
             let in_repl = sess.read().is_in_lua();
@@ -134,6 +151,7 @@ async fn dispatch_command(raw: &str, ctx: Arc<AppCtx>, sess: Arc<RwLock<Session>
 
     match process_command(raw, cmd_ctx.clone()).await {
         Ok(_) => {}
+        Err(e) if e.is_busy() => ctx.output.system(e.to_string()).await,
         Err(e) => {
             ctx.output
                 .system(format!(
@@ -182,7 +200,10 @@ async fn handle_repl_input(raw: &str, ctx: Arc<AppCtx>, sess: Arc<RwLock<Session
         code: raw.to_string(),
         reply: reply_tx,
     };
-    _ = ctx.lua_tx.send(job).await;
+    if let Err(e) = ctx.lua_tx.send(job).await {
+        ctx.output.system(e.to_string()).await;
+        return Ok(());
+    }
 
     // New line for enter
     ctx.output.line("\n").await;
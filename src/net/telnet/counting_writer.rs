@@ -0,0 +1,33 @@
+use crate::net::stats::ConnStats;
+use std::pin::Pin;
+use std::sync::Arc;
+use std::task::{Context, Poll, ready};
+use tokio::io::{self, AsyncWrite};
+
+/// Counts the bytes written through it into the connection stats
+pub struct CountingWriter<W> {
+    inner: W,
+    stats: Arc<ConnStats>,
+}
+
+impl<W> CountingWriter<W> {
+    pub fn new(inner: W, stats: Arc<ConnStats>) -> Self {
+        Self { inner, stats }
+    }
+}
+
+impl<W: AsyncWrite + Unpin> AsyncWrite for CountingWriter<W> {
+    fn poll_write(mut self: Pin<&mut Self>, cx: &mut Context<'_>, buf: &[u8]) -> Poll<io::Result<usize>> {
+        let n = ready!(Pin::new(&mut self.inner).poll_write(cx, buf))?;
+        self.stats.add_bytes_out(n);
+        Poll::Ready(Ok(n))
+    }
+
+    fn poll_flush(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Pin::new(&mut self.inner).poll_flush(cx)
+    }
+
+    fn poll_shutdown(mut self: Pin<&mut Self>, cx: &mut Context<'_>) -> Poll<io::Result<()>> {
+        Pin::new(&mut self.inner).poll_shutdown(cx)
+    }
+}
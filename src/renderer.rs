@@ -1,12 +1,16 @@
 mod ansi;
 mod parser;
 
+pub mod inventory;
 mod objects;
+pub mod prompt;
 pub mod room_view;
+pub mod theme;
 pub mod vars;
 
 use crate::Session;
 use crate::renderer::parser::Alignment;
+use crate::renderer::theme::Theme;
 use once_cell::sync::Lazy;
 use parking_lot::RwLock;
 pub use parser::{Token, VarFmt};
@@ -23,6 +27,8 @@ pub struct RenderVars {
     pub global: HashMap<String, String>,
     // RoomView values accessed with {rv:var_name}
     pub room_view: HashMap<String, String>,
+    // Theme for {c:@role} colors
+    pub theme: Theme,
 }
 
 impl RenderVars {
@@ -145,7 +151,10 @@ fn render_single_pass(template: &str, vars: &RenderVars, opts: &RenderOptions) -
             }
             Token::ColorReset => out.push_str(ansi::RESET),
             Token::Color { fg, bg, attrs } => {
-                let code = ansi::compose_sgr(fg.as_deref(), bg.as_deref(), &attrs);
+                let code = match fg.as_deref().and_then(|f| f.strip_prefix('@')) {
+                    Some(role) => vars.theme.sgr(role),
+                    None => ansi::compose_sgr(fg.as_deref(), bg.as_deref(), &attrs),
+                };
                 if !code.is_empty() {
                     out.push_str(&code);
                 }
@@ -183,7 +192,7 @@ fn expand_inline_object_tokens(s: &str, vars: &RenderVars) -> String {
         .replace_all(s, |caps: &regex::Captures| {
             let oid = &caps[1];
             match resolve_object_label(oid, vars) {
-                Some(label) => format!("{{c:bright_yellow}}{}{{c}}", label),
+                Some(label) => format!("{{c:@object}}{}{{c}}", label),
                 None => caps[0].to_string(), // keep as-is; avoids breaking authoring
             }
         })
@@ -216,6 +225,11 @@ pub fn render_template_with_opts(template: &str, vars: &RenderVars, opts: &Rende
         }
     }
 
+    // Also drops the colors of text that came with escape codes already
+    if !vars.theme.is_colored() {
+        s = strip_ansi(&s);
+    }
+
     if opts.max_width > 0 {
         wrap_ansi_aware(&s, opts.max_width)
     } else {
@@ -298,6 +312,11 @@ fn pad_string(s: &str, width: usize, alignment: Alignment) -> String {
 static ANSI_RE: Lazy<Regex> = Lazy::new(|| Regex::new(r"\x1B\[[0-9;]*m").unwrap());
 static WS_RE: Lazy<Regex> = Lazy::new(|| Regex::new(r"\s+").unwrap());
 
+/// Removes ANSI color codes
+pub fn strip_ansi(s: &str) -> String {
+    ANSI_RE.replace_all(s, "").into_owned()
+}
+
 fn visible_len(s: &str) -> usize {
     ANSI_RE.replace_all(s, "").chars().count()
 }
@@ -407,10 +426,7 @@ mod tests {
 
     #[test]
     fn var_default_and_format() {
-        let mut vars = RenderVars {
-            global: HashMap::new(),
-            room_view: HashMap::new(),
-        };
+        let mut vars = RenderVars::default();
         vars.global.insert("score".into(), "7".into());
         let s = render_template("Score {v:score|%05d}", &vars, 80);
         assert_eq!(s, "Score 00007");
@@ -635,4 +651,18 @@ mod tests {
         let out = wrap_ansi_aware(s, 80);
         assert_eq!(out, "   foo  \n  bar ");
     }
+
+    #[test]
+    fn theme_roles_and_no_color() {
+        let mut vars = RenderVars::default();
+        vars.room_view.insert("obj.map.short".into(), "wall map".into());
+        let tpl = "{c:@title}Hall{c} {c:bright_red}!{c} {o:map}";
+
+        let s = render_template(tpl, &vars, 80);
+        assert_eq!(s, "\x1b[94mHall\x1b[0m \x1b[91m!\x1b[0m \x1b[93mwall map\x1b[0m");
+
+        vars.theme = Theme::NoColor;
+        let s = render_template(tpl, &vars, 80);
+        assert_eq!(s, "Hall ! wall map");
+    }
 }
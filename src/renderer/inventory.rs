@@ -0,0 +1,166 @@
+//! Inventory listing.
+//!
+//! The carried items come in flat, each with its location. They are turned into a tree: items
+//! inside a carried container are listed under it, indented. Identical items without contents
+//! are grouped into one line with their total quantity, whether they are stacks or single items.
+
+use crate::models::inventory::{ItemInstance, ItemLocation};
+use crate::models::types::AccountId;
+
+/// One line of the inventory, with what is inside it
+#[derive(Debug)]
+pub struct InventoryEntry<'a> {
+    pub item: &'a ItemInstance,
+    pub quantity: i32,
+    pub contents: Vec<InventoryEntry<'a>>,
+}
+
+impl InventoryEntry<'_> {
+    /// The item is called by `filter`: one of its nouns or its key, or a part of its name
+    fn matches(&self, filter: &str) -> bool {
+        let item = self.item;
+        item.nouns.iter().any(|n| n.eq_ignore_ascii_case(filter))
+            || item.item_key.eq_ignore_ascii_case(filter)
+            || item.name.to_lowercase().contains(filter)
+    }
+}
+
+/// Builds the inventory tree of `account_id` from everything they carry
+pub fn inventory_tree(items: &[ItemInstance], account_id: AccountId) -> Vec<InventoryEntry<'_>> {
+    entries_at(items, ItemLocation::Player(account_id))
+}
+
+fn entries_at(items: &[ItemInstance], location: ItemLocation) -> Vec<InventoryEntry<'_>> {
+    let mut entries: Vec<InventoryEntry> = Vec::new();
+    for item in items.iter().filter(|i| i.location == location) {
+        let contents = entries_at(items, ItemLocation::Container(item.instance_id));
+        let same = entries.iter_mut().find(|e| {
+            contents.is_empty()
+                && e.contents.is_empty()
+                && e.item.catalog_id == item.catalog_id
+                && e.item.is_broken() == item.is_broken()
+        });
+        match same {
+            Some(entry) => entry.quantity += item.quantity,
+            None => entries.push(InventoryEntry {
+                item,
+                quantity: item.quantity,
+                contents,
+            }),
+        }
+    }
+    entries
+}
+
+/// Keeps the entries called by `filter`, with all their contents, and the containers holding them
+pub fn filter_tree<'a>(entries: Vec<InventoryEntry<'a>>, filter: &str) -> Vec<InventoryEntry<'a>> {
+    let filter = filter.trim().to_lowercase();
+    filter_entries(entries, &filter)
+}
+
+fn filter_entries<'a>(entries: Vec<InventoryEntry<'a>>, filter: &str) -> Vec<InventoryEntry<'a>> {
+    entries
+        .into_iter()
+        .filter_map(|mut entry| {
+            if entry.matches(filter) {
+                return Some(entry);
+            }
+            entry.contents = filter_entries(entry.contents, filter);
+            (!entry.contents.is_empty()).then_some(entry)
+        })
+        .collect()
+}
+
+/// One line per entry, contents indented below their container
+pub fn render_inventory(entries: &[InventoryEntry]) -> Vec<String> {
+    let mut lines = Vec::new();
+    render_entries(entries, 1, &mut lines);
+    lines
+}
+
+fn render_entries(entries: &[InventoryEntry], depth: usize, lines: &mut Vec<String>) {
+    for entry in entries {
+        let mut line = format!("{}{}", "  ".repeat(depth), entry.item.short);
+        if entry.quantity > 1 {
+            line.push_str(&format!(" (x{})", entry.quantity));
+        }
+        if entry.item.is_broken() {
+            line.push_str(" (broken)");
+        }
+        lines.push(line);
+        render_entries(&entry.contents, depth + 1, lines);
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::types::{ItemId, RealmId};
+
+    fn item(key: &str, short: &str, location: ItemLocation, quantity: i32) -> ItemInstance {
+        ItemInstance {
+            instance_id: ItemId::new(),
+            realm_id: RealmId::new(),
+            catalog_id: ItemId::new(),
+            location,
+            quantity,
+            condition: None,
+            item_key: key.into(),
+            name: short.trim_start_matches("a ").into(),
+            short: short.into(),
+            description: String::new(),
+            examine: None,
+            stackable: quantity > 1,
+            nouns: vec![key.into()],
+            max_durability: None,
+            wear_per_use: 1,
+            repair_tool: None,
+            weight: None,
+            is_coin: false,
+            created_at: chrono::Utc::now(),
+            updated_at: chrono::Utc::now(),
+        }
+    }
+
+    fn carried() -> (AccountId, Vec<ItemInstance>) {
+        let player = AccountId::new();
+        let bag = item("bag", "a bag", ItemLocation::Player(player), 1);
+        let pouch = item("pouch", "a pouch", ItemLocation::Container(bag.instance_id), 1);
+        let coins = item("coin", "a coin", ItemLocation::Player(player), 3);
+        let more_coins = ItemInstance {
+            instance_id: ItemId::new(),
+            quantity: 2,
+            ..coins.clone()
+        };
+        let items = vec![
+            coins,
+            more_coins,
+            item("key", "a key", ItemLocation::Container(pouch.instance_id), 1),
+            item("rope", "a rope", ItemLocation::Container(bag.instance_id), 1),
+            bag,
+            pouch,
+        ];
+        (player, items)
+    }
+
+    #[test]
+    fn t_groups_and_nests() {
+        let (player, items) = carried();
+        assert_eq!(
+            render_inventory(&inventory_tree(&items, player)),
+            vec!["  a coin (x5)", "  a bag", "    a rope", "    a pouch", "      a key",]
+        );
+    }
+
+    #[test]
+    fn t_filter_keeps_containers() {
+        let (player, items) = carried();
+        let tree = filter_tree(inventory_tree(&items, player), "Key");
+        assert_eq!(render_inventory(&tree), vec!["  a bag", "    a pouch", "      a key"]);
+
+        let tree = filter_tree(inventory_tree(&items, player), "pouch");
+        assert_eq!(render_inventory(&tree), vec!["  a bag", "    a pouch", "      a key"]);
+
+        assert!(filter_tree(inventory_tree(&items, player), "lamp").is_empty());
+    }
+}
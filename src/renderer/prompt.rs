@@ -0,0 +1,108 @@
+//! Prompt templates.
+//!
+//! A prompt is a render template like any other, but players rarely want to type `{v:...}`
+//! tokens. Short codes like `%hp` or `%room` are expanded into the matching template tokens first,
+//! so `%hp %room >` renders as `30 Lobby > `. A blueprint can set its own prompt to style it for
+//! the realm; a prompt the player picked always wins over the realm's prompt.
+
+use crate::error::{AppResult, DomainError};
+
+/// Longest prompt template a player or blueprint can set
+pub const MAX_PROMPT_LEN: usize = 200;
+
+/// Every prompt code with the template it expands to and what it shows
+pub const PROMPT_CODES: &[(&str, &str, &str)] = &[
+    ("hp", "{v:account.health}", "your health"),
+    ("coins", "{v:account.coins}", "your coins"),
+    ("xp", "{v:account.xp}", "your experience points"),
+    ("level", "{v:account.xp_level_name}", "your level"),
+    ("name", "{v:account.name}", "your name"),
+    ("room", "{rv:title:Nowhere}", "the room you are in"),
+    ("realm", "{v:cursor.realm:Nowhere}", "the realm you are in"),
+    ("exits", "{rv:exits}", "the exits of the room"),
+    ("time", "{v:wall_time}", "the time of day"),
+    ("wtime", "{v:world_time}", "the time in the world"),
+];
+
+/// Replaces the `%code`s in `prompt` by their template tokens. `%%` is a literal `%`, and a `%`
+/// that does not start a known code is kept as it is.
+pub fn expand_prompt(prompt: &str) -> String {
+    let mut out = String::with_capacity(prompt.len());
+    let mut rest = prompt;
+    while let Some(i) = rest.find('%') {
+        out.push_str(&rest[..i]);
+        rest = &rest[i + 1..];
+        if let Some(after) = rest.strip_prefix('%') {
+            out.push('%');
+            rest = after;
+            continue;
+        }
+
+        let len = rest.find(|c: char| !c.is_ascii_alphanumeric()).unwrap_or(rest.len());
+        match PROMPT_CODES.iter().find(|(code, _, _)| *code == &rest[..len]) {
+            Some((_, template, _)) => {
+                out.push_str(template);
+                rest = &rest[len..];
+            }
+            None => out.push('%'),
+        }
+    }
+    out.push_str(rest);
+    out
+}
+
+/// Checks a prompt as typed by a player or written in a manifest. Empty or `default` means the
+/// default prompt. A space is added so what the player types does not stick to the prompt.
+pub fn parse_prompt(field: &'static str, value: &str) -> AppResult<Option<String>> {
+    let value = value.trim();
+    if value.is_empty() || value == "default" {
+        return Ok(None);
+    }
+    let invalid = |message: &str| DomainError::Validation {
+        field,
+        message: format!("prompt {}", message),
+    };
+    if value.chars().count() > MAX_PROMPT_LEN {
+        return Err(invalid("is too long"));
+    }
+    if value.chars().any(char::is_control) {
+        return Err(invalid("cannot contain control characters"));
+    }
+    Ok(Some(format!("{} ", value)))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::renderer::{RenderVars, render_template};
+    use std::collections::HashMap;
+
+    #[test]
+    fn t_expand_prompt() {
+        assert_eq!(
+            expand_prompt("%hp %room > "),
+            "{v:account.health} {rv:title:Nowhere} > "
+        );
+        assert_eq!(expand_prompt("100%% %hpx %"), "100% %hpx %");
+        assert_eq!(expand_prompt("{c:@title}%name{c}"), "{c:@title}{v:account.name}{c}");
+        assert_eq!(expand_prompt("%wtime"), "{v:world_time}");
+    }
+
+    #[test]
+    fn t_render_prompt() {
+        let vars = RenderVars {
+            global: HashMap::from([("account.health".to_string(), "30".to_string())]),
+            room_view: HashMap::from([("title".to_string(), "Lobby".to_string())]),
+            ..RenderVars::default()
+        };
+        assert_eq!(render_template(&expand_prompt("%hp %room > "), &vars, 0), "30 Lobby > ");
+    }
+
+    #[test]
+    fn t_parse_prompt() {
+        assert_eq!(parse_prompt("prompt", " %hp > ").unwrap().as_deref(), Some("%hp > "));
+        assert_eq!(parse_prompt("prompt", "default").unwrap(), None);
+        assert!(parse_prompt("prompt", "a\x1bb").is_err());
+        assert!(parse_prompt("prompt", &"x".repeat(MAX_PROMPT_LEN + 1)).is_err());
+    }
+}
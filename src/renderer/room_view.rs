@@ -1,15 +1,35 @@
 pub fn render_room_view() -> String {
     let res = [
-        "{c:blue}--------------------------------------------------{c}",
-        "{c:bright_blue}{rv:title|%*50s}{c}",
-        "{c:blue}--------------------------------------------------{c}",
+        "{c:@border}--------------------------------------------------{c}",
+        "{c:@title}{rv:title|%*50s}{c}",
+        "{c:@border}--------------------------------------------------{c}",
         "\n",
-        "{c:bright_white}{rv:body}{c}",
+        "{c:@body}{rv:body}{c}",
         "\n",
-        "Visible items: {c:green}{rv:items}{c}",
-        "Visible exits: {c:green}{rv:exits}{c}",
+        "Visible items: {c:@items}{rv:items}{c}",
+        "Visible exits: {c:@exits}{rv:exits}{c}",
         "\n",
     ];
 
     res.join("\n")
 }
+
+/// Only the title and exits, for players with the `brief` setting on
+pub fn render_room_view_brief() -> String {
+    let res = [
+        "{c:@title}{rv:title}{c}",
+        "Visible exits: {c:@exits}{rv:exits}{c}",
+        "\n",
+    ];
+
+    res.join("\n")
+}
+
+/// View shown when arriving in a room; `look` always shows the full view
+pub fn render_arrival_view(brief: bool) -> String {
+    if brief {
+        render_room_view_brief()
+    } else {
+        render_room_view()
+    }
+}
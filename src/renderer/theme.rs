@@ -0,0 +1,156 @@
+//! Color themes. Templates mark engine highlights with a role, like `{c:@title}`, and the
+//! player's theme decides what the role looks like. The `none` theme strips all color.
+
+use crate::error::DomainError;
+use crate::renderer::ansi;
+
+/// Roles a template can color with `{c:@role}`
+pub const ROLES: &[&str] = &["title", "border", "body", "items", "exits", "object", "hint"];
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq, Default)]
+pub enum Theme {
+    #[default]
+    Default,
+    /// Brighter and bold, for dark terminals and low vision
+    Contrast,
+    /// Bold and underline only, for terminals without color
+    Mono,
+    /// No escape codes at all, for screen readers and logs
+    NoColor,
+}
+
+struct Style {
+    fg: Option<&'static str>,
+    bg: Option<&'static str>,
+    attrs: &'static [&'static str],
+}
+
+const fn fg(name: &'static str) -> Style {
+    Style {
+        fg: Some(name),
+        bg: None,
+        attrs: &[],
+    }
+}
+
+const fn fg_attrs(name: &'static str, attrs: &'static [&'static str]) -> Style {
+    Style {
+        fg: Some(name),
+        bg: None,
+        attrs,
+    }
+}
+
+const fn attrs(attrs: &'static [&'static str]) -> Style {
+    Style {
+        fg: None,
+        bg: None,
+        attrs,
+    }
+}
+
+impl Theme {
+    pub const ALL: [Theme; 4] = [Theme::Default, Theme::Contrast, Theme::Mono, Theme::NoColor];
+
+    pub fn as_str(&self) -> &'static str {
+        match self {
+            Theme::Default => "default",
+            Theme::Contrast => "contrast",
+            Theme::Mono => "mono",
+            Theme::NoColor => "none",
+        }
+    }
+
+    pub fn is_colored(&self) -> bool {
+        *self != Theme::NoColor
+    }
+
+    fn style(&self, role: &str) -> Option<Style> {
+        let style = match (self, role) {
+            (Theme::Default, "title") => fg("bright_blue"),
+            (Theme::Default, "border") => fg("blue"),
+            (Theme::Default, "body") => fg("bright_white"),
+            (Theme::Default, "items" | "exits") => fg("green"),
+            (Theme::Default, "object") => fg("bright_yellow"),
+            (Theme::Default, "hint") => Style {
+                fg: Some("cyan"),
+                bg: Some("bright_cyan"),
+                attrs: &[],
+            },
+
+            (Theme::Contrast, "title") => fg_attrs("bright_yellow", &["bold"]),
+            (Theme::Contrast, "border") => fg("bright_white"),
+            (Theme::Contrast, "body") => fg("white"),
+            (Theme::Contrast, "items") => fg_attrs("bright_green", &["bold"]),
+            (Theme::Contrast, "exits") => fg_attrs("bright_cyan", &["bold"]),
+            (Theme::Contrast, "object") => fg_attrs("bright_magenta", &["bold"]),
+            (Theme::Contrast, "hint") => fg("bright_cyan"),
+
+            (Theme::Mono, "title" | "object") => attrs(&["bold"]),
+            (Theme::Mono, "border" | "hint") => attrs(&["dim"]),
+            (Theme::Mono, "items" | "exits") => attrs(&["underline"]),
+
+            _ => return None,
+        };
+        Some(style)
+    }
+
+    /// Escape code for `role`; empty when the theme leaves the role plain or does not know it
+    pub fn sgr(&self, role: &str) -> String {
+        match self.style(role) {
+            Some(style) => {
+                let attrs: Vec<String> = style.attrs.iter().map(|a| a.to_string()).collect();
+                ansi::compose_sgr(style.fg, style.bg, &attrs)
+            }
+            None => String::new(),
+        }
+    }
+}
+
+impl std::fmt::Display for Theme {
+    fn fmt(&self, f: &mut std::fmt::Formatter<'_>) -> std::fmt::Result {
+        f.write_str(self.as_str())
+    }
+}
+
+impl std::str::FromStr for Theme {
+    type Err = DomainError;
+
+    fn from_str(s: &str) -> Result<Self, Self::Err> {
+        match s.trim().to_ascii_lowercase().as_str() {
+            "default" => Ok(Theme::Default),
+            "contrast" => Ok(Theme::Contrast),
+            "mono" => Ok(Theme::Mono),
+            "none" | "off" | "nocolor" => Ok(Theme::NoColor),
+            _ => Err(DomainError::Validation {
+                field: "theme",
+                message: format!("unknown theme '{}' (default, contrast, mono or none)", s),
+            }),
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn t_theme_roles() {
+        assert_eq!(Theme::Default.sgr("title"), "\x1b[94m");
+        assert_eq!(Theme::Mono.sgr("title"), "\x1b[1m");
+        assert_eq!(Theme::Mono.sgr("body"), "");
+        assert_eq!(Theme::Default.sgr("no_such_role"), "");
+        for role in ROLES {
+            assert_eq!(Theme::NoColor.sgr(role), "");
+        }
+    }
+
+    #[test]
+    fn t_theme_names() {
+        for theme in Theme::ALL {
+            assert_eq!(theme.as_str().parse::<Theme>().unwrap(), theme);
+        }
+        assert_eq!("off".parse::<Theme>().unwrap(), Theme::NoColor);
+        assert!("neon".parse::<Theme>().is_err());
+    }
+}
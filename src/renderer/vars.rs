@@ -2,6 +2,7 @@ use crate::Session;
 use crate::game::{xp_to_level, xp_to_level_name};
 use crate::models::room::RoomView;
 use crate::renderer::RenderVars;
+use crate::state::clock;
 use parking_lot::RwLock;
 use std::collections::HashMap;
 use std::sync::Arc;
@@ -14,9 +15,12 @@ pub fn generate_render_vars(sess: Arc<RwLock<Session>>) -> RenderVars {
         None => HashMap::new(),
     };
 
+    let theme = sess.read().get_account().map(|a| a.settings.theme).unwrap_or_default();
+
     RenderVars {
         global: get_global_vars(sess.clone()),
         room_view,
+        theme,
     }
 }
 
@@ -38,6 +42,12 @@ fn get_global_vars(sess: Arc<RwLock<Session>>) -> HashMap<String, String> {
     vars.insert("now_utc".to_string(), chrono::Utc::now().to_rfc3339());
     vars.insert("now_local".to_string(), chrono::Local::now().to_rfc3339());
 
+    // In-world clock
+    let world = clock::world_time();
+    vars.insert("world_time".to_string(), world.format("%H:%M").to_string());
+    vars.insert("world_date".to_string(), world.format("%Y-%m-%d").to_string());
+    vars.insert("world_hour".to_string(), world.format("%-H").to_string());
+
     if let Some(account) = sess.read().get_account().as_ref() {
         vars.insert("account.name".to_string(), account.username.to_string());
         vars.insert("account.role".to_string(), account.role.to_string());
@@ -0,0 +1,331 @@
+//! Scripted playthroughs of a blueprint.
+//!
+//! A blueprint directory can hold a `tests/` directory with scenario files. Each scenario logs a
+//! fresh tester account into a throwaway realm of the blueprint, sends its commands one by one
+//! and checks the output and state after each step. The realm and the tester are deleted again
+//! afterwards, so scenarios can run against any database that has the blueprint imported.
+//!
+//! ```yaml
+//! name: the crate opens with the crowbar
+//! start: cell_block          # room key, defaults to the blueprint entry room
+//! steps:
+//!   - send: look
+//!     expect: ["cell block"]   # substrings of the output (case-insensitive)
+//!   - send: take crowbar
+//!     has_item: [crowbar]      # item keys in the inventory
+//!   - send: go north
+//!     room: corridor           # room key the player must be in
+//!     expect_not: ["locked"]
+//! ```
+
+use crate::commands::CmdCtx;
+use crate::error::{AppResult, DomainError, InfraError};
+use crate::lua::LuaPool;
+use crate::models::realm::RealmKind;
+use crate::net::output::{OutEvent, OutFrame, OutputHandle};
+use crate::process_command;
+use crate::renderer::strip_ansi;
+use crate::state::registry::Registry;
+use crate::state::session::{Protocol, Session};
+use crate::util::list_yaml_files_guarded;
+use parking_lot::RwLock;
+use serde::Deserialize;
+use std::fs;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+use tokio::sync::mpsc;
+
+/// Directory inside a blueprint directory that holds the scenarios
+pub const SCENARIO_DIR: &str = "tests";
+
+/// Output frames buffered per step; well above what a single command produces
+const OUTPUT_BUFFER: usize = 1024;
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct ScenarioYaml {
+    pub name: String,
+    /// Room key to start in, defaults to the entry room
+    #[serde(default)]
+    pub start: Option<String>,
+    pub steps: Vec<StepYaml>,
+}
+
+#[derive(Debug, Clone, Deserialize)]
+#[serde(deny_unknown_fields)]
+pub struct StepYaml {
+    pub send: String,
+    #[serde(default)]
+    pub expect: Vec<String>,
+    #[serde(default)]
+    pub expect_not: Vec<String>,
+    #[serde(default)]
+    pub room: Option<String>,
+    #[serde(default)]
+    pub has_item: Vec<String>,
+}
+
+/// Outcome of a single scenario
+#[derive(Debug)]
+pub struct ScenarioReport {
+    pub name: String,
+    pub file: PathBuf,
+    /// First failing step, if any
+    pub failure: Option<String>,
+}
+
+impl ScenarioReport {
+    pub fn passed(&self) -> bool {
+        self.failure.is_none()
+    }
+}
+
+/// Reads all scenarios from the `tests/` directory of a blueprint directory. A missing directory
+/// means no scenarios.
+pub fn load_scenarios(bp_dir: &Path) -> AppResult<Vec<(PathBuf, ScenarioYaml)>> {
+    let dir = bp_dir.join(SCENARIO_DIR);
+    if !dir.is_dir() {
+        return Ok(Vec::new());
+    }
+
+    let mut files = list_yaml_files_guarded(&dir)?;
+    files.sort();
+
+    let mut out = Vec::with_capacity(files.len());
+    for path in files {
+        let text = fs::read_to_string(&path).map_err(InfraError::from)?;
+        let scenario: ScenarioYaml = serde_yaml::from_str(&text)?;
+        validate_scenario(&scenario).map_err(|message| DomainError::Validation {
+            field: "scenario",
+            message: format!("{}: {}", path.display(), message),
+        })?;
+        out.push((path, scenario));
+    }
+
+    Ok(out)
+}
+
+fn validate_scenario(s: &ScenarioYaml) -> Result<(), String> {
+    if s.name.trim().is_empty() {
+        return Err("name cannot be empty".into());
+    }
+    if s.steps.is_empty() {
+        return Err("needs at least one step".into());
+    }
+    if let Some(i) = s.steps.iter().position(|st| st.send.trim().is_empty()) {
+        return Err(format!("step {} has an empty `send`", i + 1));
+    }
+    Ok(())
+}
+
+/// Runs all scenarios against the blueprint `bp_key`
+pub async fn run_scenarios(
+    registry: Arc<Registry>,
+    lua_tx: LuaPool,
+    bp_key: &str,
+    scenarios: &[(PathBuf, ScenarioYaml)],
+) -> AppResult<Vec<ScenarioReport>> {
+    let mut reports = Vec::with_capacity(scenarios.len());
+    for (file, scenario) in scenarios {
+        let failure = run_scenario(registry.clone(), lua_tx.clone(), bp_key, scenario).await?;
+        reports.push(ScenarioReport {
+            name: scenario.name.clone(),
+            file: file.clone(),
+            failure,
+        });
+    }
+    Ok(reports)
+}
+
+/// Runs a single scenario in its own realm. Returns the first failure, or an error when the
+/// scenario could not be set up at all.
+async fn run_scenario(
+    registry: Arc<Registry>,
+    lua_tx: LuaPool,
+    bp_key: &str,
+    scenario: &ScenarioYaml,
+) -> AppResult<Option<String>> {
+    let services = registry.services.clone();
+    let bp = services.blueprint.get_by_key(bp_key).await?;
+
+    let realm = services
+        .realm
+        .create_persistent_realm(bp.id, format!("test: {}", scenario.name), RealmKind::Draft)
+        .await?;
+    let tester = match services
+        .playtest
+        .create_tester(&format!("test~{}", uuid::Uuid::new_v4().simple()))
+        .await
+    {
+        Ok(tester) => tester,
+        Err(e) => {
+            services.realm.delete_realm(realm.id).await?;
+            return Err(e);
+        }
+    };
+
+    let sess = Arc::new(RwLock::new(Session::new(Protocol::Telnet)));
+    let (tx, mut rx) = mpsc::channel(OUTPUT_BUFFER);
+    let ctx = Arc::new(CmdCtx {
+        output: OutputHandle::new(tx, sess.clone()),
+        registry: registry.clone(),
+        lua_tx,
+        sess: sess.clone(),
+    });
+
+    let result = async {
+        let room_id = match &scenario.start {
+            Some(key) => services
+                .room
+                .get_room_id_by_key(realm.id, key)
+                .await?
+                .ok_or_else(|| DomainError::NotFound(format!("start room '{}'", key)))?,
+            None => bp.entry_room_id,
+        };
+
+        let cursor = services.room.create_cursor(realm.id, room_id, tester.id).await?;
+        sess.write().login(
+            (*cursor.account).clone(),
+            (*cursor.realm).clone(),
+            (*cursor.room).clone(),
+        );
+        services.room.enter_room(ctx.clone(), &cursor).await?;
+        drain(&mut rx);
+
+        for (i, step) in scenario.steps.iter().enumerate() {
+            let _ = process_command(&step.send, ctx.clone()).await;
+            let output = drain(&mut rx);
+
+            if let Some(msg) = check_output(step, &output) {
+                return Ok(Some(format!("step {} `{}`: {}", i + 1, step.send, msg)));
+            }
+
+            let cursor = ctx.cursor()?;
+            if let Some(room) = &step.room
+                && &cursor.room.blueprint.key != room
+            {
+                return Ok(Some(format!(
+                    "step {} `{}`: expected room '{}', got '{}'",
+                    i + 1,
+                    step.send,
+                    room,
+                    cursor.room.blueprint.key
+                )));
+            }
+            for item in &step.has_item {
+                if !services.inventory.has_item_by_key(realm.id, tester.id, item).await? {
+                    return Ok(Some(format!(
+                        "step {} `{}`: expected '{}' in the inventory",
+                        i + 1,
+                        step.send,
+                        item
+                    )));
+                }
+            }
+        }
+
+        Ok(None)
+    }
+    .await;
+
+    // Always clean up, also when the scenario itself errored
+    services.realm.delete_realm(realm.id).await?;
+    services.playtest.discard(tester.id).await?;
+
+    result
+}
+
+/// Collects the text output produced so far, without colors and with whitespace collapsed so
+/// expectations do not depend on line wrapping
+fn drain(rx: &mut mpsc::Receiver<OutEvent>) -> String {
+    let mut parts = Vec::new();
+    while let Ok(ev) = rx.try_recv() {
+        if let OutEvent::Frame(OutFrame::Line(s) | OutFrame::System(s) | OutFrame::RoomView { content: s }, _) = ev {
+            parts.push(strip_ansi(&s));
+        }
+    }
+    parts.join(" ").split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+fn check_output(step: &StepYaml, output: &str) -> Option<String> {
+    let lower = output.to_lowercase();
+    for want in &step.expect {
+        if !lower.contains(&collapse(want)) {
+            return Some(format!("expected output to contain '{}', got: {}", want, output));
+        }
+    }
+    for unwanted in &step.expect_not {
+        if lower.contains(&collapse(unwanted)) {
+            return Some(format!("expected output not to contain '{}'", unwanted));
+        }
+    }
+    None
+}
+
+fn collapse(s: &str) -> String {
+    s.to_lowercase().split_whitespace().collect::<Vec<_>>().join(" ")
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn step(yaml: &str) -> StepYaml {
+        serde_yaml::from_str(yaml).unwrap()
+    }
+
+    #[test]
+    fn t_parse_scenario() {
+        let s: ScenarioYaml = serde_yaml::from_str(
+            r#"
+name: open the crate
+start: cell_block
+steps:
+  - send: look
+    expect: ["Cell Block"]
+  - send: go north
+    room: corridor
+    has_item: [crowbar]
+"#,
+        )
+        .unwrap();
+        assert_eq!(s.start.as_deref(), Some("cell_block"));
+        assert_eq!(s.steps.len(), 2);
+        assert_eq!(s.steps[1].room.as_deref(), Some("corridor"));
+        assert!(validate_scenario(&s).is_ok());
+
+        let err = serde_yaml::from_str::<ScenarioYaml>("name: x\nsteps: []\nbogus: 1\n").unwrap_err();
+        assert!(err.to_string().contains("bogus"));
+    }
+
+    #[test]
+    fn t_validate_scenario() {
+        let s: ScenarioYaml = serde_yaml::from_str("name: x\nsteps: []\n").unwrap();
+        assert!(validate_scenario(&s).is_err());
+        let s: ScenarioYaml = serde_yaml::from_str("name: x\nsteps: [{send: ''}]\n").unwrap();
+        assert!(validate_scenario(&s).is_err());
+    }
+
+    #[test]
+    fn t_check_output() {
+        let out = "You are in the Cell Block. A crate stands here.";
+        assert!(check_output(&step("send: look\nexpect: ['cell  block']"), out).is_none());
+        assert!(check_output(&step("send: look\nexpect: ['corridor']"), out).is_some());
+        assert!(check_output(&step("send: look\nexpect_not: ['crate']"), out).is_some());
+    }
+
+    #[tokio::test]
+    async fn t_drain_strips_and_collapses() {
+        let (tx, mut rx) = mpsc::channel(4);
+        tx.send(OutEvent::Frame(
+            OutFrame::Line("\x1b[1mHello\x1b[0m\n  world".into()),
+            1,
+        ))
+        .await
+        .unwrap();
+        tx.send(OutEvent::Frame(OutFrame::Prompt("> ".into()), 2))
+            .await
+            .unwrap();
+        assert_eq!(drain(&mut rx), "Hello world");
+    }
+}
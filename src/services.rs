@@ -1,16 +1,52 @@
 mod account;
+mod account_deletion;
+mod ambience;
 mod auth;
 mod blueprint;
+mod clock;
+mod collaborator;
+mod content_filter;
+mod crafting;
+mod currency;
 mod error;
+mod feature;
 mod inventory;
+mod maintenance;
+mod moderation;
 mod navigator;
+mod player_export;
+mod playtest;
 mod realm;
+mod recording;
+mod review;
 mod room;
+mod schedule;
+mod submission;
+mod tutorial;
 
-pub use account::AccountService;
+pub use account::{AccountService, LoginSuccess};
+pub use account_deletion::AccountDeletionService;
+pub use ambience::AmbienceService;
 pub use blueprint::BlueprintService;
-pub use inventory::InventoryService;
+pub use clock::ClockService;
+pub use collaborator::CollaboratorService;
+pub use content_filter::{
+    CONTENT_FILTER_FEATURE, CallbackFilter, ContentFilter, ContentFilterService, FilterOutcome, WordlistFilter,
+};
+pub use crafting::{CraftOutcome, CraftingService};
+pub use currency::{CurrencyService, Deposit, coins_text};
+pub use feature::FeatureService;
+pub use inventory::{Durability, InventoryService, RepairOutcome, StashOutcome, describe_item};
+pub use maintenance::MaintenanceService;
+pub use moderation::ModerationService;
+pub use player_export::PlayerExportService;
+pub use playtest::PlaytestService;
 pub use realm::RealmService;
-pub use room::RoomService;
+pub use recording::RecordingService;
+pub use review::ReviewService;
+pub use room::{HintOutcome, RoomService, SearchOutcome};
+pub use schedule::ScheduleService;
+pub use submission::SubmissionService;
+pub use tutorial::TutorialService;
 
 pub use error::ServiceError;
@@ -1,22 +1,39 @@
-use crate::db::repo::AccountRepo;
-use crate::error::{AppResult, LoginError};
-use crate::models::account::Account;
+use crate::db::repo::{AccountRepo, LoginAttemptRepo};
+use crate::error::{AppResult, DomainError, LoginError};
+use crate::models::account::{Account, AccountRole, MAX_HEALTH};
+use crate::models::login::LoginThrottle;
+use crate::models::settings::PlayerSettings;
 use crate::models::types::AccountId;
 use argon2::Argon2;
-use password_hash::{PasswordHash, PasswordVerifier};
+use chrono::{DateTime, TimeDelta, Utc};
+use password_hash::rand_core::OsRng;
+use password_hash::{PasswordHash, PasswordHasher, PasswordVerifier, SaltString};
+use std::net::IpAddr;
 use std::sync::Arc;
 
 pub struct AccountService {
     repo: Arc<dyn AccountRepo>,
+    attempts: Arc<dyn LoginAttemptRepo>,
     argon: Argon2<'static>,
 }
 
+/// Failed logins are forgotten after this long
+const FAILURE_RETENTION: TimeDelta = TimeDelta::days(30);
+
+/// A successful login
+#[derive(Debug)]
+pub struct LoginSuccess {
+    pub account: Account,
+    /// Failed logins on the account since the previous successful one
+    pub failed_attempts: u32,
+}
+
 pub type LoginResult<T> = Result<T, LoginError>;
 
 impl AccountService {
-    pub fn new(repo: Arc<dyn AccountRepo>) -> Self {
+    pub fn new(repo: Arc<dyn AccountRepo>, attempts: Arc<dyn LoginAttemptRepo>) -> Self {
         let argon = Argon2::default();
-        Self { repo, argon }
+        Self { repo, attempts, argon }
     }
 
     pub async fn get_by_id(&self, account_id: AccountId) -> AppResult<Option<Account>> {
@@ -24,6 +41,19 @@ impl AccountService {
         Ok(account)
     }
 
+    /// Heals (positive) or damages (negative) the account, returns the new health
+    pub async fn adjust_health(&self, account_id: AccountId, delta: i32) -> AppResult<u32> {
+        Ok(self.repo.adjust_health(account_id, delta, MAX_HEALTH).await?)
+    }
+
+    pub async fn save_settings(&self, account_id: AccountId, settings: &PlayerSettings) -> AppResult<()> {
+        Ok(self.repo.save_settings(account_id, settings).await?)
+    }
+
+    pub async fn get_by_username(&self, username: &str) -> AppResult<Option<Account>> {
+        Ok(self.repo.get_by_username(username).await?)
+    }
+
     pub async fn exists(&self, username: &str) -> AppResult<bool> {
         Ok(self.repo.get_by_username(username).await?.is_some())
     }
@@ -32,38 +62,164 @@ impl AccountService {
         Ok(self.repo.get_by_email(email).await?.is_some())
     }
 
-    pub async fn login(&self, username: &str, password: &str) -> LoginResult<Account> {
-        // Validate username input
-        match Account::validate_username(username) {
-            Ok(account) => account,
-            Err(_) => return Err(LoginError::UserNotFound),
+    /// Creates a new account with a freshly hashed password
+    pub async fn create(&self, username: &str, email: &str, password: &str, role: AccountRole) -> AppResult<Account> {
+        Account::validate_username(username)?;
+        if password.is_empty() {
+            return Err(DomainError::Validation {
+                field: "password",
+                message: "cannot be empty".into(),
+            });
+        }
+        if self.exists(username).await? {
+            return Err(DomainError::Validation {
+                field: "username",
+                message: format!("'{}' is already taken", username),
+            });
+        }
+        if self.exists_email(email).await? {
+            return Err(DomainError::Validation {
+                field: "email",
+                message: format!("'{}' is already registered", email),
+            });
         }
 
-        let Some(account) = self
-            .repo
-            .get_by_username(username)
-            .await
-            .map_err(|_| LoginError::UserNotFound)?
-        else {
+        let salt = SaltString::generate(&mut OsRng);
+        let hash = self.argon.hash_password(password.as_bytes(), &salt)?.to_string();
+
+        let account = Account {
+            id: AccountId::new(),
+            username: username.trim().to_string(),
+            email: email.to_string(),
+            password_hash: hash,
+            role,
+            created_at: chrono::Utc::now(),
+            last_login: None,
+            locked_out: false,
+            show_motd: true,
+            current_realm_id: None,
+            current_room_id: None,
+            spawn_realm_id: None,
+            spawn_room_id: None,
+            health: MAX_HEALTH,
+            xp: 0,
+            coins: 0,
+            perception: 10,
+            delete_after: None,
+            locked_until: None,
+            tutorial_completed_at: None,
+            settings: PlayerSettings::default(),
+        };
+
+        Ok(self.repo.insert_account(account).await?)
+    }
+
+    /// Whether `password` is the account's password, for confirming destructive actions
+    pub fn verify_password(&self, account: &Account, password: &str) -> bool {
+        PasswordHash::new(&account.password_hash)
+            .is_ok_and(|hash| self.argon.verify_password(password.as_bytes(), &hash).is_ok())
+    }
+
+    /// Checks the credentials. Failures are recorded per account and IP address and answered with
+    /// a growing delay; too many lock the account for a while.
+    pub async fn login(
+        &self,
+        username: &str,
+        password: &str,
+        ip: Option<IpAddr>,
+        throttle: &LoginThrottle,
+    ) -> LoginResult<LoginSuccess> {
+        let now = Utc::now();
+        let window_start = now - throttle.window;
+
+        let ip_failures = match ip {
+            Some(ip) => self
+                .attempts
+                .failures_for_ip(ip, window_start)
+                .await
+                .map_err(internal)?,
+            None => 0,
+        };
+        if throttle.blocks_ip(ip_failures) {
+            tokio::time::sleep(throttle.delay(ip_failures)).await;
+            return Err(LoginError::TooManyAttempts);
+        }
+
+        let account = match Account::validate_username(username) {
+            Ok(()) => self.repo.get_by_username(username).await.map_err(internal)?,
+            Err(_) => None,
+        };
+        let Some(account) = account else {
+            self.attempts.record_failure(None, ip).await.map_err(internal)?;
+            tokio::time::sleep(throttle.delay(ip_failures + 1)).await;
             return Err(LoginError::UserNotFound);
         };
 
+        if let Some(until) = account.locked_until.filter(|until| *until > now) {
+            return Err(LoginError::LockedUntil(until));
+        }
+
         let parsed = PasswordHash::new(&account.password_hash)
             .map_err(|_| LoginError::InternalError("cannot generate password hash".into()))?;
         if self.argon.verify_password(password.as_bytes(), &parsed).is_err() {
-            return Err(LoginError::InvalidPassword);
+            return Err(self.fail(&account, ip, throttle, now).await);
         };
 
         if account.locked_out {
             return Err(LoginError::AccountLocked);
         }
 
+        let failed_attempts = self
+            .attempts
+            .failures_for_account(account.id, account.last_login.unwrap_or(DateTime::UNIX_EPOCH))
+            .await
+            .map_err(internal)?;
+
         // We are logged in. Update last login time
         self.repo
             .update_last_login(account.id)
             .await
             .map_err(|_| LoginError::InternalError("cannot update login timestamp".into()))?;
+        _ = self.attempts.prune(now - FAILURE_RETENTION).await;
 
-        Ok(account)
+        Ok(LoginSuccess {
+            account,
+            failed_attempts,
+        })
     }
+
+    /// Records a wrong password, locks the account when there were too many, and waits
+    async fn fail(
+        &self,
+        account: &Account,
+        ip: Option<IpAddr>,
+        throttle: &LoginThrottle,
+        now: DateTime<Utc>,
+    ) -> LoginError {
+        if let Err(e) = self.attempts.record_failure(Some(account.id), ip).await {
+            return internal(e);
+        }
+        // Failures before the last successful login do not count
+        let since = account
+            .last_login
+            .map_or(now - throttle.window, |last| last.max(now - throttle.window));
+        let failures = match self.attempts.failures_for_account(account.id, since).await {
+            Ok(n) => n,
+            Err(e) => return internal(e),
+        };
+
+        tokio::time::sleep(throttle.delay(failures)).await;
+        if throttle.locks_account(failures) {
+            let until = now + throttle.window;
+            if let Err(e) = self.attempts.lock_until(account.id, until).await {
+                return internal(e);
+            }
+            return LoginError::LockedUntil(until);
+        }
+        LoginError::InvalidPassword
+    }
+}
+
+fn internal(e: impl std::fmt::Display) -> LoginError {
+    LoginError::InternalError(e.to_string())
 }
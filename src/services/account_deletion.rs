@@ -0,0 +1,74 @@
+use crate::db::repo::{AccountDeletionRepo, AccountRepo};
+use crate::error::{AppResult, DomainError};
+use crate::models::types::AccountId;
+use chrono::{DateTime, TimeDelta, Utc};
+use std::sync::Arc;
+
+/// Deleting accounts: scheduled by the player with a cooldown, or forced by an admin. Purging
+/// removes the account with everything linked to it; what must stay (picked up loot, realms) is
+/// kept without the account.
+pub struct AccountDeletionService {
+    repo: Arc<dyn AccountDeletionRepo>,
+    accounts: Arc<dyn AccountRepo>,
+}
+
+impl AccountDeletionService {
+    pub fn new(repo: Arc<dyn AccountDeletionRepo>, accounts: Arc<dyn AccountRepo>) -> Self {
+        Self { repo, accounts }
+    }
+
+    /// When the account's deletion is carried out, None when none is pending
+    pub async fn pending(&self, account_id: AccountId) -> AppResult<Option<DateTime<Utc>>> {
+        let account = self
+            .accounts
+            .get_by_id(account_id)
+            .await?
+            .ok_or_else(|| DomainError::NotFound(format!("account {}", account_id)))?;
+        Ok(account.delete_after)
+    }
+
+    /// Schedules the account to be purged after `cooldown`. Builders have to hand over their
+    /// blueprints first.
+    pub async fn request(&self, account_id: AccountId, cooldown: TimeDelta) -> AppResult<DateTime<Utc>> {
+        let blueprints = self.repo.owned_blueprints(account_id).await?;
+        if !blueprints.is_empty() {
+            return Err(DomainError::Validation {
+                field: "account",
+                message: format!(
+                    "you still own blueprints ({}); ask an admin to take them over first",
+                    blueprints.join(", ")
+                ),
+            });
+        }
+
+        let at = Utc::now() + cooldown;
+        self.repo.set_delete_after(account_id, Some(at)).await?;
+        Ok(at)
+    }
+
+    /// Returns false when no deletion was pending
+    pub async fn cancel(&self, account_id: AccountId) -> AppResult<bool> {
+        if self.pending(account_id).await?.is_none() {
+            return Ok(false);
+        }
+        self.repo.set_delete_after(account_id, None).await?;
+        Ok(true)
+    }
+
+    /// Accounts whose cooldown has run out
+    pub async fn due(&self) -> AppResult<Vec<AccountId>> {
+        Ok(self.repo.due(Utc::now()).await?)
+    }
+
+    /// Removes the account right away. Its blueprints go to `heir`; without one the account may
+    /// not own any.
+    pub async fn purge(&self, account_id: AccountId, heir: Option<AccountId>) -> AppResult<()> {
+        if heir.is_none() && !self.repo.owned_blueprints(account_id).await?.is_empty() {
+            return Err(DomainError::Conflict(format!(
+                "account {} still owns blueprints",
+                account_id
+            )));
+        }
+        Ok(self.repo.purge(account_id, heir).await?)
+    }
+}
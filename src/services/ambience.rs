@@ -0,0 +1,75 @@
+use crate::db::repo::AmbienceRepo;
+use crate::error::AppResult;
+use crate::models::ambience::{AmbientEvent, AmbientStep, step_key};
+use crate::models::room::Kv;
+use crate::models::types::RealmId;
+use rand::Rng;
+use serde_json::Value;
+use std::sync::Arc;
+use std::time::Duration;
+
+/// Ambient events and the zone state they keep, per realm
+pub struct AmbienceService {
+    repo: Arc<dyn AmbienceRepo>,
+}
+
+impl AmbienceService {
+    pub fn new(repo: Arc<dyn AmbienceRepo>) -> Self {
+        Self { repo }
+    }
+
+    pub async fn events(&self, realm_id: RealmId) -> AppResult<Vec<AmbientEvent>> {
+        Ok(self.repo.events_for_realm(realm_id).await?)
+    }
+
+    pub async fn zone_state(&self, realm_id: RealmId) -> AppResult<Kv> {
+        Ok(self.repo.zone_kv(realm_id).await?)
+    }
+
+    pub async fn zone_value(&self, realm_id: RealmId, key: &str) -> AppResult<Option<Value>> {
+        Ok(self.zone_state(realm_id).await?.get(key).cloned())
+    }
+
+    pub async fn set_zone_state(&self, realm_id: RealmId, key: &str, value: &Value) -> AppResult<()> {
+        Ok(self.repo.set_zone_kv(realm_id, key, value).await?)
+    }
+
+    /// Sets zone state that reads as unset again after `ttl`
+    pub async fn set_zone_state_for(
+        &self,
+        realm_id: RealmId,
+        key: &str,
+        value: &Value,
+        ttl: Duration,
+    ) -> AppResult<()> {
+        Ok(self.repo.set_zone_kv_expiring(realm_id, key, value, ttl).await?)
+    }
+
+    /// Deletes expired zone state, returns how many entries went
+    pub async fn sweep_expired(&self) -> AppResult<u64> {
+        Ok(self.repo.delete_expired_zone_kv().await?)
+    }
+
+    /// Moves the event on to its next step and writes the step's state. Returns the step, or None
+    /// when the event has no steps.
+    pub async fn advance<'a>(&self, realm_id: RealmId, event: &'a AmbientEvent) -> AppResult<Option<&'a AmbientStep>> {
+        if event.steps.is_empty() {
+            return Ok(None);
+        }
+
+        let key = step_key(&event.id);
+        let current = self
+            .zone_value(realm_id, &key)
+            .await?
+            .and_then(|v| v.as_u64())
+            .map(|v| v as usize);
+        let idx = event.next_step(current, |n| rand::rng().random_range(0..n));
+        let step = &event.steps[idx];
+
+        for (k, v) in &step.set {
+            self.repo.set_zone_kv(realm_id, k, v).await?;
+        }
+        self.repo.set_zone_kv(realm_id, &key, &Value::from(idx)).await?;
+        Ok(Some(step))
+    }
+}
@@ -1,10 +1,21 @@
 #![allow(unused)]
 
 use crate::db::repo::{BlueprintAndRoomKey, RoomRepo};
-use crate::error::AppResult;
+use crate::error::{AppResult, DomainError};
+use crate::import_blueprint::{
+    compile_lua_chunk, validate_exit, validate_object_text, validate_room_body, validate_room_title,
+};
+use crate::lua::lint::{ChunkKind, LintWarning, lint_chunk};
 use crate::models::blueprint::Blueprint;
-use crate::models::room::{BlueprintExit, BlueprintObject, BlueprintRoom, Kv, RoomScripts, RoomView};
+use crate::models::room::{
+    BlueprintExit, BlueprintObject, BlueprintRoom, BuilderNote, Kv, ObjectFlags, RoomScripts, RoomView,
+};
+use crate::models::room_graph::{GraphFormat, RoomGraph};
 use crate::models::types::{AccountId, BlueprintId, RoomId};
+use chrono::Utc;
+use mlua::Lua;
+use std::collections::HashSet;
+use std::path::{Path, PathBuf};
 use std::sync::Arc;
 
 pub struct BlueprintService {
@@ -21,6 +32,11 @@ impl BlueprintService {
         Ok(blueprint)
     }
 
+    pub async fn get_by_id(&self, bp_id: BlueprintId) -> AppResult<Blueprint> {
+        let blueprint = self.repo.blueprint_by_id(bp_id).await?;
+        Ok(blueprint)
+    }
+
     pub async fn room_by_id(&self, bp_id: BlueprintId, room_id: RoomId) -> AppResult<BlueprintRoom> {
         let bp_room = self.repo.room_by_id(bp_id, room_id).await?;
         Ok(bp_room)
@@ -46,6 +62,37 @@ impl BlueprintService {
         Ok(scripts)
     }
 
+    /// Builder notes of the whole blueprint, or of one of its rooms
+    pub async fn builder_notes(&self, bp_id: BlueprintId, room_id: Option<RoomId>) -> AppResult<Vec<BuilderNote>> {
+        let notes = self.repo.builder_notes(bp_id, room_id).await?;
+        Ok(notes)
+    }
+
+    pub async fn room_graph(&self, bp_id: BlueprintId) -> AppResult<RoomGraph> {
+        let graph = self.repo.room_graph(bp_id).await?;
+        Ok(graph)
+    }
+
+    /// Writes the room graph of the blueprint to a new file in `dir`
+    pub async fn room_graph_to_file(&self, bp: &Blueprint, format: GraphFormat, dir: &Path) -> AppResult<PathBuf> {
+        let graph = self.room_graph(bp.id).await?;
+
+        std::fs::create_dir_all(dir)?;
+        let path = dir.join(format!(
+            "{}-graph-{}.{}",
+            bp.key,
+            Utc::now().format("%Y%m%d-%H%M%S"),
+            format.extension()
+        ));
+        std::fs::write(&path, graph.render(&bp.key, format))?;
+        Ok(path)
+    }
+
+    pub async fn lua_module(&self, bp_id: BlueprintId, name: &str) -> AppResult<Option<String>> {
+        let source = self.repo.lua_module(bp_id, name).await?;
+        Ok(source)
+    }
+
     pub async fn room_kv(&self, _bp_id: BlueprintId, room_id: RoomId) -> AppResult<Kv> {
         let kv_pairs = self.repo.room_kv(room_id).await?;
         Ok(kv_pairs)
@@ -80,15 +127,200 @@ impl BlueprintService {
         Ok(res)
     }
 
+    /// Copies `source` with all its rooms, objects, exits, items, scripts and modules into a new draft
+    /// blueprint `new_key` owned by `owner_id`.
+    pub async fn fork(&self, source: &Blueprint, new_key: &str, owner_id: AccountId) -> AppResult<Blueprint> {
+        validate_key(new_key)?;
+        match self.repo.fork_blueprint(source.id, new_key, owner_id).await? {
+            Some(bp_id) => Ok(self.repo.blueprint_by_id(bp_id).await?),
+            None => Err(DomainError::Conflict(format!(
+                "a blueprint '{}' already exists",
+                new_key
+            ))),
+        }
+    }
+
     /// Creates a new room in a blueprint.
     pub async fn new_room(&self, key: &BlueprintAndRoomKey, title: &str, body: &str) -> AppResult<bool> {
         let res = self.repo.insert_room(key, title, body).await?;
         Ok(res)
     }
 
-    /// Submits a blueprint for review.
-    pub async fn submit(&self, bp: &str) -> AppResult<bool> {
-        let res = self.repo.submit(bp).await?;
+    /// Gives a room a new title and description, after the same checks the importer does
+    pub async fn update_room_text(&self, room: &BlueprintRoom, title: &str, body: &str) -> AppResult<bool> {
+        validate_room_title(title)?;
+        let objects = self.repo.room_objects(room.id).await?;
+        let object_ids: HashSet<&str> = objects.iter().map(|o| o.name.as_str()).collect();
+        validate_room_body(body, &object_ids)?;
+
+        let res = self.repo.update_room_text(room.id, title, body).await?;
+        Ok(res)
+    }
+
+    /// The object with key `key` in the room, as it is in the blueprint
+    pub async fn room_object(&self, room_id: RoomId, key: &str) -> AppResult<Option<BlueprintObject>> {
+        let objects = self.repo.room_objects(room_id).await?;
+        Ok(objects.into_iter().find(|o| o.name == key))
+    }
+
+    /// Adds an object to the room. Returns false when the room already has one called `key`.
+    pub async fn add_object(&self, room: &BlueprintRoom, key: &str, short: &str) -> AppResult<bool> {
+        if key.trim().is_empty() {
+            return Err(DomainError::Validation {
+                field: "object",
+                message: "object with empty id".into(),
+            });
+        }
+        validate_object_text(key, short, short)?;
+
+        let res = self.repo.insert_object(room.id, key, short).await?;
         Ok(res)
     }
+
+    pub async fn update_object_text(
+        &self,
+        obj: &BlueprintObject,
+        short: &str,
+        description: &str,
+        examine: Option<&str>,
+    ) -> AppResult<bool> {
+        validate_object_text(&obj.name, short, description)?;
+
+        let res = self
+            .repo
+            .update_object_text(obj.id, short, description, examine)
+            .await?;
+        Ok(res)
+    }
+
+    pub async fn set_object_flag(&self, obj: &BlueprintObject, flag: &str, on: bool) -> AppResult<bool> {
+        if !ObjectFlags::NAMES.contains(&flag) {
+            return Err(DomainError::Validation {
+                field: "object.flags",
+                message: format!("unknown flag '{}', try one of {}", flag, ObjectFlags::NAMES.join(", ")),
+            });
+        }
+
+        let res = self.repo.set_object_flag(obj.id, flag, on).await?;
+        Ok(res)
+    }
+
+    /// Replaces the nouns of an object. Nouns are unique within a room.
+    pub async fn set_object_nouns(
+        &self,
+        room: &BlueprintRoom,
+        obj: &BlueprintObject,
+        nouns: &[String],
+    ) -> AppResult<()> {
+        if nouns.is_empty() || nouns.iter().any(|n| n.trim().is_empty()) {
+            return Err(DomainError::Validation {
+                field: "object.nouns",
+                message: format!("object '{}' needs nouns that are not empty", obj.name),
+            });
+        }
+        let objects = self.repo.room_objects(room.id).await?;
+        for other in objects.iter().filter(|o| o.id != obj.id) {
+            if let Some(noun) = nouns.iter().find(|n| other.nouns.contains(n)) {
+                return Err(DomainError::Validation {
+                    field: "object.nouns",
+                    message: format!("noun '{}' is already used by object '{}'", noun, other.name),
+                });
+            }
+        }
+
+        self.repo.set_object_nouns(room.id, obj.id, nouns).await?;
+        Ok(())
+    }
+
+    /// Sets one of the Lua scripts of an object, or removes it when `code` is empty. The script
+    /// must compile, like at import; the lint warnings about it are returned.
+    pub async fn set_object_script(
+        &self,
+        room: &BlueprintRoom,
+        obj: &BlueprintObject,
+        kind: &ChunkKind,
+        code: &str,
+    ) -> AppResult<Vec<LintWarning>> {
+        if code.trim().is_empty() {
+            self.repo.set_object_script(obj.id, kind, None).await?;
+            return Ok(Vec::new());
+        }
+
+        let lua = Lua::new();
+        let name = format!("room:{}:object:{}:{}", room.key, obj.name, kind.as_str());
+        compile_lua_chunk(&lua, &name, code)?;
+        let warnings = lint_chunk(&lua, code, kind, room.api_version);
+
+        self.repo.set_object_script(obj.id, kind, Some(code)).await?;
+        Ok(warnings)
+    }
+
+    /// Removes an object from the room, unless the room description still shows it
+    pub async fn remove_object(&self, room: &BlueprintRoom, obj: &BlueprintObject) -> AppResult<bool> {
+        let objects = self.repo.room_objects(room.id).await?;
+        let remaining: HashSet<&str> = objects
+            .iter()
+            .filter(|o| o.id != obj.id)
+            .map(|o| o.name.as_str())
+            .collect();
+        validate_room_body(&room.body, &remaining)?;
+
+        let res = self.repo.delete_object(obj.id).await?;
+        Ok(res)
+    }
+
+    /// Adds (or redirects) the exit `dir` of a room to another room of the same blueprint, after
+    /// the same checks the importer does
+    pub async fn add_room_exit(
+        &self,
+        bp: &Blueprint,
+        from: &BlueprintRoom,
+        dir: &str,
+        to_room_key: &str,
+    ) -> AppResult<bool> {
+        validate_exit(dir, to_room_key)?;
+        if self.repo.get_room_id_by_key(bp.id, to_room_key).await?.is_none() {
+            return Err(DomainError::NotFound(format!("room '{}'", to_room_key)));
+        }
+
+        let from_key = BlueprintAndRoomKey::new(&bp.key, &from.key);
+        let to_key = BlueprintAndRoomKey::new(&bp.key, to_room_key);
+        let res = self.repo.add_exit(&from_key, dir, &to_key).await?;
+        Ok(res)
+    }
+}
+
+/// Blueprint keys are short lowercase identifiers, they end up in room keys and realm keys
+fn validate_key(key: &str) -> AppResult<()> {
+    let valid = !key.is_empty()
+        && key.len() <= 64
+        && key.starts_with(|c: char| c.is_ascii_lowercase())
+        && key
+            .chars()
+            .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '_' || c == '-');
+    if !valid {
+        return Err(DomainError::Validation {
+            field: "key",
+            message: format!(
+                "'{}' is not a valid key, use lowercase letters, digits, '_' and '-'",
+                key
+            ),
+        });
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn t_validate_key() {
+        assert!(validate_key("hub").is_ok());
+        assert!(validate_key("my_hub-2").is_ok());
+        assert!(validate_key("").is_err());
+        assert!(validate_key("2hub").is_err());
+        assert!(validate_key("hub:room").is_err());
+        assert!(validate_key(&"a".repeat(65)).is_err());
+    }
 }
@@ -0,0 +1,33 @@
+use crate::config::ClockConfig;
+use crate::db::repo::ClockRepo;
+use crate::error::AppResult;
+use crate::state::clock;
+use chrono::{DateTime, Utc};
+use std::sync::Arc;
+
+/// Keeps the in-world clock (see `state::clock`) across restarts
+pub struct ClockService {
+    repo: Arc<dyn ClockRepo>,
+}
+
+impl ClockService {
+    pub fn new(repo: Arc<dyn ClockRepo>) -> Self {
+        Self { repo }
+    }
+
+    /// Starts the clock at the saved world time, or at the configured epoch when it was never
+    /// saved. Returns the world time it starts at.
+    pub async fn resume(&self, cfg: &ClockConfig) -> AppResult<DateTime<Utc>> {
+        let world = match self.repo.load_world_time().await? {
+            Some(saved) => saved,
+            None => cfg.epoch().unwrap_or_else(Utc::now),
+        };
+        clock::start(world, cfg.speed);
+        Ok(world)
+    }
+
+    /// Stores the current world time
+    pub async fn save(&self) -> AppResult<()> {
+        Ok(self.repo.save_world_time(clock::world_time()).await?)
+    }
+}
@@ -0,0 +1,185 @@
+use crate::db::repo::{AccountRepo, CollaboratorRepo, RoomRepo};
+use crate::error::{AppResult, DomainError};
+use crate::models::account::Account;
+use crate::models::blueprint::{Blueprint, BlueprintStatus};
+use crate::models::collaborator::{BlueprintAccess, BlueprintRight, BlueprintRole, Collaborator};
+use std::sync::Arc;
+
+/// Blueprint owners, editors and testers, and what each of them may do
+pub struct CollaboratorService {
+    repo: Arc<dyn CollaboratorRepo>,
+    rooms: Arc<dyn RoomRepo>,
+    accounts: Arc<dyn AccountRepo>,
+}
+
+impl CollaboratorService {
+    pub fn new(repo: Arc<dyn CollaboratorRepo>, rooms: Arc<dyn RoomRepo>, accounts: Arc<dyn AccountRepo>) -> Self {
+        Self { repo, rooms, accounts }
+    }
+
+    /// What `account` may do with `bp`
+    pub async fn access(&self, account: &Account, bp: &Blueprint) -> AppResult<BlueprintAccess> {
+        let admin = account.is_admin();
+        if bp.owner_id == account.id {
+            return Ok(BlueprintAccess {
+                role: Some(BlueprintRole::Owner),
+                rights: BlueprintRole::Owner.default_rights(),
+                admin,
+            });
+        }
+
+        Ok(match self.repo.get(bp.id, account.id).await? {
+            Some(c) => BlueprintAccess {
+                role: Some(c.role),
+                rights: c.rights,
+                admin,
+            },
+            None => BlueprintAccess {
+                role: None,
+                rights: vec![],
+                admin,
+            },
+        })
+    }
+
+    /// The blueprint, when `account` has `right` on it
+    pub async fn require(&self, account: &Account, bp_key: &str, right: BlueprintRight) -> AppResult<Blueprint> {
+        let bp = self.blueprint(bp_key).await?;
+        self.require_for(account, &bp, right).await?;
+        Ok(bp)
+    }
+
+    pub async fn require_for(&self, account: &Account, bp: &Blueprint, right: BlueprintRight) -> AppResult<()> {
+        if !self.access(account, bp).await?.allows(right) {
+            return Err(DomainError::Validation {
+                field: "blueprint",
+                message: format!("you may not {} '{}'", right.describe(), bp.key),
+            });
+        }
+        Ok(())
+    }
+
+    /// The blueprint when `account` is its owner or a collaborator on it
+    pub async fn require_member(&self, account: &Account, bp_key: &str) -> AppResult<(Blueprint, BlueprintAccess)> {
+        let bp = self.blueprint(bp_key).await?;
+        let access = self.access(account, &bp).await?;
+        if access.role.is_none() && !access.admin {
+            return Err(DomainError::Validation {
+                field: "blueprint",
+                message: format!("you are not working on '{}'", bp.key),
+            });
+        }
+        Ok((bp, access))
+    }
+
+    /// A blueprint `account` may fork: published ones are shared templates, others only their
+    /// owner and collaborators can copy
+    pub async fn require_forkable(&self, account: &Account, bp_key: &str) -> AppResult<Blueprint> {
+        let bp = self.blueprint(bp_key).await?;
+        if bp.status != BlueprintStatus::Published {
+            let access = self.access(account, &bp).await?;
+            if access.role.is_none() && !access.admin {
+                return Err(DomainError::Validation {
+                    field: "blueprint",
+                    message: format!("'{}' is not published, only its collaborators can fork it", bp.key),
+                });
+            }
+        }
+        Ok(bp)
+    }
+
+    /// Collaborators of a blueprint `account` works on
+    pub async fn list(&self, account: &Account, bp_key: &str) -> AppResult<(Blueprint, Vec<Collaborator>)> {
+        let (bp, _) = self.require_member(account, bp_key).await?;
+        let collaborators = self.repo.list(bp.id).await?;
+        Ok((bp, collaborators))
+    }
+
+    /// Adds `username` to the blueprint with the default rights of `role`, or gives an existing
+    /// collaborator the new role and its defaults
+    pub async fn add(
+        &self,
+        manager: &Account,
+        bp_key: &str,
+        username: &str,
+        role: BlueprintRole,
+    ) -> AppResult<Collaborator> {
+        let bp = self.managed(manager, bp_key).await?;
+        let Some(account) = self.accounts.get_by_username(username).await? else {
+            return Err(DomainError::NotFound(format!("player '{}'", username)));
+        };
+        if account.id == bp.owner_id {
+            return Err(DomainError::Validation {
+                field: "player",
+                message: format!("{} owns '{}'", account.username, bp.key),
+            });
+        }
+
+        let rights = role.default_rights();
+        self.repo.upsert(bp.id, account.id, role, &rights, manager.id).await?;
+        Ok(Collaborator {
+            account_id: account.id,
+            username: account.username,
+            role,
+            rights,
+        })
+    }
+
+    /// Gives or takes one right, returns the collaborator's rights afterwards
+    pub async fn set_right(
+        &self,
+        manager: &Account,
+        bp_key: &str,
+        username: &str,
+        right: BlueprintRight,
+        granted: bool,
+    ) -> AppResult<Vec<BlueprintRight>> {
+        let bp = self.managed(manager, bp_key).await?;
+        let collaborator = self.collaborator(&bp, username).await?;
+
+        let mut rights = collaborator.rights;
+        rights.retain(|r| *r != right);
+        if granted {
+            rights.push(right);
+            rights.sort();
+        }
+        self.repo.set_rights(bp.id, collaborator.account_id, &rights).await?;
+        Ok(rights)
+    }
+
+    pub async fn remove(&self, manager: &Account, bp_key: &str, username: &str) -> AppResult<()> {
+        let bp = self.managed(manager, bp_key).await?;
+        let collaborator = self.collaborator(&bp, username).await?;
+        self.repo.remove(bp.id, collaborator.account_id).await?;
+        Ok(())
+    }
+
+    async fn blueprint(&self, bp_key: &str) -> AppResult<Blueprint> {
+        let Ok(bp) = self.rooms.blueprint_by_key(bp_key).await else {
+            return Err(DomainError::NotFound(format!("blueprint '{}'", bp_key)));
+        };
+        Ok(bp)
+    }
+
+    async fn managed(&self, manager: &Account, bp_key: &str) -> AppResult<Blueprint> {
+        let bp = self.blueprint(bp_key).await?;
+        if !self.access(manager, &bp).await?.can_manage() {
+            return Err(DomainError::Validation {
+                field: "blueprint",
+                message: format!("only the owner manages the collaborators of '{}'", bp.key),
+            });
+        }
+        Ok(bp)
+    }
+
+    async fn collaborator(&self, bp: &Blueprint, username: &str) -> AppResult<Collaborator> {
+        let found = match self.accounts.get_by_username(username).await? {
+            Some(account) => self.repo.get(bp.id, account.id).await?,
+            None => None,
+        };
+        found.ok_or_else(|| DomainError::Validation {
+            field: "player",
+            message: format!("{} is not a collaborator on '{}'", username, bp.key),
+        })
+    }
+}
@@ -0,0 +1,294 @@
+use crate::config::{Config, FilterAction};
+use crate::error::AppResult;
+use crate::models::account::Account;
+use crate::models::types::RealmId;
+use crate::services::FeatureService;
+use parking_lot::RwLock;
+use std::collections::HashSet;
+use std::sync::Arc;
+
+/// Feature flag that switches the content filter on for a realm (or blueprint, or globally)
+pub const CONTENT_FILTER_FEATURE: &str = "content_filter";
+
+/// Result of running text through a filter
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum FilterOutcome {
+    /// Nothing to filter
+    Clean,
+    /// Text may be sent in this altered form
+    Masked(String),
+    /// Text may not be sent at all
+    Blocked(String),
+}
+
+/// A single content filter. Filters run in registration order; masked text is passed on to the
+/// next filter, a block stops the chain.
+pub trait ContentFilter: Send + Sync {
+    fn name(&self) -> &str;
+    fn check(&self, text: &str) -> FilterOutcome;
+}
+
+/// Wraps a callback so it can be registered as a filter
+pub struct CallbackFilter<F> {
+    name: String,
+    f: F,
+}
+
+impl<F> CallbackFilter<F>
+where
+    F: Fn(&str) -> FilterOutcome + Send + Sync,
+{
+    pub fn new(name: impl Into<String>, f: F) -> Self {
+        Self { name: name.into(), f }
+    }
+}
+
+impl<F> ContentFilter for CallbackFilter<F>
+where
+    F: Fn(&str) -> FilterOutcome + Send + Sync,
+{
+    fn name(&self) -> &str {
+        &self.name
+    }
+
+    fn check(&self, text: &str) -> FilterOutcome {
+        (self.f)(text)
+    }
+}
+
+/// Matches whole words, case-insensitive, against a list
+pub struct WordlistFilter {
+    words: HashSet<String>,
+    action: FilterAction,
+}
+
+impl WordlistFilter {
+    pub fn new<I, S>(words: I, action: FilterAction) -> Self
+    where
+        I: IntoIterator<Item = S>,
+        S: AsRef<str>,
+    {
+        let words = words
+            .into_iter()
+            .map(|w| w.as_ref().trim().to_lowercase())
+            .filter(|w| !w.is_empty())
+            .collect();
+        Self { words, action }
+    }
+
+    /// Builds the filter from the `[filter]` config section. An unreadable wordlist file is
+    /// logged and skipped, so a bad reload does not disable the inline words.
+    pub fn from_config(config: &Config) -> Self {
+        let mut words = config.filter.words.clone();
+        if let Some(path) = &config.filter.wordlist_path {
+            match std::fs::read_to_string(path) {
+                Ok(text) => words.extend(
+                    text.lines()
+                        .map(str::trim)
+                        .filter(|l| !l.is_empty() && !l.starts_with('#'))
+                        .map(String::from),
+                ),
+                Err(e) => tracing::warn!(path = %path.display(), error = %e, "cannot read filter wordlist"),
+            }
+        }
+        Self::new(words, config.filter.action)
+    }
+}
+
+impl ContentFilter for WordlistFilter {
+    fn name(&self) -> &str {
+        "wordlist"
+    }
+
+    fn check(&self, text: &str) -> FilterOutcome {
+        if self.words.is_empty() {
+            return FilterOutcome::Clean;
+        }
+
+        let mut out = String::with_capacity(text.len());
+        let mut word = String::new();
+        let mut hit = false;
+
+        let mut flush = |word: &mut String, out: &mut String| {
+            if self.words.contains(&word.to_lowercase()) {
+                hit = true;
+                out.extend(word.chars().map(|_| '*'));
+            } else {
+                out.push_str(word);
+            }
+            word.clear();
+        };
+
+        for c in text.chars() {
+            if c.is_alphanumeric() {
+                word.push(c);
+            } else {
+                flush(&mut word, &mut out);
+                out.push(c);
+            }
+        }
+        flush(&mut word, &mut out);
+
+        match (hit, self.action) {
+            (false, _) => FilterOutcome::Clean,
+            (true, FilterAction::Mask) => FilterOutcome::Masked(out),
+            (true, FilterAction::Block) => FilterOutcome::Blocked("language".into()),
+        }
+    }
+}
+
+/// Runs player text (say, and any later chat channels) through the registered filters.
+///
+/// The filter only runs in realms where the `content_filter` feature is enabled, and never for
+/// moderators. Everything that is filtered is logged with its original text.
+pub struct ContentFilterService {
+    features: Arc<FeatureService>,
+    config: Arc<RwLock<Arc<Config>>>,
+    /// Wordlist built from the config it belongs to, rebuilt when the config is reloaded
+    wordlist: RwLock<Option<(Arc<Config>, Arc<WordlistFilter>)>>,
+    /// Filters registered on top of the wordlist
+    extra: RwLock<Vec<Arc<dyn ContentFilter>>>,
+}
+
+impl ContentFilterService {
+    pub fn new(features: Arc<FeatureService>, config: Arc<RwLock<Arc<Config>>>) -> Self {
+        Self {
+            features,
+            config,
+            wordlist: RwLock::new(None),
+            extra: RwLock::new(Vec::new()),
+        }
+    }
+
+    /// Adds a filter that runs after the wordlist
+    pub fn register(&self, filter: Arc<dyn ContentFilter>) {
+        self.extra.write().push(filter);
+    }
+
+    /// Filters text that `account` sends on `channel` in the given realm
+    pub async fn apply(
+        &self,
+        account: &Account,
+        realm_id: RealmId,
+        channel: &str,
+        text: &str,
+    ) -> AppResult<FilterOutcome> {
+        if account.is_moderator() || !self.features.enabled(CONTENT_FILTER_FEATURE, realm_id).await? {
+            return Ok(FilterOutcome::Clean);
+        }
+
+        let mut filters: Vec<Arc<dyn ContentFilter>> = vec![self.wordlist()];
+        filters.extend(self.extra.read().iter().cloned());
+
+        let (outcome, filter) = run_chain(&filters, text);
+        match &outcome {
+            FilterOutcome::Clean => {}
+            FilterOutcome::Masked(masked) => tracing::info!(
+                target: "port4k::filter",
+                account = %account.username,
+                realm = %realm_id,
+                channel,
+                filter,
+                original = text,
+                masked = masked.as_str(),
+                "masked player text"
+            ),
+            FilterOutcome::Blocked(reason) => tracing::info!(
+                target: "port4k::filter",
+                account = %account.username,
+                realm = %realm_id,
+                channel,
+                filter,
+                original = text,
+                reason = reason.as_str(),
+                "blocked player text"
+            ),
+        }
+
+        Ok(outcome)
+    }
+
+    fn wordlist(&self) -> Arc<WordlistFilter> {
+        let config = self.config.read().clone();
+        if let Some((built_for, filter)) = self.wordlist.read().as_ref()
+            && Arc::ptr_eq(built_for, &config)
+        {
+            return filter.clone();
+        }
+
+        let filter = Arc::new(WordlistFilter::from_config(&config));
+        *self.wordlist.write() = Some((config, filter.clone()));
+        filter
+    }
+}
+
+/// Runs the filters in order. Returns the combined outcome and the name of the last filter that
+/// changed anything.
+fn run_chain(filters: &[Arc<dyn ContentFilter>], text: &str) -> (FilterOutcome, String) {
+    let mut current: Option<String> = None;
+    let mut by = String::new();
+
+    for f in filters {
+        match f.check(current.as_deref().unwrap_or(text)) {
+            FilterOutcome::Clean => {}
+            FilterOutcome::Masked(masked) => {
+                current = Some(masked);
+                by = f.name().to_string();
+            }
+            FilterOutcome::Blocked(reason) => return (FilterOutcome::Blocked(reason), f.name().to_string()),
+        }
+    }
+
+    match current {
+        Some(masked) => (FilterOutcome::Masked(masked), by),
+        None => (FilterOutcome::Clean, by),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn t_wordlist_masks_whole_words() {
+        let f = WordlistFilter::new(["darn", "Heck"], FilterAction::Mask);
+        assert_eq!(
+            f.check("well, DARN it!"),
+            FilterOutcome::Masked("well, **** it!".into())
+        );
+        assert_eq!(f.check("what the heck"), FilterOutcome::Masked("what the ****".into()));
+        // Only whole words
+        assert_eq!(f.check("darned hecklers"), FilterOutcome::Clean);
+    }
+
+    #[test]
+    fn t_wordlist_block() {
+        let f = WordlistFilter::new(["darn"], FilterAction::Block);
+        assert!(matches!(f.check("darn"), FilterOutcome::Blocked(_)));
+        assert_eq!(f.check("hello"), FilterOutcome::Clean);
+    }
+
+    #[test]
+    fn t_chain_passes_masked_text_on() {
+        let filters: Vec<Arc<dyn ContentFilter>> = vec![
+            Arc::new(WordlistFilter::new(["darn"], FilterAction::Mask)),
+            Arc::new(CallbackFilter::new("no-links", |t: &str| {
+                if t.contains("http") {
+                    FilterOutcome::Blocked("links".into())
+                } else {
+                    FilterOutcome::Clean
+                }
+            })),
+        ];
+
+        let (outcome, by) = run_chain(&filters, "darn it");
+        assert_eq!(outcome, FilterOutcome::Masked("**** it".into()));
+        assert_eq!(by, "wordlist");
+
+        let (outcome, by) = run_chain(&filters, "darn, see http://x");
+        assert_eq!(outcome, FilterOutcome::Blocked("links".into()));
+        assert_eq!(by, "no-links");
+
+        assert_eq!(run_chain(&filters, "hello").0, FilterOutcome::Clean);
+    }
+}
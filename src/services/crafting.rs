@@ -0,0 +1,106 @@
+use crate::db::repo::CraftingRepo;
+use crate::error::{AppResult, DomainError};
+use crate::game::xp_to_level;
+use crate::models::account::Account;
+use crate::models::inventory::Recipe;
+use crate::models::types::{ItemId, RealmId};
+use crate::services::{Durability, InventoryService};
+use std::sync::Arc;
+
+/// Result of combining two inventory items
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum CraftOutcome {
+    /// The inputs were consumed and the output added to the inventory; the tool, if any, wore down
+    Crafted {
+        recipe: Recipe,
+        output: ItemId,
+        tool: Option<Durability>,
+    },
+    /// The player does not carry anything matching this noun
+    NotCarried(String),
+    /// Combining an item with itself needs two of them
+    NeedTwo(String),
+    /// The two items do not combine
+    NoRecipe,
+    /// Name of the tool the recipe needs
+    MissingTool(String),
+    /// The tool the recipe needs is broken
+    BrokenTool(String),
+    /// Level the recipe needs
+    LevelTooLow(i32),
+}
+
+/// Combining inventory items by blueprint recipes
+pub struct CraftingService {
+    repo: Arc<dyn CraftingRepo>,
+    inventory: Arc<InventoryService>,
+}
+
+impl CraftingService {
+    pub fn new(repo: Arc<dyn CraftingRepo>, inventory: Arc<InventoryService>) -> Self {
+        Self { repo, inventory }
+    }
+
+    pub async fn recipes(&self, realm_id: RealmId) -> AppResult<Vec<Recipe>> {
+        Ok(self.repo.recipes_for_realm(realm_id).await?)
+    }
+
+    /// Combines the inventory items matching nouns `a` and `b`. The inputs are consumed and the
+    /// output is created in one transaction, so a failed craft never loses items.
+    pub async fn combine(&self, realm_id: RealmId, account: &Account, a: &str, b: &str) -> AppResult<CraftOutcome> {
+        let Some(item_a) = self.inventory.find_in_inventory(realm_id, account.id, a).await? else {
+            return Ok(CraftOutcome::NotCarried(a.to_string()));
+        };
+        let Some(item_b) = self.inventory.find_in_inventory(realm_id, account.id, b).await? else {
+            return Ok(CraftOutcome::NotCarried(b.to_string()));
+        };
+        if item_a.instance_id == item_b.instance_id && item_a.quantity < 2 {
+            return Ok(CraftOutcome::NeedTwo(item_a.name));
+        }
+
+        let recipes = self.repo.recipes_for_realm(realm_id).await?;
+        let Some(recipe) = recipes
+            .into_iter()
+            .find(|r| r.matches(&item_a.item_key, &item_b.item_key))
+        else {
+            return Ok(CraftOutcome::NoRecipe);
+        };
+
+        if let Some(level) = recipe.min_level
+            && xp_to_level(account.xp) < level
+        {
+            return Ok(CraftOutcome::LevelTooLow(level));
+        }
+        let tool = match &recipe.tool_key {
+            Some(tool_key) => {
+                let Some(tool) = self.inventory.find_by_key(realm_id, account.id, tool_key).await? else {
+                    let name = self.inventory.get_item_by_key(realm_id, tool_key).await?.name;
+                    return Ok(CraftOutcome::MissingTool(name));
+                };
+                if tool.is_broken() {
+                    return Ok(CraftOutcome::BrokenTool(tool.name));
+                }
+                Some(tool)
+            }
+            None => None,
+        };
+
+        let output = self
+            .repo
+            .craft(
+                realm_id,
+                account.id,
+                &[item_a.instance_id, item_b.instance_id],
+                &recipe.output_key,
+                recipe.output_qty,
+            )
+            .await?
+            .ok_or_else(|| DomainError::Conflict("the items changed while crafting".into()))?;
+        let tool = match tool {
+            Some(tool) => self.inventory.wear_item(&tool).await?,
+            None => None,
+        };
+
+        Ok(CraftOutcome::Crafted { recipe, output, tool })
+    }
+}
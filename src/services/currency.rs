@@ -0,0 +1,118 @@
+use crate::db::repo::CurrencyRepo;
+use crate::error::{AppResult, DomainError};
+use crate::models::inventory::{ItemInstance, ItemLocation};
+use crate::models::types::{AccountId, RealmId};
+use std::sync::Arc;
+
+/// Coins picked up from a coin stack
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub struct Deposit {
+    pub coins: i32,
+    pub balance: i64,
+}
+
+/// Money, kept as a balance per player in each realm. Coin items never sit in the inventory:
+/// picking them up adds them to the balance.
+pub struct CurrencyService {
+    repo: Arc<dyn CurrencyRepo>,
+}
+
+impl CurrencyService {
+    pub fn new(repo: Arc<dyn CurrencyRepo>) -> Self {
+        Self { repo }
+    }
+
+    pub async fn balance(&self, realm_id: RealmId, account_id: AccountId) -> AppResult<i64> {
+        Ok(self.repo.balance(realm_id, account_id).await?)
+    }
+
+    /// Adds `amount` to the balance. Returns the new balance.
+    pub async fn credit(&self, realm_id: RealmId, account_id: AccountId, amount: i64) -> AppResult<i64> {
+        check_amount(amount)?;
+        Ok(self.repo.credit(realm_id, account_id, amount).await?)
+    }
+
+    /// Takes `amount` off the balance. Returns the new balance, or None when the balance is short.
+    pub async fn debit(&self, realm_id: RealmId, account_id: AccountId, amount: i64) -> AppResult<Option<i64>> {
+        check_amount(amount)?;
+        Ok(self.repo.debit(realm_id, account_id, amount).await?)
+    }
+
+    /// Pays `amount` from one player to another. Returns the payer's new balance, or None when
+    /// their balance is short.
+    pub async fn pay(&self, realm_id: RealmId, from: AccountId, to: AccountId, amount: i64) -> AppResult<Option<i64>> {
+        check_amount(amount)?;
+        if from == to {
+            return Err(DomainError::Validation {
+                field: "to",
+                message: "cannot pay yourself".into(),
+            });
+        }
+        Ok(self.repo.transfer(realm_id, from, to, amount).await?)
+    }
+
+    /// Picks up `quantity` of a coin stack lying in a room, or all of it, into the player's
+    /// balance. None when someone else got to the stack first.
+    pub async fn deposit_coins(
+        &self,
+        account_id: AccountId,
+        item: &ItemInstance,
+        quantity: Option<u32>,
+    ) -> AppResult<Option<Deposit>> {
+        if !item.is_coin {
+            return Err(DomainError::Validation {
+                field: "item",
+                message: format!("{} is not money", item.item_key),
+            });
+        }
+        let ItemLocation::Room(room_id) = item.location else {
+            return Err(DomainError::Validation {
+                field: "item",
+                message: format!("{} is not lying in a room", item.item_key),
+            });
+        };
+        let wanted = quantity.map_or(item.quantity, |n| i32::try_from(n).unwrap_or(i32::MAX));
+        let deposit = self
+            .repo
+            .deposit_coins(item.realm_id, account_id, room_id, item.instance_id, wanted)
+            .await?;
+        Ok(deposit.map(|(coins, balance)| Deposit { coins, balance }))
+    }
+}
+
+fn check_amount(amount: i64) -> AppResult<()> {
+    if amount <= 0 {
+        return Err(DomainError::Validation {
+            field: "amount",
+            message: "must be at least 1".into(),
+        });
+    }
+    Ok(())
+}
+
+/// "1 coin", "12 coins"
+pub fn coins_text(amount: i64) -> String {
+    match amount {
+        1 => "1 coin".to_string(),
+        n => format!("{} coins", n),
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn t_coins_text() {
+        assert_eq!(coins_text(0), "0 coins");
+        assert_eq!(coins_text(1), "1 coin");
+        assert_eq!(coins_text(12), "12 coins");
+    }
+
+    #[test]
+    fn t_check_amount() {
+        assert!(check_amount(1).is_ok());
+        assert!(check_amount(0).is_err());
+        assert!(check_amount(-5).is_err());
+    }
+}
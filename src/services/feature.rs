@@ -0,0 +1,94 @@
+use crate::config::Config;
+use crate::db::repo::FeatureRepo;
+use crate::error::{AppResult, DomainError};
+use crate::models::feature::{FeatureFlag, FeatureScope};
+use crate::models::types::RealmId;
+use dashmap::DashMap;
+use parking_lot::RwLock;
+use std::sync::Arc;
+
+/// Per realm / per blueprint feature flags.
+///
+/// A realm flag wins over a blueprint flag. When neither is set, the global `[features]` from the
+/// server config decide. Resolved database lookups are cached until a flag is changed.
+pub struct FeatureService {
+    repo: Arc<dyn FeatureRepo>,
+    config: Arc<RwLock<Arc<Config>>>,
+    cache: DashMap<(String, RealmId), Option<bool>>,
+}
+
+impl FeatureService {
+    pub fn new(repo: Arc<dyn FeatureRepo>, config: Arc<RwLock<Arc<Config>>>) -> Self {
+        Self {
+            repo,
+            config,
+            cache: DashMap::new(),
+        }
+    }
+
+    /// Returns true when `feature` is enabled in the given realm
+    pub async fn enabled(&self, feature: &str, realm_id: RealmId) -> AppResult<bool> {
+        let key = (feature.to_string(), realm_id);
+        let resolved = match self.cache.get(&key) {
+            Some(v) => *v,
+            None => {
+                let v = self.repo.resolve(feature, realm_id).await?;
+                self.cache.insert(key, v);
+                v
+            }
+        };
+
+        Ok(resolved.unwrap_or_else(|| self.config.read().feature(feature)))
+    }
+
+    pub async fn list(&self, realm_id: RealmId) -> AppResult<Vec<FeatureFlag>> {
+        Ok(self.repo.list_for_realm(realm_id).await?)
+    }
+
+    pub async fn set(&self, feature: &str, scope: FeatureScope, enabled: bool) -> AppResult<()> {
+        validate_feature_name(feature)?;
+        self.repo.set(feature, scope, enabled).await?;
+        self.invalidate(feature);
+        Ok(())
+    }
+
+    pub async fn clear(&self, feature: &str, scope: FeatureScope) -> AppResult<bool> {
+        let removed = self.repo.clear(feature, scope).await?;
+        self.invalidate(feature);
+        Ok(removed)
+    }
+
+    /// A blueprint flag affects all its realms, so drop every cached entry of the feature
+    fn invalidate(&self, feature: &str) {
+        self.cache.retain(|(f, _), _| f != feature);
+    }
+}
+
+/// Same rules as the `[features]` keys in the config file
+fn validate_feature_name(name: &str) -> AppResult<()> {
+    if name.is_empty()
+        || !name
+            .chars()
+            .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '_')
+    {
+        return Err(DomainError::Validation {
+            field: "feature",
+            message: "feature names may only contain a-z, 0-9 and _".into(),
+        });
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn t_feature_names() {
+        assert!(validate_feature_name("combat").is_ok());
+        assert!(validate_feature_name("economy_v2").is_ok());
+        assert!(validate_feature_name("").is_err());
+        assert!(validate_feature_name("Combat").is_err());
+        assert!(validate_feature_name("new-combat").is_err());
+    }
+}
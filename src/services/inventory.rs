@@ -1,6 +1,7 @@
 use crate::db::repo::InventoryRepo;
 use crate::error::{AppResult, DomainError};
-use crate::models::inventory::{Item, ItemInstance, ItemLocation};
+use crate::models::inventory::{ClearedItem, Item, ItemInstance, ItemLocation, StashedItem};
+use crate::models::room::{ResolvedObject, Storage};
 use crate::models::types::{AccountId, ItemId, ObjectId, RealmId, RoomId};
 use std::sync::Arc;
 
@@ -79,6 +80,11 @@ impl InventoryService {
         Ok(items)
     }
 
+    /// Everything the player carries, including the contents of carried containers
+    pub async fn get_carried_items(&self, realm_id: RealmId, account_id: AccountId) -> AppResult<Vec<ItemInstance>> {
+        Ok(self.repo.get_carried_items(realm_id, account_id).await?)
+    }
+
     /// Get player inventory grouped by item type (for display)
     pub async fn get_player_inventory_summary(
         &self,
@@ -121,6 +127,20 @@ impl InventoryService {
         Ok(instance)
     }
 
+    /// Find specific item in player inventory by item key
+    pub async fn find_by_key(
+        &self,
+        realm_id: RealmId,
+        account_id: AccountId,
+        item_key: &str,
+    ) -> AppResult<Option<ItemInstance>> {
+        let instance = self
+            .repo
+            .find_item_by_key_in_inventory(realm_id, account_id, item_key)
+            .await?;
+        Ok(instance)
+    }
+
     // ========================================================================
     // ROOM ITEMS
     // ========================================================================
@@ -297,6 +317,24 @@ impl InventoryService {
         Ok(())
     }
 
+    /// Move `quantity` of a stack, or the whole stack when None. Items that do not stack always
+    /// move whole. Returns how many were moved, which is less than asked when the stack is smaller.
+    pub async fn move_quantity(
+        &self,
+        item: &ItemInstance,
+        quantity: Option<u32>,
+        new_location: ItemLocation,
+    ) -> AppResult<i32> {
+        let wanted = match quantity {
+            Some(n) if item.stackable => i32::try_from(n).unwrap_or(i32::MAX),
+            _ => item.quantity,
+        };
+        Ok(self
+            .repo
+            .move_item_quantity(item.instance_id, wanted, new_location)
+            .await?)
+    }
+
     /// Take item from room/object and put in player inventory
     pub async fn take_item(&self, instance_id: ItemId, account_id: AccountId) -> AppResult<()> {
         self.move_item(instance_id, ItemLocation::Player(account_id)).await
@@ -333,6 +371,94 @@ impl InventoryService {
         self.move_item(instance_id, ItemLocation::Player(to_account)).await
     }
 
+    // ========================================================================
+    // STORAGE
+    // ========================================================================
+
+    /// The player's stash in a storage object
+    pub async fn get_stash(
+        &self,
+        realm_id: RealmId,
+        account_id: AccountId,
+        obj: &ResolvedObject,
+    ) -> AppResult<Vec<StashedItem>> {
+        let storage = storage_of(obj)?;
+        Ok(self
+            .repo
+            .get_stash(obj.id, account_id, storage.stash_realm(realm_id))
+            .await?)
+    }
+
+    /// Deposit `quantity` of an inventory item, or all of it, into the player's stash in a storage
+    /// object. Equipped items and containers with things in them stay in the inventory.
+    pub async fn deposit(
+        &self,
+        account_id: AccountId,
+        obj: &ResolvedObject,
+        item: &ItemInstance,
+        quantity: Option<u32>,
+    ) -> AppResult<StashOutcome> {
+        let storage = storage_of(obj)?;
+        if item.is_equipped() {
+            return Ok(StashOutcome::Equipped);
+        }
+        let carried = self.repo.get_carried_items(item.realm_id, account_id).await?;
+        if carried
+            .iter()
+            .any(|i| i.location == ItemLocation::Container(item.instance_id))
+        {
+            return Ok(StashOutcome::NotEmpty);
+        }
+
+        let wanted = match quantity {
+            Some(n) if item.stackable => i32::try_from(n).unwrap_or(i32::MAX),
+            _ => item.quantity,
+        };
+        let capacity = i32::try_from(storage.capacity).unwrap_or(i32::MAX);
+        let stored = self
+            .repo
+            .stash_item(
+                item.instance_id,
+                wanted,
+                obj.id,
+                account_id,
+                storage.stash_realm(item.realm_id),
+                capacity,
+            )
+            .await?;
+        Ok(match stored {
+            Some(n) => StashOutcome::Stored(n),
+            None => StashOutcome::Full {
+                capacity: storage.capacity,
+            },
+        })
+    }
+
+    /// Withdraw `quantity` of a stashed item, or all of it, into the player's inventory. Returns
+    /// how many were withdrawn, 0 when someone (another session) got to it first.
+    pub async fn withdraw(
+        &self,
+        realm_id: RealmId,
+        account_id: AccountId,
+        stashed: &StashedItem,
+        quantity: Option<u32>,
+    ) -> AppResult<i32> {
+        let wanted = match quantity {
+            Some(n) if stashed.stackable => i32::try_from(n).unwrap_or(i32::MAX),
+            _ => stashed.quantity,
+        };
+        Ok(self.repo.unstash_item(stashed.id, wanted, realm_id, account_id).await?)
+    }
+
+    // ========================================================================
+    // DROPPED ITEMS
+    // ========================================================================
+
+    /// Clear away dropped items that are due under their blueprint's drop policy
+    pub async fn clear_dropped_items(&self) -> AppResult<Vec<ClearedItem>> {
+        Ok(self.repo.clear_dropped_items().await?)
+    }
+
     // ========================================================================
     // ITEM MODIFICATION
     // ========================================================================
@@ -369,6 +495,78 @@ impl InventoryService {
         Ok(())
     }
 
+    /// Wears an item down by one use. Returns None for items that never wear.
+    pub async fn wear_item(&self, item: &ItemInstance) -> AppResult<Option<Durability>> {
+        self.adjust_durability(item, -item.wear_per_use).await
+    }
+
+    /// Changes the durability of an item by `delta` (negative wears it down). Returns None for
+    /// items that never wear.
+    pub async fn adjust_durability(&self, item: &ItemInstance, delta: i32) -> AppResult<Option<Durability>> {
+        let Some(max) = item.max_durability else {
+            return Ok(None);
+        };
+        let remaining = self.repo.adjust_durability(item.instance_id, delta, max).await?;
+        Ok(Some(Durability {
+            name: item.name.clone(),
+            remaining,
+            max,
+        }))
+    }
+
+    /// Repairs the carried item matching `noun` back to full durability. When `with` is given, it
+    /// must name the repair tool. The tool wears like any other use.
+    pub async fn repair(
+        &self,
+        realm_id: RealmId,
+        account_id: AccountId,
+        noun: &str,
+        with: Option<&str>,
+    ) -> AppResult<RepairOutcome> {
+        let Some(item) = self.find_in_inventory(realm_id, account_id, noun).await? else {
+            return Ok(RepairOutcome::NotCarried(noun.to_string()));
+        };
+        let (Some(max), Some(current)) = (item.max_durability, item.durability()) else {
+            return Ok(RepairOutcome::CannotRepair(item.name));
+        };
+        if current >= max {
+            return Ok(RepairOutcome::NotDamaged(item.name));
+        }
+
+        let tool = match &item.repair_tool {
+            Some(tool_key) => {
+                let Some(tool) = self.find_by_key(realm_id, account_id, tool_key).await? else {
+                    let name = self.get_item_by_key(realm_id, tool_key).await?.name;
+                    return Ok(RepairOutcome::MissingTool(name));
+                };
+                if tool.is_broken() {
+                    return Ok(RepairOutcome::BrokenTool(tool.name));
+                }
+                Some(tool)
+            }
+            None => None,
+        };
+        if let Some(with) = with {
+            let named = self.find_in_inventory(realm_id, account_id, with).await?;
+            match (named, &tool) {
+                (None, _) => return Ok(RepairOutcome::NotCarried(with.to_string())),
+                (Some(n), Some(t)) if n.instance_id == t.instance_id => {}
+                (Some(n), _) => return Ok(RepairOutcome::WrongTool(n.name)),
+            }
+        }
+
+        let repaired = self
+            .adjust_durability(&item, max - current)
+            .await?
+            .ok_or_else(|| DomainError::InternalError("repaired item does not wear".into()))?;
+        let tool = match tool {
+            Some(tool) => self.wear_item(&tool).await?,
+            None => None,
+        };
+
+        Ok(RepairOutcome::Repaired { item: repaired, tool })
+    }
+
     /// Delete item instance entirely
     pub async fn delete_item(&self, instance_id: ItemId) -> AppResult<()> {
         self.repo.delete_item(instance_id).await?;
@@ -474,6 +672,112 @@ pub enum LootInstantiationResult {
     },
 }
 
+/// Durability of an item after it was worn or repaired
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub struct Durability {
+    pub name: String,
+    pub remaining: i32,
+    pub max: i32,
+}
+
+impl Durability {
+    pub fn is_broken(&self) -> bool {
+        self.remaining == 0
+    }
+
+    /// What the player notices after using the item, if anything: it broke, or it is close to
+    /// breaking (a quarter of its durability left)
+    pub fn wear_message(&self) -> Option<String> {
+        if self.is_broken() {
+            Some(format!("Your {} breaks.", self.name))
+        } else if self.remaining * 4 <= self.max {
+            Some(format!("Your {} is badly worn.", self.name))
+        } else {
+            None
+        }
+    }
+}
+
+/// Result of depositing an item into a storage object
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+pub enum StashOutcome {
+    /// This many went into the stash; 0 when the item was no longer carried
+    Stored(i32),
+    /// Every slot of the stash is taken
+    Full { capacity: u32 },
+    /// The item is worn or wielded
+    Equipped,
+    /// The item holds other items
+    NotEmpty,
+}
+
+/// Result of repairing an inventory item
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum RepairOutcome {
+    /// The item is back at full durability; the tool, if any, wore down
+    Repaired { item: Durability, tool: Option<Durability> },
+    /// The player does not carry anything matching this noun
+    NotCarried(String),
+    /// The item never wears, so there is nothing to repair
+    CannotRepair(String),
+    /// The item is already at full durability
+    NotDamaged(String),
+    /// Name of the tool the repair needs
+    MissingTool(String),
+    /// The repair tool itself is broken
+    BrokenTool(String),
+    /// The named item is not the tool the repair needs
+    WrongTool(String),
+}
+
+fn storage_of(obj: &ResolvedObject) -> AppResult<Storage> {
+    obj.storage.ok_or_else(|| DomainError::Validation {
+        field: "object",
+        message: format!("the {} does not store things", obj.name),
+    })
+}
+
+/// The lines `examine` shows for an item: its text, then how many there are, its condition,
+/// weight, whether it is equipped and the nouns it answers to
+pub fn describe_item(item: &ItemInstance) -> Vec<String> {
+    let mut lines = vec![item.examine.clone().unwrap_or_else(|| item.description.clone())];
+
+    if item.quantity > 1 {
+        lines.push(format!("There are {} of them.", item.quantity));
+    }
+    if let Some(condition) = condition_descriptor(item) {
+        lines.push(format!("It is {}.", condition));
+    }
+    if let Some(weight) = item.weight {
+        lines.push(format!("It weighs {} kg.", weight));
+    }
+    if item.is_equipped() {
+        lines.push("You have it equipped.".to_string());
+    }
+    if let [rest @ .., last] = item.nouns.as_slice() {
+        let nouns = match rest {
+            [] => last.clone(),
+            _ => format!("{} or {}", rest.join(", "), last),
+        };
+        lines.push(format!("You can refer to it as {}.", nouns));
+    }
+    lines
+}
+
+/// How worn the item looks, None when it never wears. Badly worn matches the warning players get
+/// from `Durability::wear_message`.
+fn condition_descriptor(item: &ItemInstance) -> Option<&'static str> {
+    let max = item.max_durability?;
+    let remaining = item.durability()?;
+    Some(match remaining {
+        0 => "broken",
+        r if r == max => "in mint condition",
+        r if r * 4 <= max => "badly worn",
+        r if r * 2 <= max => "worn",
+        _ => "in good condition",
+    })
+}
+
 /// Summary item for inventory display (grouped/stacked)
 #[derive(Debug, Clone)]
 pub struct InventorySummaryItem {
@@ -485,6 +789,83 @@ pub struct InventorySummaryItem {
     pub stackable: bool,
 }
 
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn t_wear_message() {
+        let d = |remaining| Durability {
+            name: "spanner".into(),
+            remaining,
+            max: 8,
+        };
+        assert_eq!(d(5).wear_message(), None);
+        assert_eq!(d(2).wear_message().as_deref(), Some("Your spanner is badly worn."));
+        assert_eq!(d(0).wear_message().as_deref(), Some("Your spanner breaks."));
+    }
+
+    fn spanner(condition: serde_json::Value) -> ItemInstance {
+        ItemInstance {
+            instance_id: ItemId::new(),
+            realm_id: RealmId::new(),
+            catalog_id: ItemId::new(),
+            location: ItemLocation::Player(AccountId::new()),
+            quantity: 1,
+            condition: Some(condition),
+            item_key: "spanner".into(),
+            name: "Spanner".into(),
+            short: "a spanner".into(),
+            description: "A spanner.".into(),
+            examine: Some("A well-used multi-spanner.".into()),
+            stackable: false,
+            nouns: vec!["spanner".into(), "tool".into(), "wrench".into()],
+            max_durability: Some(8),
+            wear_per_use: 1,
+            repair_tool: None,
+            weight: Some(1.5),
+            is_coin: false,
+            created_at: chrono::Utc::now(),
+            updated_at: chrono::Utc::now(),
+        }
+    }
+
+    #[test]
+    fn t_describe_item() {
+        assert_eq!(
+            describe_item(&spanner(serde_json::json!({"durability": 3, "equipped": true}))),
+            vec![
+                "A well-used multi-spanner.",
+                "It is worn.",
+                "It weighs 1.5 kg.",
+                "You have it equipped.",
+                "You can refer to it as spanner, tool or wrench.",
+            ]
+        );
+
+        let mut coin = spanner(serde_json::json!({}));
+        coin.examine = None;
+        coin.quantity = 4;
+        coin.max_durability = None;
+        coin.weight = None;
+        coin.nouns = vec!["coin".into()];
+        assert_eq!(
+            describe_item(&coin),
+            vec!["A spanner.", "There are 4 of them.", "You can refer to it as coin."]
+        );
+    }
+
+    #[test]
+    fn t_condition_descriptor() {
+        let condition = |d: i32| condition_descriptor(&spanner(serde_json::json!({"durability": d})));
+        assert_eq!(condition(8), Some("in mint condition"));
+        assert_eq!(condition(6), Some("in good condition"));
+        assert_eq!(condition(4), Some("worn"));
+        assert_eq!(condition(2), Some("badly worn"));
+        assert_eq!(condition(0), Some("broken"));
+    }
+}
+
 // ============================================================================
 // EXAMPLES
 // ============================================================================
@@ -0,0 +1,56 @@
+use crate::db::repo::MaintenanceRepo;
+use crate::error::AppResult;
+use crate::models::maintenance::Garbage;
+use std::sync::Arc;
+
+/// Garbage collection of rows nothing points at anymore: item instances of deleted realms,
+/// rooms, objects and accounts, and player overlays left over from another blueprint
+pub struct MaintenanceService {
+    repo: Arc<dyn MaintenanceRepo>,
+}
+
+impl MaintenanceService {
+    pub fn new(repo: Arc<dyn MaintenanceRepo>) -> Self {
+        Self { repo }
+    }
+
+    /// The garbage there is, by kind; kinds without any are left out
+    pub async fn find_garbage(&self) -> AppResult<Vec<Garbage>> {
+        Ok(found(self.repo.count_garbage().await?))
+    }
+
+    /// Deletes all garbage. Returns what went, by kind; kinds without any are left out.
+    pub async fn purge_garbage(&self) -> AppResult<Vec<Garbage>> {
+        Ok(found(self.repo.purge_garbage().await?))
+    }
+}
+
+fn found(garbage: Vec<Garbage>) -> Vec<Garbage> {
+    garbage.into_iter().filter(|g| g.count > 0).collect()
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::maintenance::total;
+
+    #[test]
+    fn t_found_leaves_out_empty_kinds() {
+        let garbage = found(vec![
+            Garbage {
+                what: "items in deleted realms",
+                count: 3,
+            },
+            Garbage {
+                what: "items in deleted rooms",
+                count: 0,
+            },
+            Garbage {
+                what: "items of deleted accounts",
+                count: 2,
+            },
+        ]);
+        assert_eq!(garbage.len(), 2);
+        assert_eq!(total(&garbage), 5);
+    }
+}
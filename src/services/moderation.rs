@@ -0,0 +1,104 @@
+use crate::db::repo::{AccountRepo, ModerationRepo};
+use crate::error::{AppResult, DomainError};
+use crate::models::account::Account;
+use crate::models::report::{NewReport, Report};
+use crate::models::types::{RealmId, RoomId};
+use std::sync::Arc;
+
+/// Longest reason a player can give, in characters
+const MAX_REASON_LEN: usize = 500;
+
+/// Player abuse reports and the moderation queue
+pub struct ModerationService {
+    repo: Arc<dyn ModerationRepo>,
+    accounts: Arc<dyn AccountRepo>,
+}
+
+impl ModerationService {
+    pub fn new(repo: Arc<dyn ModerationRepo>, accounts: Arc<dyn AccountRepo>) -> Self {
+        Self { repo, accounts }
+    }
+
+    /// Files a report by `reporter` against the player named `target`. Returns the report id and
+    /// the target's username as stored.
+    pub async fn file_report(
+        &self,
+        reporter: &Account,
+        target: &str,
+        reason: &str,
+        realm_id: Option<RealmId>,
+        room_id: Option<RoomId>,
+    ) -> AppResult<(i64, String)> {
+        let reason = validate_reason(reason)?;
+
+        let Some(target) = self.accounts.get_by_username(target).await? else {
+            return Err(DomainError::NotFound(format!("player '{}'", target)));
+        };
+        if target.id == reporter.id {
+            return Err(DomainError::Validation {
+                field: "player",
+                message: "you cannot report yourself".into(),
+            });
+        }
+        if self.repo.has_open_report(reporter.id, target.id).await? {
+            return Err(DomainError::Conflict(format!(
+                "you already have an open report against {}",
+                target.username
+            )));
+        }
+
+        let id = self
+            .repo
+            .file_report(&NewReport {
+                reporter_id: reporter.id,
+                target_id: target.id,
+                reason,
+                realm_id,
+                room_id,
+            })
+            .await?;
+
+        Ok((id, target.username))
+    }
+
+    pub async fn open_reports(&self) -> AppResult<Vec<Report>> {
+        Ok(self.repo.open_reports().await?)
+    }
+
+    /// Resolves an open report, returns false when no open report has this id
+    pub async fn resolve(&self, report_id: i64, moderator: &Account, resolution: Option<&str>) -> AppResult<bool> {
+        let resolution = resolution.map(str::trim).filter(|s| !s.is_empty());
+        Ok(self.repo.resolve(report_id, moderator.id, resolution).await?)
+    }
+}
+
+fn validate_reason(reason: &str) -> AppResult<String> {
+    let reason = reason.trim();
+    if reason.is_empty() {
+        return Err(DomainError::Validation {
+            field: "reason",
+            message: "please give a reason for the report".into(),
+        });
+    }
+    if reason.chars().count() > MAX_REASON_LEN {
+        return Err(DomainError::Validation {
+            field: "reason",
+            message: format!("the reason may be at most {} characters", MAX_REASON_LEN),
+        });
+    }
+    Ok(reason.to_string())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn t_validate_reason() {
+        assert_eq!(validate_reason("  spamming the hub  ").unwrap(), "spamming the hub");
+        assert!(validate_reason("").is_err());
+        assert!(validate_reason("   ").is_err());
+        assert!(validate_reason(&"x".repeat(MAX_REASON_LEN)).is_ok());
+        assert!(validate_reason(&"x".repeat(MAX_REASON_LEN + 1)).is_err());
+    }
+}
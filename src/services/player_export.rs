@@ -0,0 +1,66 @@
+use crate::db::repo::{AccountRepo, PlayerExportRepo};
+use crate::error::{AppResult, DomainError};
+use crate::models::player_export::PlayerBundle;
+use chrono::Utc;
+use std::path::{Path, PathBuf};
+use std::sync::Arc;
+
+/// Player state exports for data requests and support restores (see `models::player_export`)
+pub struct PlayerExportService {
+    repo: Arc<dyn PlayerExportRepo>,
+    accounts: Arc<dyn AccountRepo>,
+}
+
+impl PlayerExportService {
+    pub fn new(repo: Arc<dyn PlayerExportRepo>, accounts: Arc<dyn AccountRepo>) -> Self {
+        Self { repo, accounts }
+    }
+
+    pub async fn export(&self, username: &str) -> AppResult<PlayerBundle> {
+        let account = self
+            .accounts
+            .get_by_username(username)
+            .await?
+            .ok_or_else(|| DomainError::NotFound(format!("account '{}'", username)))?;
+        self.repo
+            .export(account.id)
+            .await?
+            .ok_or_else(|| DomainError::NotFound(format!("account '{}'", username)))
+    }
+
+    /// Writes the player's bundle to a new file in `dir`, returns its path
+    pub async fn export_to_file(&self, username: &str, dir: &Path) -> AppResult<PathBuf> {
+        let bundle = self.export(username).await?;
+        let name = bundle.username().unwrap_or(username).to_string();
+
+        std::fs::create_dir_all(dir)?;
+        let path = dir.join(format!("{}-{}.json", name, Utc::now().format("%Y%m%d-%H%M%S")));
+        std::fs::write(&path, serde_json::to_string_pretty(&bundle)?)?;
+        Ok(path)
+    }
+
+    /// Restores a bundle over the account's current state. Returns true when the account did not
+    /// exist anymore: it is recreated without a usable password.
+    pub async fn import(&self, bundle: &PlayerBundle) -> AppResult<bool> {
+        let account_id = bundle.validate().map_err(|message| DomainError::Validation {
+            field: "bundle",
+            message,
+        })?;
+        let recreated = self.accounts.get_by_id(account_id).await?.is_none();
+        self.repo.import(account_id, bundle).await?;
+        Ok(recreated)
+    }
+
+    /// Restores the bundle in file `file_name` of `dir`. Only plain file names are accepted.
+    pub async fn import_from_file(&self, dir: &Path, file_name: &str) -> AppResult<(PlayerBundle, bool)> {
+        if file_name.is_empty() || file_name.contains(['/', '\\']) || file_name.starts_with('.') {
+            return Err(DomainError::Validation {
+                field: "file",
+                message: format!("'{}' is not a file name in the export directory", file_name),
+            });
+        }
+        let bundle: PlayerBundle = serde_json::from_str(&std::fs::read_to_string(dir.join(file_name))?)?;
+        let recreated = self.import(&bundle).await?;
+        Ok((bundle, recreated))
+    }
+}
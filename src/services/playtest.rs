@@ -0,0 +1,35 @@
+use crate::db::repo::PlaytestRepo;
+use crate::error::AppResult;
+use crate::models::account::Account;
+use crate::models::types::AccountId;
+use std::sync::Arc;
+
+/// Playtesting blueprints as a synthetic persona, so authors can see the first-time experience
+/// without touching their own progress.
+pub struct PlaytestService {
+    repo: Arc<dyn PlaytestRepo>,
+}
+
+impl PlaytestService {
+    pub fn new(repo: Arc<dyn PlaytestRepo>) -> Self {
+        Self { repo }
+    }
+
+    /// Returns the guest persona of `owner` with all its state wiped: no inventory, nothing
+    /// discovered, no position.
+    pub async fn fresh_guest(&self, owner: &Account) -> AppResult<Account> {
+        let persona = self.repo.persona_for(owner).await?;
+        self.repo.reset_persona(persona.id).await?;
+        Ok(persona)
+    }
+
+    /// Creates a throwaway account for an automated playthrough; remove it with `discard`
+    pub async fn create_tester(&self, username: &str) -> AppResult<Account> {
+        Ok(self.repo.create_tester(username).await?)
+    }
+
+    /// Deletes a tester or persona account with all its state
+    pub async fn discard(&self, account_id: AccountId) -> AppResult<()> {
+        Ok(self.repo.delete_persona(account_id).await?)
+    }
+}
@@ -81,6 +81,41 @@ impl RealmService {
         Ok(realm)
     }
 
+    /// Live realms of published blueprints, open to every player, with their keys
+    pub async fn list_public(&self) -> AppResult<Vec<(String, Realm)>> {
+        Ok(self.realm_repo.list_public().await?)
+    }
+
+    /// Remembers when the account first entered a stored realm, for its completion time
+    pub async fn record_start(&self, realm: &Realm, account_id: AccountId) -> AppResult<()> {
+        if realm.is_persistent() {
+            self.realm_repo.record_start(realm.id, account_id).await?;
+        }
+        Ok(())
+    }
+
+    /// Returns false when the account already completed the realm
+    pub async fn complete(&self, realm_id: RealmId, account_id: AccountId) -> AppResult<bool> {
+        Ok(self.realm_repo.complete(realm_id, account_id).await?)
+    }
+
+    /// Atomically adds `by` to a realm counter, or to the player's own counter when an account is
+    /// given. Returns the new value.
+    pub async fn counter_incr(
+        &self,
+        realm_id: RealmId,
+        account_id: Option<AccountId>,
+        key: &str,
+        by: i64,
+    ) -> AppResult<i64> {
+        Ok(self.realm_repo.incr_counter(realm_id, account_id, key, by).await?)
+    }
+
+    /// Current value of a realm or player counter, 0 when never incremented
+    pub async fn counter(&self, realm_id: RealmId, account_id: Option<AccountId>, key: &str) -> AppResult<i64> {
+        Ok(self.realm_repo.counter(realm_id, account_id, key).await?)
+    }
+
     pub fn create_ephemeral_realm(&self, owner: AccountId, bp_id: BlueprintId, title: String) -> Realm {
         Realm {
             id: RealmId::new(),
@@ -88,6 +123,7 @@ impl RealmService {
             title,
             kind: RealmKind::Test { owner },
             created_at: Utc::now(),
+            prompt: None,
         }
     }
 
@@ -109,12 +145,21 @@ impl RealmService {
             title,
             kind,
             created_at: Utc::now(),
+            prompt: None,
         };
 
         let realm = self.realm_repo.create(realm).await?;
         Ok(realm)
     }
 
+    pub async fn delete_realm(&self, realm_id: RealmId) -> AppResult<bool> {
+        Ok(self.realm_repo.delete(realm_id).await?)
+    }
+
+    pub async fn reset_realm(&self, realm_id: RealmId) -> AppResult<bool> {
+        Ok(self.realm_repo.reset(realm_id).await?)
+    }
+
     pub async fn get_realm(&self, realm_id: RealmId) -> AppResult<Option<Realm>> {
         let realm = self.realm_repo.get(realm_id).await?;
         Ok(realm)
@@ -0,0 +1,46 @@
+use crate::db::repo::RecordingRepo;
+use crate::error::{AppResult, DomainError};
+use crate::models::account::Account;
+use crate::models::recording::{NewRecording, Recording, RecordingSummary, SessionRecorder};
+use crate::models::types::AccountId;
+use std::sync::Arc;
+
+/// Recordings shown by `@replay` without an id
+const LIST_LIMIT: i64 = 20;
+
+/// Stored session transcripts, for replaying what a player saw
+pub struct RecordingService {
+    repo: Arc<dyn RecordingRepo>,
+}
+
+impl RecordingService {
+    pub fn new(repo: Arc<dyn RecordingRepo>) -> Self {
+        Self { repo }
+    }
+
+    /// Stores a finished recording of `account_id`, returns its id
+    pub async fn save(&self, account_id: AccountId, recorder: SessionRecorder) -> AppResult<i64> {
+        let recording = NewRecording {
+            account_id,
+            recorded_by: recorder.recorded_by,
+            started_at: recorder.started_at,
+            events: recorder.into_events(),
+        };
+        Ok(self.repo.save(&recording).await?)
+    }
+
+    /// Recent recordings `viewer` may replay: their own, or everyone's for moderators
+    pub async fn list(&self, viewer: &Account) -> AppResult<Vec<RecordingSummary>> {
+        let account_id = (!viewer.is_moderator()).then_some(viewer.id);
+        Ok(self.repo.list(account_id, LIST_LIMIT).await?)
+    }
+
+    /// Loads a recording for `viewer`. Players only see their own recordings; a recording of
+    /// someone else is reported as not found.
+    pub async fn get(&self, recording_id: i64, viewer: &Account) -> AppResult<Recording> {
+        match self.repo.get(recording_id).await? {
+            Some(r) if r.account_id == viewer.id || viewer.is_moderator() => Ok(r),
+            _ => Err(DomainError::NotFound(format!("recording #{}", recording_id))),
+        }
+    }
+}
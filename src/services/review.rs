@@ -0,0 +1,88 @@
+use crate::db::repo::ReviewRepo;
+use crate::error::{AppResult, DomainError};
+use crate::models::realm::Realm;
+use crate::models::review::{
+    BlueprintFeedback, MAX_TAGS_PER_PLAYER, Rating, normalize_review, normalize_tag, parse_stars,
+};
+use crate::models::types::{AccountId, BlueprintId, RealmId};
+use std::sync::Arc;
+
+/// Reviews shown by `@bp feedback`
+const FEEDBACK_REVIEWS: i64 = 10;
+
+/// Ratings, reviews and tags players give the realms they completed
+pub struct ReviewService {
+    repo: Arc<dyn ReviewRepo>,
+}
+
+impl ReviewService {
+    pub fn new(repo: Arc<dyn ReviewRepo>) -> Self {
+        Self { repo }
+    }
+
+    /// The account's rating and tags of the realm
+    pub async fn mine(&self, realm_id: RealmId, account_id: AccountId) -> AppResult<(Option<Rating>, Vec<String>)> {
+        let rating = self.repo.rating(realm_id, account_id).await?;
+        let tags = self.repo.tags(realm_id, account_id).await?;
+        Ok((rating, tags))
+    }
+
+    /// Rates the realm with `stars` (1 to 5) and an optional review, replacing an earlier rating
+    pub async fn rate(&self, realm: &Realm, account_id: AccountId, stars: &str, review: &str) -> AppResult<u8> {
+        let stars = parse_stars(stars).map_err(|message| DomainError::Validation {
+            field: "stars",
+            message,
+        })?;
+        let review = normalize_review(review).map_err(|message| DomainError::Validation {
+            field: "review",
+            message,
+        })?;
+        self.ensure_completed(realm, account_id).await?;
+
+        self.repo
+            .set_rating(realm.id, account_id, stars, review.as_deref())
+            .await?;
+        Ok(stars)
+    }
+
+    /// Returns the normalized tag, or None when the account already used it
+    pub async fn tag(&self, realm: &Realm, account_id: AccountId, tag: &str) -> AppResult<Option<String>> {
+        let tag = normalize_tag(tag).map_err(|message| DomainError::Validation { field: "tag", message })?;
+        self.ensure_completed(realm, account_id).await?;
+
+        let tags = self.repo.tags(realm.id, account_id).await?;
+        if tags.contains(&tag) {
+            return Ok(None);
+        }
+        if tags.len() >= MAX_TAGS_PER_PLAYER {
+            return Err(DomainError::Validation {
+                field: "tag",
+                message: format!("you can put at most {} tags on a realm", MAX_TAGS_PER_PLAYER),
+            });
+        }
+
+        self.repo.add_tag(realm.id, account_id, &tag).await?;
+        Ok(Some(tag))
+    }
+
+    /// Returns false when the account did not use the tag
+    pub async fn untag(&self, realm_id: RealmId, account_id: AccountId, tag: &str) -> AppResult<bool> {
+        let tag = normalize_tag(tag).map_err(|message| DomainError::Validation { field: "tag", message })?;
+        Ok(self.repo.remove_tag(realm_id, account_id, &tag).await?)
+    }
+
+    /// What players think of the realms of the blueprint
+    pub async fn feedback(&self, bp_id: BlueprintId) -> AppResult<BlueprintFeedback> {
+        Ok(self.repo.feedback(bp_id, FEEDBACK_REVIEWS).await?)
+    }
+
+    async fn ensure_completed(&self, realm: &Realm, account_id: AccountId) -> AppResult<()> {
+        if realm.is_ephemeral() || !self.repo.has_completed(realm.id, account_id).await? {
+            return Err(DomainError::Validation {
+                field: "realm",
+                message: format!("complete {} before reviewing it", realm.title),
+            });
+        }
+        Ok(())
+    }
+}
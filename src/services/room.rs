@@ -1,17 +1,112 @@
 use crate::commands::CmdCtx;
 use crate::db::repo::{AccountRepo, RealmRepo, RoomRepo, UserRepo};
 use crate::error::{AppResult, DomainError};
-use crate::lua::{LUA_CMD_TIMEOUT, LuaJob, LuaResult, ScriptHook};
-use crate::models::room::{RoomView, build_room_view_impl};
+use crate::hardening::MAX_SPAWNED_OBJECTS_PER_ROOM;
+use crate::lua::{LuaJob, LuaResult, ScriptHook, lua_pages};
+use crate::models::inventory::{ItemInstance, Recipe};
+use crate::models::room::{
+    BlueprintRoom, Discovery, ExitRedirect, Hint, HintAvailability, HintState, Kv, OBJECT_USAGE_KEY, ObjectUsage,
+    Place, ResolvedObject, RoomView, SpawnedObject, UseDenied, UseScope, build_room_view_impl,
+};
 use crate::models::types::{AccountId, Direction, ExitId, ObjectId, RealmId, RoomId};
+use crate::models::vehicle::{VEHICLE_STATE_KEY, Vehicle, VehicleState};
 use crate::services::inventory::LootConfig;
 use crate::state::session::Cursor;
+use rand::Rng;
 use rand::seq::IndexedRandom;
 use std::collections::HashMap;
 use std::sync::Arc;
 use tokio::sync::oneshot;
 use tokio::time::timeout;
 
+/// Number of times a compare-and-swap update on shared state is retried before giving up
+const SHARED_KV_CAS_RETRIES: usize = 5;
+
+/// Seconds before a player can search the same room again, unless the room state sets
+/// `search_cooldown`
+const DEFAULT_SEARCH_COOLDOWN: i64 = 30;
+
+/// User room KV key holding the unix timestamp of the last search
+const SEARCHED_AT_KEY: &str = "__searched_at";
+
+/// Shared room KV key holding the unix timestamp at which a timed lockdown lifts
+pub const LOCKDOWN_UNTIL_KEY: &str = "__lockdown_until";
+
+/// Result of searching a room
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum SearchOutcome {
+    /// Searched too recently; seconds until the room can be searched again
+    CoolingDown(u64),
+    /// Short descriptions of the obscured objects that were found
+    Searched { found: Vec<String> },
+}
+
+/// Seconds left of a lockdown expiring at `until`, None when there is none or it has passed
+fn lockdown_remaining(until: Option<i64>, now: i64) -> Option<u64> {
+    until.filter(|&u| u > now).map(|u| (u - now) as u64)
+}
+
+/// A d20 perception roll: the roll plus the perception modifier must meet the difficulty class.
+/// Perception 10 is average and gives no modifier, every 2 points above or below adds or
+/// subtracts one.
+fn perception_check(roll: u32, perception: u32, dc: u32) -> bool {
+    let modifier = (perception as i64 - 10).div_euclid(2);
+    roll as i64 + modifier >= dc as i64
+}
+
+/// Result of a player asking for a hint
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum HintOutcome {
+    Hint(String),
+    /// Every remaining hint is cooling down; seconds until the first is available
+    CoolingDown(u64),
+    /// No hints left in this room
+    NoHints,
+}
+
+enum HintPick<'a> {
+    Show(&'a Hint),
+    Wait(u64),
+    None,
+}
+
+fn pick_hint<'a>(hints: &'a [Hint], kv: &Kv, now: i64) -> HintPick<'a> {
+    // Keep escalating the hint the player is working through
+    for hint in hints {
+        let state = HintState::from_kv(kv, hint);
+        if state.seen > 0 && state.tier < hint.last_tier() {
+            return match hint.availability(&state, now) {
+                HintAvailability::Ready => HintPick::Show(hint),
+                HintAvailability::CoolingDown(secs) => HintPick::Wait(secs),
+                HintAvailability::Exhausted => continue,
+            };
+        }
+    }
+
+    let mut best: Option<(u32, bool, &Hint)> = None;
+    let mut wait: Option<u64> = None;
+
+    for hint in hints {
+        let state = HintState::from_kv(kv, hint);
+        match hint.availability(&state, now) {
+            HintAvailability::Ready => {
+                let key = (state.seen, hint.when != "manual");
+                if best.is_none_or(|(seen, auto, _)| key < (seen, auto)) {
+                    best = Some((key.0, key.1, hint));
+                }
+            }
+            HintAvailability::CoolingDown(secs) => wait = Some(wait.map_or(secs, |w| w.min(secs))),
+            HintAvailability::Exhausted => {}
+        }
+    }
+
+    match (best, wait) {
+        (Some((_, _, hint)), _) => HintPick::Show(hint),
+        (None, Some(secs)) => HintPick::Wait(secs),
+        (None, None) => HintPick::None,
+    }
+}
+
 pub struct RoomService {
     room_repo: Arc<dyn RoomRepo>,
     realm_repo: Arc<dyn RealmRepo>,
@@ -52,78 +147,172 @@ impl RoomService {
         Ok(room_id)
     }
 
+    /// Shows the first hint for `trigger` the player is eligible for (not exhausted, not
+    /// cooling down), in declaration order
     pub async fn hint_consider(&self, cursor: &Cursor, trigger: &str) -> AppResult<Option<String>> {
-        let current_visit = cursor.room.visit_count;
-        let rv = &cursor.room;
-        let room_id = cursor.room_id;
-        let account_id = cursor.account_id;
-        let realm_id = cursor.realm_id;
+        let kv = self.hint_progress(cursor).await?;
+        let now = chrono::Utc::now().timestamp();
 
-        let mut result = None;
+        let hint = cursor
+            .room
+            .blueprint
+            .hints
+            .iter()
+            .filter(|hint| hint.when == trigger)
+            .find(|hint| hint.availability(&HintState::from_kv(&kv, hint), now) == HintAvailability::Ready);
 
-        for hint in rv.blueprint.hints.iter() {
-            if hint.when == trigger {
-                let shown = rv.room_kv.get_bool(&format!("hint_shown_{}", hint.id), false);
+        match hint {
+            Some(hint) => Ok(Some(self.show_hint(cursor, &kv, hint, now).await?)),
+            None => Ok(None),
+        }
+    }
 
-                if hint.once.unwrap_or(false) && shown {
-                    continue; // Already shown
-                }
+    /// Shows a random eligible hint for `trigger`
+    pub async fn hint_trigger(&self, cursor: &Cursor, trigger: &str) -> AppResult<Option<String>> {
+        let kv = self.hint_progress(cursor).await?;
+        let now = chrono::Utc::now().timestamp();
 
-                // Check cooldown
-                if let Some(cooldown) = hint.cooldown {
-                    let last_shown_visit = rv.room_kv.get_num::<i64>(&format!("hint_last_visit_{}", hint.id), 0);
-                    if current_visit - last_shown_visit < cooldown as i64 {
-                        continue; // Still in cooldown
-                    }
-                }
+        let hints: Vec<&Hint> = cursor
+            .room
+            .blueprint
+            .hints
+            .iter()
+            .filter(|hint| hint.when == trigger)
+            .filter(|hint| hint.availability(&HintState::from_kv(&kv, hint), now) == HintAvailability::Ready)
+            .collect();
 
-                // Return the hint
-                result = Some(format!("{{c:cyan:bright_cyan}}Hint: {}{{c}}", hint.text));
-
-                // Mark as shown
-                if hint.once.unwrap_or(false) {
-                    self.user_repo
-                        .set_room_kv(
-                            realm_id,
-                            room_id,
-                            account_id,
-                            &format!("hint_shown_{}", hint.id),
-                            &serde_json::Value::Bool(true),
-                        )
-                        .await?;
-                }
+        let hint = {
+            let mut rng = rand::rng();
+            hints.choose(&mut rng).copied()
+        };
+        match hint {
+            Some(hint) => Ok(Some(self.show_hint(cursor, &kv, hint, now).await?)),
+            None => Ok(None),
+        }
+    }
 
-                // Update last shown visit for cooldown
-                if hint.cooldown.is_some() {
-                    self.user_repo
-                        .set_room_kv(
-                            realm_id,
-                            room_id,
-                            account_id,
-                            &format!("hint_last_visit_{}", hint.id),
-                            &serde_json::Value::Number(current_visit.into()),
-                        )
-                        .await?;
-                }
+    /// Picks the best hint for a player asking with `hint`. A tiered hint that has started
+    /// escalating comes first; otherwise the eligible hint seen least often, preferring `manual`
+    /// hints, then declaration order.
+    pub async fn hint_request(&self, cursor: &Cursor) -> AppResult<HintOutcome> {
+        let kv = self.hint_progress(cursor).await?;
+        let now = chrono::Utc::now().timestamp();
+
+        match pick_hint(&cursor.room.blueprint.hints, &kv, now) {
+            HintPick::Show(hint) => Ok(HintOutcome::Hint(self.show_hint(cursor, &kv, hint, now).await?)),
+            HintPick::Wait(secs) => Ok(HintOutcome::CoolingDown(secs)),
+            HintPick::None => Ok(HintOutcome::NoHints),
+        }
+    }
+
+    /// Searches the current room: every obscured object the player has not found yet gets a
+    /// perception roll against its difficulty class. Found objects are revealed for this player
+    /// only. A room can only be searched once per cooldown.
+    pub async fn search_room(&self, cursor: &Cursor, perception: u32) -> AppResult<SearchOutcome> {
+        let now = chrono::Utc::now().timestamp();
+        let user_kv = self
+            .user_repo
+            .room_kv(cursor.realm_id, cursor.room_id, cursor.account_id)
+            .await?;
+
+        let cooldown = cursor
+            .room
+            .room_kv
+            .get("search_cooldown")
+            .and_then(|v| v.as_i64())
+            .unwrap_or(DEFAULT_SEARCH_COOLDOWN);
+        if let Some(last) = user_kv.get(SEARCHED_AT_KEY).and_then(|v| v.as_i64())
+            && now < last + cooldown
+        {
+            return Ok(SearchOutcome::CoolingDown((last + cooldown - now) as u64));
+        }
+        self.user_repo
+            .set_room_kv(
+                cursor.realm_id,
+                cursor.room_id,
+                cursor.account_id,
+                SEARCHED_AT_KEY,
+                &serde_json::Value::from(now),
+            )
+            .await?;
+
+        let mut found = Vec::new();
+        for obj in &cursor.room.objects {
+            let Discovery::Obscured { dc } = obj.discovery else {
+                continue;
+            };
+            if obj.flags.revealed {
+                continue;
+            }
+
+            let roll = rand::rng().random_range(1..=20);
+            if perception_check(roll, perception, dc) {
+                self.user_repo
+                    .set_object_kv(
+                        cursor.realm_id,
+                        cursor.account_id,
+                        obj.id,
+                        "revealed",
+                        &serde_json::Value::Bool(true),
+                    )
+                    .await?;
+                found.push(obj.short.clone());
             }
         }
 
-        Ok(result)
+        Ok(SearchOutcome::Searched { found })
     }
 
-    pub async fn hint_trigger(&self, cursor: &Cursor, trigger: &str) -> AppResult<Option<String>> {
-        let rv = &cursor.room;
+    /// Looks behind, under or over the object `key`: everything stashed there is revealed for this
+    /// player. Returns the short descriptions of what is there.
+    pub async fn look_at_place(&self, cursor: &Cursor, place: Place, key: &str) -> AppResult<Vec<String>> {
+        let mut found = Vec::new();
+        for obj in &cursor.room.objects {
+            if !obj.discovery.is_stashed(place, key) {
+                continue;
+            }
+            if !obj.flags.revealed {
+                self.user_repo
+                    .set_object_kv(
+                        cursor.realm_id,
+                        cursor.account_id,
+                        obj.id,
+                        "revealed",
+                        &serde_json::Value::Bool(true),
+                    )
+                    .await?;
+            }
+            found.push(obj.short.clone());
+        }
 
-        // collect all entries that have hint.when == trigger
-        let hints: Vec<_> = rv.blueprint.hints.iter().filter(|hint| hint.when == trigger).collect();
+        Ok(found)
+    }
 
-        if hints.is_empty() {
-            Ok(None)
-        } else {
-            let mut rng = rand::rng();
-            let hint = hints.choose(&mut rng).unwrap();
-            Ok(Some(format!("{{c:cyan:bright_cyan}}Hint: {}{{c}}", hint.text)))
-        }
+    /// The player's hint progress for the current room, read fresh so cooldowns and limits hold
+    /// even when the cursor is stale
+    async fn hint_progress(&self, cursor: &Cursor) -> AppResult<Kv> {
+        Ok(self
+            .user_repo
+            .room_kv(cursor.realm_id, cursor.room_id, cursor.account_id)
+            .await?)
+    }
+
+    /// Shows the next tier of the hint, records the progress and returns the text
+    async fn show_hint(&self, cursor: &Cursor, kv: &Kv, hint: &Hint, now: i64) -> AppResult<String> {
+        let state = HintState::from_kv(kv, hint);
+        let tier = hint.next_tier(&state);
+        let state = state.shown(tier, now);
+        self.user_repo
+            .set_room_kv(
+                cursor.realm_id,
+                cursor.room_id,
+                cursor.account_id,
+                &hint.state_key(),
+                &serde_json::to_value(&state)?,
+            )
+            .await?;
+
+        Ok(format!("{{c:@hint}}Hint: {}{{c}}", hint.tier_text(tier)))
     }
 
     // Travel to the given room
@@ -168,6 +357,10 @@ impl RoomService {
             }
         }
 
+        if let Some(cue) = &c.room.blueprint.sounds.enter {
+            ctx.output.sound(cue).await;
+        }
+
         // Enter or First enter lua hooks
         self.lua_on_enter(ctx.clone()).await?;
 
@@ -215,8 +408,7 @@ impl RoomService {
                     account_id: ctx.account_id()?,
                     reply: tx,
                 })
-                .await
-                .map_err(Box::from)?;
+                .await?;
         } else {
             // Run on the first entry when there is no first enter hook
             // Run on each subsequent times
@@ -227,8 +419,7 @@ impl RoomService {
                     account_id: ctx.account_id()?,
                     reply: tx,
                 })
-                .await
-                .map_err(Box::from)?
+                .await?
                 // .map_err(|e| {
                 //     panic!("{}", e.to_string());
                 //     Box::from(e)
@@ -236,7 +427,7 @@ impl RoomService {
             ;
         }
 
-        match timeout(LUA_CMD_TIMEOUT, rx).await {
+        match timeout(ctx.registry.config().lua.command_timeout(), rx).await {
             Ok(Ok(lua_result)) => match lua_result {
                 LuaResult::Failed(msg) => {
                     let s = format!("{{c:yellow:bright_red}}Lua script failure: {msg}{{c}}");
@@ -275,16 +466,155 @@ impl RoomService {
                 account_id: ctx.account_id()?,
                 reply: tx,
             })
-            .await
-            .map_err(Box::from)?;
+            .await?;
 
-        if let Err(e) = timeout(LUA_CMD_TIMEOUT, rx).await {
+        if let Err(e) = timeout(ctx.registry.config().lua.command_timeout(), rx).await {
             ctx.output.system(format!("The room doesn't react ({e})")).await;
         }
 
         Ok(())
     }
 
+    /// Runs the room's `on_craft` hook, if it has one, after a player crafted `recipe`
+    pub async fn lua_on_craft(&self, ctx: Arc<CmdCtx>, recipe: &Recipe) -> AppResult<()> {
+        let cursor = ctx.cursor()?;
+        if cursor.room.scripts.get(&ScriptHook::OnCraft).is_none() {
+            return Ok(());
+        }
+
+        let (tx, rx) = oneshot::channel();
+        ctx.lua_tx
+            .send(LuaJob::OnCraft {
+                output_handle: ctx.output.clone(),
+                cursor: Box::new(cursor),
+                account_id: ctx.account_id()?,
+                recipe: Box::new(recipe.clone()),
+                reply: tx,
+            })
+            .await?;
+
+        match timeout(ctx.registry.config().lua.command_timeout(), rx).await {
+            Ok(Ok(LuaResult::Failed(msg))) => {
+                let s = format!("{{c:yellow:bright_red}}Lua script failure: {msg}{{c}}");
+                ctx.output.system(s).await;
+            }
+            Ok(_) => {}
+            Err(_elapsed) => {
+                let s = "{c:yellow:bright_red}The room doesn't react (script timed out){c}";
+                ctx.output.system(s).await;
+            }
+        }
+
+        Ok(())
+    }
+
+    /// Runs the object's on_read script, returning the pages it generated. None when the object
+    /// has no script, or the script returned nothing usable.
+    pub async fn lua_on_read(&self, ctx: Arc<CmdCtx>, obj: &ResolvedObject) -> AppResult<Option<Vec<String>>> {
+        if obj.on_read.as_deref().is_none_or(str::is_empty) {
+            return Ok(None);
+        }
+
+        let (tx, rx) = oneshot::channel();
+        ctx.lua_tx
+            .send(LuaJob::OnRead {
+                output_handle: ctx.output.clone(),
+                cursor: Box::new(ctx.cursor()?),
+                account_id: ctx.account_id()?,
+                obj: Box::new(obj.clone()),
+                reply: tx,
+            })
+            .await?;
+
+        match timeout(ctx.registry.config().lua.command_timeout(), rx).await {
+            Ok(Ok(LuaResult::Success(v))) => Ok(lua_pages(&v)),
+            Ok(Ok(LuaResult::Failed(msg))) => {
+                let s = format!("{{c:yellow:bright_red}}Lua script failure: {msg}{{c}}");
+                ctx.output.system(s).await;
+                Ok(None)
+            }
+            Ok(Err(_)) => Ok(None),
+            Err(_elapsed) => {
+                let s = "{c:yellow:bright_red}The text blurs before your eyes (script timed out){c}";
+                ctx.output.system(s).await;
+                Ok(None)
+            }
+        }
+    }
+
+    /// Offers `quantity` of an item to the object's on_receive script. Returns true when the script
+    /// accepts it by returning true; objects without a script refuse everything.
+    pub async fn lua_on_receive(
+        &self,
+        ctx: Arc<CmdCtx>,
+        obj: &ResolvedObject,
+        item: &ItemInstance,
+        quantity: i32,
+    ) -> AppResult<bool> {
+        if obj.on_receive.as_deref().is_none_or(str::is_empty) {
+            return Ok(false);
+        }
+
+        let (tx, rx) = oneshot::channel();
+        ctx.lua_tx
+            .send(LuaJob::OnReceive {
+                output_handle: ctx.output.clone(),
+                cursor: Box::new(ctx.cursor()?),
+                account_id: ctx.account_id()?,
+                obj: Box::new(obj.clone()),
+                item: Box::new(item.clone()),
+                quantity,
+                reply: tx,
+            })
+            .await?;
+
+        match timeout(ctx.registry.config().lua.command_timeout(), rx).await {
+            Ok(Ok(LuaResult::Success(v))) => Ok(matches!(v, mlua::Value::Boolean(true))),
+            Ok(Ok(LuaResult::Failed(msg))) => {
+                let s = format!("{{c:yellow:bright_red}}Lua script failure: {msg}{{c}}");
+                ctx.output.system(s).await;
+                Ok(false)
+            }
+            Ok(Err(_)) => Ok(false),
+            Err(_elapsed) => {
+                let s = "{c:yellow:bright_red}Nobody seems to notice (script timed out){c}";
+                ctx.output.system(s).await;
+                Ok(false)
+            }
+        }
+    }
+
+    /// Hands a line typed at a terminal object to its on_terminal script (None when switching it
+    /// on). Returns false when the terminal shuts: the script returned false, failed or timed out.
+    pub async fn lua_on_terminal(&self, ctx: Arc<CmdCtx>, obj: &ResolvedObject, line: Option<&str>) -> AppResult<bool> {
+        let (tx, rx) = oneshot::channel();
+        ctx.lua_tx
+            .send(LuaJob::OnTerminal {
+                output_handle: ctx.output.clone(),
+                cursor: Box::new(ctx.cursor()?),
+                account_id: ctx.account_id()?,
+                obj: Box::new(obj.clone()),
+                line: line.map(str::to_string),
+                reply: tx,
+            })
+            .await?;
+
+        match timeout(ctx.registry.config().lua.command_timeout(), rx).await {
+            Ok(Ok(LuaResult::Success(v))) => Ok(!matches!(v, mlua::Value::Boolean(false))),
+            Ok(Ok(LuaResult::Failed(msg))) => {
+                let s = format!("{{c:yellow:bright_red}}Lua script failure: {msg}{{c}}");
+                ctx.output.system(s).await;
+                Ok(false)
+            }
+            Ok(Err(_)) => Ok(false),
+            Err(_elapsed) => {
+                let s = "{c:yellow:bright_red}The screen freezes (script timed out){c}";
+                ctx.output.system(s).await;
+                Ok(false)
+            }
+        }
+    }
+
     pub async fn exit_by_direction(&self, room_id: RoomId, direction: Direction) -> AppResult<Option<ExitId>> {
         let exits = self.room_repo.room_exits(room_id).await?;
         for exit in exits {
@@ -351,6 +681,190 @@ impl RoomService {
         }
     }
 
+    /// Hides or reveals the exit in every player's view of the realm
+    pub async fn set_exit_hidden(
+        &self,
+        realm_id: RealmId,
+        room_id: RoomId,
+        exit_dir: Direction,
+        hidden: bool,
+    ) -> AppResult<()> {
+        if self.exit_by_direction(room_id, exit_dir.clone()).await?.is_none() {
+            return Err(DomainError::NotFound("Exit not found".into()));
+        }
+        self.realm_repo
+            .set_room_kv(
+                realm_id,
+                room_id,
+                &format!("exit.{}.visible", exit_dir),
+                &serde_json::Value::Bool(!hidden),
+            )
+            .await?;
+        Ok(())
+    }
+
+    /// Lets the exit lead to the room `to_key` of the realm in every player's view, or back to the
+    /// room of the blueprint when `to_key` is None
+    pub async fn redirect_exit(
+        &self,
+        realm_id: RealmId,
+        room_id: RoomId,
+        exit_dir: Direction,
+        to_key: Option<&str>,
+    ) -> AppResult<()> {
+        if self.exit_by_direction(room_id, exit_dir.clone()).await?.is_none() {
+            return Err(DomainError::NotFound("Exit not found".into()));
+        }
+        let value = match to_key {
+            Some(key) => {
+                let to_room_id = self
+                    .get_room_id_by_key(realm_id, key)
+                    .await?
+                    .ok_or_else(|| DomainError::NotFound(format!("Room '{}' not found", key)))?;
+                serde_json::to_value(ExitRedirect {
+                    room_id: to_room_id,
+                    room_key: key.to_string(),
+                })?
+            }
+            None => serde_json::Value::Null,
+        };
+        self.realm_repo
+            .set_room_kv(realm_id, room_id, &format!("exit.{}.to", exit_dir), &value)
+            .await?;
+        Ok(())
+    }
+
+    /// Puts the room in lockdown for `seconds` in every player's view of the realm, or lifts it
+    /// when `seconds` is 0. The expiry is stored as a timestamp, so it survives restarts. Returns the
+    /// new expiry when a lockdown was started or changed.
+    pub async fn set_lockdown(&self, realm_id: RealmId, room_id: RoomId, seconds: u64) -> AppResult<Option<i64>> {
+        let current = self.lockdown_until(realm_id, room_id).await?;
+
+        if seconds == 0 {
+            if let Some(until) = current {
+                self.lift_lockdown(realm_id, room_id, until).await?;
+            }
+            return Ok(None);
+        }
+
+        let until = chrono::Utc::now().timestamp() + seconds as i64;
+        if current == Some(until) {
+            return Ok(None);
+        }
+        self.realm_repo
+            .set_room_kv(realm_id, room_id, LOCKDOWN_UNTIL_KEY, &serde_json::Value::from(until))
+            .await?;
+        Ok(Some(until))
+    }
+
+    /// Stored lockdown expiry of the room, also when it already passed
+    pub async fn lockdown_until(&self, realm_id: RealmId, room_id: RoomId) -> AppResult<Option<i64>> {
+        let stored = self
+            .realm_repo
+            .room_kv_versioned(realm_id, room_id, LOCKDOWN_UNTIL_KEY)
+            .await?;
+        Ok(stored.and_then(|v| v.value.as_i64()))
+    }
+
+    /// Seconds until the lockdown of the room lifts, None when it is not in a timed lockdown
+    pub async fn lockdown_remaining(&self, realm_id: RealmId, room_id: RoomId) -> AppResult<Option<u64>> {
+        let until = self.lockdown_until(realm_id, room_id).await?;
+        Ok(lockdown_remaining(until, chrono::Utc::now().timestamp()))
+    }
+
+    /// Removes the lockdown expiring at `until`; a newer lockdown of the same room is kept. Returns
+    /// false when it was already lifted or replaced.
+    pub async fn lift_lockdown(&self, realm_id: RealmId, room_id: RoomId, until: i64) -> AppResult<bool> {
+        Ok(self
+            .realm_repo
+            .delete_room_kv_if(realm_id, room_id, LOCKDOWN_UNTIL_KEY, &serde_json::Value::from(until))
+            .await?)
+    }
+
+    /// All stored lockdowns as (realm, room, expiry), including expired ones not yet lifted
+    pub async fn lockdowns(&self) -> AppResult<Vec<(RealmId, RoomId, i64)>> {
+        let rows = self.realm_repo.room_kv_by_key(LOCKDOWN_UNTIL_KEY).await?;
+        Ok(rows
+            .into_iter()
+            .filter_map(|(realm_id, room_id, v)| v.as_i64().map(|until| (realm_id, room_id, until)))
+            .collect())
+    }
+
+    /// Blueprint data of a room of the realm, without anyone's view of it
+    pub async fn blueprint_room(&self, realm_id: RealmId, room_id: RoomId) -> AppResult<BlueprintRoom> {
+        let Some(realm) = self.realm_repo.get(realm_id).await? else {
+            return Err(DomainError::NotFound("Realm not found".into()));
+        };
+        Ok(self.room_repo.room_by_id(realm.bp_id, room_id).await?)
+    }
+
+    /// Where the vehicle room is; a vehicle that never moved is docked at its first stop
+    pub async fn vehicle_state(&self, realm_id: RealmId, room_id: RoomId) -> AppResult<VehicleState> {
+        let stored = self
+            .realm_repo
+            .room_kv_versioned(realm_id, room_id, VEHICLE_STATE_KEY)
+            .await?;
+        Ok(stored
+            .and_then(|v| serde_json::from_value(v.value).ok())
+            .unwrap_or_default())
+    }
+
+    /// Sends the vehicle off to stop `to`. Returns the new state, or None when it is already
+    /// there or travelling.
+    pub async fn depart_vehicle(
+        &self,
+        realm_id: RealmId,
+        room_id: RoomId,
+        vehicle: &Vehicle,
+        to: usize,
+    ) -> AppResult<Option<VehicleState>> {
+        let now = chrono::Utc::now().timestamp();
+        self.update_vehicle_state(realm_id, room_id, |state| state.depart(to, vehicle.travel, now))
+            .await
+    }
+
+    /// Docks a travelling vehicle at its destination once it is due. Returns the new state, or
+    /// None when it is not travelling or not there yet.
+    pub async fn arrive_vehicle(&self, realm_id: RealmId, room_id: RoomId) -> AppResult<Option<VehicleState>> {
+        let now = chrono::Utc::now().timestamp();
+        self.update_vehicle_state(realm_id, room_id, |state| state.arrive(now))
+            .await
+    }
+
+    /// All vehicles on their way somewhere, as (realm, vehicle room, state)
+    pub async fn vehicles_in_transit(&self) -> AppResult<Vec<(RealmId, RoomId, VehicleState)>> {
+        let rows = self.realm_repo.room_kv_by_key(VEHICLE_STATE_KEY).await?;
+        Ok(rows
+            .into_iter()
+            .filter_map(|(realm_id, room_id, v)| {
+                let state: VehicleState = serde_json::from_value(v).ok()?;
+                state.in_transit().then_some((realm_id, room_id, state))
+            })
+            .collect())
+    }
+
+    /// Applies `change` to the stored vehicle state; None from `change` leaves it as it is
+    async fn update_vehicle_state<F>(
+        &self,
+        realm_id: RealmId,
+        room_id: RoomId,
+        change: F,
+    ) -> AppResult<Option<VehicleState>>
+    where
+        F: Fn(&VehicleState) -> Option<VehicleState> + Send + Sync,
+    {
+        let mut changed = None;
+        self.update_room_state_shared(realm_id, room_id, VEHICLE_STATE_KEY, |current| {
+            let state: VehicleState = current
+                .and_then(|v| serde_json::from_value(v.clone()).ok())
+                .unwrap_or_default();
+            changed = change(&state);
+            Ok(serde_json::to_value(changed.unwrap_or(state))?)
+        })
+        .await?;
+        Ok(changed)
+    }
+
     pub async fn build_room_view(
         &self,
         realm_id: RealmId,
@@ -371,6 +885,7 @@ impl RoomService {
         // Get zone info
         let zone_room_kv = self.realm_repo.room_kv(realm_id, room_id).await?;
         let zone_obj_kv = self.realm_repo.obj_kv(realm_id, room_id).await?;
+        let spawned = self.realm_repo.spawned_objects(realm_id, room_id).await?;
 
         // get account info
         let user_room_kv = self.user_repo.room_kv(realm_id, room_id, account_id).await?;
@@ -380,7 +895,7 @@ impl RoomService {
         let zone_qty = HashMap::new();
         let user_qty = HashMap::new();
 
-        let rv = build_room_view_impl(
+        let mut rv = build_room_view_impl(
             &bp_room,
             bp_exits.as_slice(),
             bp_objs.as_slice(),
@@ -393,10 +908,42 @@ impl RoomService {
             &user_obj_kv,
             &user_qty,
         );
+        rv.add_spawned(&spawned);
 
         Ok(rv)
     }
 
+    /// Puts `obj` into the room for everyone in the realm, replacing an object spawned there
+    /// before with the same key. Blueprint objects of the room keep their keys.
+    pub async fn spawn_object(&self, realm_id: RealmId, room_id: RoomId, obj: &SpawnedObject) -> AppResult<()> {
+        validate_spawned(obj)?;
+
+        let bp_objs = self.room_repo.room_objects(room_id).await?;
+        if bp_objs.iter().any(|o| o.name == obj.key) {
+            return Err(DomainError::Conflict(format!(
+                "the room already has an object '{}'",
+                obj.key
+            )));
+        }
+        let spawned = self.realm_repo.spawned_objects(realm_id, room_id).await?;
+        if spawned.len() >= MAX_SPAWNED_OBJECTS_PER_ROOM && !spawned.iter().any(|s| s.key == obj.key) {
+            return Err(DomainError::Validation {
+                field: "key",
+                message: format!(
+                    "the room already has {} spawned objects, remove some first",
+                    MAX_SPAWNED_OBJECTS_PER_ROOM
+                ),
+            });
+        }
+
+        Ok(self.realm_repo.upsert_spawned_object(realm_id, room_id, obj).await?)
+    }
+
+    /// Removes a spawned object from the room, returns false when there was none with that key
+    pub async fn despawn_object(&self, realm_id: RealmId, room_id: RoomId, key: &str) -> AppResult<bool> {
+        Ok(self.realm_repo.delete_spawned_object(realm_id, room_id, key).await?)
+    }
+
     pub async fn set_object_state(
         &self,
         realm_id: RealmId,
@@ -421,4 +968,530 @@ impl RoomService {
         self.realm_repo.set_object_kv(realm_id, object_id, key, val).await?;
         Ok(())
     }
+
+    /// Counts a use of the object against its cooldown and maximum number of uses. Returns why the
+    /// object cannot be used instead when the limits refuse it; nothing is counted then.
+    pub async fn claim_object_use(
+        &self,
+        realm_id: RealmId,
+        room_id: RoomId,
+        account_id: AccountId,
+        obj: &ResolvedObject,
+    ) -> AppResult<Option<UseDenied>> {
+        let limits = obj.use_limits;
+        if !limits.is_limited() {
+            return Ok(None);
+        }
+        let now = chrono::Utc::now().timestamp();
+        let parse = |v: Option<&serde_json::Value>| -> ObjectUsage {
+            v.and_then(|v| serde_json::from_value(v.clone()).ok())
+                .unwrap_or_default()
+        };
+
+        match limits.scope {
+            UseScope::Player => {
+                let kv = self.user_repo.obj_kv(realm_id, room_id, account_id).await?;
+                let usage = parse(kv.get(&obj.key).and_then(|kv| kv.get(OBJECT_USAGE_KEY)));
+                let usage = match limits.try_use(&usage, now) {
+                    Ok(usage) => usage,
+                    Err(denied) => return Ok(Some(denied)),
+                };
+                self.user_repo
+                    .set_object_kv(
+                        realm_id,
+                        account_id,
+                        obj.id,
+                        OBJECT_USAGE_KEY,
+                        &serde_json::to_value(usage)?,
+                    )
+                    .await?;
+                Ok(None)
+            }
+            UseScope::Shared => {
+                // Two players using the object at once must not both get the last use
+                let mut denied = None;
+                let res =
+                    update_object_kv_cas(
+                        &*self.realm_repo,
+                        realm_id,
+                        obj.id,
+                        OBJECT_USAGE_KEY,
+                        |current| match limits.try_use(&parse(current), now) {
+                            Ok(usage) => Ok(serde_json::to_value(usage)?),
+                            Err(d) => {
+                                denied = Some(d);
+                                Err(DomainError::PreconditionFailed("object use denied"))
+                            }
+                        },
+                    )
+                    .await;
+                match (res, denied) {
+                    (_, Some(denied)) => Ok(Some(denied)),
+                    (Ok(_), None) => Ok(None),
+                    (Err(DomainError::Conflict(_)), None) => Err(DomainError::Conflict(format!(
+                        "object '{}' is being used by someone else",
+                        obj.key
+                    ))),
+                    (Err(e), None) => Err(e),
+                }
+            }
+        }
+    }
+
+    /// Read-modify-write a shared (realm level) object state value. The `update` closure receives the
+    /// current value (if any) and returns the new value. When another player writes the same key in
+    /// between, the update is retried against the fresh value. Returns the value that was stored.
+    pub async fn update_object_state_shared<F>(
+        &self,
+        realm_id: RealmId,
+        object_id: ObjectId,
+        key: &str,
+        update: F,
+    ) -> AppResult<serde_json::Value>
+    where
+        F: FnMut(Option<&serde_json::Value>) -> AppResult<serde_json::Value> + Send,
+    {
+        update_object_kv_cas(&*self.realm_repo, realm_id, object_id, key, update).await
+    }
+
+    /// Read-modify-write a shared (realm level) room state value. See `update_object_state_shared`.
+    pub async fn update_room_state_shared<F>(
+        &self,
+        realm_id: RealmId,
+        room_id: RoomId,
+        key: &str,
+        update: F,
+    ) -> AppResult<serde_json::Value>
+    where
+        F: FnMut(Option<&serde_json::Value>) -> AppResult<serde_json::Value> + Send,
+    {
+        update_room_kv_cas(&*self.realm_repo, realm_id, room_id, key, update).await
+    }
+}
+
+/// Compare-and-swap loop behind `update_object_state_shared`: reads the value with its version,
+/// and writes the update only while that version is still current. Gives up with a conflict after
+/// `SHARED_KV_CAS_RETRIES` lost races.
+async fn update_object_kv_cas<F>(
+    repo: &dyn RealmRepo,
+    realm_id: RealmId,
+    object_id: ObjectId,
+    key: &str,
+    mut update: F,
+) -> AppResult<serde_json::Value>
+where
+    F: FnMut(Option<&serde_json::Value>) -> AppResult<serde_json::Value> + Send,
+{
+    for _ in 0..SHARED_KV_CAS_RETRIES {
+        let current = repo.object_kv_versioned(realm_id, object_id, key).await?;
+        let new_value = update(current.as_ref().map(|c| &c.value))?;
+
+        let expected_version = current.map(|c| c.version);
+        if repo
+            .cas_object_kv(realm_id, object_id, key, &new_value, expected_version)
+            .await?
+        {
+            return Ok(new_value);
+        }
+    }
+
+    Err(DomainError::Conflict(format!(
+        "object state '{}' is being changed by someone else",
+        key
+    )))
+}
+
+/// Compare-and-swap loop behind `update_room_state_shared`, see `update_object_kv_cas`
+async fn update_room_kv_cas<F>(
+    repo: &dyn RealmRepo,
+    realm_id: RealmId,
+    room_id: RoomId,
+    key: &str,
+    mut update: F,
+) -> AppResult<serde_json::Value>
+where
+    F: FnMut(Option<&serde_json::Value>) -> AppResult<serde_json::Value> + Send,
+{
+    for _ in 0..SHARED_KV_CAS_RETRIES {
+        let current = repo.room_kv_versioned(realm_id, room_id, key).await?;
+        let new_value = update(current.as_ref().map(|c| &c.value))?;
+
+        let expected_version = current.map(|c| c.version);
+        if repo
+            .cas_room_kv(realm_id, room_id, key, &new_value, expected_version)
+            .await?
+        {
+            return Ok(new_value);
+        }
+    }
+
+    Err(DomainError::Conflict(format!(
+        "room state '{}' is being changed by someone else",
+        key
+    )))
+}
+
+/// Spawned object keys look like blueprint object keys; players need something to call it
+fn validate_spawned(obj: &SpawnedObject) -> AppResult<()> {
+    let valid_key = !obj.key.is_empty()
+        && obj.key.len() <= 64
+        && obj.key.starts_with(|c: char| c.is_ascii_lowercase())
+        && obj
+            .key
+            .chars()
+            .all(|c| c.is_ascii_lowercase() || c.is_ascii_digit() || c == '_');
+    if !valid_key {
+        return Err(DomainError::Validation {
+            field: "key",
+            message: format!(
+                "'{}' is not a valid object key, use lowercase letters, digits and '_'",
+                obj.key
+            ),
+        });
+    }
+    if obj.short.trim().is_empty() {
+        return Err(DomainError::Validation {
+            field: "short",
+            message: format!("object '{}' needs a short description", obj.key),
+        });
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::db::DbResult;
+    use crate::models::realm::Realm;
+    use crate::models::room::VersionedValue;
+    use parking_lot::Mutex;
+    use serde_json::json;
+
+    /// Realm repo with a single shared KV value, where another writer gets in first on the next
+    /// `races` compare-and-swap attempts
+    struct RacingRealmRepo {
+        races: Mutex<usize>,
+        attempts: Mutex<usize>,
+        stored: Mutex<Option<VersionedValue>>,
+    }
+
+    impl RacingRealmRepo {
+        fn new(races: usize) -> Self {
+            Self {
+                races: Mutex::new(races),
+                attempts: Mutex::new(0),
+                stored: Mutex::new(None),
+            }
+        }
+
+        fn read(&self) -> Option<VersionedValue> {
+            self.stored.lock().clone()
+        }
+
+        fn cas(&self, value: &serde_json::Value, expected_version: Option<i64>) -> bool {
+            *self.attempts.lock() += 1;
+            let mut stored = self.stored.lock();
+            let mut races = self.races.lock();
+            if *races > 0 {
+                *races -= 1;
+                let version = stored.as_ref().map_or(1, |s| s.version + 1);
+                *stored = Some(VersionedValue {
+                    value: json!("theirs"),
+                    version,
+                });
+                return false;
+            }
+            if stored.as_ref().map(|s| s.version) != expected_version {
+                return false;
+            }
+            *stored = Some(VersionedValue {
+                value: value.clone(),
+                version: expected_version.map_or(1, |v| v + 1),
+            });
+            true
+        }
+    }
+
+    #[async_trait::async_trait]
+    impl RealmRepo for RacingRealmRepo {
+        async fn object_kv_versioned(&self, _: RealmId, _: ObjectId, _: &str) -> DbResult<Option<VersionedValue>> {
+            Ok(self.read())
+        }
+        async fn cas_object_kv(
+            &self,
+            _: RealmId,
+            _: ObjectId,
+            _: &str,
+            value: &serde_json::Value,
+            expected_version: Option<i64>,
+        ) -> DbResult<bool> {
+            Ok(self.cas(value, expected_version))
+        }
+        async fn room_kv_versioned(&self, _: RealmId, _: RoomId, _: &str) -> DbResult<Option<VersionedValue>> {
+            Ok(self.read())
+        }
+        async fn cas_room_kv(
+            &self,
+            _: RealmId,
+            _: RoomId,
+            _: &str,
+            value: &serde_json::Value,
+            expected_version: Option<i64>,
+        ) -> DbResult<bool> {
+            Ok(self.cas(value, expected_version))
+        }
+
+        async fn get(&self, _: RealmId) -> DbResult<Option<Realm>> {
+            unimplemented!()
+        }
+        async fn get_by_key(&self, _: &str) -> DbResult<Option<Realm>> {
+            unimplemented!()
+        }
+        async fn create(&self, _: Realm) -> DbResult<Realm> {
+            unimplemented!()
+        }
+        async fn find_by_owner(&self, _: AccountId) -> DbResult<Vec<Realm>> {
+            unimplemented!()
+        }
+        async fn list_public(&self) -> DbResult<Vec<(String, Realm)>> {
+            unimplemented!()
+        }
+        async fn record_start(&self, _: RealmId, _: AccountId) -> DbResult<()> {
+            unimplemented!()
+        }
+        async fn complete(&self, _: RealmId, _: AccountId) -> DbResult<bool> {
+            unimplemented!()
+        }
+        async fn delete(&self, _: RealmId) -> DbResult<bool> {
+            unimplemented!()
+        }
+        async fn reset(&self, _: RealmId) -> DbResult<bool> {
+            unimplemented!()
+        }
+        async fn room_kv(&self, _: RealmId, _: RoomId) -> DbResult<Kv> {
+            unimplemented!()
+        }
+        async fn obj_kv(&self, _: RealmId, _: RoomId) -> DbResult<HashMap<String, Kv>> {
+            unimplemented!()
+        }
+        async fn set_room_kv(&self, _: RealmId, _: RoomId, _: &str, _: &serde_json::Value) -> DbResult<()> {
+            unimplemented!()
+        }
+        async fn set_object_kv(&self, _: RealmId, _: ObjectId, _: &str, _: &serde_json::Value) -> DbResult<()> {
+            unimplemented!()
+        }
+        async fn delete_room_kv_if(&self, _: RealmId, _: RoomId, _: &str, _: &serde_json::Value) -> DbResult<bool> {
+            unimplemented!()
+        }
+        async fn room_kv_by_key(&self, _: &str) -> DbResult<Vec<(RealmId, RoomId, serde_json::Value)>> {
+            unimplemented!()
+        }
+        async fn set_exit_locked(&self, _: RealmId, _: RoomId, _: ExitId, _: bool) -> DbResult<()> {
+            unimplemented!()
+        }
+        async fn incr_counter(&self, _: RealmId, _: Option<AccountId>, _: &str, _: i64) -> DbResult<i64> {
+            unimplemented!()
+        }
+        async fn counter(&self, _: RealmId, _: Option<AccountId>, _: &str) -> DbResult<i64> {
+            unimplemented!()
+        }
+        async fn spawned_objects(&self, _: RealmId, _: RoomId) -> DbResult<Vec<SpawnedObject>> {
+            unimplemented!()
+        }
+        async fn upsert_spawned_object(&self, _: RealmId, _: RoomId, _: &SpawnedObject) -> DbResult<()> {
+            unimplemented!()
+        }
+        async fn delete_spawned_object(&self, _: RealmId, _: RoomId, _: &str) -> DbResult<bool> {
+            unimplemented!()
+        }
+    }
+
+    /// Adds one to the stored number, counting how often it was asked to
+    fn increment(calls: &mut usize) -> impl FnMut(Option<&serde_json::Value>) -> AppResult<serde_json::Value> + Send {
+        move |current| {
+            *calls += 1;
+            Ok(json!(current.and_then(|v| v.as_i64()).unwrap_or(0) + 1))
+        }
+    }
+
+    #[tokio::test]
+    async fn t_cas_retries_after_losing_a_race() {
+        let repo = RacingRealmRepo::new(2);
+        let mut calls = 0;
+        let stored = update_object_kv_cas(&repo, RealmId::new(), ObjectId::new(), "count", increment(&mut calls))
+            .await
+            .unwrap();
+
+        // Recomputed against the other writer's value each time, written on the third attempt
+        assert_eq!(stored, json!(1));
+        assert_eq!(calls, 3);
+        assert_eq!(*repo.attempts.lock(), 3);
+        assert_eq!(
+            repo.read(),
+            Some(VersionedValue {
+                value: json!(1),
+                version: 3
+            })
+        );
+    }
+
+    #[tokio::test]
+    async fn t_cas_gives_up_with_a_conflict() {
+        let repo = RacingRealmRepo::new(SHARED_KV_CAS_RETRIES);
+        let mut calls = 0;
+        let err = update_object_kv_cas(&repo, RealmId::new(), ObjectId::new(), "lever", increment(&mut calls))
+            .await
+            .unwrap_err();
+
+        assert!(matches!(err, DomainError::Conflict(_)));
+        assert_eq!(
+            err.to_string(),
+            "conflict: object state 'lever' is being changed by someone else"
+        );
+        assert_eq!(*repo.attempts.lock(), SHARED_KV_CAS_RETRIES);
+        assert_eq!(repo.read().map(|v| v.value), Some(json!("theirs")));
+
+        let repo = RacingRealmRepo::new(SHARED_KV_CAS_RETRIES);
+        let err = update_room_kv_cas(&repo, RealmId::new(), RoomId::new(), "alarm", increment(&mut calls))
+            .await
+            .unwrap_err();
+        assert_eq!(
+            err.to_string(),
+            "conflict: room state 'alarm' is being changed by someone else"
+        );
+
+        // One race fewer and the last attempt lands
+        let repo = RacingRealmRepo::new(SHARED_KV_CAS_RETRIES - 1);
+        assert!(
+            update_room_kv_cas(&repo, RealmId::new(), RoomId::new(), "alarm", increment(&mut calls))
+                .await
+                .is_ok()
+        );
+    }
+
+    #[tokio::test]
+    async fn t_cas_update_error_writes_nothing() {
+        let repo = RacingRealmRepo::new(0);
+        let err = update_object_kv_cas(&repo, RealmId::new(), ObjectId::new(), "lever", |_| {
+            Err(DomainError::PreconditionFailed("expected value mismatch"))
+        })
+        .await
+        .unwrap_err();
+
+        assert!(matches!(err, DomainError::PreconditionFailed(_)));
+        assert_eq!(*repo.attempts.lock(), 0);
+        assert_eq!(repo.read(), None);
+    }
+
+    fn hint(id: &str, when: &str, once: bool, cooldown: Option<u32>) -> Hint {
+        Hint {
+            id: id.into(),
+            once: Some(once),
+            text: id.into(),
+            when: when.into(),
+            cooldown,
+            limit: None,
+            tiers: Vec::new(),
+            tier_delay: None,
+        }
+    }
+
+    fn picked<'a>(pick: HintPick<'a>) -> Option<&'a str> {
+        match pick {
+            HintPick::Show(h) => Some(h.id.as_str()),
+            _ => None,
+        }
+    }
+
+    #[test]
+    fn t_pick_hint_prefers_least_seen_manual() {
+        let hints = vec![
+            hint("enter", "enter", false, None),
+            hint("manual", "manual", false, None),
+            hint("seen", "manual", false, None),
+        ];
+        let mut kv = Kv::default();
+        assert_eq!(picked(pick_hint(&hints, &kv, 0)), Some("manual"));
+
+        kv.insert("__hint_manual".into(), json!({"seen": 1, "last_shown_at": 0}));
+        kv.insert("__hint_seen".into(), json!({"seen": 1, "last_shown_at": 0}));
+        assert_eq!(picked(pick_hint(&hints, &kv, 0)), Some("enter"));
+    }
+
+    #[test]
+    fn t_pick_hint_waits_for_cooldown() {
+        let hints = vec![hint("a", "manual", true, None), hint("b", "manual", false, Some(60))];
+        let mut kv = Kv::default();
+        kv.insert(
+            "__hint_a".into(),
+            json!({"seen": 1, "tier_seen": 1, "last_shown_at": 0}),
+        );
+        kv.insert(
+            "__hint_b".into(),
+            json!({"seen": 1, "tier_seen": 1, "last_shown_at": 100}),
+        );
+
+        assert!(matches!(pick_hint(&hints, &kv, 110), HintPick::Wait(50)));
+        assert_eq!(picked(pick_hint(&hints, &kv, 160)), Some("b"));
+
+        let none: Vec<Hint> = vec![hint("a", "manual", true, None)];
+        assert!(matches!(pick_hint(&none, &kv, 0), HintPick::None));
+    }
+
+    #[test]
+    fn t_pick_hint_keeps_escalating() {
+        let mut tiered = hint("tiered", "manual", false, None);
+        tiered.tiers = vec!["clearer".into(), "solution".into()];
+        tiered.tier_delay = Some(20);
+        let hints = vec![hint("other", "manual", false, None), tiered];
+
+        let mut kv = Kv::default();
+        kv.insert(
+            "__hint_tiered".into(),
+            json!({"seen": 1, "tier": 0, "tier_seen": 1, "last_shown_at": 100}),
+        );
+        assert!(matches!(pick_hint(&hints, &kv, 110), HintPick::Wait(10)));
+        assert_eq!(picked(pick_hint(&hints, &kv, 120)), Some("tiered"));
+
+        kv.insert(
+            "__hint_tiered".into(),
+            json!({"seen": 3, "tier": 2, "tier_seen": 1, "last_shown_at": 100}),
+        );
+        assert_eq!(picked(pick_hint(&hints, &kv, 120)), Some("other"));
+    }
+
+    #[test]
+    fn t_lockdown_remaining() {
+        assert_eq!(lockdown_remaining(None, 100), None);
+        assert_eq!(lockdown_remaining(Some(90), 100), None);
+        assert_eq!(lockdown_remaining(Some(100), 100), None);
+        assert_eq!(lockdown_remaining(Some(130), 100), Some(30));
+    }
+
+    #[test]
+    fn t_perception_check() {
+        // Average perception: the bare roll must meet the dc
+        assert!(perception_check(12, 10, 12));
+        assert!(!perception_check(11, 10, 12));
+        // Sharp eyes: +3
+        assert!(perception_check(9, 16, 12));
+        // Poor eyes: -1
+        assert!(!perception_check(12, 9, 12));
+        assert!(perception_check(20, 0, 15));
+    }
+
+    #[test]
+    fn t_validate_spawned() {
+        let obj = |key: &str, short: &str| SpawnedObject {
+            id: ObjectId::new(),
+            key: key.into(),
+            short: short.into(),
+            description: String::new(),
+            nouns: Vec::new(),
+        };
+        assert!(validate_spawned(&obj("debris_2", "some debris")).is_ok());
+        assert!(validate_spawned(&obj("Debris", "some debris")).is_err());
+        assert!(validate_spawned(&obj("", "some debris")).is_err());
+        assert!(validate_spawned(&obj("debris", " ")).is_err());
+    }
 }
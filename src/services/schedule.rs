@@ -0,0 +1,74 @@
+use crate::db::repo::ScheduleRepo;
+use crate::error::{AppResult, DomainError};
+use crate::import_blueprint::compile_lua_chunk;
+use crate::models::schedule::{CronSchedule, EventAction, ScheduledEvent};
+use crate::models::types::{AccountId, RealmId};
+use chrono::{DateTime, Utc};
+use mlua::Lua;
+use std::sync::Arc;
+
+/// Scheduled announcements and realm scripts (see `models::schedule`)
+pub struct ScheduleService {
+    repo: Arc<dyn ScheduleRepo>,
+}
+
+impl ScheduleService {
+    pub fn new(repo: Arc<dyn ScheduleRepo>) -> Self {
+        Self { repo }
+    }
+
+    pub async fn list(&self) -> AppResult<Vec<ScheduledEvent>> {
+        Ok(self.repo.list().await?)
+    }
+
+    /// Adds an event after checking its schedule, and that Lua compiles. Lua events need the realm
+    /// they run in.
+    pub async fn add(
+        &self,
+        schedule: &str,
+        action: EventAction,
+        realm_id: Option<RealmId>,
+        created_by: AccountId,
+    ) -> AppResult<i64> {
+        let schedule = CronSchedule::parse(schedule).map_err(|message| DomainError::Validation {
+            field: "schedule",
+            message,
+        })?;
+
+        match &action {
+            EventAction::Announce(text) if text.trim().is_empty() => {
+                return Err(DomainError::Validation {
+                    field: "announce",
+                    message: "the announcement cannot be empty".into(),
+                });
+            }
+            EventAction::Lua(_) if realm_id.is_none() => {
+                return Err(DomainError::Validation {
+                    field: "lua",
+                    message: "Lua events need a realm to run in".into(),
+                });
+            }
+            EventAction::Lua(code) => compile_lua_chunk(&Lua::new(), "scheduled event", code)?,
+            EventAction::Announce(_) => {}
+        }
+
+        Ok(self.repo.insert(&schedule, &action, realm_id, created_by).await?)
+    }
+
+    /// Returns false when there is no such event
+    pub async fn remove(&self, id: i64) -> AppResult<bool> {
+        Ok(self.repo.delete(id).await?)
+    }
+
+    /// The events to run in `minute`. Each one is claimed, so it is returned only once even when
+    /// asked again in the same minute.
+    pub async fn take_due(&self, minute: DateTime<Utc>) -> AppResult<Vec<ScheduledEvent>> {
+        let mut due = Vec::new();
+        for ev in self.repo.list().await? {
+            if ev.due(minute) && self.repo.mark_run(ev.id, minute).await? {
+                due.push(ev);
+            }
+        }
+        Ok(due)
+    }
+}
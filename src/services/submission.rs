@@ -0,0 +1,123 @@
+use crate::db::repo::SubmissionRepo;
+use crate::error::{AppResult, DomainError};
+use crate::models::account::Account;
+use crate::models::blueprint::Blueprint;
+use crate::models::collaborator::BlueprintRight;
+use crate::models::submission::{MAX_COMMENT_LEN, Submission};
+use crate::models::types::AccountId;
+use crate::services::CollaboratorService;
+use std::sync::Arc;
+
+/// The blueprint moderation queue: authors submit, moderators approve or reject
+pub struct SubmissionService {
+    repo: Arc<dyn SubmissionRepo>,
+    collaborators: Arc<CollaboratorService>,
+}
+
+impl SubmissionService {
+    pub fn new(repo: Arc<dyn SubmissionRepo>, collaborators: Arc<CollaboratorService>) -> Self {
+        Self { repo, collaborators }
+    }
+
+    /// Puts the blueprint in the queue for `author`, who needs the publish right. Returns the
+    /// blueprint and the submission id.
+    pub async fn submit(&self, author: &Account, bp_key: &str) -> AppResult<(Blueprint, i64)> {
+        let bp = self
+            .collaborators
+            .require(author, bp_key, BlueprintRight::Publish)
+            .await?;
+        if !bp.status.can_submit() {
+            return Err(DomainError::Conflict(format!(
+                "'{}' is {} and cannot be submitted",
+                bp.key,
+                bp.status.as_str()
+            )));
+        }
+
+        match self.repo.submit(bp.id, author.id).await? {
+            Some(id) => Ok((bp, id)),
+            // Changed status between reading and submitting
+            None => Err(DomainError::Conflict(format!(
+                "'{}' cannot be submitted right now",
+                bp.key
+            ))),
+        }
+    }
+
+    /// A blueprint `account` works on, with its latest submission if any
+    pub async fn status(&self, account: &Account, bp_key: &str) -> AppResult<(Blueprint, Option<Submission>)> {
+        let (bp, _) = self.collaborators.require_member(account, bp_key).await?;
+        let latest = self.repo.latest(bp.id).await?;
+        Ok((bp, latest))
+    }
+
+    pub async fn pending(&self) -> AppResult<Vec<Submission>> {
+        Ok(self.repo.pending().await?)
+    }
+
+    /// Approves or rejects a pending submission. A rejection needs a comment for the author.
+    /// Returns the decided submission, or None when no pending submission has this id.
+    pub async fn decide(
+        &self,
+        id: i64,
+        moderator: &Account,
+        approve: bool,
+        comment: &str,
+    ) -> AppResult<Option<Submission>> {
+        let comment = validate_comment(comment, !approve)?;
+        if !self.repo.decide(id, moderator.id, approve, comment.as_deref()).await? {
+            return Ok(None);
+        }
+        Ok(self.repo.get(id).await?)
+    }
+
+    /// Decisions the owner has not seen yet; they count as seen once returned
+    pub async fn take_undelivered(&self, owner_id: AccountId) -> AppResult<Vec<Submission>> {
+        let decided = self.repo.undelivered(owner_id).await?;
+        if !decided.is_empty() {
+            let ids: Vec<i64> = decided.iter().map(|s| s.id).collect();
+            self.repo.mark_delivered(&ids).await?;
+        }
+        Ok(decided)
+    }
+
+    pub async fn mark_delivered(&self, id: i64) -> AppResult<()> {
+        Ok(self.repo.mark_delivered(&[id]).await?)
+    }
+}
+
+fn validate_comment(comment: &str, required: bool) -> AppResult<Option<String>> {
+    let comment = comment.trim();
+    if comment.is_empty() {
+        if required {
+            return Err(DomainError::Validation {
+                field: "comment",
+                message: "tell the author why the blueprint is rejected".into(),
+            });
+        }
+        return Ok(None);
+    }
+    if comment.chars().count() > MAX_COMMENT_LEN {
+        return Err(DomainError::Validation {
+            field: "comment",
+            message: format!("a comment may be at most {} characters", MAX_COMMENT_LEN),
+        });
+    }
+    Ok(Some(comment.to_string()))
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn t_validate_comment() {
+        assert_eq!(validate_comment("  ", false).unwrap(), None);
+        assert!(validate_comment("  ", true).is_err());
+        assert_eq!(
+            validate_comment(" Needs an exit back ", true).unwrap().as_deref(),
+            Some("Needs an exit back")
+        );
+        assert!(validate_comment(&"x".repeat(MAX_COMMENT_LEN + 1), false).is_err());
+    }
+}
@@ -0,0 +1,83 @@
+use crate::db::repo::{RealmRepo, RoomRepo, TutorialRepo};
+use crate::error::AppResult;
+use crate::models::account::Account;
+use crate::models::realm::{Realm, RealmKind};
+use crate::models::types::{AccountId, RealmId, RoomId};
+use chrono::Utc;
+use std::sync::Arc;
+
+/// The starter tutorial: every new account plays its own instance of the starter blueprint until
+/// a script calls `port4k.complete_tutorial()`. Only then can the player list and enter other
+/// realms.
+pub struct TutorialService {
+    repo: Arc<dyn TutorialRepo>,
+    realms: Arc<dyn RealmRepo>,
+    rooms: Arc<dyn RoomRepo>,
+}
+
+impl TutorialService {
+    pub fn new(repo: Arc<dyn TutorialRepo>, realms: Arc<dyn RealmRepo>, rooms: Arc<dyn RoomRepo>) -> Self {
+        Self { repo, realms, rooms }
+    }
+
+    /// Key of the account's own tutorial realm
+    pub fn instance_key(account_id: AccountId) -> String {
+        format!("tutorial-{}", account_id)
+    }
+
+    /// The realm and room the account starts in while the tutorial is not completed. The instance
+    /// is created from `starter_bp` on first use; later logins continue where the player left.
+    pub async fn enroll(&self, account: &Account, starter_bp: &str) -> AppResult<(Realm, RoomId)> {
+        let key = Self::instance_key(account.id);
+        let bp = self.rooms.blueprint_by_key(starter_bp).await?;
+
+        let realm = match self.realms.get_by_key(&key).await? {
+            Some(realm) => realm,
+            None => {
+                let realm = Realm {
+                    id: RealmId::new(),
+                    bp_id: bp.id,
+                    title: bp.title.clone(),
+                    kind: RealmKind::Live,
+                    created_at: Utc::now(),
+                    prompt: bp.prompt.clone(),
+                };
+                self.repo.create_instance(realm, &key, account.id).await?
+            }
+        };
+
+        let room_id = match (account.current_realm_id, account.current_room_id) {
+            (Some(realm_id), Some(room_id)) if realm_id == realm.id => room_id,
+            _ => {
+                self.repo.set_position(account.id, realm.id, bp.entry_room_id).await?;
+                bp.entry_room_id
+            }
+        };
+
+        Ok((realm, room_id))
+    }
+
+    /// Whether the account has finished the tutorial
+    pub fn is_completed(account: &Account) -> bool {
+        account.tutorial_completed_at.is_some()
+    }
+
+    /// Returns false when the tutorial was already completed
+    pub async fn complete(&self, account_id: AccountId) -> AppResult<bool> {
+        Ok(self.repo.complete(account_id).await?)
+    }
+
+    /// Moves the account's login position, so it comes back to `realm_id` next time
+    pub async fn set_position(&self, account_id: AccountId, realm_id: RealmId, room_id: RoomId) -> AppResult<()> {
+        Ok(self.repo.set_position(account_id, realm_id, room_id).await?)
+    }
+
+    /// Removes the account's tutorial instance once the player has left it. Returns false when
+    /// there was none.
+    pub async fn discard_instance(&self, account_id: AccountId) -> AppResult<bool> {
+        match self.realms.get_by_key(&Self::instance_key(account_id)).await? {
+            Some(realm) => Ok(self.realms.delete(realm.id).await?),
+            None => Ok(false),
+        }
+    }
+}
@@ -1,3 +1,16 @@
+pub mod ambience;
+pub mod clock;
+pub mod deletions;
+pub mod dropped;
+pub mod events;
+pub mod hazards;
 pub mod interactive;
+pub mod lockdown;
+pub mod maintenance;
+pub mod presence;
+pub mod random;
 pub mod registry;
+pub mod script_actions;
 pub mod session;
+pub mod vehicles;
+pub mod zone_kv;
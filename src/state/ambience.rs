@@ -0,0 +1,133 @@
+//! Zone ambience ticking over (see `models::ambience`).
+//!
+//! Only realms with players in them have weather: the interval of an event starts when the first
+//! player is seen in its realm, and is forgotten once the realm is empty. The step an event is at
+//! is kept in the zone state, so a restart carries on where it left off.
+
+use crate::error::AppResult;
+use crate::models::ambience::AmbientEvent;
+use crate::models::types::RealmId;
+use crate::state::registry::{ConnectedPlayer, Registry};
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+/// How often the tick looks for due ambient events
+const AMBIENCE_TICK: Duration = Duration::from_secs(1);
+
+/// When each event of each realm was last rolled
+#[derive(Debug, Default)]
+struct Schedule {
+    since: HashMap<(RealmId, String), Instant>,
+}
+
+impl Schedule {
+    /// Whether the event is due. The first call for a key only starts the clock.
+    fn due(&mut self, realm_id: RealmId, event: &AmbientEvent, now: Instant) -> bool {
+        let interval = Duration::from_secs(event.every as u64);
+        match self.since.get_mut(&(realm_id, event.id.clone())) {
+            None => {
+                self.since.insert((realm_id, event.id.clone()), now);
+                false
+            }
+            Some(last) if now.duration_since(*last) >= interval => {
+                *last = now;
+                true
+            }
+            Some(_) => false,
+        }
+    }
+
+    /// Forgets realms nobody is in anymore
+    fn retain(&mut self, occupied: &HashMap<RealmId, Vec<(ConnectedPlayer, String)>>) {
+        self.since.retain(|(realm_id, _), _| occupied.contains_key(realm_id));
+    }
+}
+
+/// Runs the ambient events of occupied realms, forever. Spawned once when the server starts.
+pub async fn run_ambience_tick(registry: Arc<Registry>) {
+    let mut interval = tokio::time::interval(AMBIENCE_TICK);
+    let mut schedule = Schedule::default();
+
+    loop {
+        interval.tick().await;
+        let now = Instant::now();
+
+        // Players by realm, with the key of the room they are in
+        let mut occupied: HashMap<RealmId, Vec<(ConnectedPlayer, String)>> = HashMap::new();
+        for p in registry.connected_where(|_| true) {
+            let Some(cursor) = p.sess.read().get_cursor() else {
+                continue;
+            };
+            occupied
+                .entry(cursor.realm_id)
+                .or_default()
+                .push((p.clone(), cursor.room.blueprint.key.clone()));
+        }
+        schedule.retain(&occupied);
+
+        for (realm_id, players) in &occupied {
+            if let Err(e) = run_realm(&registry, &mut schedule, *realm_id, players, now).await {
+                tracing::warn!(%realm_id, error = %e, "ambience: tick failed");
+            }
+        }
+    }
+}
+
+async fn run_realm(
+    registry: &Registry,
+    schedule: &mut Schedule,
+    realm_id: RealmId,
+    players: &[(ConnectedPlayer, String)],
+    now: Instant,
+) -> AppResult<()> {
+    let ambience = &registry.services.ambience;
+
+    for event in ambience.events(realm_id).await? {
+        if !schedule.due(realm_id, &event, now) || rand::random::<f64>() >= event.chance {
+            continue;
+        }
+        let Some(step) = ambience.advance(realm_id, &event).await? else {
+            continue;
+        };
+        let Some(msg) = &step.message else {
+            continue;
+        };
+        for (p, room_key) in players {
+            if event.reaches(room_key) {
+                p.output.line(msg).await;
+            }
+        }
+    }
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::ambience::AmbientStep;
+
+    #[test]
+    fn t_schedule_due_after_interval() {
+        let mut schedule = Schedule::default();
+        let realm_id = RealmId::new();
+        let event = AmbientEvent {
+            id: "storm".into(),
+            every: 60,
+            chance: 1.0,
+            random: false,
+            rooms: vec![],
+            steps: vec![AmbientStep::default()],
+        };
+        let t0 = Instant::now();
+
+        assert!(!schedule.due(realm_id, &event, t0));
+        assert!(!schedule.due(realm_id, &event, t0 + Duration::from_secs(59)));
+        assert!(schedule.due(realm_id, &event, t0 + Duration::from_secs(60)));
+        assert!(!schedule.due(realm_id, &event, t0 + Duration::from_secs(90)));
+
+        schedule.retain(&HashMap::new());
+        // Realm empty in between: the clock starts over
+        assert!(!schedule.due(realm_id, &event, t0 + Duration::from_secs(200)));
+    }
+}
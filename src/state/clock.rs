@@ -0,0 +1,56 @@
+//! The in-world clock of the server.
+//!
+//! There is one clock for all realms. It lives outside the registry so templates can show the
+//! time without one. It is started from the stored time when the server starts and saved
+//! regularly, so it only loses the last save interval on a crash.
+
+use crate::config::ClockConfig;
+use crate::models::clock::WorldClock;
+use crate::state::registry::Registry;
+use chrono::{DateTime, Utc};
+use once_cell::sync::Lazy;
+use parking_lot::RwLock;
+use std::sync::Arc;
+use std::time::Duration;
+
+/// How often the world time is saved
+const CLOCK_SAVE_INTERVAL: Duration = Duration::from_secs(30);
+
+/// Runs from the default epoch until the server starts it
+static CLOCK: Lazy<RwLock<WorldClock>> = Lazy::new(|| {
+    let cfg = ClockConfig::default();
+    let now = Utc::now();
+    RwLock::new(WorldClock::new(cfg.epoch().unwrap_or(now), now, cfg.speed))
+});
+
+/// The current in-world time
+pub fn world_time() -> DateTime<Utc> {
+    CLOCK.read().now()
+}
+
+/// Sets the clock to `world` from now on
+pub fn start(world: DateTime<Utc>, speed: f64) {
+    *CLOCK.write() = WorldClock::new(world, Utc::now(), speed);
+}
+
+/// Changes the speed of the clock without jumping in time
+pub fn set_speed(speed: f64) {
+    let mut clock = CLOCK.write();
+    if clock.speed() != speed {
+        clock.set_speed(speed, Utc::now());
+    }
+}
+
+/// Saves the world time, forever. Spawned once when the server starts, after resuming the clock.
+pub async fn run_clock_tick(registry: Arc<Registry>) {
+    let mut interval = tokio::time::interval(CLOCK_SAVE_INTERVAL);
+    // The first tick completes immediately, and there is nothing new to save yet
+    interval.tick().await;
+
+    loop {
+        interval.tick().await;
+        if let Err(e) = registry.services.clock.save().await {
+            tracing::warn!(error = %e, "clock: cannot save world time");
+        }
+    }
+}
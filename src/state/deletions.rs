@@ -0,0 +1,38 @@
+//! Carrying out account deletions once their cooldown has run out (see `AccountDeletionService`).
+//!
+//! An account that is online when its time comes is left alone until the player logs off, so a
+//! session never outlives its account.
+
+use crate::state::registry::Registry;
+use std::sync::Arc;
+use std::time::Duration;
+
+/// How often the tick looks for due deletions
+const DELETION_TICK: Duration = Duration::from_secs(60);
+
+/// Purges accounts whose deletion is due, forever. Spawned once when the server starts.
+pub async fn run_deletion_tick(registry: Arc<Registry>) {
+    let mut interval = tokio::time::interval(DELETION_TICK);
+    let deletion = &registry.services.account_deletion;
+
+    loop {
+        interval.tick().await;
+
+        let due = match deletion.due().await {
+            Ok(due) => due,
+            Err(e) => {
+                tracing::warn!(error = %e, "deletions: cannot load due deletions");
+                continue;
+            }
+        };
+        for account_id in due {
+            if !registry.connected_where(|a| a.id == account_id).is_empty() {
+                continue;
+            }
+            match deletion.purge(account_id, None).await {
+                Ok(()) => tracing::info!(%account_id, "deletions: account purged"),
+                Err(e) => tracing::warn!(%account_id, error = %e, "deletions: purge failed"),
+            }
+        }
+    }
+}
@@ -0,0 +1,70 @@
+//! Clearing away items players left lying around (see `models::blueprint::DropPolicy`).
+//!
+//! Every item put down in a room gets a timestamp. Once it has been left alone for as long as
+//! its blueprint allows, the tick despawns it or takes it back to where it was spawned, and
+//! players in the room see it go.
+
+use crate::models::inventory::ClearedItem;
+use crate::state::registry::Registry;
+use std::sync::Arc;
+use std::time::Duration;
+
+/// How often the tick looks for items that are due
+const DROPPED_TICK: Duration = Duration::from_secs(60);
+
+/// Clears away due dropped items, forever. Spawned once when the server starts.
+pub async fn run_dropped_item_tick(registry: Arc<Registry>) {
+    let mut interval = tokio::time::interval(DROPPED_TICK);
+
+    loop {
+        interval.tick().await;
+
+        let cleared = match registry.services.inventory.clear_dropped_items().await {
+            Ok(cleared) => cleared,
+            Err(e) => {
+                tracing::warn!(error = %e, "dropped items: cannot clear");
+                continue;
+            }
+        };
+        if !cleared.is_empty() {
+            tracing::debug!(count = cleared.len(), "dropped items: cleared");
+        }
+        for item in cleared {
+            let msg = cleared_message(&item);
+            for p in registry.players_in_realm_room(item.realm_id, item.room_id) {
+                p.output.line(msg.clone()).await;
+            }
+        }
+    }
+}
+
+/// What players in the room see when the item goes
+fn cleared_message(item: &ClearedItem) -> String {
+    if item.returned {
+        format!("Someone takes {} back to where it belongs.", item.text)
+    } else {
+        format!("Someone clears away {}.", item.text)
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use crate::models::types::{RealmId, RoomId};
+
+    #[test]
+    fn t_cleared_message() {
+        let mut item = ClearedItem {
+            realm_id: RealmId::new(),
+            room_id: RoomId::new(),
+            text: "a coin (x3)".into(),
+            returned: false,
+        };
+        assert_eq!(cleared_message(&item), "Someone clears away a coin (x3).");
+        item.returned = true;
+        assert_eq!(
+            cleared_message(&item),
+            "Someone takes a coin (x3) back to where it belongs."
+        );
+    }
+}
@@ -0,0 +1,95 @@
+//! Scheduled server events running at their time (see `models::schedule`).
+//!
+//! The tick looks at the schedules whenever the wall clock (UTC) enters a new minute. An event is
+//! claimed in the database before it runs, so it runs once per matching minute, also across
+//! restarts. Minutes the server was down for are not caught up on.
+
+use crate::lua::{LuaJob, LuaPool, LuaResult};
+use crate::models::schedule::{EventAction, ScheduledEvent};
+use crate::state::registry::Registry;
+use chrono::{DateTime, DurationRound, TimeDelta, Utc};
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::oneshot;
+use tokio::time::timeout;
+
+/// How often the tick looks whether a new minute started
+const EVENT_TICK: Duration = Duration::from_secs(1);
+
+/// Start of the minute `t` falls in
+fn minute_of(t: DateTime<Utc>) -> DateTime<Utc> {
+    t.duration_trunc(TimeDelta::minutes(1)).unwrap_or(t)
+}
+
+/// Runs due scheduled events, forever. Spawned once when the server starts.
+pub async fn run_event_tick(registry: Arc<Registry>, lua_tx: LuaPool) {
+    let mut interval = tokio::time::interval(EVENT_TICK);
+    let mut last_minute = None;
+
+    loop {
+        interval.tick().await;
+        let minute = minute_of(Utc::now());
+        if last_minute == Some(minute) {
+            continue;
+        }
+        last_minute = Some(minute);
+
+        let due = match registry.services.schedule.take_due(minute).await {
+            Ok(due) => due,
+            Err(e) => {
+                tracing::warn!(error = %e, "events: cannot load scheduled events");
+                continue;
+            }
+        };
+        for event in due {
+            run_event(&registry, &lua_tx, &event).await;
+        }
+    }
+}
+
+async fn run_event(registry: &Registry, lua_tx: &LuaPool, event: &ScheduledEvent) {
+    match &event.action {
+        EventAction::Announce(text) => {
+            for p in registry.connected_where(|_| true) {
+                p.output.system(format!("[announcement] {}", text)).await;
+            }
+        }
+        EventAction::Lua(code) => {
+            let Some(realm_id) = event.realm_id else {
+                tracing::warn!(event = event.id, "events: Lua event without a realm");
+                return;
+            };
+
+            let (tx, rx) = oneshot::channel();
+            let job = LuaJob::Scheduled {
+                realm_id,
+                name: format!("event:{}", event.id),
+                code: code.clone(),
+                reply: tx,
+            };
+            if lua_tx.send(job).await.is_err() {
+                tracing::warn!(event = event.id, "events: Lua worker is gone");
+                return;
+            }
+            match timeout(registry.config().lua.command_timeout(), rx).await {
+                Ok(Ok(LuaResult::Failed(e))) => tracing::warn!(event = event.id, error = %e, "events: Lua failed"),
+                Ok(Ok(_)) => {}
+                Ok(Err(_)) => tracing::warn!(event = event.id, "events: Lua worker dropped the job"),
+                Err(_) => tracing::warn!(event = event.id, "events: Lua timed out"),
+            }
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+    use chrono::TimeZone;
+
+    #[test]
+    fn t_minute_of() {
+        let t = Utc.with_ymd_and_hms(2025, 5, 2, 18, 30, 42).unwrap();
+        assert_eq!(minute_of(t), Utc.with_ymd_and_hms(2025, 5, 2, 18, 30, 0).unwrap());
+        assert_eq!(minute_of(minute_of(t)), minute_of(t));
+    }
+}
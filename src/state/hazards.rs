@@ -0,0 +1,262 @@
+//! Environmental hazards of rooms.
+//!
+//! Rooms can define hazards (gas, radiation, vacuum) in their YAML. A background tick applies each
+//! hazard to the players in the room once per its interval: a message, damage and/or being forced
+//! into another room. Carrying one of the `mitigated_by` items protects against it, unless it is
+//! broken; protecting wears the item down like any other use. The interval starts when a player
+//! is first seen in the room, so walking in is not punished right away.
+
+use crate::commands::CmdCtx;
+use crate::error::{AppResult, DomainError};
+use crate::lua::LuaPool;
+use crate::models::account::MAX_HEALTH;
+use crate::models::room::Hazard;
+use crate::models::types::{AccountId, RoomId};
+use crate::state::presence;
+use crate::state::registry::{ConnectedPlayer, Registry};
+use crate::state::session::Cursor;
+use std::collections::{HashMap, HashSet};
+use std::sync::Arc;
+use std::time::{Duration, Instant};
+
+/// How often the tick looks for due hazards
+const HAZARD_TICK: Duration = Duration::from_secs(1);
+
+/// A player exposed to one hazard (by index) of a room
+type ExposureKey = (AccountId, RoomId, usize);
+
+/// When each exposed player was last hit by each hazard
+#[derive(Debug, Default)]
+struct Exposure {
+    since: HashMap<ExposureKey, Instant>,
+}
+
+impl Exposure {
+    /// Whether the hazard is due for the player. The first call for a key only starts the clock.
+    fn due(&mut self, key: ExposureKey, interval: Duration, now: Instant) -> bool {
+        match self.since.get_mut(&key) {
+            None => {
+                self.since.insert(key, now);
+                false
+            }
+            Some(last) if now.duration_since(*last) >= interval => {
+                *last = now;
+                true
+            }
+            Some(_) => false,
+        }
+    }
+
+    /// Forgets players that left the room or went offline; coming back starts a fresh interval
+    fn retain(&mut self, present: &HashSet<ExposureKey>) {
+        self.since.retain(|k, _| present.contains(k));
+    }
+}
+
+/// Applies hazards to connected players, forever. Spawned once when the server starts.
+pub async fn run_hazard_tick(registry: Arc<Registry>, lua_tx: LuaPool) {
+    let mut interval = tokio::time::interval(HAZARD_TICK);
+    let mut exposure = Exposure::default();
+
+    loop {
+        interval.tick().await;
+        let now = Instant::now();
+
+        let mut present = HashSet::new();
+        let mut due = Vec::new();
+        for p in registry.connected_where(|_| true) {
+            let Some(cursor) = p.sess.read().get_cursor() else {
+                continue;
+            };
+            for (idx, hazard) in cursor.room.blueprint.hazards.iter().enumerate() {
+                let key = (cursor.account_id, cursor.room_id, idx);
+                present.insert(key);
+                if exposure.due(key, Duration::from_secs(hazard.interval as u64), now) {
+                    due.push((p.clone(), cursor.clone(), hazard.clone()));
+                }
+            }
+        }
+        exposure.retain(&present);
+
+        for (p, cursor, hazard) in due {
+            if let Err(e) = apply_hazard(&registry, &lua_tx, &p, &cursor, &hazard).await {
+                tracing::warn!(account_id = %cursor.account_id, room_id = %cursor.room_id, error = %e, "hazard failed");
+            }
+        }
+    }
+}
+
+async fn apply_hazard(
+    registry: &Arc<Registry>,
+    lua_tx: &LuaPool,
+    p: &ConnectedPlayer,
+    cursor: &Cursor,
+    hazard: &Hazard,
+) -> AppResult<()> {
+    let services = &registry.services;
+
+    for item in &hazard.mitigated_by {
+        let Some(item) = services
+            .inventory
+            .find_by_key(cursor.realm_id, cursor.account_id, item)
+            .await?
+        else {
+            continue;
+        };
+        if item.is_broken() {
+            continue;
+        }
+
+        if let Some(msg) = &hazard.mitigated_message {
+            p.output.line(msg).await;
+        }
+        // Protecting against a hazard counts as a use
+        if let Some(msg) = services
+            .inventory
+            .wear_item(&item)
+            .await?
+            .and_then(|d| d.wear_message())
+        {
+            p.output.line(msg).await;
+        }
+        return Ok(());
+    }
+
+    p.output.line(hazard.message()).await;
+
+    if hazard.damage > 0 {
+        let health = services
+            .account
+            .adjust_health(cursor.account_id, -(hazard.damage as i32))
+            .await?;
+        p.sess.write().set_health(health);
+        p.output
+            .system(format!(
+                "You take {} damage ({}/{} health).",
+                hazard.damage, health, MAX_HEALTH
+            ))
+            .await;
+    }
+
+    if let Some(key) = &hazard.move_to {
+        let room_id = services
+            .room
+            .get_room_id_by_key(cursor.realm_id, key)
+            .await?
+            .ok_or_else(|| DomainError::NotFound(format!("hazard target room '{}'", key)))?;
+        force_move(registry, lua_tx, p, cursor, room_id).await?;
+    }
+
+    Ok(())
+}
+
+/// Moves the player like `go` does, except that the room scripts cannot refuse
+pub(crate) async fn force_move(
+    registry: &Arc<Registry>,
+    lua_tx: &LuaPool,
+    p: &ConnectedPlayer,
+    cursor: &Cursor,
+    room_id: RoomId,
+) -> AppResult<()> {
+    let ctx = Arc::new(CmdCtx {
+        output: p.output.clone(),
+        registry: registry.clone(),
+        lua_tx: lua_tx.clone(),
+        sess: p.sess.clone(),
+    });
+    let room = &registry.services.room;
+
+    if let Err(e) = room.exit_room(ctx.clone()).await {
+        tracing::debug!(error = %e, "forced move: leave hook failed, moving anyway");
+    }
+    let new_cursor = room.create_cursor(cursor.realm_id, room_id, cursor.account_id).await?;
+    ctx.sess.write().set_cursor(Some(new_cursor.clone()));
+    room.enter_room(ctx.clone(), &new_cursor).await?;
+    presence::announce_departure(registry, cursor, &p.account.username, None).await;
+    presence::announce_arrival(registry, &new_cursor, &p.account.username, None).await;
+    presence::show_room(&ctx, true).await?;
+
+    Ok(())
+}
+
+/// Moves all of `players` from one room to another together, like `force_move` does one player.
+/// Every leave hook runs before any player arrives, and each room hears about the group in one
+/// line instead of player by player.
+pub(crate) async fn force_move_group(
+    registry: &Arc<Registry>,
+    lua_tx: &LuaPool,
+    players: &[ConnectedPlayer],
+    from: &Cursor,
+    room_id: RoomId,
+) -> AppResult<()> {
+    let room = &registry.services.room;
+    let ctxs: Vec<Arc<CmdCtx>> = players
+        .iter()
+        .map(|p| {
+            Arc::new(CmdCtx {
+                output: p.output.clone(),
+                registry: registry.clone(),
+                lua_tx: lua_tx.clone(),
+                sess: p.sess.clone(),
+            })
+        })
+        .collect();
+
+    for ctx in &ctxs {
+        if let Err(e) = room.exit_room(ctx.clone()).await {
+            tracing::debug!(error = %e, "forced move: leave hook failed, moving anyway");
+        }
+    }
+
+    let mut cursors = Vec::with_capacity(players.len());
+    for (p, ctx) in players.iter().zip(&ctxs) {
+        let cursor = room.create_cursor(from.realm_id, room_id, p.account.id).await?;
+        ctx.sess.write().set_cursor(Some(cursor.clone()));
+        cursors.push(cursor);
+    }
+    for (ctx, cursor) in ctxs.iter().zip(&cursors) {
+        room.enter_room(ctx.clone(), cursor).await?;
+    }
+
+    let names: Vec<String> = players.iter().map(|p| p.account.username.clone()).collect();
+    presence::announce_group_departure(registry, from.realm_id, from.room_id, &names).await;
+    presence::announce_group_arrival(registry, from.realm_id, room_id, &names).await;
+    for ctx in &ctxs {
+        presence::show_room(ctx, true).await?;
+    }
+
+    Ok(())
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn t_exposure_due_after_interval() {
+        let mut exp = Exposure::default();
+        let key = (AccountId::new(), RoomId::new(), 0);
+        let t0 = Instant::now();
+        let interval = Duration::from_secs(10);
+
+        assert!(!exp.due(key, interval, t0));
+        assert!(!exp.due(key, interval, t0 + Duration::from_secs(9)));
+        assert!(exp.due(key, interval, t0 + Duration::from_secs(10)));
+        assert!(!exp.due(key, interval, t0 + Duration::from_secs(15)));
+        assert!(exp.due(key, interval, t0 + Duration::from_secs(20)));
+    }
+
+    #[test]
+    fn t_exposure_resets_after_leaving() {
+        let mut exp = Exposure::default();
+        let key = (AccountId::new(), RoomId::new(), 0);
+        let t0 = Instant::now();
+        let interval = Duration::from_secs(10);
+
+        exp.due(key, interval, t0);
+        exp.retain(&HashSet::new());
+        // Back in the room: the clock starts over
+        assert!(!exp.due(key, interval, t0 + Duration::from_secs(30)));
+        assert!(exp.due(key, interval, t0 + Duration::from_secs(40)));
+    }
+}
@@ -1,9 +1,42 @@
+use crate::lua::lint::ChunkKind;
+use crate::models::types::RoomId;
+
 #[derive(Debug, Clone)]
 pub enum InteractiveState {
     None,
     LoginAskUsername,
-    LoginAskPassword { username: String },
+    LoginAskPassword {
+        username: String,
+    },
     Register(RegisterState),
+    /// Lines go to the on_terminal script of this object (key) in the current room
+    Terminal {
+        object: String,
+        /// Display name, for when the object is gone by the time the player steps away
+        name: String,
+    },
+    /// Waiting for the password that confirms `delete account`
+    DeleteAccountConfirm,
+    /// Collecting lines for `@room set body` or an object script, until a lone '.'
+    Editor(EditorState),
+}
+
+#[derive(Debug, Clone)]
+pub struct EditorState {
+    /// Room being edited; nothing is saved when the builder left it in the meantime
+    pub room_id: RoomId,
+    pub target: EditTarget,
+    pub lines: Vec<String>,
+}
+
+#[derive(Debug, Clone)]
+pub enum EditTarget {
+    RoomBody,
+    /// A Lua script of the object with this key
+    ObjectScript {
+        object: String,
+        kind: ChunkKind,
+    },
 }
 
 #[derive(Debug, Clone, Default)]
@@ -0,0 +1,124 @@
+//! Countdowns of timed room lockdowns.
+//!
+//! A lockdown is stored as an expiry timestamp in the shared room state (see
+//! `RoomService::set_lockdown`), so exits stay sealed across restarts without any task running.
+//! The countdown task only tells the players in the room how long is left, and removes the
+//! stored expiry once it passed. `resume_lockdowns` restarts the tasks after a restart.
+
+use crate::error::AppResult;
+use crate::models::types::{RealmId, RoomId};
+use crate::state::registry::Registry;
+use std::sync::Arc;
+use std::time::Duration;
+
+/// How often the countdown checks the stored lockdown
+const COUNTDOWN_TICK: Duration = Duration::from_secs(1);
+
+const LIFTED: &str = "The lockdown lifts and the exits unseal.";
+
+/// Restarts the countdowns of all stored lockdowns; expired ones are lifted right away by their
+/// first tick. Returns the number of countdowns started.
+pub async fn resume_lockdowns(registry: Arc<Registry>) -> AppResult<usize> {
+    let lockdowns = registry.services.room.lockdowns().await?;
+    let count = lockdowns.len();
+    for (realm_id, room_id, until) in lockdowns {
+        tokio::spawn(run_countdown(registry.clone(), realm_id, room_id, until));
+    }
+    Ok(count)
+}
+
+/// Counts down the lockdown expiring at `until`. Stops silently when the lockdown was replaced by
+/// another one (which runs its own countdown), and announces the lift when it expired or was
+/// lifted early.
+pub async fn run_countdown(registry: Arc<Registry>, realm_id: RealmId, room_id: RoomId, until: i64) {
+    let mut interval = tokio::time::interval(COUNTDOWN_TICK);
+    let mut last_announced = None;
+
+    loop {
+        interval.tick().await;
+
+        let stored = match registry.services.room.lockdown_until(realm_id, room_id).await {
+            Ok(stored) => stored,
+            Err(e) => {
+                tracing::warn!(%realm_id, %room_id, error = %e, "lockdown: cannot read state");
+                continue;
+            }
+        };
+        match stored {
+            Some(u) if u == until => {}
+            Some(_) => return,
+            None => {
+                broadcast(&registry, realm_id, room_id, LIFTED).await;
+                return;
+            }
+        }
+
+        let remaining = until - chrono::Utc::now().timestamp();
+        if remaining <= 0 {
+            match registry.services.room.lift_lockdown(realm_id, room_id, until).await {
+                Ok(true) => {
+                    broadcast(&registry, realm_id, room_id, LIFTED).await;
+                    return;
+                }
+                Ok(false) => {} // changed in between, the next tick finds out how
+                Err(e) => tracing::warn!(%realm_id, %room_id, error = %e, "lockdown: cannot lift"),
+            }
+            continue;
+        }
+
+        let remaining = remaining as u64;
+        if last_announced.is_none() || (should_announce(remaining) && last_announced != Some(remaining)) {
+            last_announced = Some(remaining);
+            broadcast(&registry, realm_id, room_id, &countdown_message(remaining)).await;
+        }
+    }
+}
+
+/// Announce every minute, every ten seconds during the last minute and each of the last five
+fn should_announce(remaining: u64) -> bool {
+    match remaining {
+        0 => false,
+        1..=5 => true,
+        6..=60 => remaining.is_multiple_of(10),
+        _ => remaining.is_multiple_of(60),
+    }
+}
+
+fn countdown_message(remaining: u64) -> String {
+    match remaining {
+        1 => "LOCKDOWN: the exits unseal in 1 second.".to_string(),
+        s if s < 60 || !s.is_multiple_of(60) => format!("LOCKDOWN: the exits unseal in {} seconds.", s),
+        60 => "LOCKDOWN: the exits unseal in 1 minute.".to_string(),
+        s => format!("LOCKDOWN: the exits unseal in {} minutes.", s / 60),
+    }
+}
+
+/// Sends a line to the players in this room of this realm
+async fn broadcast(registry: &Registry, realm_id: RealmId, room_id: RoomId, msg: &str) {
+    for p in registry.players_in_room(room_id) {
+        let in_realm = p.sess.read().get_cursor().is_some_and(|c| c.realm_id == realm_id);
+        if in_realm {
+            p.output.line(msg).await;
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn t_should_announce() {
+        let announced: Vec<u64> = (1..=130).rev().filter(|&s| should_announce(s)).collect();
+        assert_eq!(announced, vec![120, 60, 50, 40, 30, 20, 10, 5, 4, 3, 2, 1]);
+    }
+
+    #[test]
+    fn t_countdown_message() {
+        assert_eq!(countdown_message(1), "LOCKDOWN: the exits unseal in 1 second.");
+        assert_eq!(countdown_message(30), "LOCKDOWN: the exits unseal in 30 seconds.");
+        assert_eq!(countdown_message(60), "LOCKDOWN: the exits unseal in 1 minute.");
+        assert_eq!(countdown_message(180), "LOCKDOWN: the exits unseal in 3 minutes.");
+        assert_eq!(countdown_message(90), "LOCKDOWN: the exits unseal in 90 seconds.");
+    }
+}
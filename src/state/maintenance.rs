@@ -0,0 +1,39 @@
+//! Periodic garbage collection report (see `MaintenanceService`).
+//!
+//! The tick only looks: it logs the garbage it finds, and an admin decides whether to purge it
+//! with `@admin gc purge`.
+
+use crate::models::maintenance::total;
+use crate::state::registry::Registry;
+use std::sync::Arc;
+use std::time::Duration;
+
+/// How often the tick looks for garbage
+const GC_TICK: Duration = Duration::from_secs(6 * 60 * 60);
+
+/// Logs the garbage in the database, forever. Spawned once when the server starts.
+pub async fn run_gc_tick(registry: Arc<Registry>) {
+    let mut interval = tokio::time::interval(GC_TICK);
+
+    loop {
+        interval.tick().await;
+
+        let garbage = match registry.services.maintenance.find_garbage().await {
+            Ok(garbage) => garbage,
+            Err(e) => {
+                tracing::warn!(error = %e, "gc: cannot look for garbage");
+                continue;
+            }
+        };
+        if garbage.is_empty() {
+            continue;
+        }
+        tracing::warn!(
+            rows = total(&garbage),
+            "gc: garbage found, '@admin gc purge' deletes it"
+        );
+        for g in garbage {
+            tracing::info!(what = g.what, count = g.count, "gc: garbage");
+        }
+    }
+}
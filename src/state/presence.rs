@@ -0,0 +1,200 @@
+//! Who is in a room, and telling the others when someone comes or goes.
+//!
+//! Presence is not stored separately: a player is in the room their session's cursor points at,
+//! so it cannot go stale when a session drops. NPCs are the room's objects with a dialogue.
+
+use crate::commands::CmdCtx;
+use crate::error::AppResult;
+use crate::models::types::{Direction, RealmId, RoomId};
+use crate::renderer::room_view::render_arrival_view;
+use crate::state::registry::Registry;
+use crate::state::session::Cursor;
+
+/// Names of the other players in the cursor's room, then the NPCs
+pub fn occupants(registry: &Registry, cursor: &Cursor) -> Vec<String> {
+    let mut players: Vec<String> = registry
+        .players_in_realm_room(cursor.realm_id, cursor.room_id)
+        .into_iter()
+        .filter(|p| p.account.id != cursor.account_id)
+        .map(|p| p.account.username.clone())
+        .collect();
+    players.sort();
+    players.dedup();
+
+    let npcs = cursor
+        .room
+        .objects
+        .iter()
+        .filter(|o| o.dialogue.is_some())
+        .map(|o| o.name.clone());
+    players.into_iter().chain(npcs).collect()
+}
+
+/// "Alice is here.", "Alice and Bob are here." or "Alice, Bob and Carol are here."
+pub fn presence_line(names: &[String]) -> Option<String> {
+    group_line(names, "is here", "are here")
+}
+
+/// The names joined like "Alice, Bob and Carol", followed by `one` or `many`
+fn group_line(names: &[String], one: &str, many: &str) -> Option<String> {
+    match names {
+        [] => None,
+        [name] => Some(format!("{} {}.", name, one)),
+        [rest @ .., last] => Some(format!("{} and {} {}.", rest.join(", "), last, many)),
+    }
+}
+
+/// Prints the room view followed by who is here. On `arrival`, players with the `brief` setting
+/// on get the short view. While playtesting, the builder notes of the room come last.
+pub async fn show_room(ctx: &CmdCtx, arrival: bool) -> AppResult<()> {
+    let brief = arrival && ctx.account()?.settings.brief;
+    ctx.output.line(render_arrival_view(brief)).await;
+    show_occupants(ctx).await?;
+    if ctx.sess.read().is_playtesting() {
+        show_builder_notes(ctx).await?;
+    }
+    Ok(())
+}
+
+/// Prints the notes builders left on the room and its objects, if any
+async fn show_builder_notes(ctx: &CmdCtx) -> AppResult<()> {
+    let cursor = ctx.cursor()?;
+    let notes = ctx
+        .registry
+        .services
+        .blueprint
+        .builder_notes(cursor.room.blueprint.bp_id, Some(cursor.room_id))
+        .await?;
+    for note in notes {
+        ctx.output.system(format!("[note] {}", note)).await;
+    }
+    Ok(())
+}
+
+/// Prints who else is in the room, if anyone
+pub async fn show_occupants(ctx: &CmdCtx) -> AppResult<()> {
+    if let Some(line) = presence_line(&occupants(&ctx.registry, &ctx.cursor()?)) {
+        ctx.output.line(line).await;
+    }
+    Ok(())
+}
+
+/// Tells the others in the room `name` left it, through `dir` when they walked
+pub async fn announce_departure(registry: &Registry, cursor: &Cursor, name: &str, dir: Option<&Direction>) {
+    broadcast(registry, cursor, &departure_message(name, dir)).await;
+}
+
+/// Tells the others in the room `name` entered it, after walking `dir`
+pub async fn announce_arrival(registry: &Registry, cursor: &Cursor, name: &str, dir: Option<&Direction>) {
+    broadcast(registry, cursor, &arrival_message(name, dir)).await;
+}
+
+/// Tells the others in the room the players `names` left it together
+pub async fn announce_group_departure(registry: &Registry, realm_id: RealmId, room_id: RoomId, names: &[String]) {
+    if let Some(msg) = group_line(names, "leaves", "leave") {
+        broadcast_except(registry, realm_id, room_id, names, &msg).await;
+    }
+}
+
+/// Tells the others in the room the players `names` entered it together
+pub async fn announce_group_arrival(registry: &Registry, realm_id: RealmId, room_id: RoomId, names: &[String]) {
+    if let Some(msg) = group_line(names, "arrives", "arrive") {
+        broadcast_except(registry, realm_id, room_id, names, &msg).await;
+    }
+}
+
+async fn broadcast_except(registry: &Registry, realm_id: RealmId, room_id: RoomId, names: &[String], msg: &str) {
+    for p in registry.players_in_realm_room(realm_id, room_id) {
+        if !names.contains(&p.account.username) {
+            p.output.line(msg).await;
+        }
+    }
+}
+
+async fn broadcast(registry: &Registry, cursor: &Cursor, msg: &str) {
+    for p in registry.players_in_realm_room(cursor.realm_id, cursor.room_id) {
+        if p.account.id != cursor.account_id {
+            p.output.line(msg).await;
+        }
+    }
+}
+
+fn departure_message(name: &str, dir: Option<&Direction>) -> String {
+    match dir {
+        Some(Direction::In) => format!("{} goes inside.", name),
+        Some(Direction::Out) => format!("{} goes outside.", name),
+        Some(Direction::Custom(_)) | None => format!("{} leaves.", name),
+        Some(dir) => format!("{} leaves {}.", name, dir),
+    }
+}
+
+/// Someone who walked north arrives from the south
+fn arrival_message(name: &str, dir: Option<&Direction>) -> String {
+    let from = match dir {
+        Some(Direction::North) => "the south",
+        Some(Direction::South) => "the north",
+        Some(Direction::East) => "the west",
+        Some(Direction::West) => "the east",
+        Some(Direction::Northeast) => "the southwest",
+        Some(Direction::Northwest) => "the southeast",
+        Some(Direction::Southeast) => "the northwest",
+        Some(Direction::Southwest) => "the northeast",
+        Some(Direction::Up) => "below",
+        Some(Direction::Down) => "above",
+        Some(Direction::In) => "outside",
+        Some(Direction::Out) => "inside",
+        Some(Direction::Custom(_)) | None => return format!("{} arrives.", name),
+    };
+    format!("{} arrives from {}.", name, from)
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn names(n: &[&str]) -> Vec<String> {
+        n.iter().map(|s| s.to_string()).collect()
+    }
+
+    #[test]
+    fn t_presence_line() {
+        assert_eq!(presence_line(&[]), None);
+        assert_eq!(presence_line(&names(&["Alice"])).unwrap(), "Alice is here.");
+        assert_eq!(
+            presence_line(&names(&["Alice", "Bob"])).unwrap(),
+            "Alice and Bob are here."
+        );
+        assert_eq!(
+            presence_line(&names(&["Alice", "Bob", "the guard"])).unwrap(),
+            "Alice, Bob and the guard are here."
+        );
+    }
+
+    #[test]
+    fn t_group_line() {
+        assert_eq!(
+            group_line(&names(&["Alice"]), "leaves", "leave").unwrap(),
+            "Alice leaves."
+        );
+        assert_eq!(
+            group_line(&names(&["Alice", "Bob", "Carol"]), "arrives", "arrive").unwrap(),
+            "Alice, Bob and Carol arrive."
+        );
+    }
+
+    #[test]
+    fn t_movement_messages() {
+        assert_eq!(
+            arrival_message("Bob", Some(&Direction::South)),
+            "Bob arrives from the north."
+        );
+        assert_eq!(arrival_message("Bob", Some(&Direction::Up)), "Bob arrives from below.");
+        assert_eq!(arrival_message("Bob", None), "Bob arrives.");
+        assert_eq!(departure_message("Bob", Some(&Direction::North)), "Bob leaves north.");
+        assert_eq!(departure_message("Bob", Some(&Direction::In)), "Bob goes inside.");
+        assert_eq!(
+            departure_message("Bob", Some(&Direction::Custom("hatch".into()))),
+            "Bob leaves."
+        );
+    }
+}
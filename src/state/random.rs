@@ -0,0 +1,111 @@
+//! Seeded random numbers for realm scripts.
+//!
+//! Every realm instance has its own generator, seeded from the realm id: a realm rolls the same
+//! sequence each time the server starts, so a puzzle that misbehaves can be replayed, while two
+//! instances of the same blueprint still differ. Authors can pick another seed while playtesting
+//! (`@playtest seed`), which also restarts the sequence.
+
+use crate::models::types::RealmId;
+use parking_lot::Mutex;
+use rand::rngs::StdRng;
+use rand::{Rng, SeedableRng};
+use std::collections::HashMap;
+
+struct SeededRng {
+    seed: u64,
+    rng: StdRng,
+}
+
+impl SeededRng {
+    fn new(seed: u64) -> Self {
+        Self {
+            seed,
+            rng: StdRng::seed_from_u64(seed),
+        }
+    }
+}
+
+#[derive(Default)]
+pub struct RealmRandom {
+    rngs: Mutex<HashMap<RealmId, SeededRng>>,
+}
+
+impl RealmRandom {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Next number from `min` to `max`, both included. The bounds may be given in either order.
+    pub fn range(&self, realm_id: RealmId, min: i64, max: i64) -> i64 {
+        let (lo, hi) = if min <= max { (min, max) } else { (max, min) };
+        let mut rngs = self.rngs.lock();
+        let entry = rngs
+            .entry(realm_id)
+            .or_insert_with(|| SeededRng::new(default_seed(realm_id)));
+        entry.rng.random_range(lo..=hi)
+    }
+
+    /// Seed the realm's generator currently runs from
+    pub fn seed(&self, realm_id: RealmId) -> u64 {
+        self.rngs
+            .lock()
+            .get(&realm_id)
+            .map(|r| r.seed)
+            .unwrap_or_else(|| default_seed(realm_id))
+    }
+
+    /// Restarts the realm's sequence from `seed`, or from the realm's own seed when None. Returns
+    /// the seed now in use.
+    pub fn reseed(&self, realm_id: RealmId, seed: Option<u64>) -> u64 {
+        let seed = seed.unwrap_or_else(|| default_seed(realm_id));
+        self.rngs.lock().insert(realm_id, SeededRng::new(seed));
+        seed
+    }
+}
+
+/// The seed a realm starts with, folded from its id
+pub fn default_seed(realm_id: RealmId) -> u64 {
+    let id = realm_id.as_uuid().as_u128();
+    (id >> 64) as u64 ^ id as u64
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    fn roll(random: &RealmRandom, realm_id: RealmId) -> Vec<i64> {
+        (0..20).map(|_| random.range(realm_id, 1, 1000)).collect()
+    }
+
+    #[test]
+    fn t_same_seed_same_sequence() {
+        let realm_id = RealmId::new();
+        let first = roll(&RealmRandom::new(), realm_id);
+        assert_eq!(roll(&RealmRandom::new(), realm_id), first);
+        assert_ne!(roll(&RealmRandom::new(), RealmId::new()), first);
+    }
+
+    #[test]
+    fn t_reseed() {
+        let random = RealmRandom::new();
+        let realm_id = RealmId::new();
+        assert_eq!(random.seed(realm_id), default_seed(realm_id));
+
+        assert_eq!(random.reseed(realm_id, Some(42)), 42);
+        let first = roll(&random, realm_id);
+        random.reseed(realm_id, Some(42));
+        assert_eq!(roll(&random, realm_id), first);
+
+        assert_eq!(random.reseed(realm_id, None), default_seed(realm_id));
+    }
+
+    #[test]
+    fn t_range_bounds() {
+        let random = RealmRandom::new();
+        let realm_id = RealmId::new();
+        for _ in 0..100 {
+            assert!((3..=5).contains(&random.range(realm_id, 5, 3)));
+        }
+        assert_eq!(random.range(realm_id, 7, 7), 7);
+    }
+}
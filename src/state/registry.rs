@@ -1,10 +1,35 @@
-use crate::config::Config;
+use crate::commands::pipeline::Pipeline;
+use crate::config::{Config, ReloadReport};
 use crate::db::Db;
-use crate::db::repo::{AccountRepo, AccountRepository, RoomRepository, UserRepo, UserRepository};
-use crate::db::repo::{InventoryRepo, InventoryRepository, RoomRepo};
-use crate::db::repo::{RealmRepo, RealmRepository};
+use crate::db::repo::{
+    AccountDeletionRepository, AccountRepo, AccountRepository, FeatureRepository, RoomRepository, UserRepo,
+    UserRepository,
+};
+use crate::db::repo::{
+    AmbienceRepository, ClockRepository, CraftingRepository, CurrencyRepository, RecordingRepository, ReviewRepository,
+};
+use crate::db::repo::{CollaboratorRepository, SubmissionRepository};
+use crate::db::repo::{
+    InventoryRepo, InventoryRepository, LoginAttemptRepository, MaintenanceRepository, ModerationRepository,
+    PlaytestRepository, RoomRepo,
+};
+use crate::db::repo::{PlayerExportRepository, RealmRepo, RealmRepository, ScheduleRepository, TutorialRepository};
+use crate::error::AppResult;
 use crate::models::account::Account;
-use crate::services::{AccountService, BlueprintService, InventoryService, RealmService, RoomService};
+use crate::models::types::{AccountId, RealmId, RoomId};
+use crate::net::limits::ConnectionLimiter;
+use crate::net::output::OutputHandle;
+use crate::services::{
+    AccountDeletionService, AccountService, AmbienceService, BlueprintService, ClockService, CollaboratorService,
+    ContentFilterService, CraftingService, CurrencyService, FeatureService, InventoryService, MaintenanceService,
+    ModerationService, PlayerExportService, PlaytestService, RealmService, RecordingService, ReviewService,
+    RoomService, ScheduleService, SubmissionService, TutorialService,
+};
+use crate::state::clock;
+use crate::state::random::RealmRandom;
+use crate::state::script_actions::ScriptActionQueue;
+use crate::state::session::Session;
+use dashmap::DashMap;
 use parking_lot::RwLock;
 use std::collections::BTreeSet;
 use std::sync::Arc;
@@ -23,14 +48,53 @@ pub struct Services {
     pub room: Arc<RoomService>,
     pub realm: Arc<RealmService>,
     pub inventory: Arc<InventoryService>,
+    pub moderation: Arc<ModerationService>,
+    pub playtest: Arc<PlaytestService>,
+    pub recording: Arc<RecordingService>,
+    pub crafting: Arc<CraftingService>,
+    pub currency: Arc<CurrencyService>,
+    pub maintenance: Arc<MaintenanceService>,
+    pub ambience: Arc<AmbienceService>,
+    pub clock: Arc<ClockService>,
+    pub schedule: Arc<ScheduleService>,
+    pub player_export: Arc<PlayerExportService>,
+    pub account_deletion: Arc<AccountDeletionService>,
+    pub tutorial: Arc<TutorialService>,
+    pub review: Arc<ReviewService>,
+    pub submission: Arc<SubmissionService>,
+    pub collaborator: Arc<CollaboratorService>,
 }
 
 pub struct Registry {
     pub db: Arc<Db>,
     pub repos: Arc<Repos>,
     pub services: Arc<Services>,
-    pub config: Arc<Config>,
+    /// Per realm / per blueprint feature flags
+    pub features: Arc<FeatureService>,
+    /// Filter for player text, per realm through the `content_filter` feature
+    pub filter: Arc<ContentFilterService>,
+    /// Current configuration; swapped on reload, so always read it through `config()`
+    config: Arc<RwLock<Arc<Config>>>,
     pub online: RwLock<BTreeSet<String>>,
+    /// Logged-in players with their output, to reach them outside their own commands
+    pub connected: DashMap<AccountId, ConnectedPlayer>,
+    /// Open connections per client address, capped by `rate_limit.connections_per_ip`
+    pub connections: Arc<ConnectionLimiter>,
+    /// Spectator -> spectated player
+    spectating: DashMap<AccountId, AccountId>,
+    /// Seeded random numbers for realm scripts
+    pub random: RealmRandom,
+    /// Moves and commands of finished scripts, for their players
+    pub script_actions: ScriptActionQueue,
+    /// Stages every command goes through; subsystems register their own here
+    pub commands: Pipeline,
+}
+
+#[derive(Clone)]
+pub struct ConnectedPlayer {
+    pub account: Arc<Account>,
+    pub output: OutputHandle,
+    pub sess: Arc<RwLock<Session>>,
 }
 
 impl Registry {
@@ -53,29 +117,244 @@ impl Registry {
             inventory_service.clone(),
         ));
 
+        let collaborator_service = Arc::new(CollaboratorService::new(
+            Arc::new(CollaboratorRepository::new(db.clone())),
+            repos.room.clone(),
+            repos.account.clone(),
+        ));
+
         let services = Arc::new(Services {
-            account: Arc::new(AccountService::new(repos.account.clone())),
+            account: Arc::new(AccountService::new(
+                repos.account.clone(),
+                Arc::new(LoginAttemptRepository::new(db.clone())),
+            )),
             blueprint: blueprint_service.clone(),
-            inventory: inventory_service,
+            inventory: inventory_service.clone(),
             room: room_service.clone(),
             realm: Arc::new(RealmService::new(repos.realm.clone(), repos.user.clone())),
+            moderation: Arc::new(ModerationService::new(
+                Arc::new(ModerationRepository::new(db.clone())),
+                repos.account.clone(),
+            )),
+            playtest: Arc::new(PlaytestService::new(Arc::new(PlaytestRepository::new(db.clone())))),
+            recording: Arc::new(RecordingService::new(Arc::new(RecordingRepository::new(db.clone())))),
+            crafting: Arc::new(CraftingService::new(
+                Arc::new(CraftingRepository::new(db.clone())),
+                inventory_service,
+            )),
+            currency: Arc::new(CurrencyService::new(Arc::new(CurrencyRepository::new(db.clone())))),
+            maintenance: Arc::new(MaintenanceService::new(Arc::new(MaintenanceRepository::new(
+                db.clone(),
+            )))),
+            ambience: Arc::new(AmbienceService::new(Arc::new(AmbienceRepository::new(db.clone())))),
+            clock: Arc::new(ClockService::new(Arc::new(ClockRepository::new(db.clone())))),
+            schedule: Arc::new(ScheduleService::new(Arc::new(ScheduleRepository::new(db.clone())))),
+            player_export: Arc::new(PlayerExportService::new(
+                Arc::new(PlayerExportRepository::new(db.clone())),
+                repos.account.clone(),
+            )),
+            account_deletion: Arc::new(AccountDeletionService::new(
+                Arc::new(AccountDeletionRepository::new(db.clone())),
+                repos.account.clone(),
+            )),
+            tutorial: Arc::new(TutorialService::new(
+                Arc::new(TutorialRepository::new(db.clone())),
+                repos.realm.clone(),
+                repos.room.clone(),
+            )),
+            review: Arc::new(ReviewService::new(Arc::new(ReviewRepository::new(db.clone())))),
+            submission: Arc::new(SubmissionService::new(
+                Arc::new(SubmissionRepository::new(db.clone())),
+                collaborator_service.clone(),
+            )),
+            collaborator: collaborator_service,
         });
 
+        let config = Arc::new(RwLock::new(config));
+        let features = Arc::new(FeatureService::new(
+            Arc::new(FeatureRepository::new(db.clone())),
+            config.clone(),
+        ));
+        let filter = Arc::new(ContentFilterService::new(features.clone(), config.clone()));
+
         Self {
             db,
+            features,
+            filter,
             config,
             repos,
             services,
             online: RwLock::new(BTreeSet::new()),
+            connected: DashMap::new(),
+            connections: Arc::new(ConnectionLimiter::new()),
+            spectating: DashMap::new(),
+            random: RealmRandom::new(),
+            script_actions: ScriptActionQueue::new(),
+            commands: Pipeline::new(),
         }
     }
 
+    pub fn config(&self) -> Arc<Config> {
+        self.config.read().clone()
+    }
+
+    /// Re-reads the config file and applies the hot-reloadable settings. An invalid file leaves
+    /// the current config untouched.
+    pub fn reload_config(&self) -> AppResult<ReloadReport> {
+        let current = self.config();
+        let new = Config::load_layered(current.source.as_deref())?;
+
+        let (merged, report) = current.merge_reloaded(&new);
+        clock::set_speed(merged.clock.speed);
+        *self.config.write() = Arc::new(merged);
+
+        Ok(report)
+    }
+
     pub async fn set_online(&self, account: &Account, online: bool) {
-        let mut g = self.online.write();
         if online {
-            g.insert(account.username.clone());
-        } else {
-            g.remove(&account.username);
+            self.online.write().insert(account.username.clone());
+            return;
+        }
+
+        self.online.write().remove(&account.username);
+        if let Some(p) = self.connected.get(&account.id).map(|e| e.value().clone())
+            && let Err(e) = self.save_recording(account.id, &p.sess).await
+        {
+            tracing::warn!(error = %e, account = %account.username, "could not save session recording");
+        }
+        self.stop_spectating(account.id);
+        for p in self.spectators_of(account.id) {
+            self.stop_spectating(p.account.id);
+            p.output
+                .system(format!("[spectate] {} disconnected.", account.username))
+                .await;
+        }
+        self.connected.remove(&account.id);
+    }
+
+    /// Stops a running session recording and stores it. Returns the recording id, or `None` when
+    /// the session was not being recorded.
+    pub async fn save_recording(&self, account_id: AccountId, sess: &RwLock<Session>) -> AppResult<Option<i64>> {
+        let Some(recorder) = sess.write().stop_recording() else {
+            return Ok(None);
+        };
+        Ok(Some(self.services.recording.save(account_id, recorder).await?))
+    }
+
+    /// Mirrors the output of `target` to `spectator`. A spectator watches one player at a time.
+    pub fn start_spectating(&self, spectator: &ConnectedPlayer, target: &ConnectedPlayer) {
+        self.stop_spectating(spectator.account.id);
+        target
+            .output
+            .add_mirror(spectator.account.id, target.account.username.clone(), &spectator.output);
+        self.spectating.insert(spectator.account.id, target.account.id);
+    }
+
+    /// Stops the spectator watching, returns the player that was watched
+    pub fn stop_spectating(&self, spectator_id: AccountId) -> Option<ConnectedPlayer> {
+        let (_, target_id) = self.spectating.remove(&spectator_id)?;
+        let target = self.connected.get(&target_id).map(|e| e.value().clone())?;
+        target.output.remove_mirror(spectator_id);
+        Some(target)
+    }
+
+    /// The player the spectator is watching
+    pub fn spectated_by(&self, spectator_id: AccountId) -> Option<ConnectedPlayer> {
+        let target_id = *self.spectating.get(&spectator_id)?;
+        self.connected.get(&target_id).map(|e| e.value().clone())
+    }
+
+    /// Everyone watching the target
+    pub fn spectators_of(&self, target_id: AccountId) -> Vec<ConnectedPlayer> {
+        let ids: Vec<AccountId> = self
+            .spectating
+            .iter()
+            .filter(|e| *e.value() == target_id)
+            .map(|e| *e.key())
+            .collect();
+        ids.iter()
+            .filter_map(|id| self.connected.get(id).map(|e| e.value().clone()))
+            .collect()
+    }
+
+    /// Looks up a connected player by username (case-insensitive)
+    pub fn connected_by_name(&self, username: &str) -> Option<ConnectedPlayer> {
+        self.connected
+            .iter()
+            .find(|e| e.account.username.eq_ignore_ascii_case(username))
+            .map(|e| e.value().clone())
+    }
+
+    /// Marks the account online and remembers its output, so other players can reach it
+    pub async fn connect(&self, account: Arc<Account>, output: OutputHandle, sess: Arc<RwLock<Session>>) {
+        self.set_online(&account, true).await;
+        self.connected
+            .insert(account.id, ConnectedPlayer { account, output, sess });
+    }
+
+    /// Connected players matching the filter. Cloned out, so no map guard is held while sending.
+    pub fn connected_where(&self, f: impl Fn(&Account) -> bool) -> Vec<ConnectedPlayer> {
+        self.connected
+            .iter()
+            .filter(|e| f(&e.account))
+            .map(|e| e.value().clone())
+            .collect()
+    }
+
+    /// Connected players whose session is currently in the given room
+    pub fn players_in_room(&self, room_id: RoomId) -> Vec<ConnectedPlayer> {
+        self.connected
+            .iter()
+            .filter(|e| e.sess.read().get_cursor().is_some_and(|c| c.room_id == room_id))
+            .map(|e| e.value().clone())
+            .collect()
+    }
+
+    /// Connected players whose session is currently in the given room of the given realm
+    pub fn players_in_realm_room(&self, realm_id: RealmId, room_id: RoomId) -> Vec<ConnectedPlayer> {
+        self.connected
+            .iter()
+            .filter(|e| {
+                e.sess
+                    .read()
+                    .get_cursor()
+                    .is_some_and(|c| c.realm_id == realm_id && c.room_id == room_id)
+            })
+            .map(|e| e.value().clone())
+            .collect()
+    }
+
+    /// Rebuilds the cached room view of everyone in the room of the realm, so a change to the
+    /// room shows without moving
+    pub async fn refresh_room_views(&self, realm_id: RealmId, room_id: RoomId) -> AppResult<()> {
+        for player in self.players_in_realm_room(realm_id, room_id) {
+            let Some(c) = player.sess.read().get_cursor() else {
+                continue;
+            };
+            let rv = self
+                .services
+                .room
+                .build_room_view(c.realm_id, c.account_id, c.room_id)
+                .await?;
+            player.sess.write().replace_room(rv);
+        }
+        Ok(())
+    }
+
+    /// Connected players whose session is currently in the given realm
+    pub fn players_in_realm(&self, realm_id: RealmId) -> Vec<ConnectedPlayer> {
+        self.connected
+            .iter()
+            .filter(|e| e.sess.read().get_cursor().is_some_and(|c| c.realm_id == realm_id))
+            .map(|e| e.value().clone())
+            .collect()
+    }
+
+    /// Sends a system message to every online moderator
+    pub async fn notify_moderators(&self, msg: &str) {
+        for p in self.connected_where(Account::is_moderator) {
+            p.output.system(msg).await;
         }
     }
 
@@ -0,0 +1,342 @@
+//! Moves and commands scripts ask for on behalf of their player (`port4k.move_player`,
+//! `port4k.move_all_players`, `port4k.force_command`).
+//!
+//! A script runs on a Lua worker, and moving a player runs room scripts on that same worker, so
+//! the actions are only collected while the script runs. When it is done they are queued here and
+//! carried out in order: after the output of the script, and after the command of the player that
+//! ran it. Scripts triggering each other (a conveyor belt dropping the player on the next belt)
+//! are stopped after `MAX_SCRIPT_CHAIN` runs without the player typing anything.
+//!
+//! Sequences (`port4k.sequence`) wait between their steps here too, so no script ever sleeps on
+//! a Lua worker.
+
+use crate::commands::{CmdCtx, run_command};
+use crate::hardening::{MAX_INPUT_LINE_BYTES, MAX_SCRIPT_CHAIN, MAX_SEQUENCE_DURATION, MAX_SEQUENCE_STEPS};
+use crate::input::parser::{Verb, parse_command};
+use crate::input::shell::parse_shell_cmd;
+use crate::lua::LuaPool;
+use crate::models::types::{AccountId, RoomId};
+use crate::state::hazards::{force_move, force_move_group};
+use crate::state::interactive::InteractiveState;
+use crate::state::registry::{ConnectedPlayer, Registry};
+use parking_lot::Mutex;
+use std::sync::Arc;
+use std::time::Duration;
+use tokio::sync::mpsc;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
+pub enum ScriptAction {
+    /// Move the player to the room, like a hazard does
+    Move(RoomId),
+    /// Move everyone in the player's room to the room, together
+    MoveAll(RoomId),
+    /// Run the command as if the player typed it
+    Command(String),
+}
+
+/// One step of a `port4k.sequence`
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct SequenceStep {
+    /// Wait before the step, counted from the step before it
+    pub delay: Duration,
+    pub text: Option<String>,
+    pub sound: Option<String>,
+    pub action: Option<ScriptAction>,
+}
+
+enum Queued {
+    Actions(AccountId, Vec<ScriptAction>),
+    /// Steps to play while the player stays in the room
+    Sequence(AccountId, RoomId, Vec<SequenceStep>),
+}
+
+/// Actions of finished scripts, waiting for `run_script_actions`
+pub struct ScriptActionQueue {
+    tx: mpsc::UnboundedSender<Queued>,
+    rx: Mutex<Option<mpsc::UnboundedReceiver<Queued>>>,
+}
+
+impl Default for ScriptActionQueue {
+    fn default() -> Self {
+        let (tx, rx) = mpsc::unbounded_channel();
+        Self {
+            tx,
+            rx: Mutex::new(Some(rx)),
+        }
+    }
+}
+
+impl ScriptActionQueue {
+    pub fn new() -> Self {
+        Self::default()
+    }
+
+    /// Queues the actions of one script run for the player
+    pub fn push(&self, account_id: AccountId, actions: Vec<ScriptAction>) {
+        if !actions.is_empty() {
+            _ = self.tx.send(Queued::Actions(account_id, actions));
+        }
+    }
+
+    /// Queues a sequence for the player, started in the room `room_id`
+    pub fn push_sequence(&self, account_id: AccountId, room_id: RoomId, steps: Vec<SequenceStep>) {
+        if !steps.is_empty() {
+            _ = self.tx.send(Queued::Sequence(account_id, room_id, steps));
+        }
+    }
+}
+
+/// Commands scripts may force on a player: things to do in the room, never anything about the
+/// account, the connection, money or talking to other players
+const FORCEABLE_COMMANDS: [Verb; 19] = [
+    Verb::Look,
+    Verb::Examine,
+    Verb::Search,
+    Verb::Hint,
+    Verb::Read,
+    Verb::Board,
+    Verb::Disembark,
+    Verb::Press,
+    Verb::Take,
+    Verb::Drop,
+    Verb::Push,
+    Verb::Pull,
+    Verb::Turn,
+    Verb::Throw,
+    Verb::Open,
+    Verb::Use,
+    Verb::Put,
+    Verb::Go,
+    Verb::Inventory,
+];
+
+/// Whether a script may force `raw` on a player; the error says why not
+pub fn check_forced_command(raw: &str) -> Result<(), String> {
+    let raw = raw.trim();
+    if raw.is_empty() {
+        return Err("empty command".to_string());
+    }
+    if raw.len() > MAX_INPUT_LINE_BYTES {
+        return Err(format!("command longer than {} bytes", MAX_INPUT_LINE_BYTES));
+    }
+    if raw.starts_with('@') || parse_shell_cmd(raw).is_some() {
+        return Err(format!("'{}' cannot be forced on a player", raw));
+    }
+    match parse_command(raw).verb {
+        Verb::Custom(_) => Ok(()),
+        verb if FORCEABLE_COMMANDS.contains(&verb) => Ok(()),
+        verb => Err(format!("'{}' cannot be forced on a player", verb.as_str())),
+    }
+}
+
+/// Whether the steps fit in a sequence; the error says why not
+pub fn check_sequence(steps: &[SequenceStep]) -> Result<(), String> {
+    if steps.len() > MAX_SEQUENCE_STEPS {
+        return Err(format!("more than {} steps", MAX_SEQUENCE_STEPS));
+    }
+    if steps.iter().map(|s| s.delay).sum::<Duration>() > MAX_SEQUENCE_DURATION {
+        return Err(format!("longer than {} seconds", MAX_SEQUENCE_DURATION.as_secs()));
+    }
+    if let Some(n) = steps
+        .iter()
+        .position(|s| s.text.is_none() && s.sound.is_none() && s.action.is_none())
+    {
+        return Err(format!("step {} has no text, sound, move or command", n + 1));
+    }
+    Ok(())
+}
+
+/// Carries out queued script actions, forever. Spawned once when the server starts.
+pub async fn run_script_actions(registry: Arc<Registry>, lua_tx: LuaPool) {
+    let Some(mut rx) = registry.script_actions.rx.lock().take() else {
+        tracing::warn!("script actions: already running");
+        return;
+    };
+
+    while let Some(queued) = rx.recv().await {
+        // Each player's actions wait for their own commands, not for other players
+        match queued {
+            Queued::Actions(account_id, actions) => {
+                tokio::spawn(run_batch(registry.clone(), lua_tx.clone(), account_id, actions));
+            }
+            Queued::Sequence(account_id, room_id, steps) => {
+                tokio::spawn(run_sequence(
+                    registry.clone(),
+                    lua_tx.clone(),
+                    account_id,
+                    room_id,
+                    steps,
+                ));
+            }
+        }
+    }
+}
+
+async fn run_batch(registry: Arc<Registry>, lua_tx: LuaPool, account_id: AccountId, actions: Vec<ScriptAction>) {
+    let Some(p) = registry.connected.get(&account_id).map(|p| p.clone()) else {
+        return;
+    };
+    let lock = p.sess.read().command_lock();
+    let _running = lock.lock().await;
+
+    run_actions(&registry, &lua_tx, &p, actions).await;
+}
+
+/// Plays the steps one after the other, until the player leaves the room the sequence expects
+/// them in. Moves and commands of the sequence take the player along.
+async fn run_sequence(
+    registry: Arc<Registry>,
+    lua_tx: LuaPool,
+    account_id: AccountId,
+    mut room_id: RoomId,
+    steps: Vec<SequenceStep>,
+) {
+    for step in steps {
+        tokio::time::sleep(step.delay).await;
+
+        let Some(p) = registry.connected.get(&account_id).map(|p| p.clone()) else {
+            return;
+        };
+        let lock = p.sess.read().command_lock();
+        let _running = lock.lock().await;
+
+        let current = p.sess.read().get_cursor().map(|c| c.room_id);
+        if current != Some(room_id) {
+            return;
+        }
+        if let Some(text) = step.text {
+            p.output.line(text).await;
+        }
+        if let Some(cue) = step.sound {
+            p.output.sound(cue).await;
+        }
+        if let Some(action) = step.action {
+            if !run_actions(&registry, &lua_tx, &p, vec![action]).await {
+                return;
+            }
+            match p.sess.read().get_cursor() {
+                Some(cursor) => room_id = cursor.room_id,
+                None => return,
+            }
+        }
+    }
+}
+
+/// Carries out the actions in order, with the command lock of the player held. False when they
+/// were stopped early.
+async fn run_actions(
+    registry: &Arc<Registry>,
+    lua_tx: &LuaPool,
+    p: &ConnectedPlayer,
+    actions: Vec<ScriptAction>,
+) -> bool {
+    let chain = p.sess.write().next_script_chain();
+    if chain > MAX_SCRIPT_CHAIN {
+        tracing::warn!(account = %p.account.id, chain, "script actions: too many in a row, dropped");
+        return false;
+    }
+
+    for action in actions {
+        let done = match action {
+            ScriptAction::Move(room_id) => move_player(registry, lua_tx, p, room_id).await,
+            ScriptAction::MoveAll(room_id) => move_all_players(registry, lua_tx, p, room_id).await,
+            ScriptAction::Command(raw) => force_command(registry, lua_tx, p, &raw).await,
+        };
+        if !done {
+            return false;
+        }
+    }
+    true
+}
+
+/// False when the player cannot be moved, which cancels the actions after it
+async fn move_player(registry: &Arc<Registry>, lua_tx: &LuaPool, p: &ConnectedPlayer, room_id: RoomId) -> bool {
+    let Some(cursor) = p.sess.read().get_cursor() else {
+        return false;
+    };
+    match force_move(registry, lua_tx, p, &cursor, room_id).await {
+        Ok(()) => true,
+        Err(e) => {
+            tracing::warn!(error = %e, "script actions: cannot move player");
+            false
+        }
+    }
+}
+
+/// Moves everyone in the room of `p` along with them. False when they cannot be moved, which
+/// cancels the actions after it.
+async fn move_all_players(registry: &Arc<Registry>, lua_tx: &LuaPool, p: &ConnectedPlayer, room_id: RoomId) -> bool {
+    let Some(cursor) = p.sess.read().get_cursor() else {
+        return false;
+    };
+    let players = registry.players_in_realm_room(cursor.realm_id, cursor.room_id);
+    match force_move_group(registry, lua_tx, &players, &cursor, room_id).await {
+        Ok(()) => true,
+        Err(e) => {
+            tracing::warn!(error = %e, "script actions: cannot move players");
+            false
+        }
+    }
+}
+
+/// False when the command could not run, which cancels the actions after it
+async fn force_command(registry: &Arc<Registry>, lua_tx: &LuaPool, p: &ConnectedPlayer, raw: &str) -> bool {
+    let ctx = Arc::new(CmdCtx {
+        output: p.output.clone(),
+        registry: registry.clone(),
+        lua_tx: lua_tx.clone(),
+        sess: p.sess.clone(),
+    });
+    // Halfway a wizard or prompt the command would be taken as the answer
+    if !matches!(ctx.get_interactive(), InteractiveState::None) {
+        return false;
+    }
+    match run_command(raw, ctx).await {
+        Ok(()) => true,
+        Err(e) => {
+            tracing::debug!(error = %e, command = raw, "script actions: forced command failed");
+            false
+        }
+    }
+}
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn t_check_forced_command() {
+        assert!(check_forced_command("look").is_ok());
+        assert!(check_forced_command("go north").is_ok());
+        assert!(check_forced_command("pull lever").is_ok());
+        assert!(check_forced_command("frobnicate").is_ok());
+
+        assert!(check_forced_command("  ").is_err());
+        assert!(check_forced_command("quit").is_err());
+        assert!(check_forced_command("delete account").is_err());
+        assert!(check_forced_command("say I am a bot").is_err());
+        assert!(check_forced_command("pay bob 100").is_err());
+        assert!(check_forced_command("@realm reset").is_err());
+        assert!(check_forced_command("\\dbg roomview").is_err());
+        assert!(check_forced_command(&"x".repeat(MAX_INPUT_LINE_BYTES + 1)).is_err());
+    }
+
+    #[test]
+    fn t_check_sequence() {
+        let step = |delay: u64, text: Option<&str>| SequenceStep {
+            delay: Duration::from_secs(delay),
+            text: text.map(String::from),
+            ..Default::default()
+        };
+        assert!(check_sequence(&[step(0, Some("The belt starts.")), step(2, Some("It speeds up."))]).is_ok());
+        assert!(check_sequence(&[]).is_ok());
+
+        let err = check_sequence(&[step(1, Some("Clunk.")), step(1, None)]).unwrap_err();
+        assert_eq!(err, "step 2 has no text, sound, move or command");
+
+        let too_long = [step(MAX_SEQUENCE_DURATION.as_secs() + 1, Some("Finally."))];
+        assert!(check_sequence(&too_long).is_err());
+        let too_many = vec![step(0, Some("Tick.")); MAX_SEQUENCE_STEPS + 1];
+        assert!(check_sequence(&too_many).is_err());
+    }
+}
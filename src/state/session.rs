@@ -1,9 +1,17 @@
 use crate::models::account::Account;
+use crate::models::readable::Readable;
 use crate::models::realm::Realm;
+use crate::models::recording::{Recording, SessionRecorder};
 use crate::models::room::RoomView;
+use crate::models::settings::PlayerSettings;
 use crate::models::types::{AccountId, RealmId, RoomId};
 use crate::net::InputMode;
+use crate::net::limits::CommandBucket;
+use crate::net::stats::ConnStats;
+use crate::renderer::prompt::expand_prompt;
 use crate::state::interactive::InteractiveState;
+use crate::util::telnet::TerminalInfo;
+use std::net::IpAddr;
 use std::sync::Arc;
 
 const DEFAULT_USER_PROMPT: &str =
@@ -52,13 +60,30 @@ impl Cursor {
     }
 }
 
+/// A conversation with an NPC in progress
+#[derive(Clone, Debug, PartialEq, Eq)]
+pub struct Conversation {
+    pub room_id: RoomId,
+    /// Object key of the NPC
+    pub npc: String,
+    /// Dialogue node the player is answering
+    pub node: String,
+}
+
+/// Where a builder came from while playtesting as a persona
+#[derive(Clone, Debug)]
+pub struct PersonaOrigin {
+    pub account: Arc<Account>,
+    pub cursor: Option<Cursor>,
+    prev_cursors: usize,
+}
+
 #[derive(Debug)]
 pub struct Session {
     // When is the session started/created
     pub session_started: std::time::Instant,
 
     /// Protocol used by the client
-    #[allow(unused)]
     protocol: Protocol,
     /// User Account (if logged in)
     account: Option<Arc<Account>>,
@@ -81,9 +106,40 @@ pub struct Session {
     // Previous cursors (for "back" command)
     prev_cursors: Vec<Cursor>,
 
+    // Set while playing as a persona (`@playtest as guest`)
+    persona_origin: Option<PersonaOrigin>,
+    // Set by `@playtest`: rooms show their builder notes
+    playtesting: bool,
+
+    // May builders spectate this session?
+    allow_spectators: bool,
+
+    // Running `@record` transcript, if any
+    recorder: Option<SessionRecorder>,
+    // Recording being stepped through with `@replay`, and the current step
+    replay: Option<(Arc<Recording>, usize)>,
+    // Book or log being paged through with `read`, and the current page
+    reading: Option<(Arc<Readable>, usize)>,
+    // NPC being talked to, and the dialogue node the player is answering
+    conversation: Option<Conversation>,
+
     // Terminal size (if known)
     tty_cols: Option<usize>,
     tty_rows: Option<usize>,
+    // Client and terminal type reported over telnet (if any)
+    terminal: Option<TerminalInfo>,
+
+    // Address the client connects from, when known
+    peer: Option<IpAddr>,
+    // Traffic counters, shared with the output task
+    stats: Arc<ConnStats>,
+    // Commands left before the connection hits `rate_limit`
+    command_bucket: CommandBucket,
+
+    // Held while a command runs, so script actions wait for the command that caused them
+    command_lock: Arc<tokio::sync::Mutex<()>>,
+    // Script runs in a row that moved the player or forced commands, since the player last typed
+    script_chain: u32,
 }
 
 impl Session {
@@ -101,7 +157,20 @@ impl Session {
             prev_cursors: Vec::new(),
             tty_cols: None,
             tty_rows: None,
+            terminal: None,
             in_lua_repl: false,
+            allow_spectators: true,
+            persona_origin: None,
+            playtesting: false,
+            recorder: None,
+            replay: None,
+            reading: None,
+            conversation: None,
+            peer: None,
+            stats: Arc::new(ConnStats::default()),
+            command_bucket: CommandBucket::new(),
+            command_lock: Arc::new(tokio::sync::Mutex::new(())),
+            script_chain: 0,
         }
     }
 
@@ -128,6 +197,33 @@ impl Session {
         self.cursor = cursor;
     }
 
+    /// Swaps in a rebuilt view of the current room, e.g. after the player's state in it changed
+    pub fn replace_room(&mut self, room: RoomView) {
+        if let Some(c) = self.cursor.as_mut() {
+            c.room = Arc::new(room);
+        }
+    }
+
+    /// Keeps the cached account in step after its health changed in the database
+    pub fn set_health(&mut self, health: u32) {
+        if let Some(acc) = self.account.as_mut() {
+            Arc::make_mut(acc).health = health;
+        }
+        if let Some(c) = self.cursor.as_mut() {
+            Arc::make_mut(&mut c.account).health = health;
+        }
+    }
+
+    /// Keeps the cached account in step after the player changed their settings
+    pub fn set_settings(&mut self, settings: PlayerSettings) {
+        if let Some(c) = self.cursor.as_mut() {
+            Arc::make_mut(&mut c.account).settings = settings.clone();
+        }
+        if let Some(acc) = self.account.as_mut() {
+            Arc::make_mut(acc).settings = settings;
+        }
+    }
+
     pub fn login(&mut self, account: Account, realm: Realm, room: RoomView) {
         let acc = Arc::new(account);
         self.account = Some(acc.clone());
@@ -136,10 +232,16 @@ impl Session {
     }
 
     pub fn logout(&mut self) {
+        self.persona_origin = None;
+        self.playtesting = false;
         self.account = None;
         self.state = ConnState::PreLogin;
         self.cursor = None;
         self.prev_cursors.clear();
+        self.recorder = None;
+        self.replay = None;
+        self.reading = None;
+        self.conversation = None;
     }
 
     pub fn in_lua(&mut self, in_repl: bool) {
@@ -149,6 +251,105 @@ impl Session {
         self.in_lua_repl
     }
 
+    /// Switches the session to a persona account. The current account and position are kept,
+    /// and restored by `end_persona`.
+    pub fn begin_persona(&mut self, persona: Account) {
+        if self.persona_origin.is_none()
+            && let Some(account) = self.account.clone()
+        {
+            self.persona_origin = Some(PersonaOrigin {
+                account,
+                cursor: self.cursor.clone(),
+                prev_cursors: self.prev_cursors.len(),
+            });
+        }
+        self.account = Some(Arc::new(persona));
+    }
+
+    /// Switches back to the real account, returns None when not playing a persona
+    pub fn end_persona(&mut self) -> Option<PersonaOrigin> {
+        let origin = self.persona_origin.take()?;
+        self.account = Some(origin.account.clone());
+        self.cursor = origin.cursor.clone();
+        self.prev_cursors.truncate(origin.prev_cursors);
+        Some(origin)
+    }
+
+    pub fn persona_origin(&self) -> Option<&PersonaOrigin> {
+        self.persona_origin.as_ref()
+    }
+
+    pub fn is_playtesting(&self) -> bool {
+        self.playtesting
+    }
+
+    pub fn set_playtesting(&mut self, playtesting: bool) {
+        self.playtesting = playtesting;
+    }
+
+    pub fn allows_spectators(&self) -> bool {
+        self.allow_spectators
+    }
+
+    pub fn set_allow_spectators(&mut self, allow: bool) {
+        self.allow_spectators = allow;
+    }
+
+    /// Starts recording this session, returns false when it is already being recorded
+    pub fn start_recording(&mut self, recorded_by: AccountId) -> bool {
+        if self.recorder.is_some() {
+            return false;
+        }
+        self.recorder = Some(SessionRecorder::new(recorded_by));
+        true
+    }
+
+    pub fn stop_recording(&mut self) -> Option<SessionRecorder> {
+        self.recorder.take()
+    }
+
+    pub fn recorder(&self) -> Option<&SessionRecorder> {
+        self.recorder.as_ref()
+    }
+
+    /// Records a line of input; input typed while hidden (passwords) is masked
+    pub fn record_input(&mut self, text: &str) {
+        let hidden = matches!(self.input_mode, InputMode::Hidden(_));
+        if let Some(r) = self.recorder.as_mut() {
+            r.input(text, hidden);
+        }
+    }
+
+    pub fn record_output(&mut self, text: &str) {
+        if let Some(r) = self.recorder.as_mut() {
+            r.output(text);
+        }
+    }
+
+    pub fn replay(&self) -> Option<(Arc<Recording>, usize)> {
+        self.replay.clone()
+    }
+
+    pub fn set_replay(&mut self, replay: Option<(Arc<Recording>, usize)>) {
+        self.replay = replay;
+    }
+
+    pub fn reading(&self) -> Option<(Arc<Readable>, usize)> {
+        self.reading.clone()
+    }
+
+    pub fn set_reading(&mut self, reading: Option<(Arc<Readable>, usize)>) {
+        self.reading = reading;
+    }
+
+    pub fn conversation(&self) -> Option<Conversation> {
+        self.conversation.clone()
+    }
+
+    pub fn set_conversation(&mut self, conversation: Option<Conversation>) {
+        self.conversation = conversation;
+    }
+
     pub fn set_tty(&mut self, cols: usize, rows: usize) {
         self.tty_cols = Some(cols);
         self.tty_rows = Some(rows);
@@ -161,6 +362,14 @@ impl Session {
         }
     }
 
+    pub fn terminal(&self) -> Option<&TerminalInfo> {
+        self.terminal.as_ref()
+    }
+
+    pub fn set_terminal(&mut self, terminal: TerminalInfo) {
+        self.terminal = Some(terminal);
+    }
+
     pub fn interactive_state(&self) -> InteractiveState {
         self.interactive_state.clone()
     }
@@ -169,6 +378,42 @@ impl Session {
         self.interactive_state = state;
     }
 
+    pub fn peer(&self) -> Option<IpAddr> {
+        self.peer
+    }
+
+    pub fn set_peer(&mut self, peer: IpAddr) {
+        self.peer = Some(peer);
+    }
+
+    pub fn protocol(&self) -> Protocol {
+        self.protocol
+    }
+
+    pub fn stats(&self) -> Arc<ConnStats> {
+        self.stats.clone()
+    }
+
+    /// Counts a typed command against the rate limit; false when the connection is over it
+    pub fn take_command(&mut self, per_second: u32, burst: u32) -> bool {
+        self.command_bucket
+            .try_take(per_second, burst, std::time::Instant::now())
+    }
+
+    pub fn command_lock(&self) -> Arc<tokio::sync::Mutex<()>> {
+        self.command_lock.clone()
+    }
+
+    /// Counts one more script run acting on the player, returning how many there were in a row
+    pub fn next_script_chain(&mut self) -> u32 {
+        self.script_chain += 1;
+        self.script_chain
+    }
+
+    pub fn reset_script_chain(&mut self) {
+        self.script_chain = 0;
+    }
+
     pub fn input_mode(&self) -> InputMode {
         self.input_mode
     }
@@ -186,7 +431,11 @@ impl Session {
         self.prompt = p.into();
     }
 
-    pub fn default_user_prompt(&self) -> &str {
-        &self.default_user_prompt
+    /// Prompt template with its %codes expanded: the player's own prompt, else the one of the
+    /// realm they are in, else the default prompt
+    pub fn default_user_prompt(&self) -> String {
+        let own = self.account.as_ref().and_then(|a| a.settings.prompt.as_deref());
+        let realm = self.cursor.as_ref().and_then(|c| c.realm.prompt.as_deref());
+        expand_prompt(own.or(realm).unwrap_or(&self.default_user_prompt))
     }
 }
@@ -0,0 +1,203 @@
+//! Vehicles travelling between their stops.
+//!
+//! Trips are stored in the shared state of the vehicle room (see `RoomService::depart_vehicle`).
+//! A background tick docks vehicles whose trip is over and moves everyone aboard into the room of
+//! the stop, and sends scheduled vehicles off again while anyone is aboard. Because the trip is
+//! stored, a vehicle caught in transit by a restart arrives on the first tick afterwards.
+
+use crate::error::{AppResult, DomainError};
+use crate::lua::LuaPool;
+use crate::models::room::BlueprintRoom;
+use crate::models::types::{RealmId, RoomId};
+use crate::models::vehicle::VehicleState;
+use crate::state::hazards::force_move;
+use crate::state::registry::{ConnectedPlayer, Registry};
+use std::collections::HashMap;
+use std::sync::Arc;
+use std::time::Duration;
+
+/// How often the tick looks for arriving and scheduled vehicles
+const VEHICLE_TICK: Duration = Duration::from_secs(1);
+
+/// Docks arriving vehicles and departs scheduled ones, forever. Spawned once when the server
+/// starts.
+pub async fn run_vehicle_tick(registry: Arc<Registry>, lua_tx: LuaPool) {
+    let mut interval = tokio::time::interval(VEHICLE_TICK);
+    // When passengers were first seen aboard a vehicle that never moved, so its schedule has
+    // something to count from
+    let mut boarded: HashMap<(RealmId, RoomId), i64> = HashMap::new();
+
+    loop {
+        interval.tick().await;
+        let now = chrono::Utc::now().timestamp();
+
+        match registry.services.room.vehicles_in_transit().await {
+            Ok(trips) => {
+                for (realm_id, room_id, state) in trips {
+                    if state.arrives.is_some_and(|t| t <= now)
+                        && let Err(e) = arrive(&registry, &lua_tx, realm_id, room_id).await
+                    {
+                        tracing::warn!(%realm_id, %room_id, error = %e, "vehicle: arrival failed");
+                    }
+                }
+            }
+            Err(e) => tracing::warn!(error = %e, "vehicle: cannot read trips"),
+        }
+
+        let mut occupied: HashMap<(RealmId, RoomId), BlueprintRoom> = HashMap::new();
+        for p in registry.connected_where(|_| true) {
+            let Some(cursor) = p.sess.read().get_cursor() else {
+                continue;
+            };
+            if cursor
+                .room
+                .blueprint
+                .vehicle
+                .as_ref()
+                .is_some_and(|v| v.schedule.is_some())
+            {
+                occupied.insert((cursor.realm_id, cursor.room_id), cursor.room.blueprint.clone());
+            }
+        }
+        boarded.retain(|k, _| occupied.contains_key(k));
+
+        for ((realm_id, room_id), room) in occupied {
+            if let Err(e) = run_schedule(&registry, &mut boarded, realm_id, &room, now).await {
+                tracing::warn!(%realm_id, %room_id, error = %e, "vehicle: scheduled departure failed");
+            }
+        }
+    }
+}
+
+async fn run_schedule(
+    registry: &Registry,
+    boarded: &mut HashMap<(RealmId, RoomId), i64>,
+    realm_id: RealmId,
+    room: &BlueprintRoom,
+    now: i64,
+) -> AppResult<()> {
+    let Some(vehicle) = &room.vehicle else {
+        return Ok(());
+    };
+    let Some(schedule) = vehicle.schedule else {
+        return Ok(());
+    };
+
+    let mut state = registry.services.room.vehicle_state(realm_id, room.id).await?;
+    if state.since == 0 {
+        state.since = *boarded.entry((realm_id, room.id)).or_insert(now);
+    }
+    if state.schedule_due(schedule, now) {
+        boarded.remove(&(realm_id, room.id));
+        depart(registry, realm_id, room, vehicle.next_stop(state.stop)).await?;
+    }
+    Ok(())
+}
+
+/// Sends the vehicle room off to stop `to` and tells the passengers and the players at the stop
+/// it leaves. Returns the new state, or None when it is already there or travelling.
+pub async fn depart(
+    registry: &Registry,
+    realm_id: RealmId,
+    room: &BlueprintRoom,
+    to: usize,
+) -> AppResult<Option<VehicleState>> {
+    let Some(vehicle) = &room.vehicle else {
+        return Err(DomainError::Validation {
+            field: "vehicle",
+            message: format!("room '{}' is not a vehicle", room.key),
+        });
+    };
+    let Some(dest) = vehicle.stops.get(to) else {
+        return Err(DomainError::NotFound(format!("stop {} of vehicle '{}'", to, room.key)));
+    };
+
+    let Some(state) = registry
+        .services
+        .room
+        .depart_vehicle(realm_id, room.id, vehicle, to)
+        .await?
+    else {
+        return Ok(None);
+    };
+
+    broadcast(
+        registry,
+        realm_id,
+        room.id,
+        &format!("The doors close. The {} sets off towards {}.", room.title, dest.name()),
+    )
+    .await;
+    if let Some(stop_room) = stop_room_id(registry, realm_id, room, state.stop).await? {
+        broadcast(
+            registry,
+            realm_id,
+            stop_room,
+            &format!("The doors of the {} close and it departs.", room.title),
+        )
+        .await;
+    }
+
+    Ok(Some(state))
+}
+
+/// Docks the vehicle at its destination and lets everyone aboard step out there
+async fn arrive(registry: &Arc<Registry>, lua_tx: &LuaPool, realm_id: RealmId, room_id: RoomId) -> AppResult<()> {
+    let room = registry.services.room.blueprint_room(realm_id, room_id).await?;
+    let Some(state) = registry.services.room.arrive_vehicle(realm_id, room_id).await? else {
+        return Ok(());
+    };
+    let Some(stop_room) = stop_room_id(registry, realm_id, &room, state.stop).await? else {
+        return Ok(());
+    };
+    let stop_name = room
+        .vehicle
+        .as_ref()
+        .and_then(|v| v.stops.get(state.stop))
+        .map(|s| s.name().to_string())
+        .unwrap_or_default();
+
+    broadcast(registry, realm_id, stop_room, &format!("The {} arrives.", room.title)).await;
+
+    for p in in_realm(registry.players_in_room(room_id), realm_id) {
+        let Some(cursor) = p.sess.read().get_cursor() else {
+            continue;
+        };
+        p.output
+            .line(format!("The {} arrives at {}. You step out.", room.title, stop_name))
+            .await;
+        force_move(registry, lua_tx, &p, &cursor, stop_room).await?;
+    }
+
+    Ok(())
+}
+
+/// Room id of a stop, None when the vehicle has no such stop
+async fn stop_room_id(
+    registry: &Registry,
+    realm_id: RealmId,
+    room: &BlueprintRoom,
+    stop: usize,
+) -> AppResult<Option<RoomId>> {
+    let Some(stop) = room.vehicle.as_ref().and_then(|v| v.stops.get(stop)) else {
+        return Ok(None);
+    };
+    let id = registry.services.room.get_room_id_by_key(realm_id, &stop.room).await?;
+    if id.is_none() {
+        tracing::warn!(vehicle = %room.key, stop = %stop.room, "vehicle: stop room does not exist");
+    }
+    Ok(id)
+}
+
+fn in_realm(players: Vec<ConnectedPlayer>, realm_id: RealmId) -> impl Iterator<Item = ConnectedPlayer> {
+    players
+        .into_iter()
+        .filter(move |p| p.sess.read().get_cursor().is_some_and(|c| c.realm_id == realm_id))
+}
+
+/// Sends a line to the players in this room of this realm
+async fn broadcast(registry: &Registry, realm_id: RealmId, room_id: RoomId, msg: &str) {
+    for p in in_realm(registry.players_in_room(room_id), realm_id) {
+        p.output.line(msg).await;
+    }
+}
@@ -0,0 +1,25 @@
+//! Sweeping away expired zone state (see `port4k.zone_set`).
+//!
+//! Expired entries already read as unset; the sweep only deletes their rows.
+
+use crate::state::registry::Registry;
+use std::sync::Arc;
+use std::time::Duration;
+
+/// How often the tick deletes expired zone state
+const ZONE_KV_SWEEP_TICK: Duration = Duration::from_secs(60);
+
+/// Deletes expired zone state, forever. Spawned once when the server starts.
+pub async fn run_zone_kv_sweep(registry: Arc<Registry>) {
+    let mut interval = tokio::time::interval(ZONE_KV_SWEEP_TICK);
+
+    loop {
+        interval.tick().await;
+
+        match registry.services.ambience.sweep_expired().await {
+            Ok(0) => {}
+            Ok(n) => tracing::debug!(count = n, "zone state: expired entries swept"),
+            Err(e) => tracing::warn!(error = %e, "zone state: cannot sweep expired entries"),
+        }
+    }
+}
@@ -55,6 +55,11 @@ pub fn resolve_content_subdir(base: &Path, subdir: &str) -> AppResult<PathBuf> {
 }
 
 pub fn list_yaml_files_guarded(dir: &Path) -> AppResult<Vec<PathBuf>> {
+    list_files_guarded(dir, &["yml", "yaml"])
+}
+
+/// Like `list_yaml_files_guarded`, for files with one of the `extensions`
+pub fn list_files_guarded(dir: &Path, extensions: &[&str]) -> AppResult<Vec<PathBuf>> {
     use std::fs;
 
     let mut files = Vec::new();
@@ -69,10 +74,12 @@ pub fn list_yaml_files_guarded(dir: &Path) -> AppResult<Vec<PathBuf>> {
             continue;
         }
 
-        // Only .yml / .yaml
-        match path.extension().and_then(|s| s.to_str()) {
-            Some("yml") | Some("yaml") => {}
-            _ => continue,
+        if !path
+            .extension()
+            .and_then(|s| s.to_str())
+            .is_some_and(|ext| extensions.contains(&ext))
+        {
+            continue;
         }
 
         if !ALLOW_SYMLINKS
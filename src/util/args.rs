@@ -26,6 +26,15 @@ pub fn normalize_dir(s: &str) -> Option<&'static str> {
     }
 }
 
+/// What follows the first `n` words of `s`, for arguments that must keep their case and spacing
+pub fn words_after(s: &str, n: usize) -> &str {
+    let mut rest = s.trim_start();
+    for _ in 0..n {
+        rest = rest.find(char::is_whitespace).map_or("", |i| rest[i..].trim_start());
+    }
+    rest.trim_end()
+}
+
 // pub fn split_args_quoted(s: &str) -> Vec<String> {
 //     let mut out = Vec::new();
 //     let mut cur = String::new();
@@ -59,3 +68,16 @@ pub fn normalize_dir(s: &str) -> Option<&'static str> {
 //     }
 //     out
 // }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    #[test]
+    fn t_words_after() {
+        let raw = "@event add 0 18 * * 5   announce  Happy hour at the Bar!";
+        assert_eq!(words_after(raw, 8), "Happy hour at the Bar!");
+        assert_eq!(words_after(raw, 2), "0 18 * * 5   announce  Happy hour at the Bar!");
+        assert_eq!(words_after("@event add", 8), "");
+    }
+}
@@ -1,3 +1,15 @@
+//! Telnet protocol handling for the input side of a connection.
+//!
+//! `TelnetMachine` is fed every byte the client sends. It consumes any IAC sequence, whether or
+//! not the client negotiates cooperatively: two-byte commands (NOP, GA, AYT, ...), option
+//! negotiation and subnegotiations of any option, with bounded buffering. Options are tracked per
+//! side so acknowledgements are never answered again, which keeps negotiation from looping.
+//!
+//! Subnegotiations (`IAC SB <option> ... IAC SE`) are dispatched by option to a `Subnegotiation`
+//! handler. NAWS (window size), MTTS (terminal type) and GMCP are registered by default; more can
+//! be plugged in with `TelnetMachine::register`.
+
+use std::collections::HashMap;
 use tokio::io::{AsyncWrite, AsyncWriteExt};
 
 const IAC: u8 = 255; // Interpret As Command
@@ -10,19 +22,40 @@ const SE: u8 = 240; // Subnegotiation end
 
 const ECHO: u8 = 1; // Who echoes (server will echo if we send WILL ECHO)
 const SGA: u8 = 3; // Suppress Go-Ahead (interactive mode)
-const TTYPE: u8 = 24; // Terminal type
+const TTYPE: u8 = 24; // Terminal type, MTTS when cycled
 const NAWS: u8 = 31; // Negotiate About Window Size
 const LINEMODE: u8 = 34; // We want this OFF for char-at-a-time
+const GMCP: u8 = 201; // Generic MUD Communication Protocol
+
+const TTYPE_IS: u8 = 0;
+const TTYPE_SEND: u8 = 1;
 
-#[derive(Debug)]
+/// Longest subnegotiation payload kept; longer ones are consumed and dropped
+const MAX_SUBNEGOTIATION: usize = 8192;
+
+#[derive(Debug, Clone, PartialEq, Eq)]
 pub enum TelnetIn {
     /// Regular data byte
     Data(u8),
     /// Client resized terminal; cols and rows in characters
     Naws { cols: u16, rows: u16 },
+    /// What the client told about itself through TTYPE/MTTS
+    Terminal(TerminalInfo),
+    /// A GMCP message, e.g. package "Core.Hello" with JSON data
+    Gmcp { package: String, data: String },
 }
 
-#[derive(Debug)]
+/// Client and terminal reported through MTTS. Clients without MTTS only report a terminal type,
+/// which ends up as `client`.
+#[derive(Debug, Clone, Default, PartialEq, Eq)]
+pub struct TerminalInfo {
+    pub client: String,
+    pub terminal: Option<String>,
+    /// MTTS bit vector (1 = ANSI, 4 = UTF-8, 8 = 256 colors, ...)
+    pub mtts: Option<u32>,
+}
+
+#[derive(Debug, Default)]
 pub struct TelnetResponse {
     /// Event to be processed (if any)
     pub event: Option<TelnetIn>,
@@ -30,17 +63,69 @@ pub struct TelnetResponse {
     pub response: Option<Vec<u8>>,
 }
 
+impl TelnetResponse {
+    fn none() -> Self {
+        Self::default()
+    }
+
+    fn event(event: TelnetIn) -> Self {
+        Self {
+            event: Some(event),
+            response: None,
+        }
+    }
+
+    fn respond(bytes: Vec<u8>) -> Self {
+        Self {
+            event: None,
+            response: Some(bytes),
+        }
+    }
+}
+
+/// Handles the subnegotiations of one option
+pub trait Subnegotiation: Send {
+    /// Whether the option is enabled on our side (GMCP) rather than the client's (NAWS, TTYPE)
+    fn server_side(&self) -> bool {
+        false
+    }
+
+    /// Sent once the client agrees to the option, e.g. a request for its terminal type
+    fn on_enabled(&mut self) -> Option<Vec<u8>> {
+        None
+    }
+
+    /// The payload of `IAC SB <option> <payload> IAC SE`, with IAC IAC unescaped
+    fn handle(&mut self, payload: &[u8]) -> TelnetResponse;
+}
+
+#[derive(Debug, Clone, Copy, PartialEq, Eq)]
+enum State {
+    /// Plain data
+    Data,
+    /// After IAC
+    Iac,
+    /// After IAC WILL/WONT/DO/DONT, waiting for the option
+    Negotiate(u8),
+    /// After IAC SB, waiting for the option
+    SbOption,
+    /// In a subnegotiation, collecting its payload
+    Sb(u8),
+    /// After IAC in a subnegotiation
+    SbIac(u8),
+}
+
 pub struct TelnetMachine {
-    /// Are we in an IAC sequence?
-    in_iac: bool,
-    /// If in_iac, which command are we processing (WILL/WONT/DO/DONT/SB)
-    in_cmd: Option<u8>,
-    /// Are we inside a subnegotiation (after SB, before IAC SE)?
-    in_sb: bool,
-    /// If in_sb, which option are we negotiating?
-    sb_opt: u8,
-    /// If in_sb, buffer of data bytes collected so far
+    state: State,
+    /// Payload of the current subnegotiation
     sb_buf: Vec<u8>,
+    /// Set when the current subnegotiation is too long to keep
+    sb_overflow: bool,
+    /// Options enabled on our side (we WILL)
+    ours: [bool; 256],
+    /// Options enabled on the client's side (they WILL)
+    theirs: [bool; 256],
+    handlers: HashMap<u8, Box<dyn Subnegotiation>>,
 }
 
 impl Default for TelnetMachine {
@@ -51,13 +136,24 @@ impl Default for TelnetMachine {
 
 impl TelnetMachine {
     pub fn new() -> Self {
-        Self {
-            in_iac: false,
-            in_cmd: None,
-            in_sb: false,
-            sb_opt: 0,
+        let mut machine = Self {
+            state: State::Data,
             sb_buf: Vec::with_capacity(16),
-        }
+            sb_overflow: false,
+            ours: [false; 256],
+            theirs: [false; 256],
+            handlers: HashMap::new(),
+        };
+        machine.register(NAWS, Box::new(NawsHandler));
+        machine.register(TTYPE, Box::new(MttsHandler::default()));
+        machine.register(GMCP, Box::new(GmcpHandler));
+        machine
+    }
+
+    /// Routes the subnegotiations of `option` to `handler`, and accepts the option when the client
+    /// offers it (or asks us for it, for a server side option)
+    pub fn register(&mut self, option: u8, handler: Box<dyn Subnegotiation>) {
+        self.handlers.insert(option, handler);
     }
 
     /// Send a sane baseline: character mode, we echo (so client stops local echo), and ask for NAWS.
@@ -73,156 +169,137 @@ impl TelnetMachine {
         // Ask for window size; if client supports it, we'll get SB NAWS cols rows
         send_do(w, NAWS).await?;
 
-        // (Optional) ask for terminal type
-        // send_do(w, TTYPE).await?;
+        // Asked for as enabled, so the client's agreement is not answered again
+        self.ours[SGA as usize] = true;
+        self.ours[ECHO as usize] = true;
+        self.theirs[SGA as usize] = true;
+        self.theirs[NAWS as usize] = true;
 
         Ok(())
     }
 
     /// Feed one byte; respond to negotiations and produce `TelnetIn::Data` for your editor.
     pub fn push(&mut self, b: u8) -> TelnetResponse {
-        if !self.in_iac {
-            if b == IAC {
-                self.in_iac = true;
-                return TelnetResponse {
-                    event: None,
-                    response: None,
-                };
+        match (self.state, b) {
+            (State::Data, IAC) => {
+                self.state = State::Iac;
+                TelnetResponse::none()
             }
-            // Normal data byte
-            return if !self.in_sb {
-                TelnetResponse {
-                    event: Some(TelnetIn::Data(b)),
-                    response: None,
-                }
-            } else {
-                // Inside SB: collect until IAC SE
-                self.sb_buf.push(b);
-                TelnetResponse {
-                    event: None,
-                    response: None,
-                }
-            };
-        }
+            (State::Data, b) => TelnetResponse::event(TelnetIn::Data(b)),
 
-        // We are after an IAC
-        self.in_iac = false;
-
-        match b {
-            IAC => {
-                // Escaped 0xFF in data
-                if !self.in_sb {
-                    TelnetResponse {
-                        event: Some(TelnetIn::Data(IAC)),
-                        response: None,
-                    }
-                } else {
-                    self.sb_buf.push(IAC);
-                    TelnetResponse {
-                        event: None,
-                        response: None,
-                    }
-                }
+            // Escaped 0xFF in data
+            (State::Iac, IAC) => {
+                self.state = State::Data;
+                TelnetResponse::event(TelnetIn::Data(IAC))
+            }
+            (State::Iac, WILL | WONT | DO | DONT) => {
+                self.state = State::Negotiate(b);
+                TelnetResponse::none()
+            }
+            (State::Iac, SB) => {
+                self.state = State::SbOption;
+                TelnetResponse::none()
             }
-            DO | DONT | WILL | WONT => {
-                self.in_cmd = Some(b);
-                self.in_iac = true; // expect option next
-                TelnetResponse {
-                    event: None,
-                    response: None,
+            // NOP, GA, AYT, BRK, IP, a stray SE, ...: nothing to do for a MUD
+            (State::Iac, _) => {
+                self.state = State::Data;
+                TelnetResponse::none()
+            }
+
+            (State::Negotiate(cmd), opt) => {
+                self.state = State::Data;
+                match self.negotiate(cmd, opt) {
+                    Some(bytes) => TelnetResponse::respond(bytes),
+                    None => TelnetResponse::none(),
                 }
             }
-            SB => {
-                self.in_sb = true;
+
+            (State::SbOption, opt) => {
+                self.state = State::Sb(opt);
                 self.sb_buf.clear();
-                self.in_iac = true; // next should be the option byte
-                self.in_cmd = Some(SB);
-                TelnetResponse {
-                    event: None,
-                    response: None,
-                }
+                self.sb_overflow = false;
+                TelnetResponse::none()
+            }
+            (State::Sb(opt), IAC) => {
+                self.state = State::SbIac(opt);
+                TelnetResponse::none()
+            }
+            (State::Sb(_), b) => {
+                self.collect(b);
+                TelnetResponse::none()
             }
-            SE => {
-                // End subnegotiation
-                if self.in_sb {
-                    let opt = self.sb_opt;
-                    let data = std::mem::take(&mut self.sb_buf);
-                    self.in_sb = false;
-                    // Handle NAWS: 4 bytes: cols_hi, cols_lo, rows_hi, rows_lo
-                    if opt == NAWS && data.len() >= 4 {
-                        let cols = u16::from_be_bytes([data[0], data[1]]);
-                        let rows = u16::from_be_bytes([data[2], data[3]]);
-                        return TelnetResponse {
-                            event: Some(TelnetIn::Naws { cols, rows }),
-                            response: None,
-                        };
-                    }
+            (State::SbIac(opt), IAC) => {
+                self.state = State::Sb(opt);
+                self.collect(IAC);
+                TelnetResponse::none()
+            }
+            (State::SbIac(opt), SE) => {
+                self.state = State::Data;
+                let payload = std::mem::take(&mut self.sb_buf);
+                if self.sb_overflow {
+                    return TelnetResponse::none();
                 }
-                TelnetResponse {
-                    event: None,
-                    response: None,
+                match self.handlers.get_mut(&opt) {
+                    Some(handler) => handler.handle(&payload),
+                    None => TelnetResponse::none(),
                 }
             }
-            opt => {
-                // This branch is hit when we were expecting an option byte after DO/DON'T/WILL/WON'T, or SB's option.
-                if let Some(cmd) = self.in_cmd.take() {
-                    let response = match cmd {
-                        DO => {
-                            // Client requests we WILL <opt>
-                            match opt {
-                                ECHO => Some(make_will(ECHO)),         // We'll echo (client stops local echo)
-                                SGA => Some(make_will(SGA)),           // We'll suppress go-ahead
-                                LINEMODE => Some(make_wont(LINEMODE)), // We refuse LINEMODE
-                                NAWS => None,
-                                _ => Some(make_wont(opt)), // We won't do any unknown options
-                            }
-                        }
-                        DONT => {
-                            // Client says DON'T <opt> (stop doing it)
-                            match opt {
-                                ECHO | SGA | LINEMODE => Some(make_wont(opt)),
-                                _ => None,
-                            }
-                        }
-                        WILL => {
-                            // Client will do <opt>
-                            match opt {
-                                ECHO => Some(make_do(ECHO)),           // ok, you handle echo (we'd usually prefer we echo)
-                                SGA => Some(make_do(SGA)),             // ok, you suppress go-ahead too
-                                LINEMODE => Some(make_dont(LINEMODE)), // nope, please don't
-                                NAWS => Some(make_do(NAWS)),           // yes, please send SB NAWS
-                                TTYPE => Some(make_do(TTYPE)),         // yes, please send SB TTYPE
-                                _ => Some(make_dont(opt)),
-                            }
-                        }
-                        WONT => None,
-                        SB => {
-                            // This was SB option byte
-                            self.sb_opt = opt;
-                            return TelnetResponse {
-                                event: None,
-                                response: None,
-                            };
-                        }
-                        _ => None,
-                    };
-
-                    return TelnetResponse { event: None, response };
-                }
+            // A client that never sends SE: drop the subnegotiation and read the command
+            (State::SbIac(_), b) => {
+                self.sb_buf.clear();
+                self.state = State::Iac;
+                self.push(b)
+            }
+        }
+    }
 
-                // Unexpected lone option byte; if in SB, treat as data start
-                if self.in_sb {
-                    self.sb_opt = opt;
-                }
-                TelnetResponse {
-                    event: None,
-                    response: None,
+    fn collect(&mut self, b: u8) {
+        if self.sb_buf.len() < MAX_SUBNEGOTIATION {
+            self.sb_buf.push(b);
+        } else {
+            self.sb_overflow = true;
+        }
+    }
+
+    /// Answer to `IAC <cmd> <opt>`. Requests for a state an option is already in are
+    /// acknowledgements and get no answer.
+    fn negotiate(&mut self, cmd: u8, opt: u8) -> Option<Vec<u8>> {
+        let i = opt as usize;
+        match cmd {
+            // Client asks us to do <opt>
+            DO if self.ours[i] => None,
+            DO if matches!(opt, ECHO | SGA) || self.handlers.get(&opt).is_some_and(|h| h.server_side()) => {
+                self.ours[i] = true;
+                Some(make_will(opt))
+            }
+            DO => Some(make_wont(opt)),
+            DONT if self.ours[i] => {
+                self.ours[i] = false;
+                Some(make_wont(opt))
+            }
+            DONT => None,
+
+            // Client offers to do <opt>
+            WILL if self.theirs[i] => None,
+            WILL if opt == SGA || self.handlers.get(&opt).is_some_and(|h| !h.server_side()) => {
+                self.theirs[i] = true;
+                let mut out = make_do(opt);
+                if let Some(bytes) = self.handlers.get_mut(&opt).and_then(|h| h.on_enabled()) {
+                    out.extend(bytes);
                 }
+                Some(out)
+            }
+            WILL => Some(make_dont(opt)),
+            WONT if self.theirs[i] => {
+                self.theirs[i] = false;
+                Some(make_dont(opt))
             }
+            _ => None,
         }
     }
 
     pub async fn set_echo<W: AsyncWrite + Unpin>(&mut self, w: &mut W, enabled: bool) -> std::io::Result<()> {
+        self.ours[ECHO as usize] = enabled;
         if enabled {
             // go back to: server will echo -> client stops local echo
             send_will(w, ECHO).await
@@ -234,6 +311,93 @@ impl TelnetMachine {
     }
 }
 
+/// NAWS: 4 bytes, cols_hi, cols_lo, rows_hi, rows_lo
+struct NawsHandler;
+
+impl Subnegotiation for NawsHandler {
+    fn handle(&mut self, payload: &[u8]) -> TelnetResponse {
+        match payload {
+            [c1, c2, r1, r2, ..] => TelnetResponse::event(TelnetIn::Naws {
+                cols: u16::from_be_bytes([*c1, *c2]),
+                rows: u16::from_be_bytes([*r1, *r2]),
+            }),
+            _ => TelnetResponse::none(),
+        }
+    }
+}
+
+/// MTTS: each `SEND` gets the next of client name, terminal type and "MTTS <bits>". A client
+/// repeating itself has nothing more to tell.
+#[derive(Default)]
+struct MttsHandler {
+    names: Vec<String>,
+    done: bool,
+}
+
+impl MttsHandler {
+    /// Most names asked for
+    const MAX_NAMES: usize = 3;
+
+    fn info(&self) -> TerminalInfo {
+        let mtts = self
+            .names
+            .iter()
+            .find_map(|n| n.strip_prefix("MTTS ").and_then(|bits| bits.trim().parse().ok()));
+        let mut names = self.names.iter().filter(|n| !n.starts_with("MTTS "));
+        TerminalInfo {
+            client: names.next().cloned().unwrap_or_default(),
+            terminal: names.next().cloned(),
+            mtts,
+        }
+    }
+}
+
+impl Subnegotiation for MttsHandler {
+    fn on_enabled(&mut self) -> Option<Vec<u8>> {
+        Some(make_ttype_send())
+    }
+
+    fn handle(&mut self, payload: &[u8]) -> TelnetResponse {
+        let [TTYPE_IS, name @ ..] = payload else {
+            return TelnetResponse::none();
+        };
+        if self.done {
+            return TelnetResponse::none();
+        }
+        let name = String::from_utf8_lossy(name).trim().to_string();
+        let repeated = self.names.last() == Some(&name);
+        if !repeated {
+            self.names.push(name);
+        }
+        if repeated || self.names.len() >= Self::MAX_NAMES {
+            self.done = true;
+            return TelnetResponse::event(TelnetIn::Terminal(self.info()));
+        }
+        TelnetResponse::respond(make_ttype_send())
+    }
+}
+
+/// GMCP: "<package> <json>", the data being optional
+struct GmcpHandler;
+
+impl Subnegotiation for GmcpHandler {
+    fn server_side(&self) -> bool {
+        true
+    }
+
+    fn handle(&mut self, payload: &[u8]) -> TelnetResponse {
+        let text = String::from_utf8_lossy(payload);
+        let (package, data) = text.split_once(' ').unwrap_or((&text, ""));
+        if package.is_empty() {
+            return TelnetResponse::none();
+        }
+        TelnetResponse::event(TelnetIn::Gmcp {
+            package: package.to_string(),
+            data: data.trim().to_string(),
+        })
+    }
+}
+
 // Helper functions to build IAC response bytes
 fn make_do(opt: u8) -> Vec<u8> {
     vec![IAC, DO, opt]
@@ -251,6 +415,10 @@ fn make_wont(opt: u8) -> Vec<u8> {
     vec![IAC, WONT, opt]
 }
 
+fn make_ttype_send() -> Vec<u8> {
+    vec![IAC, SB, TTYPE, TTYPE_SEND, IAC, SE]
+}
+
 // Keep these for initial negotiation
 async fn send3<W: AsyncWrite + Unpin>(w: &mut W, a: u8, b: u8, c: u8) -> std::io::Result<()> {
     w.write_all(&[a, b, c]).await
@@ -265,7 +433,132 @@ async fn send_dont<W: AsyncWrite + Unpin>(w: &mut W, opt: u8) -> std::io::Result
 async fn send_will<W: AsyncWrite + Unpin>(w: &mut W, opt: u8) -> std::io::Result<()> {
     send3(w, IAC, WILL, opt).await
 }
-#[allow(unused)]
 async fn send_wont<W: AsyncWrite + Unpin>(w: &mut W, opt: u8) -> std::io::Result<()> {
     send3(w, IAC, WONT, opt).await
 }
+
+#[cfg(test)]
+mod tests {
+    use super::*;
+
+    /// Events and responses for `bytes`
+    fn feed(m: &mut TelnetMachine, bytes: &[u8]) -> (Vec<TelnetIn>, Vec<u8>) {
+        let mut events = Vec::new();
+        let mut out = Vec::new();
+        for b in bytes {
+            let r = m.push(*b);
+            events.extend(r.event);
+            out.extend(r.response.unwrap_or_default());
+        }
+        (events, out)
+    }
+
+    fn data(s: &str) -> Vec<TelnetIn> {
+        s.bytes().map(TelnetIn::Data).collect()
+    }
+
+    #[test]
+    fn t_telnet_consumes_commands() {
+        let mut m = TelnetMachine::new();
+        // NOP, GA, AYT, an escaped 0xFF and a subnegotiation of an unknown option
+        let (events, out) = feed(
+            &mut m,
+            &[
+                b'a', IAC, 241, b'b', IAC, 249, IAC, 246, IAC, IAC, IAC, SB, 99, 1, 2, IAC, SE, b'c',
+            ],
+        );
+        let mut expected = data("ab");
+        expected.push(TelnetIn::Data(IAC));
+        expected.extend(data("c"));
+        assert_eq!(events, expected);
+        assert!(out.is_empty());
+
+        // Option bytes that look like commands are still options
+        let (events, out) = feed(&mut m, &[IAC, DO, SB, b'x']);
+        assert_eq!(events, data("x"));
+        assert_eq!(out, make_wont(SB));
+    }
+
+    #[test]
+    fn t_telnet_negotiation_does_not_loop() {
+        let mut m = TelnetMachine::new();
+        m.ours[ECHO as usize] = true;
+        assert_eq!(feed(&mut m, &[IAC, DO, ECHO]).1, Vec::<u8>::new());
+        assert_eq!(feed(&mut m, &[IAC, DONT, ECHO]).1, make_wont(ECHO));
+        assert_eq!(feed(&mut m, &[IAC, DONT, ECHO]).1, Vec::<u8>::new());
+        assert_eq!(feed(&mut m, &[IAC, WILL, LINEMODE]).1, make_dont(LINEMODE));
+        assert_eq!(feed(&mut m, &[IAC, DO, GMCP]).1, make_will(GMCP));
+        assert_eq!(feed(&mut m, &[IAC, DO, GMCP]).1, Vec::<u8>::new());
+        assert_eq!(feed(&mut m, &[IAC, DO, NAWS]).1, make_wont(NAWS));
+    }
+
+    #[test]
+    fn t_telnet_subnegotiations() {
+        let mut m = TelnetMachine::new();
+        let (events, _) = feed(&mut m, &[IAC, SB, NAWS, 0, 120, 0, 40, IAC, SE]);
+        assert_eq!(events, vec![TelnetIn::Naws { cols: 120, rows: 40 }]);
+
+        // An escaped 255 in the payload
+        let (events, _) = feed(&mut m, &[IAC, SB, NAWS, 0, IAC, IAC, 0, 40, IAC, SE]);
+        assert_eq!(events, vec![TelnetIn::Naws { cols: 255, rows: 40 }]);
+
+        let mut gmcp = vec![IAC, SB, GMCP];
+        gmcp.extend(b"Core.Hello {\"client\":\"Mudlet\"}");
+        gmcp.extend([IAC, SE]);
+        let (events, _) = feed(&mut m, &gmcp);
+        assert_eq!(
+            events,
+            vec![TelnetIn::Gmcp {
+                package: "Core.Hello".into(),
+                data: "{\"client\":\"Mudlet\"}".into()
+            }]
+        );
+
+        // Too long to keep: consumed and dropped, the data after it still comes through
+        let mut long = vec![IAC, SB, GMCP];
+        long.extend(std::iter::repeat_n(b'x', MAX_SUBNEGOTIATION + 10));
+        long.extend([IAC, SE, b'k']);
+        assert_eq!(feed(&mut m, &long).0, data("k"));
+    }
+
+    #[test]
+    fn t_telnet_mtts() {
+        let mut m = TelnetMachine::new();
+        let (_, out) = feed(&mut m, &[IAC, WILL, TTYPE]);
+        let mut expected = make_do(TTYPE);
+        expected.extend(make_ttype_send());
+        assert_eq!(out, expected);
+
+        let ttype = |name: &str| {
+            let mut v = vec![IAC, SB, TTYPE, TTYPE_IS];
+            v.extend(name.bytes());
+            v.extend([IAC, SE]);
+            v
+        };
+        assert_eq!(feed(&mut m, &ttype("MUDLET")).1, make_ttype_send());
+        assert_eq!(feed(&mut m, &ttype("XTERM-256COLOR")).1, make_ttype_send());
+        let (events, _) = feed(&mut m, &ttype("MTTS 137"));
+        assert_eq!(
+            events,
+            vec![TelnetIn::Terminal(TerminalInfo {
+                client: "MUDLET".into(),
+                terminal: Some("XTERM-256COLOR".into()),
+                mtts: Some(137),
+            })]
+        );
+
+        // Without MTTS the client repeats its only terminal type
+        let mut m = TelnetMachine::new();
+        feed(&mut m, &[IAC, WILL, TTYPE]);
+        feed(&mut m, &ttype("ANSI"));
+        let (events, _) = feed(&mut m, &ttype("ANSI"));
+        assert_eq!(
+            events,
+            vec![TelnetIn::Terminal(TerminalInfo {
+                client: "ANSI".into(),
+                terminal: None,
+                mtts: None,
+            })]
+        );
+    }
+}
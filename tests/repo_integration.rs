@@ -0,0 +1,2180 @@
+//! Repository tests against a real Postgres, so a migration that renames a column or drops a
+//! constraint breaks a test instead of a query in production. See `support` for the setup; the
+//! tests skip when `PORT4K_TEST_DATABASE_URL` is not set.
+
+mod support;
+
+use chrono::{DateTime, Duration, Utc};
+use port4k::db::repo::{
+    AccountDeletionRepo, AccountDeletionRepository, AccountRepo, AccountRepository, AmbienceRepo, AmbienceRepository,
+    BlueprintAndRoomKey, ClockRepo, ClockRepository, CollaboratorRepo, CollaboratorRepository, CraftingRepo,
+    CraftingRepository, CurrencyRepo, CurrencyRepository, FeatureRepo, FeatureRepository, InventoryRepo,
+    InventoryRepository, LoginAttemptRepo, LoginAttemptRepository, MaintenanceRepo, MaintenanceRepository,
+    ModerationRepo, ModerationRepository, PlayerExportRepo, PlayerExportRepository, PlaytestRepo, PlaytestRepository,
+    RealmRepo, RealmRepository, RecordingRepo, RecordingRepository, ReviewRepo, ReviewRepository, RoomRepo,
+    RoomRepository, ScheduleRepo, ScheduleRepository, SubmissionRepo, SubmissionRepository, TutorialRepo,
+    TutorialRepository, UserRepo, UserRepository,
+};
+use port4k::lua::ScriptHook;
+use port4k::lua::lint::ChunkKind;
+use port4k::models::account::{Account, MAX_HEALTH};
+use port4k::models::blueprint::BlueprintStatus;
+use port4k::models::collaborator::{BlueprintRight, BlueprintRole};
+use port4k::models::feature::FeatureScope;
+use port4k::models::inventory::ItemLocation;
+use port4k::models::maintenance::total;
+use port4k::models::realm::{Realm, RealmKind};
+use port4k::models::recording::{NewRecording, RecordingEvent, RecordingEventKind};
+use port4k::models::report::NewReport;
+use port4k::models::room::SpawnedObject;
+use port4k::models::schedule::{CronSchedule, EventAction};
+use port4k::models::settings::PlayerSettings;
+use port4k::models::submission::SubmissionStatus;
+use port4k::models::types::{AccountId, Direction, ObjectId, RealmId};
+use port4k::services::{InventoryService, RoomService};
+use serde_json::json;
+use std::net::IpAddr;
+use std::sync::Arc;
+use support::{TestDb, World};
+
+#[tokio::test]
+async fn t_migrations_are_idempotent() {
+    let Some(t) = TestDb::start().await else { return };
+
+    assert!(t.db.migrate().await.unwrap().is_empty());
+}
+
+#[tokio::test]
+async fn t_catalog_queries() {
+    let Some(t) = TestDb::start().await else { return };
+    let w = World::seed(&t).await;
+    let repo = InventoryRepository::new(t.db.clone());
+
+    let coin = repo.get_item_by_key(w.realm_id, "coin").await.unwrap();
+    assert!(coin.stackable);
+    assert_eq!(coin.nouns, vec!["coin".to_string()]);
+    assert_eq!(repo.get_item_by_id(coin.id).await.unwrap().item_key, "coin");
+    assert_eq!(
+        repo.find_item_by_noun(w.realm_id, "wrench")
+            .await
+            .unwrap()
+            .unwrap()
+            .item_key,
+        "wrench"
+    );
+    assert!(repo.find_item_by_noun(w.realm_id, "spoon").await.unwrap().is_none());
+    assert_eq!(repo.get_realm_catalog(w.realm_id).await.unwrap().len(), 3);
+    assert!(repo.get_item_by_key(w.realm_id, "spoon").await.is_err());
+}
+
+#[tokio::test]
+async fn t_spawn_item_stacks() {
+    let Some(t) = TestDb::start().await else { return };
+    let w = World::seed(&t).await;
+    let repo = InventoryRepository::new(t.db.clone());
+
+    let a = repo
+        .spawn_item(w.realm_id, "coin", ItemLocation::Player(w.player), 3)
+        .await
+        .unwrap();
+    let b = repo
+        .spawn_item(w.realm_id, "coin", ItemLocation::Player(w.player), 2)
+        .await
+        .unwrap();
+    assert_eq!(a, b);
+    assert_eq!(repo.get_item_instance(a).await.unwrap().quantity, 5);
+
+    // Another location is another stack
+    let c = repo
+        .spawn_item(w.realm_id, "coin", ItemLocation::Room(w.hall), 1)
+        .await
+        .unwrap();
+    assert_ne!(a, c);
+
+    // Items that do not stack get an instance each
+    let w1 = repo
+        .spawn_item(w.realm_id, "wrench", ItemLocation::Player(w.player), 1)
+        .await
+        .unwrap();
+    let w2 = repo
+        .spawn_item(w.realm_id, "wrench", ItemLocation::Player(w.player), 1)
+        .await
+        .unwrap();
+    assert_ne!(w1, w2);
+    assert_eq!(repo.get_player_inventory(w.realm_id, w.player).await.unwrap().len(), 3);
+
+    assert!(
+        repo.spawn_item(w.realm_id, "spoon", ItemLocation::Room(w.hall), 1)
+            .await
+            .is_err()
+    );
+}
+
+#[tokio::test]
+async fn t_inventory_queries() {
+    let Some(t) = TestDb::start().await else { return };
+    let w = World::seed(&t).await;
+    let repo = InventoryRepository::new(t.db.clone());
+
+    let wrench = repo
+        .spawn_item(w.realm_id, "wrench", ItemLocation::Player(w.player), 1)
+        .await
+        .unwrap();
+    repo.spawn_item(w.realm_id, "coin", ItemLocation::Room(w.hall), 4)
+        .await
+        .unwrap();
+    repo.spawn_item(w.realm_id, "bag", ItemLocation::Object(w.chest), 1)
+        .await
+        .unwrap();
+
+    assert!(repo.has_item(w.realm_id, w.player, wrench).await.unwrap());
+    assert!(!repo.has_item(w.realm_id, w.other, wrench).await.unwrap());
+    assert!(repo.has_item_by_key(w.realm_id, w.player, "wrench").await.unwrap());
+    assert!(repo.has_item_by_noun(w.realm_id, w.player, "wrench").await.unwrap());
+    assert!(!repo.has_item_by_noun(w.realm_id, w.player, "coin").await.unwrap());
+
+    let found = repo
+        .find_item_in_player_inventory(w.realm_id, w.player, "wrench")
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(found.instance_id, wrench);
+    assert!(found.is_at(ItemLocation::Player(w.player)));
+    assert!(
+        repo.find_item_by_key_in_inventory(w.realm_id, w.player, "wrench")
+            .await
+            .unwrap()
+            .is_some()
+    );
+
+    let coins = repo.get_room_items(w.realm_id, w.hall).await.unwrap();
+    assert_eq!(coins.len(), 1);
+    assert_eq!(coins[0].quantity, 4);
+    assert!(
+        repo.find_item_in_room(w.realm_id, w.hall, "coin")
+            .await
+            .unwrap()
+            .is_some()
+    );
+    assert!(
+        repo.find_item_in_room(w.realm_id, w.vault, "coin")
+            .await
+            .unwrap()
+            .is_none()
+    );
+
+    assert_eq!(repo.get_object_items(w.realm_id, w.chest).await.unwrap().len(), 1);
+    assert!(
+        repo.find_item_in_object(w.realm_id, w.chest, "bag")
+            .await
+            .unwrap()
+            .is_some()
+    );
+}
+
+#[tokio::test]
+async fn t_carried_items_include_containers() {
+    let Some(t) = TestDb::start().await else { return };
+    let w = World::seed(&t).await;
+    let repo = InventoryRepository::new(t.db.clone());
+
+    let bag = repo
+        .spawn_item(w.realm_id, "bag", ItemLocation::Player(w.player), 1)
+        .await
+        .unwrap();
+    let inner = repo
+        .spawn_item(w.realm_id, "bag", ItemLocation::Container(bag), 1)
+        .await
+        .unwrap();
+    repo.spawn_item(w.realm_id, "wrench", ItemLocation::Container(inner), 1)
+        .await
+        .unwrap();
+
+    assert_eq!(repo.get_player_inventory(w.realm_id, w.player).await.unwrap().len(), 1);
+    assert_eq!(repo.get_carried_items(w.realm_id, w.player).await.unwrap().len(), 3);
+}
+
+#[tokio::test]
+async fn t_move_item_merges_stacks() {
+    let Some(t) = TestDb::start().await else { return };
+    let w = World::seed(&t).await;
+    let repo = InventoryRepository::new(t.db.clone());
+
+    let carried = repo
+        .spawn_item(w.realm_id, "coin", ItemLocation::Player(w.player), 2)
+        .await
+        .unwrap();
+    let lying = repo
+        .spawn_item(w.realm_id, "coin", ItemLocation::Room(w.hall), 3)
+        .await
+        .unwrap();
+
+    repo.move_item(lying, ItemLocation::Player(w.player)).await.unwrap();
+    assert!(repo.get_item_instance(lying).await.is_err());
+    assert_eq!(repo.get_item_instance(carried).await.unwrap().quantity, 5);
+
+    // Without a stack to join it simply moves
+    repo.move_item(carried, ItemLocation::Room(w.vault)).await.unwrap();
+    let moved = repo.get_item_instance(carried).await.unwrap();
+    assert!(moved.is_at(ItemLocation::Room(w.vault)));
+    assert_eq!(moved.quantity, 5);
+}
+
+#[tokio::test]
+async fn t_move_item_quantity_splits() {
+    let Some(t) = TestDb::start().await else { return };
+    let w = World::seed(&t).await;
+    let repo = InventoryRepository::new(t.db.clone());
+
+    let pile = repo
+        .spawn_item(w.realm_id, "coin", ItemLocation::Room(w.hall), 5)
+        .await
+        .unwrap();
+
+    assert_eq!(
+        repo.move_item_quantity(pile, 2, ItemLocation::Player(w.player))
+            .await
+            .unwrap(),
+        2
+    );
+    assert_eq!(repo.get_item_instance(pile).await.unwrap().quantity, 3);
+    let carried = repo.get_player_inventory(w.realm_id, w.player).await.unwrap();
+    assert_eq!(carried.len(), 1);
+    assert_eq!(carried[0].quantity, 2);
+
+    // Asking for more than there is moves what is left, into the stack already carried
+    assert_eq!(
+        repo.move_item_quantity(pile, 10, ItemLocation::Player(w.player))
+            .await
+            .unwrap(),
+        3
+    );
+    assert!(repo.get_room_items(w.realm_id, w.hall).await.unwrap().is_empty());
+    let carried = repo.get_player_inventory(w.realm_id, w.player).await.unwrap();
+    assert_eq!(carried.len(), 1);
+    assert_eq!(carried[0].quantity, 5);
+}
+
+#[tokio::test]
+async fn t_consume_and_modify_item() {
+    let Some(t) = TestDb::start().await else { return };
+    let w = World::seed(&t).await;
+    let repo = InventoryRepository::new(t.db.clone());
+
+    let coins = repo
+        .spawn_item(w.realm_id, "coin", ItemLocation::Player(w.player), 2)
+        .await
+        .unwrap();
+    repo.consume_item(w.realm_id, w.player, coins).await.unwrap();
+    assert_eq!(repo.get_item_instance(coins).await.unwrap().quantity, 1);
+    repo.consume_item(w.realm_id, w.player, coins).await.unwrap();
+    assert!(repo.get_item_instance(coins).await.is_err());
+    assert!(repo.consume_item(w.realm_id, w.player, coins).await.is_err());
+
+    let wrench = repo
+        .spawn_item(w.realm_id, "wrench", ItemLocation::Player(w.player), 1)
+        .await
+        .unwrap();
+    assert_eq!(repo.adjust_durability(wrench, -3, 10).await.unwrap(), 7);
+    assert_eq!(repo.adjust_durability(wrench, -30, 10).await.unwrap(), 0);
+    assert_eq!(repo.adjust_durability(wrench, 50, 10).await.unwrap(), 10);
+
+    repo.set_item_condition(wrench, json!({"charges": 2})).await.unwrap();
+    assert_eq!(
+        repo.get_item_instance(wrench).await.unwrap().condition,
+        Some(json!({"charges": 2}))
+    );
+    repo.set_item_quantity(wrench, 4).await.unwrap();
+    assert_eq!(repo.get_item_instance(wrench).await.unwrap().quantity, 4);
+
+    repo.delete_item(wrench).await.unwrap();
+    assert!(!repo.has_item(w.realm_id, w.player, wrench).await.unwrap());
+}
+
+#[tokio::test]
+async fn t_stash_round_trip() {
+    let Some(t) = TestDb::start().await else { return };
+    let w = World::seed(&t).await;
+    let repo = InventoryRepository::new(t.db.clone());
+    let realm = Some(w.realm_id);
+
+    let coins = repo
+        .spawn_item(w.realm_id, "coin", ItemLocation::Player(w.player), 5)
+        .await
+        .unwrap();
+    let wrench = repo
+        .spawn_item(w.realm_id, "wrench", ItemLocation::Player(w.player), 1)
+        .await
+        .unwrap();
+
+    assert_eq!(
+        repo.stash_item(coins, 2, w.chest, w.player, realm, 1).await.unwrap(),
+        Some(2)
+    );
+    // Coins join their slot, even with all slots taken
+    assert_eq!(
+        repo.stash_item(coins, 1, w.chest, w.player, realm, 1).await.unwrap(),
+        Some(1)
+    );
+    assert_eq!(
+        repo.stash_item(wrench, 1, w.chest, w.player, realm, 1).await.unwrap(),
+        None
+    );
+    // Only what the player carries can go in
+    assert_eq!(
+        repo.stash_item(wrench, 1, w.chest, w.other, realm, 5).await.unwrap(),
+        Some(0)
+    );
+
+    let stash = repo.get_stash(w.chest, w.player, realm).await.unwrap();
+    assert_eq!(stash.len(), 1);
+    assert_eq!(stash[0].quantity, 3);
+    assert!(repo.get_stash(w.chest, w.player, None).await.unwrap().is_empty());
+    assert_eq!(repo.get_item_instance(coins).await.unwrap().quantity, 2);
+
+    assert_eq!(
+        repo.unstash_item(stash[0].id, 10, w.realm_id, w.player).await.unwrap(),
+        3
+    );
+    assert!(repo.get_stash(w.chest, w.player, realm).await.unwrap().is_empty());
+    assert_eq!(repo.get_item_instance(coins).await.unwrap().quantity, 5);
+    assert_eq!(
+        repo.unstash_item(stash[0].id, 1, w.realm_id, w.player).await.unwrap(),
+        0
+    );
+}
+
+#[tokio::test]
+async fn t_clear_dropped_items() {
+    let Some(t) = TestDb::start().await else { return };
+    let w = World::seed(&t).await;
+    let repo = InventoryRepository::new(t.db.clone());
+
+    let spawned = repo
+        .spawn_item(w.realm_id, "wrench", ItemLocation::Object(w.chest), 1)
+        .await
+        .unwrap();
+    let dropped = repo
+        .spawn_item(w.realm_id, "bag", ItemLocation::Player(w.player), 1)
+        .await
+        .unwrap();
+    repo.move_item(spawned, ItemLocation::Room(w.vault)).await.unwrap();
+    repo.move_item(dropped, ItemLocation::Room(w.vault)).await.unwrap();
+
+    let client = t.client().await;
+    client
+        .execute("UPDATE item_instances SET dropped_at = NOW() - INTERVAL '2 hours'", &[])
+        .await
+        .unwrap();
+
+    // Persisting blueprints keep everything
+    assert!(repo.clear_dropped_items().await.unwrap().is_empty());
+
+    client
+        .execute(
+            "UPDATE blueprints SET drop_policy = 'return', drop_after_minutes = 30 WHERE id = $1",
+            &[&w.bp_id],
+        )
+        .await
+        .unwrap();
+    let mut cleared = repo.clear_dropped_items().await.unwrap();
+    cleared.sort_by(|a, b| a.text.cmp(&b.text));
+    assert_eq!(cleared.len(), 2);
+    assert_eq!(cleared[0].text, "a bag");
+    assert!(!cleared[0].returned);
+    assert_eq!(cleared[1].text, "a wrench");
+    assert!(cleared[1].returned);
+
+    assert!(repo.get_item_instance(dropped).await.is_err());
+    assert!(
+        repo.get_item_instance(spawned)
+            .await
+            .unwrap()
+            .is_at(ItemLocation::Object(w.chest))
+    );
+    assert!(repo.clear_dropped_items().await.unwrap().is_empty());
+}
+
+#[tokio::test]
+async fn t_loot_instantiation_state() {
+    let Some(t) = TestDb::start().await else { return };
+    let w = World::seed(&t).await;
+    let repo = InventoryRepository::new(t.db.clone());
+
+    for account in [Some(w.player), None] {
+        assert!(!repo.is_loot_instantiated(w.realm_id, w.chest, account).await.unwrap());
+        repo.mark_loot_instantiated(w.realm_id, w.chest, account).await.unwrap();
+        repo.mark_loot_instantiated(w.realm_id, w.chest, account).await.unwrap();
+        assert!(repo.is_loot_instantiated(w.realm_id, w.chest, account).await.unwrap());
+    }
+    assert!(
+        !repo
+            .is_loot_instantiated(w.realm_id, w.chest, Some(w.other))
+            .await
+            .unwrap()
+    );
+}
+
+#[tokio::test]
+async fn t_loot_spawns_and_coin_pickup() {
+    let Some(t) = TestDb::start().await else { return };
+    let w = World::seed(&t).await;
+    let accounts = AccountRepository::new(t.db.clone());
+    let player = accounts.get_by_id(w.player).await.unwrap().unwrap();
+
+    t.client()
+        .await
+        .execute(
+            "INSERT INTO loot_spawns (realm_id, room_id, item, qty_min, qty_max, max_instances)
+             VALUES ($1, $2, 'coin', 4, 4, 1)",
+            &[&w.realm_id, &w.hall],
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(t.db.spawn_tick().await.unwrap(), 1);
+    // Not due again yet
+    assert_eq!(t.db.spawn_tick().await.unwrap(), 0);
+
+    assert_eq!(t.db.pickup_coins(&player, w.realm_id, w.vault, 1).await.unwrap(), 0);
+    assert_eq!(t.db.pickup_coins(&player, w.realm_id, w.hall, 3).await.unwrap(), 3);
+    assert_eq!(t.db.pickup_coins(&player, w.realm_id, w.hall, 3).await.unwrap(), 1);
+    assert_eq!(t.db.pickup_coins(&player, w.realm_id, w.hall, 3).await.unwrap(), 0);
+
+    let balance: i64 = t
+        .client()
+        .await
+        .query_one(
+            "SELECT amount FROM realm_balances WHERE realm_id = $1 AND account_id = $2",
+            &[&w.realm_id, &w.player],
+        )
+        .await
+        .unwrap()
+        .get(0);
+    assert_eq!(balance, 4);
+}
+
+#[tokio::test]
+async fn t_move_character() {
+    let Some(t) = TestDb::start().await else { return };
+    let w = World::seed(&t).await;
+
+    assert_eq!(t.db.move_character(w.player, "north").await.unwrap(), None);
+    t.client()
+        .await
+        .execute(
+            "INSERT INTO characters (account_id, name, realm_id, room_id) VALUES ($1, 'Alice', $2, $3)",
+            &[&w.player, &w.realm_id, &w.hall],
+        )
+        .await
+        .unwrap();
+
+    assert_eq!(t.db.move_character(w.player, "NORTH").await.unwrap(), Some(w.vault));
+    assert_eq!(t.db.move_character(w.player, "north").await.unwrap(), None);
+    assert_eq!(t.db.move_character(w.player, "south").await.unwrap(), Some(w.hall));
+}
+
+#[tokio::test]
+async fn t_room_blueprint_queries() {
+    let Some(t) = TestDb::start().await else { return };
+    let w = World::seed(&t).await;
+    let rooms = RoomRepository::new(t.db.clone());
+
+    let hall = rooms.room_by_id(w.bp_id, w.hall).await.unwrap();
+    assert_eq!(hall.key, "hall");
+    assert_eq!(rooms.get_room_id_by_key(w.bp_id, "vault").await.unwrap(), Some(w.vault));
+    assert_eq!(rooms.get_room_id_by_key(w.bp_id, "attic").await.unwrap(), None);
+
+    let exits = rooms.room_exits(w.hall).await.unwrap();
+    assert_eq!(exits.len(), 1);
+    assert_eq!(exits[0].to_room_id, w.vault);
+    assert!(exits[0].default_locked);
+
+    let objects = rooms.room_objects(w.hall).await.unwrap();
+    assert_eq!(objects.len(), 1);
+    assert_eq!(objects[0].id, w.chest);
+    assert!(rooms.room_objects(w.vault).await.unwrap().is_empty());
+}
+
+#[tokio::test]
+async fn t_room_content_queries() {
+    let Some(t) = TestDb::start().await else { return };
+    let w = World::seed(&t).await;
+    let rooms = RoomRepository::new(t.db.clone());
+    let client = t.client().await;
+
+    client
+        .execute(
+            "INSERT INTO bp_room_scripts (room_id, hook, script) VALUES ($1, 'on_enter', 'print(1)')",
+            &[&w.hall],
+        )
+        .await
+        .unwrap();
+    client
+        .execute(
+            "INSERT INTO bp_room_kv (room_id, key, value) VALUES ($1, 'lights', 'true')",
+            &[&w.hall],
+        )
+        .await
+        .unwrap();
+    client
+        .execute(
+            "INSERT INTO bp_lua_modules (bp_id, name, source) VALUES ($1, 'util', 'return {}')",
+            &[&w.bp_id],
+        )
+        .await
+        .unwrap();
+    client
+        .execute(
+            "UPDATE bp_rooms SET notes = ARRAY['too dark'] WHERE id = $1",
+            &[&w.vault],
+        )
+        .await
+        .unwrap();
+    client
+        .execute(
+            "UPDATE bp_objects SET notes = ARRAY['needs a lock'] WHERE id = $1",
+            &[&w.chest],
+        )
+        .await
+        .unwrap();
+
+    let scripts = rooms.room_scripts(w.hall).await.unwrap();
+    assert_eq!(scripts.get(&ScriptHook::OnEnter).map(String::as_str), Some("print(1)"));
+    assert!(scripts.get(&ScriptHook::OnLeave).is_none());
+    assert_eq!(
+        rooms.room_kv(w.hall).await.unwrap().inner.get("lights"),
+        Some(&json!(true))
+    );
+    assert!(rooms.room_kv(w.vault).await.unwrap().inner.is_empty());
+    assert_eq!(
+        rooms.lua_module(w.bp_id, "util").await.unwrap().as_deref(),
+        Some("return {}")
+    );
+    assert_eq!(rooms.lua_module(w.bp_id, "other").await.unwrap(), None);
+
+    let notes = rooms.builder_notes(w.bp_id, None).await.unwrap();
+    let notes: Vec<_> = notes
+        .iter()
+        .map(|n| (n.room_key.as_str(), n.object_key.as_deref(), n.text.as_str()))
+        .collect();
+    assert_eq!(
+        notes,
+        vec![("hall", Some("chest"), "needs a lock"), ("vault", None, "too dark")]
+    );
+    assert_eq!(rooms.builder_notes(w.bp_id, Some(w.vault)).await.unwrap().len(), 1);
+
+    let graph = rooms.room_graph(w.bp_id).await.unwrap();
+    assert_eq!(graph.entry, None);
+    assert_eq!(graph.rooms, vec!["hall".to_string(), "vault".to_string()]);
+    assert_eq!(graph.exits.len(), 2);
+    assert_eq!(
+        (
+            graph.exits[0].from.as_str(),
+            graph.exits[0].dir.as_str(),
+            graph.exits[0].to.as_str()
+        ),
+        ("hall", "north", "vault")
+    );
+    assert!(graph.exits[0].locked);
+}
+
+#[tokio::test]
+async fn t_blueprint_editing() {
+    let Some(t) = TestDb::start().await else { return };
+    let w = World::seed(&t).await;
+    let rooms = RoomRepository::new(t.db.clone());
+    let lobby = BlueprintAndRoomKey::new("annex", "lobby");
+    let attic = BlueprintAndRoomKey::new("annex", "attic");
+
+    assert!(rooms.insert_blueprint("annex", "Annex", w.other).await.unwrap());
+    assert!(!rooms.insert_blueprint("annex", "Annex", w.player).await.unwrap());
+    assert!(rooms.insert_room(&lobby, "Lobby", "A lobby.").await.unwrap());
+    assert!(rooms.insert_room(&attic, "Attic", "An attic.").await.unwrap());
+    assert!(!rooms.insert_room(&attic, "Attic", "An attic.").await.unwrap());
+    assert!(
+        !rooms
+            .insert_room(&BlueprintAndRoomKey::new("nowhere", "lobby"), "Lobby", "A lobby.")
+            .await
+            .unwrap()
+    );
+
+    assert!(rooms.set_entry(&lobby).await.unwrap());
+    assert!(
+        !rooms
+            .set_entry(&BlueprintAndRoomKey::new("annex", "cellar"))
+            .await
+            .unwrap()
+    );
+    assert!(rooms.add_exit(&lobby, "Up", &attic).await.unwrap());
+    assert!(
+        rooms
+            .add_exit(&lobby, "north", &BlueprintAndRoomKey::new("station", "hall"))
+            .await
+            .is_err()
+    );
+    assert!(rooms.set_locked(&attic, true).await.unwrap());
+
+    let bp = rooms.blueprint_by_key("annex").await.unwrap();
+    assert_eq!(bp.owner_id, w.other);
+    assert_eq!(bp.status, BlueprintStatus::Draft);
+    assert_eq!(rooms.blueprint_by_id(bp.id).await.unwrap().key, "annex");
+    let lobby_id = rooms.get_room_id_by_key(bp.id, "lobby").await.unwrap().unwrap();
+    let attic_id = rooms.get_room_id_by_key(bp.id, "attic").await.unwrap().unwrap();
+    assert_eq!(bp.entry_room_id, lobby_id);
+    assert!(rooms.room_by_id(bp.id, attic_id).await.unwrap().lockdown);
+    let exits = rooms.room_exits(lobby_id).await.unwrap();
+    assert_eq!(exits.len(), 1);
+    assert_eq!(exits[0].dir, Direction::Up);
+    assert_eq!(exits[0].to_room_id, attic_id);
+
+    assert!(
+        rooms
+            .update_room_text(lobby_id, "Hall", "A bright hall.")
+            .await
+            .unwrap()
+    );
+    let room = rooms.room_by_id(bp.id, lobby_id).await.unwrap();
+    assert_eq!((room.title.as_str(), room.body.as_str()), ("Hall", "A bright hall."));
+
+    assert!(rooms.insert_object(lobby_id, "desk", "a desk").await.unwrap());
+    assert!(!rooms.insert_object(lobby_id, "desk", "a desk").await.unwrap());
+    assert!(rooms.insert_object(lobby_id, "lamp", "a lamp").await.unwrap());
+    let objects = rooms.room_objects(lobby_id).await.unwrap();
+    assert_eq!(
+        objects.iter().map(|o| o.name.as_str()).collect::<Vec<_>>(),
+        vec!["desk", "lamp"]
+    );
+    let desk = &objects[0];
+    assert_eq!((desk.short.as_str(), desk.description.as_str()), ("a desk", "a desk"));
+    assert_eq!(desk.nouns, vec!["desk".to_string()]);
+    assert!(desk.default_revealed);
+    assert!(!desk.default_locked);
+
+    assert!(
+        rooms
+            .update_object_text(desk.id, "an oak desk", "An oak desk.", Some("Deep drawers."))
+            .await
+            .unwrap()
+    );
+    assert!(rooms.set_object_flag(desk.id, "locked", true).await.unwrap());
+    rooms
+        .set_object_nouns(lobby_id, desk.id, &["desk".into(), "table".into()])
+        .await
+        .unwrap();
+    assert!(
+        rooms
+            .set_object_script(desk.id, &ChunkKind::OnUse, Some("print('open')"))
+            .await
+            .unwrap()
+    );
+    assert!(
+        rooms
+            .set_object_script(desk.id, &ChunkKind::Room(ScriptHook::OnEnter), Some("print(1)"))
+            .await
+            .is_err()
+    );
+
+    let desk = rooms.room_objects(lobby_id).await.unwrap().remove(0);
+    assert_eq!(desk.short, "an oak desk");
+    assert_eq!(desk.examine.as_deref(), Some("Deep drawers."));
+    assert!(desk.default_locked);
+    let mut nouns = desk.nouns.clone();
+    nouns.sort();
+    assert_eq!(nouns, vec!["desk".to_string(), "table".to_string()]);
+    assert_eq!(desk.on_use_lua.as_deref(), Some("print('open')"));
+
+    assert!(rooms.set_object_script(desk.id, &ChunkKind::OnUse, None).await.unwrap());
+    assert!(rooms.room_objects(lobby_id).await.unwrap()[0].on_use_lua.is_none());
+    assert!(rooms.delete_object(desk.id).await.unwrap());
+    assert!(!rooms.delete_object(desk.id).await.unwrap());
+    assert!(!rooms.update_object_text(desk.id, "a", "b", None).await.unwrap());
+    assert_eq!(rooms.room_objects(lobby_id).await.unwrap().len(), 1);
+}
+
+#[tokio::test]
+async fn t_fork_blueprint() {
+    let Some(t) = TestDb::start().await else { return };
+    let w = World::seed(&t).await;
+    let rooms = RoomRepository::new(t.db.clone());
+
+    t.client()
+        .await
+        .execute(
+            "INSERT INTO bp_lua_modules (bp_id, name, source) VALUES ($1, 'util', 'return {}')",
+            &[&w.bp_id],
+        )
+        .await
+        .unwrap();
+    assert!(
+        rooms
+            .set_entry(&BlueprintAndRoomKey::new("station", "hall"))
+            .await
+            .unwrap()
+    );
+
+    let fork = rooms
+        .fork_blueprint(w.bp_id, "outpost", w.other)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(rooms.fork_blueprint(w.bp_id, "outpost", w.other).await.unwrap(), None);
+
+    let bp = rooms.blueprint_by_id(fork).await.unwrap();
+    assert_eq!(bp.key, "outpost");
+    assert_eq!(bp.owner_id, w.other);
+    assert_eq!(bp.status, BlueprintStatus::Draft);
+    assert_eq!(bp.forked_from, Some(w.bp_id));
+
+    let hall = rooms.get_room_id_by_key(fork, "hall").await.unwrap().unwrap();
+    assert_ne!(hall, w.hall);
+    assert_eq!(bp.entry_room_id, hall);
+    assert_eq!(
+        rooms.room_graph(fork).await.unwrap().exits,
+        rooms.room_graph(w.bp_id).await.unwrap().exits
+    );
+    let objects = rooms.room_objects(hall).await.unwrap();
+    assert_eq!(objects.len(), 1);
+    assert_ne!(objects[0].id, w.chest);
+    assert_eq!(objects[0].nouns, vec!["chest".to_string()]);
+    assert_eq!(
+        rooms.lua_module(fork, "util").await.unwrap().as_deref(),
+        Some("return {}")
+    );
+
+    // The copy is independent of the original
+    assert!(rooms.delete_object(objects[0].id).await.unwrap());
+    assert_eq!(rooms.room_objects(w.hall).await.unwrap().len(), 1);
+}
+
+#[tokio::test]
+async fn t_exit_lock_overlays() {
+    let Some(t) = TestDb::start().await else { return };
+    let w = World::seed(&t).await;
+    let realms = RealmRepository::new(t.db.clone());
+    let users = UserRepository::new(t.db.clone());
+
+    realms
+        .set_exit_locked(w.realm_id, w.hall, w.north, false)
+        .await
+        .unwrap();
+    realms.set_exit_locked(w.realm_id, w.hall, w.north, true).await.unwrap();
+
+    users
+        .set_exit_locked(w.realm_id, w.hall, w.player, w.north, false)
+        .await
+        .unwrap();
+    assert!(
+        !users
+            .is_exit_locked(w.realm_id, w.hall, w.player, w.north)
+            .await
+            .unwrap()
+    );
+    users
+        .set_exit_locked(w.realm_id, w.hall, w.player, w.north, true)
+        .await
+        .unwrap();
+    assert!(
+        users
+            .is_exit_locked(w.realm_id, w.hall, w.player, w.north)
+            .await
+            .unwrap()
+    );
+}
+
+#[tokio::test]
+async fn t_room_view_merges_overlays() {
+    let Some(t) = TestDb::start().await else { return };
+    let w = World::seed(&t).await;
+    let room_repo = Arc::new(RoomRepository::new(t.db.clone()));
+    let realm_repo = Arc::new(RealmRepository::new(t.db.clone()));
+    let user_repo = Arc::new(UserRepository::new(t.db.clone()));
+    let inventory_repo = Arc::new(InventoryRepository::new(t.db.clone()));
+    let rooms = RoomService::new(
+        room_repo,
+        realm_repo.clone(),
+        user_repo.clone(),
+        Arc::new(AccountRepository::new(t.db.clone())),
+        Arc::new(InventoryService::new(inventory_repo)),
+    );
+
+    t.client()
+        .await
+        .execute(
+            "INSERT INTO bp_room_kv (room_id, key, value) VALUES ($1, 'lights', '\"dim\"'), ($1, 'alarm', 'false')",
+            &[&w.hall],
+        )
+        .await
+        .unwrap();
+    realm_repo
+        .set_room_kv(w.realm_id, w.hall, "lights", &json!("off"))
+        .await
+        .unwrap();
+    realm_repo
+        .set_object_kv(w.realm_id, w.chest, "open", &json!(true))
+        .await
+        .unwrap();
+    realm_repo
+        .set_room_kv(w.realm_id, w.hall, "exit.north.locked", &json!(false))
+        .await
+        .unwrap();
+    user_repo
+        .set_room_kv(w.realm_id, w.hall, w.player, "lights", &json!("bright"))
+        .await
+        .unwrap();
+    user_repo
+        .set_object_kv(w.realm_id, w.player, w.chest, "open", &json!(false))
+        .await
+        .unwrap();
+
+    // The player's own state wins over the realm's, which wins over the blueprint's
+    let rv = rooms.build_room_view(w.realm_id, w.player, w.hall).await.unwrap();
+    assert_eq!(rv.room_kv.get("lights"), Some(&json!("bright")));
+    assert_eq!(rv.room_kv.get("alarm"), Some(&json!(false)));
+    assert_eq!(
+        rv.objects[rv.objects_by_key["chest"]].kv.get("open"),
+        Some(&json!(false))
+    );
+    assert!(!rv.exits[rv.exits_by_dir[&Direction::North]].flags.locked);
+
+    let rv = rooms.build_room_view(w.realm_id, w.other, w.hall).await.unwrap();
+    assert_eq!(rv.room_kv.get("lights"), Some(&json!("off")));
+    assert_eq!(
+        rv.objects[rv.objects_by_key["chest"]].kv.get("open"),
+        Some(&json!(true))
+    );
+
+    // A room without overlays is as the blueprint has it
+    let rv = rooms.build_room_view(w.realm_id, w.other, w.vault).await.unwrap();
+    assert!(rv.room_kv.get("lights").is_none());
+    assert!(!rv.exits[rv.exits_by_dir[&Direction::South]].flags.locked);
+}
+
+#[tokio::test]
+async fn t_room_kv_counters_and_cas() {
+    let Some(t) = TestDb::start().await else { return };
+    let w = World::seed(&t).await;
+    let realms = RealmRepository::new(t.db.clone());
+    let users = UserRepository::new(t.db.clone());
+
+    assert!(
+        realms
+            .cas_room_kv(w.realm_id, w.hall, "door", &json!(1), None)
+            .await
+            .unwrap()
+    );
+    assert!(
+        !realms
+            .cas_room_kv(w.realm_id, w.hall, "door", &json!(2), None)
+            .await
+            .unwrap()
+    );
+    let v = realms
+        .room_kv_versioned(w.realm_id, w.hall, "door")
+        .await
+        .unwrap()
+        .unwrap();
+    assert!(
+        realms
+            .cas_room_kv(w.realm_id, w.hall, "door", &json!(2), Some(v.version))
+            .await
+            .unwrap()
+    );
+    assert!(
+        !realms
+            .cas_room_kv(w.realm_id, w.hall, "door", &json!(3), Some(v.version))
+            .await
+            .unwrap()
+    );
+    assert!(
+        !realms
+            .delete_room_kv_if(w.realm_id, w.hall, "door", &json!(1))
+            .await
+            .unwrap()
+    );
+    assert!(
+        realms
+            .delete_room_kv_if(w.realm_id, w.hall, "door", &json!(2))
+            .await
+            .unwrap()
+    );
+
+    assert_eq!(realms.incr_counter(w.realm_id, None, "visits", 2).await.unwrap(), 2);
+    assert_eq!(
+        realms
+            .incr_counter(w.realm_id, Some(w.player), "visits", 1)
+            .await
+            .unwrap(),
+        1
+    );
+    assert_eq!(realms.counter(w.realm_id, None, "visits").await.unwrap(), 2);
+    assert_eq!(realms.counter(w.realm_id, Some(w.other), "visits").await.unwrap(), 0);
+
+    assert_eq!(
+        users
+            .inc_room_kv(w.realm_id, w.hall, w.player, "steps", 2)
+            .await
+            .unwrap(),
+        2
+    );
+    assert_eq!(
+        users
+            .inc_room_kv(w.realm_id, w.hall, w.player, "steps", 3)
+            .await
+            .unwrap(),
+        5
+    );
+}
+
+#[tokio::test]
+async fn t_room_and_object_kv_reads() {
+    let Some(t) = TestDb::start().await else { return };
+    let w = World::seed(&t).await;
+    let realms = RealmRepository::new(t.db.clone());
+    let users = UserRepository::new(t.db.clone());
+
+    realms
+        .set_room_kv(w.realm_id, w.hall, "door", &json!("open"))
+        .await
+        .unwrap();
+    realms
+        .set_room_kv(w.realm_id, w.vault, "door", &json!("shut"))
+        .await
+        .unwrap();
+    realms
+        .set_object_kv(w.realm_id, w.chest, "open", &json!(true))
+        .await
+        .unwrap();
+    users
+        .set_room_kv(w.realm_id, w.hall, w.player, "seen", &json!(true))
+        .await
+        .unwrap();
+    users
+        .set_object_kv(w.realm_id, w.player, w.chest, "searched", &json!(1))
+        .await
+        .unwrap();
+
+    let room = realms.room_kv(w.realm_id, w.hall).await.unwrap();
+    assert_eq!(room.inner.get("door"), Some(&json!("open")));
+    assert_eq!(room.inner.len(), 1);
+    let objects = realms.obj_kv(w.realm_id, w.hall).await.unwrap();
+    assert_eq!(objects["chest"].inner.get("open"), Some(&json!(true)));
+    assert!(realms.obj_kv(w.realm_id, w.vault).await.unwrap().is_empty());
+
+    let mut doors = realms.room_kv_by_key("door").await.unwrap();
+    doors.sort_by_key(|(_, room, _)| *room != w.hall);
+    assert_eq!(
+        doors,
+        vec![
+            (w.realm_id, w.hall, json!("open")),
+            (w.realm_id, w.vault, json!("shut"))
+        ]
+    );
+
+    let mine = users.room_kv(w.realm_id, w.hall, w.player).await.unwrap();
+    assert_eq!(mine.inner.get("seen"), Some(&json!(true)));
+    assert!(
+        users
+            .room_kv(w.realm_id, w.hall, w.other)
+            .await
+            .unwrap()
+            .inner
+            .is_empty()
+    );
+    let mine = users.obj_kv(w.realm_id, w.hall, w.player).await.unwrap();
+    assert_eq!(mine["chest"].inner.get("searched"), Some(&json!(1)));
+    assert!(users.obj_kv(w.realm_id, w.hall, w.other).await.unwrap().is_empty());
+
+    users.set_current_room(w.realm_id, w.other, w.vault).await.unwrap();
+    let bob = AccountRepository::new(t.db.clone())
+        .get_by_id(w.other)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(
+        (bob.current_realm_id, bob.current_room_id),
+        (Some(w.realm_id), Some(w.vault))
+    );
+}
+
+#[tokio::test]
+async fn t_spawned_objects() {
+    let Some(t) = TestDb::start().await else { return };
+    let w = World::seed(&t).await;
+    let realms = RealmRepository::new(t.db.clone());
+    let spawned = |key: &str, short: &str| SpawnedObject {
+        id: ObjectId::new(),
+        key: key.into(),
+        short: short.into(),
+        description: format!("{}.", short),
+        nouns: vec![key.into()],
+    };
+
+    let crate_ = spawned("crate", "A crate");
+    realms.upsert_spawned_object(w.realm_id, w.hall, &crate_).await.unwrap();
+    realms
+        .upsert_spawned_object(w.realm_id, w.hall, &spawned("barrel", "A barrel"))
+        .await
+        .unwrap();
+    // Same key replaces, keeping the first id
+    realms
+        .upsert_spawned_object(w.realm_id, w.hall, &spawned("crate", "A broken crate"))
+        .await
+        .unwrap();
+
+    let objects = realms.spawned_objects(w.realm_id, w.hall).await.unwrap();
+    assert_eq!(
+        objects.iter().map(|o| o.key.as_str()).collect::<Vec<_>>(),
+        vec!["crate", "barrel"]
+    );
+    assert_eq!(objects[0].id, crate_.id);
+    assert_eq!(objects[0].short, "A broken crate");
+    assert_eq!(objects[0].nouns, vec!["crate".to_string()]);
+    assert!(realms.spawned_objects(w.realm_id, w.vault).await.unwrap().is_empty());
+
+    assert!(realms.delete_spawned_object(w.realm_id, w.hall, "crate").await.unwrap());
+    assert!(!realms.delete_spawned_object(w.realm_id, w.hall, "crate").await.unwrap());
+    assert_eq!(realms.spawned_objects(w.realm_id, w.hall).await.unwrap().len(), 1);
+}
+
+#[tokio::test]
+async fn t_object_kv_cas_rejects_stale_versions() {
+    let Some(t) = TestDb::start().await else { return };
+    let w = World::seed(&t).await;
+    let realms = RealmRepository::new(t.db.clone());
+
+    assert!(
+        realms
+            .object_kv_versioned(w.realm_id, w.chest, "uses")
+            .await
+            .unwrap()
+            .is_none()
+    );
+    assert!(
+        realms
+            .cas_object_kv(w.realm_id, w.chest, "uses", &json!(1), None)
+            .await
+            .unwrap()
+    );
+    let stale = realms
+        .object_kv_versioned(w.realm_id, w.chest, "uses")
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(stale.value, json!(1));
+
+    // Another player writes first; the version read before that no longer applies
+    assert!(
+        realms
+            .cas_object_kv(w.realm_id, w.chest, "uses", &json!(2), Some(stale.version))
+            .await
+            .unwrap()
+    );
+    assert!(
+        !realms
+            .cas_object_kv(w.realm_id, w.chest, "uses", &json!(5), Some(stale.version))
+            .await
+            .unwrap()
+    );
+    assert!(
+        !realms
+            .cas_object_kv(w.realm_id, w.chest, "uses", &json!(5), None)
+            .await
+            .unwrap()
+    );
+    let current = realms
+        .object_kv_versioned(w.realm_id, w.chest, "uses")
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(current.value, json!(2));
+    assert_ne!(current.version, stale.version);
+}
+
+#[tokio::test]
+async fn t_zone_kv_expiry() {
+    let Some(t) = TestDb::start().await else { return };
+    let w = World::seed(&t).await;
+    let zone = AmbienceRepository::new(t.db.clone());
+    let hour = std::time::Duration::from_secs(3600);
+    let expires_at = |key: &'static str| {
+        let t = &t;
+        async move {
+            t.client()
+                .await
+                .query_one(
+                    "SELECT expires_at FROM realm_kv WHERE realm_id = $1 AND key = $2",
+                    &[&w.realm_id, &key],
+                )
+                .await
+                .unwrap()
+                .get::<_, Option<DateTime<Utc>>>("expires_at")
+        }
+    };
+
+    // A TTL of zero has elapsed by the next statement
+    zone.set_zone_kv_expiring(w.realm_id, "alarm", &json!(true), std::time::Duration::ZERO)
+        .await
+        .unwrap();
+    zone.set_zone_kv_expiring(w.realm_id, "fog", &json!("thick"), hour)
+        .await
+        .unwrap();
+    zone.set_zone_kv(w.realm_id, "lights", &json!("off")).await.unwrap();
+    let kv = zone.zone_kv(w.realm_id).await.unwrap();
+    assert!(kv.get("alarm").is_none());
+    assert_eq!(kv.get("fog"), Some(&json!("thick")));
+    assert_eq!(kv.get("lights"), Some(&json!("off")));
+    assert!(expires_at("fog").await.is_some_and(|at| at > Utc::now()));
+
+    // A plain set makes an expiring entry permanent again
+    zone.set_zone_kv(w.realm_id, "alarm", &json!(false)).await.unwrap();
+    assert_eq!(expires_at("alarm").await, None);
+    assert_eq!(
+        zone.zone_kv(w.realm_id).await.unwrap().get("alarm"),
+        Some(&json!(false))
+    );
+
+    // Only the expired rows are swept
+    zone.set_zone_kv_expiring(w.realm_id, "smoke", &json!(1), std::time::Duration::ZERO)
+        .await
+        .unwrap();
+    assert_eq!(zone.delete_expired_zone_kv().await.unwrap(), 1);
+    assert_eq!(zone.delete_expired_zone_kv().await.unwrap(), 0);
+    let left: Vec<String> = t
+        .client()
+        .await
+        .query(
+            "SELECT key FROM realm_kv WHERE realm_id = $1 ORDER BY key",
+            &[&w.realm_id],
+        )
+        .await
+        .unwrap()
+        .iter()
+        .map(|r| r.get("key"))
+        .collect();
+    assert_eq!(left, vec!["alarm", "fog", "lights"]);
+}
+
+#[tokio::test]
+async fn t_realm_reset() {
+    let Some(t) = TestDb::start().await else { return };
+    let w = World::seed(&t).await;
+    let realms = RealmRepository::new(t.db.clone());
+    let users = UserRepository::new(t.db.clone());
+    let ambience = AmbienceRepository::new(t.db.clone());
+    let inventory = InventoryRepository::new(t.db.clone());
+    let currency = CurrencyRepository::new(t.db.clone());
+    let client = t.client().await;
+
+    client
+        .execute(
+            "UPDATE blueprints SET entry_room_id = $2 WHERE id = $1",
+            &[&w.bp_id, &w.vault],
+        )
+        .await
+        .unwrap();
+
+    ambience.set_zone_kv(w.realm_id, "alarm", &json!(true)).await.unwrap();
+    realms.set_room_kv(w.realm_id, w.hall, "door", &json!(1)).await.unwrap();
+    realms
+        .set_object_kv(w.realm_id, w.chest, "open", &json!(true))
+        .await
+        .unwrap();
+    users
+        .set_room_kv(w.realm_id, w.hall, w.player, "seen", &json!(true))
+        .await
+        .unwrap();
+    users
+        .set_object_kv(w.realm_id, w.player, w.chest, "searched", &json!(true))
+        .await
+        .unwrap();
+    realms
+        .set_exit_locked(w.realm_id, w.hall, w.north, false)
+        .await
+        .unwrap();
+    users
+        .set_exit_locked(w.realm_id, w.hall, w.player, w.north, false)
+        .await
+        .unwrap();
+    realms.incr_counter(w.realm_id, None, "visits", 1).await.unwrap();
+    realms
+        .incr_counter(w.realm_id, Some(w.player), "visits", 1)
+        .await
+        .unwrap();
+    currency.credit(w.realm_id, w.player, 10).await.unwrap();
+    inventory
+        .spawn_item(w.realm_id, "wrench", ItemLocation::Room(w.hall), 1)
+        .await
+        .unwrap();
+    let coins = inventory
+        .spawn_item(w.realm_id, "coin", ItemLocation::Player(w.player), 2)
+        .await
+        .unwrap();
+    inventory
+        .stash_item(coins, 1, w.chest, w.player, Some(w.realm_id), 5)
+        .await
+        .unwrap();
+    inventory
+        .mark_loot_instantiated(w.realm_id, w.chest, Some(w.player))
+        .await
+        .unwrap();
+    client
+        .execute(
+            "INSERT INTO loot_spawns (realm_id, room_id, item, qty_min, qty_max, max_instances)
+             VALUES ($1, $2, 'coin', 1, 1, 1)",
+            &[&w.realm_id, &w.hall],
+        )
+        .await
+        .unwrap();
+    realms
+        .upsert_spawned_object(
+            w.realm_id,
+            w.hall,
+            &SpawnedObject {
+                id: ObjectId::new(),
+                key: "crate".into(),
+                short: "A crate".into(),
+                description: "A wooden crate.".into(),
+                nouns: vec!["crate".into()],
+            },
+        )
+        .await
+        .unwrap();
+    realms.record_start(w.realm_id, w.player).await.unwrap();
+    assert!(realms.complete(w.realm_id, w.player).await.unwrap());
+
+    assert!(realms.reset(w.realm_id).await.unwrap());
+    assert!(!realms.reset(RealmId::new()).await.unwrap());
+
+    for table in [
+        "item_instances",
+        "loot_instantiation_state",
+        "loot_spawns",
+        "realm_kv",
+        "realm_room_kv",
+        "user_room_kv",
+        "realm_object_kv",
+        "user_object_kv",
+        "realm_exits",
+        "user_exits",
+        "realm_counters",
+        "user_counters",
+        "realm_balances",
+        "object_stashes",
+        "realm_spawned_objects",
+    ] {
+        let left: i64 = client
+            .query_one(
+                &format!("SELECT count(*) FROM {} WHERE realm_id = $1", table),
+                &[&w.realm_id],
+            )
+            .await
+            .unwrap()
+            .get(0);
+        assert_eq!(left, 0, "{} was not wiped", table);
+    }
+
+    let completions: i64 = client
+        .query_one(
+            "SELECT count(*) FROM realm_completions WHERE realm_id = $1",
+            &[&w.realm_id],
+        )
+        .await
+        .unwrap()
+        .get(0);
+    assert_eq!(completions, 1);
+
+    let accounts = AccountRepository::new(t.db.clone());
+    for id in [w.player, w.other] {
+        let account = accounts.get_by_id(id).await.unwrap().unwrap();
+        assert_eq!(account.current_realm_id, Some(w.realm_id));
+        assert_eq!(account.current_room_id, Some(w.vault));
+    }
+}
+
+#[tokio::test]
+async fn t_playtest_personas_and_testers() {
+    let Some(t) = TestDb::start().await else { return };
+    let w = World::seed(&t).await;
+    let accounts = AccountRepository::new(t.db.clone());
+    let inventory = InventoryRepository::new(t.db.clone());
+    let playtest = PlaytestRepository::new(t.db.clone());
+    let owner = accounts.get_by_id(w.player).await.unwrap().unwrap();
+
+    let persona = playtest.persona_for(&owner).await.unwrap();
+    assert_eq!(persona.username, "alice~guest");
+    assert_eq!(playtest.persona_for(&owner).await.unwrap().id, persona.id);
+
+    inventory
+        .spawn_item(w.realm_id, "wrench", ItemLocation::Player(persona.id), 1)
+        .await
+        .unwrap();
+    playtest.reset_persona(persona.id).await.unwrap();
+    assert!(
+        inventory
+            .get_player_inventory(w.realm_id, persona.id)
+            .await
+            .unwrap()
+            .is_empty()
+    );
+
+    let tester = playtest.create_tester("tester1").await.unwrap();
+    assert_eq!(tester.email, "tester1@tester.invalid");
+    playtest.delete_persona(tester.id).await.unwrap();
+    assert!(accounts.get_by_id(tester.id).await.unwrap().is_none());
+
+    // Real accounts are never deleted through the persona path
+    playtest.delete_persona(w.other).await.unwrap();
+    assert!(accounts.get_by_id(w.other).await.unwrap().is_some());
+}
+
+#[tokio::test]
+async fn t_currency_balances() {
+    let Some(t) = TestDb::start().await else { return };
+    let w = World::seed(&t).await;
+    let currency = CurrencyRepository::new(t.db.clone());
+
+    assert_eq!(currency.balance(w.realm_id, w.player).await.unwrap(), 0);
+    assert_eq!(currency.credit(w.realm_id, w.player, 10).await.unwrap(), 10);
+    assert_eq!(currency.debit(w.realm_id, w.player, 11).await.unwrap(), None);
+    assert_eq!(currency.debit(w.realm_id, w.player, 4).await.unwrap(), Some(6));
+
+    assert_eq!(currency.transfer(w.realm_id, w.player, w.other, 7).await.unwrap(), None);
+    assert_eq!(
+        currency.transfer(w.realm_id, w.player, w.other, 5).await.unwrap(),
+        Some(1)
+    );
+    assert_eq!(currency.balance(w.realm_id, w.other).await.unwrap(), 5);
+}
+
+#[tokio::test]
+async fn t_deposit_coins_from_the_room() {
+    let Some(t) = TestDb::start().await else { return };
+    let w = World::seed(&t).await;
+    let inventory = InventoryRepository::new(t.db.clone());
+    let currency = CurrencyRepository::new(t.db.clone());
+
+    let pile = inventory
+        .spawn_item(w.realm_id, "coin", ItemLocation::Room(w.hall), 5)
+        .await
+        .unwrap();
+
+    // Not where the player saw it
+    assert_eq!(
+        currency
+            .deposit_coins(w.realm_id, w.player, w.vault, pile, 2)
+            .await
+            .unwrap(),
+        None
+    );
+    assert_eq!(
+        currency
+            .deposit_coins(w.realm_id, w.player, w.hall, pile, 2)
+            .await
+            .unwrap(),
+        Some((2, 2))
+    );
+
+    // Someone else carried the rest off in the meantime
+    inventory.move_item(pile, ItemLocation::Player(w.other)).await.unwrap();
+    assert_eq!(
+        currency
+            .deposit_coins(w.realm_id, w.player, w.hall, pile, 3)
+            .await
+            .unwrap(),
+        None
+    );
+    assert_eq!(inventory.get_item_instance(pile).await.unwrap().quantity, 3);
+    assert_eq!(currency.balance(w.realm_id, w.player).await.unwrap(), 2);
+
+    inventory.move_item(pile, ItemLocation::Room(w.hall)).await.unwrap();
+    assert_eq!(
+        currency
+            .deposit_coins(w.realm_id, w.player, w.hall, pile, 10)
+            .await
+            .unwrap(),
+        Some((3, 5))
+    );
+    assert!(inventory.get_item_instance(pile).await.is_err());
+}
+
+#[tokio::test]
+async fn t_player_export_round_trip() {
+    let Some(t) = TestDb::start().await else { return };
+    let w = World::seed(&t).await;
+    let exports = PlayerExportRepository::new(t.db.clone());
+    let inventory = InventoryRepository::new(t.db.clone());
+    let users = UserRepository::new(t.db.clone());
+
+    t.client()
+        .await
+        .execute(
+            "INSERT INTO characters (account_id, name, realm_id, room_id) VALUES ($1, 'Alice', $2, $3)",
+            &[&w.player, &w.realm_id, &w.hall],
+        )
+        .await
+        .unwrap();
+    let bag = inventory
+        .spawn_item(w.realm_id, "bag", ItemLocation::Player(w.player), 1)
+        .await
+        .unwrap();
+    let wrench = inventory
+        .spawn_item(w.realm_id, "wrench", ItemLocation::Container(bag), 1)
+        .await
+        .unwrap();
+    users
+        .set_room_kv(w.realm_id, w.hall, w.player, "quest", &json!("started"))
+        .await
+        .unwrap();
+    users
+        .set_exit_locked(w.realm_id, w.hall, w.player, w.north, false)
+        .await
+        .unwrap();
+
+    let bundle = exports.export(w.player).await.unwrap().unwrap();
+    assert_eq!(bundle.validate(), Ok(w.player));
+    assert_eq!(bundle.characters.len(), 1);
+    assert_eq!(bundle.items.len(), 2);
+    assert_eq!(bundle.room_kv.len(), 1);
+    assert_eq!(bundle.exits.len(), 1);
+    assert!(bundle.account.get("password_hash").is_none());
+    assert!(exports.export(AccountId::new()).await.unwrap().is_none());
+
+    // Progress made after the export is rolled back, and the wrench comes back from the room
+    inventory.move_item(wrench, ItemLocation::Room(w.hall)).await.unwrap();
+    inventory
+        .spawn_item(w.realm_id, "coin", ItemLocation::Player(w.player), 4)
+        .await
+        .unwrap();
+    users
+        .set_room_kv(w.realm_id, w.hall, w.player, "quest", &json!("done"))
+        .await
+        .unwrap();
+    exports.import(w.player, &bundle).await.unwrap();
+
+    let restored = exports.export(w.player).await.unwrap().unwrap();
+    assert_eq!(restored.items, bundle.items);
+    assert_eq!(restored.room_kv, bundle.room_kv);
+    assert!(inventory.get_room_items(w.realm_id, w.hall).await.unwrap().is_empty());
+    assert_eq!(
+        inventory.get_item_instance(wrench).await.unwrap().location,
+        ItemLocation::Container(bag)
+    );
+
+    // A deleted account is recreated, with a password that never matches
+    AccountDeletionRepository::new(t.db.clone())
+        .purge(w.player, Some(w.other))
+        .await
+        .unwrap();
+    exports.import(w.player, &bundle).await.unwrap();
+    let account = AccountRepository::new(t.db.clone())
+        .get_by_id(w.player)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(account.username, "alice");
+    let hash: String = t
+        .client()
+        .await
+        .query_one("SELECT password_hash FROM accounts WHERE id = $1", &[&w.player])
+        .await
+        .unwrap()
+        .get("password_hash");
+    assert_eq!(hash, "!");
+    assert_eq!(exports.export(w.player).await.unwrap().unwrap().items, bundle.items);
+}
+
+#[tokio::test]
+async fn t_account_deletion_purge() {
+    let Some(t) = TestDb::start().await else { return };
+    let w = World::seed(&t).await;
+    let deletions = AccountDeletionRepository::new(t.db.clone());
+    let accounts = AccountRepository::new(t.db.clone());
+    let inventory = InventoryRepository::new(t.db.clone());
+    let playtest = PlaytestRepository::new(t.db.clone());
+    let now = Utc::now();
+
+    deletions
+        .set_delete_after(w.player, Some(now - Duration::hours(1)))
+        .await
+        .unwrap();
+    deletions
+        .set_delete_after(w.other, Some(now + Duration::hours(1)))
+        .await
+        .unwrap();
+    assert_eq!(deletions.due(now).await.unwrap(), vec![w.player]);
+    deletions.set_delete_after(w.other, None).await.unwrap();
+    assert_eq!(
+        deletions.owned_blueprints(w.player).await.unwrap(),
+        vec!["station".to_string()]
+    );
+
+    let owner = accounts.get_by_id(w.player).await.unwrap().unwrap();
+    let persona = playtest.persona_for(&owner).await.unwrap();
+    let bag = inventory
+        .spawn_item(w.realm_id, "bag", ItemLocation::Player(w.player), 1)
+        .await
+        .unwrap();
+    let wrench = inventory
+        .spawn_item(w.realm_id, "wrench", ItemLocation::Container(bag), 1)
+        .await
+        .unwrap();
+    let dropped = inventory
+        .spawn_item(w.realm_id, "coin", ItemLocation::Room(w.hall), 1)
+        .await
+        .unwrap();
+
+    deletions.purge(w.player, Some(w.other)).await.unwrap();
+
+    assert!(accounts.get_by_id(w.player).await.unwrap().is_none());
+    assert!(accounts.get_by_id(persona.id).await.unwrap().is_none());
+    assert!(inventory.get_item_instance(bag).await.is_err());
+    assert!(inventory.get_item_instance(wrench).await.is_err());
+    assert!(inventory.get_item_instance(dropped).await.is_ok());
+    assert_eq!(
+        deletions.owned_blueprints(w.other).await.unwrap(),
+        vec!["station".to_string()]
+    );
+    assert!(deletions.due(now).await.unwrap().is_empty());
+}
+
+#[tokio::test]
+async fn t_login_attempts() {
+    let Some(t) = TestDb::start().await else { return };
+    let w = World::seed(&t).await;
+    let attempts = LoginAttemptRepository::new(t.db.clone());
+    let ip: IpAddr = "192.0.2.7".parse().unwrap();
+    let hour_ago = Utc::now() - Duration::hours(1);
+
+    attempts.record_failure(Some(w.player), Some(ip)).await.unwrap();
+    attempts.record_failure(Some(w.player), None).await.unwrap();
+    attempts.record_failure(None, Some(ip)).await.unwrap();
+    assert_eq!(attempts.failures_for_account(w.player, hour_ago).await.unwrap(), 2);
+    assert_eq!(attempts.failures_for_account(w.other, hour_ago).await.unwrap(), 0);
+    assert_eq!(attempts.failures_for_ip(ip, hour_ago).await.unwrap(), 2);
+    assert_eq!(attempts.failures_for_ip(ip, Utc::now()).await.unwrap(), 0);
+
+    let until = Utc::now() + Duration::minutes(15);
+    attempts.lock_until(w.player, until).await.unwrap();
+    let locked: Option<DateTime<Utc>> = t
+        .client()
+        .await
+        .query_one("SELECT locked_until FROM accounts WHERE id = $1", &[&w.player])
+        .await
+        .unwrap()
+        .get("locked_until");
+    assert_eq!(locked.map(|l| l.timestamp()), Some(until.timestamp()));
+
+    attempts.prune(hour_ago).await.unwrap();
+    assert_eq!(attempts.failures_for_ip(ip, hour_ago).await.unwrap(), 2);
+    attempts.prune(Utc::now() + Duration::seconds(1)).await.unwrap();
+    assert_eq!(attempts.failures_for_ip(ip, hour_ago).await.unwrap(), 0);
+}
+
+#[tokio::test]
+async fn t_account_queries() {
+    let Some(t) = TestDb::start().await else { return };
+    let w = World::seed(&t).await;
+    let accounts = AccountRepository::new(t.db.clone());
+
+    let alice = accounts.get_by_username("alice").await.unwrap().unwrap();
+    assert_eq!(alice.id, w.player);
+    assert_eq!(
+        accounts.get_by_email("bob@example.com").await.unwrap().unwrap().id,
+        w.other
+    );
+    assert!(accounts.get_by_username("carol").await.unwrap().is_none());
+
+    let carol = accounts
+        .insert_account(Account {
+            username: "carol".into(),
+            email: "carol@example.com".into(),
+            ..alice.clone()
+        })
+        .await
+        .unwrap();
+    assert_ne!(carol.id, alice.id);
+    assert!(carol.last_login.is_none());
+    assert!(carol.current_realm_id.is_none());
+
+    accounts.update_last_login(carol.id).await.unwrap();
+    assert!(
+        accounts
+            .get_by_id(carol.id)
+            .await
+            .unwrap()
+            .unwrap()
+            .last_login
+            .is_some()
+    );
+
+    assert_eq!(accounts.adjust_health(carol.id, -30, MAX_HEALTH).await.unwrap(), 70);
+    assert_eq!(
+        accounts.adjust_health(carol.id, 50, MAX_HEALTH).await.unwrap(),
+        MAX_HEALTH
+    );
+    assert_eq!(accounts.adjust_health(carol.id, -500, MAX_HEALTH).await.unwrap(), 0);
+
+    let settings = PlayerSettings {
+        brief: true,
+        prompt: Some("%hp >".into()),
+        ..PlayerSettings::default()
+    };
+    accounts.save_settings(carol.id, &settings).await.unwrap();
+    assert_eq!(accounts.get_by_id(carol.id).await.unwrap().unwrap().settings, settings);
+}
+
+#[tokio::test]
+async fn t_world_clock() {
+    let Some(t) = TestDb::start().await else { return };
+    let clock = ClockRepository::new(t.db.clone());
+    let noon: DateTime<Utc> = "2030-01-01T12:00:00Z".parse().unwrap();
+
+    assert_eq!(clock.load_world_time().await.unwrap(), None);
+    clock.save_world_time(noon).await.unwrap();
+    clock.save_world_time(noon + Duration::hours(1)).await.unwrap();
+    assert_eq!(clock.load_world_time().await.unwrap(), Some(noon + Duration::hours(1)));
+}
+
+#[tokio::test]
+async fn t_collaborators() {
+    let Some(t) = TestDb::start().await else { return };
+    let w = World::seed(&t).await;
+    let collaborators = CollaboratorRepository::new(t.db.clone());
+
+    assert!(collaborators.list(w.bp_id).await.unwrap().is_empty());
+    collaborators
+        .upsert(
+            w.bp_id,
+            w.other,
+            BlueprintRole::Tester,
+            &BlueprintRole::Tester.default_rights(),
+            w.player,
+        )
+        .await
+        .unwrap();
+    collaborators
+        .upsert(
+            w.bp_id,
+            w.other,
+            BlueprintRole::Editor,
+            &[BlueprintRight::Playtest, BlueprintRight::EditRooms],
+            w.player,
+        )
+        .await
+        .unwrap();
+
+    let list = collaborators.list(w.bp_id).await.unwrap();
+    assert_eq!(list.len(), 1);
+    assert_eq!(list[0].username, "bob");
+    assert_eq!(list[0].role, BlueprintRole::Editor);
+    assert_eq!(
+        list[0].rights,
+        vec![BlueprintRight::EditRooms, BlueprintRight::Playtest]
+    );
+
+    assert!(
+        collaborators
+            .set_rights(w.bp_id, w.other, &[BlueprintRight::Publish])
+            .await
+            .unwrap()
+    );
+    assert!(
+        !collaborators
+            .set_rights(w.bp_id, w.player, &[BlueprintRight::Publish])
+            .await
+            .unwrap()
+    );
+    let bob = collaborators.get(w.bp_id, w.other).await.unwrap().unwrap();
+    assert_eq!(bob.rights, vec![BlueprintRight::Publish]);
+
+    assert!(collaborators.remove(w.bp_id, w.other).await.unwrap());
+    assert!(!collaborators.remove(w.bp_id, w.other).await.unwrap());
+    assert!(collaborators.get(w.bp_id, w.other).await.unwrap().is_none());
+}
+
+#[tokio::test]
+async fn t_feature_flags() {
+    let Some(t) = TestDb::start().await else { return };
+    let w = World::seed(&t).await;
+    let features = FeatureRepository::new(t.db.clone());
+    let realm = FeatureScope::Realm(w.realm_id);
+    let blueprint = FeatureScope::Blueprint(w.bp_id);
+
+    assert_eq!(features.resolve("weather", w.realm_id).await.unwrap(), None);
+    features.set("weather", blueprint, true).await.unwrap();
+    assert_eq!(features.resolve("weather", w.realm_id).await.unwrap(), Some(true));
+    // The realm flag wins over the blueprint flag
+    features.set("weather", realm, true).await.unwrap();
+    features.set("weather", realm, false).await.unwrap();
+    assert_eq!(features.resolve("weather", w.realm_id).await.unwrap(), Some(false));
+
+    let flags = features.list_for_realm(w.realm_id).await.unwrap();
+    assert_eq!(flags.len(), 2);
+    assert_eq!(flags[0].scope, realm);
+    assert!(!flags[0].enabled);
+    assert_eq!(flags[1].scope, blueprint);
+
+    assert!(features.clear("weather", realm).await.unwrap());
+    assert!(!features.clear("weather", realm).await.unwrap());
+    assert_eq!(features.resolve("weather", w.realm_id).await.unwrap(), Some(true));
+}
+
+#[tokio::test]
+async fn t_moderation_reports() {
+    let Some(t) = TestDb::start().await else { return };
+    let w = World::seed(&t).await;
+    let moderation = ModerationRepository::new(t.db.clone());
+
+    assert!(!moderation.has_open_report(w.player, w.other).await.unwrap());
+    let id = moderation
+        .file_report(&NewReport {
+            reporter_id: w.player,
+            target_id: w.other,
+            reason: "spam".into(),
+            realm_id: Some(w.realm_id),
+            room_id: Some(w.hall),
+        })
+        .await
+        .unwrap();
+    assert!(moderation.has_open_report(w.player, w.other).await.unwrap());
+    assert!(!moderation.has_open_report(w.other, w.player).await.unwrap());
+
+    let open = moderation.open_reports().await.unwrap();
+    assert_eq!(open.len(), 1);
+    assert_eq!(open[0].id, id);
+    assert_eq!((open[0].reporter.as_str(), open[0].target.as_str()), ("alice", "bob"));
+    assert_eq!(open[0].room_id, Some(w.hall));
+
+    assert!(moderation.resolve(id, w.player, Some("warned")).await.unwrap());
+    assert!(!moderation.resolve(id, w.player, None).await.unwrap());
+    assert!(moderation.open_reports().await.unwrap().is_empty());
+    assert!(!moderation.has_open_report(w.player, w.other).await.unwrap());
+}
+
+#[tokio::test]
+async fn t_session_recordings() {
+    let Some(t) = TestDb::start().await else { return };
+    let w = World::seed(&t).await;
+    let recordings = RecordingRepository::new(t.db.clone());
+    let events = vec![
+        RecordingEvent {
+            at_ms: 0,
+            kind: RecordingEventKind::Input,
+            text: "look".into(),
+        },
+        RecordingEvent {
+            at_ms: 5,
+            kind: RecordingEventKind::Output,
+            text: "A room.".into(),
+        },
+    ];
+
+    let first = recordings
+        .save(&NewRecording {
+            account_id: w.player,
+            recorded_by: w.player,
+            started_at: Utc::now() - Duration::minutes(10),
+            events: events.clone(),
+        })
+        .await
+        .unwrap();
+    let second = recordings
+        .save(&NewRecording {
+            account_id: w.other,
+            recorded_by: w.player,
+            started_at: Utc::now(),
+            events: Vec::new(),
+        })
+        .await
+        .unwrap();
+
+    let all = recordings.list(None, 10).await.unwrap();
+    assert_eq!(all.iter().map(|r| r.id).collect::<Vec<_>>(), vec![second, first]);
+    assert_eq!(all[1].events, 2);
+    let alices = recordings.list(Some(w.player), 10).await.unwrap();
+    assert_eq!(alices.len(), 1);
+    assert_eq!(alices[0].username, "alice");
+    assert_eq!(recordings.list(None, 1).await.unwrap().len(), 1);
+
+    let recording = recordings.get(first).await.unwrap().unwrap();
+    assert_eq!(recording.events, events);
+    assert_eq!(recording.recorded_by, Some(w.player));
+    assert!(recordings.get(second + 1).await.unwrap().is_none());
+}
+
+#[tokio::test]
+async fn t_scheduled_events() {
+    let Some(t) = TestDb::start().await else { return };
+    let w = World::seed(&t).await;
+    let schedule = ScheduleRepository::new(t.db.clone());
+    let hourly = CronSchedule::parse("0 * * * *").unwrap();
+    let minute: DateTime<Utc> = "2030-01-01T12:00:00Z".parse().unwrap();
+
+    let announce = schedule
+        .insert(&hourly, &EventAction::Announce("Reboot soon".into()), None, w.player)
+        .await
+        .unwrap();
+    let lua = schedule
+        .insert(
+            &hourly,
+            &EventAction::Lua("print('tick')".into()),
+            Some(w.realm_id),
+            w.player,
+        )
+        .await
+        .unwrap();
+
+    let events = schedule.list().await.unwrap();
+    assert_eq!(events.iter().map(|e| e.id).collect::<Vec<_>>(), vec![announce, lua]);
+    assert_eq!(events[0].schedule, hourly);
+    assert_eq!(events[1].action, EventAction::Lua("print('tick')".into()));
+    assert_eq!(events[1].realm_id, Some(w.realm_id));
+    assert!(events[0].last_run_at.is_none());
+
+    assert!(schedule.mark_run(announce, minute).await.unwrap());
+    assert!(!schedule.mark_run(announce, minute).await.unwrap());
+    assert!(!schedule.mark_run(announce, minute - Duration::hours(1)).await.unwrap());
+    assert!(schedule.mark_run(announce, minute + Duration::hours(1)).await.unwrap());
+
+    assert!(schedule.delete(lua).await.unwrap());
+    assert!(!schedule.delete(lua).await.unwrap());
+    assert_eq!(schedule.list().await.unwrap().len(), 1);
+}
+
+#[tokio::test]
+async fn t_tutorial_instances() {
+    let Some(t) = TestDb::start().await else { return };
+    let w = World::seed(&t).await;
+    let tutorial = TutorialRepository::new(t.db.clone());
+    let realms = RealmRepository::new(t.db.clone());
+    let accounts = AccountRepository::new(t.db.clone());
+
+    let realm = tutorial
+        .create_instance(
+            Realm {
+                id: RealmId::new(),
+                bp_id: w.bp_id,
+                title: "Tutorial".into(),
+                kind: RealmKind::Live,
+                created_at: Utc::now(),
+                prompt: None,
+            },
+            "tutorial-bob",
+            w.other,
+        )
+        .await
+        .unwrap();
+    assert_eq!(realms.get_by_key("tutorial-bob").await.unwrap().unwrap().id, realm.id);
+    assert_eq!(
+        realms
+            .find_by_owner(w.other)
+            .await
+            .unwrap()
+            .iter()
+            .map(|r| r.id)
+            .collect::<Vec<_>>(),
+        vec![realm.id]
+    );
+
+    tutorial.set_position(w.other, realm.id, w.vault).await.unwrap();
+    let bob = accounts.get_by_id(w.other).await.unwrap().unwrap();
+    assert_eq!(
+        (bob.current_realm_id, bob.current_room_id),
+        (Some(realm.id), Some(w.vault))
+    );
+    assert!(bob.tutorial_completed_at.is_none());
+
+    assert!(tutorial.complete(w.other).await.unwrap());
+    assert!(!tutorial.complete(w.other).await.unwrap());
+    let bob = accounts.get_by_id(w.other).await.unwrap().unwrap();
+    assert!(bob.tutorial_completed_at.is_some());
+}
+
+#[tokio::test]
+async fn t_realm_lifecycle() {
+    let Some(t) = TestDb::start().await else { return };
+    let w = World::seed(&t).await;
+    let realms = RealmRepository::new(t.db.clone());
+    let inventory = InventoryRepository::new(t.db.clone());
+    let client = t.client().await;
+
+    let realm = realms
+        .create(Realm {
+            id: RealmId::new(),
+            bp_id: w.bp_id,
+            title: "Station Two".into(),
+            kind: RealmKind::Draft,
+            created_at: Utc::now(),
+            prompt: None,
+        })
+        .await
+        .unwrap();
+    let stored = realms.get(realm.id).await.unwrap().unwrap();
+    assert_eq!(stored.title, "Station Two");
+    assert!(matches!(stored.kind, RealmKind::Draft));
+    assert!(realms.get_by_key("nowhere").await.unwrap().is_none());
+    assert_eq!(realms.get_by_key("station").await.unwrap().unwrap().id, w.realm_id);
+    assert_eq!(realms.find_by_owner(w.player).await.unwrap().len(), 1);
+    assert!(realms.find_by_owner(w.other).await.unwrap().is_empty());
+
+    // Only unowned live realms of published blueprints are public
+    assert!(realms.list_public().await.unwrap().is_empty());
+    client
+        .execute("UPDATE realms SET owner_id = NULL WHERE id = $1", &[&w.realm_id])
+        .await
+        .unwrap();
+    client
+        .execute("UPDATE blueprints SET status = 'published' WHERE id = $1", &[&w.bp_id])
+        .await
+        .unwrap();
+    let public = realms.list_public().await.unwrap();
+    assert_eq!(public.len(), 1);
+    assert_eq!(public[0].0, "station");
+    assert_eq!(public[0].1.id, w.realm_id);
+
+    realms.record_start(w.realm_id, w.player).await.unwrap();
+    assert!(!realms.complete(w.realm_id, w.other).await.unwrap());
+    assert!(realms.complete(w.realm_id, w.player).await.unwrap());
+    assert!(!realms.complete(w.realm_id, w.player).await.unwrap());
+
+    let coin = inventory
+        .spawn_item(w.realm_id, "coin", ItemLocation::Room(w.hall), 1)
+        .await
+        .unwrap();
+    assert!(realms.delete(w.realm_id).await.unwrap());
+    assert!(!realms.delete(w.realm_id).await.unwrap());
+    assert!(realms.get(w.realm_id).await.unwrap().is_none());
+    assert!(inventory.get_item_instance(coin).await.is_err());
+    let bob = AccountRepository::new(t.db.clone())
+        .get_by_id(w.other)
+        .await
+        .unwrap()
+        .unwrap();
+    assert!(bob.current_realm_id.is_none());
+}
+
+#[tokio::test]
+async fn t_ambient_events() {
+    let Some(t) = TestDb::start().await else { return };
+    let w = World::seed(&t).await;
+    let ambience = AmbienceRepository::new(t.db.clone());
+
+    assert!(ambience.events_for_realm(w.realm_id).await.unwrap().is_empty());
+    for key in ["storm", "daylight"] {
+        t.client()
+            .await
+            .execute(
+                "INSERT INTO bp_ambience (bp_id, event_key, event) VALUES ($1, $2, $3)",
+                &[
+                    &w.bp_id,
+                    &key,
+                    &json!({ "id": key, "every": 600, "steps": [{ "message": "Time passes." }] }),
+                ],
+            )
+            .await
+            .unwrap();
+    }
+
+    let events = ambience.events_for_realm(w.realm_id).await.unwrap();
+    assert_eq!(
+        events.iter().map(|e| e.id.as_str()).collect::<Vec<_>>(),
+        vec!["daylight", "storm"]
+    );
+    assert_eq!(events[0].every, 600);
+    assert_eq!(events[0].steps.len(), 1);
+}
+
+#[tokio::test]
+async fn t_crafting() {
+    let Some(t) = TestDb::start().await else { return };
+    let w = World::seed(&t).await;
+    let crafting = CraftingRepository::new(t.db.clone());
+    let inventory = InventoryRepository::new(t.db.clone());
+
+    t.client()
+        .await
+        .execute(
+            "INSERT INTO bp_recipes (bp_id, recipe_key, inputs, output_key, tool_key)
+             VALUES ($1, 'pouch', ARRAY['coin', 'wrench'], 'bag', 'wrench')",
+            &[&w.bp_id],
+        )
+        .await
+        .unwrap();
+    let recipes = crafting.recipes_for_realm(w.realm_id).await.unwrap();
+    assert_eq!(recipes.len(), 1);
+    assert_eq!(recipes[0].key, "pouch");
+    assert_eq!(recipes[0].inputs, vec!["coin".to_string(), "wrench".to_string()]);
+    assert_eq!((recipes[0].output_key.as_str(), recipes[0].output_qty), ("bag", 1));
+    assert_eq!(recipes[0].tool_key.as_deref(), Some("wrench"));
+
+    let coins = inventory.add_item(w.realm_id, w.player, "coin", 2).await.unwrap();
+    let wrench = inventory.add_item(w.realm_id, w.player, "wrench", 1).await.unwrap();
+    assert_eq!(
+        inventory.get_item_instance(coins).await.unwrap().location,
+        ItemLocation::Player(w.player)
+    );
+
+    let bag = crafting
+        .craft(w.realm_id, w.player, &[coins, wrench], "bag", 1)
+        .await
+        .unwrap()
+        .unwrap();
+    assert_eq!(inventory.get_item_instance(bag).await.unwrap().item_key, "bag");
+    assert_eq!(inventory.get_item_instance(coins).await.unwrap().quantity, 1);
+    assert!(inventory.get_item_instance(wrench).await.is_err());
+
+    // The wrench is gone, so nothing is consumed
+    assert_eq!(
+        crafting
+            .craft(w.realm_id, w.player, &[coins, wrench], "bag", 1)
+            .await
+            .unwrap(),
+        None
+    );
+    assert_eq!(inventory.get_item_instance(coins).await.unwrap().quantity, 1);
+    // Nor can one player craft with another's items
+    assert_eq!(
+        crafting.craft(w.realm_id, w.other, &[coins], "bag", 1).await.unwrap(),
+        None
+    );
+}
+
+#[tokio::test]
+async fn t_garbage_collection() {
+    let Some(t) = TestDb::start().await else { return };
+    let w = World::seed(&t).await;
+    let maintenance = MaintenanceRepository::new(t.db.clone());
+    let inventory = InventoryRepository::new(t.db.clone());
+    let client = t.client().await;
+
+    assert_eq!(total(&maintenance.count_garbage().await.unwrap()), 0);
+
+    let kept = inventory
+        .spawn_item(w.realm_id, "coin", ItemLocation::Room(w.vault), 1)
+        .await
+        .unwrap();
+    let in_chest = inventory
+        .spawn_item(w.realm_id, "wrench", ItemLocation::Object(w.chest), 1)
+        .await
+        .unwrap();
+    inventory
+        .mark_loot_instantiated(w.realm_id, w.chest, Some(w.player))
+        .await
+        .unwrap();
+    client
+        .execute("DELETE FROM bp_objects WHERE id = $1", &[&w.chest])
+        .await
+        .unwrap();
+
+    let garbage = maintenance.count_garbage().await.unwrap();
+    let count = |what: &str| garbage.iter().find(|g| g.what == what).unwrap().count;
+    assert_eq!(count("items in deleted objects"), 1);
+    assert_eq!(count("loot state of deleted realms, objects or accounts"), 1);
+    assert_eq!(total(&garbage), 2);
+
+    assert_eq!(total(&maintenance.purge_garbage().await.unwrap()), 2);
+    assert_eq!(total(&maintenance.count_garbage().await.unwrap()), 0);
+    assert!(inventory.get_item_instance(in_chest).await.is_err());
+    assert!(inventory.get_item_instance(kept).await.is_ok());
+}
+
+#[tokio::test]
+async fn t_reviews_and_tags() {
+    let Some(t) = TestDb::start().await else { return };
+    let w = World::seed(&t).await;
+    let reviews = ReviewRepository::new(t.db.clone());
+    let realms = RealmRepository::new(t.db.clone());
+
+    assert!(!reviews.has_completed(w.realm_id, w.player).await.unwrap());
+    realms.record_start(w.realm_id, w.player).await.unwrap();
+    assert!(!reviews.has_completed(w.realm_id, w.player).await.unwrap());
+    realms.complete(w.realm_id, w.player).await.unwrap();
+    assert!(reviews.has_completed(w.realm_id, w.player).await.unwrap());
+
+    assert!(reviews.rating(w.realm_id, w.player).await.unwrap().is_none());
+    reviews.set_rating(w.realm_id, w.player, 4, Some("Fun")).await.unwrap();
+    reviews.set_rating(w.realm_id, w.player, 5, None).await.unwrap();
+    let rating = reviews.rating(w.realm_id, w.player).await.unwrap().unwrap();
+    assert_eq!((rating.stars, rating.review), (5, None));
+    reviews
+        .set_rating(w.realm_id, w.other, 2, Some("Too dark"))
+        .await
+        .unwrap();
+
+    assert!(reviews.add_tag(w.realm_id, w.player, "spooky").await.unwrap());
+    assert!(!reviews.add_tag(w.realm_id, w.player, "spooky").await.unwrap());
+    assert!(reviews.add_tag(w.realm_id, w.player, "puzzles").await.unwrap());
+    assert!(reviews.add_tag(w.realm_id, w.other, "spooky").await.unwrap());
+    assert_eq!(
+        reviews.tags(w.realm_id, w.player).await.unwrap(),
+        vec!["puzzles".to_string(), "spooky".to_string()]
+    );
+    assert!(reviews.remove_tag(w.realm_id, w.player, "puzzles").await.unwrap());
+    assert!(!reviews.remove_tag(w.realm_id, w.player, "puzzles").await.unwrap());
+
+    let feedback = reviews.feedback(w.bp_id, 10).await.unwrap();
+    assert_eq!(feedback.histogram, [0, 1, 0, 0, 1]);
+    assert_eq!(feedback.tags, vec![("spooky".to_string(), 2)]);
+    // Reviews with text come first
+    assert_eq!(feedback.reviews.len(), 2);
+    assert_eq!(feedback.reviews[0].username.as_deref(), Some("bob"));
+    assert_eq!(feedback.reviews[0].realm_key.as_deref(), Some("station"));
+    assert_eq!(feedback.reviews[0].rating.review.as_deref(), Some("Too dark"));
+    assert_eq!(reviews.feedback(w.bp_id, 1).await.unwrap().reviews.len(), 1);
+}
+
+#[tokio::test]
+async fn t_blueprint_submissions() {
+    let Some(t) = TestDb::start().await else { return };
+    let w = World::seed(&t).await;
+    let submissions = SubmissionRepository::new(t.db.clone());
+    let status = || async {
+        let status: String = t
+            .client()
+            .await
+            .query_one("SELECT status FROM blueprints WHERE id = $1", &[&w.bp_id])
+            .await
+            .unwrap()
+            .get(0);
+        status
+    };
+
+    assert!(submissions.latest(w.bp_id).await.unwrap().is_none());
+    let first = submissions.submit(w.bp_id, w.player).await.unwrap().unwrap();
+    assert_eq!(status().await, "pending");
+    // Already pending
+    assert_eq!(submissions.submit(w.bp_id, w.player).await.unwrap(), None);
+
+    let pending = submissions.pending().await.unwrap();
+    assert_eq!(pending.len(), 1);
+    assert_eq!(pending[0].id, first);
+    assert_eq!(pending[0].bp_key, "station");
+    assert_eq!(pending[0].submitted_by.as_deref(), Some("alice"));
+    assert!(submissions.undelivered(w.player).await.unwrap().is_empty());
+
+    assert!(
+        submissions
+            .decide(first, w.other, false, Some("Too short"))
+            .await
+            .unwrap()
+    );
+    assert!(!submissions.decide(first, w.other, true, None).await.unwrap());
+    assert_eq!(status().await, "rejected");
+    let rejected = submissions.get(first).await.unwrap().unwrap();
+    assert_eq!(rejected.status, SubmissionStatus::Rejected);
+    assert_eq!(rejected.decided_by.as_deref(), Some("bob"));
+    assert_eq!(rejected.comment.as_deref(), Some("Too short"));
+
+    let undelivered = submissions.undelivered(w.player).await.unwrap();
+    assert_eq!(undelivered.iter().map(|s| s.id).collect::<Vec<_>>(), vec![first]);
+    submissions.mark_delivered(&[first]).await.unwrap();
+    assert!(submissions.undelivered(w.player).await.unwrap().is_empty());
+
+    let second = submissions.submit(w.bp_id, w.player).await.unwrap().unwrap();
+    assert!(submissions.pending().await.unwrap().iter().all(|s| s.id == second));
+    assert!(submissions.decide(second, w.other, true, None).await.unwrap());
+    assert_eq!(status().await, "published");
+    assert_eq!(submissions.latest(w.bp_id).await.unwrap().unwrap().id, second);
+    assert_eq!(submissions.submit(w.bp_id, w.player).await.unwrap(), None);
+}
@@ -0,0 +1,200 @@
+//! Disposable databases for the repository tests.
+//!
+//! `PORT4K_TEST_DATABASE_URL` points at a Postgres server (`make rust-db-test` starts one in
+//! docker). Every test gets its own freshly migrated database, dropped again when the test ends,
+//! so tests can run in parallel and leave nothing behind. Without the variable the tests skip.
+
+use port4k::db::Db;
+use port4k::models::types::{AccountId, BlueprintId, ExitId, ObjectId, RealmId, RoomId};
+use std::str::FromStr;
+use std::sync::Arc;
+use tokio_postgres::{Client, Config, NoTls};
+
+pub const DATABASE_URL_ENV: &str = "PORT4K_TEST_DATABASE_URL";
+
+pub struct TestDb {
+    pub db: Arc<Db>,
+    admin: Config,
+    name: String,
+}
+
+impl TestDb {
+    /// Creates and migrates a new database, or None when no test server is configured
+    pub async fn start() -> Option<TestDb> {
+        let Ok(url) = std::env::var(DATABASE_URL_ENV) else {
+            eprintln!("{} is not set, skipping", DATABASE_URL_ENV);
+            return None;
+        };
+        let admin = Config::from_str(&url).expect("invalid PORT4K_TEST_DATABASE_URL");
+        let name = format!("port4k_test_{}", uuid::Uuid::new_v4().simple());
+
+        let client = connect(&admin).await;
+        client
+            .batch_execute(&format!("CREATE DATABASE {}", name))
+            .await
+            .expect("cannot create test database");
+
+        let mut cfg = admin.clone();
+        cfg.dbname(&name);
+        let db = Arc::new(Db::from_config(cfg).expect("cannot build pool"));
+
+        // Guard first, so a failing migration still drops the database
+        let t = TestDb { db, admin, name };
+        t.db.init().await.expect("migrations failed");
+        Some(t)
+    }
+
+    /// A plain connection to the test database, for fixtures and for checking rows
+    pub async fn client(&self) -> deadpool_postgres::Client {
+        self.db.get_client().await.expect("no connection")
+    }
+}
+
+impl Drop for TestDb {
+    fn drop(&mut self) {
+        // The test runtime may be shutting down (or gone, after a panic), so drop the database
+        // from a runtime of our own
+        let admin = self.admin.clone();
+        let name = self.name.clone();
+        let dropped = std::thread::spawn(move || {
+            tokio::runtime::Builder::new_current_thread()
+                .enable_all()
+                .build()
+                .expect("no runtime")
+                .block_on(async {
+                    connect(&admin)
+                        .await
+                        .batch_execute(&format!("DROP DATABASE IF EXISTS {} WITH (FORCE)", name))
+                        .await
+                })
+        })
+        .join();
+        if !matches!(dropped, Ok(Ok(()))) {
+            eprintln!("could not drop test database {}", self.name);
+        }
+    }
+}
+
+async fn connect(cfg: &Config) -> Client {
+    let (client, conn) = cfg.connect(NoTls).await.expect("cannot connect to the test server");
+    tokio::spawn(conn);
+    client
+}
+
+/// A small world: a blueprint with two rooms joined north/south, a chest in the hall, and a coin,
+/// wrench and bag in the item catalog. Two players stand in the hall of a realm of it.
+pub struct World {
+    pub bp_id: BlueprintId,
+    pub realm_id: RealmId,
+    pub player: AccountId,
+    pub other: AccountId,
+    pub hall: RoomId,
+    pub vault: RoomId,
+    pub north: ExitId,
+    pub chest: ObjectId,
+}
+
+impl World {
+    pub async fn seed(t: &TestDb) -> World {
+        let w = World {
+            bp_id: BlueprintId::new(),
+            realm_id: RealmId::new(),
+            player: AccountId::new(),
+            other: AccountId::new(),
+            hall: RoomId::new(),
+            vault: RoomId::new(),
+            north: ExitId::new(),
+            chest: ObjectId::new(),
+        };
+        let mut client = t.client().await;
+        let tx = client.transaction().await.unwrap();
+
+        for (id, name) in [(w.player, "alice"), (w.other, "bob")] {
+            tx.execute(
+                "INSERT INTO accounts (id, username, email, password_hash) VALUES ($1, $2, $3, '-')",
+                &[&id, &name, &format!("{}@example.com", name)],
+            )
+            .await
+            .unwrap();
+        }
+        tx.execute(
+            "INSERT INTO blueprints (id, key, title, owner_id) VALUES ($1, 'station', 'Station', $2)",
+            &[&w.bp_id, &w.player],
+        )
+        .await
+        .unwrap();
+        for (id, key) in [(w.hall, "hall"), (w.vault, "vault")] {
+            tx.execute(
+                "INSERT INTO bp_rooms (id, bp_id, key, title, body) VALUES ($1, $2, $3, $3, 'A room.')",
+                &[&id, &w.bp_id, &key],
+            )
+            .await
+            .unwrap();
+        }
+        tx.execute(
+            "INSERT INTO bp_exits (id, from_room_id, dir, to_room_id, locked) VALUES ($1, $2, 'north', $3, true)",
+            &[&w.north, &w.hall, &w.vault],
+        )
+        .await
+        .unwrap();
+        tx.execute(
+            "INSERT INTO bp_exits (from_room_id, dir, to_room_id) VALUES ($1, 'south', $2)",
+            &[&w.vault, &w.hall],
+        )
+        .await
+        .unwrap();
+        tx.execute(
+            "INSERT INTO bp_objects (id, room_id, name, short, description) VALUES ($1, $2, 'chest', 'a chest', 'A chest.')",
+            &[&w.chest, &w.hall],
+        )
+        .await
+        .unwrap();
+        tx.execute(
+            "INSERT INTO bp_object_nouns (room_id, obj_id, noun) VALUES ($1, $2, 'chest')",
+            &[&w.hall, &w.chest],
+        )
+        .await
+        .unwrap();
+        for (key, stackable) in [("coin", true), ("wrench", false), ("bag", false)] {
+            let item_id: uuid::Uuid = tx
+                .query_one(
+                    "INSERT INTO bp_items_catalog (bp_id, item_key, name, short, description, stackable, is_coin)
+                     VALUES ($1, $2, $3, $4, $5, $6, $7)
+                     RETURNING id",
+                    &[
+                        &w.bp_id,
+                        &key,
+                        &key,
+                        &format!("a {}", key),
+                        &format!("A {}.", key),
+                        &stackable,
+                        &(key == "coin"),
+                    ],
+                )
+                .await
+                .unwrap()
+                .get(0);
+            tx.execute(
+                "INSERT INTO bp_item_nouns (bp_id, item_id, noun) VALUES ($1, $2, $3)",
+                &[&w.bp_id, &item_id, &key],
+            )
+            .await
+            .unwrap();
+        }
+        tx.execute(
+            "INSERT INTO realms (id, bp_id, key, title, kind, owner_id) VALUES ($1, $2, 'station', 'Station', 'live', $3)",
+            &[&w.realm_id, &w.bp_id, &w.player],
+        )
+        .await
+        .unwrap();
+        tx.execute(
+            "UPDATE accounts SET current_realm_id = $1, current_room_id = $2",
+            &[&w.realm_id, &w.hall],
+        )
+        .await
+        .unwrap();
+
+        tx.commit().await.unwrap();
+        w
+    }
+}